@@ -1,5 +1,19 @@
-use anyhow::{Context, Result};
+use actix_web::http::header::HeaderName;
+use anyhow::{bail, Context, Result};
+use ethers::types::Address;
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
 use std::env;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+use crate::error::{AppError, AppResult};
+
+/// Shortest accepted JWT / quote signing secret (HS256 wants at least 256 bits)
+const MIN_SECRET_LEN: usize = 32;
+
+/// Most token decimals amounts are scaled by (18, as for ETH-style tokens)
+pub const MAX_IDRX_DECIMALS: u8 = 18;
 
 /// Application configuration loaded from environment variables
 #[derive(Debug, Clone)]
@@ -37,7 +51,22 @@ pub struct Config {
     pub invoice_nft_contract_addr: String,
     pub invoice_pool_contract_addr: String,
     pub idrx_token_contract_addr: String,
+    // Fallback deployments (token, InvoicePool, InvoiceNFT), in priority order
+    // after the primary one above
+    pub idrx_fallback_tokens: Vec<TokenDeployment>,
+    // Contract addresses that must not be used (e.g. paused or compromised)
+    pub idrx_paused_tokens: Vec<String>,
+    // Used when the token's decimals() cannot be read, and in test mode
+    pub idrx_decimals: u8,
     pub platform_wallet_address: String,
+    // Receives bank-mode disbursements on-chain; the off-ramp pays them out in
+    // fiat. Empty disables bank disbursement
+    pub offramp_wallet_address: String,
+    pub multicall3_addr: String,
+    // Transfer history scans start at the token deployment block and cover at
+    // most this many blocks per request (RPC providers cap eth_getLogs ranges)
+    pub transfer_history_start_block: u64,
+    pub transfer_history_max_block_range: u64,
 
     // Pinata (IPFS)
     pub pinata_api_key: String,
@@ -50,13 +79,75 @@ pub struct Config {
     pub allowed_file_types: String,
 
     // Platform Settings
-    pub platform_fee_percentage: f64,
+    pub platform_fee_bps: u32,
+    pub quote_signing_secret: String,
+    pub quote_ttl_minutes: i64,
     pub default_advance_percentage: f64,
     pub min_invoice_amount: f64,
     pub max_invoice_amount: f64,
+    pub require_investor_kyc: bool,
+
+    // How investments are paid for (one mode per deployment)
+    pub investment_mode: InvestmentMode,
+    // Whether the IDRX token supports EIP-2612 permit, enabling /investments/permit
+    pub enable_permit_investments: bool,
+
+    // Compliance (AML velocity caps, 0 disables)
+    pub velocity_window_days: i64,
+    pub investor_velocity_cap: f64,
+    pub mitra_velocity_cap: f64,
+    // Max active principal an investor may hold across one mitra's pools, 0 disables
+    pub investor_mitra_exposure_cap: f64,
+
+    // Investment limits (percent of the chosen tranche target)
+    pub priority_min_investment_pct: f64,
+    pub priority_max_investment_pct: f64,
+    pub catalyst_min_investment_pct: f64,
+    pub catalyst_max_investment_pct: f64,
+    // Share of the target a pool must raise before its mitra may request disbursement
+    pub min_disbursement_ratio: f64,
+
+    // Rate limiting (per client IP and route, fixed window)
+    pub rate_limit_window_secs: u64,
+    pub rate_limit_login: u32,
+    pub rate_limit_send_otp: u32,
+    pub rate_limit_public_pay: u32,
+    // Header carrying the client IP (e.g. Fly-Client-IP), read only from trusted proxies
+    pub rate_limit_client_ip_header: Option<String>,
+    pub rate_limit_trusted_proxies: Vec<TrustedProxy>,
+
+    // Idempotency
+    pub idempotency_key_ttl_hours: i64,
+
+    // Lifetime of the one-time link to a buyer's payments overview
+    pub importer_access_token_ttl_minutes: i64,
+    // How often pending importer payments past due are flagged overdue
+    pub importer_overdue_scan_interval_minutes: i64,
+    // How often disbursed invoices past their due date are marked matured, and
+    // how many days past due a matured invoice becomes eligible for default
+    pub invoice_maturity_scan_interval_minutes: i64,
+    pub invoice_default_grace_days: i64,
+    // How often pools with recent on-chain investments are audited against the
+    // InvoicePool contract (0 disables), and how far back "recent" reaches
+    pub onchain_audit_interval_minutes: u64,
+    pub onchain_audit_lookback_hours: i64,
+
+    // Outbound webhooks (empty URL disables)
+    pub webhook_url: String,
+    pub webhook_secret: String,
+    pub webhook_max_retries: u32,
+    pub webhook_timeout_secs: u64,
+
+    // Response compression
+    pub enable_compression: bool,
+
+    // Prometheus metrics; 0 serves /metrics on the API port
+    pub metrics_port: u16,
 
     // CORS
     pub cors_allowed_origins: String,
+    pub cors_allowed_methods: Vec<String>,
+    pub cors_allowed_headers: Vec<String>,
 
     // Frontend URL
     pub frontend_url: String,
@@ -68,6 +159,11 @@ pub struct Config {
     pub smtp_password: String,
     pub smtp_from: String,
 
+    // Queued email delivery
+    pub email_max_attempts: i32,
+    pub email_retry_base_secs: i64,
+    pub email_retry_interval_secs: u64,
+
     // OTP Settings
     pub otp_expiry_minutes: i64,
     pub otp_max_attempts: i32,
@@ -78,6 +174,9 @@ pub struct Config {
     // Google OAuth
     pub google_client_id: String,
 
+    // Whether wallet login may create investor accounts for unknown wallets
+    pub allow_wallet_autoregister: bool,
+
     // Test Config
     pub skip_blockchain_verification: bool,
 }
@@ -99,7 +198,7 @@ impl Config {
             )
         });
 
-        Ok(Self {
+        let config = Self {
             // Server
             port: get_env_or_default("PORT", "8080").parse().unwrap_or(8080),
             rust_log: get_env_or_default("RUST_LOG", "info"),
@@ -159,7 +258,34 @@ impl Config {
                 }
             },
             idrx_token_contract_addr: get_env_or_default("IDRX_TOKEN_CONTRACT_ADDRESS", ""),
+            idrx_fallback_tokens: parse_token_deployments(&get_env_or_default(
+                "IDRX_FALLBACK_TOKENS",
+                "",
+            ))?,
+            idrx_paused_tokens: get_env_or_default("IDRX_PAUSED_TOKENS", "")
+                .split(',')
+                .map(|addr| addr.trim().to_lowercase())
+                .filter(|addr| !addr.is_empty())
+                .collect(),
+            idrx_decimals: get_env_or_default("IDRX_DECIMALS", "2")
+                .parse()
+                .unwrap_or(2),
             platform_wallet_address: get_env_or_default("PLATFORM_WALLET_ADDRESS", ""),
+            offramp_wallet_address: get_env_or_default("OFFRAMP_WALLET_ADDRESS", ""),
+            // Canonical Multicall3 deployment (same address on Base and most EVM chains)
+            multicall3_addr: get_env_or_default(
+                "MULTICALL3_ADDRESS",
+                "0xcA11bde05977b3631167028862bE2a173976CA11",
+            ),
+            transfer_history_start_block: get_env_or_default("TRANSFER_HISTORY_START_BLOCK", "0")
+                .parse()
+                .unwrap_or(0),
+            transfer_history_max_block_range: get_env_or_default(
+                "TRANSFER_HISTORY_MAX_BLOCK_RANGE",
+                "10000",
+            )
+            .parse()
+            .unwrap_or(10_000),
 
             // Pinata (IPFS)
             pinata_api_key: get_env_or_default("PINATA_API_KEY", ""),
@@ -174,9 +300,15 @@ impl Config {
             allowed_file_types: get_env_or_default("ALLOWED_FILE_TYPES", "pdf,png,jpg,jpeg"),
 
             // Platform Settings
-            platform_fee_percentage: get_env_or_default("PLATFORM_FEE_PERCENTAGE", "2.0")
+            // Must match InvoicePool.platformFeeBps on-chain (200 = 2%)
+            platform_fee_bps: get_env_or_default("PLATFORM_FEE_BPS", "200")
+                .parse()
+                .unwrap_or(200),
+            // Signs exchange rate quotes (falls back to JWT_SECRET when empty)
+            quote_signing_secret: get_env_or_default("QUOTE_SIGNING_SECRET", ""),
+            quote_ttl_minutes: get_env_or_default("QUOTE_TTL_MINUTES", "30")
                 .parse()
-                .unwrap_or(2.0),
+                .unwrap_or(30),
             default_advance_percentage: get_env_or_default("DEFAULT_ADVANCE_PERCENTAGE", "80.0")
                 .parse()
                 .unwrap_or(80.0),
@@ -186,12 +318,145 @@ impl Config {
             max_invoice_amount: get_env_or_default("MAX_INVOICE_AMOUNT", "1000000")
                 .parse()
                 .unwrap_or(1000000.0),
+            require_investor_kyc: get_env_or_default("REQUIRE_INVESTOR_KYC", "true")
+                .parse()
+                .unwrap_or(true),
+
+            // Investment settlement
+            investment_mode: get_env_or_default("INVESTMENT_MODE", "on_chain").parse()?,
+            enable_permit_investments: get_env_or_default("ENABLE_PERMIT_INVESTMENTS", "false")
+                .parse()
+                .unwrap_or(false),
+
+            // Compliance (AML velocity caps)
+            velocity_window_days: get_env_or_default("VELOCITY_WINDOW_DAYS", "30")
+                .parse()
+                .unwrap_or(30),
+            investor_velocity_cap: get_env_or_default("INVESTOR_VELOCITY_CAP", "1000000000")
+                .parse()
+                .unwrap_or(1_000_000_000.0),
+            mitra_velocity_cap: get_env_or_default("MITRA_VELOCITY_CAP", "1000000000")
+                .parse()
+                .unwrap_or(1_000_000_000.0),
+            investor_mitra_exposure_cap: get_env_or_default("INVESTOR_MITRA_EXPOSURE_CAP", "0")
+                .parse()
+                .unwrap_or(0.0),
+
+            // Investment limits (percent of the chosen tranche target)
+            priority_min_investment_pct: get_env_or_default("PRIORITY_MIN_INVESTMENT_PCT", "10")
+                .parse()
+                .unwrap_or(10.0),
+            priority_max_investment_pct: get_env_or_default("PRIORITY_MAX_INVESTMENT_PCT", "90")
+                .parse()
+                .unwrap_or(90.0),
+            catalyst_min_investment_pct: get_env_or_default("CATALYST_MIN_INVESTMENT_PCT", "10")
+                .parse()
+                .unwrap_or(10.0),
+            catalyst_max_investment_pct: get_env_or_default("CATALYST_MAX_INVESTMENT_PCT", "90")
+                .parse()
+                .unwrap_or(90.0),
+            // 1.0 requires a filled pool; e.g. 0.8 allows disbursement from 80% funded
+            min_disbursement_ratio: get_env_or_default("MIN_DISBURSEMENT_RATIO", "1.0")
+                .parse()
+                .unwrap_or(1.0),
+
+            // Rate limiting (requests allowed per window)
+            rate_limit_window_secs: get_env_or_default("RATE_LIMIT_WINDOW_SECS", "60")
+                .parse()
+                .unwrap_or(60),
+            rate_limit_login: get_env_or_default("RATE_LIMIT_LOGIN", "10")
+                .parse()
+                .unwrap_or(10),
+            rate_limit_send_otp: get_env_or_default("RATE_LIMIT_SEND_OTP", "5")
+                .parse()
+                .unwrap_or(5),
+            rate_limit_public_pay: get_env_or_default("RATE_LIMIT_PUBLIC_PAY", "20")
+                .parse()
+                .unwrap_or(20),
+            rate_limit_client_ip_header: Some(get_env_or_default(
+                "RATE_LIMIT_CLIENT_IP_HEADER",
+                "",
+            ))
+            .map(|header| header.trim().to_string())
+            .filter(|header| !header.is_empty()),
+            rate_limit_trusted_proxies: parse_list(&get_env_or_default(
+                "RATE_LIMIT_TRUSTED_PROXIES",
+                "",
+            ))
+            .iter()
+            .map(|entry| entry.parse())
+            .collect::<Result<_>>()?,
+
+            // Idempotency
+            idempotency_key_ttl_hours: get_env_or_default("IDEMPOTENCY_KEY_TTL_HOURS", "24")
+                .parse()
+                .unwrap_or(24),
+
+            importer_access_token_ttl_minutes: get_env_or_default(
+                "IMPORTER_ACCESS_TOKEN_TTL_MINUTES",
+                "30",
+            )
+            .parse()
+            .unwrap_or(30),
+            importer_overdue_scan_interval_minutes: get_env_or_default(
+                "IMPORTER_OVERDUE_SCAN_INTERVAL_MINUTES",
+                "60",
+            )
+            .parse()
+            .unwrap_or(60),
+            invoice_maturity_scan_interval_minutes: get_env_or_default(
+                "INVOICE_MATURITY_SCAN_INTERVAL_MINUTES",
+                "60",
+            )
+            .parse()
+            .unwrap_or(60),
+            invoice_default_grace_days: get_env_or_default("INVOICE_DEFAULT_GRACE_DAYS", "30")
+                .parse()
+                .unwrap_or(30),
+            onchain_audit_interval_minutes: get_env_or_default(
+                "ONCHAIN_AUDIT_INTERVAL_MINUTES",
+                "0",
+            )
+            .parse()
+            .unwrap_or(0),
+            onchain_audit_lookback_hours: get_env_or_default("ONCHAIN_AUDIT_LOOKBACK_HOURS", "24")
+                .parse()
+                .unwrap_or(24),
+
+            // Outbound webhooks
+            webhook_url: get_env_or_default("WEBHOOK_URL", ""),
+            webhook_secret: get_env_or_default("WEBHOOK_SECRET", ""),
+            webhook_max_retries: get_env_or_default("WEBHOOK_MAX_RETRIES", "3")
+                .parse()
+                .unwrap_or(3),
+            webhook_timeout_secs: get_env_or_default("WEBHOOK_TIMEOUT_SECS", "10")
+                .parse()
+                .unwrap_or(10),
+
+            // Response compression
+            enable_compression: get_env_or_default("ENABLE_COMPRESSION", "true")
+                .parse()
+                .unwrap_or(true),
+
+            // Prometheus metrics
+            metrics_port: get_env_or_default("METRICS_PORT", "0").parse().unwrap_or(0),
 
             // CORS
             cors_allowed_origins: get_env_or_default(
                 "CORS_ALLOWED_ORIGINS",
                 "http://localhost:3000,http://localhost:8080",
             ),
+            cors_allowed_methods: parse_list(&get_env_or_default(
+                "CORS_ALLOWED_METHODS",
+                "GET,POST,PUT,DELETE,OPTIONS",
+            ))
+            .into_iter()
+            .map(|method| method.to_uppercase())
+            .collect(),
+            cors_allowed_headers: parse_list(&get_env_or_default(
+                "CORS_ALLOWED_HEADERS",
+                "Authorization,Content-Type,Accept,Idempotency-Key,X-Request-Id",
+            )),
 
             // Frontend URL
             frontend_url: get_env_or_default("FRONTEND_URL", "http://localhost:3000"),
@@ -205,6 +470,17 @@ impl Config {
             smtp_password: get_env_or_default("SMTP_PASSWORD", "").replace(" ", ""),
             smtp_from: get_env_or_default("SMTP_FROM", ""),
 
+            // Queued email delivery
+            email_max_attempts: get_env_or_default("EMAIL_MAX_ATTEMPTS", "5")
+                .parse()
+                .unwrap_or(5),
+            email_retry_base_secs: get_env_or_default("EMAIL_RETRY_BASE_SECS", "60")
+                .parse()
+                .unwrap_or(60),
+            email_retry_interval_secs: get_env_or_default("EMAIL_RETRY_INTERVAL_SECS", "30")
+                .parse()
+                .unwrap_or(30),
+
             // OTP Settings
             otp_expiry_minutes: get_env_or_default("OTP_EXPIRY_MINUTES", "5")
                 .parse()
@@ -221,6 +497,10 @@ impl Config {
             // Google OAuth
             google_client_id: get_env_or_default("GOOGLE_CLIENT_ID", ""),
 
+            allow_wallet_autoregister: get_env_or_default("ALLOW_WALLET_AUTOREGISTER", "true")
+                .parse()
+                .unwrap_or(true),
+
             // Test Config (Optional, defaults to false)
             skip_blockchain_verification: get_env_or_default(
                 "SKIP_BLOCKCHAIN_VERIFICATION",
@@ -228,10 +508,400 @@ impl Config {
             )
             .parse()
             .unwrap_or(false),
+        };
+
+        config.validate_idrx_tokens()?;
+        Ok(config)
+    }
+
+    /// All IDRX deployments in priority order, primary (CHAIN_ID / BLOCKCHAIN_RPC_URL /
+    /// IDRX_TOKEN_CONTRACT_ADDRESS and the contract addresses above) first
+    pub fn idrx_tokens(&self) -> Vec<TokenDeployment> {
+        let primary = TokenDeployment {
+            chain_id: self.chain_id,
+            contract_addr: self.idrx_token_contract_addr.clone(),
+            pool_contract_addr: self.invoice_pool_contract_addr.clone(),
+            nft_contract_addr: self.invoice_nft_contract_addr.clone(),
+            rpc_url: self.blockchain_rpc_url.clone(),
+        };
+        std::iter::once(primary)
+            .chain(self.idrx_fallback_tokens.iter().cloned())
+            .collect()
+    }
+
+    pub fn is_idrx_token_paused(&self, contract_addr: &str) -> bool {
+        self.idrx_paused_tokens
+            .iter()
+            .any(|paused| paused.eq_ignore_ascii_case(contract_addr))
+    }
+
+    /// Every configured IDRX address must be a valid EVM address. An empty primary
+    /// address is allowed so local setups can run without a token. A fallback must
+    /// also carry its own InvoicePool and InvoiceNFT addresses: switching the token
+    /// to another chain while still calling the primary chain's contracts would send
+    /// funds and records to addresses that do not exist there.
+    pub fn validate_idrx_tokens(&self) -> Result<()> {
+        for (i, token) in self.idrx_tokens().iter().enumerate() {
+            if i == 0 && token.contract_addr.is_empty() {
+                continue;
+            }
+            if token.contract_addr.parse::<Address>().is_err() {
+                bail!(
+                    "Invalid IDRX token address '{}' for chain {}",
+                    token.contract_addr,
+                    token.chain_id
+                );
+            }
+            if i == 0 {
+                continue;
+            }
+            for (name, addr) in [
+                ("InvoicePool", &token.pool_contract_addr),
+                ("InvoiceNFT", &token.nft_contract_addr),
+            ] {
+                if addr.parse::<Address>().is_err() {
+                    bail!(
+                        "Invalid {} address '{}' for IDRX fallback on chain {}",
+                        name,
+                        addr,
+                        token.chain_id
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reject settings that would otherwise surface as obscure failures later on:
+    /// malformed addresses, short signing secrets and non-positive expiries.
+    /// Empty contract and wallet addresses are allowed, as for the IDRX token.
+    pub fn validate(&self) -> AppResult<()> {
+        let addresses = [
+            (
+                "INVOICE_NFT_CONTRACT_ADDRESS",
+                &self.invoice_nft_contract_addr,
+            ),
+            (
+                "FUNDING_POOL_CONTRACT_ADDRESS",
+                &self.invoice_pool_contract_addr,
+            ),
+            ("PLATFORM_WALLET_ADDRESS", &self.platform_wallet_address),
+            ("OFFRAMP_WALLET_ADDRESS", &self.offramp_wallet_address),
+            ("MULTICALL3_ADDRESS", &self.multicall3_addr),
+        ];
+        for (key, addr) in addresses {
+            if !addr.is_empty() && !is_evm_address(addr) {
+                return Err(invalid(key, format!("'{}' is not a 0x address", addr)));
+            }
+        }
+        self.validate_idrx_tokens()
+            .map_err(|e| AppError::ValidationError(e.to_string()))?;
+        if self.idrx_decimals > MAX_IDRX_DECIMALS {
+            return Err(invalid(
+                "IDRX_DECIMALS",
+                format!("must be at most {}", MAX_IDRX_DECIMALS),
+            ));
+        }
+
+        if !(self.min_disbursement_ratio > 0.0 && self.min_disbursement_ratio <= 1.0) {
+            return Err(invalid(
+                "MIN_DISBURSEMENT_RATIO",
+                "must be greater than 0 and at most 1",
+            ));
+        }
+
+        if self.jwt_secret.len() < MIN_SECRET_LEN {
+            return Err(invalid(
+                "JWT_SECRET",
+                format!("must be at least {} characters", MIN_SECRET_LEN),
+            ));
+        }
+        if self.quote_signing_secret.len() < MIN_SECRET_LEN {
+            return Err(invalid(
+                "QUOTE_SIGNING_SECRET",
+                format!("must be at least {} characters", MIN_SECRET_LEN),
+            ));
+        }
+        // A leak of either key must not let anyone forge the other's signatures
+        if self.quote_signing_secret == self.jwt_secret {
+            return Err(invalid(
+                "QUOTE_SIGNING_SECRET",
+                "must differ from JWT_SECRET",
+            ));
+        }
+        if !self.webhook_url.is_empty() && self.webhook_secret.is_empty() {
+            return Err(invalid(
+                "WEBHOOK_SECRET",
+                "is required when WEBHOOK_URL is set",
+            ));
+        }
+
+        if self.cors_allowed_methods.is_empty() {
+            return Err(invalid(
+                "CORS_ALLOWED_METHODS",
+                "must list at least one method",
+            ));
+        }
+        for method in &self.cors_allowed_methods {
+            if !CORS_METHODS.contains(&method.as_str()) {
+                return Err(invalid(
+                    "CORS_ALLOWED_METHODS",
+                    format!("'{}' is not an HTTP method", method),
+                ));
+            }
+        }
+        for header in &self.cors_allowed_headers {
+            if HeaderName::from_bytes(header.as_bytes()).is_err() {
+                return Err(invalid(
+                    "CORS_ALLOWED_HEADERS",
+                    format!("'{}' is not a valid header name", header),
+                ));
+            }
+        }
+        if let Some(header) = &self.rate_limit_client_ip_header {
+            if HeaderName::from_bytes(header.as_bytes()).is_err() {
+                return Err(invalid(
+                    "RATE_LIMIT_CLIENT_IP_HEADER",
+                    format!("'{}' is not a valid header name", header),
+                ));
+            }
+            if self.rate_limit_trusted_proxies.is_empty() {
+                return Err(invalid(
+                    "RATE_LIMIT_CLIENT_IP_HEADER",
+                    "requires RATE_LIMIT_TRUSTED_PROXIES",
+                ));
+            }
+        }
+
+        if self.chain_id == 0 {
+            return Err(invalid("CHAIN_ID", "must be positive"));
+        }
+        let expiries = [
+            ("JWT_EXPIRY_HOURS", self.jwt_expiry_hours),
+            ("JWT_REFRESH_EXPIRY_HOURS", self.jwt_refresh_expiry_hours),
+            ("QUOTE_TTL_MINUTES", self.quote_ttl_minutes),
+            ("IDEMPOTENCY_KEY_TTL_HOURS", self.idempotency_key_ttl_hours),
+            (
+                "IMPORTER_ACCESS_TOKEN_TTL_MINUTES",
+                self.importer_access_token_ttl_minutes,
+            ),
+            (
+                "IMPORTER_OVERDUE_SCAN_INTERVAL_MINUTES",
+                self.importer_overdue_scan_interval_minutes,
+            ),
+            (
+                "INVOICE_MATURITY_SCAN_INTERVAL_MINUTES",
+                self.invoice_maturity_scan_interval_minutes,
+            ),
+            (
+                "ONCHAIN_AUDIT_LOOKBACK_HOURS",
+                self.onchain_audit_lookback_hours,
+            ),
+            ("OTP_EXPIRY_MINUTES", self.otp_expiry_minutes),
+        ];
+        for (key, value) in expiries {
+            if value <= 0 {
+                return Err(invalid(key, "must be positive"));
+            }
+        }
+        if self.invoice_default_grace_days < 0 {
+            return Err(invalid(
+                "INVOICE_DEFAULT_GRACE_DAYS",
+                "must not be negative",
+            ));
+        }
+        if self.email_max_attempts <= 0 {
+            return Err(invalid("EMAIL_MAX_ATTEMPTS", "must be positive"));
+        }
+        if self.email_retry_base_secs <= 0 {
+            return Err(invalid("EMAIL_RETRY_BASE_SECS", "must be positive"));
+        }
+        // tokio::time::interval panics on a zero period
+        if self.email_retry_interval_secs == 0 {
+            return Err(invalid("EMAIL_RETRY_INTERVAL_SECS", "must be positive"));
+        }
+        if self.jwt_refresh_expiry_hours < self.jwt_expiry_hours {
+            return Err(invalid(
+                "JWT_REFRESH_EXPIRY_HOURS",
+                "must not be shorter than JWT_EXPIRY_HOURS",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// `platform_fee_bps` as a percentage (200 bps = 2.0%)
+    pub fn platform_fee_percentage(&self) -> f64 {
+        self.platform_fee_bps as f64 / 100.0
+    }
+
+    /// (min, max) single-investment size for a tranche, as a fraction of that
+    /// tranche's target (0.1 = 10%)
+    pub fn investment_limit_ratios(&self, is_catalyst: bool) -> (Decimal, Decimal) {
+        let (min_pct, max_pct) = if is_catalyst {
+            (
+                self.catalyst_min_investment_pct,
+                self.catalyst_max_investment_pct,
+            )
+        } else {
+            (
+                self.priority_min_investment_pct,
+                self.priority_max_investment_pct,
+            )
+        };
+        let ratio = |pct: f64| Decimal::from_f64(pct).unwrap_or_default() / Decimal::ONE_HUNDRED;
+        (ratio(min_pct), ratio(max_pct))
+    }
+}
+
+/// An IDRX token contract the platform can settle in, together with the chain it
+/// lives on and the platform contracts deployed next to it
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct TokenDeployment {
+    pub chain_id: u64,
+    pub contract_addr: String,
+    pub pool_contract_addr: String,
+    pub nft_contract_addr: String,
+    /// Not exposed: RPC URLs often embed provider API keys
+    #[serde(skip_serializing)]
+    pub rpc_url: String,
+}
+
+/// A proxy address or CIDR range in `RATE_LIMIT_TRUSTED_PROXIES`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrustedProxy {
+    network: IpAddr,
+    prefix_len: u32,
+}
+
+impl TrustedProxy {
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let (network, ip, bits) = match (self.network, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                (u32::from(network) as u128, u32::from(ip) as u128, 32)
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => (u128::from(network), u128::from(ip), 128),
+            _ => return false,
+        };
+        let host_bits = bits - self.prefix_len;
+        network.checked_shr(host_bits).unwrap_or(0) == ip.checked_shr(host_bits).unwrap_or(0)
+    }
+}
+
+impl FromStr for TrustedProxy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (addr, prefix_len) = match s.split_once('/') {
+            Some((addr, prefix_len)) => (addr, Some(prefix_len)),
+            None => (s, None),
+        };
+        let network: IpAddr = addr.trim().parse().with_context(|| {
+            format!(
+                "Invalid address in RATE_LIMIT_TRUSTED_PROXIES entry '{}'",
+                s
+            )
+        })?;
+        let max_prefix_len = if network.is_ipv4() { 32 } else { 128 };
+        let prefix_len = match prefix_len {
+            Some(prefix_len) => prefix_len
+                .trim()
+                .parse()
+                .ok()
+                .filter(|len| *len <= max_prefix_len)
+                .with_context(|| {
+                    format!(
+                        "Invalid prefix length in RATE_LIMIT_TRUSTED_PROXIES entry '{}'",
+                        s
+                    )
+                })?,
+            None => max_prefix_len,
+        };
+
+        Ok(Self {
+            network,
+            prefix_len,
         })
     }
 }
 
+/// Parse `IDRX_FALLBACK_TOKENS`: comma-separated
+/// `chain_id|token_address|pool_address|nft_address|rpc_url` entries
+fn parse_token_deployments(raw: &str) -> Result<Vec<TokenDeployment>> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let parts: Vec<&str> = entry.splitn(5, '|').map(str::trim).collect();
+            match parts.as_slice() {
+                [chain_id, contract_addr, pool_contract_addr, nft_contract_addr, rpc_url]
+                    if !rpc_url.is_empty() =>
+                {
+                    Ok(TokenDeployment {
+                        chain_id: chain_id.parse().with_context(|| {
+                            format!("Invalid chain id in IDRX_FALLBACK_TOKENS entry '{}'", entry)
+                        })?,
+                        contract_addr: contract_addr.to_string(),
+                        pool_contract_addr: pool_contract_addr.to_string(),
+                        nft_contract_addr: nft_contract_addr.to_string(),
+                        rpc_url: rpc_url.to_string(),
+                    })
+                }
+                _ => bail!(
+                    "Invalid IDRX_FALLBACK_TOKENS entry '{}': expected chain_id|token_address|pool_address|nft_address|rpc_url",
+                    entry
+                ),
+            }
+        })
+        .collect()
+}
+
+/// Settlement path for investments, fixed per deployment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvestmentMode {
+    /// Investor transfers IDRX to the platform wallet and the transfer is verified on Base
+    OnChain,
+    /// Investment is debited from the investor's `balance_idrx` without touching the chain
+    OffChain,
+}
+
+impl FromStr for InvestmentMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "on_chain" => Ok(Self::OnChain),
+            "off_chain" => Ok(Self::OffChain),
+            other => bail!(
+                "Invalid INVESTMENT_MODE '{}': expected 'on_chain' or 'off_chain'",
+                other
+            ),
+        }
+    }
+}
+
+/// Methods accepted in `CORS_ALLOWED_METHODS`
+const CORS_METHODS: [&str; 9] = [
+    "GET", "HEAD", "POST", "PUT", "PATCH", "DELETE", "OPTIONS", "CONNECT", "TRACE",
+];
+
+/// Comma-separated list with blanks dropped
+fn parse_list(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn is_evm_address(addr: &str) -> bool {
+    addr.starts_with("0x") && addr.len() == 42 && addr.parse::<Address>().is_ok()
+}
+
+fn invalid(key: &str, reason: impl std::fmt::Display) -> AppError {
+    AppError::ValidationError(format!("{} {}", key, reason))
+}
+
 fn get_env(key: &str) -> Result<String> {
     env::var(key).with_context(|| format!("Missing environment variable: {}", key))
 }
@@ -2,6 +2,8 @@ use anyhow::Result;
 use sqlx::PgPool;
 use tracing::{info, warn};
 
+use crate::config::InvestmentMode;
+
 /// Run database migrations
 pub async fn run_migrations(pool: &PgPool) -> Result<()> {
     let migrations = vec![
@@ -393,9 +395,330 @@ pub async fn run_migrations(pool: &PgPool) -> Result<()> {
         r#"ALTER TABLE invoices ADD COLUMN IF NOT EXISTS exporter_wallet_address VARCHAR(42);"#,
         // ============ CLEANUP MIGRATIONS (Balance & KYC Removal) ============
         r#"ALTER TABLE users ALTER COLUMN email DROP NOT NULL;"#,
-        r#"ALTER TABLE users DROP COLUMN IF EXISTS balance_idrx;"#,
         r#"DROP TABLE IF EXISTS user_identities;"#,
-        r#"DROP TABLE IF EXISTS balance_transactions;"#,
+        // users.balance_idrx and balance_transactions are cleaned up or kept by
+        // run_investment_mode_migrations, depending on the investment mode
+        // KYC verifications (admin-reviewed identity checks for investors)
+        r#"CREATE TABLE IF NOT EXISTS kyc_verifications (
+            id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+            user_id UUID UNIQUE REFERENCES users(id) ON DELETE CASCADE NOT NULL,
+            nik VARCHAR(16) NOT NULL,
+            full_name VARCHAR(255) NOT NULL,
+            ktp_photo_url TEXT NOT NULL,
+            selfie_url TEXT NOT NULL,
+            status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'approved', 'rejected')),
+            rejection_reason TEXT,
+            verified_by UUID REFERENCES users(id),
+            verified_at TIMESTAMPTZ,
+            created_at TIMESTAMPTZ DEFAULT NOW(),
+            updated_at TIMESTAMPTZ DEFAULT NOW()
+        );"#,
+        r#"CREATE INDEX IF NOT EXISTS idx_kyc_verifications_user ON kyc_verifications(user_id);"#,
+        r#"CREATE INDEX IF NOT EXISTS idx_kyc_verifications_status ON kyc_verifications(status);"#,
+        // Fix importer_payments timestamps. Only columns still without a time zone are
+        // converted, so later boots neither rewrite the table nor shift stored values.
+        r#"DO $$
+        DECLARE
+            col TEXT;
+        BEGIN
+            FOREACH col IN ARRAY ARRAY['due_date', 'paid_at', 'created_at', 'updated_at'] LOOP
+                IF EXISTS (
+                    SELECT 1 FROM information_schema.columns
+                    WHERE table_name = 'importer_payments' AND column_name = col
+                      AND data_type = 'timestamp without time zone'
+                ) THEN
+                    EXECUTE format(
+                        'ALTER TABLE importer_payments ALTER COLUMN %I TYPE TIMESTAMPTZ USING %I AT TIME ZONE ''UTC''',
+                        col, col
+                    );
+                END IF;
+            END LOOP;
+        END $$;"#,
+        // Idempotency keys for replay-safe importer payments (scoped per payment)
+        r#"CREATE TABLE IF NOT EXISTS idempotency_keys (
+            id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+            payment_id UUID REFERENCES importer_payments(id) ON DELETE CASCADE NOT NULL,
+            idempotency_key VARCHAR(255) NOT NULL,
+            request_fingerprint TEXT NOT NULL,
+            response_body TEXT NOT NULL,
+            created_at TIMESTAMPTZ DEFAULT NOW(),
+            UNIQUE (payment_id, idempotency_key)
+        );"#,
+        r#"CREATE INDEX IF NOT EXISTS idx_idempotency_keys_created ON idempotency_keys(created_at);"#,
+        // Invoice soft delete (financial records stay for audit)
+        r#"ALTER TABLE invoices ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP;"#,
+        r#"CREATE INDEX IF NOT EXISTS idx_invoices_exporter_active ON invoices(exporter_id) WHERE deleted_at IS NULL;"#,
+        // JWT revocation: tokens carry the version they were issued under
+        r#"ALTER TABLE users ADD COLUMN IF NOT EXISTS token_version INTEGER NOT NULL DEFAULT 0;"#,
+        // Per-user activity feed
+        r#"CREATE TABLE IF NOT EXISTS user_activity (
+            id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+            user_id UUID REFERENCES users(id) ON DELETE CASCADE NOT NULL,
+            activity_type VARCHAR(50) NOT NULL,
+            summary TEXT NOT NULL,
+            created_at TIMESTAMP NOT NULL DEFAULT NOW()
+        );"#,
+        r#"CREATE INDEX IF NOT EXISTS idx_user_activity_user_created ON user_activity(user_id, created_at DESC);"#,
+        // Wallet that signed for an importer payment
+        r#"ALTER TABLE importer_payments ADD COLUMN IF NOT EXISTS payer_wallet VARCHAR(42);"#,
+        // Overpayment on importer payments is kept as buyer credit
+        r#"ALTER TABLE importer_payments ADD COLUMN IF NOT EXISTS credit_amount DECIMAL(20,2) NOT NULL DEFAULT 0;"#,
+        // One-time emailed links to a buyer's payments overview (only the token hash is stored)
+        r#"CREATE TABLE IF NOT EXISTS importer_access_tokens (
+            id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+            buyer_email VARCHAR(255) NOT NULL,
+            token_hash VARCHAR(64) NOT NULL UNIQUE,
+            expires_at TIMESTAMPTZ NOT NULL,
+            used_at TIMESTAMPTZ,
+            created_at TIMESTAMPTZ DEFAULT NOW()
+        );"#,
+        r#"CREATE INDEX IF NOT EXISTS idx_importer_access_tokens_email ON importer_access_tokens(buyer_email);"#,
+        // Currencies an exporter can invoice in, and whether they convert to IDRX
+        r#"CREATE TABLE IF NOT EXISTS supported_currencies (
+            code VARCHAR(3) PRIMARY KEY,
+            name VARCHAR(100) NOT NULL,
+            symbol VARCHAR(10) NOT NULL,
+            decimals SMALLINT NOT NULL DEFAULT 2,
+            flag_emoji VARCHAR(16) NOT NULL DEFAULT '',
+            conversion_enabled BOOLEAN NOT NULL DEFAULT true,
+            created_at TIMESTAMPTZ DEFAULT NOW()
+        );"#,
+        r#"INSERT INTO supported_currencies (code, name, symbol, decimals, flag_emoji, conversion_enabled) VALUES
+            ('USD', 'US Dollar', '$', 2, '🇺🇸', true),
+            ('EUR', 'Euro', '€', 2, '🇪🇺', true),
+            ('GBP', 'British Pound', '£', 2, '🇬🇧', true),
+            ('JPY', 'Japanese Yen', '¥', 0, '🇯🇵', true),
+            ('SGD', 'Singapore Dollar', 'S$', 2, '🇸🇬', true),
+            ('AUD', 'Australian Dollar', 'A$', 2, '🇦🇺', true),
+            ('CNY', 'Chinese Yuan', '¥', 2, '🇨🇳', true)
+        ON CONFLICT (code) DO NOTHING;"#,
+        // A transfer finalizes at most one investment per investor
+        r#"CREATE UNIQUE INDEX IF NOT EXISTS idx_investments_investor_tx_hash ON investments(investor_id, tx_hash) WHERE tx_hash IS NOT NULL;"#,
+        // Price per gas unit (wei) paid by the platform wallet for the transaction
+        r#"ALTER TABLE transactions ADD COLUMN IF NOT EXISTS effective_gas_price BIGINT;"#,
+        // Wallet investor returns are paid to when it differs from the login wallet
+        r#"ALTER TABLE users ADD COLUMN IF NOT EXISTS payout_wallet_address VARCHAR(42);"#,
+        r#"ALTER TABLE users ADD COLUMN IF NOT EXISTS payout_wallet_verified_at TIMESTAMP;"#,
+        // One non-closed pool per invoice; closed pools stay for history so the invoice can be relisted
+        r#"ALTER TABLE funding_pools DROP CONSTRAINT IF EXISTS funding_pools_invoice_id_key;"#,
+        r#"CREATE UNIQUE INDEX IF NOT EXISTS idx_funding_pools_active_invoice ON funding_pools(invoice_id) WHERE status <> 'closed';"#,
+        // Per-pool single-investment limits as a fraction of the tranche target (NULL uses the config defaults)
+        r#"ALTER TABLE funding_pools ADD COLUMN IF NOT EXISTS min_invest_ratio DECIMAL(5,4);"#,
+        r#"ALTER TABLE funding_pools ADD COLUMN IF NOT EXISTS max_invest_ratio DECIMAL(5,4);"#,
+        // In-app notification inbox (payload is a JSON document stored as text)
+        r#"CREATE TABLE IF NOT EXISTS notifications (
+            id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+            user_id UUID REFERENCES users(id) ON DELETE CASCADE NOT NULL,
+            kind VARCHAR(50) NOT NULL,
+            payload TEXT NOT NULL DEFAULT '{}',
+            read_at TIMESTAMP,
+            created_at TIMESTAMP NOT NULL DEFAULT NOW()
+        );"#,
+        r#"CREATE INDEX IF NOT EXISTS idx_notifications_user_created ON notifications(user_id, created_at DESC);"#,
+        // One row per on-chain transaction and type: before the unique index is built,
+        // rows duplicated by retried recordings (all but the first) are moved to
+        // transactions_quarantine for review rather than deleted
+        r#"DO $$
+        BEGIN
+            IF NOT EXISTS (SELECT 1 FROM pg_indexes WHERE indexname = 'idx_transactions_tx_hash_type') THEN
+                CREATE TABLE IF NOT EXISTS transactions_quarantine (LIKE transactions INCLUDING DEFAULTS);
+                ALTER TABLE transactions_quarantine ADD COLUMN IF NOT EXISTS quarantined_at TIMESTAMP NOT NULL DEFAULT NOW();
+                WITH moved AS (
+                    DELETE FROM transactions a USING transactions b
+                    WHERE a.tx_hash = b.tx_hash AND a.type = b.type
+                      AND (a.created_at, a.id) > (b.created_at, b.id)
+                    RETURNING a.*
+                )
+                INSERT INTO transactions_quarantine SELECT moved.*, NOW() FROM moved;
+            END IF;
+        END $$;"#,
+        r#"CREATE UNIQUE INDEX IF NOT EXISTS idx_transactions_tx_hash_type ON transactions(tx_hash, type);"#,
+        // Pinned NFT metadata by content hash, so identical metadata is uploaded once
+        r#"CREATE TABLE IF NOT EXISTS nft_metadata_cache (
+            content_hash VARCHAR(64) PRIMARY KEY,
+            cid VARCHAR(255) NOT NULL,
+            created_at TIMESTAMP NOT NULL DEFAULT NOW()
+        );"#,
+        // Mitra bank accounts for fiat (off-ramp) disbursements
+        r#"CREATE TABLE IF NOT EXISTS bank_accounts (
+            id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+            user_id UUID REFERENCES users(id) ON DELETE CASCADE NOT NULL,
+            bank_code VARCHAR(20) NOT NULL,
+            bank_name VARCHAR(100) NOT NULL,
+            account_number VARCHAR(50) NOT NULL,
+            account_name VARCHAR(255) NOT NULL,
+            is_primary BOOLEAN NOT NULL DEFAULT false,
+            verified_at TIMESTAMP,
+            created_at TIMESTAMP NOT NULL DEFAULT NOW()
+        );"#,
+        r#"CREATE UNIQUE INDEX IF NOT EXISTS idx_bank_accounts_primary ON bank_accounts(user_id) WHERE is_primary;"#,
+        // Bank disbursements wait in 'pending_offramp' until the fiat payout settles
+        r#"ALTER TABLE transactions ADD COLUMN IF NOT EXISTS bank_account_id UUID REFERENCES bank_accounts(id);"#,
+        r#"ALTER TABLE transactions DROP CONSTRAINT IF EXISTS transactions_type_check;"#,
+        r#"ALTER TABLE transactions ADD CONSTRAINT transactions_type_check CHECK (type IN (
+            'investment', 'advance_payment', 'buyer_repayment',
+            'investor_return', 'platform_fee', 'refund', 'bank_disbursement'
+        ));"#,
+        r#"ALTER TABLE transactions DROP CONSTRAINT IF EXISTS transactions_status_check;"#,
+        r#"ALTER TABLE transactions ADD CONSTRAINT transactions_status_check CHECK (status IN ('pending', 'confirmed', 'failed', 'pending_offramp'));"#,
+        // Admins can revoke an approved mitra; the reason goes in rejection_reason
+        r#"ALTER TABLE mitra_applications DROP CONSTRAINT IF EXISTS mitra_applications_status_check;"#,
+        r#"ALTER TABLE mitra_applications ADD CONSTRAINT mitra_applications_status_check CHECK (status IN ('pending', 'approved', 'rejected', 'revoked'));"#,
+        // Versioned risk questionnaire. Exactly one version is active; answers
+        // are stored against the version they were given for.
+        r#"CREATE TABLE IF NOT EXISTS risk_question_versions (
+            version INTEGER PRIMARY KEY,
+            catalyst_min_score INTEGER NOT NULL DEFAULT 0,
+            is_active BOOLEAN NOT NULL DEFAULT false,
+            created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+        );"#,
+        r#"CREATE UNIQUE INDEX IF NOT EXISTS idx_risk_question_versions_active ON risk_question_versions(is_active) WHERE is_active;"#,
+        r#"CREATE TABLE IF NOT EXISTS risk_questions (
+            version INTEGER NOT NULL REFERENCES risk_question_versions(version) ON DELETE CASCADE,
+            question_id INTEGER NOT NULL,
+            question TEXT NOT NULL,
+            required_for_catalyst BOOLEAN NOT NULL DEFAULT false,
+            PRIMARY KEY (version, question_id)
+        );"#,
+        r#"CREATE TABLE IF NOT EXISTS risk_question_options (
+            version INTEGER NOT NULL,
+            question_id INTEGER NOT NULL,
+            value INTEGER NOT NULL,
+            label TEXT NOT NULL,
+            unlocks_catalyst BOOLEAN NOT NULL DEFAULT false,
+            score INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (version, question_id, value),
+            FOREIGN KEY (version, question_id) REFERENCES risk_questions(version, question_id) ON DELETE CASCADE
+        );"#,
+        r#"ALTER TABLE risk_questionnaires ADD COLUMN IF NOT EXISTS question_version INTEGER NOT NULL DEFAULT 1;"#,
+        r#"CREATE TABLE IF NOT EXISTS risk_questionnaire_answers (
+            questionnaire_id UUID NOT NULL REFERENCES risk_questionnaires(id) ON DELETE CASCADE,
+            question_id INTEGER NOT NULL,
+            value INTEGER NOT NULL,
+            PRIMARY KEY (questionnaire_id, question_id)
+        );"#,
+        // Version 1 is the original three-question set
+        r#"INSERT INTO risk_question_versions (version, catalyst_min_score, is_active) VALUES (1, 0, true)
+           ON CONFLICT (version) DO NOTHING;"#,
+        r#"INSERT INTO risk_questions (version, question_id, question, required_for_catalyst) VALUES
+            (1, 1, 'Seberapa lama pengalaman Anda dalam berinvestasi?', true),
+            (1, 2, 'Apakah Anda memahami bahwa tranche Catalyst memiliki risiko lebih tinggi dan dapat kehilangan modal?', true),
+            (1, 3, 'Apakah Anda bersedia dana Anda menjadi jaminan pertama jika terjadi gagal bayar?', true)
+           ON CONFLICT (version, question_id) DO NOTHING;"#,
+        r#"INSERT INTO risk_question_options (version, question_id, value, label, unlocks_catalyst, score) VALUES
+            (1, 1, 1, 'Kurang dari 1 tahun', false, 0),
+            (1, 1, 2, '1-3 tahun', true, 1),
+            (1, 1, 3, 'Lebih dari 3 tahun', true, 1),
+            (1, 2, 1, 'Ya, saya memahami risikonya', true, 1),
+            (1, 2, 2, 'Tidak, saya tidak mau mengambil risiko tersebut', false, 0),
+            (1, 3, 1, 'Ya, saya bersedia', true, 1),
+            (1, 3, 2, 'Tidak, saya tidak bersedia', false, 0)
+           ON CONFLICT (version, question_id, value) DO NOTHING;"#,
+        // users.member_status decodes into MemberStatus; map legacy values onto it
+        r#"UPDATE users SET member_status = CASE
+            WHEN role = 'admin' THEN 'admin'
+            WHEN role IN ('mitra', 'exporter') THEN 'calon_anggota_mitra'
+            ELSE 'calon_anggota_pendana'
+           END
+           WHERE member_status IS NULL
+              OR member_status NOT IN ('calon_anggota_pendana', 'calon_anggota_mitra', 'member_mitra', 'admin');"#,
+        r#"ALTER TABLE users ALTER COLUMN member_status SET NOT NULL;"#,
+        r#"ALTER TABLE users DROP CONSTRAINT IF EXISTS users_member_status_check;"#,
+        r#"ALTER TABLE users ADD CONSTRAINT users_member_status_check CHECK (member_status IN ('calon_anggota_pendana', 'calon_anggota_mitra', 'member_mitra', 'admin'));"#,
+        // Queued notification emails and their delivery attempts
+        r#"CREATE TABLE IF NOT EXISTS outbound_emails (
+            id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+            recipient VARCHAR(255) NOT NULL,
+            subject VARCHAR(255) NOT NULL,
+            template VARCHAR(50) NOT NULL,
+            html_body TEXT NOT NULL,
+            text_body TEXT NOT NULL,
+            status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'sent', 'failed', 'dead')),
+            attempts INTEGER NOT NULL DEFAULT 0,
+            last_error TEXT,
+            next_attempt_at TIMESTAMP NOT NULL DEFAULT NOW(),
+            sent_at TIMESTAMP,
+            created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+            updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+        );"#,
+        r#"CREATE INDEX IF NOT EXISTS idx_outbound_emails_due ON outbound_emails(next_attempt_at) WHERE status IN ('pending', 'failed');"#,
+        // On-chain investments are claimed as pending before their funds are forwarded
+        r#"ALTER TABLE investments DROP CONSTRAINT IF EXISTS investments_status_check;"#,
+        r#"ALTER TABLE investments ADD CONSTRAINT investments_status_check CHECK (status IN ('pending', 'active', 'repaid', 'defaulted'));"#,
+        // A transfer backs at most one investment, whoever claims it
+        r#"CREATE UNIQUE INDEX IF NOT EXISTS idx_investments_tx_hash ON investments(tx_hash) WHERE tx_hash IS NOT NULL;"#,
+        // The per-investor index is dropped only once the global one exists
+        r#"DO $$
+        BEGIN
+            IF EXISTS (SELECT 1 FROM pg_indexes WHERE indexname = 'idx_investments_tx_hash') THEN
+                DROP INDEX IF EXISTS idx_investments_investor_tx_hash;
+            END IF;
+        END $$;"#,
+        // A transfer settles at most one importer payment
+        r#"CREATE UNIQUE INDEX IF NOT EXISTS idx_importer_payments_tx_hash ON importer_payments(tx_hash) WHERE tx_hash IS NOT NULL;"#,
+        // Idempotency keys are reserved before the payment is applied
+        r#"ALTER TABLE idempotency_keys ADD COLUMN IF NOT EXISTS status VARCHAR(20) NOT NULL DEFAULT 'completed' CHECK (status IN ('in_progress', 'completed'));"#,
+        // Wallet the InvoicePool contract recorded for the investment, for audits
+        r#"ALTER TABLE investments ADD COLUMN IF NOT EXISTS payout_wallet VARCHAR(42);"#,
+        // min/max_invest_ratio limit the priority tranche; the catalyst tranche gets its own
+        r#"ALTER TABLE funding_pools ADD COLUMN IF NOT EXISTS catalyst_min_invest_ratio DECIMAL(5,4);"#,
+        r#"ALTER TABLE funding_pools ADD COLUMN IF NOT EXISTS catalyst_max_invest_ratio DECIMAL(5,4);"#,
+        // Investors commit funds against an invoice's amount, due date, currency and tranche
+        // ratios, so every write path is refused once funding has started
+        r#"CREATE OR REPLACE FUNCTION lock_invoice_financial_terms() RETURNS trigger AS $$
+        BEGIN
+            IF OLD.status IN ('funding', 'funded', 'disbursed', 'matured', 'repaid', 'defaulted')
+               AND (NEW.amount IS DISTINCT FROM OLD.amount
+                    OR NEW.currency IS DISTINCT FROM OLD.currency
+                    OR NEW.due_date IS DISTINCT FROM OLD.due_date
+                    OR NEW.priority_ratio IS DISTINCT FROM OLD.priority_ratio
+                    OR NEW.catalyst_ratio IS DISTINCT FROM OLD.catalyst_ratio) THEN
+                RAISE EXCEPTION 'Invoice financial terms (amount, due date, currency, tranche ratios) are locked once funding has started (status: %)', OLD.status
+                    USING ERRCODE = 'check_violation', CONSTRAINT = 'invoice_financial_terms_locked';
+            END IF;
+            RETURN NEW;
+        END;
+        $$ LANGUAGE plpgsql;"#,
+        r#"DO $$
+        BEGIN
+            IF NOT EXISTS (SELECT 1 FROM pg_trigger WHERE tgname = 'trg_invoices_lock_financial_terms') THEN
+                CREATE TRIGGER trg_invoices_lock_financial_terms BEFORE UPDATE ON invoices
+                    FOR EACH ROW EXECUTE FUNCTION lock_invoice_financial_terms();
+            END IF;
+        END $$;"#,
+        // One payment request per pool, so concurrent requests cannot email the buyer twice.
+        // Before the unique index is built, extra requests for a pool (all but a paid one,
+        // else the first) are moved to importer_payments_quarantine for review rather than deleted
+        r#"DO $$
+        BEGIN
+            IF NOT EXISTS (SELECT 1 FROM pg_indexes WHERE indexname = 'idx_importer_payments_pool_unique') THEN
+                CREATE TABLE IF NOT EXISTS importer_payments_quarantine (LIKE importer_payments INCLUDING DEFAULTS);
+                ALTER TABLE importer_payments_quarantine ADD COLUMN IF NOT EXISTS quarantined_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+                WITH ranked AS (
+                    SELECT id, ROW_NUMBER() OVER (
+                        PARTITION BY pool_id
+                        ORDER BY (payment_status = 'paid') DESC, created_at, id
+                    ) AS rn
+                    FROM importer_payments
+                ),
+                moved AS (
+                    DELETE FROM importer_payments p USING ranked r
+                    WHERE p.id = r.id AND r.rn > 1
+                    RETURNING p.*
+                )
+                INSERT INTO importer_payments_quarantine SELECT moved.*, NOW() FROM moved;
+            END IF;
+        END $$;"#,
+        r#"CREATE UNIQUE INDEX IF NOT EXISTS idx_importer_payments_pool_unique ON importer_payments(pool_id);"#,
+        // Transfer that forwarded an on-chain investment to the InvoicePool contract,
+        // checked before a pending investment is reconciled
+        r#"ALTER TABLE investments ADD COLUMN IF NOT EXISTS forward_tx_hash VARCHAR(66);"#,
+        // Disbursement moves the invoice to 'disbursed' until it matures
+        r#"ALTER TABLE invoices DROP CONSTRAINT IF EXISTS invoices_status_check;"#,
+        r#"ALTER TABLE invoices ADD CONSTRAINT invoices_status_check CHECK (status IN (
+            'draft', 'pending_review', 'approved', 'rejected', 'tokenized',
+            'funding', 'funded', 'disbursed', 'matured', 'repaid', 'defaulted'
+        ));"#,
     ];
 
     for (i, migration) in migrations.iter().enumerate() {
@@ -414,3 +737,55 @@ pub async fn run_migrations(pool: &PgPool) -> Result<()> {
     info!("All migrations completed successfully");
     Ok(())
 }
+
+/// Balance schema for the deployment's investment mode. On-chain deployments
+/// keep dropping the unused IDRX balances; off-chain mode debits them, so they
+/// are kept, and re-created with a warning if an earlier cleanup dropped them.
+pub async fn run_investment_mode_migrations(pool: &PgPool, mode: InvestmentMode) -> Result<()> {
+    match mode {
+        InvestmentMode::OnChain => {
+            sqlx::query("ALTER TABLE users DROP COLUMN IF EXISTS balance_idrx;")
+                .execute(pool)
+                .await?;
+            sqlx::query("DROP TABLE IF EXISTS balance_transactions;")
+                .execute(pool)
+                .await?;
+        }
+        InvestmentMode::OffChain => {
+            let (has_balance,): (bool,) = sqlx::query_as(
+                "SELECT EXISTS (SELECT 1 FROM information_schema.columns WHERE table_name = 'users' AND column_name = 'balance_idrx')",
+            )
+            .fetch_one(pool)
+            .await?;
+            if !has_balance {
+                warn!("users.balance_idrx was dropped earlier; re-creating it, every off-chain balance starts at 0");
+                sqlx::query("ALTER TABLE users ADD COLUMN balance_idrx DECIMAL(20,2) DEFAULT 0;")
+                    .execute(pool)
+                    .await?;
+            }
+            sqlx::query(
+                r#"CREATE TABLE IF NOT EXISTS balance_transactions (
+                    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+                    user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+                    type VARCHAR(30) NOT NULL CHECK (type IN ('deposit', 'withdrawal', 'funding', 'return', 'refund', 'disbursement')),
+                    amount DECIMAL(20,2) NOT NULL,
+                    balance_before DECIMAL(20,2),
+                    balance_after DECIMAL(20,2),
+                    reference_id UUID,
+                    reference_type VARCHAR(30),
+                    description TEXT,
+                    created_at TIMESTAMP DEFAULT NOW()
+                );"#,
+            )
+            .execute(pool)
+            .await?;
+            sqlx::query(
+                "CREATE INDEX IF NOT EXISTS idx_balance_tx_user ON balance_transactions(user_id);",
+            )
+            .execute(pool)
+            .await?;
+        }
+    }
+
+    Ok(())
+}
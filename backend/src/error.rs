@@ -1,6 +1,10 @@
 use actix_web::{HttpResponse, ResponseError};
-use serde_json::json;
+use serde::Serialize;
 use std::fmt;
+use utoipa::ToSchema;
+
+use crate::middleware::current_request_id;
+use crate::utils::{ApiError, FieldError};
 
 #[derive(Debug, Clone)]
 #[allow(dead_code)] // Some variants reserved for future business logic
@@ -14,6 +18,8 @@ pub enum AppError {
 
     // Validation errors
     ValidationError(String),
+    /// Request body failed its `Validate` rules, one entry per failed check
+    InvalidFields(Vec<FieldError>),
     BadRequest(String),
 
     // Resource errors
@@ -38,6 +44,14 @@ pub enum AppError {
     CatalystNotUnlocked,
     InvalidTrancheSelection,
     ProfileNotComplete,
+    KycRequired,
+    RiskQuestionnaireRequired,
+    /// The platform wallet cannot pay the gas of a transaction it has to send
+    InsufficientGas(String),
+
+    // Compliance errors
+    ComplianceLimitExceeded(String),
+    ConcentrationLimitExceeded(String),
 }
 
 impl fmt::Display for AppError {
@@ -49,6 +63,9 @@ impl fmt::Display for AppError {
             AppError::TokenExpired => write!(f, "Token has expired"),
             AppError::InvalidToken => write!(f, "Invalid token"),
             AppError::ValidationError(msg) => write!(f, "Validation error: {}", msg),
+            AppError::InvalidFields(fields) => {
+                write!(f, "Validation error: {}", describe_fields(fields))
+            }
             AppError::BadRequest(msg) => write!(f, "Bad request: {}", msg),
             AppError::NotFound(msg) => write!(f, "Not found: {}", msg),
             AppError::Conflict(msg) => write!(f, "Conflict: {}", msg),
@@ -63,10 +80,26 @@ impl fmt::Display for AppError {
             AppError::CatalystNotUnlocked => write!(f, "Catalyst tranche not unlocked"),
             AppError::InvalidTrancheSelection => write!(f, "Invalid tranche selection"),
             AppError::ProfileNotComplete => write!(f, "Profile is not complete"),
+            AppError::KycRequired => write!(f, "KYC verification required"),
+            AppError::RiskQuestionnaireRequired => write!(f, "Risk questionnaire required"),
+            AppError::InsufficientGas(msg) => write!(f, "Insufficient gas: {}", msg),
+            AppError::ComplianceLimitExceeded(msg) => {
+                write!(f, "Compliance limit exceeded: {}", msg)
+            }
+            AppError::ConcentrationLimitExceeded(msg) => {
+                write!(f, "Concentration limit exceeded: {}", msg)
+            }
         }
     }
 }
 
+/// Body of every error response: `{"success": false, "error": {"code", "message"}}`
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorResponse {
+    pub success: bool,
+    pub error: ApiError,
+}
+
 impl ResponseError for AppError {
     fn error_response(&self) -> HttpResponse {
         let (status, code, message) = match self {
@@ -100,6 +133,11 @@ impl ResponseError for AppError {
                 "VALIDATION_ERROR",
                 msg.clone(),
             ),
+            AppError::InvalidFields(fields) => (
+                actix_web::http::StatusCode::BAD_REQUEST,
+                "VALIDATION_ERROR",
+                describe_fields(fields),
+            ),
             AppError::BadRequest(msg) => (
                 actix_web::http::StatusCode::BAD_REQUEST,
                 "BAD_REQUEST",
@@ -170,20 +208,99 @@ impl ResponseError for AppError {
                 "PROFILE_NOT_COMPLETE",
                 "Please complete your profile first".to_string(),
             ),
+            AppError::KycRequired => (
+                actix_web::http::StatusCode::FORBIDDEN,
+                "KYC_REQUIRED",
+                "KYC verification must be approved before investing".to_string(),
+            ),
+            AppError::RiskQuestionnaireRequired => (
+                actix_web::http::StatusCode::FORBIDDEN,
+                "RISK_QUESTIONNAIRE_REQUIRED",
+                "Complete the risk questionnaire before investing".to_string(),
+            ),
+            AppError::InsufficientGas(msg) => (
+                actix_web::http::StatusCode::SERVICE_UNAVAILABLE,
+                "INSUFFICIENT_GAS",
+                msg.clone(),
+            ),
+            AppError::ComplianceLimitExceeded(msg) => (
+                actix_web::http::StatusCode::FORBIDDEN,
+                "COMPLIANCE_LIMIT_EXCEEDED",
+                msg.clone(),
+            ),
+            AppError::ConcentrationLimitExceeded(msg) => (
+                actix_web::http::StatusCode::FORBIDDEN,
+                "CONCENTRATION_LIMIT_EXCEEDED",
+                msg.clone(),
+            ),
         };
 
-        HttpResponse::build(status).json(json!({
-            "success": false,
-            "error": {
-                "code": code,
-                "message": message
-            }
-        }))
+        let details = match self {
+            AppError::InvalidFields(fields) => Some(fields.clone()),
+            _ => None,
+        };
+
+        HttpResponse::build(status).json(ErrorResponse {
+            success: false,
+            error: ApiError {
+                code: code.to_string(),
+                message,
+                request_id: current_request_id(),
+                details,
+            },
+        })
     }
 }
 
+/// `field: message` pairs, for the error message and logs
+fn describe_fields(fields: &[FieldError]) -> String {
+    fields
+        .iter()
+        .map(|f| format!("{}: {}", f.field, f.message))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 impl From<sqlx::Error> for AppError {
     fn from(err: sqlx::Error) -> Self {
+        // Constraint violations are caused by the request, not the server
+        if let sqlx::Error::Database(db_err) = &err {
+            let constraint = db_err.constraint().unwrap_or("unknown").to_string();
+            match db_err.kind() {
+                sqlx::error::ErrorKind::UniqueViolation => {
+                    tracing::warn!("Unique constraint violated: {}", constraint);
+                    return AppError::Conflict(format!(
+                        "Resource already exists (constraint {})",
+                        constraint
+                    ));
+                }
+                sqlx::error::ErrorKind::ForeignKeyViolation => {
+                    tracing::warn!("Foreign key constraint violated: {}", constraint);
+                    return AppError::BadRequest(format!(
+                        "Referenced resource does not exist (constraint {})",
+                        constraint
+                    ));
+                }
+                // Raised by the invoices trigger that locks the financial terms
+                sqlx::error::ErrorKind::CheckViolation
+                    if constraint == "invoice_financial_terms_locked" =>
+                {
+                    return AppError::Conflict(db_err.message().to_string());
+                }
+                sqlx::error::ErrorKind::NotNullViolation => {
+                    // Postgres reports the column rather than a constraint name
+                    let column = db_err
+                        .try_downcast_ref::<sqlx::postgres::PgDatabaseError>()
+                        .and_then(|pg| pg.column())
+                        .unwrap_or(&constraint)
+                        .to_string();
+                    tracing::warn!("Not-null constraint violated: {}", column);
+                    return AppError::ValidationError(format!("{} is required", column));
+                }
+                _ => {}
+            }
+        }
+
         tracing::error!("Database error: {:?}", err);
         AppError::DatabaseError(err.to_string())
     }
@@ -1,18 +1,39 @@
-use actix_web::{web, HttpResponse};
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use uuid::Uuid;
 
 use super::AppState;
-use crate::error::AppResult;
+use crate::error::{AppError, AppResult, ErrorResponse};
 use crate::models::{
     GetNonceRequest, GoogleAuthRequest, InvestorWalletRegisterRequest, LoginRequest,
     RefreshTokenRequest, RegisterRequest, SendOtpRequest, VerifyOtpRequest, WalletLoginRequest,
 };
-use crate::utils::ApiResponse;
+use crate::utils::{
+    ApiResponse, ApiResponseBody, Claims, LoginApiResponse, ValidatedJson, WalletNonceApiResponse,
+};
+
+fn get_user_id(req: &HttpRequest) -> AppResult<Uuid> {
+    req.extensions()
+        .get::<Claims>()
+        .map(|c| c.user_id())
+        .ok_or_else(|| AppError::Unauthorized("User not authenticated".to_string()))
+}
 
 /// POST /api/v1/auth/send-otp
 /// For mitra/admin registration - not needed for investors
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/send-otp",
+    tag = "auth",
+    request_body = SendOtpRequest,
+    responses(
+        (status = 200, description = "OTP sent", body = ApiResponseBody),
+        (status = 400, description = "Invalid email", body = ErrorResponse),
+        (status = 429, description = "Too many requests")
+    )
+)]
 pub async fn send_otp(
     state: web::Data<AppState>,
-    body: web::Json<SendOtpRequest>,
+    body: ValidatedJson<SendOtpRequest>,
 ) -> AppResult<HttpResponse> {
     let result = state
         .otp_service
@@ -23,9 +44,19 @@ pub async fn send_otp(
 
 /// POST /api/v1/auth/verify-otp
 /// For mitra/admin registration - not needed for investors
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/verify-otp",
+    tag = "auth",
+    request_body = VerifyOtpRequest,
+    responses(
+        (status = 200, description = "Email verified, returns an OTP token", body = ApiResponseBody),
+        (status = 400, description = "Invalid or expired code", body = ErrorResponse)
+    )
+)]
 pub async fn verify_otp(
     state: web::Data<AppState>,
-    body: web::Json<VerifyOtpRequest>,
+    body: ValidatedJson<VerifyOtpRequest>,
 ) -> AppResult<HttpResponse> {
     let result = state.auth_service.verify_otp(body.into_inner()).await?;
     Ok(HttpResponse::Ok().json(ApiResponse::success(result, "Email verified successfully")))
@@ -33,9 +64,20 @@ pub async fn verify_otp(
 
 /// POST /api/v1/auth/register
 /// For mitra/admin registration only - investors use wallet-connect
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/register",
+    tag = "auth",
+    request_body = RegisterRequest,
+    responses(
+        (status = 201, description = "Registration successful", body = LoginApiResponse),
+        (status = 400, description = "Invalid registration data", body = ErrorResponse),
+        (status = 409, description = "Email or username already taken", body = ErrorResponse)
+    )
+)]
 pub async fn register(
     state: web::Data<AppState>,
-    body: web::Json<RegisterRequest>,
+    body: ValidatedJson<RegisterRequest>,
 ) -> AppResult<HttpResponse> {
     let result = state.auth_service.register(body.into_inner()).await?;
     Ok(HttpResponse::Created().json(ApiResponse::success(result, "Registration successful")))
@@ -43,9 +85,20 @@ pub async fn register(
 
 /// POST /api/v1/auth/login
 /// For mitra/admin login only - investors use wallet-connect
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/login",
+    tag = "auth",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Login successful", body = LoginApiResponse),
+        (status = 401, description = "Invalid credentials", body = ErrorResponse),
+        (status = 429, description = "Too many requests")
+    )
+)]
 pub async fn login(
     state: web::Data<AppState>,
-    body: web::Json<LoginRequest>,
+    body: ValidatedJson<LoginRequest>,
 ) -> AppResult<HttpResponse> {
     let result = state.auth_service.login(body.into_inner()).await?;
     Ok(HttpResponse::Ok().json(ApiResponse::success(result, "Login successful")))
@@ -53,6 +106,15 @@ pub async fn login(
 
 /// POST /api/v1/auth/wallet/nonce
 /// Get nonce for wallet signature (for investors and mitra - supports Base Smart Wallet / passkey)
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/wallet/nonce",
+    tag = "auth",
+    request_body = GetNonceRequest,
+    responses(
+        (status = 200, description = "Nonce and message to sign", body = WalletNonceApiResponse)
+    )
+)]
 pub async fn get_wallet_nonce(
     state: web::Data<AppState>,
     body: web::Json<GetNonceRequest>,
@@ -66,9 +128,20 @@ pub async fn get_wallet_nonce(
 
 /// POST /api/v1/auth/wallet/login
 /// Wallet-based login for investors and mitra (supports Base Smart Wallet / passkey via ERC-1271)
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/wallet/login",
+    tag = "auth",
+    request_body = WalletLoginRequest,
+    responses(
+        (status = 200, description = "Wallet login successful", body = LoginApiResponse),
+        (status = 400, description = "Invalid or mismatched nonce", body = ErrorResponse),
+        (status = 401, description = "Invalid signature", body = ErrorResponse)
+    )
+)]
 pub async fn wallet_login(
     state: web::Data<AppState>,
-    body: web::Json<WalletLoginRequest>,
+    body: ValidatedJson<WalletLoginRequest>,
 ) -> AppResult<HttpResponse> {
     let result = state.auth_service.wallet_login(body.into_inner()).await?;
     Ok(HttpResponse::Ok().json(ApiResponse::success(result, "Wallet login successful")))
@@ -76,9 +149,20 @@ pub async fn wallet_login(
 
 /// POST /api/v1/auth/wallet/register
 /// Wallet-based registration for investors only
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/wallet/register",
+    tag = "auth",
+    request_body = InvestorWalletRegisterRequest,
+    responses(
+        (status = 201, description = "Investor registered", body = LoginApiResponse),
+        (status = 401, description = "Invalid signature", body = ErrorResponse),
+        (status = 409, description = "Wallet already registered", body = ErrorResponse)
+    )
+)]
 pub async fn wallet_register(
     state: web::Data<AppState>,
-    body: web::Json<InvestorWalletRegisterRequest>,
+    body: ValidatedJson<InvestorWalletRegisterRequest>,
 ) -> AppResult<HttpResponse> {
     let result = state
         .auth_service
@@ -91,6 +175,16 @@ pub async fn wallet_register(
 }
 
 /// POST /api/v1/auth/refresh
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/refresh",
+    tag = "auth",
+    request_body = RefreshTokenRequest,
+    responses(
+        (status = 200, description = "New access and refresh tokens", body = ApiResponseBody),
+        (status = 401, description = "Invalid or revoked refresh token", body = ErrorResponse)
+    )
+)]
 pub async fn refresh_token(
     state: web::Data<AppState>,
     body: web::Json<RefreshTokenRequest>,
@@ -108,8 +202,38 @@ pub async fn refresh_token(
     )))
 }
 
+/// POST /api/v1/auth/logout
+/// Revokes every access and refresh token issued to the user
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/logout",
+    tag = "auth",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Logged out", body = ApiResponseBody),
+        (status = 401, description = "Not authenticated", body = ErrorResponse)
+    )
+)]
+pub async fn logout(state: web::Data<AppState>, req: HttpRequest) -> AppResult<HttpResponse> {
+    let user_id = get_user_id(&req)?;
+    state.auth_service.logout(user_id).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::<()>::success_message(
+        "Logged out successfully",
+    )))
+}
+
 /// POST /api/v1/auth/google
 /// Google OAuth authentication - verifies Google token and returns OTP token
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/google",
+    tag = "auth",
+    request_body = GoogleAuthRequest,
+    responses(
+        (status = 200, description = "Google token verified, returns an OTP token", body = ApiResponseBody),
+        (status = 401, description = "Invalid Google token", body = ErrorResponse)
+    )
+)]
 pub async fn google_auth(
     state: web::Data<AppState>,
     body: web::Json<GoogleAuthRequest>,
@@ -1,9 +1,11 @@
 use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use chrono::NaiveDate;
 use uuid::Uuid;
 
 use super::AppState;
 use crate::error::{AppError, AppResult};
-use crate::utils::{ApiResponse, Claims};
+use crate::models::{TransactionStatus, TransactionType, TxFilter};
+use crate::utils::{ApiResponse, Claims, Cursor};
 
 fn get_user_id(req: &HttpRequest) -> AppResult<Uuid> {
     req.extensions()
@@ -90,23 +92,26 @@ pub async fn verify_transaction(
 }
 
 /// GET /api/v1/blockchain/transfers/{address}
-/// Get IDRX transfer history for an address (public, transparent)
+/// Get IDRX transfer history for an address (public, transparent), newest first
 pub async fn get_transfer_history(
     state: web::Data<AppState>,
     path: web::Path<String>,
     query: web::Query<TransferHistoryQuery>,
 ) -> AppResult<HttpResponse> {
     let address = path.into_inner();
-    let transfers = state
+    let page = state
         .blockchain_service
-        .get_transfer_history(&address, query.from_block)
+        .get_transfer_history(&address, query.from_block, query.to_block, query.limit)
         .await?;
 
     Ok(HttpResponse::Ok().json(ApiResponse::success(
         serde_json::json!({
             "address": address,
-            "transfers": transfers,
-            "count": transfers.len(),
+            "count": page.transfers.len(),
+            "transfers": page.transfers,
+            "from_block": page.from_block,
+            "to_block": page.to_block,
+            "next_from_block": page.next_from_block,
             "chain": "Base Mainnet",
             "chain_id": 8453
         }),
@@ -119,25 +124,33 @@ pub async fn get_transfer_history(
 pub async fn get_my_transactions(
     state: web::Data<AppState>,
     req: HttpRequest,
-    query: web::Query<PaginationQuery>,
+    query: web::Query<MyTransactionsQuery>,
 ) -> AppResult<HttpResponse> {
     let user_id = get_user_id(&req)?;
+    let query = query.into_inner();
+    let per_page = query.per_page.unwrap_or(20);
+    let cursor = query.cursor.as_deref().map(Cursor::decode).transpose()?;
+    let start_of = |date: NaiveDate| date.and_hms_opt(0, 0, 0).unwrap();
 
+    let filter = TxFilter {
+        user_id: Some(user_id),
+        tx_type: query.tx_type,
+        status: query.status,
+        invoice_id: query.invoice_id,
+        created_from: query.from.map(start_of),
+        created_to: query.to.and_then(|d| d.succ_opt()).map(start_of),
+        on_chain_only: true,
+    };
     let (transactions, total) = state
         .tx_repo
-        .find_blockchain_transactions_by_user(
-            user_id,
-            query.page.unwrap_or(1),
-            query.per_page.unwrap_or(20),
-        )
+        .find(&filter, query.page.unwrap_or(1), per_page, cursor)
         .await?;
+    let next_cursor = Cursor::after_page(&transactions, per_page, |t| (t.created_at, t.id));
 
-    Ok(HttpResponse::Ok().json(ApiResponse::paginated(
-        transactions,
-        total,
-        query.page.unwrap_or(1),
-        query.per_page.unwrap_or(20),
-    )))
+    Ok(HttpResponse::Ok().json(
+        ApiResponse::paginated(transactions, total, query.page.unwrap_or(1), per_page)
+            .with_next_cursor(next_cursor),
+    ))
 }
 
 /// GET /api/v1/blockchain/my-idrx-balance
@@ -230,20 +243,65 @@ pub async fn get_chain_info(state: web::Data<AppState>) -> AppResult<HttpRespons
             "current_block": block_number,
             "rpc_url": "https://mainnet.base.org",
             "explorer_url": state.config.block_explorer_url,
-            "idrx_contract": state.config.idrx_token_contract_addr,
+            "idrx_contract": state.blockchain_service.active_idrx_token().contract_addr,
             "platform_wallet": state.blockchain_service.get_platform_wallet()
         }),
         "Chain info retrieved",
     )))
 }
 
+/// GET /api/v1/blockchain/config
+/// Configured IDRX deployments in priority order and the one currently in use
+pub async fn get_blockchain_config(state: web::Data<AppState>) -> AppResult<HttpResponse> {
+    let active = state.blockchain_service.active_idrx_token();
+    let tokens: Vec<_> = state
+        .config
+        .idrx_tokens()
+        .into_iter()
+        .enumerate()
+        .map(|(priority, token)| {
+            serde_json::json!({
+                "priority": priority,
+                "chain_id": token.chain_id,
+                "contract_addr": token.contract_addr,
+                "pool_contract_addr": token.pool_contract_addr,
+                "nft_contract_addr": token.nft_contract_addr,
+                "paused": state.config.is_idrx_token_paused(&token.contract_addr),
+                "active": &token == active,
+            })
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        serde_json::json!({
+            "active_token": active,
+            "tokens": tokens,
+            "platform_wallet": state.blockchain_service.get_platform_wallet(),
+            "explorer_url": state.config.block_explorer_url
+        }),
+        "Blockchain config retrieved",
+    )))
+}
+
 #[derive(serde::Deserialize)]
 pub struct TransferHistoryQuery {
     pub from_block: Option<u64>,
+    pub to_block: Option<u64>,
+    pub limit: Option<usize>,
 }
 
 #[derive(serde::Deserialize)]
-pub struct PaginationQuery {
+pub struct MyTransactionsQuery {
     pub page: Option<i32>,
     pub per_page: Option<i32>,
+    /// `next_cursor` of the previous page; takes the place of `page`
+    pub cursor: Option<String>,
+    #[serde(rename = "type")]
+    pub tx_type: Option<TransactionType>,
+    pub status: Option<TransactionStatus>,
+    pub invoice_id: Option<Uuid>,
+    /// First day included, `YYYY-MM-DD`
+    pub from: Option<NaiveDate>,
+    /// Last day included, `YYYY-MM-DD`
+    pub to: Option<NaiveDate>,
 }
@@ -6,7 +6,7 @@ use crate::utils::ApiResponse;
 
 /// GET /api/v1/currency/supported
 pub async fn get_supported_currencies(state: web::Data<AppState>) -> AppResult<HttpResponse> {
-    let currencies = state.currency_service.get_supported_currencies();
+    let currencies = state.currency_service.get_supported_currencies().await?;
     Ok(HttpResponse::Ok().json(ApiResponse::success(
         currencies,
         "Supported currencies retrieved",
@@ -25,6 +25,21 @@ pub async fn get_locked_exchange_rate(
     Ok(HttpResponse::Ok().json(ApiResponse::success(result, "Exchange rate locked")))
 }
 
+/// POST /api/v1/currency/preview
+pub async fn preview_conversion(
+    state: web::Data<AppState>,
+    body: web::Json<ConvertRequest>,
+) -> AppResult<HttpResponse> {
+    let preview = state
+        .currency_service
+        .preview_conversion(&body.from_currency, body.amount)
+        .await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        preview,
+        "Conversion preview calculated",
+    )))
+}
+
 /// GET /api/v1/currency/disbursement-estimate
 pub async fn calculate_estimated_disbursement(
     state: web::Data<AppState>,
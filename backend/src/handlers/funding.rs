@@ -2,9 +2,20 @@ use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
 use uuid::Uuid;
 
 use super::AppState;
-use crate::error::{AppError, AppResult};
-use crate::models::InvestRequest;
-use crate::utils::{ApiResponse, Claims};
+use crate::error::{AppError, AppResult, ErrorResponse};
+use crate::models::{
+    CatalystConsents, DisbursementMode, FundableInvoiceFilter, InvestRequest,
+    InvestWithPermitRequest, PoolFilter, PoolInvestmentLimitsRequest, RepayInvoiceRequest, Role,
+};
+use crate::utils::{
+    conditional_json, AdminPoolDetailApiResponse, AdminPoolListApiResponse, ApiResponse,
+    ApiResponseBody, Claims, Cursor, DisbursementPreviewApiResponse, FundingPoolApiResponse,
+    FundingPoolDetailApiResponse, FundingPoolListApiResponse, InvestmentDetailApiResponse,
+    InvestmentListApiResponse, InvestmentReceiptApiResponse, InvoiceFundingStatusApiResponse,
+    InvoiceListApiResponse, Masked, MitraDashboardApiResponse, PlatformStatsApiResponse,
+    PoolOnChainAuditApiResponse, PortfolioApiResponse, RepaymentScheduleApiResponse,
+    TaxSummaryApiResponse, ValidatedJson,
+};
 
 fn get_user_id(req: &HttpRequest) -> AppResult<Uuid> {
     req.extensions()
@@ -14,6 +25,19 @@ fn get_user_id(req: &HttpRequest) -> AppResult<Uuid> {
 }
 
 /// POST /api/v1/invoices/{id}/pool
+#[utoipa::path(
+    post,
+    path = "/api/v1/invoices/{id}/pool",
+    tag = "funding",
+    params(("id" = Uuid, Path, description = "Invoice ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 201, description = "Funding pool created", body = FundingPoolApiResponse),
+        (status = 400, description = "Invoice is not tokenized", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 404, description = "Invoice not found", body = ErrorResponse)
+    )
+)]
 pub async fn create_pool(
     state: web::Data<AppState>,
     _req: HttpRequest,
@@ -28,6 +52,15 @@ pub async fn create_pool(
 }
 
 /// GET /api/v1/pools
+#[utoipa::path(
+    get,
+    path = "/api/v1/pools",
+    tag = "funding",
+    params(PoolListQuery),
+    responses(
+        (status = 200, description = "Paginated funding pools", body = FundingPoolListApiResponse)
+    )
+)]
 pub async fn list_pools(
     state: web::Data<AppState>,
     query: web::Query<PoolListQuery>,
@@ -37,7 +70,7 @@ pub async fn list_pools(
         .list_pools(query.page.unwrap_or(1), query.per_page.unwrap_or(10))
         .await?;
     Ok(HttpResponse::Ok().json(ApiResponse::paginated(
-        pools,
+        Masked(pools),
         total,
         query.page.unwrap_or(1),
         query.per_page.unwrap_or(10),
@@ -45,49 +78,186 @@ pub async fn list_pools(
 }
 
 /// GET /api/v1/pools/{id}
+#[utoipa::path(
+    get,
+    path = "/api/v1/pools/{id}",
+    tag = "funding",
+    params(("id" = Uuid, Path, description = "Pool ID")),
+    responses(
+        (status = 200, description = "Funding pool", body = FundingPoolDetailApiResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse)
+    )
+)]
 pub async fn get_pool(
     state: web::Data<AppState>,
     path: web::Path<Uuid>,
 ) -> AppResult<HttpResponse> {
     let pool_id = path.into_inner();
     let pool = state.funding_service.get_pool(pool_id).await?;
-    Ok(HttpResponse::Ok().json(ApiResponse::success(pool, "Pool retrieved successfully")))
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        Masked(pool),
+        "Pool retrieved successfully",
+    )))
+}
+
+/// GET /api/v1/pools/{id}/repayment-schedule
+#[utoipa::path(
+    get,
+    path = "/api/v1/pools/{id}/repayment-schedule",
+    tag = "funding",
+    params(("id" = Uuid, Path, description = "Pool ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Per-investor repayment schedule", body = RepaymentScheduleApiResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Not the invoice owner or an admin", body = ErrorResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_repayment_schedule(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+) -> AppResult<HttpResponse> {
+    let (user_id, is_admin) = req
+        .extensions()
+        .get::<Claims>()
+        .map(|c| (c.user_id(), c.role == Role::Admin))
+        .ok_or_else(|| AppError::Unauthorized("User not authenticated".to_string()))?;
+
+    let schedule = state
+        .funding_service
+        .get_repayment_schedule(user_id, is_admin, path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        schedule,
+        "Repayment schedule retrieved successfully",
+    )))
 }
 
-/// GET /api/v1/marketplace - uses list_pools for now
+/// GET /api/v1/marketplace
+#[utoipa::path(
+    get,
+    path = "/api/v1/marketplace",
+    tag = "funding",
+    params(MarketplaceQuery),
+    responses(
+        (status = 200, description = "Paginated marketplace pools", body = FundingPoolListApiResponse),
+        (status = 400, description = "Invalid filter", body = ErrorResponse)
+    )
+)]
 pub async fn get_marketplace(
     state: web::Data<AppState>,
     query: web::Query<MarketplaceQuery>,
 ) -> AppResult<HttpResponse> {
+    let query = query.into_inner();
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query.per_page.unwrap_or(10).clamp(1, 100);
+    let filter = FundableInvoiceFilter {
+        grade: query.grade,
+        country_tier: query.country_tier,
+        min_yield: query.min_yield,
+        min_days_remaining: query.min_days_remaining,
+        max_days_remaining: query.max_days_remaining,
+    };
+
     let (pools, total) = state
         .funding_service
-        .list_pools(query.page.unwrap_or(1), query.per_page.unwrap_or(10))
+        .list_marketplace(&filter, page, per_page)
         .await?;
-    Ok(HttpResponse::Ok().json(ApiResponse::paginated(
-        pools,
-        total,
-        query.page.unwrap_or(1),
-        query.per_page.unwrap_or(10),
-    )))
+    Ok(HttpResponse::Ok()
+        .json(ApiResponse::paginated(Masked(pools), total, page, per_page).with_filters(&filter)))
+}
+
+/// GET /api/v1/marketplace/stats
+#[utoipa::path(
+    get,
+    path = "/api/v1/marketplace/stats",
+    tag = "funding",
+    responses(
+        (status = 200, description = "Platform-wide funding aggregates", body = PlatformStatsApiResponse)
+    )
+)]
+pub async fn get_platform_stats(state: web::Data<AppState>) -> AppResult<HttpResponse> {
+    let stats = state.funding_service.get_platform_stats().await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(stats, "Platform stats retrieved")))
 }
 
 /// GET /api/v1/marketplace/{id}/detail
+#[utoipa::path(
+    get,
+    path = "/api/v1/marketplace/{id}/detail",
+    tag = "funding",
+    params(("id" = Uuid, Path, description = "Pool ID")),
+    responses(
+        (status = 200, description = "Marketplace pool detail", body = FundingPoolDetailApiResponse),
+        (status = 304, description = "Unchanged since the ETag sent in If-None-Match"),
+        (status = 404, description = "Pool not found", body = ErrorResponse)
+    )
+)]
 pub async fn get_pool_detail(
     state: web::Data<AppState>,
+    req: HttpRequest,
     path: web::Path<Uuid>,
 ) -> AppResult<HttpResponse> {
     let pool_id = path.into_inner();
     let detail = state.funding_service.get_pool(pool_id).await?;
-    Ok(HttpResponse::Ok().json(ApiResponse::success(detail, "Pool detail retrieved")))
+
+    let mut versions = vec![detail.pool.updated_at];
+    versions.extend(detail.invoice.as_ref().map(|invoice| invoice.updated_at));
+
+    Ok(conditional_json(
+        &req,
+        &versions,
+        ApiResponse::success(Masked(detail), "Pool detail retrieved"),
+    ))
 }
 
 /// POST /api/v1/marketplace/calculate
+#[utoipa::path(
+    post,
+    path = "/api/v1/marketplace/calculate",
+    tag = "funding",
+    request_body = CalculateInvestmentRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Projected return, or an `InvestmentPreview` on a dry run", body = ApiResponseBody),
+        (status = 400, description = "Dry run failed an investment check", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse)
+    )
+)]
 pub async fn calculate_investment(
-    _state: web::Data<AppState>,
+    state: web::Data<AppState>,
+    req: HttpRequest,
     body: web::Json<CalculateInvestmentRequest>,
 ) -> AppResult<HttpResponse> {
-    // Simple calculation stub
     let data = body.into_inner();
+
+    // A dry run goes through every `invest` check without the transfer or any writes
+    if data.dry_run {
+        let user_id = get_user_id(&req)?;
+        let preview = state
+            .funding_service
+            .preview_investment(
+                user_id,
+                InvestRequest {
+                    pool_id: data.pool_id,
+                    amount: data.amount,
+                    tranche: data.tranche,
+                    tx_hash: String::new(),
+                    tnc_accepted: data.tnc_accepted,
+                    catalyst_consents: data.catalyst_consents,
+                },
+            )
+            .await?;
+        return Ok(HttpResponse::Ok().json(ApiResponse::success(
+            preview,
+            "Investment passed all checks",
+        )));
+    }
+
+    // Simple calculation stub
     let interest_rate = if data.tranche == "priority" {
         0.08
     } else {
@@ -109,46 +279,187 @@ pub async fn calculate_investment(
 }
 
 /// POST /api/v1/investments
+#[utoipa::path(
+    post,
+    path = "/api/v1/investments",
+    tag = "funding",
+    request_body = InvestRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 201, description = "Investment recorded", body = InvestmentReceiptApiResponse),
+        (status = 400, description = "Invalid amount, tranche or transfer", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse)
+    )
+)]
 pub async fn invest(
     state: web::Data<AppState>,
     req: HttpRequest,
-    body: web::Json<InvestRequest>,
+    body: ValidatedJson<InvestRequest>,
 ) -> AppResult<HttpResponse> {
     let user_id = get_user_id(&req)?;
     let investment = state
         .funding_service
         .invest(user_id, body.into_inner())
         .await?;
-    Ok(HttpResponse::Created().json(ApiResponse::success(investment, "Investment initiated")))
+    let receipt = state
+        .funding_service
+        .build_investment_receipt(investment)
+        .await?;
+    Ok(HttpResponse::Created().json(ApiResponse::success(receipt, "Investment initiated")))
 }
 
-/// POST /api/v1/investments/confirm
-pub async fn confirm_investment(
-    _state: web::Data<AppState>,
-    _req: HttpRequest,
-    body: web::Json<ConfirmInvestmentRequest>,
+/// GET /api/v1/investments/{id}/receipt
+#[utoipa::path(
+    get,
+    path = "/api/v1/investments/{id}/receipt",
+    tag = "funding",
+    params(("id" = Uuid, Path, description = "Investment ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Investment receipt", body = InvestmentReceiptApiResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 404, description = "Investment not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_investment_receipt(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+) -> AppResult<HttpResponse> {
+    let user_id = get_user_id(&req)?;
+    let receipt = state
+        .funding_service
+        .get_investment_receipt(user_id, path.into_inner())
+        .await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        receipt,
+        "Investment receipt retrieved",
+    )))
+}
+
+/// GET /api/v1/investments/{id}
+/// The caller's investment with its pool, invoice, current return and explorer links
+#[utoipa::path(
+    get,
+    path = "/api/v1/investments/{id}",
+    tag = "funding",
+    params(("id" = Uuid, Path, description = "Investment ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Investment detail", body = InvestmentDetailApiResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Investment belongs to another investor", body = ErrorResponse),
+        (status = 404, description = "Investment not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_investment_detail(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
 ) -> AppResult<HttpResponse> {
-    // Stub - investment confirmation handled in invest
+    let user_id = get_user_id(&req)?;
+    let detail = state
+        .funding_service
+        .get_investment_detail(user_id, path.into_inner())
+        .await?;
+    // The embedded invoice belongs to the exporter, not to the investor
     Ok(HttpResponse::Ok().json(ApiResponse::success(
-        serde_json::json!({ "investment_id": body.investment_id }),
-        "Investment confirmed",
+        Masked(detail),
+        "Investment detail retrieved",
     )))
 }
 
+/// POST /api/v1/investments/confirm
+#[utoipa::path(
+    post,
+    path = "/api/v1/investments/confirm",
+    tag = "funding",
+    request_body = InvestRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Investment confirmed", body = InvestmentReceiptApiResponse),
+        (status = 400, description = "Transfer could not be verified", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse)
+    )
+)]
+pub async fn confirm_investment(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    body: ValidatedJson<InvestRequest>,
+) -> AppResult<HttpResponse> {
+    let user_id = get_user_id(&req)?;
+    let investment = state
+        .funding_service
+        .confirm_investment(user_id, body.into_inner())
+        .await?;
+    let receipt = state
+        .funding_service
+        .build_investment_receipt(investment)
+        .await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(receipt, "Investment confirmed")))
+}
+
+/// POST /api/v1/investments/permit
+#[utoipa::path(
+    post,
+    path = "/api/v1/investments/permit",
+    tag = "funding",
+    request_body = InvestWithPermitRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 201, description = "Investment recorded", body = InvestmentReceiptApiResponse),
+        (status = 400, description = "Permit disabled, expired or invalid", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Permit owner is not the investor's wallet", body = ErrorResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse)
+    )
+)]
+pub async fn invest_with_permit(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    body: ValidatedJson<InvestWithPermitRequest>,
+) -> AppResult<HttpResponse> {
+    let user_id = get_user_id(&req)?;
+    let investment = state
+        .funding_service
+        .invest_with_permit(user_id, body.into_inner())
+        .await?;
+    let receipt = state
+        .funding_service
+        .build_investment_receipt(investment)
+        .await?;
+    Ok(HttpResponse::Created().json(ApiResponse::success(receipt, "Investment initiated")))
+}
+
 /// GET /api/v1/investments
+#[utoipa::path(
+    get,
+    path = "/api/v1/investments",
+    tag = "funding",
+    params(InvestmentListQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Investments of the current investor", body = InvestmentListApiResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse)
+    )
+)]
 pub async fn get_my_investments(
     state: web::Data<AppState>,
     req: HttpRequest,
-    query: web::Query<PaginationQuery>,
+    query: web::Query<InvestmentListQuery>,
 ) -> AppResult<HttpResponse> {
     let user_id = get_user_id(&req)?;
     let page = query.page.unwrap_or(1);
     let per_page = query.per_page.unwrap_or(10);
+    let cursor = query.cursor.as_deref().map(Cursor::decode).transpose()?;
 
     let (investments, total) = state
         .funding_service
-        .get_investor_investments(user_id, page, per_page)
+        .get_investor_investments(user_id, page, per_page, cursor)
         .await?;
+    let next_cursor =
+        Cursor::after_page(&investments, per_page, |i| (i.invested_at, i.investment_id));
 
     Ok(HttpResponse::Ok().json(ApiResponse::success(
         crate::models::ActiveInvestmentListResponse {
@@ -157,12 +468,23 @@ pub async fn get_my_investments(
             page,
             per_page,
             total_pages: (total as f64 / per_page as f64).ceil() as i32,
+            next_cursor: next_cursor.map(|c| c.encode()),
         },
         "Investments retrieved",
     )))
 }
 
 /// GET /api/v1/investments/portfolio
+#[utoipa::path(
+    get,
+    path = "/api/v1/investments/portfolio",
+    tag = "funding",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Portfolio summary", body = PortfolioApiResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse)
+    )
+)]
 pub async fn get_portfolio(
     state: web::Data<AppState>,
     req: HttpRequest,
@@ -175,7 +497,91 @@ pub async fn get_portfolio(
     Ok(HttpResponse::Ok().json(ApiResponse::success(portfolio, "Portfolio retrieved")))
 }
 
+/// GET /api/v1/investments/export
+/// Download the investor's investments and returns as CSV
+#[utoipa::path(
+    get,
+    path = "/api/v1/investments/export",
+    tag = "funding",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Investments as CSV", content_type = "text/csv", body = String),
+        (status = 401, description = "Not authenticated", body = ErrorResponse)
+    )
+)]
+pub async fn export_portfolio_csv(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+) -> AppResult<HttpResponse> {
+    let user_id = get_user_id(&req)?;
+    let csv = state.funding_service.export_portfolio_csv(user_id).await?;
+    let filename = format!(
+        "vessel_portfolio_{}.csv",
+        chrono::Utc::now().format("%Y%m%d")
+    );
+    Ok(HttpResponse::Ok()
+        .content_type("text/csv; charset=utf-8")
+        .insert_header((
+            "Content-Disposition",
+            format!("attachment; filename=\"{}\"", filename),
+        ))
+        .body(csv))
+}
+
+/// GET /api/v1/investments/tax-summary?year=YYYY
+/// Realized returns for a calendar year, as JSON or with `format=csv` as a CSV download
+#[utoipa::path(
+    get,
+    path = "/api/v1/investments/tax-summary",
+    tag = "funding",
+    params(TaxSummaryQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Tax summary for the year", body = TaxSummaryApiResponse),
+        (status = 400, description = "Invalid year", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse)
+    )
+)]
+pub async fn get_tax_summary(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    query: web::Query<TaxSummaryQuery>,
+) -> AppResult<HttpResponse> {
+    let user_id = get_user_id(&req)?;
+
+    if query.format.as_deref() == Some("csv") {
+        let csv = state
+            .funding_service
+            .export_tax_summary_csv(user_id, query.year)
+            .await?;
+        let filename = format!("vessel_tax_summary_{}.csv", query.year);
+        return Ok(HttpResponse::Ok()
+            .content_type("text/csv; charset=utf-8")
+            .insert_header((
+                "Content-Disposition",
+                format!("attachment; filename=\"{}\"", filename),
+            ))
+            .body(csv));
+    }
+
+    let summary = state
+        .funding_service
+        .get_tax_summary(user_id, query.year)
+        .await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(summary, "Tax summary retrieved")))
+}
+
 /// GET /api/v1/investments/active
+#[utoipa::path(
+    get,
+    path = "/api/v1/investments/active",
+    tag = "funding",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Active investments", body = ApiResponseBody),
+        (status = 401, description = "Not authenticated", body = ErrorResponse)
+    )
+)]
 pub async fn get_active_investments(
     _state: web::Data<AppState>,
     _req: HttpRequest,
@@ -186,18 +592,78 @@ pub async fn get_active_investments(
 }
 
 /// POST /api/v1/exporter/disbursement
+#[utoipa::path(
+    post,
+    path = "/api/v1/exporter/disbursement",
+    tag = "funding",
+    request_body = ExporterDisbursementRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Pool disbursed", body = FundingPoolApiResponse),
+        (status = 400, description = "Pool below the disbursement threshold or no verified bank account", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Not the pool's mitra", body = ErrorResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse)
+    )
+)]
 pub async fn exporter_disbursement(
-    _state: web::Data<AppState>,
-    _req: HttpRequest,
-    _body: web::Json<ExporterDisbursementRequest>,
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    body: web::Json<ExporterDisbursementRequest>,
 ) -> AppResult<HttpResponse> {
-    // Stub
-    Ok(HttpResponse::Ok().json(ApiResponse::<()>::success_message(
-        "Disbursement request received",
-    )))
+    let user_id = get_user_id(&req)?;
+    let pool = state
+        .funding_service
+        .request_exporter_disbursement(user_id, body.pool_id, body.mode)
+        .await?;
+    bill_importer(&state, pool.id).await;
+
+    let message = match body.mode {
+        DisbursementMode::Wallet => "Disbursement sent to your wallet",
+        DisbursementMode::Bank => "Disbursement to your bank account is being processed",
+    };
+    Ok(HttpResponse::Ok().json(ApiResponse::success(pool, message)))
+}
+
+/// GET /api/v1/exporter/invoices/{id}/funding-status
+/// Live funding progress of one of the caller's invoices
+#[utoipa::path(
+    get,
+    path = "/api/v1/exporter/invoices/{id}/funding-status",
+    tag = "funding",
+    params(("id" = Uuid, Path, description = "Invoice ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Funding progress", body = InvoiceFundingStatusApiResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Invoice belongs to another mitra", body = ErrorResponse),
+        (status = 404, description = "Invoice or pool not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_invoice_funding_status(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+) -> AppResult<HttpResponse> {
+    let user_id = get_user_id(&req)?;
+    let status = state
+        .funding_service
+        .get_invoice_funding_status(user_id, path.into_inner())
+        .await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(status, "Funding status retrieved")))
 }
 
 /// GET /api/v1/mitra/dashboard
+#[utoipa::path(
+    get,
+    path = "/api/v1/mitra/dashboard",
+    tag = "funding",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Mitra dashboard", body = MitraDashboardApiResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse)
+    )
+)]
 pub async fn get_mitra_dashboard(
     state: web::Data<AppState>,
     req: HttpRequest,
@@ -208,6 +674,17 @@ pub async fn get_mitra_dashboard(
 }
 
 /// GET /api/v1/mitra/invoices
+#[utoipa::path(
+    get,
+    path = "/api/v1/mitra/invoices",
+    tag = "funding",
+    params(PaginationQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Paginated invoices of the current mitra", body = InvoiceListApiResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse)
+    )
+)]
 pub async fn get_mitra_active_invoices(
     state: web::Data<AppState>,
     req: HttpRequest,
@@ -220,13 +697,24 @@ pub async fn get_mitra_active_invoices(
     // Get all invoices for this mitra (can filter by status via query param if needed)
     let (invoices, total) = state
         .invoice_repo
-        .find_by_exporter(user_id, None, page, per_page)
+        .find_by_exporter(user_id, None, false, page, per_page, None)
         .await?;
 
     Ok(HttpResponse::Ok().json(ApiResponse::paginated(invoices, total, page, per_page)))
 }
 
 /// GET /api/v1/mitra/pools - List all pools owned by mitra with full details
+#[utoipa::path(
+    get,
+    path = "/api/v1/mitra/pools",
+    tag = "funding",
+    params(PaginationQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Paginated pools of the current mitra", body = FundingPoolListApiResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse)
+    )
+)]
 pub async fn get_mitra_pools(
     state: web::Data<AppState>,
     req: HttpRequest,
@@ -245,6 +733,18 @@ pub async fn get_mitra_pools(
 }
 
 /// GET /api/v1/mitra/invoices/{id}/pool - Get pool detail for a specific invoice
+#[utoipa::path(
+    get,
+    path = "/api/v1/mitra/invoices/{id}/pool",
+    tag = "funding",
+    params(("id" = Uuid, Path, description = "Invoice ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Pool of the invoice", body = FundingPoolDetailApiResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse)
+    )
+)]
 pub async fn get_pool_by_invoice(
     state: web::Data<AppState>,
     req: HttpRequest,
@@ -263,7 +763,169 @@ pub async fn get_pool_by_invoice(
 
 // ============ Admin Funding Endpoints ============
 
+/// GET /api/v1/admin/pools
+/// Every pool, optionally of one status, e.g. `filled` pools awaiting disbursement
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/pools",
+    tag = "funding",
+    params(AdminPoolListQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Paginated pools", body = AdminPoolListApiResponse),
+        (status = 400, description = "Unknown status or sort", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Admin only", body = ErrorResponse)
+    )
+)]
+pub async fn list_all_pools(
+    state: web::Data<AppState>,
+    query: web::Query<AdminPoolListQuery>,
+) -> AppResult<HttpResponse> {
+    let query = query.into_inner();
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query.per_page.unwrap_or(10).clamp(1, 100);
+    let filter = PoolFilter {
+        status: query.status,
+        sort_by: query.sort_by,
+    };
+
+    let (pools, total) = state
+        .funding_service
+        .list_all_pools(&filter, page, per_page)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::paginated(pools, total, page, per_page)))
+}
+
+/// GET /api/v1/admin/pools/{id}
+/// Pool with its invoice, investors, transactions, on-chain checks and timeline
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/pools/{id}",
+    tag = "funding",
+    params(("id" = Uuid, Path, description = "Pool ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Pool detail for investigation", body = AdminPoolDetailApiResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Admin only", body = ErrorResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_admin_pool_detail(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> AppResult<HttpResponse> {
+    let pool_id = path.into_inner();
+    let detail = state.funding_service.get_admin_pool_detail(pool_id).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(detail, "Pool detail retrieved")))
+}
+
+/// GET /api/v1/admin/pools/{id}/disburse-preview
+/// IDRX amount and gas cost of disbursing the pool, checked against the platform wallet
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/pools/{id}/disburse-preview",
+    tag = "funding",
+    params(("id" = Uuid, Path, description = "Pool ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Disbursement preview", body = DisbursementPreviewApiResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Admin only", body = ErrorResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_disbursement_preview(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> AppResult<HttpResponse> {
+    let pool_id = path.into_inner();
+    let preview = state
+        .funding_service
+        .get_disbursement_preview(pool_id)
+        .await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        preview,
+        "Disbursement preview retrieved",
+    )))
+}
+
+/// GET /api/v1/admin/pools/{id}/onchain-audit
+/// Compare the pool's recorded investments with the InvoicePool contract's events
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/pools/{id}/onchain-audit",
+    tag = "funding",
+    params(("id" = Uuid, Path, description = "Pool ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Discrepancy report", body = PoolOnChainAuditApiResponse),
+        (status = 400, description = "Invoice has no minted NFT", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Admin only", body = ErrorResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse),
+        (status = 500, description = "Contract events could not be read", body = ErrorResponse)
+    )
+)]
+pub async fn audit_pool_onchain(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> AppResult<HttpResponse> {
+    let pool_id = path.into_inner();
+    let audit = state.funding_service.audit_pool_onchain(pool_id).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(audit, "On-chain audit completed")))
+}
+
+/// POST /api/v1/admin/investments/{id}/reconcile
+/// Complete an on-chain investment left pending after its funds were forwarded
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/investments/{id}/reconcile",
+    tag = "funding",
+    params(("id" = Uuid, Path, description = "Investment ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Investment completed", body = InvestmentReceiptApiResponse),
+        (status = 400, description = "Investments are off-chain", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Admin only", body = ErrorResponse),
+        (status = 404, description = "Investment not found", body = ErrorResponse),
+        (status = 409, description = "Investment is not pending or was claimed too recently", body = ErrorResponse)
+    )
+)]
+pub async fn reconcile_pending_investment(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> AppResult<HttpResponse> {
+    let investment_id = path.into_inner();
+    let investment = state
+        .funding_service
+        .reconcile_pending_investment(investment_id)
+        .await?;
+    let receipt = state
+        .funding_service
+        .build_investment_receipt(investment)
+        .await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(receipt, "Investment reconciled")))
+}
+
 /// POST /api/v1/admin/pools/{id}/disburse
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/pools/{id}/disburse",
+    tag = "funding",
+    params(("id" = Uuid, Path, description = "Pool ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Pool disbursed", body = FundingPoolApiResponse),
+        (status = 400, description = "Pool cannot be disbursed", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Admin only", body = ErrorResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse)
+    )
+)]
 pub async fn disburse(
     state: web::Data<AppState>,
     req: HttpRequest,
@@ -273,10 +935,33 @@ pub async fn disburse(
     let _admin_id = get_user_id(&req)?; // Ensure authenticated (Role check usually in middleware)
 
     let pool = state.funding_service.disburse_pool(pool_id).await?;
+    bill_importer(&state, pool.id).await;
     Ok(HttpResponse::Ok().json(ApiResponse::success(pool, "Disbursement initiated successfully")))
 }
 
+/// Once funds go out, the buyer owes the invoice. Billing problems are logged
+/// rather than failing a disbursement that already happened.
+async fn bill_importer(state: &AppState, pool_id: Uuid) {
+    if let Err(e) = state.payment_service.create_importer_payment(pool_id).await {
+        tracing::error!("Failed to bill importer for pool {}: {}", pool_id, e);
+    }
+}
+
 /// POST /api/v1/admin/pools/{id}/close
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/pools/{id}/close",
+    tag = "funding",
+    params(("id" = Uuid, Path, description = "Pool ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Pool closed", body = FundingPoolApiResponse),
+        (status = 400, description = "Pool cannot be closed", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Admin only", body = ErrorResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse)
+    )
+)]
 pub async fn close_pool_and_notify(
     state: web::Data<AppState>,
     _req: HttpRequest,
@@ -287,7 +972,53 @@ pub async fn close_pool_and_notify(
     Ok(HttpResponse::Ok().json(ApiResponse::success(pool, "Pool closed successfully")))
 }
 
+/// PUT /api/v1/admin/pools/{id}/investment-limits
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/pools/{id}/investment-limits",
+    tag = "funding",
+    params(("id" = Uuid, Path, description = "Pool ID")),
+    request_body = PoolInvestmentLimitsRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Investment limits updated", body = FundingPoolApiResponse),
+        (status = 400, description = "Pool is not open or ratios are invalid", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Admin only", body = ErrorResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse)
+    )
+)]
+pub async fn set_pool_investment_limits(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    body: web::Json<PoolInvestmentLimitsRequest>,
+) -> AppResult<HttpResponse> {
+    let pool = state
+        .funding_service
+        .set_pool_investment_limits(path.into_inner(), body.into_inner())
+        .await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        pool,
+        "Investment limits updated successfully",
+    )))
+}
+
 /// GET /api/v1/admin/users/{id}/pools
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/users/{id}/pools",
+    tag = "funding",
+    params(
+        ("id" = Uuid, Path, description = "User ID"),
+        PaginationQuery,
+    ),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Paginated pools of a mitra", body = FundingPoolListApiResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Admin only", body = ErrorResponse)
+    )
+)]
 pub async fn get_exporter_pools(
     state: web::Data<AppState>,
     path: web::Path<Uuid>,
@@ -299,18 +1030,33 @@ pub async fn get_exporter_pools(
 
     let (pools, total) = state
         .funding_service
-        .get_mitra_pools(user_id, page, per_page)
+        .get_exporter_pools_for_audit(user_id, page, per_page)
         .await?;
 
     Ok(HttpResponse::Ok().json(ApiResponse::paginated(pools, total, page, per_page)))
 }
 
 /// POST /api/v1/admin/pools/{id}/repay
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/pools/{id}/repay",
+    tag = "funding",
+    request_body = RepayInvoiceRequest,
+    params(("id" = Uuid, Path, description = "Pool ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Repayment processed", body = FundingPoolApiResponse),
+        (status = 400, description = "Invalid repayment", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Admin only", body = ErrorResponse),
+        (status = 404, description = "Pool not found", body = ErrorResponse)
+    )
+)]
 pub async fn process_pool_repayment(
     state: web::Data<AppState>,
     _req: HttpRequest, // Admin check in middleware
     path: web::Path<Uuid>,
-    body: web::Json<crate::models::RepayInvoiceRequest>,
+    body: web::Json<RepayInvoiceRequest>,
 ) -> AppResult<HttpResponse> {
     let pool_id = path.into_inner();
     let data = body.into_inner();
@@ -329,11 +1075,25 @@ pub async fn process_pool_repayment(
 }
 
 /// POST /api/v1/admin/invoices/{id}/repay (Used by Mitra/Admin)
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/invoices/{id}/repay",
+    tag = "funding",
+    request_body = RepayInvoiceRequest,
+    params(("id" = Uuid, Path, description = "Invoice ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Repayment processed", body = ApiResponseBody),
+        (status = 400, description = "Invalid repayment", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 404, description = "Invoice not found", body = ErrorResponse)
+    )
+)]
 pub async fn process_repayment(
     state: web::Data<AppState>,
     req: HttpRequest,
     path: web::Path<Uuid>,
-    body: web::Json<crate::models::RepayInvoiceRequest>,
+    body: web::Json<RepayInvoiceRequest>,
 ) -> AppResult<HttpResponse> {
     let invoice_id = path.into_inner();
     let user_id = get_user_id(&req)?;
@@ -351,7 +1111,8 @@ pub async fn process_repayment(
     )))
 }
 
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
 #[allow(dead_code)] // Fields used for query deserialization
 pub struct PoolListQuery {
     pub status: Option<String>,
@@ -359,35 +1120,79 @@ pub struct PoolListQuery {
     pub per_page: Option<i32>,
 }
 
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AdminPoolListQuery {
+    /// `open`, `filled`, `disbursed` or `closed`
+    pub status: Option<String>,
+    /// `deadline` for the soonest deadline first, `newest` (default) otherwise
+    pub sort_by: Option<String>,
+    pub page: Option<i32>,
+    pub per_page: Option<i32>,
+}
+
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TaxSummaryQuery {
+    /// Calendar year of repayment
+    pub year: i32,
+    /// `csv` for a CSV download, JSON otherwise
+    pub format: Option<String>,
+}
+
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct MarketplaceQuery {
     pub page: Option<i32>,
     pub per_page: Option<i32>,
+    /// Invoice grade, e.g. `A`
+    pub grade: Option<String>,
+    /// Buyer country tier (1-3)
+    pub country_tier: Option<i32>,
+    /// Minimum annual rate (%) of the better-paying tranche
+    pub min_yield: Option<rust_decimal::Decimal>,
+    /// Minimum days until the invoice is due
+    pub min_days_remaining: Option<i32>,
+    /// Maximum days until the invoice is due
+    pub max_days_remaining: Option<i32>,
 }
 
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct InvestmentListQuery {
+    pub page: Option<i32>,
+    pub per_page: Option<i32>,
+    /// `next_cursor` of the previous page; takes the place of `page`
+    pub cursor: Option<String>,
+}
+
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
 #[allow(dead_code)] // Fields used for query deserialization
 pub struct PaginationQuery {
     pub page: Option<i32>,
     pub per_page: Option<i32>,
 }
 
-#[derive(serde::Deserialize)]
-#[allow(dead_code)] // Fields used for request deserialization
+#[derive(serde::Deserialize, utoipa::ToSchema)]
 pub struct CalculateInvestmentRequest {
     pub pool_id: Uuid,
     pub amount: f64,
     pub tranche: String,
+    /// Run every `invest` check for the caller instead of the plain projection
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Checked on a dry run, as `invest` would
+    #[serde(default)]
+    pub tnc_accepted: bool,
+    /// Checked on a dry run of the catalyst tranche
+    pub catalyst_consents: Option<CatalystConsents>,
 }
 
-#[derive(serde::Deserialize)]
-pub struct ConfirmInvestmentRequest {
-    pub investment_id: Uuid,
-}
-
-#[derive(serde::Deserialize)]
-#[allow(dead_code)] // Fields used for request deserialization
+#[derive(serde::Deserialize, utoipa::ToSchema)]
 pub struct ExporterDisbursementRequest {
     pub pool_id: Uuid,
-    // Bank account removed
+    /// `wallet` (default) or `bank`
+    #[serde(default)]
+    pub mode: DisbursementMode,
 }
@@ -1,11 +1,9 @@
-use actix_web::{web, HttpResponse};
-use rust_decimal::prelude::FromPrimitive;
-use rust_decimal::Decimal;
+use actix_web::{web, HttpRequest, HttpResponse};
 use uuid::Uuid;
 
 use super::AppState;
 use crate::error::{AppError, AppResult};
-use crate::models::ImporterPayRequest;
+use crate::models::{BuyerAccessRequest, GetNonceRequest, ImporterPayRequest};
 use crate::utils::ApiResponse;
 
 /// GET /api/v1/public/payments/{payment_id}
@@ -23,35 +21,40 @@ pub async fn get_payment_info(
     Ok(HttpResponse::Ok().json(ApiResponse::success(payment, "Payment info retrieved")))
 }
 
+/// POST /api/v1/public/payments/{payment_id}/nonce
+/// Issue a message for the importer's wallet to sign before paying
+pub async fn get_payment_nonce(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    body: web::Json<GetNonceRequest>,
+) -> AppResult<HttpResponse> {
+    let payment_id = path.into_inner();
+    let response = state
+        .payment_service
+        .get_payment_nonce(payment_id, &body.wallet_address)
+        .await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(response, "Nonce generated")))
+}
+
 /// POST /api/v1/public/payments/{payment_id}/pay
-/// Public endpoint for importers to submit payment
+/// Public endpoint for importers to submit payment, signed by the wallet the nonce was issued to.
+/// An optional `Idempotency-Key` header makes resubmissions return the original result.
 pub async fn pay(
     state: web::Data<AppState>,
+    req: HttpRequest,
     path: web::Path<Uuid>,
     body: web::Json<ImporterPayRequest>,
 ) -> AppResult<HttpResponse> {
     let payment_id = path.into_inner();
+    let idempotency_key = req
+        .headers()
+        .get("Idempotency-Key")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim());
 
-    // Get the payment
-    let payment = state
-        .importer_payment_repo
-        .find_by_id(payment_id)
-        .await?
-        .ok_or_else(|| AppError::NotFound("Payment not found".to_string()))?;
-
-    // Validate payment status
-    if payment.payment_status != "pending" {
-        return Err(AppError::BadRequest(
-            "Payment is not in pending status".to_string(),
-        ));
-    }
-
-    // Update payment with proof
-    let amount = Decimal::from_f64(body.amount)
-        .ok_or_else(|| AppError::ValidationError("Invalid amount".to_string()))?;
     let updated = state
-        .importer_payment_repo
-        .update_payment(payment_id, amount, &body.tx_hash)
+        .payment_service
+        .submit_importer_payment(payment_id, &body, idempotency_key)
         .await?;
 
     Ok(HttpResponse::Ok().json(ApiResponse::success(
@@ -59,3 +62,38 @@ pub async fn pay(
         "Payment submitted successfully",
     )))
 }
+
+/// POST /api/v1/public/importer/access-link
+/// Email the buyer a one-time link to all of their payments. The response is the
+/// same whether or not the email has payments.
+pub async fn request_access_link(
+    state: web::Data<AppState>,
+    body: web::Json<BuyerAccessRequest>,
+) -> AppResult<HttpResponse> {
+    state
+        .payment_service
+        .request_buyer_access(&body.email)
+        .await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::<()>::success_message(
+        "If this email has invoice payments, an access link has been sent to it",
+    )))
+}
+
+/// GET /api/v1/public/importer/payments?email=..&token=..
+/// Every payment owed by the buyer email, using the token from the emailed link
+pub async fn list_buyer_payments(
+    state: web::Data<AppState>,
+    query: web::Query<BuyerPaymentsQuery>,
+) -> AppResult<HttpResponse> {
+    let payments = state
+        .payment_service
+        .list_buyer_payments(&query.email, &query.token)
+        .await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(payments, "Buyer payments retrieved")))
+}
+
+#[derive(serde::Deserialize)]
+pub struct BuyerPaymentsQuery {
+    pub email: String,
+    pub token: String,
+}
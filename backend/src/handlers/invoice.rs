@@ -1,14 +1,23 @@
 use actix_multipart::Multipart;
+use actix_web::http::header::{
+    Charset, ContentDisposition, DispositionParam, DispositionType, ExtendedValue,
+};
 use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
 use futures_util::StreamExt;
 use uuid::Uuid;
 
 use super::AppState;
-use crate::error::{AppError, AppResult};
+use crate::error::{AppError, AppResult, ErrorResponse};
 use crate::models::{
-    AdminReviewInvoiceRequest, CreateInvoiceFundingRequest, RepeatBuyerCheckRequest,
+    AdminReviewInvoiceRequest, CreateInvoiceFundingRequest, FundableInvoiceFilter,
+    RepeatBuyerCheckRequest, Role,
+};
+use crate::utils::{
+    conditional_json, ApiResponse, ApiResponseBody, Claims, Cursor, DocumentIntegrityApiResponse,
+    GradeSuggestionApiResponse, InvoiceApiResponse, InvoiceDocumentApiResponse,
+    InvoiceDocumentListApiResponse, InvoiceListApiResponse, InvoiceNftApiResponse, Masked,
+    RepeatBuyerCheckApiResponse, ValidatedJson,
 };
-use crate::utils::{ApiResponse, Claims};
 
 fn get_user_id(req: &HttpRequest) -> AppResult<Uuid> {
     req.extensions()
@@ -18,10 +27,22 @@ fn get_user_id(req: &HttpRequest) -> AppResult<Uuid> {
 }
 
 /// POST /api/v1/invoices - Create a simple invoice (uses funding request flow)
+#[utoipa::path(
+    post,
+    path = "/api/v1/invoices",
+    tag = "invoices",
+    request_body = CreateInvoiceFundingRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 201, description = "Invoice created", body = InvoiceApiResponse),
+        (status = 400, description = "Invalid invoice data", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse)
+    )
+)]
 pub async fn create(
     state: web::Data<AppState>,
     req: HttpRequest,
-    body: web::Json<CreateInvoiceFundingRequest>,
+    body: ValidatedJson<CreateInvoiceFundingRequest>,
 ) -> AppResult<HttpResponse> {
     let user_id = get_user_id(&req)?;
     let invoice = state
@@ -35,10 +56,22 @@ pub async fn create(
 }
 
 /// POST /api/v1/invoices/funding-request
+#[utoipa::path(
+    post,
+    path = "/api/v1/invoices/funding-request",
+    tag = "invoices",
+    request_body = CreateInvoiceFundingRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 201, description = "Funding request created", body = InvoiceApiResponse),
+        (status = 400, description = "Invalid invoice data", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse)
+    )
+)]
 pub async fn create_funding_request(
     state: web::Data<AppState>,
     req: HttpRequest,
-    body: web::Json<CreateInvoiceFundingRequest>,
+    body: ValidatedJson<CreateInvoiceFundingRequest>,
 ) -> AppResult<HttpResponse> {
     let user_id = get_user_id(&req)?;
     let invoice = state
@@ -52,6 +85,17 @@ pub async fn create_funding_request(
 }
 
 /// POST /api/v1/invoices/check-repeat-buyer
+#[utoipa::path(
+    post,
+    path = "/api/v1/invoices/check-repeat-buyer",
+    tag = "invoices",
+    request_body = RepeatBuyerCheckRequest,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Repeat buyer check result", body = RepeatBuyerCheckApiResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse)
+    )
+)]
 pub async fn check_repeat_buyer(
     state: web::Data<AppState>,
     req: HttpRequest,
@@ -66,47 +110,94 @@ pub async fn check_repeat_buyer(
 }
 
 /// GET /api/v1/invoices
+#[utoipa::path(
+    get,
+    path = "/api/v1/invoices",
+    tag = "invoices",
+    params(InvoiceListQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Paginated invoices of the current mitra", body = InvoiceListApiResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse)
+    )
+)]
 pub async fn list(
     state: web::Data<AppState>,
     req: HttpRequest,
     query: web::Query<InvoiceListQuery>,
 ) -> AppResult<HttpResponse> {
     let user_id = get_user_id(&req)?;
+    let per_page = query.per_page.unwrap_or(10);
+    let cursor = query.cursor.as_deref().map(Cursor::decode).transpose()?;
     let (invoices, total) = state
         .invoice_service
         .list_by_exporter(
             user_id,
             query.page.unwrap_or(1),
-            query.per_page.unwrap_or(10),
+            per_page,
             query.status.clone(),
+            false,
+            cursor,
         )
         .await?;
-    Ok(HttpResponse::Ok().json(ApiResponse::paginated(
-        invoices,
-        total,
-        query.page.unwrap_or(1),
-        query.per_page.unwrap_or(10),
-    )))
+    let next_cursor = Cursor::after_page(&invoices, per_page, |i| (i.created_at, i.id));
+    Ok(HttpResponse::Ok().json(
+        ApiResponse::paginated(invoices, total, query.page.unwrap_or(1), per_page)
+            .with_next_cursor(next_cursor),
+    ))
 }
 
 /// GET /api/v1/invoices/fundable
+#[utoipa::path(
+    get,
+    path = "/api/v1/invoices/fundable",
+    tag = "invoices",
+    params(FundableQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Paginated fundable invoices", body = InvoiceListApiResponse),
+        (status = 400, description = "Invalid filter", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse)
+    )
+)]
 pub async fn list_fundable(
     state: web::Data<AppState>,
-    query: web::Query<PaginationQuery>,
+    query: web::Query<FundableQuery>,
 ) -> AppResult<HttpResponse> {
+    let query = query.into_inner();
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query.per_page.unwrap_or(10).clamp(1, 100);
+    let filter = FundableInvoiceFilter {
+        grade: query.grade,
+        country_tier: query.country_tier,
+        min_yield: query.min_yield,
+        min_days_remaining: query.min_days_remaining,
+        max_days_remaining: query.max_days_remaining,
+    };
+
     let (invoices, total) = state
         .invoice_service
-        .list_fundable(query.page.unwrap_or(1), query.per_page.unwrap_or(10))
+        .list_fundable(&filter, page, per_page)
         .await?;
-    Ok(HttpResponse::Ok().json(ApiResponse::paginated(
-        invoices,
-        total,
-        query.page.unwrap_or(1),
-        query.per_page.unwrap_or(10),
-    )))
+    // Any signed-in user can browse fundable invoices, so buyer and exporter details are masked
+    Ok(HttpResponse::Ok().json(
+        ApiResponse::paginated(Masked(invoices), total, page, per_page).with_filters(&filter),
+    ))
 }
 
 /// GET /api/v1/invoices/{id}
+#[utoipa::path(
+    get,
+    path = "/api/v1/invoices/{id}",
+    tag = "invoices",
+    params(("id" = Uuid, Path, description = "Invoice ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Invoice", body = InvoiceApiResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 404, description = "Invoice not found", body = ErrorResponse)
+    )
+)]
 pub async fn get(
     state: web::Data<AppState>,
     _req: HttpRequest,
@@ -121,20 +212,80 @@ pub async fn get(
 }
 
 /// GET /api/v1/invoices/{id}/detail
+#[utoipa::path(
+    get,
+    path = "/api/v1/invoices/{id}/detail",
+    tag = "invoices",
+    params(("id" = Uuid, Path, description = "Invoice ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Invoice with documents", body = InvoiceApiResponse),
+        (status = 304, description = "Unchanged since the ETag sent in If-None-Match"),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 404, description = "Invoice not found", body = ErrorResponse)
+    )
+)]
 pub async fn get_detail(
     state: web::Data<AppState>,
-    _req: HttpRequest,
+    req: HttpRequest,
     path: web::Path<Uuid>,
 ) -> AppResult<HttpResponse> {
     let invoice_id = path.into_inner();
     let invoice = state.invoice_service.get_invoice_detail(invoice_id).await?;
-    Ok(HttpResponse::Ok().json(ApiResponse::success(
-        invoice,
-        "Invoice detail retrieved successfully",
-    )))
+
+    // Documents and the NFT are part of the detail, so their versions feed the ETag too
+    let mut versions = vec![invoice.updated_at];
+    versions.extend(invoice.documents.iter().flatten().map(|d| d.uploaded_at));
+    versions.extend(invoice.nft.as_ref().map(|nft| nft.updated_at));
+
+    Ok(conditional_json(
+        &req,
+        &versions,
+        ApiResponse::success(invoice, "Invoice detail retrieved successfully"),
+    ))
+}
+
+/// GET /api/v1/invoices/{id}/nft
+#[utoipa::path(
+    get,
+    path = "/api/v1/invoices/{id}/nft",
+    tag = "invoices",
+    params(("id" = Uuid, Path, description = "Invoice ID"), InvoiceNftQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Invoice NFT, `minted: false` before minting", body = InvoiceNftApiResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 404, description = "Invoice not found", body = ErrorResponse)
+    )
+)]
+pub async fn get_nft(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    query: web::Query<InvoiceNftQuery>,
+) -> AppResult<HttpResponse> {
+    let view = state
+        .funding_service
+        .get_invoice_nft(path.into_inner(), query.verify.unwrap_or(false))
+        .await?;
+    let message = if view.minted {
+        "Invoice NFT retrieved"
+    } else {
+        "Invoice NFT not minted yet"
+    };
+    Ok(HttpResponse::Ok().json(ApiResponse::success(view, message)))
 }
 
 /// PUT /api/v1/invoices/{id} - Not implemented (invoices are immutable after creation)
+#[utoipa::path(
+    put,
+    path = "/api/v1/invoices/{id}",
+    tag = "invoices",
+    params(("id" = Uuid, Path, description = "Invoice ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 400, description = "Invoices are immutable", body = ErrorResponse)
+    )
+)]
 pub async fn update(
     _state: web::Data<AppState>,
     _req: HttpRequest,
@@ -145,18 +296,51 @@ pub async fn update(
     ))
 }
 
-/// DELETE /api/v1/invoices/{id} - Not implemented (invoices are immutable)
+/// DELETE /api/v1/invoices/{id} - Soft delete (pools and investments are kept for audit)
+#[utoipa::path(
+    delete,
+    path = "/api/v1/invoices/{id}",
+    tag = "invoices",
+    params(("id" = Uuid, Path, description = "Invoice ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Invoice soft-deleted", body = InvoiceApiResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Not the invoice owner", body = ErrorResponse),
+        (status = 404, description = "Invoice not found", body = ErrorResponse),
+        (status = 409, description = "Invoice is not draft or rejected, or has an active pool", body = ErrorResponse)
+    )
+)]
 pub async fn delete(
-    _state: web::Data<AppState>,
-    _req: HttpRequest,
-    _path: web::Path<Uuid>,
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
 ) -> AppResult<HttpResponse> {
-    Err(AppError::BadRequest(
-        "Invoice deletion is not supported.".to_string(),
-    ))
+    let user_id = get_user_id(&req)?;
+    let invoice = state
+        .invoice_service
+        .delete_invoice(user_id, path.into_inner())
+        .await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        invoice,
+        "Invoice deleted successfully",
+    )))
 }
 
 /// POST /api/v1/invoices/{id}/submit - Submit for review (not implemented)
+#[utoipa::path(
+    post,
+    path = "/api/v1/invoices/{id}/submit",
+    tag = "invoices",
+    params(("id" = Uuid, Path, description = "Invoice ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Invoice submitted for review", body = InvoiceApiResponse),
+        (status = 400, description = "Invoice is not a draft", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 404, description = "Invoice not found", body = ErrorResponse)
+    )
+)]
 pub async fn submit(
     state: web::Data<AppState>,
     _req: HttpRequest,
@@ -172,6 +356,19 @@ pub async fn submit(
 }
 
 /// POST /api/v1/invoices/{id}/documents
+#[utoipa::path(
+    post,
+    path = "/api/v1/invoices/{id}/documents",
+    tag = "invoices",
+    request_body(content = String, content_type = "multipart/form-data", description = "Fields `file` and `document_type`"),
+    params(("id" = Uuid, Path, description = "Invoice ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 201, description = "Document uploaded", body = InvoiceDocumentApiResponse),
+        (status = 400, description = "Missing file or document type", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse)
+    )
+)]
 pub async fn upload_document(
     state: web::Data<AppState>,
     req: HttpRequest,
@@ -232,6 +429,17 @@ pub async fn upload_document(
 }
 
 /// GET /api/v1/invoices/{id}/documents
+#[utoipa::path(
+    get,
+    path = "/api/v1/invoices/{id}/documents",
+    tag = "invoices",
+    params(("id" = Uuid, Path, description = "Invoice ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Invoice documents", body = InvoiceDocumentListApiResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse)
+    )
+)]
 pub async fn get_documents(
     state: web::Data<AppState>,
     path: web::Path<Uuid>,
@@ -244,7 +452,84 @@ pub async fn get_documents(
     )))
 }
 
+/// GET /api/v1/invoices/{id}/documents/{doc_id}/download
+#[utoipa::path(
+    get,
+    path = "/api/v1/invoices/{id}/documents/{doc_id}/download",
+    tag = "invoices",
+    params(
+        ("id" = Uuid, Path, description = "Invoice ID"),
+        ("doc_id" = Uuid, Path, description = "Document ID")
+    ),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Document file", content_type = "application/octet-stream", body = Vec<u8>),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Not the invoice owner or an admin", body = ErrorResponse),
+        (status = 404, description = "Invoice or document not found", body = ErrorResponse)
+    )
+)]
+pub async fn download_document(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<(Uuid, Uuid)>,
+) -> AppResult<HttpResponse> {
+    let (user_id, is_admin) = req
+        .extensions()
+        .get::<Claims>()
+        .map(|c| (c.user_id(), c.role == Role::Admin))
+        .ok_or_else(|| AppError::Unauthorized("User not authenticated".to_string()))?;
+    let (invoice_id, document_id) = path.into_inner();
+
+    let (document, data) = state
+        .invoice_service
+        .download_document(user_id, is_admin, invoice_id, document_id)
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(document.content_type())
+        .insert_header(attachment_disposition(&document.file_name))
+        .body(data))
+}
+
+/// `attachment` disposition for an uploaded file name: a printable ASCII
+/// `filename` for old clients plus the exact name as UTF-8 in `filename*`
+pub fn attachment_disposition(file_name: &str) -> ContentDisposition {
+    let ascii_name: String = file_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii() && !c.is_ascii_control() {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    ContentDisposition {
+        disposition: DispositionType::Attachment,
+        parameters: vec![
+            DispositionParam::Filename(ascii_name),
+            DispositionParam::FilenameExt(ExtendedValue {
+                charset: Charset::Ext("UTF-8".to_string()),
+                language_tag: None,
+                value: file_name.as_bytes().to_vec(),
+            }),
+        ],
+    }
+}
+
 /// POST /api/v1/invoices/{id}/tokenize - Not implemented yet
+#[utoipa::path(
+    post,
+    path = "/api/v1/invoices/{id}/tokenize",
+    tag = "invoices",
+    params(("id" = Uuid, Path, description = "Invoice ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 400, description = "Tokenization happens on approval", body = ErrorResponse)
+    )
+)]
 pub async fn tokenize(
     _state: web::Data<AppState>,
     _req: HttpRequest,
@@ -258,6 +543,18 @@ pub async fn tokenize(
 // ============ Admin Invoice Endpoints ============
 
 /// GET /api/v1/admin/invoices/pending
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/invoices/pending",
+    tag = "invoices",
+    params(PaginationQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Paginated invoices awaiting review", body = InvoiceListApiResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Admin only", body = ErrorResponse)
+    )
+)]
 pub async fn get_pending_invoices(
     state: web::Data<AppState>,
     query: web::Query<PaginationQuery>,
@@ -275,6 +572,18 @@ pub async fn get_pending_invoices(
 }
 
 /// GET /api/v1/admin/invoices/approved
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/invoices/approved",
+    tag = "invoices",
+    params(PaginationQuery),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Paginated approved invoices", body = InvoiceListApiResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Admin only", body = ErrorResponse)
+    )
+)]
 pub async fn get_approved_invoices(
     state: web::Data<AppState>,
     query: web::Query<PaginationQuery>,
@@ -291,7 +600,40 @@ pub async fn get_approved_invoices(
     )))
 }
 
+/// GET /api/v1/admin/invoices/default-eligible
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/invoices/default-eligible",
+    tag = "invoices",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Matured invoices past the default grace period", body = InvoiceListApiResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Admin only", body = ErrorResponse)
+    )
+)]
+pub async fn get_default_eligible_invoices(state: web::Data<AppState>) -> AppResult<HttpResponse> {
+    let invoices = state.funding_service.default_eligible_invoices().await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        invoices,
+        "Default-eligible invoices retrieved successfully",
+    )))
+}
+
 /// GET /api/v1/admin/invoices/{id}/grade-suggestion
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/invoices/{id}/grade-suggestion",
+    tag = "invoices",
+    params(("id" = Uuid, Path, description = "Invoice ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Suggested grade", body = GradeSuggestionApiResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Admin only", body = ErrorResponse),
+        (status = 404, description = "Invoice not found", body = ErrorResponse)
+    )
+)]
 pub async fn get_grade_suggestion(
     state: web::Data<AppState>,
     path: web::Path<Uuid>,
@@ -308,6 +650,19 @@ pub async fn get_grade_suggestion(
 }
 
 /// GET /api/v1/admin/invoices/{id}/review
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/invoices/{id}/review",
+    tag = "invoices",
+    params(("id" = Uuid, Path, description = "Invoice ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Invoice, grade suggestion and documents", body = ApiResponseBody),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Admin only", body = ErrorResponse),
+        (status = 404, description = "Invoice not found", body = ErrorResponse)
+    )
+)]
 pub async fn get_invoice_review_data(
     state: web::Data<AppState>,
     path: web::Path<Uuid>,
@@ -331,7 +686,52 @@ pub async fn get_invoice_review_data(
     )))
 }
 
+/// GET /api/v1/admin/invoices/{id}/documents/verify
+/// Re-download each document from IPFS and compare it with the hash recorded at upload
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/invoices/{id}/documents/verify",
+    tag = "invoices",
+    params(("id" = Uuid, Path, description = "Invoice ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Per-document integrity report", body = DocumentIntegrityApiResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Admin only", body = ErrorResponse),
+        (status = 404, description = "Invoice not found", body = ErrorResponse)
+    )
+)]
+pub async fn verify_documents(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> AppResult<HttpResponse> {
+    let invoice_id = path.into_inner();
+    let report = state.invoice_service.verify_documents(invoice_id).await?;
+
+    let message = if report.all_verified {
+        "All documents match their recorded hashes"
+    } else {
+        "Some documents failed verification"
+    };
+    Ok(HttpResponse::Ok().json(ApiResponse::success(report, message)))
+}
+
 /// POST /api/v1/admin/invoices/{id}/approve
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/invoices/{id}/approve",
+    tag = "invoices",
+    request_body = AdminReviewInvoiceRequest,
+    params(("id" = Uuid, Path, description = "Invoice ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Invoice approved and tokenized", body = InvoiceApiResponse),
+        (status = 400, description = "Invoice cannot be approved", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Admin only", body = ErrorResponse),
+        (status = 404, description = "Invoice not found", body = ErrorResponse)
+    )
+)]
 pub async fn approve(
     state: web::Data<AppState>,
     _req: HttpRequest,
@@ -341,78 +741,21 @@ pub async fn approve(
     let invoice_id = path.into_inner();
     let data = body.into_inner();
 
-    let invoice = state
-        .invoice_service
-        .approve(
-            invoice_id,
-            data.grade.as_deref().unwrap_or("B"),
-            data.priority_interest_rate,
-            data.catalyst_interest_rate,
-        )
-        .await?;
-
-    // 2. Mint NFT
-    // Generate metadata URI
-    let metadata_uri = state
-        .blockchain_service
-        .create_nft_metadata(invoice.id)
-        .await?;
-
-    // Mint on chain
-    let mint_result = state
-        .blockchain_service
-        .mint_invoice_nft(&invoice, &metadata_uri)
-        .await;
-
-    let (token_id, tx_hash, contract_addr) = match mint_result {
-        Ok(res) => res,
-        Err(e) => {
-            // Revert status to pending_review if minting fails
-            tracing::error!("Minting failed, reverting invoice status: {}", e);
-            let _ = state
-                .invoice_repo
-                .update_status(invoice.id, "pending_review")
-                .await
-                .map_err(|re| tracing::error!("Failed to revert invoice status: {}", re));
-            
-            return Err(e);
-        }
-    };
+    let approve = state.invoice_service.approve(
+        invoice_id,
+        data.grade.as_deref().unwrap_or("B"),
+        data.priority_interest_rate,
+        data.catalyst_interest_rate,
+    );
 
-    // Create NFT record in DB
+    // Grade, mint the NFT and open the funding pool under the invoice's approval
+    // lock. A failed mint leaves the invoice approved for
+    // POST /admin/invoices/{id}/retry-tokenize
     state
-        .invoice_repo
-        .create_nft(
-            invoice.id,
-            token_id,
-            &contract_addr,
-            state.config.chain_id as i32,
-            invoice.exporter_wallet_address.as_deref().unwrap_or(""), // Should valid if mint succeeded
-            &tx_hash,
-            &metadata_uri,
-        )
-        .await?;
-
-    // Update status to tokenized
-    let invoice = state
-        .invoice_repo
-        .update_status(invoice.id, "tokenized")
-        .await?;
-
-    // 3. Verify Shipment on-chain (Required prerequisite for creating a pool)
-    let _verify_tx = state
-        .blockchain_service
-        .verify_shipment_on_chain(token_id)
-        .await?;
-
-    // 4. Create Pool on-chain
-    let _pool_tx = state
-        .blockchain_service
-        .create_pool_on_chain(token_id)
+        .funding_service
+        .approve_and_tokenize(invoice_id, approve)
         .await?;
-
-    // 5. Create Funding Pool (DB) - This also updates invoice status to "funding"
-    let _pool = state.funding_service.create_pool(invoice.id).await?;
+    let invoice = state.invoice_service.get_invoice(invoice_id).await?;
 
     Ok(HttpResponse::Ok().json(ApiResponse::success(
         invoice,
@@ -420,7 +763,53 @@ pub async fn approve(
     )))
 }
 
+/// POST /api/v1/admin/invoices/{id}/retry-tokenize
+/// Retry minting and pool creation for an approved invoice; a no-op once tokenized
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/invoices/{id}/retry-tokenize",
+    tag = "invoices",
+    params(("id" = Uuid, Path, description = "Invoice ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Invoice tokenized, or already tokenized", body = InvoiceApiResponse),
+        (status = 400, description = "Invoice is not approved", body = ErrorResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Admin only", body = ErrorResponse),
+        (status = 404, description = "Invoice not found", body = ErrorResponse)
+    )
+)]
+pub async fn retry_tokenize(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> AppResult<HttpResponse> {
+    let invoice_id = path.into_inner();
+    let created = state.funding_service.retry_tokenize(invoice_id).await?;
+    let invoice = state.invoice_service.get_invoice(invoice_id).await?;
+
+    let message = if created.is_some() {
+        "Invoice tokenized and funding pool created on-chain"
+    } else {
+        "Invoice is already tokenized"
+    };
+    Ok(HttpResponse::Ok().json(ApiResponse::success(invoice, message)))
+}
+
 /// POST /api/v1/admin/invoices/{id}/reject
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/invoices/{id}/reject",
+    tag = "invoices",
+    request_body = RejectRequest,
+    params(("id" = Uuid, Path, description = "Invoice ID")),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Invoice rejected", body = InvoiceApiResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Admin only", body = ErrorResponse),
+        (status = 404, description = "Invoice not found", body = ErrorResponse)
+    )
+)]
 pub async fn reject(
     state: web::Data<AppState>,
     _req: HttpRequest,
@@ -436,44 +825,90 @@ pub async fn reject(
 }
 
 /// GET /api/v1/admin/users/{id}/invoices
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/users/{id}/invoices",
+    tag = "invoices",
+    params(
+        ("id" = Uuid, Path, description = "User ID"),
+        InvoiceListQuery,
+    ),
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Paginated invoices of a mitra, including deleted", body = InvoiceListApiResponse),
+        (status = 401, description = "Not authenticated", body = ErrorResponse),
+        (status = 403, description = "Admin only", body = ErrorResponse)
+    )
+)]
 pub async fn get_exporter_invoices(
     state: web::Data<AppState>,
     path: web::Path<Uuid>,
     query: web::Query<InvoiceListQuery>,
 ) -> AppResult<HttpResponse> {
     let user_id = path.into_inner();
+    let per_page = query.per_page.unwrap_or(10);
+    let cursor = query.cursor.as_deref().map(Cursor::decode).transpose()?;
     let (invoices, total) = state
         .invoice_service
         .list_by_exporter(
             user_id,
             query.page.unwrap_or(1),
-            query.per_page.unwrap_or(10),
+            per_page,
             query.status.clone(),
+            true,
+            cursor,
         )
         .await?;
-    Ok(HttpResponse::Ok().json(ApiResponse::paginated(
-        invoices,
-        total,
-        query.page.unwrap_or(1),
-        query.per_page.unwrap_or(10),
-    )))
+    let next_cursor = Cursor::after_page(&invoices, per_page, |i| (i.created_at, i.id));
+    Ok(HttpResponse::Ok().json(
+        ApiResponse::paginated(invoices, total, query.page.unwrap_or(1), per_page)
+            .with_next_cursor(next_cursor),
+    ))
 }
 
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
 #[allow(dead_code)] // Fields used for query deserialization
 pub struct InvoiceListQuery {
     pub status: Option<String>,
     pub page: Option<i32>,
     pub per_page: Option<i32>,
+    /// `next_cursor` of the previous page; takes the place of `page`
+    pub cursor: Option<String>,
+}
+
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct InvoiceNftQuery {
+    /// Also read the owner from the contract (`ownerOf`) and compare it
+    pub verify: Option<bool>,
 }
 
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct PaginationQuery {
     pub page: Option<i32>,
     pub per_page: Option<i32>,
 }
 
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FundableQuery {
+    pub page: Option<i32>,
+    pub per_page: Option<i32>,
+    /// Invoice grade, e.g. `A`
+    pub grade: Option<String>,
+    /// Buyer country tier (1-3)
+    pub country_tier: Option<i32>,
+    /// Minimum annual rate (%) of the better-paying tranche
+    pub min_yield: Option<rust_decimal::Decimal>,
+    /// Minimum days until the invoice is due
+    pub min_days_remaining: Option<i32>,
+    /// Maximum days until the invoice is due
+    pub max_days_remaining: Option<i32>,
+}
+
+#[derive(serde::Deserialize, utoipa::ToSchema)]
 pub struct RejectRequest {
     pub reason: String,
 }
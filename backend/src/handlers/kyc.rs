@@ -0,0 +1,103 @@
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use uuid::Uuid;
+
+use super::AppState;
+use crate::error::{AppError, AppResult};
+use crate::models::KycSubmitRequest;
+use crate::utils::{validate_request, ApiResponse, Claims};
+
+fn get_user_id(req: &HttpRequest) -> AppResult<Uuid> {
+    req.extensions()
+        .get::<Claims>()
+        .map(|c| c.user_id())
+        .ok_or_else(|| AppError::Unauthorized("User not authenticated".to_string()))
+}
+
+/// POST /api/v1/user/kyc
+pub async fn submit(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    body: web::Json<KycSubmitRequest>,
+) -> AppResult<HttpResponse> {
+    let user_id = get_user_id(&req)?;
+    validate_request(&*body)?;
+    let verification = state.kyc_service.submit(user_id, body.into_inner()).await?;
+    Ok(HttpResponse::Created().json(ApiResponse::success(
+        verification,
+        "KYC verification submitted successfully",
+    )))
+}
+
+/// GET /api/v1/user/kyc/status
+pub async fn get_status(state: web::Data<AppState>, req: HttpRequest) -> AppResult<HttpResponse> {
+    let user_id = get_user_id(&req)?;
+    let status = state.kyc_service.get_status(user_id).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        status,
+        "KYC status retrieved successfully",
+    )))
+}
+
+// ============ Admin KYC Endpoints ============
+
+/// GET /api/v1/admin/kyc/pending
+pub async fn get_pending(
+    state: web::Data<AppState>,
+    query: web::Query<PaginationQuery>,
+) -> AppResult<HttpResponse> {
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query.per_page.unwrap_or(10).clamp(1, 100);
+    let (verifications, total) = state.kyc_service.get_pending(page, per_page).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::paginated(
+        verifications,
+        total,
+        page,
+        per_page,
+    )))
+}
+
+/// POST /api/v1/admin/kyc/{id}/approve
+pub async fn approve(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+) -> AppResult<HttpResponse> {
+    let admin_id = get_user_id(&req)?;
+    let verification = state
+        .kyc_service
+        .approve(path.into_inner(), admin_id)
+        .await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        verification,
+        "KYC verification approved",
+    )))
+}
+
+/// POST /api/v1/admin/kyc/{id}/reject
+pub async fn reject(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    body: web::Json<RejectRequest>,
+) -> AppResult<HttpResponse> {
+    let admin_id = get_user_id(&req)?;
+    let verification = state
+        .kyc_service
+        .reject(path.into_inner(), admin_id, &body.reason)
+        .await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        verification,
+        "KYC verification rejected",
+    )))
+}
+
+#[derive(serde::Deserialize)]
+pub struct PaginationQuery {
+    pub page: Option<i32>,
+    pub per_page: Option<i32>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct RejectRequest {
+    pub reason: String,
+}
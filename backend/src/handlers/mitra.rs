@@ -122,20 +122,17 @@ pub async fn get_active_invoices(
 
 /// GET /api/v1/mitra/pools/{id}/breakdown
 pub async fn get_repayment_breakdown(
-    _state: web::Data<AppState>,
+    state: web::Data<AppState>,
+    req: HttpRequest,
     path: web::Path<Uuid>,
 ) -> AppResult<HttpResponse> {
-    let pool_id = path.into_inner();
-    // Stub - return basic breakdown
+    let user_id = get_user_id(&req)?;
+    let breakdown = state
+        .mitra_service
+        .get_repayment_breakdown(user_id, path.into_inner())
+        .await?;
     Ok(HttpResponse::Ok().json(ApiResponse::success(
-        serde_json::json!({
-            "pool_id": pool_id,
-            "invoice_number": "INV-001",
-            "principal_amount": 0.0,
-            "total_interest": 0.0,
-            "platform_fee": 0.0,
-            "total_repayment": 0.0
-        }),
+        breakdown,
         "Repayment breakdown retrieved",
     )))
 }
@@ -243,6 +240,23 @@ pub async fn reject(
     )))
 }
 
+/// POST /api/v1/admin/mitra/{id}/revoke
+/// Suspend an approved mitra; blocks new funding requests, funded invoices are unaffected
+pub async fn revoke(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    body: web::Json<RejectRequest>,
+) -> AppResult<HttpResponse> {
+    let admin_id = get_user_id(&req)?;
+    let application_id = path.into_inner();
+    let application = state
+        .mitra_service
+        .revoke(application_id, admin_id, &body.reason)
+        .await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(application, "Mitra status revoked")))
+}
+
 #[derive(serde::Deserialize)]
 pub struct PaginationQuery {
     pub page: Option<i32>,
@@ -5,19 +5,21 @@ pub mod currency;
 pub mod funding;
 pub mod importer;
 pub mod invoice;
+pub mod kyc;
 pub mod mitra;
 pub mod payment;
 pub mod risk_questionnaire;
 pub mod user;
 
-use actix_web::HttpResponse;
+use actix_web::{web, HttpResponse};
 use sqlx::PgPool;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 use crate::config::Config;
 use crate::repository::*;
 use crate::services::*;
-use crate::utils::{ApiResponse, JwtManager};
+use crate::utils::{ApiResponse, JwtManager, Metrics};
 
 /// Application state shared across all handlers
 #[allow(dead_code)] // Fields accessed by various handlers via web::Data
@@ -42,6 +44,7 @@ pub struct AppState {
     pub otp_service: Arc<OtpService>,
     pub mitra_service: Arc<MitraService>,
     pub invoice_service: Arc<InvoiceService>,
+    pub kyc_service: Arc<KycService>,
     pub funding_service: Arc<FundingService>,
     pub payment_service: Arc<PaymentService>,
     pub rq_service: Arc<RiskQuestionnaireService>,
@@ -50,6 +53,12 @@ pub struct AppState {
     pub pinata_service: Arc<PinataService>,
     pub email_service: Arc<EmailService>,
     pub escrow_service: Arc<EscrowService>,
+    pub activity_logger: Arc<ActivityLogger>,
+    pub notification_service: Arc<NotificationService>,
+    pub user_service: Arc<UserService>,
+
+    // Observability
+    pub metrics: Arc<Metrics>,
 }
 
 /// Health check endpoint
@@ -64,3 +73,70 @@ pub async fn health_check() -> HttpResponse {
         "Service is healthy",
     ))
 }
+
+/// Readiness gate for `/readyz`: startup must have finished (migrations run,
+/// startup checks passed and the listener bound) and the database must answer
+/// before the instance takes traffic
+pub struct Readiness {
+    db_pool: PgPool,
+    started: AtomicBool,
+}
+
+impl Readiness {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self {
+            db_pool,
+            started: AtomicBool::new(false),
+        }
+    }
+
+    pub fn mark_started(&self) {
+        self.started.store(true, Ordering::SeqCst);
+    }
+
+    pub fn started(&self) -> bool {
+        self.started.load(Ordering::SeqCst)
+    }
+}
+
+/// GET /livez
+/// Liveness probe: 200 whenever the process is serving requests
+pub async fn livez() -> HttpResponse {
+    HttpResponse::Ok().json(ApiResponse::success(
+        serde_json::json!({ "status": "alive" }),
+        "Service is alive",
+    ))
+}
+
+/// GET /readyz
+/// Readiness probe: 503 until startup is done and the database is reachable
+pub async fn readyz(readiness: web::Data<Readiness>) -> HttpResponse {
+    if !readiness.started() {
+        return HttpResponse::ServiceUnavailable()
+            .json(ApiResponse::<()>::error("Startup has not completed"));
+    }
+
+    if let Err(e) = sqlx::query("SELECT 1").execute(&readiness.db_pool).await {
+        tracing::warn!("Readiness check failed, database unreachable: {}", e);
+        return HttpResponse::ServiceUnavailable()
+            .json(ApiResponse::<()>::error("Database is unreachable"));
+    }
+
+    HttpResponse::Ok().json(ApiResponse::success(
+        serde_json::json!({
+            "status": "ready",
+            "startup": "complete",
+            "database": "ok"
+        }),
+        "Service is ready",
+    ))
+}
+
+/// GET /metrics
+/// Prometheus scrape endpoint. Unauthenticated; set `METRICS_PORT` to serve it
+/// on a separate port that is not exposed publicly.
+pub async fn metrics(metrics: web::Data<Metrics>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4; charset=utf-8")
+        .body(metrics.render())
+}
@@ -3,6 +3,7 @@ use uuid::Uuid;
 
 use super::AppState;
 use crate::error::{AppError, AppResult};
+use crate::models::PlatformRevenueQuery;
 use crate::utils::{ApiResponse, Claims};
 
 fn get_user_id(req: &HttpRequest) -> AppResult<Uuid> {
@@ -12,11 +13,23 @@ fn get_user_id(req: &HttpRequest) -> AppResult<Uuid> {
         .ok_or_else(|| AppError::Unauthorized("User not authenticated".to_string()))
 }
 
-/// GET /api/v1/admin/platform/revenue
-pub async fn get_platform_revenue(state: web::Data<AppState>) -> AppResult<HttpResponse> {
-    let revenue = state.payment_service.get_platform_revenue().await?;
+/// GET /api/v1/admin/platform/revenue?from=YYYY-MM-DD&to=YYYY-MM-DD&granularity=month
+pub async fn get_platform_revenue(
+    state: web::Data<AppState>,
+    query: web::Query<PlatformRevenueQuery>,
+) -> AppResult<HttpResponse> {
+    let revenue = state.payment_service.get_platform_revenue(&query).await?;
     Ok(HttpResponse::Ok().json(ApiResponse::success(
-        serde_json::json!({ "revenue": revenue, "currency": "IDRX" }),
+        revenue,
         "Platform revenue retrieved",
     )))
 }
+
+/// GET /api/v1/admin/platform/stats
+pub async fn get_platform_stats(state: web::Data<AppState>) -> AppResult<HttpResponse> {
+    let gas = state.payment_service.get_platform_gas_stats().await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        serde_json::json!({ "gas": gas }),
+        "Platform stats retrieved",
+    )))
+}
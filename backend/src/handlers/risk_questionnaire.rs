@@ -14,15 +14,28 @@ fn get_user_id(req: &HttpRequest) -> AppResult<Uuid> {
 }
 
 /// GET /api/v1/risk-questionnaire/questions
+/// The active version of the question set
 pub async fn get_questions(state: web::Data<AppState>) -> AppResult<HttpResponse> {
-    let questions = state.rq_service.get_questions();
+    let questions = state.rq_service.get_questions().await?;
     Ok(HttpResponse::Ok().json(ApiResponse::success(
         questions,
         "Questions retrieved successfully",
     )))
 }
 
+/// GET /api/v1/risk-questionnaire
+/// The caller's stored answers, to review before re-taking the questionnaire
+pub async fn get_answers(state: web::Data<AppState>, req: HttpRequest) -> AppResult<HttpResponse> {
+    let user_id = get_user_id(&req)?;
+    let review = state.rq_service.get_answers(user_id).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        review,
+        "Risk questionnaire answers retrieved successfully",
+    )))
+}
+
 /// POST /api/v1/risk-questionnaire
+/// First submission or re-take; a re-take replaces the earlier answers
 pub async fn submit(
     state: web::Data<AppState>,
     req: HttpRequest,
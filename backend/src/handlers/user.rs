@@ -6,9 +6,11 @@ use uuid::Uuid;
 use super::AppState;
 use crate::error::{AppError, AppResult};
 use crate::models::{
-    ChangePasswordRequest, CompleteProfileRequest, ConnectWalletRequest, UpdateProfileRequest,
+    AddBankAccountRequest, ChangePasswordRequest, CompleteProfileRequest, ConnectWalletRequest,
+    Role, UpdateProfileRequest,
 };
-use crate::utils::{hash_password, verify_password, ApiResponse, Claims};
+use crate::services::ActivityType;
+use crate::utils::{hash_password, validate_request, verify_password, ApiResponse, Claims};
 
 fn get_user_id(req: &HttpRequest) -> AppResult<Uuid> {
     req.extensions()
@@ -48,6 +50,16 @@ pub async fn update_profile(
             data.business_sector.as_deref(),
         )
         .await?;
+    state
+        .user_service
+        .mark_profile_completed_if_complete(user_id)
+        .await?;
+
+    state
+        .activity_logger
+        .log(user_id, ActivityType::ProfileUpdated, "Updated profile details")
+        .await;
+
     Ok(HttpResponse::Ok().json(ApiResponse::success(
         profile,
         "Profile updated successfully",
@@ -69,11 +81,22 @@ pub async fn complete_profile(
         .create_profile(user_id, &data.full_name)
         .await?;
 
-    // Mark profile as completed
-    state.user_repo.set_profile_completed(user_id, true).await?;
+    // Marks the profile completed once every required field is filled
+    let completeness = state
+        .user_service
+        .mark_profile_completed_if_complete(user_id)
+        .await?;
+
+    state
+        .activity_logger
+        .log(user_id, ActivityType::ProfileUpdated, "Completed profile")
+        .await;
 
     Ok(HttpResponse::Ok().json(ApiResponse::success(
-        profile,
+        serde_json::json!({
+            "profile": profile,
+            "completeness": completeness
+        }),
         "Profile completed successfully",
     )))
 }
@@ -84,7 +107,7 @@ pub async fn upload_document(
     req: HttpRequest,
     mut payload: Multipart,
 ) -> AppResult<HttpResponse> {
-    let _user_id = get_user_id(&req)?;
+    let user_id = get_user_id(&req)?;
 
     let mut file_data: Option<Vec<u8>> = None;
     let mut file_name: Option<String> = None;
@@ -128,6 +151,15 @@ pub async fn upload_document(
         .upload_file(file_data, &file_name)
         .await?;
 
+    state
+        .activity_logger
+        .log(
+            user_id,
+            ActivityType::DocumentUploaded,
+            &format!("Uploaded document {}", file_name),
+        )
+        .await;
+
     Ok(HttpResponse::Created().json(ApiResponse::success(
         serde_json::json!({ "url": url }),
         "Document uploaded successfully",
@@ -135,11 +167,13 @@ pub async fn upload_document(
 }
 
 /// GET /api/v1/user/profile/data
+/// Profile fields plus completeness: percentage and the required fields still missing
 pub async fn get_personal_data(
     state: web::Data<AppState>,
     req: HttpRequest,
 ) -> AppResult<HttpResponse> {
     let user_id = get_user_id(&req)?;
+    let completeness = state.user_service.profile_completeness(user_id).await?;
     let user = state
         .user_repo
         .find_by_id(user_id)
@@ -149,7 +183,8 @@ pub async fn get_personal_data(
     Ok(HttpResponse::Ok().json(ApiResponse::success(
         serde_json::json!({
             "user": user,
-            "profile": profile
+            "profile": profile,
+            "completeness": completeness
         }),
         "Personal data retrieved",
     )))
@@ -189,20 +224,26 @@ pub async fn change_password(
     let new_hash = hash_password(&data.new_password)?;
     state.user_repo.update_password(user_id, &new_hash).await?;
 
+    state
+        .activity_logger
+        .log(user_id, ActivityType::PasswordChanged, "Changed password")
+        .await;
+
     Ok(HttpResponse::Ok().json(ApiResponse::<()>::success_message(
         "Password changed successfully",
     )))
 }
 
-/// PUT /api/v1/user/wallet
+/// POST /api/v1/user/wallet/connect (also PUT /api/v1/user/wallet)
 /// Connect wallet with signature verification (supports Base Smart Wallet / passkey via ERC-1271)
-/// Flow: 1) POST /auth/wallet/nonce → 2) Sign message with wallet → 3) PUT /user/wallet
+/// Flow: 1) POST /auth/wallet/nonce → 2) Sign message with wallet → 3) POST /user/wallet/connect
 pub async fn connect_wallet(
     state: web::Data<AppState>,
     req: HttpRequest,
     body: web::Json<ConnectWalletRequest>,
 ) -> AppResult<HttpResponse> {
     let user_id = get_user_id(&req)?;
+    validate_request(&*body)?;
     let user = state
         .auth_service
         .connect_wallet(user_id, body.into_inner())
@@ -210,6 +251,92 @@ pub async fn connect_wallet(
     Ok(HttpResponse::Ok().json(ApiResponse::success(user, "Wallet connected successfully")))
 }
 
+/// PUT /api/v1/user/payout-wallet
+/// Set the wallet investor returns are paid to, proven with a signed nonce like PUT /user/wallet.
+/// The login wallet stays unchanged.
+pub async fn set_payout_wallet(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    body: web::Json<ConnectWalletRequest>,
+) -> AppResult<HttpResponse> {
+    let user_id = get_user_id(&req)?;
+    let user = state
+        .auth_service
+        .set_payout_wallet(user_id, body.into_inner())
+        .await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(user, "Payout wallet verified")))
+}
+
+/// GET /api/v1/user/activity
+/// Authenticated user's recent activity, newest first
+pub async fn get_activity(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    query: web::Query<PaginationQuery>,
+) -> AppResult<HttpResponse> {
+    let user_id = get_user_id(&req)?;
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query.per_page.unwrap_or(20).clamp(1, 100);
+
+    let (activities, total) = state
+        .activity_logger
+        .list_for_user(user_id, page, per_page)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::paginated(activities, total, page, per_page)))
+}
+
+/// GET /api/v1/user/notifications
+/// In-app notifications, newest first. `unread=true` limits to unread ones.
+pub async fn list_notifications(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    query: web::Query<NotificationListQuery>,
+) -> AppResult<HttpResponse> {
+    let user_id = get_user_id(&req)?;
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query.per_page.unwrap_or(20).clamp(1, 100);
+
+    let (notifications, total) = state
+        .notification_service
+        .list_for_user(user_id, query.unread.unwrap_or(false), page, per_page)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::paginated(notifications, total, page, per_page)))
+}
+
+/// POST /api/v1/user/notifications/{id}/read
+pub async fn read_notification(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+) -> AppResult<HttpResponse> {
+    let user_id = get_user_id(&req)?;
+    let notification = state
+        .notification_service
+        .mark_read(user_id, path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        notification,
+        "Notification marked as read",
+    )))
+}
+
+/// POST /api/v1/user/notifications/read-all
+pub async fn read_all_notifications(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+) -> AppResult<HttpResponse> {
+    let user_id = get_user_id(&req)?;
+    let updated = state.notification_service.mark_all_read(user_id).await?;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        serde_json::json!({ "updated": updated }),
+        "All notifications marked as read",
+    )))
+}
+
 /// GET /api/v1/admin/users
 pub async fn list_users(
     state: web::Data<AppState>,
@@ -218,7 +345,7 @@ pub async fn list_users(
     let (users, total) = state
         .user_repo
         .list_users(
-            query.role.as_deref(),
+            query.role,
             query.page.unwrap_or(1),
             query.per_page.unwrap_or(10),
         )
@@ -231,9 +358,82 @@ pub async fn list_users(
     )))
 }
 
+/// GET /api/v1/admin/users/{id}
+pub async fn admin_get_user_detail(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> AppResult<HttpResponse> {
+    let detail = state
+        .user_service
+        .get_admin_user_detail(path.into_inner())
+        .await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        detail,
+        "User detail retrieved successfully",
+    )))
+}
+
+/// GET /api/v1/exporter/bank-accounts
+pub async fn list_bank_accounts(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+) -> AppResult<HttpResponse> {
+    let user_id = get_user_id(&req)?;
+    let accounts = state.user_repo.find_bank_accounts_by_user(user_id).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(
+        accounts,
+        "Bank accounts retrieved successfully",
+    )))
+}
+
+/// POST /api/v1/exporter/bank-accounts
+/// Add a bank account as the primary payout account. Bank disbursements
+/// go to it once an admin has verified it.
+pub async fn add_bank_account(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    body: web::Json<AddBankAccountRequest>,
+) -> AppResult<HttpResponse> {
+    let user_id = get_user_id(&req)?;
+    validate_request(&*body)?;
+    let account = state
+        .user_service
+        .add_bank_account(user_id, body.into_inner())
+        .await?;
+    Ok(HttpResponse::Created().json(ApiResponse::success(
+        account,
+        "Bank account added and awaiting verification",
+    )))
+}
+
+/// POST /api/v1/admin/bank-accounts/{id}/verify
+pub async fn admin_verify_bank_account(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> AppResult<HttpResponse> {
+    let account = state
+        .user_service
+        .verify_bank_account(path.into_inner())
+        .await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::success(account, "Bank account verified")))
+}
+
 #[derive(serde::Deserialize)]
 pub struct UserListQuery {
-    pub role: Option<String>,
+    pub role: Option<Role>,
+    pub page: Option<i32>,
+    pub per_page: Option<i32>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct PaginationQuery {
+    pub page: Option<i32>,
+    pub per_page: Option<i32>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct NotificationListQuery {
+    pub unread: Option<bool>,
     pub page: Option<i32>,
     pub per_page: Option<i32>,
 }
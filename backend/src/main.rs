@@ -1,11 +1,12 @@
 #![allow(dead_code)] // Many structs/methods are scaffolded for future features
 #![allow(clippy::too_many_arguments)] // Suppress too many arguments lint globally
 
-use actix_cors::Cors;
 use actix_web::{middleware::Logger, web, App, HttpServer};
 use std::sync::Arc;
 use tracing::info;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
 
 mod config;
 mod database;
@@ -13,6 +14,7 @@ mod error;
 mod handlers;
 mod middleware;
 mod models;
+mod openapi;
 mod repository;
 mod services;
 mod utils;
@@ -32,6 +34,7 @@ async fn main() -> std::io::Result<()> {
 
     // Load configuration
     let config = Config::from_env().expect("Failed to load configuration");
+    config.validate().expect("Invalid configuration");
     let config = Arc::new(config);
 
     info!("Starting VESSEL Backend on port {}", config.port);
@@ -41,10 +44,16 @@ async fn main() -> std::io::Result<()> {
         .await
         .expect("Failed to create database pool");
 
+    // Readiness stays 503 until startup has finished and the listener is bound
+    let readiness = web::Data::new(handlers::Readiness::new(db_pool.clone()));
+
     // Run migrations
     database::run_migrations(&db_pool)
         .await
         .expect("Failed to run migrations");
+    database::run_investment_mode_migrations(&db_pool, config.investment_mode)
+        .await
+        .expect("Failed to run investment mode migrations");
 
     // Initialize Redis (optional)
     let redis_pool = match create_redis_pool(&config).await {
@@ -70,6 +79,10 @@ async fn main() -> std::io::Result<()> {
     let rq_repo = Arc::new(repository::RiskQuestionnaireRepository::new(
         db_pool.clone(),
     ));
+    let activity_repo = Arc::new(repository::ActivityRepository::new(db_pool.clone()));
+    let notification_repo = Arc::new(repository::NotificationRepository::new(db_pool.clone()));
+    let currency_repo = Arc::new(repository::CurrencyRepository::new(db_pool.clone()));
+    let outbound_email_repo = Arc::new(repository::OutboundEmailRepository::new(db_pool.clone()));
 
     // Initialize JWT Manager
     let jwt_manager = Arc::new(utils::JwtManager::new(
@@ -78,9 +91,17 @@ async fn main() -> std::io::Result<()> {
         config.jwt_refresh_expiry_hours,
     ));
 
+    // Prometheus metrics, shared by the services and the request middleware
+    let metrics = Arc::new(utils::Metrics::new().with_db_pool(db_pool.clone()));
+
     // Initialize services
     let pinata_service = Arc::new(services::PinataService::new(config.clone()));
-    let email_service = Arc::new(services::EmailService::new(config.clone()));
+    let email_service =
+        Arc::new(services::EmailService::new(config.clone()).with_outbox(outbound_email_repo));
+    services::spawn_email_delivery_task(
+        email_service.clone(),
+        std::time::Duration::from_secs(config.email_retry_interval_secs),
+    );
     let blockchain_service = Arc::new(
         services::BlockchainService::new(
             config.clone(),
@@ -89,8 +110,34 @@ async fn main() -> std::io::Result<()> {
             pinata_service.clone(),
         )
         .await
-        .expect("Failed to initialize blockchain service"),
+        .expect("Failed to initialize blockchain service")
+        .with_redis(redis_pool.clone())
+        .with_metrics(metrics.clone()),
     );
+    // A reachable RPC on the wrong network would sign for the wrong chain, and
+    // a fee differing from the contract's would misstate every repayment
+    if !config.skip_blockchain_verification {
+        let expected = blockchain_service.active_idrx_token().chain_id;
+        match blockchain_service.get_chain_id().await {
+            Ok(chain_id) if chain_id != expected => panic!(
+                "Configured chain id {} does not match the RPC chain id {}",
+                expected, chain_id
+            ),
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Could not verify CHAIN_ID against the RPC: {}", e),
+        }
+        match blockchain_service.get_platform_fee_bps().await {
+            Ok(fee_bps) if fee_bps != config.platform_fee_bps => panic!(
+                "PLATFORM_FEE_BPS {} does not match InvoicePool.platformFeeBps {}",
+                config.platform_fee_bps, fee_bps
+            ),
+            Ok(_) => {}
+            Err(e) => tracing::warn!(
+                "Could not verify PLATFORM_FEE_BPS against the pool contract: {}",
+                e
+            ),
+        }
+    }
     let escrow_service = Arc::new(services::EscrowService::new());
     let otp_service = Arc::new(services::OtpService::new(
         otp_repo.clone(),
@@ -98,6 +145,7 @@ async fn main() -> std::io::Result<()> {
         config.clone(),
         jwt_manager.clone(),
     ));
+    let activity_logger = Arc::new(services::ActivityLogger::new(activity_repo));
     let auth_service = Arc::new(services::AuthService::new(
         user_repo.clone(),
         mitra_repo.clone(),
@@ -105,12 +153,7 @@ async fn main() -> std::io::Result<()> {
         otp_service.clone(),
         config.clone(),
         blockchain_service.clone(),
-    ));
-    let mitra_service = Arc::new(services::MitraService::new(
-        mitra_repo.clone(),
-        user_repo.clone(),
-        email_service.clone(),
-        pinata_service.clone(),
+        activity_logger.clone(),
     ));
     let invoice_service = Arc::new(services::InvoiceService::new(
         invoice_repo.clone(),
@@ -120,26 +163,76 @@ async fn main() -> std::io::Result<()> {
         pinata_service.clone(),
         config.clone(),
     ));
-    let funding_service = Arc::new(services::FundingService::new(
-        funding_repo.clone(),
-        invoice_repo.clone(),
-        tx_repo.clone(),
+    let kyc_service = Arc::new(services::KycService::new(
         user_repo.clone(),
-        rq_repo.clone(),
         email_service.clone(),
-        escrow_service.clone(),
-        blockchain_service.clone(),
+    ));
+    let notification_service = Arc::new(services::NotificationService::new(
         config.clone(),
+        notification_repo,
     ));
+    let funding_service = Arc::new(
+        services::FundingService::new(
+            funding_repo.clone(),
+            invoice_repo.clone(),
+            tx_repo.clone(),
+            user_repo.clone(),
+            rq_repo.clone(),
+            email_service.clone(),
+            escrow_service.clone(),
+            blockchain_service.clone(),
+            notification_service.clone(),
+            activity_logger.clone(),
+            config.clone(),
+        )
+        .with_redis(redis_pool.clone())
+        .with_metrics(metrics.clone()),
+    );
+    let mitra_service = Arc::new(
+        services::MitraService::new(
+            mitra_repo.clone(),
+            user_repo.clone(),
+            email_service.clone(),
+            pinata_service.clone(),
+        )
+        .with_funding_service(funding_service.clone()),
+    );
+    if config.onchain_audit_interval_minutes > 0 {
+        services::spawn_onchain_audit_task(
+            funding_service.clone(),
+            std::time::Duration::from_secs(config.onchain_audit_interval_minutes * 60),
+            chrono::Duration::hours(config.onchain_audit_lookback_hours),
+        );
+    }
+    services::spawn_invoice_maturity_task(
+        funding_service.clone(),
+        std::time::Duration::from_secs(config.invoice_maturity_scan_interval_minutes as u64 * 60),
+    );
     let payment_service = Arc::new(services::PaymentService::new(
         user_repo.clone(),
         tx_repo.clone(),
         funding_repo.clone(),
         invoice_repo.clone(),
+        importer_payment_repo.clone(),
         blockchain_service.clone(),
+        email_service.clone(),
+        config.clone(),
     ));
+    services::spawn_overdue_payment_task(
+        payment_service.clone(),
+        std::time::Duration::from_secs(config.importer_overdue_scan_interval_minutes as u64 * 60),
+    );
     let rq_service = Arc::new(services::RiskQuestionnaireService::new(rq_repo.clone()));
-    let currency_service = Arc::new(services::CurrencyService::new(config.clone()));
+    let user_service = Arc::new(services::UserService::new(
+        user_repo.clone(),
+        mitra_repo.clone(),
+        funding_repo.clone(),
+        invoice_repo.clone(),
+    ));
+    let currency_service = Arc::new(services::CurrencyService::new(
+        currency_repo,
+        config.clone(),
+    ));
 
     // Create application state
     let app_state = web::Data::new(handlers::AppState {
@@ -159,6 +252,7 @@ async fn main() -> std::io::Result<()> {
         otp_service,
         mitra_service,
         invoice_service,
+        kyc_service,
         funding_service,
         payment_service,
         rq_service,
@@ -167,51 +261,100 @@ async fn main() -> std::io::Result<()> {
         pinata_service,
         email_service,
         escrow_service,
+        activity_logger,
+        notification_service,
+        user_service,
+        metrics: metrics.clone(),
     });
 
+    // Rate limiters for sensitive endpoints, shared by all workers
+    let new_limiter = |requests_per_window: u32| {
+        let limiter = Arc::new(middleware::rate_limit::RateLimiter::with_redis(
+            middleware::rate_limit::RateLimitConfig::new(
+                requests_per_window,
+                config.rate_limit_window_secs,
+            )
+            .with_client_ip_header(
+                config.rate_limit_client_ip_header.clone(),
+                config.rate_limit_trusted_proxies.clone(),
+            ),
+            app_state.redis_pool.clone(),
+        ));
+        middleware::rate_limit::spawn_cleanup_task(limiter.clone());
+        limiter
+    };
+    let login_limiter = new_limiter(config.rate_limit_login);
+    let send_otp_limiter = new_limiter(config.rate_limit_send_otp);
+    let public_pay_limiter = new_limiter(config.rate_limit_public_pay);
+
     let server_port = config.port;
-    let cors_origins = config.cors_allowed_origins.clone();
+    let enable_compression = config.enable_compression;
+    let cors_config = config.clone();
+    let openapi_doc = openapi::ApiDoc::openapi();
+    let metrics_data = web::Data::from(metrics.clone());
+    let serve_metrics_on_api_port = config.metrics_port == 0;
+    let startup_readiness = readiness.clone();
 
-    HttpServer::new(move || {
-        let cors_origins_inner = cors_origins.clone();
-        let cors = Cors::default()
-            .allowed_origin_fn(move |origin, _req_head| {
-                let origin_str = origin.to_str().unwrap_or("");
-                if cors_origins_inner == "*" {
-                    return true;
-                }
-                cors_origins_inner
-                    .split(',')
-                    .any(|o| o.trim() == origin_str)
+    // Scrapes on a separate port stay off the public API listener
+    let metrics_server = if serve_metrics_on_api_port {
+        None
+    } else {
+        info!("Serving metrics on port {}", config.metrics_port);
+        let metrics_data = metrics_data.clone();
+        Some(
+            HttpServer::new(move || {
+                App::new()
+                    .app_data(metrics_data.clone())
+                    .route("/metrics", web::get().to(handlers::metrics))
             })
-            .allowed_methods(vec!["GET", "POST", "PUT", "DELETE", "OPTIONS"])
-            .allowed_headers(vec!["Authorization", "Content-Type", "Accept"])
-            .supports_credentials()
-            .max_age(3600);
+            .workers(1)
+            .bind(format!("0.0.0.0:{}", config.metrics_port))?
+            .run(),
+        )
+    };
+
+    let api_server = HttpServer::new(move || {
+        let cors = middleware::cors(&cors_config);
 
         // Custom JSON error handler
         let json_cfg = web::JsonConfig::default().error_handler(|err, _req| {
             let message = format!("{}", err);
             actix_web::error::InternalError::from_response(
                 err,
-                actix_web::HttpResponse::BadRequest().json(serde_json::json!({
-                    "success": false,
-                    "error": {
-                        "code": "VALIDATION_ERROR",
-                        "message": message
-                    }
-                })),
+                actix_web::HttpResponse::BadRequest().json(
+                    utils::ApiResponse::<()>::error_with_code("VALIDATION_ERROR", &message),
+                ),
             )
             .into()
         });
 
         App::new()
             .app_data(app_state.clone())
+            .app_data(readiness.clone())
             .app_data(json_cfg)
+            .app_data(metrics_data.clone())
+            .wrap(middleware::compression(enable_compression))
             .wrap(Logger::default())
             .wrap(cors)
+            .wrap(middleware::RequestMetrics::new(metrics.clone()))
+            // Outermost, so every error response is rendered with the request id in scope
+            .wrap(middleware::RequestIdMiddleware)
             // Health check
             .route("/health", web::get().to(handlers::health_check))
+            // Kubernetes probes
+            .route("/livez", web::get().to(handlers::livez))
+            .route("/readyz", web::get().to(handlers::readyz))
+            // Prometheus scrape endpoint, unless it has its own port
+            .configure(|cfg| {
+                if serve_metrics_on_api_port {
+                    cfg.route("/metrics", web::get().to(handlers::metrics));
+                }
+            })
+            // OpenAPI spec and Swagger UI
+            .service(
+                SwaggerUi::new("/swagger-ui/{_:.*}")
+                    .url("/api-docs/openapi.json", openapi_doc.clone()),
+            )
             // API v1 routes
             .service(
                 web::scope("/api/v1")
@@ -219,11 +362,32 @@ async fn main() -> std::io::Result<()> {
                     .service(
                         web::scope("/auth")
                             // Traditional auth (for mitra/admin)
-                            .route("/send-otp", web::post().to(handlers::auth::send_otp))
+                            .service(
+                                web::resource("/send-otp")
+                                    .wrap(middleware::rate_limit::RateLimitMiddleware::new(
+                                        "auth/send-otp",
+                                        send_otp_limiter.clone(),
+                                    ))
+                                    .route(web::post().to(handlers::auth::send_otp)),
+                            )
                             .route("/verify-otp", web::post().to(handlers::auth::verify_otp))
                             .route("/register", web::post().to(handlers::auth::register))
-                            .route("/login", web::post().to(handlers::auth::login))
+                            .service(
+                                web::resource("/login")
+                                    .wrap(middleware::rate_limit::RateLimitMiddleware::new(
+                                        "auth/login",
+                                        login_limiter.clone(),
+                                    ))
+                                    .route(web::post().to(handlers::auth::login)),
+                            )
                             .route("/refresh", web::post().to(handlers::auth::refresh_token))
+                            .service(
+                                web::resource("/logout")
+                                    .wrap(middleware::AuthMiddleware::new(
+                                        app_state.auth_service.clone(),
+                                    ))
+                                    .route(web::post().to(handlers::auth::logout)),
+                            )
                             // Google OAuth (for mitra/admin - skips OTP)
                             .route("/google", web::post().to(handlers::auth::google_auth))
                             // Wallet auth (for investors & mitra - supports Base Smart Wallet / passkey)
@@ -248,14 +412,40 @@ async fn main() -> std::io::Result<()> {
                                 web::get().to(handlers::importer::get_payment_info),
                             )
                             .route(
-                                "/payments/{payment_id}/pay",
-                                web::post().to(handlers::importer::pay),
+                                "/payments/{payment_id}/nonce",
+                                web::post().to(handlers::importer::get_payment_nonce),
+                            )
+                            .service(
+                                web::resource("/payments/{payment_id}/pay")
+                                    .wrap(middleware::rate_limit::RateLimitMiddleware::new(
+                                        "public/payments/pay",
+                                        public_pay_limiter.clone(),
+                                    ))
+                                    .route(web::post().to(handlers::importer::pay)),
+                            )
+                            .service(
+                                web::resource("/importer/access-link")
+                                    .wrap(middleware::rate_limit::RateLimitMiddleware::new(
+                                        "public/importer/access-link",
+                                        send_otp_limiter.clone(),
+                                    ))
+                                    .route(
+                                        web::post().to(handlers::importer::request_access_link),
+                                    ),
+                            )
+                            .route(
+                                "/importer/payments",
+                                web::get().to(handlers::importer::list_buyer_payments),
                             ),
                     )
                     // Public marketplace routes (no auth required for browsing)
                     .service(
                         web::scope("/marketplace")
                             .route("", web::get().to(handlers::funding::get_marketplace))
+                            .route(
+                                "/stats",
+                                web::get().to(handlers::funding::get_platform_stats),
+                            )
                             .route(
                                 "/{id}/detail",
                                 web::get().to(handlers::funding::get_pool_detail),
@@ -265,12 +455,23 @@ async fn main() -> std::io::Result<()> {
                     .service(
                         web::scope("/pools")
                             .route("", web::get().to(handlers::funding::list_pools))
-                            .route("/{id}", web::get().to(handlers::funding::get_pool)),
+                            .route("/{id}", web::get().to(handlers::funding::get_pool))
+                            // Owner/admin only; actix scopes don't fall through to the
+                            // protected scope below, so authenticate per resource
+                            .service(
+                                web::resource("/{id}/repayment-schedule")
+                                    .wrap(middleware::AuthMiddleware::new(
+                                        app_state.auth_service.clone(),
+                                    ))
+                                    .route(
+                                        web::get().to(handlers::funding::get_repayment_schedule),
+                                    ),
+                            ),
                     )
                     // Protected routes
                     .service(
                         web::scope("")
-                            .wrap(middleware::AuthMiddleware::new(config.clone()))
+                            .wrap(middleware::AuthMiddleware::new(app_state.auth_service.clone()))
                             // User routes
                             .service(
                                 web::scope("/user")
@@ -295,7 +496,31 @@ async fn main() -> std::io::Result<()> {
                                         "/profile/password",
                                         web::put().to(handlers::user::change_password),
                                     )
+                                    .route("/activity", web::get().to(handlers::user::get_activity))
+                                    .route(
+                                        "/notifications",
+                                        web::get().to(handlers::user::list_notifications),
+                                    )
+                                    .route(
+                                        "/notifications/read-all",
+                                        web::post().to(handlers::user::read_all_notifications),
+                                    )
+                                    .route(
+                                        "/notifications/{id}/read",
+                                        web::post().to(handlers::user::read_notification),
+                                    )
                                     .route("/wallet", web::put().to(handlers::user::connect_wallet))
+                                    .route(
+                                        "/wallet/connect",
+                                        web::post().to(handlers::user::connect_wallet),
+                                    )
+                                    .route(
+                                        "/payout-wallet",
+                                        web::put().to(handlers::user::set_payout_wallet),
+                                    )
+                                    // KYC verification routes
+                                    .route("/kyc", web::post().to(handlers::kyc::submit))
+                                    .route("/kyc/status", web::get().to(handlers::kyc::get_status))
                                     // Mitra application routes
                                     .service(
                                         web::scope("/mitra")
@@ -318,6 +543,10 @@ async fn main() -> std::io::Result<()> {
                                         web::post()
                                             .to(handlers::currency::get_locked_exchange_rate),
                                     )
+                                    .route(
+                                        "/preview",
+                                        web::post().to(handlers::currency::preview_conversion),
+                                    )
                                     .route(
                                         "/supported",
                                         web::get().to(handlers::currency::get_supported_currencies),
@@ -351,6 +580,7 @@ async fn main() -> std::io::Result<()> {
                                         "/{id}/detail",
                                         web::get().to(handlers::invoice::get_detail),
                                     )
+                                    .route("/{id}/nft", web::get().to(handlers::invoice::get_nft))
                                     .route("/{id}", web::put().to(handlers::invoice::update))
                                     .route("/{id}", web::delete().to(handlers::invoice::delete))
                                     .route(
@@ -365,6 +595,10 @@ async fn main() -> std::io::Result<()> {
                                         "/{id}/documents",
                                         web::get().to(handlers::invoice::get_documents),
                                     )
+                                    .route(
+                                        "/{id}/documents/{doc_id}/download",
+                                        web::get().to(handlers::invoice::download_document),
+                                    )
                                     .route(
                                         "/{id}/tokenize",
                                         web::post().to(handlers::invoice::tokenize),
@@ -393,6 +627,10 @@ async fn main() -> std::io::Result<()> {
                                         "/questions",
                                         web::get().to(handlers::risk_questionnaire::get_questions),
                                     )
+                                    .route(
+                                        "",
+                                        web::get().to(handlers::risk_questionnaire::get_answers),
+                                    )
                                     .route("", web::post().to(handlers::risk_questionnaire::submit))
                                     .route(
                                         "/status",
@@ -402,11 +640,16 @@ async fn main() -> std::io::Result<()> {
                             // Investment routes
                             .service(
                                 web::scope("/investments")
+                                    .wrap(middleware::InvestorOnlyMiddleware)
                                     .route("", web::post().to(handlers::funding::invest))
                                     .route(
                                         "/confirm",
                                         web::post().to(handlers::funding::confirm_investment),
                                     )
+                                    .route(
+                                        "/permit",
+                                        web::post().to(handlers::funding::invest_with_permit),
+                                    )
                                     .route("", web::get().to(handlers::funding::get_my_investments))
                                     .route(
                                         "/portfolio",
@@ -415,16 +658,50 @@ async fn main() -> std::io::Result<()> {
                                     .route(
                                         "/active",
                                         web::get().to(handlers::funding::get_active_investments),
+                                    )
+                                    .route(
+                                        "/export",
+                                        web::get().to(handlers::funding::export_portfolio_csv),
+                                    )
+                                    .route(
+                                        "/tax-summary",
+                                        web::get().to(handlers::funding::get_tax_summary),
+                                    )
+                                    .route(
+                                        "/{id}/receipt",
+                                        web::get().to(handlers::funding::get_investment_receipt),
+                                    )
+                                    .route(
+                                        "/{id}",
+                                        web::get().to(handlers::funding::get_investment_detail),
                                     ),
                             )
                             // Exporter routes
-                            .service(web::scope("/exporter").route(
-                                "/disbursement",
-                                web::post().to(handlers::funding::exporter_disbursement),
-                            ))
+                            .service(
+                                web::scope("/exporter")
+                                    .wrap(middleware::MitraOnlyMiddleware)
+                                    .route(
+                                        "/disbursement",
+                                        web::post().to(handlers::funding::exporter_disbursement),
+                                    )
+                                    .route(
+                                        "/bank-accounts",
+                                        web::get().to(handlers::user::list_bank_accounts),
+                                    )
+                                    .route(
+                                        "/bank-accounts",
+                                        web::post().to(handlers::user::add_bank_account),
+                                    )
+                                    .route(
+                                        "/invoices/{id}/funding-status",
+                                        web::get()
+                                            .to(handlers::funding::get_invoice_funding_status),
+                                    ),
+                            )
                             // Mitra dashboard routes
                             .service(
                                 web::scope("/mitra")
+                                    .wrap(middleware::MitraOnlyMiddleware)
                                     .route(
                                         "/dashboard",
                                         web::get().to(handlers::funding::get_mitra_dashboard),
@@ -455,6 +732,14 @@ async fn main() -> std::io::Result<()> {
                                 web::scope("/admin")
                                     .wrap(middleware::AdminOnlyMiddleware)
                                     .route("/users", web::get().to(handlers::user::list_users))
+                                    .route(
+                                        "/users/{id}",
+                                        web::get().to(handlers::user::admin_get_user_detail),
+                                    )
+                                    .route(
+                                        "/bank-accounts/{id}/verify",
+                                        web::post().to(handlers::user::admin_verify_bank_account),
+                                    )
                                     .route(
                                         "/invoices/pending",
                                         web::get().to(handlers::invoice::get_pending_invoices),
@@ -463,6 +748,11 @@ async fn main() -> std::io::Result<()> {
                                         "/invoices/approved",
                                         web::get().to(handlers::invoice::get_approved_invoices),
                                     )
+                                    .route(
+                                        "/invoices/default-eligible",
+                                        web::get()
+                                            .to(handlers::invoice::get_default_eligible_invoices),
+                                    )
                                     .route(
                                         "/invoices/{id}/grade-suggestion",
                                         web::get().to(handlers::invoice::get_grade_suggestion),
@@ -471,10 +761,18 @@ async fn main() -> std::io::Result<()> {
                                         "/invoices/{id}/review",
                                         web::get().to(handlers::invoice::get_invoice_review_data),
                                     )
+                                    .route(
+                                        "/invoices/{id}/documents/verify",
+                                        web::get().to(handlers::invoice::verify_documents),
+                                    )
                                     .route(
                                         "/invoices/{id}/approve",
                                         web::post().to(handlers::invoice::approve),
                                     )
+                                    .route(
+                                        "/invoices/{id}/retry-tokenize",
+                                        web::post().to(handlers::invoice::retry_tokenize),
+                                    )
                                     .route(
                                         "/invoices/{id}/reject",
                                         web::post().to(handlers::invoice::reject),
@@ -487,14 +785,38 @@ async fn main() -> std::io::Result<()> {
                                         "/users/{id}/pools",
                                         web::get().to(handlers::funding::get_exporter_pools),
                                     )
+                                    .route(
+                                        "/pools",
+                                        web::get().to(handlers::funding::list_all_pools),
+                                    )
+                                    .route(
+                                        "/pools/{id}",
+                                        web::get().to(handlers::funding::get_admin_pool_detail),
+                                    )
+                                    .route(
+                                        "/pools/{id}/disburse-preview",
+                                        web::get().to(handlers::funding::get_disbursement_preview),
+                                    )
+                                    .route(
+                                        "/pools/{id}/onchain-audit",
+                                        web::get().to(handlers::funding::audit_pool_onchain),
+                                    )
                                     .route(
                                         "/pools/{id}/disburse",
                                         web::post().to(handlers::funding::disburse),
                                     )
+                                    .route(
+                                        "/investments/{id}/reconcile",
+                                        web::post().to(handlers::funding::reconcile_pending_investment),
+                                    )
                                     .route(
                                         "/pools/{id}/close",
                                         web::post().to(handlers::funding::close_pool_and_notify),
                                     )
+                                    .route(
+                                        "/pools/{id}/investment-limits",
+                                        web::put().to(handlers::funding::set_pool_investment_limits),
+                                    )
                                     .route(
                                         "/pools/{id}/repay",
                                         web::post().to(handlers::funding::process_pool_repayment),
@@ -523,9 +845,29 @@ async fn main() -> std::io::Result<()> {
                                         "/mitra/{id}/reject",
                                         web::post().to(handlers::mitra::reject),
                                     )
+                                    .route(
+                                        "/mitra/{id}/revoke",
+                                        web::post().to(handlers::mitra::revoke),
+                                    )
+                                    .route(
+                                        "/kyc/pending",
+                                        web::get().to(handlers::kyc::get_pending),
+                                    )
+                                    .route(
+                                        "/kyc/{id}/approve",
+                                        web::post().to(handlers::kyc::approve),
+                                    )
+                                    .route(
+                                        "/kyc/{id}/reject",
+                                        web::post().to(handlers::kyc::reject),
+                                    )
                                     .route(
                                         "/platform/revenue",
                                         web::get().to(handlers::payment::get_platform_revenue),
+                                    )
+                                    .route(
+                                        "/platform/stats",
+                                        web::get().to(handlers::payment::get_platform_stats),
                                     ),
                             )
                             // Blockchain transparency routes (on-chain verification)
@@ -536,6 +878,10 @@ async fn main() -> std::io::Result<()> {
                                         "/chain-info",
                                         web::get().to(handlers::blockchain::get_chain_info),
                                     )
+                                    .route(
+                                        "/config",
+                                        web::get().to(handlers::blockchain::get_blockchain_config),
+                                    )
                                     .route(
                                         "/balance/{address}",
                                         web::get().to(handlers::blockchain::get_idrx_balance),
@@ -570,6 +916,13 @@ async fn main() -> std::io::Result<()> {
             )
     })
     .bind(format!("0.0.0.0:{}", server_port))?
-    .run()
-    .await
+    .run();
+
+    // Migrations, the chain id check and the listener binds have all succeeded
+    startup_readiness.mark_started();
+
+    match metrics_server {
+        Some(metrics_server) => tokio::try_join!(api_server, metrics_server).map(|_| ()),
+        None => api_server.await,
+    }
 }
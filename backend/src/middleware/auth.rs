@@ -4,18 +4,22 @@ use actix_web::{
     Error, HttpMessage, HttpResponse,
 };
 use futures_util::future::{ok, LocalBoxFuture, Ready};
+use std::rc::Rc;
 use std::sync::Arc;
 
 use crate::config::Config;
+use crate::models::Role;
+use crate::services::AuthService;
 use crate::utils::{verify_token, ApiResponse, Claims};
 
+/// Requires a valid, non-revoked access token
 pub struct AuthMiddleware {
-    config: Arc<Config>,
+    auth_service: Arc<AuthService>,
 }
 
 impl AuthMiddleware {
-    pub fn new(config: Arc<Config>) -> Self {
-        Self { config }
+    pub fn new(auth_service: Arc<AuthService>) -> Self {
+        Self { auth_service }
     }
 }
 
@@ -33,15 +37,15 @@ where
 
     fn new_transform(&self, service: S) -> Self::Future {
         ok(AuthMiddlewareService {
-            service,
-            config: self.config.clone(),
+            service: Rc::new(service),
+            auth_service: self.auth_service.clone(),
         })
     }
 }
 
 pub struct AuthMiddlewareService<S> {
-    service: S,
-    config: Arc<Config>,
+    service: Rc<S>,
+    auth_service: Arc<AuthService>,
 }
 
 impl<S, B> Service<ServiceRequest> for AuthMiddlewareService<S>
@@ -58,39 +62,41 @@ where
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
         // Extract token from Authorization header
-        let auth_header = req.headers().get("Authorization");
-
-        let token = auth_header
+        let token = req
+            .headers()
+            .get("Authorization")
             .and_then(|h| h.to_str().ok())
-            .and_then(|h| h.strip_prefix("Bearer "));
-
-        match token {
-            Some(token) => {
-                match verify_token(token, &self.config.jwt_secret) {
-                    Ok(claims) => {
-                        // Insert claims into request extensions
-                        req.extensions_mut().insert(claims);
-                        let fut = self.service.call(req);
-                        Box::pin(async move {
-                            let res = fut.await?;
-                            Ok(res.map_into_left_body())
-                        })
-                    }
-                    Err(_) => {
-                        let response = HttpResponse::Unauthorized()
-                            .json(ApiResponse::<()>::error("Invalid or expired token"));
-                        Box::pin(
-                            async move { Ok(req.into_response(response).map_into_right_body()) },
-                        )
-                    }
+            .and_then(|h| h.strip_prefix("Bearer "))
+            .map(|t| t.to_string());
+
+        let service = self.service.clone();
+        let auth_service = self.auth_service.clone();
+
+        Box::pin(async move {
+            let token = match token {
+                Some(token) => token,
+                None => {
+                    let response = HttpResponse::Unauthorized()
+                        .json(ApiResponse::<()>::error("Authorization header missing"));
+                    return Ok(req.into_response(response).map_into_right_body());
+                }
+            };
+
+            // Signature, expiry and token_version (revoked on logout / password change)
+            match auth_service.authenticate(&token).await {
+                Ok(claims) => {
+                    // Insert claims into request extensions
+                    req.extensions_mut().insert(claims);
+                    let res = service.call(req).await?;
+                    Ok(res.map_into_left_body())
+                }
+                Err(_) => {
+                    let response = HttpResponse::Unauthorized()
+                        .json(ApiResponse::<()>::error("Invalid or expired token"));
+                    Ok(req.into_response(response).map_into_right_body())
                 }
             }
-            None => {
-                let response = HttpResponse::Unauthorized()
-                    .json(ApiResponse::<()>::error("Authorization header missing"));
-                Box::pin(async move { Ok(req.into_response(response).map_into_right_body()) })
-            }
-        }
+        })
     }
 }
 
@@ -201,7 +207,7 @@ where
         let claims = req.extensions().get::<Claims>().cloned();
 
         match claims {
-            Some(claims) if claims.role == "admin" => {
+            Some(claims) if claims.role == Role::Admin => {
                 let fut = self.service.call(req);
                 Box::pin(async move {
                     let res = fut.await?;
@@ -223,7 +229,6 @@ where
 }
 
 // Mitra-only middleware
-#[allow(dead_code)] // Reserved for future use
 pub struct MitraOnlyMiddleware;
 
 impl<S, B> Transform<S, ServiceRequest> for MitraOnlyMiddleware
@@ -243,7 +248,6 @@ where
     }
 }
 
-#[allow(dead_code)] // Used by MitraOnlyMiddleware
 pub struct MitraOnlyMiddlewareService<S> {
     service: S,
 }
@@ -264,7 +268,7 @@ where
         let claims = req.extensions().get::<Claims>().cloned();
 
         match claims {
-            Some(claims) if claims.role == "mitra" || claims.role == "admin" => {
+            Some(claims) if matches!(claims.role, Role::Mitra | Role::Admin) => {
                 let fut = self.service.call(req);
                 Box::pin(async move {
                     let res = fut.await?;
@@ -284,3 +288,64 @@ where
         }
     }
 }
+
+// Investor-only middleware
+pub struct InvestorOnlyMiddleware;
+
+impl<S, B> Transform<S, ServiceRequest> for InvestorOnlyMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = InvestorOnlyMiddlewareService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(InvestorOnlyMiddlewareService { service })
+    }
+}
+
+pub struct InvestorOnlyMiddlewareService<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for InvestorOnlyMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let claims = req.extensions().get::<Claims>().cloned();
+
+        match claims {
+            Some(claims) if matches!(claims.role, Role::Investor | Role::Admin) => {
+                let fut = self.service.call(req);
+                Box::pin(async move {
+                    let res = fut.await?;
+                    Ok(res.map_into_left_body())
+                })
+            }
+            Some(_) => {
+                let response = HttpResponse::Forbidden()
+                    .json(ApiResponse::<()>::error("Investor access required"));
+                Box::pin(async move { Ok(req.into_response(response).map_into_right_body()) })
+            }
+            None => {
+                let response = HttpResponse::Unauthorized()
+                    .json(ApiResponse::<()>::error("Authentication required"));
+                Box::pin(async move { Ok(req.into_response(response).map_into_right_body()) })
+            }
+        }
+    }
+}
@@ -0,0 +1,7 @@
+use actix_web::middleware::{Compress, Condition};
+
+/// Response compression (gzip/brotli/zstd, negotiated from `Accept-Encoding`).
+/// Mostly benefits large list payloads such as the marketplace and transaction history.
+pub fn compression(enabled: bool) -> Condition<Compress> {
+    Condition::new(enabled, Compress::default())
+}
@@ -0,0 +1,23 @@
+use actix_cors::Cors;
+
+use super::REQUEST_ID_HEADER;
+use crate::config::Config;
+
+/// CORS layer from the configured origins, methods and headers.
+/// `CORS_ALLOWED_ORIGINS=*` allows any origin.
+pub fn cors(config: &Config) -> Cors {
+    let origins = config.cors_allowed_origins.clone();
+    Cors::default()
+        .allowed_origin_fn(move |origin, _req_head| {
+            let origin_str = origin.to_str().unwrap_or("");
+            if origins == "*" {
+                return true;
+            }
+            origins.split(',').any(|o| o.trim() == origin_str)
+        })
+        .allowed_methods(config.cors_allowed_methods.iter().map(String::as_str))
+        .allowed_headers(config.cors_allowed_headers.iter().map(String::as_str))
+        .expose_headers([REQUEST_ID_HEADER])
+        .supports_credentials()
+        .max_age(3600)
+}
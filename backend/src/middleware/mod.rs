@@ -1,5 +1,13 @@
 pub mod auth;
+mod compression;
+mod cors;
+mod metrics;
 pub mod rate_limit;
+mod request_id;
 
 pub use auth::*;
+pub use compression::*;
+pub use cors::*;
+pub use metrics::*;
+pub use request_id::*;
 // Note: rate_limit is available but not re-exported as it's used directly when needed
@@ -5,10 +5,13 @@ use actix_web::{
 };
 use futures_util::future::{ok, LocalBoxFuture, Ready};
 use std::collections::HashMap;
+use std::net::IpAddr;
+use std::rc::Rc;
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::sync::RwLock;
 
+use crate::config::TrustedProxy;
 use crate::utils::ApiResponse;
 
 #[derive(Clone)]
@@ -16,6 +19,9 @@ use crate::utils::ApiResponse;
 pub struct RateLimitConfig {
     pub requests_per_window: u32,
     pub window_duration: Duration,
+    /// Header naming the client IP, honoured only when the peer is a trusted proxy
+    pub client_ip_header: Option<String>,
+    pub trusted_proxies: Vec<TrustedProxy>,
 }
 
 impl Default for RateLimitConfig {
@@ -23,18 +29,70 @@ impl Default for RateLimitConfig {
         Self {
             requests_per_window: 100,
             window_duration: Duration::from_secs(60),
+            client_ip_header: None,
+            trusted_proxies: Vec::new(),
         }
     }
 }
 
+impl RateLimitConfig {
+    pub fn new(requests_per_window: u32, window_secs: u64) -> Self {
+        Self {
+            requests_per_window,
+            window_duration: Duration::from_secs(window_secs.max(1)),
+            ..Self::default()
+        }
+    }
+
+    /// Take the client IP from `header` on requests arriving from one of `trusted_proxies`
+    pub fn with_client_ip_header(
+        self,
+        header: Option<String>,
+        trusted_proxies: Vec<TrustedProxy>,
+    ) -> Self {
+        Self {
+            client_ip_header: header,
+            trusted_proxies,
+            ..self
+        }
+    }
+
+    /// The socket peer, or the trusted header's IP when the peer is a trusted
+    /// proxy. Anyone else could put any address in the header.
+    fn client_ip(&self, req: &ServiceRequest) -> String {
+        let peer = req.peer_addr().map(|addr| addr.ip());
+        let forwarded = match (&self.client_ip_header, peer) {
+            (Some(header), Some(peer))
+                if self
+                    .trusted_proxies
+                    .iter()
+                    .any(|proxy| proxy.contains(peer)) =>
+            {
+                req.headers()
+                    .get(header.as_str())
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.trim().parse::<IpAddr>().ok())
+            }
+            _ => None,
+        };
+        forwarded
+            .or(peer)
+            .map(|ip| ip.to_string())
+            .unwrap_or_else(|| "unknown".to_string())
+    }
+}
+
 struct RateLimitEntry {
     count: u32,
     window_start: Instant,
 }
 
+/// Fixed-window limiter. Counts live in Redis when a pool is given so every
+/// instance shares them; otherwise (or if Redis errors) they are kept in process.
 pub struct RateLimiter {
     config: RateLimitConfig,
     entries: Arc<RwLock<HashMap<String, RateLimitEntry>>>,
+    redis_pool: Option<deadpool_redis::Pool>,
 }
 
 impl RateLimiter {
@@ -42,10 +100,67 @@ impl RateLimiter {
         Self {
             config,
             entries: Arc::new(RwLock::new(HashMap::new())),
+            redis_pool: None,
+        }
+    }
+
+    pub fn with_redis(config: RateLimitConfig, redis_pool: Option<deadpool_redis::Pool>) -> Self {
+        Self {
+            redis_pool,
+            ..Self::new(config)
         }
     }
 
     pub async fn check(&self, key: &str) -> bool {
+        self.retry_after(key).await.is_none()
+    }
+
+    /// Count a request for `key`. Returns how long to wait when the limit is exceeded.
+    pub async fn retry_after(&self, key: &str) -> Option<Duration> {
+        if let Some(pool) = &self.redis_pool {
+            match self.retry_after_redis(pool, key).await {
+                Ok(result) => return result,
+                Err(e) => {
+                    tracing::warn!(
+                        "Redis rate limiter unavailable, using in-process limits: {}",
+                        e
+                    )
+                }
+            }
+        }
+        self.retry_after_in_process(key).await
+    }
+
+    async fn retry_after_redis(
+        &self,
+        pool: &deadpool_redis::Pool,
+        key: &str,
+    ) -> anyhow::Result<Option<Duration>> {
+        let window_secs = self.config.window_duration.as_secs().max(1);
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let window = now / window_secs;
+        let redis_key = format!("ratelimit:{}:{}", key, window);
+
+        let mut conn = pool.get().await?;
+        let (count,): (u32,) = redis::pipe()
+            .atomic()
+            .cmd("INCR")
+            .arg(&redis_key)
+            .cmd("EXPIRE")
+            .arg(&redis_key)
+            .arg(window_secs)
+            .ignore()
+            .query_async(&mut conn)
+            .await?;
+
+        if count > self.config.requests_per_window {
+            let window_end = (window + 1) * window_secs;
+            return Ok(Some(Duration::from_secs(window_end - now)));
+        }
+        Ok(None)
+    }
+
+    async fn retry_after_in_process(&self, key: &str) -> Option<Duration> {
         let now = Instant::now();
         let mut entries = self.entries.write().await;
 
@@ -62,11 +177,15 @@ impl RateLimiter {
 
         // Check limit
         if entry.count >= self.config.requests_per_window {
-            return false;
+            return Some(
+                self.config
+                    .window_duration
+                    .saturating_sub(now.duration_since(entry.window_start)),
+            );
         }
 
         entry.count += 1;
-        true
+        None
     }
 
     pub async fn cleanup_old_entries(&self) {
@@ -78,14 +197,19 @@ impl RateLimiter {
     }
 }
 
+/// Limits requests per client IP for the wrapped route. Exceeding the limit
+/// returns `429 Too Many Requests` with a `Retry-After` header.
 pub struct RateLimitMiddleware {
+    route: String,
     limiter: Arc<RateLimiter>,
 }
 
 impl RateLimitMiddleware {
-    pub fn new(config: RateLimitConfig) -> Self {
+    /// `limiter` should be created once and shared across workers
+    pub fn new(route: &str, limiter: Arc<RateLimiter>) -> Self {
         Self {
-            limiter: Arc::new(RateLimiter::new(config)),
+            route: route.to_string(),
+            limiter,
         }
     }
 }
@@ -104,14 +228,16 @@ where
 
     fn new_transform(&self, service: S) -> Self::Future {
         ok(RateLimitMiddlewareService {
-            service,
+            service: Rc::new(service),
+            route: self.route.clone(),
             limiter: self.limiter.clone(),
         })
     }
 }
 
 pub struct RateLimitMiddlewareService<S> {
-    service: S,
+    service: Rc<S>,
+    route: String,
     limiter: Arc<RateLimiter>,
 }
 
@@ -128,26 +254,24 @@ where
     forward_ready!(service);
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
-        // Get client IP for rate limiting
-        let client_ip = req
-            .connection_info()
-            .realip_remote_addr()
-            .unwrap_or("unknown")
-            .to_string();
+        // A configured client IP header is only read from RATE_LIMIT_TRUSTED_PROXIES peers
+        let client_ip = self.limiter.config.client_ip(&req);
+        let key = format!("{}:{}", self.route, client_ip);
 
+        let service = self.service.clone();
         let limiter = self.limiter.clone();
-        let fut = self.service.call(req);
 
         Box::pin(async move {
-            if !limiter.check(&client_ip).await {
-                let (req, _) = fut.await?.into_parts();
-                let response = HttpResponse::TooManyRequests().json(ApiResponse::<()>::error(
-                    "Rate limit exceeded. Please try again later.",
-                ));
-                return Ok(ServiceResponse::new(req, response).map_into_right_body());
+            if let Some(retry_after) = limiter.retry_after(&key).await {
+                let response = HttpResponse::TooManyRequests()
+                    .insert_header(("Retry-After", retry_after.as_secs().max(1).to_string()))
+                    .json(ApiResponse::<()>::error(
+                        "Rate limit exceeded. Please try again later.",
+                    ));
+                return Ok(req.into_response(response).map_into_right_body());
             }
 
-            let res = fut.await?;
+            let res = service.call(req).await?;
             Ok(res.map_into_left_body())
         })
     }
@@ -164,6 +288,7 @@ impl IpRateLimiter {
             limiter: Arc::new(RateLimiter::new(RateLimitConfig {
                 requests_per_window: requests_per_minute,
                 window_duration: Duration::from_secs(60),
+                ..RateLimitConfig::default()
             })),
         }
     }
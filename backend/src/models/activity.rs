@@ -0,0 +1,15 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Entry in a user's activity feed
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct UserActivity {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    #[serde(rename = "type")]
+    pub activity_type: String,
+    pub summary: String,
+    pub created_at: NaiveDateTime,
+}
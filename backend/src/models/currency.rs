@@ -1,59 +1,16 @@
 use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
 use validator::Validate;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, FromRow)]
 pub struct SupportedCurrency {
     pub code: String,
     pub name: String,
     pub symbol: String,
+    pub decimals: i16,
     pub flag_emoji: String,
-}
-
-pub fn get_supported_currencies() -> Vec<SupportedCurrency> {
-    vec![
-        SupportedCurrency {
-            code: "USD".to_string(),
-            name: "US Dollar".to_string(),
-            symbol: "$".to_string(),
-            flag_emoji: "🇺🇸".to_string(),
-        },
-        SupportedCurrency {
-            code: "EUR".to_string(),
-            name: "Euro".to_string(),
-            symbol: "€".to_string(),
-            flag_emoji: "🇪🇺".to_string(),
-        },
-        SupportedCurrency {
-            code: "GBP".to_string(),
-            name: "British Pound".to_string(),
-            symbol: "£".to_string(),
-            flag_emoji: "🇬🇧".to_string(),
-        },
-        SupportedCurrency {
-            code: "JPY".to_string(),
-            name: "Japanese Yen".to_string(),
-            symbol: "¥".to_string(),
-            flag_emoji: "🇯🇵".to_string(),
-        },
-        SupportedCurrency {
-            code: "SGD".to_string(),
-            name: "Singapore Dollar".to_string(),
-            symbol: "S$".to_string(),
-            flag_emoji: "🇸🇬".to_string(),
-        },
-        SupportedCurrency {
-            code: "AUD".to_string(),
-            name: "Australian Dollar".to_string(),
-            symbol: "A$".to_string(),
-            flag_emoji: "🇦🇺".to_string(),
-        },
-        SupportedCurrency {
-            code: "CNY".to_string(),
-            name: "Chinese Yuan".to_string(),
-            symbol: "¥".to_string(),
-            flag_emoji: "🇨🇳".to_string(),
-        },
-    ]
+    /// Whether amounts in this currency can be converted to IDRX
+    pub conversion_enabled: bool,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -76,6 +33,19 @@ pub struct ConvertCurrencyResponse {
     pub rate_lock_token: String,
 }
 
+/// Unlocked conversion quote: same math as a locked rate, but nothing is signed or held
+#[derive(Debug, Serialize)]
+pub struct CurrencyPreviewResponse {
+    pub from_currency: String,
+    pub to_currency: String,
+    pub original_amount: f64,
+    pub exchange_rate: f64,
+    pub buffer_rate: f64,
+    pub buffer_amount: f64,
+    pub effective_rate: f64,
+    pub converted_amount: f64,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct DisbursementEstimateRequest {
     pub idr_amount: f64,
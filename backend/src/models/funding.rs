@@ -1,11 +1,12 @@
-use chrono::NaiveDateTime;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use utoipa::ToSchema;
 use uuid::Uuid;
 use validator::Validate;
 
-use super::{Invoice, User};
+use super::{Invoice, InvoiceNft, Transaction, User};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, sqlx::Type, Default)]
 #[sqlx(type_name = "varchar", rename_all = "snake_case")]
@@ -50,7 +51,7 @@ impl std::fmt::Display for TrancheType {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct FundingPool {
     pub id: Uuid,
     pub invoice_id: Uuid,
@@ -82,6 +83,18 @@ pub struct FundingPool {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub create_pool_tx_hash: Option<String>,
 
+    // Single-investment limits as a fraction of the tranche target, taken from the
+    // config when the pool is created. min/max_invest_ratio apply to the priority
+    // tranche. Pools created before they were stored fall back to the config.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_invest_ratio: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_invest_ratio: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub catalyst_min_invest_ratio: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub catalyst_max_invest_ratio: Option<Decimal>,
+
     // Relations
     #[sqlx(skip)]
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -91,7 +104,14 @@ pub struct FundingPool {
     pub investments: Option<Vec<Investment>>,
 }
 
-#[derive(Debug, Deserialize)]
+/// Per-pool single-investment limits, as fractions of the tranche target (0.05 = 5%)
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PoolInvestmentLimitsRequest {
+    pub min_invest_ratio: Decimal,
+    pub max_invest_ratio: Decimal,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct RepayInvoiceRequest {
     pub tx_hash: String,
     pub amount: f64,
@@ -109,7 +129,7 @@ pub enum InvestmentStatus {
     Defaulted,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct Investment {
     pub id: Uuid,
     pub pool_id: Uuid,
@@ -124,6 +144,15 @@ pub struct Investment {
     pub tx_hash: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub return_tx_hash: Option<String>,
+    /// Wallet recorded on the InvoicePool contract, which receives the returns
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::utils::mask::serialize_wallet"
+    )]
+    pub payout_wallet: Option<String>,
+    /// Platform wallet transfer that moved the funds on to the InvoicePool contract
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forward_tx_hash: Option<String>,
     pub invested_at: NaiveDateTime,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub repaid_at: Option<NaiveDateTime>,
@@ -139,18 +168,43 @@ pub struct Investment {
     pub investor: Option<User>,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct InvestRequest {
     pub pool_id: Uuid,
     #[validate(range(min = 0.01, message = "Amount must be positive"))]
     pub amount: f64,
     pub tranche: String,
+    /// Transfer to the platform wallet; not used in off-chain investment mode
+    #[serde(default)]
     pub tx_hash: String,
     pub tnc_accepted: bool,
     pub catalyst_consents: Option<CatalystConsents>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+/// Investment paid by pulling IDRX with an EIP-2612 `permit` signed by the
+/// investor, instead of a transfer made beforehand
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct InvestWithPermitRequest {
+    pub pool_id: Uuid,
+    #[validate(range(min = 0.01, message = "Amount must be positive"))]
+    pub amount: f64,
+    pub tranche: String,
+    pub tnc_accepted: bool,
+    pub catalyst_consents: Option<CatalystConsents>,
+    /// Investor wallet that signed the permit
+    pub owner: String,
+    /// Must be the platform wallet
+    pub spender: String,
+    /// Allowance in token units (decimal string, uint256)
+    pub value: String,
+    /// Unix timestamp after which the permit is invalid
+    pub deadline: i64,
+    pub v: u8,
+    pub r: String,
+    pub s: String,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
 pub struct CatalystConsents {
     pub first_loss_consent: bool,
     pub risk_loss_consent: bool,
@@ -163,7 +217,7 @@ impl CatalystConsents {
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct FundingPoolResponse {
     pub pool: FundingPool,
     pub remaining_amount: f64,
@@ -194,12 +248,23 @@ pub struct InvestmentListResponse {
     pub total_pages: i32,
 }
 
+/// Filters of the admin pool list
 #[derive(Debug, Clone, Default)]
 pub struct PoolFilter {
+    /// `open`, `filled`, `disbursed` or `closed`
     pub status: Option<String>,
-    pub grade: Option<String>,
-    pub page: i32,
-    pub per_page: i32,
+    /// `deadline` for the soonest deadline first; newest pools first otherwise
+    pub sort_by: Option<String>,
+}
+
+/// Row of the admin pool list
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AdminPoolListItem {
+    #[serde(flatten)]
+    pub pool: FundingPoolResponse,
+    /// Whole days until the funding deadline, negative once it has passed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub days_to_deadline: Option<i64>,
 }
 
 #[derive(Debug, Clone, Default, Deserialize)]
@@ -257,7 +322,7 @@ pub struct MarketplaceListResponse {
     pub total_pages: i32,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct InvestorPortfolio {
     pub total_funding: f64,
     pub total_expected_gain: f64,
@@ -268,7 +333,102 @@ pub struct InvestorPortfolio {
     pub completed_deals: i32,
 }
 
-#[derive(Debug, Serialize)]
+/// Receipt returned after an investment, consolidating DB and on-chain references
+#[derive(Debug, Serialize, ToSchema)]
+pub struct InvestmentReceipt {
+    pub investment_id: Uuid,
+    pub pool_id: Uuid,
+    pub invoice_id: Uuid,
+    pub invoice_number: String,
+    pub tranche: String,
+    pub amount: Decimal,
+    pub interest_rate: Decimal,
+    pub expected_return: Decimal,
+    pub maturity_date: NaiveDate,
+    pub investment_status: String,
+    pub tx_hash: Option<String>,
+    pub explorer_url: Option<String>,
+    pub block_number: Option<i64>,
+    /// Status of the recorded on-chain transfer: `confirmed`, `pending` or `failed`
+    pub confirmation_status: String,
+    pub invested_at: NaiveDateTime,
+}
+
+/// What an investment would record, from a dry run that touched neither the
+/// chain nor the database
+#[derive(Debug, Serialize, ToSchema)]
+pub struct InvestmentPreview {
+    pub pool_id: Uuid,
+    pub tranche: String,
+    pub amount: Decimal,
+    pub interest_rate: Decimal,
+    pub expected_return: Decimal,
+    pub maturity_date: NaiveDate,
+    /// Per-investor limits for the tranche; only the remaining capacity applies
+    /// once it falls below the minimum
+    pub min_investment: Decimal,
+    pub max_investment: Decimal,
+    pub tranche_remaining: Decimal,
+}
+
+/// One investment with the pool and invoice behind it, for the investor's detail view
+#[derive(Debug, Serialize, ToSchema)]
+pub struct InvestmentDetail {
+    pub investment: Investment,
+    pub pool: FundingPool,
+    pub invoice: Invoice,
+    pub interest_rate: Decimal,
+    /// Gain so far: interest accrued to date while active, the realized gain once repaid
+    pub current_return: Decimal,
+    /// Gain at maturity (`expected_return` minus principal)
+    pub expected_gain: Decimal,
+    /// Days until the invoice is due; negative once overdue
+    pub days_remaining: i64,
+    pub tx_explorer_url: Option<String>,
+    pub return_tx_explorer_url: Option<String>,
+}
+
+/// One row of the investor portfolio CSV export
+#[derive(Debug, Clone, FromRow)]
+pub struct PortfolioExportRow {
+    pub invoice_number: String,
+    pub tranche: String,
+    pub principal: Decimal,
+    pub expected_return: Decimal,
+    pub actual_return: Option<Decimal>,
+    pub invested_at: NaiveDateTime,
+    pub repaid_at: Option<NaiveDateTime>,
+    pub status: String,
+    pub tx_hash: Option<String>,
+    pub return_tx_hash: Option<String>,
+}
+
+/// A repaid investment counted in an investor's tax summary
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct TaxSummaryItem {
+    pub investment_id: Uuid,
+    pub invoice_number: String,
+    pub tranche: String,
+    pub principal: Decimal,
+    pub realized_return: Decimal,
+    /// Realized return minus principal
+    pub gain: Decimal,
+    pub invested_at: NaiveDateTime,
+    pub repaid_at: NaiveDateTime,
+}
+
+/// Realized returns of one investor for a calendar year, by repayment date
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TaxSummary {
+    pub year: i32,
+    pub investment_count: usize,
+    pub total_principal: Decimal,
+    pub total_realized_return: Decimal,
+    pub total_gain: Decimal,
+    pub investments: Vec<TaxSummaryItem>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct InvestorActiveInvestment {
     pub investment_id: Uuid,
     pub project_name: String,
@@ -284,6 +444,8 @@ pub struct InvestorActiveInvestment {
     pub total_expected: f64,
     pub due_date: NaiveDateTime,
     pub days_remaining: i32,
+    /// Invoice past its due date and not yet repaid
+    pub is_overdue: bool,
     pub status: String,
     pub status_display: String,
     pub status_color: String,
@@ -300,7 +462,7 @@ pub struct InvestorActiveInvestmentList {
     pub summary: InvestorPortfolio,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct MitraDashboard {
     pub total_active_financing: f64,
     pub total_owed_to_investors: f64,
@@ -309,7 +471,7 @@ pub struct MitraDashboard {
     pub timeline_status: TimelineStatus,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TimelineStatus {
     pub fundraising_complete: bool,
     pub disbursement_complete: bool,
@@ -317,7 +479,7 @@ pub struct TimelineStatus {
     pub current_step: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct InvoiceDashboard {
     pub invoice_id: Uuid,
     pub invoice_number: String,
@@ -328,17 +490,158 @@ pub struct InvoiceDashboard {
     pub status: String,
     pub status_color: String,
     pub days_remaining: i32,
+    /// Past its due date and not yet repaid
+    pub is_overdue: bool,
     pub funded_amount: f64,
     pub total_owed: f64,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ActiveInvestmentListResponse {
     pub investments: Vec<InvestorActiveInvestment>,
     pub total: i64,
     pub page: i32,
     pub per_page: i32,
     pub total_pages: i32,
+    /// Pass as `cursor` to fetch the next page; absent on the last page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// An investment joined with its investor's contact details and the transaction
+/// recorded for its transfer
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct AdminPoolInvestment {
+    pub investment_id: Uuid,
+    pub investor_id: Uuid,
+    pub investor_email: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub investor_username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub investor_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub investor_phone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub investor_wallet: Option<String>,
+    pub tranche: String,
+    pub amount: Decimal,
+    pub expected_return: Decimal,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual_return: Option<Decimal>,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub return_tx_hash: Option<String>,
+    pub invested_at: NaiveDateTime,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repaid_at: Option<NaiveDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<i64>,
+    /// `tx_hash` matches a confirmed transaction record
+    pub tx_verified: bool,
+}
+
+/// On-chain consistency checks for a pool
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PoolVerificationStatus {
+    pub nft_minted: bool,
+    pub pool_created_on_chain: bool,
+    pub verified_investments: usize,
+    pub unverified_investments: usize,
+    /// Investments sum to the pool's `funded_amount`
+    pub funded_amount_matches: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PoolTimelineEvent {
+    pub event: String,
+    pub occurred_at: NaiveDateTime,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference_id: Option<Uuid>,
+}
+
+/// Everything an admin needs to investigate a pool
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AdminPoolDetail {
+    pub pool: FundingPool,
+    pub invoice: Invoice,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nft: Option<InvoiceNft>,
+    pub investments: Vec<AdminPoolInvestment>,
+    pub transactions: Vec<Transaction>,
+    pub verification: PoolVerificationStatus,
+    pub timeline: Vec<PoolTimelineEvent>,
+}
+
+/// What disbursing a pool to the mitra's wallet would send, and whether the
+/// platform wallet can pay the gas. Wei amounts are decimal strings. The gas
+/// fields are left out when the mitra has no wallet to estimate against.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DisbursementPreview {
+    pub pool_id: Uuid,
+    pub pool_status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recipient_wallet: Option<String>,
+    pub funded_amount: f64,
+    pub platform_fee: f64,
+    /// IDRX the mitra receives, net of the platform fee
+    pub idrx_amount: f64,
+    /// Gas units of the IDRX transfer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_gas: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_price_wei: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_gas_cost_wei: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platform_native_balance_wei: Option<String>,
+    pub sufficient_native_balance: bool,
+    /// Why the disbursement would be refused; empty when it can go ahead
+    pub blockers: Vec<String>,
+}
+
+/// A pool's investments in the DB compared with the `InvestmentRecorded`
+/// events of the InvoicePool contract. Only investments made with a verified
+/// transfer are recorded on chain, so off-chain (balance) investments are left out.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PoolOnChainAudit {
+    pub pool_id: Uuid,
+    pub token_id: i64,
+    pub db_investment_count: usize,
+    pub onchain_investment_count: usize,
+    pub db_total: f64,
+    pub onchain_total: f64,
+    /// Investors whose amount or number of investments differs; empty when consistent
+    pub discrepancies: Vec<InvestmentDiscrepancy>,
+    pub is_consistent: bool,
+    pub audited_at: DateTime<Utc>,
+}
+
+/// Per-investor totals that disagree, keyed by the wallet the contract records
+#[derive(Debug, Serialize, ToSchema)]
+pub struct InvestmentDiscrepancy {
+    pub investor_wallet: String,
+    /// Absent when no DB investment in the pool belongs to this wallet
+    pub investor_id: Option<Uuid>,
+    pub db_amount: f64,
+    pub onchain_amount: f64,
+    pub db_count: usize,
+    pub onchain_count: usize,
+}
+
+/// Live funding progress of one of an exporter's invoices
+#[derive(Debug, Serialize, ToSchema)]
+pub struct InvoiceFundingStatus {
+    pub invoice_id: Uuid,
+    pub invoice_status: String,
+    /// Pool with funded/target amounts and per-tranche progress
+    pub pool: FundingPoolResponse,
+    pub investor_count: i32,
+    /// Seconds until the funding deadline, 0 once it has passed; absent without a deadline
+    pub seconds_remaining: Option<i64>,
+    /// Whether the exporter can request disbursement now
+    pub disbursement_eligible: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -350,11 +653,6 @@ pub struct MitraInvoiceListResponse {
     pub total_pages: i32,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct ConfirmInvestmentRequest {
-    pub investment_id: Uuid,
-}
-
 #[derive(Debug, Deserialize)]
 pub struct CalculateInvestmentRequest {
     pub pool_id: Uuid,
@@ -384,15 +682,29 @@ pub struct InvestWithWalletRequest {
     #[validate(range(min = 0.01, message = "Amount must be positive"))]
     pub amount: f64,
     pub tranche: String,
+    /// Transfer to the platform wallet; not used in off-chain investment mode
+    #[serde(default)]
     pub tx_hash: String,
     pub tnc_accepted: bool,
     pub catalyst_consents: Option<CatalystConsents>,
 }
 
+/// Where a mitra's disbursement is paid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DisbursementMode {
+    /// IDRX to the registered wallet, on-chain
+    #[default]
+    Wallet,
+    /// Fiat to the primary verified bank account, through the off-ramp
+    Bank,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ExporterDisbursementRequest {
     pub pool_id: Uuid,
-    // Bank account removed, disbursement goes to registered wallet
+    #[serde(default)]
+    pub mode: DisbursementMode,
 }
 
 #[derive(Debug, Deserialize)]
@@ -402,29 +714,20 @@ pub struct ProcessRepaymentRequest {
     pub notes: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
-pub struct RepaymentBreakdown {
-    pub pool_id: Uuid,
-    pub total_principal: f64,
-    pub total_interest: f64,
-    pub total_repayment: f64,
-    pub priority_repayment: f64,
-    pub catalyst_repayment: f64,
-    pub platform_fee: f64,
-    pub due_date: NaiveDateTime,
-    pub investors: Vec<InvestorRepayment>,
-}
-
-#[derive(Debug, Serialize)]
+/// What one investor receives when the pool is repaid
+#[derive(Debug, Serialize, ToSchema)]
 pub struct InvestorRepayment {
     pub investor_id: Uuid,
     pub tranche: String,
-    pub principal: f64,
-    pub interest: f64,
-    pub total: f64,
+    pub principal: Decimal,
+    pub interest: Decimal,
+    pub total: Decimal,
 }
 
-#[derive(Debug, Serialize)]
+/// Platform-wide aggregates for the landing page. Amounts are IDRX, `average_yield`
+/// is the investment-weighted tranche interest rate and `default_rate` the share of
+/// settled principal that defaulted, both in percent.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct PlatformStats {
     pub total_funded: f64,
     pub total_repaid: f64,
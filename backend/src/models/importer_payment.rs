@@ -37,6 +37,8 @@ pub struct ImporterPayment {
     pub buyer_name: String,
     pub amount_due: Decimal,
     pub amount_paid: Decimal,
+    /// Amount paid beyond `amount_due`, held as credit for the buyer
+    pub credit_amount: Decimal,
     pub currency: String,
     pub payment_status: String,
     pub due_date: DateTime<Utc>,
@@ -44,10 +46,32 @@ pub struct ImporterPayment {
     pub paid_at: Option<DateTime<Utc>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tx_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payer_wallet: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+impl ImporterPayment {
+    /// Overdue payments can still be settled
+    pub fn is_payable(&self) -> bool {
+        self.payment_status == "pending" || self.payment_status == "overdue"
+    }
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct IdempotencyKey {
+    pub id: Uuid,
+    pub payment_id: Uuid,
+    pub idempotency_key: String,
+    pub request_fingerprint: String,
+    /// Empty while the request is `in_progress`
+    pub response_body: String,
+    /// `in_progress` from reservation until the response is stored as `completed`
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct ImporterPaymentInfoResponse {
     pub payment_id: Uuid,
@@ -69,6 +93,42 @@ pub struct ImporterPaymentInfoResponse {
 pub struct ImporterPayRequest {
     pub amount: f64,
     pub tx_hash: String,
+    /// Wallet that signed the message returned by the payment nonce endpoint
+    pub wallet_address: String,
+    pub nonce: String,
+    pub signature: String,
+}
+
+/// Request for a one-time link to the buyer's payments overview
+#[derive(Debug, Deserialize)]
+pub struct BuyerAccessRequest {
+    pub email: String,
+}
+
+/// One importer payment as listed in the buyer's overview
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct BuyerPaymentSummary {
+    pub payment_id: Uuid,
+    pub invoice_id: Uuid,
+    pub invoice_number: String,
+    pub buyer_name: String,
+    pub amount_due: Decimal,
+    pub amount_paid: Decimal,
+    pub remaining_amount: Decimal,
+    pub credit_amount: Decimal,
+    pub currency: String,
+    pub payment_status: String,
+    pub due_date: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paid_at: Option<DateTime<Utc>>,
+}
+
+/// Everything a buyer owes across mitras
+#[derive(Debug, Serialize)]
+pub struct BuyerPaymentsResponse {
+    pub buyer_email: String,
+    pub payments: Vec<BuyerPaymentSummary>,
+    pub total_outstanding: Decimal,
 }
 
 #[derive(Debug, Serialize)]
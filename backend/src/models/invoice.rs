@@ -1,7 +1,8 @@
 use chrono::{NaiveDate, NaiveDateTime};
-use rust_decimal::Decimal;
+use rust_decimal::{Decimal, RoundingStrategy};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use utoipa::ToSchema;
 use uuid::Uuid;
 use validator::Validate;
 
@@ -25,6 +26,8 @@ pub enum InvoiceStatus {
     Funding,
     #[serde(rename = "funded")]
     Funded,
+    #[serde(rename = "disbursed")]
+    Disbursed,
     #[serde(rename = "matured")]
     Matured,
     #[serde(rename = "repaid")]
@@ -43,6 +46,7 @@ impl std::fmt::Display for InvoiceStatus {
             InvoiceStatus::Tokenized => write!(f, "tokenized"),
             InvoiceStatus::Funding => write!(f, "funding"),
             InvoiceStatus::Funded => write!(f, "funded"),
+            InvoiceStatus::Disbursed => write!(f, "disbursed"),
             InvoiceStatus::Matured => write!(f, "matured"),
             InvoiceStatus::Repaid => write!(f, "repaid"),
             InvoiceStatus::Defaulted => write!(f, "defaulted"),
@@ -50,6 +54,9 @@ impl std::fmt::Display for InvoiceStatus {
     }
 }
 
+/// Statuses in which the advance has been raised and repayment is still owed
+pub const REPAYMENT_OUTSTANDING_STATUSES: [&str; 3] = ["funded", "disbursed", "matured"];
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum DocumentType {
     #[serde(rename = "invoice_pdf")]
@@ -72,13 +79,16 @@ pub enum DocumentType {
     CommercialInvoice,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct Invoice {
     pub id: Uuid,
     pub exporter_id: Uuid,
     pub buyer_name: String,
     pub buyer_country: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::utils::mask::serialize_email"
+    )]
     pub buyer_email: Option<String>,
     pub invoice_number: String,
     pub currency: String,
@@ -133,8 +143,13 @@ pub struct Invoice {
     pub funding_duration_days: i32,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub payment_link: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::utils::mask::serialize_wallet"
+    )]
     pub exporter_wallet_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<NaiveDateTime>,
 
     // Relations (not from DB, populated separately)
     #[sqlx(skip)]
@@ -148,7 +163,54 @@ pub struct Invoice {
     pub nft: Option<InvoiceNft>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+impl Invoice {
+    /// Tranche rates weighted by tranche ratio, rounded to the column's 2 decimals.
+    /// This is what `interest_rate` stores and what the invoice NFT carries.
+    pub fn blend_interest_rates(
+        priority_ratio: Decimal,
+        catalyst_ratio: Decimal,
+        priority_rate: Decimal,
+        catalyst_rate: Decimal,
+    ) -> Decimal {
+        let total_ratio = priority_ratio + catalyst_ratio;
+        if total_ratio <= Decimal::ZERO {
+            return priority_rate.round_dp(2);
+        }
+        ((priority_ratio * priority_rate + catalyst_ratio * catalyst_rate) / total_ratio)
+            .round_dp(2)
+    }
+
+    /// The stored `interest_rate`, or the blend of the tranche rates for invoices
+    /// approved before it was populated
+    pub fn effective_interest_rate(&self) -> Option<Decimal> {
+        self.interest_rate.or_else(|| {
+            match (self.priority_interest_rate, self.catalyst_interest_rate) {
+                (Some(priority), Some(catalyst)) => Some(Self::blend_interest_rates(
+                    self.priority_ratio,
+                    self.catalyst_ratio,
+                    priority,
+                    catalyst,
+                )),
+                _ => None,
+            }
+        })
+    }
+
+    /// Most that may be raised against the invoice: `funding_limit_percentage`
+    /// of `amount` (60% for new buyers, 100% for repeat buyers), truncated to
+    /// 2 decimals so rounding never exceeds the limit
+    pub fn funding_target(&self) -> Decimal {
+        (self.amount * self.funding_limit_percentage / Decimal::from(100))
+            .round_dp_with_strategy(2, RoundingStrategy::ToZero)
+    }
+
+    /// Past its due date with repayment still owed
+    pub fn is_overdue(&self, today: NaiveDate) -> bool {
+        today > self.due_date && REPAYMENT_OUTSTANDING_STATUSES.contains(&self.status.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct InvoiceDocument {
     pub id: Uuid,
     pub invoice_id: Uuid,
@@ -160,7 +222,84 @@ pub struct InvoiceDocument {
     pub uploaded_at: NaiveDateTime,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+impl InvoiceDocument {
+    /// MIME type to serve the file with, guessed from its original extension
+    pub fn content_type(&self) -> &'static str {
+        let extension = self
+            .file_name
+            .rsplit_once('.')
+            .map(|(_, ext)| ext.to_ascii_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            "pdf" => "application/pdf",
+            "png" => "image/png",
+            "jpg" | "jpeg" => "image/jpeg",
+            "gif" => "image/gif",
+            "webp" => "image/webp",
+            "txt" => "text/plain; charset=utf-8",
+            "csv" => "text/csv; charset=utf-8",
+            "json" => "application/json",
+            "doc" => "application/msword",
+            "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+            "xls" => "application/vnd.ms-excel",
+            "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+            _ => "application/octet-stream",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DocumentIntegrityStatus {
+    /// Content on IPFS hashes to the value recorded at upload
+    Verified,
+    Mismatch,
+    Unreachable,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DocumentIntegrityCheck {
+    pub document_id: Uuid,
+    pub document_type: String,
+    pub file_name: String,
+    pub cid: String,
+    pub expected_hash: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual_hash: Option<String>,
+    pub status: DocumentIntegrityStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DocumentIntegrityReport {
+    pub invoice_id: Uuid,
+    /// True only when every document was fetched and matched
+    pub all_verified: bool,
+    pub mismatched: usize,
+    pub unreachable: usize,
+    pub documents: Vec<DocumentIntegrityCheck>,
+}
+
+/// Narrows the fundable invoice list; unset fields do not filter
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct FundableInvoiceFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grade: Option<String>,
+    /// Tier of the buyer country in `country_tiers` (1 = lowest risk)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country_tier: Option<i32>,
+    /// Minimum annual rate (%) of the better-paying tranche
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_yield: Option<Decimal>,
+    /// Days until the invoice is due
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_days_remaining: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_days_remaining: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct InvoiceNft {
     pub id: Uuid,
     pub invoice_id: Uuid,
@@ -185,7 +324,55 @@ pub struct InvoiceNft {
     pub updated_at: NaiveDateTime,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+impl InvoiceNft {
+    /// Minted and not burned since
+    pub fn is_live(&self) -> bool {
+        self.token_id.is_some() && self.burned_at.is_none()
+    }
+
+    /// OpenSea page of the token, on Base mainnet or Base Sepolia
+    pub fn opensea_url(&self) -> Option<String> {
+        let (host, chain) = match self.chain_id {
+            8453 => ("opensea.io", "base"),
+            84532 => ("testnets.opensea.io", "base_sepolia"),
+            _ => return None,
+        };
+        Some(format!(
+            "https://{}/assets/{}/{}/{}",
+            host,
+            chain,
+            self.contract_address.as_deref()?,
+            self.token_id?
+        ))
+    }
+}
+
+/// An invoice's NFT as recorded at minting, optionally checked against the chain
+#[derive(Debug, Serialize, ToSchema)]
+pub struct InvoiceNftView {
+    pub invoice_id: Uuid,
+    /// `false` until the NFT is minted, in which case the fields below are absent
+    pub minted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nft: Option<InvoiceNft>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explorer_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub opensea_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mint_tx_explorer_url: Option<String>,
+    /// Holder reported by the contract's `ownerOf`, when checked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub onchain_owner: Option<String>,
+    /// Whether `onchain_owner` is the recorded `owner_address`, when checked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner_matches: Option<bool>,
+    /// Why the on-chain check could not be made
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner_check_error: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct CreateInvoiceFundingRequest {
     // Buyer data
     pub buyer_company_name: String,
@@ -199,6 +386,8 @@ pub struct CreateInvoiceFundingRequest {
     #[validate(range(min = 0.01, message = "Amount must be positive"))]
     pub original_amount: f64,
     pub locked_exchange_rate: f64,
+    /// Signed quote from `POST /currency/convert`; the rate is taken from here
+    pub rate_lock_token: String,
     #[validate(range(min = 0.01, message = "IDR amount must be positive"))]
     pub idr_amount: f64,
     pub due_date: String,
@@ -269,7 +458,7 @@ pub struct AdminApproveInvoiceRequest {
     pub notes: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct AdminGradeSuggestionResponse {
     pub invoice_id: String,
     pub suggested_grade: String,
@@ -343,12 +532,12 @@ pub struct InvoiceFilter {
     pub per_page: i32,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct RepeatBuyerCheckRequest {
     pub buyer_company_name: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct RepeatBuyerCheckResponse {
     pub is_repeat_buyer: bool,
     pub message: String,
@@ -369,7 +558,7 @@ pub type CreateFundingRequestInput = CreateInvoiceFundingRequest;
 pub type CheckRepeatBuyerRequest = RepeatBuyerCheckRequest;
 
 // Admin review request
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct AdminReviewInvoiceRequest {
     pub action: String, // "approve" or "reject"
     pub grade: Option<String>,
@@ -0,0 +1,66 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub enum KycStatus {
+    #[serde(rename = "pending")]
+    #[default]
+    Pending,
+    #[serde(rename = "approved")]
+    Approved,
+    #[serde(rename = "rejected")]
+    Rejected,
+}
+
+impl std::fmt::Display for KycStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KycStatus::Pending => write!(f, "pending"),
+            KycStatus::Approved => write!(f, "approved"),
+            KycStatus::Rejected => write!(f, "rejected"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct KycVerification {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    #[serde(serialize_with = "crate::utils::mask::serialize_nik")]
+    pub nik: String,
+    pub full_name: String,
+    pub ktp_photo_url: String,
+    pub selfie_url: String,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rejection_reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verified_by: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verified_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate, Clone)]
+pub struct KycSubmitRequest {
+    #[validate(length(min = 16, max = 16, message = "NIK must be 16 digits"))]
+    pub nik: String,
+    #[validate(length(min = 3, message = "Full name must be at least 3 characters"))]
+    pub full_name: String,
+    #[validate(length(min = 1, message = "KTP photo is required"))]
+    pub ktp_photo_url: String,
+    #[validate(length(min = 1, message = "Selfie is required"))]
+    pub selfie_url: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct KycStatusResponse {
+    pub status: String,
+    pub verification: Option<KycVerification>,
+    pub rejection_reason: Option<String>,
+    pub verified_at: Option<DateTime<Utc>>,
+}
@@ -1,9 +1,13 @@
 use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use utoipa::ToSchema;
 use uuid::Uuid;
 use validator::Validate;
 
+use super::InvestorRepayment;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 pub enum MitraApplicationStatus {
     #[serde(rename = "pending")]
@@ -121,27 +125,31 @@ pub struct MitraApplicationListResponse {
     pub total_pages: i32,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct RepaymentBreakdown {
     pub pool_id: Uuid,
     pub invoice_number: String,
-    pub principal_amount: f64,
-    pub total_interest: f64,
-    pub platform_fee: f64,
-    pub total_repayment: f64,
+    pub principal_amount: Decimal,
+    pub total_interest: Decimal,
+    /// Withheld by the contract, as recorded in the `platform_fee` transaction
+    pub platform_fee: Decimal,
+    /// Sub-cent rounding the contract keeps after the fee and the investor payouts
+    pub rounding_remainder: Decimal,
+    pub total_repayment: Decimal,
     pub priority_breakdown: TrancheBreakdown,
     pub catalyst_breakdown: TrancheBreakdown,
     pub due_date: DateTime<Utc>,
     pub days_remaining: i32,
+    pub investors: Vec<InvestorRepayment>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TrancheBreakdown {
     pub tranche: String,
-    pub principal: f64,
-    pub interest_rate: f64,
-    pub interest_amount: f64,
-    pub total: f64,
+    pub principal: Decimal,
+    pub interest_rate: Decimal,
+    pub interest_amount: Decimal,
+    pub total: Decimal,
     pub investor_count: i32,
 }
 
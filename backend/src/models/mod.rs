@@ -1,21 +1,29 @@
+mod activity;
 mod common;
 mod currency;
 mod funding;
 mod importer_payment;
 mod invoice;
+mod kyc;
 mod mitra;
+mod notification;
 mod otp;
+mod outbound_email;
 mod risk_questionnaire;
 mod transaction;
 mod user;
 
+pub use activity::*;
 pub use common::*;
 pub use currency::*;
 pub use funding::*;
 pub use importer_payment::*;
 pub use invoice::*;
+pub use kyc::*;
 pub use mitra::*;
+pub use notification::*;
 pub use otp::*;
+pub use outbound_email::*;
 pub use risk_questionnaire::*;
 pub use transaction::*;
 pub use user::*;
@@ -0,0 +1,23 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize, Serializer};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Entry in a user's in-app notification inbox
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Notification {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub kind: String,
+    #[serde(serialize_with = "serialize_payload")]
+    pub payload: String,
+    pub read_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+/// Payload is stored as JSON text; emit it as an object rather than a string
+fn serialize_payload<S: Serializer>(payload: &str, serializer: S) -> Result<S::Ok, S::Error> {
+    serde_json::from_str::<serde_json::Value>(payload)
+        .unwrap_or(serde_json::Value::Null)
+        .serialize(serializer)
+}
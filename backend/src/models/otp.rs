@@ -1,6 +1,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use utoipa::ToSchema;
 use uuid::Uuid;
 use validator::Validate;
 
@@ -36,14 +37,14 @@ pub struct OtpCode {
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct SendOtpRequest {
     #[validate(email(message = "Invalid email format"))]
     pub email: String,
     pub purpose: String, // "registration", "login", "password_reset"
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct VerifyOtpRequest {
     #[validate(email(message = "Invalid email format"))]
     pub email: String,
@@ -52,13 +53,13 @@ pub struct VerifyOtpRequest {
     pub purpose: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct SendOtpResponse {
     pub message: String,
     pub expires_in_minutes: i64,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct VerifyOtpResponse {
     pub message: String,
     pub otp_token: String,
@@ -0,0 +1,25 @@
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Notification email queued for delivery. `status` moves from `pending` to
+/// `sent`, or through `failed` retries to `dead` once the attempts run out.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct OutboundEmail {
+    pub id: Uuid,
+    pub recipient: String,
+    pub subject: String,
+    pub template: String,
+    #[serde(skip_serializing)]
+    pub html_body: String,
+    #[serde(skip_serializing)]
+    pub text_body: String,
+    pub status: String,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub next_attempt_at: NaiveDateTime,
+    pub sent_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 
+use crate::error::{AppError, AppResult};
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct RiskQuestionnaire {
     pub id: Uuid,
@@ -15,11 +17,15 @@ pub struct RiskQuestionnaire {
     pub q3_answer: Option<i32>,
     pub catalyst_unlocked: bool,
     pub selected_tier: String,
+    pub question_version: i32,
+    /// Loaded from `risk_questionnaire_answers` by the repository
+    #[sqlx(skip)]
+    pub answers: Vec<RiskAnswer>,
     pub completed_at: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RiskQuestion {
     pub id: i32,
     pub question: String,
@@ -27,108 +33,139 @@ pub struct RiskQuestion {
     pub required_for_catalyst: bool,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RiskOption {
     pub value: i32,
     pub label: String,
     pub unlocks_catalyst: bool,
+    #[serde(default)]
+    pub score: i32,
 }
 
-pub fn get_risk_questions() -> Vec<RiskQuestion> {
-    vec![
-        RiskQuestion {
-            id: 1,
-            question: "Seberapa lama pengalaman Anda dalam berinvestasi?".to_string(),
-            options: vec![
-                RiskOption {
-                    value: 1,
-                    label: "Kurang dari 1 tahun".to_string(),
-                    unlocks_catalyst: false,
-                },
-                RiskOption {
-                    value: 2,
-                    label: "1-3 tahun".to_string(),
-                    unlocks_catalyst: true,
-                },
-                RiskOption {
-                    value: 3,
-                    label: "Lebih dari 3 tahun".to_string(),
-                    unlocks_catalyst: true,
-                },
-            ],
-            required_for_catalyst: true,
-        },
-        RiskQuestion {
-            id: 2,
-            question: "Apakah Anda memahami bahwa tranche Catalyst memiliki risiko lebih tinggi dan dapat kehilangan modal?".to_string(),
-            options: vec![
-                RiskOption {
-                    value: 1,
-                    label: "Ya, saya memahami risikonya".to_string(),
-                    unlocks_catalyst: true,
-                },
-                RiskOption {
-                    value: 2,
-                    label: "Tidak, saya tidak mau mengambil risiko tersebut".to_string(),
-                    unlocks_catalyst: false,
-                },
-            ],
-            required_for_catalyst: true,
-        },
-        RiskQuestion {
-            id: 3,
-            question: "Apakah Anda bersedia dana Anda menjadi jaminan pertama jika terjadi gagal bayar?".to_string(),
-            options: vec![
-                RiskOption {
-                    value: 1,
-                    label: "Ya, saya bersedia".to_string(),
-                    unlocks_catalyst: true,
-                },
-                RiskOption {
-                    value: 2,
-                    label: "Tidak, saya tidak bersedia".to_string(),
-                    unlocks_catalyst: false,
-                },
-            ],
-            required_for_catalyst: true,
-        },
-    ]
+/// One published version of the questionnaire. Catalyst is unlocked when every
+/// `required_for_catalyst` question is answered with an option that
+/// `unlocks_catalyst` and the chosen options score at least `catalyst_min_score`.
+#[derive(Debug, Clone, Serialize)]
+pub struct RiskQuestionSet {
+    pub version: i32,
+    pub catalyst_min_score: i32,
+    pub questions: Vec<RiskQuestion>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RiskAnswer {
+    pub question_id: i32,
+    pub value: i32,
+}
+
+impl RiskQuestionSet {
+    /// Check that every question is answered exactly once with one of its
+    /// options, then apply the catalyst rule
+    pub fn evaluate(&self, answers: &[RiskAnswer]) -> AppResult<bool> {
+        if let Some(answer) = answers
+            .iter()
+            .find(|a| !self.questions.iter().any(|q| q.id == a.question_id))
+        {
+            return Err(AppError::ValidationError(format!(
+                "Unknown question Q{}",
+                answer.question_id
+            )));
+        }
+
+        let mut unlocked = true;
+        let mut score = 0;
+        for question in &self.questions {
+            let mut chosen = answers.iter().filter(|a| a.question_id == question.id);
+            let answer = match (chosen.next(), chosen.next()) {
+                (Some(answer), None) => answer,
+                (None, _) => {
+                    return Err(AppError::ValidationError(format!(
+                        "Missing answer for Q{}",
+                        question.id
+                    )))
+                }
+                (Some(_), Some(_)) => {
+                    return Err(AppError::ValidationError(format!(
+                        "Duplicate answer for Q{}",
+                        question.id
+                    )))
+                }
+            };
+            let option = question
+                .options
+                .iter()
+                .find(|o| o.value == answer.value)
+                .ok_or_else(|| {
+                    AppError::ValidationError(format!("Invalid answer for Q{}", question.id))
+                })?;
+
+            if question.required_for_catalyst && !option.unlocks_catalyst {
+                unlocked = false;
+            }
+            score += option.score;
+        }
+
+        Ok(unlocked && score >= self.catalyst_min_score)
+    }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct SubmitRiskQuestionnaireRequest {
-    pub q1_answer: i32,
-    pub q2_answer: i32,
-    pub q3_answer: i32,
+    /// Version the answers were given for; defaults to the active version
+    #[serde(default)]
+    pub version: Option<i32>,
+    #[serde(default)]
+    pub answers: Vec<RiskAnswer>,
+    /// Version 1 clients may still send the three answers as fields
+    #[serde(default)]
+    pub q1_answer: Option<i32>,
+    #[serde(default)]
+    pub q2_answer: Option<i32>,
+    #[serde(default)]
+    pub q3_answer: Option<i32>,
     pub selected_tier: String,
 }
 
+impl SubmitRiskQuestionnaireRequest {
+    /// `answers`, or the legacy `qN_answer` fields when no list was sent
+    pub fn answer_list(&self) -> Vec<RiskAnswer> {
+        if !self.answers.is_empty() {
+            return self.answers.clone();
+        }
+        legacy_answers(self.q1_answer, self.q2_answer, self.q3_answer)
+    }
+}
+
+fn legacy_answers(q1: Option<i32>, q2: Option<i32>, q3: Option<i32>) -> Vec<RiskAnswer> {
+    [(1, q1), (2, q2), (3, q3)]
+        .into_iter()
+        .filter_map(|(question_id, value)| value.map(|value| RiskAnswer { question_id, value }))
+        .collect()
+}
+
 #[derive(Debug, Serialize)]
 pub struct RiskQuestionnaireStatusResponse {
     pub completed: bool,
     pub catalyst_unlocked: bool,
     pub selected_tier: Option<String>,
     pub completed_at: Option<DateTime<Utc>>,
-    pub answers: Option<RiskQuestionnaireAnswers>,
+    pub question_version: Option<i32>,
+    pub answers: Option<Vec<RiskAnswer>>,
 }
 
+/// Stored answers returned for review before a re-take
 #[derive(Debug, Serialize)]
-pub struct RiskQuestionnaireAnswers {
-    pub q1_answer: Option<i32>,
-    pub q2_answer: Option<i32>,
-    pub q3_answer: Option<i32>,
+pub struct RiskQuestionnaireReview {
+    pub question_version: i32,
+    pub answers: Vec<RiskAnswer>,
+    pub catalyst_unlocked: bool,
+    pub selected_tier: String,
+    pub completed_at: DateTime<Utc>,
 }
 
 impl RiskQuestionnaire {
-    /// Check if the answers unlock catalyst tranche
-    pub fn check_catalyst_unlocked(&self) -> bool {
-        // Q1: Must be >= 2 (1+ years experience)
-        // Q2: Must be 1 (understands risk)
-        // Q3: Must be 1 (willing to be first loss)
-        let q1_ok = self.q1_answer.map(|a| a >= 2).unwrap_or(false);
-        let q2_ok = self.q2_answer.map(|a| a == 1).unwrap_or(false);
-        let q3_ok = self.q3_answer.map(|a| a == 1).unwrap_or(false);
-
-        q1_ok && q2_ok && q3_ok
+    /// Answers stored before versioning live in the `qN_answer` columns
+    pub fn legacy_answers(&self) -> Vec<RiskAnswer> {
+        legacy_answers(self.q1_answer, self.q2_answer, self.q3_answer)
     }
 }
@@ -1,7 +1,8 @@
-use chrono::NaiveDateTime;
+use chrono::{NaiveDate, NaiveDateTime};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -18,6 +19,8 @@ pub enum TransactionType {
     PlatformFee,
     #[serde(rename = "refund")]
     Refund,
+    #[serde(rename = "bank_disbursement")]
+    BankDisbursement,
 }
 
 impl std::fmt::Display for TransactionType {
@@ -29,6 +32,7 @@ impl std::fmt::Display for TransactionType {
             TransactionType::InvestorReturn => write!(f, "investor_return"),
             TransactionType::PlatformFee => write!(f, "platform_fee"),
             TransactionType::Refund => write!(f, "refund"),
+            TransactionType::BankDisbursement => write!(f, "bank_disbursement"),
         }
     }
 }
@@ -42,6 +46,9 @@ pub enum TransactionStatus {
     Confirmed,
     #[serde(rename = "failed")]
     Failed,
+    /// Bank disbursement waiting for the fiat payout
+    #[serde(rename = "pending_offramp")]
+    PendingOfframp,
 }
 
 impl std::fmt::Display for TransactionStatus {
@@ -50,11 +57,26 @@ impl std::fmt::Display for TransactionStatus {
             TransactionStatus::Pending => write!(f, "pending"),
             TransactionStatus::Confirmed => write!(f, "confirmed"),
             TransactionStatus::Failed => write!(f, "failed"),
+            TransactionStatus::PendingOfframp => write!(f, "pending_offramp"),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+/// Conditions for `TransactionRepository::find`; unset fields match every row.
+/// `created_from` is inclusive and `created_to` exclusive.
+#[derive(Debug, Clone, Default)]
+pub struct TxFilter {
+    pub user_id: Option<Uuid>,
+    pub tx_type: Option<TransactionType>,
+    pub status: Option<TransactionStatus>,
+    pub invoice_id: Option<Uuid>,
+    pub created_from: Option<NaiveDateTime>,
+    pub created_to: Option<NaiveDateTime>,
+    /// Only transactions with an on-chain hash
+    pub on_chain_only: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct Transaction {
     pub id: Uuid,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -77,11 +99,17 @@ pub struct Transaction {
     pub block_number: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub gas_used: Option<i64>,
+    /// Price per gas unit paid by the platform wallet, in wei
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effective_gas_price: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub notes: Option<String>,
     /// URL to view transaction on block explorer (e.g., basescan.org)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub explorer_url: Option<String>,
+    /// Destination of a `bank_disbursement`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bank_account_id: Option<Uuid>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
 }
@@ -119,3 +147,73 @@ pub struct CreateTransactionRequest {
     pub to_address: Option<String>,
     pub notes: Option<String>,
 }
+
+#[derive(Debug, Deserialize)]
+pub struct PlatformRevenueQuery {
+    /// Inclusive start date, `YYYY-MM-DD`
+    pub from: Option<String>,
+    /// Inclusive end date, `YYYY-MM-DD`
+    pub to: Option<String>,
+    /// `day` or `month` (default)
+    pub granularity: Option<String>,
+}
+
+/// One row of the platform fee aggregate. The ROLLUP total row has no period.
+#[derive(Debug, Clone, FromRow)]
+pub struct PlatformRevenueRow {
+    pub period: Option<NaiveDate>,
+    pub confirmed: Decimal,
+    pub pending: Decimal,
+    pub tx_count: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PlatformRevenuePoint {
+    pub period: NaiveDate,
+    pub confirmed: Decimal,
+    pub pending: Decimal,
+    pub tx_count: i64,
+}
+
+/// One row of the gas aggregate. The ROLLUP total row has `is_total` set.
+#[derive(Debug, Clone, FromRow)]
+pub struct GasUsageRow {
+    pub pool_id: Option<Uuid>,
+    pub is_total: bool,
+    pub gas_used: i64,
+    pub fee_wei: Decimal,
+    pub tx_count: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PoolGasUsage {
+    pub pool_id: Uuid,
+    pub gas_used: i64,
+    pub fee_wei: Decimal,
+    pub tx_count: i64,
+}
+
+/// Gas paid by the platform wallet for the transactions it sent
+#[derive(Debug, Serialize)]
+pub struct PlatformGasStats {
+    pub total_gas_used: i64,
+    pub total_fee_wei: Decimal,
+    pub tx_count: i64,
+    pub pools: Vec<PoolGasUsage>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PlatformRevenueSummary {
+    pub total_revenue: Decimal,
+    pub confirmed_revenue: Decimal,
+    pub pending_revenue: Decimal,
+    pub tx_count: i64,
+    pub currency: String,
+    pub platform_fee_bps: u32,
+    pub granularity: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<NaiveDate>,
+    pub series: Vec<PlatformRevenuePoint>,
+}
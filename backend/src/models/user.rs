@@ -1,52 +1,117 @@
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use std::str::FromStr;
+use utoipa::ToSchema;
 use uuid::Uuid;
 use validator::Validate;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, sqlx::Type, Default)]
+use super::{KycVerification, MitraApplication};
+use crate::error::AppError;
+
+/// `users.role`
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type, ToSchema, Default,
+)]
 #[sqlx(type_name = "varchar", rename_all = "lowercase")]
-pub enum UserRole {
-    #[serde(rename = "investor")]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
     #[default]
     Investor,
-    #[serde(rename = "admin")]
     Admin,
-    #[serde(rename = "mitra")]
     Mitra,
-    #[serde(rename = "exporter")]
     Exporter,
 }
 
-impl std::fmt::Display for UserRole {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl Role {
+    pub fn as_str(&self) -> &'static str {
         match self {
-            UserRole::Investor => write!(f, "investor"),
-            UserRole::Admin => write!(f, "admin"),
-            UserRole::Mitra => write!(f, "mitra"),
-            UserRole::Exporter => write!(f, "exporter"),
+            Role::Investor => "investor",
+            Role::Admin => "admin",
+            Role::Mitra => "mitra",
+            Role::Exporter => "exporter",
+        }
+    }
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Role {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "investor" => Ok(Role::Investor),
+            "admin" => Ok(Role::Admin),
+            "mitra" => Ok(Role::Mitra),
+            "exporter" => Ok(Role::Exporter),
+            other => Err(AppError::ValidationError(format!(
+                "Unknown role '{}'",
+                other
+            ))),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, sqlx::Type, Default)]
+/// `users.member_status`: where the user stands in the cooperative
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type, ToSchema, Default,
+)]
 #[sqlx(type_name = "varchar", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
 pub enum MemberStatus {
-    #[serde(rename = "calon_anggota_pendana")]
     #[default]
     CalonAnggotaPendana,
-    #[serde(rename = "calon_anggota_mitra")]
     CalonAnggotaMitra,
-    #[serde(rename = "member_mitra")]
     MemberMitra,
-    #[serde(rename = "admin")]
     Admin,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+impl MemberStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MemberStatus::CalonAnggotaPendana => "calon_anggota_pendana",
+            MemberStatus::CalonAnggotaMitra => "calon_anggota_mitra",
+            MemberStatus::MemberMitra => "member_mitra",
+            MemberStatus::Admin => "admin",
+        }
+    }
+}
+
+impl std::fmt::Display for MemberStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for MemberStatus {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "calon_anggota_pendana" => Ok(MemberStatus::CalonAnggotaPendana),
+            "calon_anggota_mitra" => Ok(MemberStatus::CalonAnggotaMitra),
+            "member_mitra" => Ok(MemberStatus::MemberMitra),
+            "admin" => Ok(MemberStatus::Admin),
+            other => Err(AppError::ValidationError(format!(
+                "Unknown member status '{}'",
+                other
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct User {
     pub id: Uuid,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::utils::mask::serialize_email"
+    )]
     pub email: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub username: Option<String>,
@@ -54,16 +119,30 @@ pub struct User {
     pub phone_number: Option<String>,
     #[serde(skip_serializing)]
     pub password_hash: String,
-    pub role: String,
+    pub role: Role,
     pub is_verified: bool,
     pub is_active: bool,
     pub cooperative_agreement: bool,
-    pub member_status: String,
+    pub member_status: MemberStatus,
 
     pub email_verified: bool,
     pub profile_completed: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::utils::mask::serialize_wallet"
+    )]
     pub wallet_address: Option<String>,
+    /// Investor returns are paid here instead of `wallet_address` once verified
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "crate::utils::mask::serialize_wallet"
+    )]
+    pub payout_wallet_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payout_wallet_verified_at: Option<NaiveDateTime>,
+    /// Bumped on logout and password change to revoke issued tokens
+    #[serde(skip_serializing)]
+    pub token_version: i32,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
     #[sqlx(skip)]
@@ -71,7 +150,19 @@ pub struct User {
     pub profile: Option<UserProfile>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+impl User {
+    /// Wallet that receives investor returns: the verified payout wallet, else the
+    /// login wallet. `None` when the payout wallet is unverified or no wallet is set.
+    pub fn payout_wallet(&self) -> Option<&str> {
+        match (&self.payout_wallet_address, self.payout_wallet_verified_at) {
+            (Some(wallet), Some(_)) => Some(wallet),
+            (Some(_), None) => None,
+            (None, _) => self.wallet_address.as_deref(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct UserProfile {
     pub id: Uuid,
     pub user_id: Uuid,
@@ -92,7 +183,44 @@ pub struct UserProfile {
     pub updated_at: NaiveDateTime,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+/// Onboarding progress for the profile checklist. Required fields depend on the role.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ProfileCompleteness {
+    /// Share of required fields filled in, 0-100
+    pub percentage: u8,
+    pub required_fields: Vec<String>,
+    pub missing_fields: Vec<String>,
+}
+
+impl ProfileCompleteness {
+    /// Build from `(field, is_filled)` checks
+    pub fn from_checks(checks: &[(&str, bool)]) -> Self {
+        let required_fields: Vec<String> = checks.iter().map(|(f, _)| f.to_string()).collect();
+        let missing_fields: Vec<String> = checks
+            .iter()
+            .filter(|(_, filled)| !filled)
+            .map(|(f, _)| f.to_string())
+            .collect();
+        let percentage = if required_fields.is_empty() {
+            100
+        } else {
+            let filled = required_fields.len() - missing_fields.len();
+            (filled * 100 / required_fields.len()) as u8
+        };
+
+        Self {
+            percentage,
+            required_fields,
+            missing_fields,
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.missing_fields.is_empty()
+    }
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct RegisterRequest {
     #[validate(email(message = "Invalid email format"))]
     pub email: String,
@@ -142,7 +270,7 @@ pub struct ChangePasswordRequest {
     pub confirm_password: String,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct LoginRequest {
     pub email_or_username: String,
     #[validate(length(min = 1, message = "Password is required"))]
@@ -155,7 +283,7 @@ pub struct UpdateWalletRequest {
 }
 
 // Wallet-based authentication for investors
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct WalletLoginRequest {
     #[validate(length(min = 42, max = 42, message = "Invalid wallet address"))]
     pub wallet_address: String,
@@ -164,19 +292,19 @@ pub struct WalletLoginRequest {
     pub nonce: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct WalletNonceResponse {
     pub nonce: String,
     pub message: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct GetNonceRequest {
     pub wallet_address: String,
 }
 
 // Simplified investor registration (wallet-only)
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct InvestorWalletRegisterRequest {
     #[validate(length(min = 42, max = 42, message = "Invalid wallet address"))]
     pub wallet_address: String,
@@ -197,7 +325,7 @@ pub struct ConnectWalletRequest {
     pub nonce: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct LoginResponse {
     pub user: User,
     pub access_token: String,
@@ -206,14 +334,81 @@ pub struct LoginResponse {
 }
 
 // Google OAuth
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct GoogleAuthRequest {
     pub id_token: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct GoogleAuthResponse {
     pub email: String,
     pub otp_token: String,
     pub expires_in_minutes: i64,
 }
+
+/// Bank account a mitra can receive fiat disbursements in. Only a verified
+/// primary account is paid out to.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct BankAccount {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub bank_code: String,
+    pub bank_name: String,
+    pub account_number: String,
+    pub account_name: String,
+    pub is_primary: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verified_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+/// Bank account a mitra adds to receive fiat disbursements
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct AddBankAccountRequest {
+    #[validate(length(min = 1, max = 20, message = "Bank code is required"))]
+    pub bank_code: String,
+    #[validate(length(min = 1, max = 100, message = "Bank name is required"))]
+    pub bank_name: String,
+    #[validate(length(min = 5, max = 50, message = "Account number must be 5-50 digits"))]
+    pub account_number: String,
+    #[validate(length(min = 1, max = 255, message = "Account name is required"))]
+    pub account_name: String,
+}
+
+impl BankAccount {
+    /// Account number with all but the last 4 digits hidden, for notes and emails
+    pub fn masked_account_number(&self) -> String {
+        let visible = self.account_number.len().saturating_sub(4);
+        format!("****{}", &self.account_number[visible..])
+    }
+}
+
+/// Everything an admin needs to review a single user
+#[derive(Debug, Serialize)]
+pub struct AdminUserDetail {
+    pub user: User,
+    pub profile: Option<UserProfile>,
+    pub kyc: Option<KycVerification>,
+    pub bank_accounts: Vec<BankAccount>,
+    pub mitra_application: Option<MitraApplication>,
+    pub investment_stats: UserInvestmentStats,
+    pub invoice_stats: UserInvoiceStats,
+}
+
+/// Investments made by the user as an investor
+#[derive(Debug, Serialize)]
+pub struct UserInvestmentStats {
+    pub active_amount: f64,
+    pub realized_gain: f64,
+    pub active_count: i64,
+    pub completed_count: i64,
+}
+
+/// Invoices submitted by the user as a mitra
+#[derive(Debug, Serialize, FromRow)]
+pub struct UserInvoiceStats {
+    pub total_count: i64,
+    pub active_count: i64,
+    pub repaid_count: i64,
+    pub total_amount: f64,
+}
@@ -0,0 +1,243 @@
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+use crate::error::ErrorResponse;
+use crate::handlers::{auth, funding, invoice};
+use crate::models::{
+    ActiveInvestmentListResponse, AdminGradeSuggestionResponse, AdminPoolDetail,
+    AdminPoolInvestment, AdminPoolListItem, AdminReviewInvoiceRequest, CatalystConsents,
+    CreateInvoiceFundingRequest, DisbursementMode, DisbursementPreview, DocumentIntegrityCheck,
+    DocumentIntegrityReport, DocumentIntegrityStatus, FundingPool, FundingPoolResponse,
+    GetNonceRequest, GoogleAuthRequest, GoogleAuthResponse, InvestRequest, InvestWithPermitRequest,
+    Investment, InvestmentDetail, InvestmentDiscrepancy, InvestmentPreview, InvestmentReceipt,
+    InvestorActiveInvestment, InvestorPortfolio, InvestorRepayment, InvestorWalletRegisterRequest,
+    Invoice, InvoiceDashboard, InvoiceDocument, InvoiceFundingStatus, InvoiceNft, InvoiceNftView,
+    LoginRequest, LoginResponse, MemberStatus, MitraDashboard, PlatformStats,
+    PoolInvestmentLimitsRequest, PoolOnChainAudit, PoolTimelineEvent, PoolVerificationStatus,
+    RefreshTokenRequest, RegisterRequest, RepayInvoiceRequest, RepaymentBreakdown,
+    RepeatBuyerCheckRequest, RepeatBuyerCheckResponse, Role, SendOtpRequest, SendOtpResponse,
+    TaxSummary, TaxSummaryItem, TimelineStatus, TrancheBreakdown, Transaction, User, UserProfile,
+    VerifyOtpRequest, VerifyOtpResponse, WalletLoginRequest, WalletNonceResponse,
+};
+use crate::utils::{
+    AdminPoolDetailApiResponse, AdminPoolListApiResponse, ApiError, ApiResponseBody,
+    DisbursementPreviewApiResponse, DocumentIntegrityApiResponse, FieldError,
+    FundingPoolApiResponse, FundingPoolDetailApiResponse, FundingPoolListApiResponse,
+    GradeSuggestionApiResponse, InvestmentDetailApiResponse, InvestmentListApiResponse,
+    InvestmentReceiptApiResponse, InvoiceApiResponse, InvoiceDocumentApiResponse,
+    InvoiceDocumentListApiResponse, InvoiceFundingStatusApiResponse, InvoiceListApiResponse,
+    InvoiceNftApiResponse, LoginApiResponse, MitraDashboardApiResponse, PaginationMeta,
+    PlatformStatsApiResponse, PoolOnChainAuditApiResponse, PortfolioApiResponse,
+    RepaymentScheduleApiResponse, RepeatBuyerCheckApiResponse, TaxSummaryApiResponse,
+    WalletNonceApiResponse,
+};
+
+/// OpenAPI document served at `/api-docs/openapi.json`
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "VESSEL Backend API",
+        description = "Invoice factoring platform on Base Network"
+    ),
+    paths(
+        auth::send_otp,
+        auth::verify_otp,
+        auth::register,
+        auth::login,
+        auth::get_wallet_nonce,
+        auth::wallet_login,
+        auth::wallet_register,
+        auth::refresh_token,
+        auth::logout,
+        auth::google_auth,
+        invoice::create,
+        invoice::create_funding_request,
+        invoice::check_repeat_buyer,
+        invoice::list,
+        invoice::list_fundable,
+        invoice::get,
+        invoice::get_detail,
+        invoice::get_nft,
+        invoice::update,
+        invoice::delete,
+        invoice::submit,
+        invoice::upload_document,
+        invoice::get_documents,
+        invoice::download_document,
+        invoice::tokenize,
+        invoice::get_pending_invoices,
+        invoice::get_approved_invoices,
+        invoice::get_default_eligible_invoices,
+        invoice::get_grade_suggestion,
+        invoice::get_invoice_review_data,
+        invoice::verify_documents,
+        invoice::approve,
+        invoice::retry_tokenize,
+        invoice::reject,
+        invoice::get_exporter_invoices,
+        funding::create_pool,
+        funding::list_pools,
+        funding::get_pool,
+        funding::get_repayment_schedule,
+        funding::get_marketplace,
+        funding::get_platform_stats,
+        funding::get_pool_detail,
+        funding::calculate_investment,
+        funding::invest,
+        funding::get_investment_receipt,
+        funding::get_investment_detail,
+        funding::confirm_investment,
+        funding::invest_with_permit,
+        funding::get_my_investments,
+        funding::get_portfolio,
+        funding::export_portfolio_csv,
+        funding::get_tax_summary,
+        funding::get_active_investments,
+        funding::exporter_disbursement,
+        funding::get_invoice_funding_status,
+        funding::get_mitra_dashboard,
+        funding::get_mitra_active_invoices,
+        funding::get_mitra_pools,
+        funding::get_pool_by_invoice,
+        funding::list_all_pools,
+        funding::get_admin_pool_detail,
+        funding::get_disbursement_preview,
+        funding::audit_pool_onchain,
+        funding::reconcile_pending_investment,
+        funding::disburse,
+        funding::close_pool_and_notify,
+        funding::set_pool_investment_limits,
+        funding::get_exporter_pools,
+        funding::process_pool_repayment,
+        funding::process_repayment,
+    ),
+    components(schemas(
+        // Envelopes
+        ApiResponseBody,
+        ApiError,
+        FieldError,
+        PaginationMeta,
+        ErrorResponse,
+        LoginApiResponse,
+        WalletNonceApiResponse,
+        InvoiceApiResponse,
+        InvoiceListApiResponse,
+        InvoiceDocumentApiResponse,
+        InvoiceDocumentListApiResponse,
+        InvoiceNftApiResponse,
+        DocumentIntegrityApiResponse,
+        GradeSuggestionApiResponse,
+        RepeatBuyerCheckApiResponse,
+        FundingPoolApiResponse,
+        FundingPoolDetailApiResponse,
+        FundingPoolListApiResponse,
+        AdminPoolDetailApiResponse,
+        AdminPoolListApiResponse,
+        DisbursementPreviewApiResponse,
+        PoolOnChainAuditApiResponse,
+        InvestmentReceiptApiResponse,
+        InvestmentDetailApiResponse,
+        InvestmentListApiResponse,
+        PortfolioApiResponse,
+        PlatformStatsApiResponse,
+        TaxSummaryApiResponse,
+        InvoiceFundingStatusApiResponse,
+        MitraDashboardApiResponse,
+        RepaymentScheduleApiResponse,
+        // Auth
+        User,
+        Role,
+        MemberStatus,
+        UserProfile,
+        SendOtpRequest,
+        SendOtpResponse,
+        VerifyOtpRequest,
+        VerifyOtpResponse,
+        RegisterRequest,
+        LoginRequest,
+        LoginResponse,
+        GetNonceRequest,
+        WalletNonceResponse,
+        WalletLoginRequest,
+        InvestorWalletRegisterRequest,
+        RefreshTokenRequest,
+        GoogleAuthRequest,
+        GoogleAuthResponse,
+        // Invoices
+        Invoice,
+        InvoiceDocument,
+        InvoiceNft,
+        InvoiceNftView,
+        DocumentIntegrityReport,
+        DocumentIntegrityCheck,
+        DocumentIntegrityStatus,
+        CreateInvoiceFundingRequest,
+        RepeatBuyerCheckRequest,
+        RepeatBuyerCheckResponse,
+        AdminGradeSuggestionResponse,
+        AdminReviewInvoiceRequest,
+        invoice::RejectRequest,
+        // Funding
+        FundingPool,
+        FundingPoolResponse,
+        Investment,
+        InvestRequest,
+        InvestWithPermitRequest,
+        CatalystConsents,
+        InvestmentReceipt,
+        InvestmentPreview,
+        InvestmentDetail,
+        InvestorPortfolio,
+        InvestorActiveInvestment,
+        PlatformStats,
+        TaxSummary,
+        TaxSummaryItem,
+        ActiveInvestmentListResponse,
+        MitraDashboard,
+        InvoiceDashboard,
+        TimelineStatus,
+        RepayInvoiceRequest,
+        PoolInvestmentLimitsRequest,
+        AdminPoolDetail,
+        AdminPoolListItem,
+        AdminPoolInvestment,
+        DisbursementPreview,
+        PoolOnChainAudit,
+        InvestmentDiscrepancy,
+        InvoiceFundingStatus,
+        PoolVerificationStatus,
+        PoolTimelineEvent,
+        RepaymentBreakdown,
+        TrancheBreakdown,
+        InvestorRepayment,
+        Transaction,
+        funding::CalculateInvestmentRequest,
+        funding::ExporterDisbursementRequest,
+        DisbursementMode,
+    )),
+    modifiers(&SecurityAddon),
+    tags(
+        (name = "auth", description = "Registration, login and token management"),
+        (name = "invoices", description = "Invoice funding requests and admin review"),
+        (name = "funding", description = "Funding pools, investments and repayments")
+    )
+)]
+pub struct ApiDoc;
+
+/// Registers the JWT bearer scheme referenced by `security(("bearer_auth" = []))`
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+    }
+}
@@ -0,0 +1,63 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::AppResult;
+use crate::models::UserActivity;
+
+#[derive(Clone)]
+pub struct ActivityRepository {
+    pool: PgPool,
+}
+
+impl ActivityRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create(
+        &self,
+        user_id: Uuid,
+        activity_type: &str,
+        summary: &str,
+    ) -> AppResult<UserActivity> {
+        let activity = sqlx::query_as::<_, UserActivity>(
+            r#"
+            INSERT INTO user_activity (user_id, activity_type, summary)
+            VALUES ($1, $2, $3)
+            RETURNING *
+            "#,
+        )
+        .bind(user_id)
+        .bind(activity_type)
+        .bind(summary)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(activity)
+    }
+
+    pub async fn find_by_user(
+        &self,
+        user_id: Uuid,
+        page: i32,
+        per_page: i32,
+    ) -> AppResult<(Vec<UserActivity>, i64)> {
+        let offset = (page - 1) * per_page;
+
+        let activities = sqlx::query_as::<_, UserActivity>(
+            "SELECT * FROM user_activity WHERE user_id = $1 ORDER BY created_at DESC, id DESC LIMIT $2 OFFSET $3",
+        )
+        .bind(user_id)
+        .bind(per_page)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let total: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM user_activity WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok((activities, total.0))
+    }
+}
@@ -0,0 +1,44 @@
+use sqlx::PgPool;
+
+use crate::error::AppResult;
+use crate::models::SupportedCurrency;
+
+#[derive(Clone)]
+pub struct CurrencyRepository {
+    pool: PgPool,
+}
+
+impl CurrencyRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn find_all(&self) -> AppResult<Vec<SupportedCurrency>> {
+        let currencies = sqlx::query_as::<_, SupportedCurrency>(
+            r#"
+            SELECT code, name, symbol, decimals, flag_emoji, conversion_enabled
+            FROM supported_currencies
+            ORDER BY code
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(currencies)
+    }
+
+    pub async fn find_by_code(&self, code: &str) -> AppResult<Option<SupportedCurrency>> {
+        let currency = sqlx::query_as::<_, SupportedCurrency>(
+            r#"
+            SELECT code, name, symbol, decimals, flag_emoji, conversion_enabled
+            FROM supported_currencies
+            WHERE code = $1
+            "#,
+        )
+        .bind(code)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(currency)
+    }
+}
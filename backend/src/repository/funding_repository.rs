@@ -1,10 +1,16 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDateTime, Utc};
 use rust_decimal::Decimal;
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool};
 use uuid::Uuid;
 
-use crate::error::AppResult;
-use crate::models::{FundingPool, Investment};
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    AdminPoolInvestment, FundableInvoiceFilter, FundingPool, Investment, PlatformStats,
+    PortfolioExportRow, TaxSummaryItem,
+};
+use crate::utils::Cursor;
+
+use super::Tx;
 
 #[derive(Clone)]
 pub struct FundingRepository {
@@ -16,6 +22,11 @@ impl FundingRepository {
         Self { pool }
     }
 
+    /// Start a unit of work for writes that span several repositories
+    pub async fn begin(&self) -> AppResult<Tx> {
+        Ok(self.pool.begin().await?)
+    }
+
     pub async fn create_pool(
         &self,
         invoice_id: Uuid,
@@ -25,15 +36,19 @@ impl FundingRepository {
         priority_interest_rate: Decimal,
         catalyst_interest_rate: Decimal,
         deadline: DateTime<Utc>,
+        priority_limits: (Decimal, Decimal),
+        catalyst_limits: (Decimal, Decimal),
     ) -> AppResult<FundingPool> {
         let pool = sqlx::query_as::<_, FundingPool>(
             r#"
             INSERT INTO funding_pools (
                 invoice_id, target_amount, priority_target, catalyst_target,
                 priority_interest_rate, catalyst_interest_rate, deadline,
+                min_invest_ratio, max_invest_ratio,
+                catalyst_min_invest_ratio, catalyst_max_invest_ratio,
                 status, opened_at, pool_currency
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, 'open', NOW(), 'IDRX')
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, 'open', NOW(), 'IDRX')
             RETURNING *
             "#,
         )
@@ -44,6 +59,10 @@ impl FundingRepository {
         .bind(priority_interest_rate)
         .bind(catalyst_interest_rate)
         .bind(deadline)
+        .bind(priority_limits.0)
+        .bind(priority_limits.1)
+        .bind(catalyst_limits.0)
+        .bind(catalyst_limits.1)
         .fetch_one(&self.pool)
         .await?;
 
@@ -59,12 +78,51 @@ impl FundingRepository {
         Ok(pool)
     }
 
+    /// The invoice's current pool: its active one if any, otherwise the most
+    /// recently closed
     pub async fn find_by_invoice(&self, invoice_id: Uuid) -> AppResult<Option<FundingPool>> {
-        let pool =
-            sqlx::query_as::<_, FundingPool>("SELECT * FROM funding_pools WHERE invoice_id = $1")
-                .bind(invoice_id)
-                .fetch_optional(&self.pool)
-                .await?;
+        let pool = sqlx::query_as::<_, FundingPool>(
+            r#"
+            SELECT * FROM funding_pools WHERE invoice_id = $1
+            ORDER BY status = 'closed', created_at DESC, id DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(invoice_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(pool)
+    }
+
+    /// `find_by_invoice` for many invoices in one query, at most one pool per invoice
+    pub async fn find_by_invoices(&self, invoice_ids: &[Uuid]) -> AppResult<Vec<FundingPool>> {
+        if invoice_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let pools = sqlx::query_as::<_, FundingPool>(
+            r#"
+            SELECT DISTINCT ON (invoice_id) * FROM funding_pools
+            WHERE invoice_id = ANY($1)
+            ORDER BY invoice_id, status = 'closed', created_at DESC, id DESC
+            "#,
+        )
+        .bind(invoice_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(pools)
+    }
+
+    /// The invoice's pool that is not closed; at most one exists
+    pub async fn find_active_by_invoice(&self, invoice_id: Uuid) -> AppResult<Option<FundingPool>> {
+        let pool = sqlx::query_as::<_, FundingPool>(
+            "SELECT * FROM funding_pools WHERE invoice_id = $1 AND status <> 'closed'",
+        )
+        .bind(invoice_id)
+        .fetch_optional(&self.pool)
+        .await?;
 
         Ok(pool)
     }
@@ -77,7 +135,7 @@ impl FundingRepository {
         let offset = (page - 1) * per_page;
 
         let pools = sqlx::query_as::<_, FundingPool>(
-            "SELECT * FROM funding_pools WHERE status = 'open' ORDER BY created_at DESC LIMIT $1 OFFSET $2"
+            "SELECT * FROM funding_pools WHERE status = 'open' ORDER BY created_at DESC, id DESC LIMIT $1 OFFSET $2"
         )
         .bind(per_page)
         .bind(offset)
@@ -96,7 +154,7 @@ impl FundingRepository {
         let offset = (page - 1) * per_page;
 
         let pools = sqlx::query_as::<_, FundingPool>(
-            "SELECT * FROM funding_pools ORDER BY created_at DESC LIMIT $1 OFFSET $2",
+            "SELECT * FROM funding_pools ORDER BY created_at DESC, id DESC LIMIT $1 OFFSET $2",
         )
         .bind(per_page)
         .bind(offset)
@@ -110,6 +168,116 @@ impl FundingRepository {
         Ok((pools, total.0))
     }
 
+    /// Marketplace pools narrowed by `filter`. Grade and days to due come from the
+    /// pool's invoice, the yield from the better-paying tranche of the pool and the
+    /// country tier from `country_tiers`, matched by code or by name as for
+    /// fundable invoices.
+    pub async fn find_marketplace(
+        &self,
+        filter: &FundableInvoiceFilter,
+        page: i32,
+        per_page: i32,
+    ) -> AppResult<(Vec<FundingPool>, i64)> {
+        let offset = (page - 1) * per_page;
+
+        let pools = sqlx::query_as::<_, FundingPool>(
+            r#"
+            SELECT p.* FROM funding_pools p
+            JOIN invoices i ON i.id = p.invoice_id
+            LEFT JOIN LATERAL (
+                SELECT tier FROM country_tiers
+                WHERE country_code = UPPER(i.buyer_country)
+                   OR LOWER(country_name) = LOWER(i.buyer_country)
+                ORDER BY country_code = UPPER(i.buyer_country) DESC
+                LIMIT 1
+            ) ct ON true
+            WHERE ($1::varchar IS NULL OR UPPER(i.grade) = UPPER($1))
+              AND ($2::int IS NULL OR ct.tier = $2)
+              AND ($3::numeric IS NULL
+                   OR GREATEST(p.priority_interest_rate, p.catalyst_interest_rate) >= $3)
+              AND ($4::int IS NULL OR i.due_date - CURRENT_DATE >= $4)
+              AND ($5::int IS NULL OR i.due_date - CURRENT_DATE <= $5)
+            ORDER BY p.created_at DESC, p.id DESC
+            LIMIT $6 OFFSET $7
+            "#,
+        )
+        .bind(filter.grade.as_deref())
+        .bind(filter.country_tier)
+        .bind(filter.min_yield)
+        .bind(filter.min_days_remaining)
+        .bind(filter.max_days_remaining)
+        .bind(per_page)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let total: (i64,) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*) FROM funding_pools p
+            JOIN invoices i ON i.id = p.invoice_id
+            LEFT JOIN LATERAL (
+                SELECT tier FROM country_tiers
+                WHERE country_code = UPPER(i.buyer_country)
+                   OR LOWER(country_name) = LOWER(i.buyer_country)
+                ORDER BY country_code = UPPER(i.buyer_country) DESC
+                LIMIT 1
+            ) ct ON true
+            WHERE ($1::varchar IS NULL OR UPPER(i.grade) = UPPER($1))
+              AND ($2::int IS NULL OR ct.tier = $2)
+              AND ($3::numeric IS NULL
+                   OR GREATEST(p.priority_interest_rate, p.catalyst_interest_rate) >= $3)
+              AND ($4::int IS NULL OR i.due_date - CURRENT_DATE >= $4)
+              AND ($5::int IS NULL OR i.due_date - CURRENT_DATE <= $5)
+            "#,
+        )
+        .bind(filter.grade.as_deref())
+        .bind(filter.country_tier)
+        .bind(filter.min_yield)
+        .bind(filter.min_days_remaining)
+        .bind(filter.max_days_remaining)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok((pools, total.0))
+    }
+
+    /// Pools in any state, optionally of one status, soonest deadline first when
+    /// `by_deadline` (pools without one last) and newest first otherwise
+    pub async fn find_all_filtered(
+        &self,
+        status: Option<&str>,
+        by_deadline: bool,
+        page: i32,
+        per_page: i32,
+    ) -> AppResult<(Vec<FundingPool>, i64)> {
+        let offset = (page - 1) * per_page;
+        let order = if by_deadline {
+            "deadline ASC NULLS LAST, id DESC"
+        } else {
+            "created_at DESC, id DESC"
+        };
+
+        let query = format!(
+            "SELECT * FROM funding_pools WHERE ($1::varchar IS NULL OR status = $1) ORDER BY {} LIMIT $2 OFFSET $3",
+            order
+        );
+        let pools = sqlx::query_as::<_, FundingPool>(&query)
+            .bind(status)
+            .bind(per_page)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let total: (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM funding_pools WHERE ($1::varchar IS NULL OR status = $1)",
+        )
+        .bind(status)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok((pools, total.0))
+    }
+
     pub async fn update_status(&self, id: Uuid, status: &str) -> AppResult<FundingPool> {
         let pool = sqlx::query_as::<_, FundingPool>(
             "UPDATE funding_pools SET status = $2, updated_at = NOW() WHERE id = $1 RETURNING *",
@@ -122,28 +290,178 @@ impl FundingRepository {
         Ok(pool)
     }
 
-    pub async fn update_funded_amount(
+    /// Atomically claim `amount` of a tranche. The pool row is locked with
+    /// `SELECT ... FOR UPDATE` so concurrent investors are checked against the
+    /// committed totals one at a time and cannot overfill the tranche.
+    pub async fn reserve_capacity(
         &self,
         id: Uuid,
-        funded_amount: Decimal,
-        priority_funded: Decimal,
-        catalyst_funded: Decimal,
-        investor_count: i32,
+        is_catalyst: bool,
+        amount: Decimal,
     ) -> AppResult<FundingPool> {
+        let mut tx = self.pool.begin().await?;
+        let pool = self
+            .reserve_capacity_tx(&mut tx, id, is_catalyst, amount)
+            .await?;
+        tx.commit().await?;
+
+        Ok(pool)
+    }
+
+    /// `reserve_capacity` inside the caller's transaction. The pool row stays
+    /// locked until that transaction ends.
+    pub async fn reserve_capacity_tx(
+        &self,
+        conn: &mut PgConnection,
+        id: Uuid,
+        is_catalyst: bool,
+        amount: Decimal,
+    ) -> AppResult<FundingPool> {
+        let current = sqlx::query_as::<_, FundingPool>(
+            "SELECT * FROM funding_pools WHERE id = $1 FOR UPDATE",
+        )
+        .bind(id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Pool not found".to_string()))?;
+
+        if current.status != "open" {
+            return Err(AppError::PoolNotOpen);
+        }
+
+        let (tranche, available) = if is_catalyst {
+            ("catalyst", current.catalyst_target - current.catalyst_funded)
+        } else {
+            ("priority", current.priority_target - current.priority_funded)
+        };
+        if amount > available {
+            return Err(AppError::BadRequest(format!(
+                "Only {} available in {} tranche",
+                available, tranche
+            )));
+        }
+
         let pool = sqlx::query_as::<_, FundingPool>(
             r#"
             UPDATE funding_pools
-            SET funded_amount = $2, priority_funded = $3, catalyst_funded = $4,
-                investor_count = $5, updated_at = NOW()
+            SET funded_amount = funded_amount + $2,
+                priority_funded = priority_funded + CASE WHEN $3 THEN 0 ELSE $2 END,
+                catalyst_funded = catalyst_funded + CASE WHEN $3 THEN $2 ELSE 0 END,
+                updated_at = NOW()
             WHERE id = $1
             RETURNING *
             "#,
         )
         .bind(id)
-        .bind(funded_amount)
-        .bind(priority_funded)
-        .bind(catalyst_funded)
+        .bind(amount)
+        .bind(is_catalyst)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(pool)
+    }
+
+    /// Give back capacity claimed by `reserve_capacity` when the investment could not be completed
+    pub async fn release_capacity(
+        &self,
+        id: Uuid,
+        is_catalyst: bool,
+        amount: Decimal,
+    ) -> AppResult<FundingPool> {
+        self.release_capacity_tx(&mut *self.pool.acquire().await?, id, is_catalyst, amount)
+            .await
+    }
+
+    pub async fn release_capacity_tx(
+        &self,
+        conn: &mut PgConnection,
+        id: Uuid,
+        is_catalyst: bool,
+        amount: Decimal,
+    ) -> AppResult<FundingPool> {
+        let pool = sqlx::query_as::<_, FundingPool>(
+            r#"
+            UPDATE funding_pools
+            SET funded_amount = funded_amount - $2,
+                priority_funded = priority_funded - CASE WHEN $3 THEN 0 ELSE $2 END,
+                catalyst_funded = catalyst_funded - CASE WHEN $3 THEN $2 ELSE 0 END,
+                updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(amount)
+        .bind(is_catalyst)
+        .fetch_one(conn)
+        .await?;
+
+        Ok(pool)
+    }
+
+    /// Lock the pool row until the caller's transaction ends
+    pub async fn lock_pool_tx(&self, conn: &mut PgConnection, id: Uuid) -> AppResult<FundingPool> {
+        sqlx::query_as::<_, FundingPool>("SELECT * FROM funding_pools WHERE id = $1 FOR UPDATE")
+            .bind(id)
+            .fetch_optional(conn)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Pool not found".to_string()))
+    }
+
+    pub async fn update_investor_count(&self, id: Uuid, investor_count: i32) -> AppResult<FundingPool> {
+        self.update_investor_count_tx(&mut *self.pool.acquire().await?, id, investor_count)
+            .await
+    }
+
+    pub async fn update_investor_count_tx(
+        &self,
+        conn: &mut PgConnection,
+        id: Uuid,
+        investor_count: i32,
+    ) -> AppResult<FundingPool> {
+        let pool = sqlx::query_as::<_, FundingPool>(
+            "UPDATE funding_pools SET investor_count = $2, updated_at = NOW() WHERE id = $1 RETURNING *",
+        )
+        .bind(id)
         .bind(investor_count)
+        .fetch_one(conn)
+        .await?;
+
+        Ok(pool)
+    }
+
+    pub async fn set_create_pool_tx_hash(&self, id: Uuid, tx_hash: &str) -> AppResult<FundingPool> {
+        let pool = sqlx::query_as::<_, FundingPool>(
+            "UPDATE funding_pools SET create_pool_tx_hash = $2, updated_at = NOW() WHERE id = $1 RETURNING *",
+        )
+        .bind(id)
+        .bind(tx_hash)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(pool)
+    }
+
+    /// Override the limits of both tranches
+    pub async fn set_investment_limits(
+        &self,
+        id: Uuid,
+        min_invest_ratio: Decimal,
+        max_invest_ratio: Decimal,
+    ) -> AppResult<FundingPool> {
+        let pool = sqlx::query_as::<_, FundingPool>(
+            r#"
+            UPDATE funding_pools
+            SET min_invest_ratio = $2, max_invest_ratio = $3,
+                catalyst_min_invest_ratio = $2, catalyst_max_invest_ratio = $3,
+                updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(min_invest_ratio)
+        .bind(max_invest_ratio)
         .fetch_one(&self.pool)
         .await?;
 
@@ -151,16 +469,91 @@ impl FundingRepository {
     }
 
     pub async fn set_filled(&self, id: Uuid) -> AppResult<FundingPool> {
+        self.set_filled_tx(&mut *self.pool.acquire().await?, id)
+            .await
+    }
+
+    pub async fn set_filled_tx(&self, conn: &mut PgConnection, id: Uuid) -> AppResult<FundingPool> {
         let pool = sqlx::query_as::<_, FundingPool>(
             "UPDATE funding_pools SET status = 'filled', filled_at = NOW(), updated_at = NOW() WHERE id = $1 RETURNING *"
         )
         .bind(id)
-        .fetch_one(&self.pool)
+        .fetch_one(conn)
         .await?;
 
         Ok(pool)
     }
 
+    /// Mark an open pool filled once its claimed capacity reaches the target and
+    /// none of its investments is still pending. Returns `None` otherwise.
+    pub async fn set_filled_if_funded_tx(
+        &self,
+        conn: &mut PgConnection,
+        id: Uuid,
+    ) -> AppResult<Option<FundingPool>> {
+        let pool = sqlx::query_as::<_, FundingPool>(
+            r#"
+            UPDATE funding_pools
+            SET status = 'filled', filled_at = NOW(), updated_at = NOW()
+            WHERE id = $1
+              AND status = 'open'
+              AND funded_amount >= target_amount
+              AND NOT EXISTS (
+                  SELECT 1 FROM investments WHERE pool_id = $1 AND status = 'pending'
+              )
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(conn)
+        .await?;
+
+        Ok(pool)
+    }
+
+    /// Stop an open pool taking investments so it can be disbursed early. Only
+    /// succeeds while no investment is pending, so `funded_amount` is final.
+    pub async fn close_for_disbursement_tx(
+        &self,
+        conn: &mut PgConnection,
+        id: Uuid,
+    ) -> AppResult<Option<FundingPool>> {
+        let pool = sqlx::query_as::<_, FundingPool>(
+            r#"
+            UPDATE funding_pools
+            SET status = 'filled', filled_at = NOW(), updated_at = NOW()
+            WHERE id = $1
+              AND status = 'open'
+              AND NOT EXISTS (
+                  SELECT 1 FROM investments WHERE pool_id = $1 AND status = 'pending'
+              )
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(conn)
+        .await?;
+
+        Ok(pool)
+    }
+
+    /// Undo `close_for_disbursement_tx` when the disbursement is refused before
+    /// anything is sent, so the pool takes investments again
+    pub async fn reopen_after_refused_disbursement(&self, id: Uuid) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE funding_pools
+            SET status = 'open', filled_at = NULL, updated_at = NOW()
+            WHERE id = $1 AND status = 'filled'
+            "#,
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
     pub async fn set_disbursed(&self, id: Uuid) -> AppResult<FundingPool> {
         let pool = sqlx::query_as::<_, FundingPool>(
             "UPDATE funding_pools SET status = 'disbursed', disbursed_at = NOW(), updated_at = NOW() WHERE id = $1 RETURNING *"
@@ -202,12 +595,39 @@ impl FundingRepository {
         amount: Decimal,
         expected_return: Decimal,
         tranche: &str,
-        tx_hash: &str,
+        tx_hash: Option<&str>,
+        status: &str,
+    ) -> AppResult<Investment> {
+        self.create_investment_tx(
+            &mut *self.pool.acquire().await?,
+            pool_id,
+            investor_id,
+            amount,
+            expected_return,
+            tranche,
+            tx_hash,
+            status,
+        )
+        .await
+    }
+
+    /// Insert an investment. On-chain investments start out `pending` and are
+    /// confirmed with `confirm_investment_tx` once their funds reached the pool contract.
+    pub async fn create_investment_tx(
+        &self,
+        conn: &mut PgConnection,
+        pool_id: Uuid,
+        investor_id: Uuid,
+        amount: Decimal,
+        expected_return: Decimal,
+        tranche: &str,
+        tx_hash: Option<&str>,
+        status: &str,
     ) -> AppResult<Investment> {
         let investment = sqlx::query_as::<_, Investment>(
             r#"
             INSERT INTO investments (pool_id, investor_id, amount, expected_return, tranche, tx_hash, status)
-            VALUES ($1, $2, $3, $4, $5, $6, 'active')
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
             RETURNING *
             "#,
         )
@@ -217,12 +637,78 @@ impl FundingRepository {
         .bind(expected_return)
         .bind(tranche)
         .bind(tx_hash)
-        .fetch_one(&self.pool)
+        .bind(status)
+        .fetch_one(conn)
         .await?;
 
         Ok(investment)
     }
 
+    /// Activate a pending on-chain investment, keeping the wallet the pool
+    /// contract recorded for it
+    pub async fn confirm_investment_tx(
+        &self,
+        conn: &mut PgConnection,
+        id: Uuid,
+        payout_wallet: &str,
+    ) -> AppResult<Investment> {
+        sqlx::query_as::<_, Investment>(
+            r#"
+            UPDATE investments
+            SET status = 'active', payout_wallet = $2, updated_at = NOW()
+            WHERE id = $1 AND status = 'pending'
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(payout_wallet)
+        .fetch_optional(conn)
+        .await?
+        .ok_or_else(|| AppError::Conflict("Investment is no longer pending".to_string()))
+    }
+
+    /// Store the transfer that forwarded a pending investment's funds to the pool contract
+    pub async fn set_investment_forward_tx(&self, id: Uuid, forward_tx_hash: &str) -> AppResult<()> {
+        sqlx::query("UPDATE investments SET forward_tx_hash = $2, updated_at = NOW() WHERE id = $1")
+            .bind(id)
+            .bind(forward_tx_hash)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Undo a pending investment whose funds never left the platform wallet:
+    /// the row is deleted and its tranche capacity and investor count given back.
+    pub async fn release_pending_investment(
+        &self,
+        investment: &Investment,
+        is_catalyst: bool,
+    ) -> AppResult<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let deleted = sqlx::query("DELETE FROM investments WHERE id = $1 AND status = 'pending'")
+            .bind(investment.id)
+            .execute(&mut *tx)
+            .await?;
+        if deleted.rows_affected() == 0 {
+            return Err(AppError::Conflict(
+                "Investment is no longer pending".to_string(),
+            ));
+        }
+
+        self.release_capacity_tx(&mut tx, investment.pool_id, is_catalyst, investment.amount)
+            .await?;
+        let investor_count = self
+            .count_investors_in_pool_tx(&mut tx, investment.pool_id)
+            .await? as i32;
+        self.update_investor_count_tx(&mut tx, investment.pool_id, investor_count)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
     pub async fn find_investment_by_pool_and_investor(
         &self,
         pool_id: Uuid,
@@ -239,6 +725,16 @@ impl FundingRepository {
         Ok(investment)
     }
 
+    pub async fn find_investment_by_tx_hash(&self, tx_hash: &str) -> AppResult<Option<Investment>> {
+        let investment =
+            sqlx::query_as::<_, Investment>("SELECT * FROM investments WHERE tx_hash = $1")
+                .bind(tx_hash)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(investment)
+    }
+
     pub async fn find_investment_by_id(&self, id: Uuid) -> AppResult<Option<Investment>> {
         let investment = sqlx::query_as::<_, Investment>("SELECT * FROM investments WHERE id = $1")
             .bind(id)
@@ -250,7 +746,67 @@ impl FundingRepository {
 
     pub async fn find_investments_by_pool(&self, pool_id: Uuid) -> AppResult<Vec<Investment>> {
         let investments = sqlx::query_as::<_, Investment>(
-            "SELECT * FROM investments WHERE pool_id = $1 ORDER BY invested_at DESC",
+            "SELECT * FROM investments WHERE pool_id = $1 ORDER BY invested_at DESC, id DESC",
+        )
+        .bind(pool_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(investments)
+    }
+
+    /// Pools that received an on-chain investment (one with a `tx_hash`) since `since`
+    pub async fn find_pools_with_onchain_investments_since(
+        &self,
+        since: NaiveDateTime,
+    ) -> AppResult<Vec<FundingPool>> {
+        let pools = sqlx::query_as::<_, FundingPool>(
+            r#"
+            SELECT * FROM funding_pools p
+            WHERE EXISTS (
+                SELECT 1 FROM investments i
+                WHERE i.pool_id = p.id AND i.tx_hash IS NOT NULL AND i.invested_at >= $1
+            )
+            ORDER BY p.created_at, p.id
+            "#,
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(pools)
+    }
+
+    /// Investments in a pool with investor contact details and the status of
+    /// the transaction recorded under each investment's `tx_hash`
+    pub async fn find_admin_pool_investments(
+        &self,
+        pool_id: Uuid,
+    ) -> AppResult<Vec<AdminPoolInvestment>> {
+        let investments = sqlx::query_as::<_, AdminPoolInvestment>(
+            r#"
+            SELECT
+                i.id AS investment_id, i.investor_id,
+                u.email AS investor_email, u.username AS investor_username,
+                up.full_name AS investor_name,
+                COALESCE(up.phone, u.phone_number) AS investor_phone,
+                u.wallet_address AS investor_wallet,
+                i.tranche, i.amount, i.expected_return, i.actual_return, i.status,
+                i.tx_hash, i.return_tx_hash, i.invested_at, i.repaid_at,
+                t.block_number,
+                COALESCE(t.status = 'confirmed', false) AS tx_verified
+            FROM investments i
+            JOIN users u ON u.id = i.investor_id
+            LEFT JOIN user_profiles up ON up.user_id = i.investor_id
+            LEFT JOIN LATERAL (
+                SELECT status, block_number FROM transactions
+                WHERE tx_hash = i.tx_hash
+                ORDER BY (status = 'confirmed') DESC, created_at
+                LIMIT 1
+            ) t ON true
+            WHERE i.pool_id = $1
+            ORDER BY i.invested_at, i.id
+            "#,
         )
         .bind(pool_id)
         .fetch_all(&self.pool)
@@ -259,20 +815,35 @@ impl FundingRepository {
         Ok(investments)
     }
 
+    /// Investments of an investor, newest first. A `cursor` takes the place of
+    /// `page` and returns the investments after it.
     pub async fn find_investments_by_investor(
         &self,
         investor_id: Uuid,
         page: i32,
         per_page: i32,
+        cursor: Option<Cursor>,
     ) -> AppResult<(Vec<Investment>, i64)> {
-        let offset = (page - 1) * per_page;
+        let offset = if cursor.is_some() {
+            0
+        } else {
+            (page - 1) * per_page
+        };
 
         let investments = sqlx::query_as::<_, Investment>(
-            "SELECT * FROM investments WHERE investor_id = $1 ORDER BY invested_at DESC LIMIT $2 OFFSET $3"
+            r#"
+            SELECT * FROM investments
+            WHERE investor_id = $1
+              AND ($4::timestamp IS NULL OR (invested_at, id) < ($4, $5))
+            ORDER BY invested_at DESC, id DESC
+            LIMIT $2 OFFSET $3
+            "#,
         )
         .bind(investor_id)
         .bind(per_page)
         .bind(offset)
+        .bind(cursor.map(|c| c.at))
+        .bind(cursor.map(|c| c.id))
         .fetch_all(&self.pool)
         .await?;
 
@@ -294,7 +865,7 @@ impl FundingRepository {
         let offset = (page - 1) * per_page;
 
         let investments = sqlx::query_as::<_, Investment>(
-            "SELECT * FROM investments WHERE investor_id = $1 AND status = 'active' ORDER BY invested_at DESC LIMIT $2 OFFSET $3"
+            "SELECT * FROM investments WHERE investor_id = $1 AND status = 'active' ORDER BY invested_at DESC, id DESC LIMIT $2 OFFSET $3"
         )
         .bind(investor_id)
         .bind(per_page)
@@ -347,6 +918,29 @@ impl FundingRepository {
         Ok(investment)
     }
 
+    /// Principal an investor still has outstanding across all pools of one exporter
+    pub async fn sum_active_exposure_to_exporter(
+        &self,
+        investor_id: Uuid,
+        exporter_id: Uuid,
+    ) -> AppResult<Decimal> {
+        let total: (Decimal,) = sqlx::query_as(
+            r#"
+            SELECT COALESCE(SUM(inv.amount), 0)
+            FROM investments inv
+            INNER JOIN funding_pools fp ON inv.pool_id = fp.id
+            INNER JOIN invoices i ON fp.invoice_id = i.id
+            WHERE inv.investor_id = $1 AND i.exporter_id = $2 AND inv.status IN ('pending', 'active')
+            "#,
+        )
+        .bind(investor_id)
+        .bind(exporter_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(total.0)
+    }
+
     pub async fn get_investor_portfolio_stats(
         &self,
         investor_id: Uuid,
@@ -372,21 +966,131 @@ impl FundingRepository {
         Ok(stats)
     }
 
+    /// Aggregates over every pool, or only the pools of `exporter_id` when given
+    pub async fn get_platform_stats(&self, exporter_id: Option<Uuid>) -> AppResult<PlatformStats> {
+        let stats = sqlx::query_as::<_, PlatformStats>(
+            r#"
+            WITH pools AS (
+                SELECT fp.id, fp.status, fp.priority_interest_rate, fp.catalyst_interest_rate,
+                       i.exporter_id
+                FROM funding_pools fp
+                INNER JOIN invoices i ON fp.invoice_id = i.id
+                WHERE $1::uuid IS NULL OR i.exporter_id = $1
+            ),
+            pool_investments AS (
+                SELECT inv.investor_id, inv.amount, inv.actual_return, inv.status,
+                       CASE WHEN inv.tranche = 'catalyst' THEN p.catalyst_interest_rate
+                            ELSE p.priority_interest_rate END AS interest_rate
+                FROM investments inv
+                INNER JOIN pools p ON inv.pool_id = p.id
+            )
+            SELECT
+                (SELECT COALESCE(SUM(amount), 0) FROM pool_investments)::FLOAT8 AS total_funded,
+                (SELECT COALESCE(SUM(actual_return), 0) FROM pool_investments
+                 WHERE status = 'repaid')::FLOAT8 AS total_repaid,
+                (SELECT COUNT(*) FROM pools
+                 WHERE status IN ('open', 'filled', 'disbursed'))::INT AS active_pools,
+                (SELECT COUNT(DISTINCT investor_id) FROM pool_investments)::INT AS total_investors,
+                (SELECT COUNT(DISTINCT exporter_id) FROM pools)::INT AS total_exporters,
+                (SELECT COALESCE(SUM(amount * interest_rate) / NULLIF(SUM(amount), 0), 0)
+                 FROM pool_investments WHERE interest_rate IS NOT NULL)::FLOAT8 AS average_yield,
+                (SELECT COALESCE(
+                     SUM(amount) FILTER (WHERE status = 'defaulted') * 100
+                         / NULLIF(SUM(amount) FILTER (WHERE status IN ('repaid', 'defaulted')), 0),
+                     0)
+                 FROM pool_investments)::FLOAT8 AS default_rate
+            "#,
+        )
+        .bind(exporter_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(stats)
+    }
+
+    /// All investments of an investor joined with their invoice, for CSV export
+    pub async fn find_portfolio_export_rows(
+        &self,
+        investor_id: Uuid,
+    ) -> AppResult<Vec<PortfolioExportRow>> {
+        let rows = sqlx::query_as::<_, PortfolioExportRow>(
+            r#"
+            SELECT
+                i.invoice_number, inv.tranche, inv.amount AS principal, inv.expected_return,
+                inv.actual_return, inv.invested_at, inv.repaid_at, inv.status,
+                inv.tx_hash, inv.return_tx_hash
+            FROM investments inv
+            INNER JOIN funding_pools fp ON inv.pool_id = fp.id
+            INNER JOIN invoices i ON fp.invoice_id = i.id
+            WHERE inv.investor_id = $1
+            ORDER BY inv.invested_at DESC, inv.id DESC
+            "#,
+        )
+        .bind(investor_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// An investor's repaid investments whose repayment falls in `year`
+    pub async fn find_repaid_in_year(
+        &self,
+        investor_id: Uuid,
+        year: i32,
+    ) -> AppResult<Vec<TaxSummaryItem>> {
+        let rows = sqlx::query_as::<_, TaxSummaryItem>(
+            r#"
+            SELECT
+                inv.id AS investment_id, i.invoice_number, inv.tranche,
+                inv.amount AS principal,
+                COALESCE(inv.actual_return, inv.expected_return, inv.amount) AS realized_return,
+                COALESCE(inv.actual_return, inv.expected_return, inv.amount) - inv.amount AS gain,
+                inv.invested_at, inv.repaid_at
+            FROM investments inv
+            INNER JOIN funding_pools fp ON inv.pool_id = fp.id
+            INNER JOIN invoices i ON fp.invoice_id = i.id
+            WHERE inv.investor_id = $1
+              AND inv.status = 'repaid'
+              AND inv.repaid_at >= make_date($2, 1, 1)
+              AND inv.repaid_at < make_date($2 + 1, 1, 1)
+            ORDER BY inv.repaid_at, inv.id
+            "#,
+        )
+        .bind(investor_id)
+        .bind(year)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
     pub async fn count_investors_in_pool(&self, pool_id: Uuid) -> AppResult<i64> {
+        self.count_investors_in_pool_tx(&mut *self.pool.acquire().await?, pool_id)
+            .await
+    }
+
+    pub async fn count_investors_in_pool_tx(
+        &self,
+        conn: &mut PgConnection,
+        pool_id: Uuid,
+    ) -> AppResult<i64> {
         let count: (i64,) = sqlx::query_as(
             "SELECT COUNT(DISTINCT investor_id) FROM investments WHERE pool_id = $1",
         )
         .bind(pool_id)
-        .fetch_one(&self.pool)
+        .fetch_one(conn)
         .await?;
 
         Ok(count.0)
     }
 
-    /// Find all funding pools for a specific exporter (mitra) by joining with invoices
+    /// Find all funding pools for a specific exporter (mitra) by joining with invoices.
+    /// Pools of soft-deleted invoices are only included when `include_deleted_invoices` is set.
     pub async fn find_by_exporter(
         &self,
         exporter_id: Uuid,
+        include_deleted_invoices: bool,
         page: i32,
         per_page: i32,
     ) -> AppResult<(Vec<FundingPool>, i64)> {
@@ -396,12 +1100,13 @@ impl FundingRepository {
             r#"
             SELECT fp.* FROM funding_pools fp
             INNER JOIN invoices i ON fp.invoice_id = i.id
-            WHERE i.exporter_id = $1
-            ORDER BY fp.created_at DESC
-            LIMIT $2 OFFSET $3
+            WHERE i.exporter_id = $1 AND ($2 OR i.deleted_at IS NULL)
+            ORDER BY fp.created_at DESC, fp.id DESC
+            LIMIT $3 OFFSET $4
             "#,
         )
         .bind(exporter_id)
+        .bind(include_deleted_invoices)
         .bind(per_page)
         .bind(offset)
         .fetch_all(&self.pool)
@@ -411,10 +1116,11 @@ impl FundingRepository {
             r#"
             SELECT COUNT(*) FROM funding_pools fp
             INNER JOIN invoices i ON fp.invoice_id = i.id
-            WHERE i.exporter_id = $1
+            WHERE i.exporter_id = $1 AND ($2 OR i.deleted_at IS NULL)
             "#,
         )
         .bind(exporter_id)
+        .bind(include_deleted_invoices)
         .fetch_one(&self.pool)
         .await?;
 
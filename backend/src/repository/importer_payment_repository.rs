@@ -3,8 +3,8 @@ use rust_decimal::Decimal;
 use sqlx::PgPool;
 use uuid::Uuid;
 
-use crate::error::AppResult;
-use crate::models::ImporterPayment;
+use crate::error::{AppError, AppResult};
+use crate::models::{BuyerPaymentSummary, IdempotencyKey, ImporterPayment};
 
 #[derive(Clone)]
 pub struct ImporterPaymentRepository {
@@ -78,27 +78,36 @@ impl ImporterPaymentRepository {
         Ok(payment)
     }
 
-    pub async fn update_payment(
+    /// Record a payment that covers the outstanding amount and mark it paid.
+    /// `credit` is the part of `amount_paid` beyond what was due. Only a payable
+    /// payment is settled, so a concurrent second settlement is a conflict.
+    pub async fn settle_payment(
         &self,
         id: Uuid,
         amount_paid: Decimal,
+        credit: Decimal,
         tx_hash: &str,
+        payer_wallet: &str,
     ) -> AppResult<ImporterPayment> {
         let payment = sqlx::query_as::<_, ImporterPayment>(
             r#"
             UPDATE importer_payments
-            SET amount_paid = amount_paid + $2, tx_hash = $3, updated_at = NOW()
-            WHERE id = $1
+            SET amount_paid = amount_paid + $2, credit_amount = credit_amount + $3,
+                tx_hash = $4, payer_wallet = $5, payment_status = 'paid', paid_at = NOW(),
+                updated_at = NOW()
+            WHERE id = $1 AND payment_status IN ('pending', 'overdue')
             RETURNING *
             "#,
         )
         .bind(id)
         .bind(amount_paid)
+        .bind(credit)
         .bind(tx_hash)
-        .fetch_one(&self.pool)
+        .bind(payer_wallet)
+        .fetch_optional(&self.pool)
         .await?;
 
-        Ok(payment)
+        payment.ok_or_else(|| AppError::Conflict("Payment has already been settled".to_string()))
     }
 
     pub async fn mark_paid(&self, id: Uuid, tx_hash: &str) -> AppResult<ImporterPayment> {
@@ -118,12 +127,13 @@ impl ImporterPaymentRepository {
         Ok(payment)
     }
 
-    pub async fn mark_overdue(&self, id: Uuid) -> AppResult<ImporterPayment> {
+    /// Flip a still-pending payment to overdue; `None` if it was settled in the meantime
+    pub async fn mark_overdue(&self, id: Uuid) -> AppResult<Option<ImporterPayment>> {
         let payment = sqlx::query_as::<_, ImporterPayment>(
-            "UPDATE importer_payments SET payment_status = 'overdue', updated_at = NOW() WHERE id = $1 RETURNING *"
+            "UPDATE importer_payments SET payment_status = 'overdue', updated_at = NOW() WHERE id = $1 AND payment_status = 'pending' RETURNING *"
         )
         .bind(id)
-        .fetch_one(&self.pool)
+        .fetch_optional(&self.pool)
         .await?;
 
         Ok(payment)
@@ -131,11 +141,186 @@ impl ImporterPaymentRepository {
 
     pub async fn find_pending_overdue(&self) -> AppResult<Vec<ImporterPayment>> {
         let payments = sqlx::query_as::<_, ImporterPayment>(
-            "SELECT * FROM importer_payments WHERE payment_status = 'pending' AND due_date < NOW()",
+            "SELECT * FROM importer_payments WHERE payment_status = 'pending' AND due_date < NOW() ORDER BY due_date, id",
         )
         .fetch_all(&self.pool)
         .await?;
 
         Ok(payments)
     }
+
+    /// All payments owed by a buyer email (case-insensitive) across mitras, soonest due first
+    pub async fn find_by_buyer_email(&self, email: &str) -> AppResult<Vec<BuyerPaymentSummary>> {
+        let payments = sqlx::query_as::<_, BuyerPaymentSummary>(
+            r#"
+            SELECT p.id AS payment_id, p.invoice_id, i.invoice_number, p.buyer_name,
+                   p.amount_due, COALESCE(p.amount_paid, 0) AS amount_paid,
+                   GREATEST(p.amount_due - COALESCE(p.amount_paid, 0), 0) AS remaining_amount,
+                   p.credit_amount, COALESCE(p.currency, 'IDRX') AS currency,
+                   COALESCE(p.payment_status, 'pending') AS payment_status, p.due_date, p.paid_at
+            FROM importer_payments p
+            JOIN invoices i ON i.id = p.invoice_id
+            WHERE LOWER(p.buyer_email) = LOWER($1)
+            ORDER BY p.due_date ASC, p.id
+            "#,
+        )
+        .bind(email)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(payments)
+    }
+
+    pub async fn buyer_has_payments(&self, email: &str) -> AppResult<bool> {
+        let exists: (bool,) = sqlx::query_as(
+            "SELECT EXISTS(SELECT 1 FROM importer_payments WHERE LOWER(buyer_email) = LOWER($1))",
+        )
+        .bind(email)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(exists.0)
+    }
+
+    // Buyer access token methods
+
+    pub async fn create_access_token(
+        &self,
+        email: &str,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> AppResult<()> {
+        sqlx::query(
+            "INSERT INTO importer_access_tokens (buyer_email, token_hash, expires_at) VALUES (LOWER($1), $2, $3)",
+        )
+        .bind(email)
+        .bind(token_hash)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Mark a live access token for `email` as used. Returns false if it does not
+    /// exist, has expired or was already used, so each link works exactly once.
+    pub async fn consume_access_token(&self, email: &str, token_hash: &str) -> AppResult<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE importer_access_tokens
+            SET used_at = NOW()
+            WHERE buyer_email = LOWER($1) AND token_hash = $2
+              AND used_at IS NULL AND expires_at > NOW()
+            "#,
+        )
+        .bind(email)
+        .bind(token_hash)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    // Idempotency methods
+
+    /// Find an idempotency key for a payment that is still within the TTL
+    pub async fn find_idempotency_key(
+        &self,
+        payment_id: Uuid,
+        key: &str,
+        ttl_hours: i64,
+    ) -> AppResult<Option<IdempotencyKey>> {
+        let record = sqlx::query_as::<_, IdempotencyKey>(
+            r#"
+            SELECT * FROM idempotency_keys
+            WHERE payment_id = $1 AND idempotency_key = $2
+              AND created_at > NOW() - make_interval(hours => $3)
+            "#,
+        )
+        .bind(payment_id)
+        .bind(key)
+        .bind(ttl_hours as i32)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(record)
+    }
+
+    /// Reserve the key for a request before it is processed. Returns false if the
+    /// key is already held, in progress or completed, within the TTL; an expired
+    /// entry is replaced.
+    pub async fn reserve_idempotency_key(
+        &self,
+        payment_id: Uuid,
+        key: &str,
+        request_fingerprint: &str,
+        ttl_hours: i64,
+    ) -> AppResult<bool> {
+        sqlx::query(
+            r#"
+            DELETE FROM idempotency_keys
+            WHERE payment_id = $1 AND idempotency_key = $2
+              AND created_at <= NOW() - make_interval(hours => $3)
+            "#,
+        )
+        .bind(payment_id)
+        .bind(key)
+        .bind(ttl_hours as i32)
+        .execute(&self.pool)
+        .await?;
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO idempotency_keys
+                (payment_id, idempotency_key, request_fingerprint, response_body, status)
+            VALUES ($1, $2, $3, '', 'in_progress')
+            ON CONFLICT (payment_id, idempotency_key) DO NOTHING
+            "#,
+        )
+        .bind(payment_id)
+        .bind(key)
+        .bind(request_fingerprint)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Store the response of a reserved key's request
+    pub async fn complete_idempotency_key(
+        &self,
+        payment_id: Uuid,
+        key: &str,
+        response_body: &str,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE idempotency_keys SET response_body = $3, status = 'completed'
+            WHERE payment_id = $1 AND idempotency_key = $2 AND status = 'in_progress'
+            "#,
+        )
+        .bind(payment_id)
+        .bind(key)
+        .bind(response_body)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Drop a reservation whose request failed, so the key can be retried
+    pub async fn release_idempotency_key(&self, payment_id: Uuid, key: &str) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            DELETE FROM idempotency_keys
+            WHERE payment_id = $1 AND idempotency_key = $2 AND status = 'in_progress'
+            "#,
+        )
+        .bind(payment_id)
+        .bind(key)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
 }
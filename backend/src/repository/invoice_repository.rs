@@ -1,10 +1,13 @@
 use chrono::NaiveDate;
 use rust_decimal::Decimal;
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool, Postgres, Transaction};
 use uuid::Uuid;
 
-use crate::error::AppResult;
-use crate::models::{Invoice, InvoiceDocument, InvoiceNft};
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    FundableInvoiceFilter, Invoice, InvoiceDocument, InvoiceNft, UserInvoiceStats,
+};
+use crate::utils::Cursor;
 
 #[derive(Clone)]
 pub struct InvoiceRepository {
@@ -76,48 +79,58 @@ impl InvoiceRepository {
         Ok(invoice)
     }
 
+    /// Invoices of an exporter. Soft-deleted invoices are only returned when
+    /// `include_deleted` is set (admin audit views). A `cursor` takes the place
+    /// of `page` and returns the invoices after it.
     pub async fn find_by_exporter(
         &self,
         exporter_id: Uuid,
         status: Option<String>,
+        include_deleted: bool,
         page: i32,
         per_page: i32,
+        cursor: Option<Cursor>,
     ) -> AppResult<(Vec<Invoice>, i64)> {
-        let offset = (page - 1) * per_page;
-
-        let invoices = if let Some(ref s) = status {
-            sqlx::query_as::<_, Invoice>(
-                "SELECT * FROM invoices WHERE exporter_id = $1 AND status = $2 ORDER BY created_at DESC LIMIT $3 OFFSET $4"
-            )
-            .bind(exporter_id)
-            .bind(s)
-            .bind(per_page)
-            .bind(offset)
-            .fetch_all(&self.pool)
-            .await?
+        let offset = if cursor.is_some() {
+            0
         } else {
-            sqlx::query_as::<_, Invoice>(
-                "SELECT * FROM invoices WHERE exporter_id = $1 ORDER BY created_at DESC LIMIT $2 OFFSET $3"
-            )
-            .bind(exporter_id)
-            .bind(per_page)
-            .bind(offset)
-            .fetch_all(&self.pool)
-            .await?
+            (page - 1) * per_page
         };
 
-        let total: (i64,) = if let Some(ref s) = status {
-            sqlx::query_as("SELECT COUNT(*) FROM invoices WHERE exporter_id = $1 AND status = $2")
-                .bind(exporter_id)
-                .bind(s)
-                .fetch_one(&self.pool)
-                .await?
-        } else {
-            sqlx::query_as("SELECT COUNT(*) FROM invoices WHERE exporter_id = $1")
-                .bind(exporter_id)
-                .fetch_one(&self.pool)
-                .await?
-        };
+        let invoices = sqlx::query_as::<_, Invoice>(
+            r#"
+            SELECT * FROM invoices
+            WHERE exporter_id = $1
+              AND ($2::varchar IS NULL OR status = $2)
+              AND ($3 OR deleted_at IS NULL)
+              AND ($6::timestamp IS NULL OR (created_at, id) < ($6, $7))
+            ORDER BY created_at DESC, id DESC
+            LIMIT $4 OFFSET $5
+            "#,
+        )
+        .bind(exporter_id)
+        .bind(status.as_deref())
+        .bind(include_deleted)
+        .bind(per_page)
+        .bind(offset)
+        .bind(cursor.map(|c| c.at))
+        .bind(cursor.map(|c| c.id))
+        .fetch_all(&self.pool)
+        .await?;
+
+        let total: (i64,) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*) FROM invoices
+            WHERE exporter_id = $1
+              AND ($2::varchar IS NULL OR status = $2)
+              AND ($3 OR deleted_at IS NULL)
+            "#,
+        )
+        .bind(exporter_id)
+        .bind(status.as_deref())
+        .bind(include_deleted)
+        .fetch_one(&self.pool)
+        .await?;
 
         Ok((invoices, total.0))
     }
@@ -131,7 +144,7 @@ impl InvoiceRepository {
         let offset = (page - 1) * per_page;
 
         let invoices = sqlx::query_as::<_, Invoice>(
-            "SELECT * FROM invoices WHERE status = $1 ORDER BY created_at DESC LIMIT $2 OFFSET $3",
+            "SELECT * FROM invoices WHERE status = $1 AND deleted_at IS NULL ORDER BY created_at DESC, id DESC LIMIT $2 OFFSET $3",
         )
         .bind(status)
         .bind(per_page)
@@ -139,28 +152,89 @@ impl InvoiceRepository {
         .fetch_all(&self.pool)
         .await?;
 
-        let total: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM invoices WHERE status = $1")
-            .bind(status)
-            .fetch_one(&self.pool)
-            .await?;
+        let total: (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM invoices WHERE status = $1 AND deleted_at IS NULL",
+        )
+        .bind(status)
+        .fetch_one(&self.pool)
+        .await?;
 
         Ok((invoices, total.0))
     }
 
-    pub async fn find_fundable(&self, page: i32, per_page: i32) -> AppResult<(Vec<Invoice>, i64)> {
+    /// Invoices open for funding, narrowed by `filter`. Grades match
+    /// case-insensitively and the buyer country is matched against
+    /// `country_tiers` by code or by name, preferring the code match so an
+    /// invoice is never returned once per matching tier row.
+    pub async fn find_fundable(
+        &self,
+        filter: &FundableInvoiceFilter,
+        page: i32,
+        per_page: i32,
+    ) -> AppResult<(Vec<Invoice>, i64)> {
         let offset = (page - 1) * per_page;
 
         let invoices = sqlx::query_as::<_, Invoice>(
-            "SELECT * FROM invoices WHERE status IN ('approved', 'tokenized', 'funding') ORDER BY created_at DESC LIMIT $1 OFFSET $2"
+            r#"
+            SELECT i.* FROM invoices i
+            LEFT JOIN LATERAL (
+                SELECT tier FROM country_tiers
+                WHERE country_code = UPPER(i.buyer_country)
+                   OR LOWER(country_name) = LOWER(i.buyer_country)
+                ORDER BY country_code = UPPER(i.buyer_country) DESC
+                LIMIT 1
+            ) ct ON true
+            WHERE i.status IN ('approved', 'tokenized', 'funding')
+              AND i.deleted_at IS NULL
+              AND ($1::varchar IS NULL OR UPPER(i.grade) = UPPER($1))
+              AND ($2::int IS NULL OR ct.tier = $2)
+              AND ($3::numeric IS NULL OR GREATEST(
+                    COALESCE(i.priority_interest_rate, 0),
+                    COALESCE(i.catalyst_interest_rate, 0)
+                  ) >= $3)
+              AND ($4::int IS NULL OR i.due_date - CURRENT_DATE >= $4)
+              AND ($5::int IS NULL OR i.due_date - CURRENT_DATE <= $5)
+            ORDER BY i.created_at DESC, i.id DESC
+            LIMIT $6 OFFSET $7
+            "#,
         )
+        .bind(filter.grade.as_deref())
+        .bind(filter.country_tier)
+        .bind(filter.min_yield)
+        .bind(filter.min_days_remaining)
+        .bind(filter.max_days_remaining)
         .bind(per_page)
         .bind(offset)
         .fetch_all(&self.pool)
         .await?;
 
         let total: (i64,) = sqlx::query_as(
-            "SELECT COUNT(*) FROM invoices WHERE status IN ('approved', 'tokenized', 'funding')",
+            r#"
+            SELECT COUNT(*) FROM invoices i
+            LEFT JOIN LATERAL (
+                SELECT tier FROM country_tiers
+                WHERE country_code = UPPER(i.buyer_country)
+                   OR LOWER(country_name) = LOWER(i.buyer_country)
+                ORDER BY country_code = UPPER(i.buyer_country) DESC
+                LIMIT 1
+            ) ct ON true
+            WHERE i.status IN ('approved', 'tokenized', 'funding')
+              AND i.deleted_at IS NULL
+              AND ($1::varchar IS NULL OR UPPER(i.grade) = UPPER($1))
+              AND ($2::int IS NULL OR ct.tier = $2)
+              AND ($3::numeric IS NULL OR GREATEST(
+                    COALESCE(i.priority_interest_rate, 0),
+                    COALESCE(i.catalyst_interest_rate, 0)
+                  ) >= $3)
+              AND ($4::int IS NULL OR i.due_date - CURRENT_DATE >= $4)
+              AND ($5::int IS NULL OR i.due_date - CURRENT_DATE <= $5)
+            "#,
         )
+        .bind(filter.grade.as_deref())
+        .bind(filter.country_tier)
+        .bind(filter.min_yield)
+        .bind(filter.min_days_remaining)
+        .bind(filter.max_days_remaining)
         .fetch_one(&self.pool)
         .await?;
 
@@ -168,11 +242,83 @@ impl InvoiceRepository {
     }
 
     pub async fn update_status(&self, id: Uuid, status: &str) -> AppResult<Invoice> {
+        self.update_status_tx(&mut *self.pool.acquire().await?, id, status)
+            .await
+    }
+
+    pub async fn update_status_tx(
+        &self,
+        conn: &mut PgConnection,
+        id: Uuid,
+        status: &str,
+    ) -> AppResult<Invoice> {
         let invoice = sqlx::query_as::<_, Invoice>(
             "UPDATE invoices SET status = $2, updated_at = NOW() WHERE id = $1 RETURNING *",
         )
         .bind(id)
         .bind(status)
+        .fetch_one(conn)
+        .await?;
+
+        Ok(invoice)
+    }
+
+    /// Move funded or disbursed invoices whose pool has been disbursed and that
+    /// are due on or before `as_of` to `matured`. Returns the invoices that changed.
+    pub async fn mark_matured(&self, as_of: NaiveDate) -> AppResult<Vec<Invoice>> {
+        let invoices = sqlx::query_as::<_, Invoice>(
+            r#"
+            UPDATE invoices i SET status = 'matured', updated_at = NOW()
+            WHERE i.status IN ('funded', 'disbursed') AND i.due_date <= $1 AND i.deleted_at IS NULL
+              AND EXISTS (
+                  SELECT 1 FROM funding_pools p
+                  WHERE p.invoice_id = i.id AND p.status = 'disbursed'
+              )
+            RETURNING i.*
+            "#,
+        )
+        .bind(as_of)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(invoices)
+    }
+
+    /// Matured invoices that fell due before `due_before`, oldest first
+    pub async fn find_matured_due_before(&self, due_before: NaiveDate) -> AppResult<Vec<Invoice>> {
+        let invoices = sqlx::query_as::<_, Invoice>(
+            r#"
+            SELECT * FROM invoices
+            WHERE status = 'matured' AND due_date < $1 AND deleted_at IS NULL
+            ORDER BY due_date, id
+            "#,
+        )
+        .bind(due_before)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(invoices)
+    }
+
+    pub async fn set_payment_link(&self, id: Uuid, payment_link: &str) -> AppResult<Invoice> {
+        let invoice = sqlx::query_as::<_, Invoice>(
+            "UPDATE invoices SET payment_link = $2, updated_at = NOW() WHERE id = $1 RETURNING *",
+        )
+        .bind(id)
+        .bind(payment_link)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(invoice)
+    }
+
+    /// Record the advance actually paid to the mitra on disbursement
+    pub async fn set_advance_amount(&self, id: Uuid, amount: Decimal) -> AppResult<Invoice> {
+        let invoice = sqlx::query_as::<_, Invoice>(
+            "UPDATE invoices SET advance_amount = $2, updated_at = NOW() WHERE id = $1 RETURNING *",
+        )
+        .bind(id)
+        .bind(amount)
         .fetch_one(&self.pool)
         .await?;
 
@@ -216,16 +362,20 @@ impl InvoiceRepository {
         Ok(invoice)
     }
 
+    /// `blended_rate` is stored in the legacy `interest_rate` column, see
+    /// `Invoice::blend_interest_rates`
     pub async fn update_interest_rates(
         &self,
         id: Uuid,
         priority_rate: Decimal,
         catalyst_rate: Decimal,
+        blended_rate: Decimal,
     ) -> AppResult<Invoice> {
         let invoice = sqlx::query_as::<_, Invoice>(
             r#"
             UPDATE invoices
-            SET priority_interest_rate = $2, catalyst_interest_rate = $3, updated_at = NOW()
+            SET priority_interest_rate = $2, catalyst_interest_rate = $3, interest_rate = $4,
+                updated_at = NOW()
             WHERE id = $1
             RETURNING *
             "#,
@@ -233,6 +383,7 @@ impl InvoiceRepository {
         .bind(id)
         .bind(priority_rate)
         .bind(catalyst_rate)
+        .bind(blended_rate)
         .fetch_one(&self.pool)
         .await?;
 
@@ -251,6 +402,44 @@ impl InvoiceRepository {
         Ok(invoice)
     }
 
+    /// Soft-delete an invoice. Refused while it has an open, filled or disbursed pool;
+    /// the check is part of the UPDATE so a pool created concurrently cannot slip through.
+    pub async fn soft_delete(&self, id: Uuid) -> AppResult<Invoice> {
+        let invoice = sqlx::query_as::<_, Invoice>(
+            r#"
+            UPDATE invoices
+            SET deleted_at = NOW(), updated_at = NOW()
+            WHERE id = $1
+              AND deleted_at IS NULL
+              AND NOT EXISTS (
+                  SELECT 1 FROM funding_pools
+                  WHERE invoice_id = $1 AND status IN ('open', 'filled', 'disbursed')
+              )
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match invoice {
+            Some(invoice) => Ok(invoice),
+            None => {
+                let existing = self
+                    .find_by_id(id)
+                    .await?
+                    .ok_or_else(|| AppError::NotFound("Invoice not found".to_string()))?;
+                if existing.deleted_at.is_some() {
+                    Err(AppError::NotFound("Invoice not found".to_string()))
+                } else {
+                    Err(AppError::Conflict(
+                        "Invoice has an active funding pool and cannot be deleted".to_string(),
+                    ))
+                }
+            }
+        }
+    }
+
     pub async fn delete(&self, id: Uuid) -> AppResult<()> {
         sqlx::query("DELETE FROM invoices WHERE id = $1")
             .bind(id)
@@ -272,7 +461,42 @@ impl InvoiceRepository {
         Ok(count.0)
     }
 
+    /// Invoice counts and total face value of an exporter, soft-deleted included
+    pub async fn get_exporter_invoice_stats(
+        &self,
+        exporter_id: Uuid,
+    ) -> AppResult<UserInvoiceStats> {
+        let stats = sqlx::query_as::<_, UserInvoiceStats>(
+            r#"
+            SELECT
+                COUNT(*) AS total_count,
+                COUNT(*) FILTER (WHERE status IN ('approved', 'tokenized', 'funding', 'funded', 'matured')) AS active_count,
+                COUNT(*) FILTER (WHERE status = 'repaid') AS repaid_count,
+                COALESCE(SUM(amount), 0)::FLOAT8 AS total_amount
+            FROM invoices
+            WHERE exporter_id = $1
+            "#,
+        )
+        .bind(exporter_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(stats)
+    }
+
     // Document methods
+    pub async fn update_document_hash(&self, id: Uuid, document_hash: &str) -> AppResult<Invoice> {
+        let invoice = sqlx::query_as::<_, Invoice>(
+            "UPDATE invoices SET document_hash = $2, updated_at = NOW() WHERE id = $1 RETURNING *",
+        )
+        .bind(id)
+        .bind(document_hash)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(invoice)
+    }
+
     pub async fn create_document(
         &self,
         invoice_id: Uuid,
@@ -306,7 +530,7 @@ impl InvoiceRepository {
         invoice_id: Uuid,
     ) -> AppResult<Vec<InvoiceDocument>> {
         let docs = sqlx::query_as::<_, InvoiceDocument>(
-            "SELECT * FROM invoice_documents WHERE invoice_id = $1 ORDER BY uploaded_at DESC",
+            "SELECT * FROM invoice_documents WHERE invoice_id = $1 ORDER BY uploaded_at DESC, id DESC",
         )
         .bind(invoice_id)
         .fetch_all(&self.pool)
@@ -315,6 +539,22 @@ impl InvoiceRepository {
         Ok(docs)
     }
 
+    pub async fn find_document(
+        &self,
+        invoice_id: Uuid,
+        document_id: Uuid,
+    ) -> AppResult<Option<InvoiceDocument>> {
+        let doc = sqlx::query_as::<_, InvoiceDocument>(
+            "SELECT * FROM invoice_documents WHERE id = $1 AND invoice_id = $2",
+        )
+        .bind(document_id)
+        .bind(invoice_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(doc)
+    }
+
     // NFT methods
     pub async fn create_nft(
         &self,
@@ -346,6 +586,31 @@ impl InvoiceRepository {
         Ok(nft)
     }
 
+    /// Serialize approval and tokenization of one invoice. Takes a
+    /// transaction-scoped advisory lock keyed on the invoice id; it is held until
+    /// `release_approval_lock` (or until the returned transaction is dropped).
+    pub async fn lock_approval(
+        &self,
+        invoice_id: Uuid,
+    ) -> AppResult<Transaction<'static, Postgres>> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+            .bind(format!("invoice_approval:{}", invoice_id))
+            .execute(&mut *tx)
+            .await?;
+
+        Ok(tx)
+    }
+
+    pub async fn release_approval_lock(
+        &self,
+        lock: Transaction<'static, Postgres>,
+    ) -> AppResult<()> {
+        lock.commit().await?;
+        Ok(())
+    }
+
     pub async fn find_nft_by_invoice(&self, invoice_id: Uuid) -> AppResult<Option<InvoiceNft>> {
         let nft =
             sqlx::query_as::<_, InvoiceNft>("SELECT * FROM invoice_nfts WHERE invoice_id = $1")
@@ -355,4 +620,33 @@ impl InvoiceRepository {
 
         Ok(nft)
     }
+
+    /// CID of metadata previously pinned with this content hash
+    pub async fn find_cached_metadata_cid(&self, content_hash: &str) -> AppResult<Option<String>> {
+        let cid: Option<(String,)> =
+            sqlx::query_as("SELECT cid FROM nft_metadata_cache WHERE content_hash = $1")
+                .bind(content_hash)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(cid.map(|c| c.0))
+    }
+
+    /// Remember a pinned CID. A concurrent upload of the same content may have
+    /// cached it first; either CID points at identical content, so the first one wins.
+    pub async fn cache_metadata_cid(&self, content_hash: &str, cid: &str) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO nft_metadata_cache (content_hash, cid)
+            VALUES ($1, $2)
+            ON CONFLICT (content_hash) DO NOTHING
+            "#,
+        )
+        .bind(content_hash)
+        .bind(cid)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
 }
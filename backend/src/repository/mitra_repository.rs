@@ -85,7 +85,7 @@ impl MitraRepository {
         let offset = (page - 1) * per_page;
 
         let apps = sqlx::query_as::<_, MitraApplication>(
-            "SELECT * FROM mitra_applications WHERE status = 'pending' ORDER BY created_at ASC LIMIT $1 OFFSET $2"
+            "SELECT * FROM mitra_applications WHERE status = 'pending' ORDER BY created_at ASC, id ASC LIMIT $1 OFFSET $2"
         )
         .bind(per_page)
         .bind(offset)
@@ -108,7 +108,7 @@ impl MitraRepository {
         let offset = (page - 1) * per_page;
 
         let apps = sqlx::query_as::<_, MitraApplication>(
-            "SELECT * FROM mitra_applications ORDER BY created_at DESC LIMIT $1 OFFSET $2",
+            "SELECT * FROM mitra_applications ORDER BY created_at DESC, id DESC LIMIT $1 OFFSET $2",
         )
         .bind(per_page)
         .bind(offset)
@@ -191,4 +191,29 @@ impl MitraRepository {
 
         Ok(app)
     }
+
+    /// Revoke an approved application. `None` when it is no longer approved,
+    /// e.g. a concurrent revocation won.
+    pub async fn revoke(
+        &self,
+        id: Uuid,
+        reviewed_by: Uuid,
+        reason: &str,
+    ) -> AppResult<Option<MitraApplication>> {
+        let app = sqlx::query_as::<_, MitraApplication>(
+            r#"
+            UPDATE mitra_applications
+            SET status = 'revoked', reviewed_by = $2, reviewed_at = NOW(), rejection_reason = $3, updated_at = NOW()
+            WHERE id = $1 AND status = 'approved'
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(reviewed_by)
+        .bind(reason)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(app)
+    }
 }
@@ -1,19 +1,29 @@
 #![allow(dead_code)]
 
+mod activity_repository;
+mod currency_repository;
 mod funding_repository;
 mod importer_payment_repository;
 mod invoice_repository;
 mod mitra_repository;
+mod notification_repository;
 mod otp_repository;
+mod outbound_email_repository;
 mod risk_questionnaire_repository;
 mod transaction_repository;
+mod unit_of_work;
 mod user_repository;
 
+pub use activity_repository::*;
+pub use currency_repository::*;
 pub use funding_repository::*;
 pub use importer_payment_repository::*;
 pub use invoice_repository::*;
 pub use mitra_repository::*;
+pub use notification_repository::*;
 pub use otp_repository::*;
+pub use outbound_email_repository::*;
 pub use risk_questionnaire_repository::*;
 pub use transaction_repository::*;
+pub use unit_of_work::*;
 pub use user_repository::*;
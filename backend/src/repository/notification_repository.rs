@@ -0,0 +1,103 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::AppResult;
+use crate::models::Notification;
+
+#[derive(Clone)]
+pub struct NotificationRepository {
+    pool: PgPool,
+}
+
+impl NotificationRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create(
+        &self,
+        user_id: Uuid,
+        kind: &str,
+        payload: &str,
+    ) -> AppResult<Notification> {
+        let notification = sqlx::query_as::<_, Notification>(
+            r#"
+            INSERT INTO notifications (user_id, kind, payload)
+            VALUES ($1, $2, $3)
+            RETURNING *
+            "#,
+        )
+        .bind(user_id)
+        .bind(kind)
+        .bind(payload)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(notification)
+    }
+
+    pub async fn find_by_user(
+        &self,
+        user_id: Uuid,
+        unread_only: bool,
+        page: i32,
+        per_page: i32,
+    ) -> AppResult<(Vec<Notification>, i64)> {
+        let offset = (page - 1) * per_page;
+
+        let notifications = sqlx::query_as::<_, Notification>(
+            r#"
+            SELECT * FROM notifications
+            WHERE user_id = $1 AND ($2 = FALSE OR read_at IS NULL)
+            ORDER BY created_at DESC, id DESC
+            LIMIT $3 OFFSET $4
+            "#,
+        )
+        .bind(user_id)
+        .bind(unread_only)
+        .bind(per_page)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let total: (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM notifications WHERE user_id = $1 AND ($2 = FALSE OR read_at IS NULL)",
+        )
+        .bind(user_id)
+        .bind(unread_only)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok((notifications, total.0))
+    }
+
+    /// Scoped to the owner so one user cannot mark another's notification.
+    /// Keeps the original read time when already read.
+    pub async fn mark_read(&self, user_id: Uuid, id: Uuid) -> AppResult<Option<Notification>> {
+        let notification = sqlx::query_as::<_, Notification>(
+            r#"
+            UPDATE notifications SET read_at = COALESCE(read_at, NOW())
+            WHERE id = $1 AND user_id = $2
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(notification)
+    }
+
+    /// Returns the number of notifications newly marked as read
+    pub async fn mark_all_read(&self, user_id: Uuid) -> AppResult<u64> {
+        let result = sqlx::query(
+            "UPDATE notifications SET read_at = NOW() WHERE user_id = $1 AND read_at IS NULL",
+        )
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
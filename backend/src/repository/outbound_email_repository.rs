@@ -0,0 +1,134 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::AppResult;
+use crate::models::OutboundEmail;
+
+/// How long a claimed email is hidden from other workers. A worker that dies
+/// mid-send leaves the email due again once the lease runs out.
+const CLAIM_LEASE_SECS: i64 = 300;
+
+#[derive(Clone)]
+pub struct OutboundEmailRepository {
+    pool: PgPool,
+}
+
+impl OutboundEmailRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create(
+        &self,
+        recipient: &str,
+        subject: &str,
+        template: &str,
+        html_body: &str,
+        text_body: &str,
+    ) -> AppResult<OutboundEmail> {
+        let email = sqlx::query_as::<_, OutboundEmail>(
+            r#"
+            INSERT INTO outbound_emails (recipient, subject, template, html_body, text_body)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING *
+            "#,
+        )
+        .bind(recipient)
+        .bind(subject)
+        .bind(template)
+        .bind(html_body)
+        .bind(text_body)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(email)
+    }
+
+    pub async fn find_by_id(&self, id: Uuid) -> AppResult<Option<OutboundEmail>> {
+        let email =
+            sqlx::query_as::<_, OutboundEmail>("SELECT * FROM outbound_emails WHERE id = $1")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(email)
+    }
+
+    /// Claim up to `limit` due emails for delivery, counting the attempt.
+    /// `SKIP LOCKED` keeps concurrent workers from claiming the same email.
+    pub async fn claim_due(&self, limit: i64) -> AppResult<Vec<OutboundEmail>> {
+        let emails = sqlx::query_as::<_, OutboundEmail>(
+            r#"
+            UPDATE outbound_emails
+            SET attempts = attempts + 1,
+                next_attempt_at = NOW() + make_interval(secs => $2),
+                updated_at = NOW()
+            WHERE id IN (
+                SELECT id FROM outbound_emails
+                WHERE status IN ('pending', 'failed') AND next_attempt_at <= NOW()
+                ORDER BY next_attempt_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING *
+            "#,
+        )
+        .bind(limit)
+        .bind(CLAIM_LEASE_SECS as f64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(emails)
+    }
+
+    pub async fn mark_sent(&self, id: Uuid) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE outbound_emails
+            SET status = 'sent', last_error = NULL, sent_at = NOW(), updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Record a failed attempt and schedule the next one `retry_in_secs` from now
+    pub async fn mark_failed(&self, id: Uuid, error: &str, retry_in_secs: i64) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE outbound_emails
+            SET status = 'failed', last_error = $2,
+                next_attempt_at = NOW() + make_interval(secs => $3), updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(error)
+        .bind(retry_in_secs as f64)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Give up on an email whose attempts ran out
+    pub async fn mark_dead(&self, id: Uuid, error: &str) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE outbound_emails
+            SET status = 'dead', last_error = $2, updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -2,7 +2,7 @@ use sqlx::PgPool;
 use uuid::Uuid;
 
 use crate::error::AppResult;
-use crate::models::RiskQuestionnaire;
+use crate::models::{RiskAnswer, RiskOption, RiskQuestion, RiskQuestionSet, RiskQuestionnaire};
 
 #[derive(Clone)]
 pub struct RiskQuestionnaireRepository {
@@ -14,31 +14,55 @@ impl RiskQuestionnaireRepository {
         Self { pool }
     }
 
-    pub async fn create(
+    /// Store the user's answers, replacing any earlier submission. A re-take
+    /// resets `completed_at` and takes the newly evaluated catalyst eligibility.
+    pub async fn upsert(
         &self,
         user_id: Uuid,
-        q1_answer: i32,
-        q2_answer: i32,
-        q3_answer: i32,
+        question_version: i32,
+        answers: &[RiskAnswer],
         catalyst_unlocked: bool,
         selected_tier: String,
     ) -> AppResult<RiskQuestionnaire> {
-        let rq = sqlx::query_as::<_, RiskQuestionnaire>(
+        let mut tx = self.pool.begin().await?;
+
+        // The legacy answer columns are cleared; answers live in their own table
+        let mut rq = sqlx::query_as::<_, RiskQuestionnaire>(
             r#"
-            INSERT INTO risk_questionnaires (user_id, q1_answer, q2_answer, q3_answer, catalyst_unlocked, selected_tier)
-            VALUES ($1, $2, $3, $4, $5, $6)
+            INSERT INTO risk_questionnaires (user_id, question_version, catalyst_unlocked, selected_tier)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (user_id) DO UPDATE
+            SET q1_answer = NULL, q2_answer = NULL, q3_answer = NULL,
+                question_version = EXCLUDED.question_version,
+                catalyst_unlocked = EXCLUDED.catalyst_unlocked, selected_tier = EXCLUDED.selected_tier, completed_at = NOW()
             RETURNING *
             "#,
         )
         .bind(user_id)
-        .bind(q1_answer)
-        .bind(q2_answer)
-        .bind(q3_answer)
+        .bind(question_version)
         .bind(catalyst_unlocked)
         .bind(selected_tier)
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *tx)
         .await?;
 
+        sqlx::query("DELETE FROM risk_questionnaire_answers WHERE questionnaire_id = $1")
+            .bind(rq.id)
+            .execute(&mut *tx)
+            .await?;
+        for answer in answers {
+            sqlx::query(
+                "INSERT INTO risk_questionnaire_answers (questionnaire_id, question_id, value) VALUES ($1, $2, $3)",
+            )
+            .bind(rq.id)
+            .bind(answer.question_id)
+            .bind(answer.value)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        rq.answers = answers.to_vec();
         Ok(rq)
     }
 
@@ -50,40 +74,192 @@ impl RiskQuestionnaireRepository {
         .fetch_optional(&self.pool)
         .await?;
 
-        Ok(rq)
+        let Some(mut rq) = rq else {
+            return Ok(None);
+        };
+
+        let answers = sqlx::query_as::<_, (i32, i32)>(
+            "SELECT question_id, value FROM risk_questionnaire_answers WHERE questionnaire_id = $1 ORDER BY question_id",
+        )
+        .bind(rq.id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rq.answers = if answers.is_empty() {
+            rq.legacy_answers()
+        } else {
+            answers
+                .into_iter()
+                .map(|(question_id, value)| RiskAnswer { question_id, value })
+                .collect()
+        };
+
+        Ok(Some(rq))
+    }
+
+    pub async fn find_active_question_set(&self) -> AppResult<Option<RiskQuestionSet>> {
+        let version: Option<(i32,)> =
+            sqlx::query_as("SELECT version FROM risk_question_versions WHERE is_active")
+                .fetch_optional(&self.pool)
+                .await?;
+
+        match version {
+            Some((version,)) => self.find_question_set(version).await,
+            None => Ok(None),
+        }
+    }
+
+    pub async fn find_question_set(&self, version: i32) -> AppResult<Option<RiskQuestionSet>> {
+        let catalyst_min_score: Option<(i32,)> = sqlx::query_as(
+            "SELECT catalyst_min_score FROM risk_question_versions WHERE version = $1",
+        )
+        .bind(version)
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some((catalyst_min_score,)) = catalyst_min_score else {
+            return Ok(None);
+        };
+
+        let questions = sqlx::query_as::<_, (i32, String, bool)>(
+            r#"
+            SELECT question_id, question, required_for_catalyst FROM risk_questions
+            WHERE version = $1
+            ORDER BY question_id
+            "#,
+        )
+        .bind(version)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let options = sqlx::query_as::<_, (i32, i32, String, bool, i32)>(
+            r#"
+            SELECT question_id, value, label, unlocks_catalyst, score FROM risk_question_options
+            WHERE version = $1
+            ORDER BY question_id, value
+            "#,
+        )
+        .bind(version)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let questions = questions
+            .into_iter()
+            .map(|(id, question, required_for_catalyst)| RiskQuestion {
+                id,
+                question,
+                options: options
+                    .iter()
+                    .filter(|(question_id, ..)| *question_id == id)
+                    .map(|(_, value, label, unlocks_catalyst, score)| RiskOption {
+                        value: *value,
+                        label: label.clone(),
+                        unlocks_catalyst: *unlocks_catalyst,
+                        score: *score,
+                    })
+                    .collect(),
+                required_for_catalyst,
+            })
+            .collect();
+
+        Ok(Some(RiskQuestionSet {
+            version,
+            catalyst_min_score,
+            questions,
+        }))
     }
 
-    pub async fn update(
+    /// Store a new, inactive question set under the next version number
+    pub async fn create_question_set(
         &self,
-        user_id: Uuid,
-        q1_answer: i32,
-        q2_answer: i32,
-        q3_answer: i32,
-        catalyst_unlocked: bool,
-        selected_tier: String,
-    ) -> AppResult<RiskQuestionnaire> {
-        let rq = sqlx::query_as::<_, RiskQuestionnaire>(
+        catalyst_min_score: i32,
+        questions: &[RiskQuestion],
+    ) -> AppResult<i32> {
+        let mut tx = self.pool.begin().await?;
+
+        let (version,): (i32,) = sqlx::query_as(
             r#"
-            UPDATE risk_questionnaires
-            SET q1_answer = $2, q2_answer = $3, q3_answer = $4, catalyst_unlocked = $5, selected_tier = $6, completed_at = NOW()
-            WHERE user_id = $1
-            RETURNING *
+            INSERT INTO risk_question_versions (version, catalyst_min_score)
+            SELECT COALESCE(MAX(version), 0) + 1, $1 FROM risk_question_versions
+            RETURNING version
             "#,
         )
+        .bind(catalyst_min_score)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        for question in questions {
+            sqlx::query(
+                "INSERT INTO risk_questions (version, question_id, question, required_for_catalyst) VALUES ($1, $2, $3, $4)",
+            )
+            .bind(version)
+            .bind(question.id)
+            .bind(&question.question)
+            .bind(question.required_for_catalyst)
+            .execute(&mut *tx)
+            .await?;
+
+            for option in &question.options {
+                sqlx::query(
+                    r#"
+                    INSERT INTO risk_question_options (version, question_id, value, label, unlocks_catalyst, score)
+                    VALUES ($1, $2, $3, $4, $5, $6)
+                    "#,
+                )
+                .bind(version)
+                .bind(question.id)
+                .bind(option.value)
+                .bind(&option.label)
+                .bind(option.unlocks_catalyst)
+                .bind(option.score)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+
+        tx.commit().await?;
+        Ok(version)
+    }
+
+    /// Make `version` the only active question set. Returns false if it does not exist.
+    pub async fn activate_question_set(&self, version: i32) -> AppResult<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "UPDATE risk_question_versions SET is_active = false WHERE is_active AND version <> $1",
+        )
+        .bind(version)
+        .execute(&mut *tx)
+        .await?;
+        let result =
+            sqlx::query("UPDATE risk_question_versions SET is_active = true WHERE version = $1")
+                .bind(version)
+                .execute(&mut *tx)
+                .await?;
+
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    pub async fn has_completed(&self, user_id: Uuid) -> AppResult<bool> {
+        let completed: (bool,) = sqlx::query_as(
+            "SELECT EXISTS(SELECT 1 FROM risk_questionnaires WHERE user_id = $1 AND completed_at IS NOT NULL)",
+        )
         .bind(user_id)
-        .bind(q1_answer)
-        .bind(q2_answer)
-        .bind(q3_answer)
-        .bind(catalyst_unlocked)
-        .bind(selected_tier)
         .fetch_one(&self.pool)
         .await?;
 
-        Ok(rq)
+        Ok(completed.0)
     }
 
     pub async fn is_catalyst_unlocked(&self, user_id: Uuid) -> AppResult<bool> {
-        let rq = self.find_by_user(user_id).await?;
-        Ok(rq.map(|r| r.catalyst_unlocked).unwrap_or(false))
+        let unlocked: Option<(Option<bool>,)> =
+            sqlx::query_as("SELECT catalyst_unlocked FROM risk_questionnaires WHERE user_id = $1")
+                .bind(user_id)
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(unlocked.and_then(|(u,)| u).unwrap_or(false))
     }
 }
@@ -1,9 +1,11 @@
-use rust_decimal::Decimal;
-use sqlx::PgPool;
+use chrono::NaiveDate;
+use rust_decimal::{Decimal, RoundingStrategy};
+use sqlx::{PgConnection, PgPool, Postgres, QueryBuilder};
 use uuid::Uuid;
 
-use crate::error::AppResult;
-use crate::models::{BalanceTransaction, Transaction};
+use crate::error::{AppError, AppResult};
+use crate::models::{BalanceTransaction, GasUsageRow, PlatformRevenueRow, Transaction, TxFilter};
+use crate::utils::Cursor;
 
 #[derive(Clone)]
 pub struct TransactionRepository {
@@ -76,7 +78,7 @@ impl TransactionRepository {
         let offset = (page - 1) * per_page;
 
         let txs = sqlx::query_as::<_, Transaction>(
-            "SELECT * FROM transactions WHERE user_id = $1 ORDER BY created_at DESC LIMIT $2 OFFSET $3"
+            "SELECT * FROM transactions WHERE user_id = $1 ORDER BY created_at DESC, id DESC LIMIT $2 OFFSET $3"
         )
         .bind(user_id)
         .bind(per_page)
@@ -92,9 +94,51 @@ impl TransactionRepository {
         Ok((txs, total.0))
     }
 
+    /// Transactions matching `filter`, newest first. A `cursor` takes the place
+    /// of `page` and returns the transactions after it.
+    pub async fn find(
+        &self,
+        filter: &TxFilter,
+        page: i32,
+        per_page: i32,
+        cursor: Option<Cursor>,
+    ) -> AppResult<(Vec<Transaction>, i64)> {
+        let offset = if cursor.is_some() {
+            0
+        } else {
+            (page - 1) * per_page
+        };
+
+        let mut query = QueryBuilder::<Postgres>::new("SELECT * FROM transactions");
+        push_tx_filter(&mut query, filter);
+        if let Some(cursor) = cursor {
+            query
+                .push(" AND (created_at, id) < (")
+                .push_bind(cursor.at)
+                .push(", ")
+                .push_bind(cursor.id)
+                .push(")");
+        }
+        query
+            .push(" ORDER BY created_at DESC, id DESC LIMIT ")
+            .push_bind(per_page)
+            .push(" OFFSET ")
+            .push_bind(offset);
+        let txs = query
+            .build_query_as::<Transaction>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut count = QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM transactions");
+        push_tx_filter(&mut count, filter);
+        let total: (i64,) = count.build_query_as().fetch_one(&self.pool).await?;
+
+        Ok((txs, total.0))
+    }
+
     pub async fn find_by_invoice(&self, invoice_id: Uuid) -> AppResult<Vec<Transaction>> {
         let txs = sqlx::query_as::<_, Transaction>(
-            "SELECT * FROM transactions WHERE invoice_id = $1 ORDER BY created_at DESC",
+            "SELECT * FROM transactions WHERE invoice_id = $1 ORDER BY created_at DESC, id DESC",
         )
         .bind(invoice_id)
         .fetch_all(&self.pool)
@@ -171,6 +215,149 @@ impl TransactionRepository {
         Ok(tx)
     }
 
+    /// Atomically subtract `amount` from the user's IDRX balance and record the
+    /// movement. Returns `None` (and changes nothing) if the balance is too low.
+    pub async fn debit_balance(
+        &self,
+        user_id: Uuid,
+        tx_type: &str,
+        amount: Decimal,
+        reference_id: Option<Uuid>,
+        reference_type: Option<&str>,
+        description: Option<&str>,
+    ) -> AppResult<Option<BalanceTransaction>> {
+        self.apply_balance_change(
+            user_id,
+            tx_type,
+            -amount,
+            reference_id,
+            reference_type,
+            description,
+        )
+        .await
+    }
+
+    /// `debit_balance` inside the caller's transaction
+    pub async fn debit_balance_tx(
+        &self,
+        conn: &mut PgConnection,
+        user_id: Uuid,
+        tx_type: &str,
+        amount: Decimal,
+        reference_id: Option<Uuid>,
+        reference_type: Option<&str>,
+        description: Option<&str>,
+    ) -> AppResult<Option<BalanceTransaction>> {
+        self.apply_balance_change_tx(
+            conn,
+            user_id,
+            tx_type,
+            -amount,
+            reference_id,
+            reference_type,
+            description,
+        )
+        .await
+    }
+
+    /// Add `amount` to the user's IDRX balance and record the movement
+    pub async fn credit_balance(
+        &self,
+        user_id: Uuid,
+        tx_type: &str,
+        amount: Decimal,
+        reference_id: Option<Uuid>,
+        reference_type: Option<&str>,
+        description: Option<&str>,
+    ) -> AppResult<BalanceTransaction> {
+        self.apply_balance_change(
+            user_id,
+            tx_type,
+            amount,
+            reference_id,
+            reference_type,
+            description,
+        )
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))
+    }
+
+    async fn apply_balance_change(
+        &self,
+        user_id: Uuid,
+        tx_type: &str,
+        delta: Decimal,
+        reference_id: Option<Uuid>,
+        reference_type: Option<&str>,
+        description: Option<&str>,
+    ) -> AppResult<Option<BalanceTransaction>> {
+        let mut tx = self.pool.begin().await?;
+        let record = self
+            .apply_balance_change_tx(
+                &mut tx,
+                user_id,
+                tx_type,
+                delta,
+                reference_id,
+                reference_type,
+                description,
+            )
+            .await?;
+        tx.commit().await?;
+
+        Ok(record)
+    }
+
+    async fn apply_balance_change_tx(
+        &self,
+        conn: &mut PgConnection,
+        user_id: Uuid,
+        tx_type: &str,
+        delta: Decimal,
+        reference_id: Option<Uuid>,
+        reference_type: Option<&str>,
+        description: Option<&str>,
+    ) -> AppResult<Option<BalanceTransaction>> {
+        // The balance check and update are one statement, so concurrent debits
+        // cannot take the balance below zero
+        let balance_after: Option<(Decimal,)> = sqlx::query_as(
+            r#"
+            UPDATE users
+            SET balance_idrx = COALESCE(balance_idrx, 0) + $2, updated_at = NOW()
+            WHERE id = $1 AND COALESCE(balance_idrx, 0) + $2 >= 0
+            RETURNING balance_idrx
+            "#,
+        )
+        .bind(user_id)
+        .bind(delta)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        let Some((balance_after,)) = balance_after else {
+            return Ok(None);
+        };
+
+        let record = sqlx::query_as::<_, BalanceTransaction>(
+            r#"
+            INSERT INTO balance_transactions (user_id, type, amount, balance_before, balance_after, reference_id, reference_type, description)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING *
+            "#,
+        )
+        .bind(user_id)
+        .bind(tx_type)
+        .bind(delta.abs())
+        .bind(balance_after - delta)
+        .bind(balance_after)
+        .bind(reference_id)
+        .bind(reference_type)
+        .bind(description)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(Some(record))
+    }
+
     pub async fn find_balance_transactions_by_user(
         &self,
         user_id: Uuid,
@@ -180,7 +367,7 @@ impl TransactionRepository {
         let offset = (page - 1) * per_page;
 
         let txs = sqlx::query_as::<_, BalanceTransaction>(
-            "SELECT * FROM balance_transactions WHERE user_id = $1 ORDER BY created_at DESC LIMIT $2 OFFSET $3"
+            "SELECT * FROM balance_transactions WHERE user_id = $1 ORDER BY created_at DESC, id DESC LIMIT $2 OFFSET $3"
         )
         .bind(user_id)
         .bind(per_page)
@@ -197,18 +384,117 @@ impl TransactionRepository {
         Ok((txs, total.0))
     }
 
-    pub async fn get_platform_revenue(&self) -> AppResult<Decimal> {
-        let revenue: (Decimal,) = sqlx::query_as(
-            "SELECT COALESCE(SUM(amount), 0) FROM transactions WHERE type = 'platform_fee' AND status = 'confirmed'"
+    /// Platform fee totals per `granularity` bucket ('day' or 'month') plus a grand
+    /// total row (period = NULL) in a single ROLLUP query. `to` is inclusive.
+    pub async fn get_platform_revenue_rows(
+        &self,
+        granularity: &str,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> AppResult<Vec<PlatformRevenueRow>> {
+        let rows = sqlx::query_as::<_, PlatformRevenueRow>(
+            r#"
+            SELECT
+                date_trunc($1, created_at)::date AS period,
+                COALESCE(SUM(amount) FILTER (WHERE status = 'confirmed'), 0) AS confirmed,
+                COALESCE(SUM(amount) FILTER (WHERE status = 'pending'), 0) AS pending,
+                COUNT(*) FILTER (WHERE status IN ('confirmed', 'pending')) AS tx_count
+            FROM transactions
+            WHERE type = 'platform_fee'
+              AND ($2::date IS NULL OR created_at >= $2::date)
+              AND ($3::date IS NULL OR created_at < $3::date + 1)
+            GROUP BY ROLLUP (date_trunc($1, created_at))
+            ORDER BY period NULLS FIRST
+            "#,
+        )
+        .bind(granularity)
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// Store the gas paid for a platform-sent transaction on every row recorded
+    /// under its hash (e.g. a disbursement and the fee it withheld)
+    pub async fn record_gas_usage(
+        &self,
+        tx_hash: &str,
+        gas_used: i64,
+        effective_gas_price: i64,
+    ) -> AppResult<u64> {
+        let result = sqlx::query(
+            r#"
+            UPDATE transactions
+            SET gas_used = $2, effective_gas_price = $3, updated_at = NOW()
+            WHERE tx_hash = $1
+            "#,
+        )
+        .bind(tx_hash)
+        .bind(gas_used)
+        .bind(effective_gas_price)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Gas spent per pool plus a ROLLUP total row. Each hash is counted once
+    /// even when several rows share it.
+    pub async fn get_gas_usage_rows(&self) -> AppResult<Vec<GasUsageRow>> {
+        let rows = sqlx::query_as::<_, GasUsageRow>(
+            r#"
+            WITH sends AS (
+                SELECT DISTINCT ON (tx_hash) tx_hash, invoice_id, gas_used, effective_gas_price
+                FROM transactions
+                WHERE gas_used IS NOT NULL AND tx_hash IS NOT NULL
+                ORDER BY tx_hash, created_at
+            )
+            SELECT
+                fp.id AS pool_id,
+                GROUPING(fp.id) = 1 AS is_total,
+                COALESCE(SUM(s.gas_used), 0)::BIGINT AS gas_used,
+                COALESCE(SUM(s.gas_used::NUMERIC * COALESCE(s.effective_gas_price, 0)), 0) AS fee_wei,
+                COUNT(s.tx_hash) AS tx_count
+            FROM sends s
+            LEFT JOIN funding_pools fp ON fp.invoice_id = s.invoice_id
+            GROUP BY ROLLUP (fp.id)
+            ORDER BY is_total DESC, gas_used DESC, pool_id
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// Sum of a user's confirmed transactions of a given type since `since`
+    /// Used for AML rolling-window velocity checks
+    pub async fn sum_user_amount_since(
+        &self,
+        user_id: Uuid,
+        tx_type: &str,
+        since: chrono::NaiveDateTime,
+    ) -> AppResult<Decimal> {
+        let total: (Decimal,) = sqlx::query_as(
+            r#"
+            SELECT COALESCE(SUM(amount), 0) FROM transactions
+            WHERE user_id = $1 AND type = $2 AND status = 'confirmed' AND created_at >= $3
+            "#,
         )
+        .bind(user_id)
+        .bind(tx_type)
+        .bind(since)
         .fetch_one(&self.pool)
         .await?;
 
-        Ok(revenue.0)
+        Ok(total.0)
     }
 
     /// Create a verified blockchain transaction record
-    /// Used for on-chain IDRX transactions that have been verified
+    /// Used for on-chain IDRX transactions that have been verified.
+    /// A hash is recorded once per type: retries return the existing row unchanged.
     pub async fn create_blockchain_transaction(
         &self,
         user_id: Uuid,
@@ -219,6 +505,32 @@ impl TransactionRepository {
         invoice_id: Option<Uuid>,
         description: Option<&str>,
         explorer_url: &str,
+    ) -> AppResult<Transaction> {
+        self.create_blockchain_transaction_tx(
+            &mut *self.pool.acquire().await?,
+            user_id,
+            tx_type,
+            amount,
+            tx_hash,
+            block_number,
+            invoice_id,
+            description,
+            explorer_url,
+        )
+        .await
+    }
+
+    pub async fn create_blockchain_transaction_tx(
+        &self,
+        conn: &mut PgConnection,
+        user_id: Uuid,
+        tx_type: &str,
+        amount: Decimal,
+        tx_hash: &str,
+        block_number: i64,
+        invoice_id: Option<Uuid>,
+        description: Option<&str>,
+        explorer_url: &str,
     ) -> AppResult<Transaction> {
         // Create the transaction record
         let tx = sqlx::query_as::<_, Transaction>(
@@ -228,6 +540,7 @@ impl TransactionRepository {
                 status, notes, explorer_url, invoice_id
             )
             VALUES ($1, $2, $3, 'IDRX', $4, $5, 'confirmed', $6, $7, $8)
+            ON CONFLICT (tx_hash, type) DO NOTHING
             RETURNING *
             "#,
         )
@@ -239,38 +552,93 @@ impl TransactionRepository {
         .bind(description)
         .bind(explorer_url)
         .bind(invoice_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        match tx {
+            Some(tx) => Ok(tx),
+            None => {
+                let existing = sqlx::query_as::<_, Transaction>(
+                    "SELECT * FROM transactions WHERE tx_hash = $1 AND type = $2",
+                )
+                .bind(tx_hash)
+                .bind(tx_type)
+                .fetch_one(&mut *conn)
+                .await?;
+
+                // A retry records the same user and amount; anything else is a
+                // different transfer claiming this hash
+                let amount =
+                    amount.round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero);
+                if existing.user_id != Some(user_id) || existing.amount != amount {
+                    return Err(AppError::Conflict(format!(
+                        "Transaction {} ({}) is already recorded for a different user or amount",
+                        tx_hash, tx_type
+                    )));
+                }
+
+                tracing::info!(
+                    "Transaction {} ({}) already recorded as {}",
+                    tx_hash,
+                    tx_type,
+                    existing.id
+                );
+                Ok(existing)
+            }
+        }
+    }
+
+    /// Record a disbursement paid out to a bank account. `tx_hash` is the on-chain
+    /// disbursement to the off-ramp wallet; the row stays `pending_offramp` until
+    /// the fiat payout settles.
+    pub async fn create_bank_disbursement(
+        &self,
+        user_id: Uuid,
+        invoice_id: Uuid,
+        bank_account_id: Uuid,
+        amount: Decimal,
+        tx_hash: &str,
+        explorer_url: &str,
+        notes: &str,
+    ) -> AppResult<Transaction> {
+        let tx = sqlx::query_as::<_, Transaction>(
+            r#"
+            INSERT INTO transactions (
+                user_id, invoice_id, type, amount, currency, status, bank_account_id,
+                tx_hash, explorer_url, notes
+            )
+            VALUES ($1, $2, 'bank_disbursement', $3, 'IDRX', 'pending_offramp', $4, $5, $6, $7)
+            RETURNING *
+            "#,
+        )
+        .bind(user_id)
+        .bind(invoice_id)
+        .bind(amount)
+        .bind(bank_account_id)
+        .bind(tx_hash)
+        .bind(explorer_url)
+        .bind(notes)
         .fetch_one(&self.pool)
         .await?;
 
         Ok(tx)
     }
 
-    /// Find blockchain transactions by user with explorer URLs
+    /// Find blockchain transactions by user with explorer URLs. A `cursor`
+    /// takes the place of `page` and returns the transactions after it.
     pub async fn find_blockchain_transactions_by_user(
         &self,
         user_id: Uuid,
         page: i32,
         per_page: i32,
+        cursor: Option<Cursor>,
     ) -> AppResult<(Vec<Transaction>, i64)> {
-        let offset = (page - 1) * per_page;
-
-        let txs = sqlx::query_as::<_, Transaction>(
-            "SELECT * FROM transactions WHERE user_id = $1 AND tx_hash IS NOT NULL ORDER BY created_at DESC LIMIT $2 OFFSET $3"
-        )
-        .bind(user_id)
-        .bind(per_page)
-        .bind(offset)
-        .fetch_all(&self.pool)
-        .await?;
-
-        let total: (i64,) = sqlx::query_as(
-            "SELECT COUNT(*) FROM transactions WHERE user_id = $1 AND tx_hash IS NOT NULL",
-        )
-        .bind(user_id)
-        .fetch_one(&self.pool)
-        .await?;
-
-        Ok((txs, total.0))
+        let filter = TxFilter {
+            user_id: Some(user_id),
+            on_chain_only: true,
+            ..Default::default()
+        };
+        self.find(&filter, page, per_page, cursor).await
     }
 
     /// Get all on-chain transactions for a pool (for transparency)
@@ -288,7 +656,7 @@ impl TransactionRepository {
             r#"
             SELECT * FROM transactions
             WHERE invoice_id = $1 AND tx_hash IS NOT NULL
-            ORDER BY created_at DESC
+            ORDER BY created_at DESC, id DESC
             "#,
         )
         .bind(invoice_id.0)
@@ -298,3 +666,30 @@ impl TransactionRepository {
         Ok(txs)
     }
 }
+
+/// Append the WHERE clause for `filter`. Only the fixed columns named here are
+/// written into the SQL; every value is bound as a parameter.
+fn push_tx_filter(query: &mut QueryBuilder<'_, Postgres>, filter: &TxFilter) {
+    query.push(" WHERE TRUE");
+    if let Some(user_id) = filter.user_id {
+        query.push(" AND user_id = ").push_bind(user_id);
+    }
+    if let Some(tx_type) = &filter.tx_type {
+        query.push(" AND type = ").push_bind(tx_type.to_string());
+    }
+    if let Some(status) = &filter.status {
+        query.push(" AND status = ").push_bind(status.to_string());
+    }
+    if let Some(invoice_id) = filter.invoice_id {
+        query.push(" AND invoice_id = ").push_bind(invoice_id);
+    }
+    if let Some(from) = filter.created_from {
+        query.push(" AND created_at >= ").push_bind(from);
+    }
+    if let Some(to) = filter.created_to {
+        query.push(" AND created_at < ").push_bind(to);
+    }
+    if filter.on_chain_only {
+        query.push(" AND tx_hash IS NOT NULL");
+    }
+}
@@ -0,0 +1,7 @@
+use sqlx::{Postgres, Transaction};
+
+/// A database transaction shared by several repositories, so a multi-repository
+/// write commits or rolls back as one unit. Repositories take it through their
+/// `_tx` methods as `&mut tx` (it derefs to `PgConnection`). Dropping it without
+/// calling `commit` rolls every write back.
+pub type Tx = Transaction<'static, Postgres>;
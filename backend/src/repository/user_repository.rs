@@ -1,8 +1,11 @@
 use sqlx::PgPool;
 use uuid::Uuid;
 
-use crate::error::AppResult;
-use crate::models::{User, UserProfile};
+use crate::error::{AppError, AppResult};
+use crate::models::{BankAccount, KycVerification, MemberStatus, Role, User, UserProfile};
+
+/// Random-suffixed usernames tried after the wallet address itself is taken
+const WALLET_USERNAME_SUFFIX_ATTEMPTS: usize = 3;
 
 #[derive(Clone)]
 pub struct UserRepository {
@@ -32,7 +35,7 @@ impl UserRepository {
         email: &str,
         username: &str,
         password_hash: &str,
-        role: &str,
+        role: Role,
     ) -> AppResult<User> {
         let user = sqlx::query_as::<_, User>(
             r#"
@@ -105,12 +108,50 @@ impl UserRepository {
         self.find_by_wallet_address(wallet_address).await
     }
 
-    /// Create investor account with wallet only (no email/password required)
+    /// Create investor account with wallet only (no email/password required).
+    /// The full wallet address is the placeholder username, so it is as unique as
+    /// the wallet itself. If someone already took that username, a random suffix
+    /// is added, and only after repeated collisions is the account created
+    /// without one. No placeholder email is stored, so emails cannot collide.
+    /// A wallet that is already registered, e.g. by a concurrent first login
+    /// from the same wallet, resolves to the existing account.
     pub async fn create_investor_with_wallet(&self, wallet_address: &str) -> AppResult<User> {
         let wallet = wallet_address.to_lowercase();
-        // No email for wallet-only investors
-        let email: Option<String> = None;
 
+        let suffixed = (0..WALLET_USERNAME_SUFFIX_ATTEMPTS)
+            .map(|_| format!("{}_{}", wallet, &Uuid::new_v4().simple().to_string()[..6]));
+        let usernames = std::iter::once(wallet.clone())
+            .chain(suffixed)
+            .map(Some)
+            .chain(std::iter::once(None));
+
+        for username in usernames {
+            if let Some(user) = self
+                .insert_wallet_investor(&wallet, username.as_deref())
+                .await?
+            {
+                return Ok(user);
+            }
+            if let Some(user) = self.find_by_wallet(&wallet).await? {
+                return Ok(user);
+            }
+            tracing::warn!(
+                "Username {:?} already taken, retrying wallet investor {}",
+                username,
+                wallet
+            );
+        }
+
+        Err(AppError::Conflict("Wallet already registered".to_string()))
+    }
+
+    /// `None` when the wallet or the username is already taken
+    async fn insert_wallet_investor(
+        &self,
+        wallet: &str,
+        username: Option<&str>,
+    ) -> AppResult<Option<User>> {
+        // No email for wallet-only investors
         let user = sqlx::query_as::<_, User>(
             r#"
             INSERT INTO users (
@@ -118,21 +159,22 @@ impl UserRepository {
                 cooperative_agreement, member_status, email_verified,
                 profile_completed, wallet_address
             )
-            VALUES ($1, $2, '', 'investor', true, true, true, 'calon_anggota_pendana', false, false, $3)
+            VALUES (NULL, $1, '', 'investor', true, true, true, 'calon_anggota_pendana', false, false, $2)
+            ON CONFLICT DO NOTHING
             RETURNING *
             "#,
         )
-        .bind(&email)
-        .bind(&wallet[2..10]) // Use part of wallet as username
-        .bind(&wallet)
-        .fetch_one(&self.pool)
+        .bind(username)
+        .bind(wallet)
+        .fetch_optional(&self.pool)
         .await?;
 
         Ok(user)
     }
 
+    /// Also bumps `token_version` so tokens issued under the old password stop working
     pub async fn update_password(&self, user_id: Uuid, password_hash: &str) -> AppResult<()> {
-        sqlx::query("UPDATE users SET password_hash = $1, updated_at = NOW() WHERE id = $2")
+        sqlx::query("UPDATE users SET password_hash = $1, token_version = token_version + 1, updated_at = NOW() WHERE id = $2")
             .bind(password_hash)
             .bind(user_id)
             .execute(&self.pool)
@@ -141,6 +183,27 @@ impl UserRepository {
         Ok(())
     }
 
+    /// Revoke every token issued to the user so far
+    pub async fn increment_token_version(&self, user_id: Uuid) -> AppResult<i32> {
+        let row: (i32,) = sqlx::query_as(
+            "UPDATE users SET token_version = token_version + 1, updated_at = NOW() WHERE id = $1 RETURNING token_version",
+        )
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row.0)
+    }
+
+    pub async fn get_token_version(&self, user_id: Uuid) -> AppResult<Option<i32>> {
+        let row: Option<(i32,)> = sqlx::query_as("SELECT token_version FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.map(|r| r.0))
+    }
+
     pub async fn update_wallet_address(
         &self,
         user_id: Uuid,
@@ -175,7 +238,7 @@ impl UserRepository {
         Ok(())
     }
 
-    pub async fn update_role(&self, user_id: Uuid, role: &str) -> AppResult<()> {
+    pub async fn update_role(&self, user_id: Uuid, role: Role) -> AppResult<()> {
         sqlx::query("UPDATE users SET role = $1, updated_at = NOW() WHERE id = $2")
             .bind(role)
             .bind(user_id)
@@ -185,7 +248,7 @@ impl UserRepository {
         Ok(())
     }
 
-    pub async fn update_member_status(&self, user_id: Uuid, status: &str) -> AppResult<()> {
+    pub async fn update_member_status(&self, user_id: Uuid, status: MemberStatus) -> AppResult<()> {
         sqlx::query("UPDATE users SET member_status = $1, updated_at = NOW() WHERE id = $2")
             .bind(status)
             .bind(user_id)
@@ -199,7 +262,7 @@ impl UserRepository {
         let offset = (page - 1) * per_page;
 
         let users = sqlx::query_as::<_, User>(
-            "SELECT * FROM users ORDER BY created_at DESC LIMIT $1 OFFSET $2",
+            "SELECT * FROM users ORDER BY created_at DESC, id DESC LIMIT $1 OFFSET $2",
         )
         .bind(per_page)
         .bind(offset)
@@ -278,6 +341,243 @@ impl UserRepository {
     }
 
     // Identity methods
+    pub async fn upsert_kyc(
+        &self,
+        user_id: Uuid,
+        nik: &str,
+        full_name: &str,
+        ktp_photo_url: &str,
+        selfie_url: &str,
+    ) -> AppResult<KycVerification> {
+        let kyc = sqlx::query_as::<_, KycVerification>(
+            r#"
+            INSERT INTO kyc_verifications (user_id, nik, full_name, ktp_photo_url, selfie_url, status)
+            VALUES ($1, $2, $3, $4, $5, 'pending')
+            ON CONFLICT (user_id) DO UPDATE
+            SET nik = EXCLUDED.nik,
+                full_name = EXCLUDED.full_name,
+                ktp_photo_url = EXCLUDED.ktp_photo_url,
+                selfie_url = EXCLUDED.selfie_url,
+                status = 'pending',
+                rejection_reason = NULL,
+                verified_by = NULL,
+                verified_at = NULL,
+                updated_at = NOW()
+            RETURNING *
+            "#,
+        )
+        .bind(user_id)
+        .bind(nik)
+        .bind(full_name)
+        .bind(ktp_photo_url)
+        .bind(selfie_url)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(kyc)
+    }
+
+    pub async fn find_kyc_by_id(&self, id: Uuid) -> AppResult<Option<KycVerification>> {
+        let kyc =
+            sqlx::query_as::<_, KycVerification>("SELECT * FROM kyc_verifications WHERE id = $1")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(kyc)
+    }
+
+    pub async fn find_kyc_by_user(&self, user_id: Uuid) -> AppResult<Option<KycVerification>> {
+        let kyc = sqlx::query_as::<_, KycVerification>(
+            "SELECT * FROM kyc_verifications WHERE user_id = $1",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(kyc)
+    }
+
+    pub async fn find_pending_kyc(
+        &self,
+        page: i32,
+        per_page: i32,
+    ) -> AppResult<(Vec<KycVerification>, i64)> {
+        let offset = (page - 1) * per_page;
+
+        let items = sqlx::query_as::<_, KycVerification>(
+            "SELECT * FROM kyc_verifications WHERE status = 'pending' ORDER BY created_at ASC, id ASC LIMIT $1 OFFSET $2",
+        )
+        .bind(per_page)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let total: (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM kyc_verifications WHERE status = 'pending'")
+                .fetch_one(&self.pool)
+                .await?;
+
+        Ok((items, total.0))
+    }
+
+    /// Approve a pending verification. `None` when it is no longer pending,
+    /// e.g. a concurrent review won.
+    pub async fn approve_kyc(
+        &self,
+        id: Uuid,
+        verified_by: Uuid,
+    ) -> AppResult<Option<KycVerification>> {
+        let kyc = sqlx::query_as::<_, KycVerification>(
+            r#"
+            UPDATE kyc_verifications
+            SET status = 'approved', verified_by = $2, verified_at = NOW(), rejection_reason = NULL, updated_at = NOW()
+            WHERE id = $1 AND status = 'pending'
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(verified_by)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(kyc)
+    }
+
+    /// Reject a pending verification. `None` when it is no longer pending.
+    pub async fn reject_kyc(
+        &self,
+        id: Uuid,
+        verified_by: Uuid,
+        reason: &str,
+    ) -> AppResult<Option<KycVerification>> {
+        let kyc = sqlx::query_as::<_, KycVerification>(
+            r#"
+            UPDATE kyc_verifications
+            SET status = 'rejected', verified_by = $2, verified_at = NOW(), rejection_reason = $3, updated_at = NOW()
+            WHERE id = $1 AND status = 'pending'
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(verified_by)
+        .bind(reason)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(kyc)
+    }
+
+    pub async fn is_kyc_approved(&self, user_id: Uuid) -> AppResult<bool> {
+        let approved: (bool,) = sqlx::query_as(
+            "SELECT EXISTS(SELECT 1 FROM kyc_verifications WHERE user_id = $1 AND status = 'approved')",
+        )
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(approved.0)
+    }
+
+    /// Store a payout wallet whose ownership has just been verified
+    pub async fn set_payout_wallet(&self, user_id: Uuid, wallet_address: &str) -> AppResult<User> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            UPDATE users
+            SET payout_wallet_address = $1, payout_wallet_verified_at = NOW(), updated_at = NOW()
+            WHERE id = $2
+            RETURNING *
+            "#,
+        )
+        .bind(wallet_address)
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    /// The user's primary bank account, if it has been verified
+    pub async fn find_primary_verified_bank_account(
+        &self,
+        user_id: Uuid,
+    ) -> AppResult<Option<BankAccount>> {
+        let account = sqlx::query_as::<_, BankAccount>(
+            r#"
+            SELECT * FROM bank_accounts
+            WHERE user_id = $1 AND is_primary AND verified_at IS NOT NULL
+            "#,
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(account)
+    }
+
+    /// Add a bank account as the user's primary one, replacing the previous
+    /// primary. It is paid out to once an admin has verified it.
+    pub async fn create_bank_account(
+        &self,
+        user_id: Uuid,
+        bank_code: &str,
+        bank_name: &str,
+        account_number: &str,
+        account_name: &str,
+    ) -> AppResult<BankAccount> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "UPDATE bank_accounts SET is_primary = false WHERE user_id = $1 AND is_primary",
+        )
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        let account = sqlx::query_as::<_, BankAccount>(
+            r#"
+            INSERT INTO bank_accounts (user_id, bank_code, bank_name, account_number, account_name, is_primary)
+            VALUES ($1, $2, $3, $4, $5, true)
+            RETURNING *
+            "#,
+        )
+        .bind(user_id)
+        .bind(bank_code)
+        .bind(bank_name)
+        .bind(account_number)
+        .bind(account_name)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(account)
+    }
+
+    pub async fn verify_bank_account(&self, id: Uuid) -> AppResult<Option<BankAccount>> {
+        let account = sqlx::query_as::<_, BankAccount>(
+            r#"
+            UPDATE bank_accounts SET verified_at = COALESCE(verified_at, NOW())
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(account)
+    }
+
+    pub async fn find_bank_accounts_by_user(&self, user_id: Uuid) -> AppResult<Vec<BankAccount>> {
+        let accounts = sqlx::query_as::<_, BankAccount>(
+            "SELECT * FROM bank_accounts WHERE user_id = $1 ORDER BY is_primary DESC, created_at, id",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(accounts)
+    }
 
     // Additional methods needed by handlers
     pub async fn update_wallet(&self, user_id: Uuid, wallet_address: &str) -> AppResult<User> {
@@ -298,7 +598,7 @@ impl UserRepository {
 
     pub async fn list_users(
         &self,
-        role: Option<&str>,
+        role: Option<Role>,
         page: i32,
         per_page: i32,
     ) -> AppResult<(Vec<User>, i64)> {
@@ -306,7 +606,7 @@ impl UserRepository {
 
         let (users, total) = if let Some(role) = role {
             let users = sqlx::query_as::<_, User>(
-                "SELECT * FROM users WHERE role = $1 ORDER BY created_at DESC LIMIT $2 OFFSET $3",
+                "SELECT * FROM users WHERE role = $1 ORDER BY created_at DESC, id DESC LIMIT $2 OFFSET $3",
             )
             .bind(role)
             .bind(per_page)
@@ -322,7 +622,7 @@ impl UserRepository {
             (users, total.0)
         } else {
             let users = sqlx::query_as::<_, User>(
-                "SELECT * FROM users ORDER BY created_at DESC LIMIT $1 OFFSET $2",
+                "SELECT * FROM users ORDER BY created_at DESC, id DESC LIMIT $1 OFFSET $2",
             )
             .bind(per_page)
             .bind(offset)
@@ -0,0 +1,72 @@
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::error::AppResult;
+use crate::models::UserActivity;
+use crate::repository::ActivityRepository;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActivityType {
+    Login,
+    WalletLogin,
+    Logout,
+    InvestmentCreated,
+    DocumentUploaded,
+    ProfileUpdated,
+    PasswordChanged,
+    PayoutWalletChanged,
+}
+
+impl ActivityType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActivityType::Login => "login",
+            ActivityType::WalletLogin => "wallet_login",
+            ActivityType::Logout => "logout",
+            ActivityType::InvestmentCreated => "investment_created",
+            ActivityType::DocumentUploaded => "document_uploaded",
+            ActivityType::ProfileUpdated => "profile_updated",
+            ActivityType::PasswordChanged => "password_changed",
+            ActivityType::PayoutWalletChanged => "payout_wallet_changed",
+        }
+    }
+}
+
+/// Writes the user activity feed. Logging is best-effort: failures are traced
+/// and never propagated to the operation being recorded.
+pub struct ActivityLogger {
+    activity_repo: Arc<ActivityRepository>,
+}
+
+impl ActivityLogger {
+    pub fn new(activity_repo: Arc<ActivityRepository>) -> Self {
+        Self { activity_repo }
+    }
+
+    pub async fn log(&self, user_id: Uuid, activity_type: ActivityType, summary: &str) {
+        if let Err(e) = self
+            .activity_repo
+            .create(user_id, activity_type.as_str(), summary)
+            .await
+        {
+            tracing::warn!(
+                "Failed to record {} activity for user {}: {}",
+                activity_type.as_str(),
+                user_id,
+                e
+            );
+        }
+    }
+
+    /// Most recent first
+    pub async fn list_for_user(
+        &self,
+        user_id: Uuid,
+        page: i32,
+        per_page: i32,
+    ) -> AppResult<(Vec<UserActivity>, i64)> {
+        self.activity_repo
+            .find_by_user(user_id, page, per_page)
+            .await
+    }
+}
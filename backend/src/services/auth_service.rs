@@ -1,5 +1,3 @@
-use ethers::types::{Address, H256, Signature};
-use ethers::utils::hex;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -9,12 +7,16 @@ use crate::config::Config;
 use crate::error::{AppError, AppResult};
 use crate::models::{
     ConnectWalletRequest, GoogleAuthRequest, GoogleAuthResponse, InvestorWalletRegisterRequest,
-    LoginRequest, LoginResponse, RegisterRequest, User, WalletLoginRequest, WalletNonceResponse,
+    LoginRequest, LoginResponse, MemberStatus, RegisterRequest, Role, User, WalletLoginRequest,
+    WalletNonceResponse,
 };
 use crate::repository::{MitraRepository, UserRepository};
-use crate::utils::{generate_random_token, hash_password, verify_password, JwtManager};
+use crate::utils::{
+    generate_random_token, hash_password, mask_email, mask_wallet, verify_password, Claims,
+    JwtManager,
+};
 
-use super::{BlockchainService, OtpService};
+use super::{ActivityLogger, ActivityType, BlockchainService, OtpService};
 
 pub struct AuthService {
     user_repo: Arc<UserRepository>,
@@ -24,6 +26,7 @@ pub struct AuthService {
     config: Arc<Config>,
     wallet_nonces: Arc<RwLock<HashMap<String, String>>>,
     blockchain_service: Arc<BlockchainService>,
+    activity_logger: Arc<ActivityLogger>,
 }
 
 impl AuthService {
@@ -34,6 +37,7 @@ impl AuthService {
         otp_service: Arc<OtpService>,
         config: Arc<Config>,
         blockchain_service: Arc<BlockchainService>,
+        activity_logger: Arc<ActivityLogger>,
     ) -> Self {
         Self {
             user_repo,
@@ -43,6 +47,7 @@ impl AuthService {
             config,
             wallet_nonces: Arc::new(RwLock::new(HashMap::new())),
             blockchain_service,
+            activity_logger,
         }
     }
 
@@ -64,65 +69,6 @@ impl AuthService {
         Ok(WalletNonceResponse { nonce, message })
     }
 
-    /// Verify wallet signature (supports EOA and ERC-1271 Smart Wallets)
-    async fn verify_wallet_signature(
-        &self,
-        wallet_address: &str,
-        signature_str: &str,
-        message: &str,
-    ) -> AppResult<bool> {
-        let wallet_addr: Address = wallet_address
-            .parse()
-            .map_err(|_| AppError::ValidationError("Invalid wallet address".to_string()))?;
-
-        // 1. Prepare message hash (EIP-191)
-        // We use BlockchainService helper to ensure consistency
-        let message_hash = self.blockchain_service.hash_message(message);
-
-        // 2. Decode signature
-        // Handle 0x prefix if present
-        let sig_clean = signature_str.strip_prefix("0x").unwrap_or(signature_str);
-        let signature_bytes = hex::decode(sig_clean)
-            .map_err(|_| AppError::ValidationError("Invalid signature hex".to_string()))?;
-
-        // 3. Attempt EOA Verification (Standard ECDSA)
-        // Only if signature length is 65 bytes
-        if signature_bytes.len() == 65 {
-            if let Ok(sig) = signature_str.parse::<Signature>() {
-                match sig.recover(H256::from(message_hash)) {
-                    Ok(recovered) => {
-                        if recovered == wallet_addr {
-                            tracing::info!("EOA signature verified for {}", wallet_address);
-                            return Ok(true);
-                        } else {
-                            tracing::warn!(
-                                "EOA signature recovery mismatch. Wallet: {}, Recovered: {}",
-                                wallet_addr,
-                                recovered
-                            );
-                        }
-                    }
-                    Err(e) => {
-                        tracing::warn!("EOA signature recovery failed: {}", e);
-                    }
-                }
-            } else {
-                tracing::warn!("Failed to parse EOA signature");
-            }
-        } else {
-            tracing::info!(
-                "Signature length {} != 65, skipping EOA check (likely Smart Wallet)",
-                signature_bytes.len()
-            );
-        }
-
-        // 4. Fallback: ERC-1271 Verification (Smart Contract Wallet)
-        tracing::info!("Attempting ERC-1271 verification for {}", wallet_address);
-        self.blockchain_service
-            .verify_signature_erc1271(wallet_address, message_hash, signature_bytes)
-            .await
-    }
-
     /// Wallet login for investors and mitra (supports Base Smart Wallet / passkey via ERC-1271)
     pub async fn wallet_login(&self, req: WalletLoginRequest) -> AppResult<LoginResponse> {
         let wallet = req.wallet_address.to_lowercase();
@@ -140,7 +86,11 @@ impl AuthService {
         }
 
         // Verify signature
-        if !self.verify_wallet_signature(&wallet, &req.signature, &req.message).await? {
+        if !self
+            .blockchain_service
+            .verify_wallet_signature(&wallet, &req.signature, &req.message)
+            .await?
+        {
             return Err(AppError::InvalidCredentials);
         }
 
@@ -154,10 +104,15 @@ impl AuthService {
         // Supports both investor and mitra with connected wallets (Base Smart Wallet / passkey)
         let user = match self.user_repo.find_by_wallet(&wallet).await? {
             Some(user) => user,
-            None => {
+            None if self.config.allow_wallet_autoregister => {
                 // Auto-create investor account with wallet
                 self.user_repo.create_investor_with_wallet(&wallet).await?
             }
+            None => {
+                return Err(AppError::Forbidden(
+                    "No account is linked to this wallet, please register first".to_string(),
+                ))
+            }
         };
 
         if !user.is_active {
@@ -168,14 +123,20 @@ impl AuthService {
         let access_token = self.jwt_manager.generate_access_token(
             user.id,
             user.email.as_deref().unwrap_or(""),
-            &user.role,
+            user.role,
+            user.token_version,
         )?;
         let refresh_token = self.jwt_manager.generate_refresh_token(
             user.id,
             user.email.as_deref().unwrap_or(""),
-            &user.role,
+            user.role,
+            user.token_version,
         )?;
 
+        self.activity_logger
+            .log(user.id, ActivityType::WalletLogin, "Signed in with wallet")
+            .await;
+
         Ok(LoginResponse {
             user,
             access_token,
@@ -211,7 +172,11 @@ impl AuthService {
         }
 
         // Verify signature
-        if !self.verify_wallet_signature(&wallet, &req.signature, &req.message).await? {
+        if !self
+            .blockchain_service
+            .verify_wallet_signature(&wallet, &req.signature, &req.message)
+            .await?
+        {
             return Err(AppError::InvalidCredentials);
         }
 
@@ -233,12 +198,14 @@ impl AuthService {
         let access_token = self.jwt_manager.generate_access_token(
             user.id,
             user.email.as_deref().unwrap_or(""),
-            &user.role,
+            user.role,
+            user.token_version,
         )?;
         let refresh_token = self.jwt_manager.generate_refresh_token(
             user.id,
             user.email.as_deref().unwrap_or(""),
-            &user.role,
+            user.role,
+            user.token_version,
         )?;
 
         Ok(LoginResponse {
@@ -273,6 +240,7 @@ impl AuthService {
 
         // Verify signature (supports both EOA and ERC-1271 / Base Smart Wallet / passkey)
         if !self
+            .blockchain_service
             .verify_wallet_signature(&wallet, &req.signature, &req.message)
             .await?
         {
@@ -302,12 +270,61 @@ impl AuthService {
         tracing::info!(
             "Wallet connected: user={}, wallet={}",
             user_id,
-            wallet
+            mask_wallet(&wallet)
         );
 
         Ok(user)
     }
 
+    /// Set the wallet investor returns are paid to, verified through the same
+    /// nonce + signature flow as `connect_wallet`. The login wallet is unchanged.
+    pub async fn set_payout_wallet(
+        &self,
+        user_id: Uuid,
+        req: ConnectWalletRequest,
+    ) -> AppResult<User> {
+        let wallet = req.wallet_address.to_lowercase();
+
+        // Verify nonce
+        {
+            let nonces = self.wallet_nonces.read().await;
+            let stored_nonce = nonces
+                .get(&wallet)
+                .ok_or_else(|| AppError::ValidationError("Invalid or expired nonce".to_string()))?;
+
+            if stored_nonce != &req.nonce {
+                return Err(AppError::ValidationError("Nonce mismatch".to_string()));
+            }
+        }
+
+        // Verify signature (supports both EOA and ERC-1271 / Base Smart Wallet / passkey)
+        if !self
+            .blockchain_service
+            .verify_wallet_signature(&wallet, &req.signature, &req.message)
+            .await?
+        {
+            return Err(AppError::InvalidCredentials);
+        }
+
+        // Clear used nonce
+        {
+            let mut nonces = self.wallet_nonces.write().await;
+            nonces.remove(&wallet);
+        }
+
+        let user = self.user_repo.set_payout_wallet(user_id, &wallet).await?;
+
+        self.activity_logger
+            .log(
+                user_id,
+                ActivityType::PayoutWalletChanged,
+                &format!("Set payout wallet to {}", wallet),
+            )
+            .await;
+
+        Ok(user)
+    }
+
     /// Traditional registration (for mitra only - investors use wallet login)
     pub async fn register(&self, req: RegisterRequest) -> AppResult<LoginResponse> {
         // Verify OTP token
@@ -323,8 +340,8 @@ impl AuthService {
         if email.to_lowercase() != req.email.to_lowercase() {
             tracing::warn!(
                 "Registration failed: Email mismatch (token: {}, req: {})",
-                email,
-                req.email
+                mask_email(&email),
+                mask_email(&req.email)
             );
             return Err(AppError::ValidationError(
                 "OTP token does not match email".to_string(),
@@ -335,7 +352,7 @@ impl AuthService {
         if self.user_repo.find_by_email(&req.email).await?.is_some() {
             tracing::warn!(
                 "Registration failed: Email already registered: {}",
-                req.email
+                mask_email(&req.email)
             );
             return Err(AppError::Conflict("Email already registered".to_string()));
         }
@@ -358,7 +375,7 @@ impl AuthService {
         if req.password != req.confirm_password {
             tracing::warn!(
                 "Registration failed: Passwords do not match for {}",
-                req.email
+                mask_email(&req.email)
             );
             return Err(AppError::ValidationError(
                 "Passwords do not match".to_string(),
@@ -369,7 +386,7 @@ impl AuthService {
         if !req.cooperative_agreement {
             tracing::warn!(
                 "Registration failed: Cooperative agreement not accepted by {}",
-                req.email
+                mask_email(&req.email)
             );
             return Err(AppError::ValidationError(
                 "Must accept cooperative agreement".to_string(),
@@ -382,14 +399,14 @@ impl AuthService {
         // Create user with role "mitra" and member_status "calon_anggota_mitra"
         let mut user = self
             .user_repo
-            .create(&req.email, &req.username, &password_hash, "mitra")
+            .create(&req.email, &req.username, &password_hash, Role::Mitra)
             .await?;
 
         // Update member_status to calon_anggota_mitra
         self.user_repo
-            .update_member_status(user.id, "calon_anggota_mitra")
+            .update_member_status(user.id, MemberStatus::CalonAnggotaMitra)
             .await?;
-        user.member_status = "calon_anggota_mitra".to_string();
+        user.member_status = MemberStatus::CalonAnggotaMitra;
 
         // Set profile_completed based on whether company details were provided
         // Check if company_name is SOME and NOT EMPTY string
@@ -425,19 +442,19 @@ impl AuthService {
 
                 tracing::info!(
                     "Mitra registered: {} with pending application for company: {}",
-                    req.email,
+                    mask_email(&req.email),
                     company_name
                 );
             } else {
                 tracing::info!(
                     "Mitra registered: {} (without initial company profile)",
-                    req.email
+                    mask_email(&req.email)
                 );
             }
         } else {
             tracing::info!(
                 "Mitra registered: {} (without initial company profile)",
-                req.email
+                mask_email(&req.email)
             );
         }
 
@@ -445,12 +462,14 @@ impl AuthService {
         let access_token = self.jwt_manager.generate_access_token(
             user.id,
             user.email.as_deref().unwrap_or(""),
-            &user.role,
+            user.role,
+            user.token_version,
         )?;
         let refresh_token = self.jwt_manager.generate_refresh_token(
             user.id,
             user.email.as_deref().unwrap_or(""),
-            &user.role,
+            user.role,
+            user.token_version,
         )?;
 
         Ok(LoginResponse {
@@ -471,7 +490,7 @@ impl AuthService {
             .ok_or(AppError::InvalidCredentials)?;
 
         // Investors must use wallet login
-        if user.role == "investor" {
+        if user.role == Role::Investor {
             return Err(AppError::Forbidden(
                 "Investors must use wallet login. Please connect your wallet instead.".to_string(),
             ));
@@ -491,14 +510,20 @@ impl AuthService {
         let access_token = self.jwt_manager.generate_access_token(
             user.id,
             user.email.as_deref().unwrap_or(""),
-            &user.role,
+            user.role,
+            user.token_version,
         )?;
         let refresh_token = self.jwt_manager.generate_refresh_token(
             user.id,
             user.email.as_deref().unwrap_or(""),
-            &user.role,
+            user.role,
+            user.token_version,
         )?;
 
+        self.activity_logger
+            .log(user.id, ActivityType::Login, "Signed in with password")
+            .await;
+
         Ok(LoginResponse {
             user,
             access_token,
@@ -521,21 +546,49 @@ impl AuthService {
             .await?
             .ok_or(AppError::InvalidToken)?;
 
+        // Revoked by logout or password change
+        claims.verify_version(user.token_version)?;
+
         // Generate new tokens
         let new_access_token = self.jwt_manager.generate_access_token(
             user.id,
             user.email.as_deref().unwrap_or(""),
-            &user.role,
+            user.role,
+            user.token_version,
         )?;
         let new_refresh_token = self.jwt_manager.generate_refresh_token(
             user.id,
             user.email.as_deref().unwrap_or(""),
-            &user.role,
+            user.role,
+            user.token_version,
         )?;
 
         Ok((new_access_token, new_refresh_token))
     }
 
+    /// Verify an access token and check it has not been revoked
+    pub async fn authenticate(&self, access_token: &str) -> AppResult<Claims> {
+        let claims = self.jwt_manager.verify_access_token(access_token)?;
+
+        let current_version = self
+            .user_repo
+            .get_token_version(claims.user_id())
+            .await?
+            .ok_or(AppError::InvalidToken)?;
+        claims.verify_version(current_version)?;
+
+        Ok(claims)
+    }
+
+    /// Revoke all access and refresh tokens issued to the user
+    pub async fn logout(&self, user_id: Uuid) -> AppResult<()> {
+        self.user_repo.increment_token_version(user_id).await?;
+        self.activity_logger
+            .log(user_id, ActivityType::Logout, "Signed out of all sessions")
+            .await;
+        Ok(())
+    }
+
     pub async fn get_user_by_id(&self, user_id: Uuid) -> AppResult<Option<User>> {
         self.user_repo.find_by_id(user_id).await
     }
@@ -1,18 +1,22 @@
 use ethers::{
+    abi,
     contract::abigen,
     prelude::*,
     providers::{Http, Provider},
     signers::{LocalWallet, Signer},
-    types::{Address, H256, U256},
+    types::{transaction::eip2718::TypedTransaction, Address, TransactionRequest, H256, U256},
 };
+use futures_util::future::BoxFuture;
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use std::sync::Arc;
 use uuid::Uuid;
 
-use crate::config::Config;
+use crate::config::{Config, TokenDeployment, MAX_IDRX_DECIMALS};
 use crate::error::{AppError, AppResult};
+use crate::models::InvestWithPermitRequest;
 use crate::repository::{FundingRepository, InvoiceRepository};
+use crate::utils::{json_content_hash, mask_wallet, Metrics};
 
 use super::PinataService;
 
@@ -34,6 +38,15 @@ abigen!(
     ]"#
 );
 
+// EIP-2612 permit extension, only on IDRX deployments that support it
+abigen!(
+    IERC20Permit,
+    r#"[
+        function permit(address owner, address spender, uint256 value, uint256 deadline, uint8 v, bytes32 r, bytes32 s) external
+        function nonces(address owner) external view returns (uint256)
+    ]"#
+);
+
 // Generate InvoiceNFT contract bindings
 abigen!(
     InvoiceNFT,
@@ -41,6 +54,7 @@ abigen!(
         function mintInvoice(address to, string memory invoiceNumber, uint256 amount, uint256 advanceAmount, uint256 interestRate, uint256 issueDate, uint256 dueDate, string memory buyerCountry, string memory documentHash, string memory uri) external returns (uint256)
         function getTokenIdByInvoiceNumber(string memory invoiceNumber) external view returns (uint256)
         function verifyShipment(uint256 tokenId) external
+        function ownerOf(uint256 tokenId) external view returns (address)
     ]"#
 );
 
@@ -51,8 +65,12 @@ abigen!(
         function createPool(uint256 tokenId) external
         function recordInvestment(uint256 tokenId, address investor, uint256 amount) external
         function recordDisbursement(uint256 tokenId) external
+        function recordDisbursementTo(uint256 tokenId, address recipient) external
         function recordRepayment(uint256 tokenId, uint256 totalAmount, uint256[] calldata investorReturns) external
         function closePoolEarly(uint256 tokenId) external
+        function reopenPool(uint256 tokenId) external
+        function platformFeeBps() external view returns (uint256)
+        event InvestmentRecorded(uint256 indexed tokenId, address indexed investor, uint256 amount, uint256 expectedReturn)
     ]"#
 );
 
@@ -72,6 +90,203 @@ abigen!(
     ]"#
 );
 
+/// Addresses per Multicall3 `aggregate3` round trip
+const MULTICALL_BATCH_SIZE: usize = 500;
+
+/// Read `balanceOf` for every holder through Multicall3 `aggregate3`,
+/// one `eth_call` per `MULTICALL_BATCH_SIZE` holders. Balances come back in input order.
+pub async fn fetch_erc20_balances<M: Middleware>(
+    client: &M,
+    multicall_addr: Address,
+    token_addr: Address,
+    holders: &[Address],
+) -> AppResult<Vec<U256>> {
+    let balance_of_selector = &ethers::utils::id("balanceOf(address)")[..4];
+    let aggregate3_selector = &ethers::utils::id("aggregate3((address,bool,bytes)[])")[..4];
+
+    let mut balances = Vec::with_capacity(holders.len());
+    for batch in holders.chunks(MULTICALL_BATCH_SIZE) {
+        let calls = batch
+            .iter()
+            .map(|holder| {
+                let mut call_data = balance_of_selector.to_vec();
+                call_data.extend(abi::encode(&[abi::Token::Address(*holder)]));
+                abi::Token::Tuple(vec![
+                    abi::Token::Address(token_addr),
+                    abi::Token::Bool(false), // any failed read reverts the whole batch
+                    abi::Token::Bytes(call_data),
+                ])
+            })
+            .collect();
+
+        let mut data = aggregate3_selector.to_vec();
+        data.extend(abi::encode(&[abi::Token::Array(calls)]));
+
+        let tx: TypedTransaction = TransactionRequest::new()
+            .to(multicall_addr)
+            .data(data)
+            .into();
+        let output = client.call(&tx, None).await.map_err(|e| {
+            AppError::BlockchainError(format!("Multicall balance lookup failed: {}", e))
+        })?;
+
+        let decode_err =
+            || AppError::BlockchainError("Malformed multicall balance response".to_string());
+        let results = abi::decode(
+            &[abi::ParamType::Array(Box::new(abi::ParamType::Tuple(vec![
+                abi::ParamType::Bool,
+                abi::ParamType::Bytes,
+            ])))],
+            &output,
+        )
+        .map_err(|_| decode_err())?;
+
+        let results = match results.into_iter().next() {
+            Some(abi::Token::Array(results)) if results.len() == batch.len() => results,
+            _ => return Err(decode_err()),
+        };
+
+        for result in results {
+            let return_data = match result {
+                abi::Token::Tuple(fields) => match fields.as_slice() {
+                    [abi::Token::Bool(true), abi::Token::Bytes(data)] => data.clone(),
+                    _ => return Err(decode_err()),
+                },
+                _ => return Err(decode_err()),
+            };
+            let balance = abi::decode(&[abi::ParamType::Uint(256)], &return_data)
+                .ok()
+                .and_then(|tokens| tokens.into_iter().next())
+                .and_then(|token| token.into_uint())
+                .ok_or_else(decode_err)?;
+            balances.push(balance);
+        }
+    }
+
+    Ok(balances)
+}
+
+/// Read the fee the InvoicePool contract withholds, in basis points. The
+/// owner can change it with `setPlatformFee`.
+pub async fn fetch_platform_fee_bps<M: Middleware + 'static>(
+    client: Arc<M>,
+    pool_addr: Address,
+) -> AppResult<u32> {
+    let fee_bps = InvoicePool::new(pool_addr, client)
+        .platform_fee_bps()
+        .call()
+        .await
+        .map_err(|e| AppError::BlockchainError(format!("Failed to read platformFeeBps: {}", e)))?;
+
+    u32::try_from(fee_bps).map_err(|_| {
+        AppError::BlockchainError(format!("platformFeeBps {} is out of range", fee_bps))
+    })
+}
+
+/// Transfers returned per transfer history page unless the caller asks otherwise
+pub const DEFAULT_TRANSFER_HISTORY_LIMIT: usize = 100;
+pub const MAX_TRANSFER_HISTORY_LIMIT: usize = 500;
+
+/// Fetch ERC-20 `Transfer` logs of `token` sent to or from `holder` within
+/// `from_block..=to_block`, one `eth_getLogs` per direction.
+pub async fn fetch_transfer_logs<M: Middleware>(
+    client: &M,
+    token: Address,
+    holder: Address,
+    from_block: u64,
+    to_block: u64,
+) -> AppResult<Vec<Log>> {
+    let transfer_topic = H256::from_slice(&ethers::utils::keccak256(
+        "Transfer(address,address,uint256)",
+    ));
+
+    // Pad address to 32 bytes for topic filter
+    let holder_topic = H256::from_slice(&{
+        let mut padded = [0u8; 32];
+        padded[12..32].copy_from_slice(holder.as_bytes());
+        padded
+    });
+
+    let base_filter = Filter::new()
+        .address(token)
+        .topic0(transfer_topic)
+        .from_block(from_block)
+        .to_block(to_block);
+
+    // Incoming transfers (to = holder), then outgoing (from = holder)
+    let mut logs = client
+        .get_logs(&base_filter.clone().topic2(holder_topic))
+        .await
+        .map_err(|e| AppError::BlockchainError(e.to_string()))?;
+    let outgoing = client
+        .get_logs(&base_filter.topic1(holder_topic))
+        .await
+        .map_err(|e| AppError::BlockchainError(e.to_string()))?;
+    logs.extend(outgoing);
+
+    Ok(logs)
+}
+
+/// A decoded IDRX `Transfer` event
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TransferRecord {
+    pub tx_hash: String,
+    pub block_number: u64,
+    pub log_index: u64,
+    pub from: String,
+    pub to: String,
+    pub amount: f64,
+    pub direction: &'static str,
+    pub explorer_url: String,
+}
+
+/// One page of transfer history, newest block first
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TransferHistoryPage {
+    pub transfers: Vec<TransferRecord>,
+    /// Block range actually scanned
+    pub from_block: u64,
+    pub to_block: u64,
+    /// Pass as `from_block` to fetch the next page; `None` once `upper_block` is reached
+    pub next_from_block: Option<u64>,
+}
+
+/// Order transfers scanned from a block range ending at `to_block` and cut them
+/// to `limit`. Pages always end on a block boundary so no transfer is skipped or
+/// repeated by the next page, except when a single block alone exceeds `limit`.
+/// Returns the page newest first and the block the next page starts at.
+pub fn paginate_transfers(
+    mut transfers: Vec<TransferRecord>,
+    limit: usize,
+    to_block: u64,
+    upper_block: u64,
+) -> (Vec<TransferRecord>, Option<u64>) {
+    transfers.sort_by(|a, b| {
+        (a.block_number, a.log_index, &a.tx_hash).cmp(&(b.block_number, b.log_index, &b.tx_hash))
+    });
+    // A self-transfer matches both the incoming and the outgoing filter
+    transfers.dedup_by(|a, b| a.tx_hash == b.tx_hash && a.log_index == b.log_index);
+
+    let mut next_from_block = (to_block < upper_block).then(|| to_block + 1);
+    if transfers.len() > limit {
+        let cut_block = transfers[limit].block_number;
+        let kept = transfers.partition_point(|t| t.block_number < cut_block);
+        if kept > 0 {
+            transfers.truncate(kept);
+            next_from_block = Some(cut_block);
+        } else {
+            transfers.truncate(limit);
+            next_from_block = (cut_block < upper_block).then(|| cut_block + 1);
+        }
+    }
+
+    transfers.reverse();
+    (transfers, next_from_block)
+}
+
+/// Rounding slack (0.01 IDRX) accepted when verifying investment and repayment transfers
+pub const TRANSFER_AMOUNT_TOLERANCE: Decimal = Decimal::from_parts(1, 0, 0, false, 2);
+
 /// Represents a verified on-chain IDRX transfer
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct VerifiedTransfer {
@@ -92,6 +307,539 @@ pub enum OnChainTxType {
     Repayment,
     InvestorReturn,
     PlatformFee,
+    Refund,
+}
+
+/// Gas reported for sends in test mode (`skip_blockchain_verification`)
+pub const TEST_MODE_GAS_USED: u64 = 65_000;
+pub const TEST_MODE_GAS_PRICE_WEI: u64 = 1_000_000;
+
+/// A transaction sent and paid for by the platform wallet, with the gas it cost
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SentTransaction {
+    pub tx_hash: String,
+    pub block_number: u64,
+    pub gas_used: u64,
+    /// Price per gas unit actually paid, in wei
+    pub effective_gas_price: u64,
+}
+
+impl SentTransaction {
+    fn from_receipt(receipt: &TransactionReceipt) -> Self {
+        Self {
+            tx_hash: format!("{:?}", receipt.transaction_hash),
+            block_number: receipt.block_number.map(|n| n.as_u64()).unwrap_or(0),
+            gas_used: receipt.gas_used.map(|g| g.as_u64()).unwrap_or(0),
+            effective_gas_price: receipt
+                .effective_gas_price
+                .map(|p| p.as_u64())
+                .unwrap_or(0),
+        }
+    }
+
+    fn simulated(tx_hash: String) -> Self {
+        Self {
+            tx_hash,
+            block_number: 12345,
+            gas_used: TEST_MODE_GAS_USED,
+            effective_gas_price: TEST_MODE_GAS_PRICE_WEI,
+        }
+    }
+}
+
+/// EIP-2612 permit signed by a token holder
+#[derive(Debug, Clone)]
+pub struct PermitAuthorization {
+    pub owner: Address,
+    pub spender: Address,
+    pub value: U256,
+    pub deadline: U256,
+    pub v: u8,
+    pub r: [u8; 32],
+    pub s: [u8; 32],
+}
+
+/// Submits a permit and then pulls the permitted funds with `transferFrom`,
+/// both sent by the platform wallet (the permit's spender)
+pub trait PermitToken: Send + Sync {
+    /// Returns the `transferFrom` transaction moving `amount` from the owner to `to`
+    fn permit_and_transfer<'a>(
+        &'a self,
+        permit: &'a PermitAuthorization,
+        to: Address,
+        amount: U256,
+    ) -> BoxFuture<'a, AppResult<SentTransaction>>;
+}
+
+/// Calls `permit` and `transferFrom` on the active IDRX contract
+pub struct ContractPermitToken {
+    config: Arc<Config>,
+    provider: Provider<Http>,
+    wallet: Option<LocalWallet>,
+    contract_addr: String,
+}
+
+impl PermitToken for ContractPermitToken {
+    fn permit_and_transfer<'a>(
+        &'a self,
+        permit: &'a PermitAuthorization,
+        to: Address,
+        amount: U256,
+    ) -> BoxFuture<'a, AppResult<SentTransaction>> {
+        Box::pin(async move {
+            if self.config.skip_blockchain_verification {
+                tracing::info!("SKIPPING permit transfer (Test Mode)");
+                return Ok(SentTransaction::simulated(format!(
+                    "0xTestPermitTransferHash_{}",
+                    Uuid::new_v4()
+                )));
+            }
+
+            let wallet = self.wallet.as_ref().ok_or_else(|| {
+                AppError::BlockchainError("Platform wallet not configured".to_string())
+            })?;
+            let contract_addr: Address = self.contract_addr.parse().map_err(|_| {
+                AppError::BlockchainError("Invalid IDRX contract address".to_string())
+            })?;
+            let client = Arc::new(SignerMiddleware::new(self.provider.clone(), wallet.clone()));
+
+            let permit_tx = IERC20Permit::new(contract_addr, client.clone()).permit(
+                permit.owner,
+                permit.spender,
+                permit.value,
+                permit.deadline,
+                permit.v,
+                permit.r,
+                permit.s,
+            );
+            let receipt = permit_tx
+                .send()
+                .await
+                .map_err(|e| AppError::BlockchainError(format!("Permit failed: {}", e)))?
+                .await
+                .map_err(|e| AppError::BlockchainError(format!("Permit failed: {}", e)))?
+                .ok_or_else(|| {
+                    AppError::BlockchainError("Permit transaction dropped".to_string())
+                })?;
+            if receipt.status.map(|s| s.as_u64()) != Some(1) {
+                return Err(AppError::BlockchainError(
+                    "Permit transaction reverted".to_string(),
+                ));
+            }
+
+            let transfer_tx =
+                IERC20::new(contract_addr, client).transfer_from(permit.owner, to, amount);
+            let receipt = transfer_tx
+                .send()
+                .await
+                .map_err(|e| AppError::BlockchainError(format!("transferFrom failed: {}", e)))?
+                .await
+                .map_err(|e| AppError::BlockchainError(format!("transferFrom failed: {}", e)))?
+                .ok_or_else(|| {
+                    AppError::BlockchainError("transferFrom transaction dropped".to_string())
+                })?;
+            if receipt.status.map(|s| s.as_u64()) != Some(1) {
+                return Err(AppError::BlockchainError(
+                    "transferFrom transaction reverted".to_string(),
+                ));
+            }
+
+            Ok(SentTransaction::from_receipt(&receipt))
+        })
+    }
+}
+
+/// Gas figures for IDRX transfers sent and paid for by the platform wallet
+pub trait GasOracle: Send + Sync {
+    /// Gas units an IDRX `transfer(to, amount)` from the platform wallet is expected to use
+    fn estimate_transfer_gas<'a>(
+        &'a self,
+        to: &'a str,
+        amount: U256,
+    ) -> BoxFuture<'a, AppResult<U256>>;
+
+    /// Current price per gas unit, in wei
+    fn gas_price(&self) -> BoxFuture<'_, AppResult<U256>>;
+
+    /// Native-token balance of the platform wallet, in wei
+    fn platform_native_balance(&self) -> BoxFuture<'_, AppResult<U256>>;
+}
+
+/// Reads gas figures from the RPC node of the active IDRX deployment
+pub struct ProviderGasOracle {
+    config: Arc<Config>,
+    provider: Provider<Http>,
+    wallet: Option<LocalWallet>,
+    contract_addr: String,
+}
+
+impl ProviderGasOracle {
+    fn platform_address(&self) -> AppResult<Address> {
+        self.wallet
+            .as_ref()
+            .map(|w| w.address())
+            .ok_or_else(|| AppError::BlockchainError("Platform wallet not configured".to_string()))
+    }
+}
+
+impl GasOracle for ProviderGasOracle {
+    fn estimate_transfer_gas<'a>(
+        &'a self,
+        to: &'a str,
+        amount: U256,
+    ) -> BoxFuture<'a, AppResult<U256>> {
+        Box::pin(async move {
+            if self.config.skip_blockchain_verification {
+                return Ok(U256::from(TEST_MODE_GAS_USED));
+            }
+
+            let to_addr: Address = to
+                .parse()
+                .map_err(|_| AppError::ValidationError("Invalid recipient address".to_string()))?;
+            let contract_addr: Address = self.contract_addr.parse().map_err(|_| {
+                AppError::BlockchainError("Invalid IDRX contract address".to_string())
+            })?;
+
+            IERC20::new(contract_addr, Arc::new(self.provider.clone()))
+                .transfer(to_addr, amount)
+                .from(self.platform_address()?)
+                .estimate_gas()
+                .await
+                .map_err(|e| AppError::BlockchainError(format!("Gas estimation failed: {}", e)))
+        })
+    }
+
+    fn gas_price(&self) -> BoxFuture<'_, AppResult<U256>> {
+        Box::pin(async move {
+            if self.config.skip_blockchain_verification {
+                return Ok(U256::from(TEST_MODE_GAS_PRICE_WEI));
+            }
+            self.provider
+                .get_gas_price()
+                .await
+                .map_err(|e| AppError::BlockchainError(e.to_string()))
+        })
+    }
+
+    fn platform_native_balance(&self) -> BoxFuture<'_, AppResult<U256>> {
+        Box::pin(async move {
+            // Test mode sends nothing, so gas is never short
+            if self.config.skip_blockchain_verification {
+                return Ok(U256::MAX);
+            }
+            self.provider
+                .get_balance(self.platform_address()?, None)
+                .await
+                .map_err(|e| AppError::BlockchainError(e.to_string()))
+        })
+    }
+}
+
+/// Expected gas cost of a platform-sent transfer against the platform wallet's native balance
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasCheck {
+    pub gas_limit: U256,
+    pub gas_price: U256,
+    pub platform_balance: U256,
+}
+
+impl GasCheck {
+    /// Estimated cost in wei
+    pub fn cost(&self) -> U256 {
+        self.gas_limit.saturating_mul(self.gas_price)
+    }
+
+    pub fn is_sufficient(&self) -> bool {
+        self.platform_balance >= self.cost()
+    }
+}
+
+/// An `InvestmentRecorded` event emitted by the InvoicePool contract
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OnChainInvestment {
+    pub investor: Address,
+    /// IDRX, converted from token units
+    pub amount: Decimal,
+    pub tx_hash: Option<H256>,
+}
+
+/// Reads the investments recorded on the InvoicePool contract
+pub trait InvestmentEventSource: Send + Sync {
+    /// Every `InvestmentRecorded` event of the pool for `token_id`
+    fn investment_events(&self, token_id: i64) -> BoxFuture<'_, AppResult<Vec<OnChainInvestment>>>;
+}
+
+/// Queries InvoicePool event logs through the RPC node
+pub struct ProviderInvestmentEventSource {
+    config: Arc<Config>,
+    provider: Provider<Http>,
+    pool_contract_addr: String,
+    idrx_decimals: u8,
+}
+
+impl InvestmentEventSource for ProviderInvestmentEventSource {
+    fn investment_events(&self, token_id: i64) -> BoxFuture<'_, AppResult<Vec<OnChainInvestment>>> {
+        Box::pin(async move {
+            // Test mode records nothing on chain, so there is nothing to compare against
+            if self.config.skip_blockchain_verification {
+                return Err(AppError::BlockchainError(
+                    "On-chain investment events are unavailable in test mode".to_string(),
+                ));
+            }
+
+            let contract_addr: Address = self.pool_contract_addr.parse().map_err(|_| {
+                AppError::BlockchainError("Invalid InvoicePool contract address".to_string())
+            })?;
+            let contract = InvoicePool::new(contract_addr, Arc::new(self.provider.clone()));
+
+            // Page from the deployment block in ranges the RPC provider accepts
+            let latest = self
+                .provider
+                .get_block_number()
+                .await
+                .map_err(|e| AppError::BlockchainError(e.to_string()))?
+                .as_u64();
+            let max_range = self.config.transfer_history_max_block_range.max(1);
+            let mut events = Vec::new();
+            let mut from = self.config.transfer_history_start_block;
+            while from <= latest {
+                let to = latest.min(from.saturating_add(max_range - 1));
+                let page = contract
+                    .investment_recorded_filter()
+                    .topic1(H256::from_low_u64_be(token_id as u64))
+                    .from_block(from)
+                    .to_block(to)
+                    .query_with_meta()
+                    .await
+                    .map_err(|e| {
+                        AppError::BlockchainError(format!(
+                            "Failed to read investment events in blocks {}-{}: {}",
+                            from, to, e
+                        ))
+                    })?;
+                events.extend(page);
+                from = to + 1;
+            }
+
+            events
+                .into_iter()
+                .map(|(event, meta)| {
+                    Ok(OnChainInvestment {
+                        investor: event.investor,
+                        amount: units_to_decimal(event.amount, self.idrx_decimals)?,
+                        tx_hash: Some(meta.transaction_hash),
+                    })
+                })
+                .collect()
+        })
+    }
+}
+
+/// Reads who holds an invoice NFT on the InvoiceNFT contract
+pub trait NftOwnerSource: Send + Sync {
+    /// `ownerOf(token_id)`
+    fn owner_of(&self, token_id: i64) -> BoxFuture<'_, AppResult<Address>>;
+}
+
+/// Calls `ownerOf` through the RPC node
+pub struct ProviderNftOwnerSource {
+    config: Arc<Config>,
+    provider: Provider<Http>,
+    nft_contract_addr: String,
+}
+
+impl NftOwnerSource for ProviderNftOwnerSource {
+    fn owner_of(&self, token_id: i64) -> BoxFuture<'_, AppResult<Address>> {
+        Box::pin(async move {
+            // Test mode mints nothing on chain
+            if self.config.skip_blockchain_verification {
+                return Err(AppError::BlockchainError(
+                    "On-chain NFT ownership is unavailable in test mode".to_string(),
+                ));
+            }
+
+            let contract_addr: Address = self.nft_contract_addr.parse().map_err(|_| {
+                AppError::BlockchainError("Invalid InvoiceNFT contract address".to_string())
+            })?;
+            let contract = InvoiceNFT::new(contract_addr, Arc::new(self.provider.clone()));
+
+            contract
+                .owner_of(U256::from(token_id as u64))
+                .call()
+                .await
+                .map_err(|e| AppError::BlockchainError(format!("Failed to read NFT owner: {}", e)))
+        })
+    }
+}
+
+/// Token units to a Decimal. Amounts beyond Decimal's 96-bit mantissa are an
+/// error rather than a silently wrong value.
+fn units_to_decimal(units: U256, decimals: u8) -> AppResult<Decimal> {
+    u128::try_from(units)
+        .ok()
+        .and_then(|units| i128::try_from(units).ok())
+        .and_then(|units| Decimal::try_from_i128_with_scale(units, decimals as u32).ok())
+        .ok_or_else(|| AppError::BlockchainError(format!("Token amount {} is out of range", units)))
+}
+
+/// Selector of Solidity's `Error(string)`, raised by `require` and `revert` with a message
+const ERROR_STRING_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+/// Message of an `Error(string)` revert payload. `None` for custom errors,
+/// panics and anything that does not decode.
+pub fn decode_revert_reason(data: &[u8]) -> Option<String> {
+    let payload = data.strip_prefix(&ERROR_STRING_SELECTOR[..])?;
+    let mut tokens = abi::decode(&[abi::ParamType::String], payload).ok()?;
+    match tokens.pop()? {
+        abi::Token::String(reason) => Some(reason),
+        _ => None,
+    }
+}
+
+/// Revert reason carried by a failed contract call, if the node returned one
+fn revert_reason<M: Middleware>(e: &ContractError<M>) -> Option<String> {
+    e.as_revert().and_then(|data| decode_revert_reason(data))
+}
+
+/// How long a successful smart wallet verification is reused, in seconds. Long
+/// enough to cover a retried login, short enough to stay well inside a nonce's life.
+pub const ERC1271_CACHE_TTL_SECS: u64 = 30;
+
+/// Checks signatures of smart contract wallets on chain
+pub trait ContractSignatureValidator: Send + Sync {
+    /// ERC-1271 for deployed wallets, EIP-6492 for counterfactual ones
+    fn is_valid_signature<'a>(
+        &'a self,
+        signer: Address,
+        message_hash: [u8; 32],
+        signature: &'a [u8],
+    ) -> BoxFuture<'a, AppResult<bool>>;
+}
+
+/// Validates through the RPC node of the active IDRX deployment
+pub struct ProviderSignatureValidator {
+    provider: Provider<Http>,
+}
+
+impl ContractSignatureValidator for ProviderSignatureValidator {
+    fn is_valid_signature<'a>(
+        &'a self,
+        signer: Address,
+        message_hash: [u8; 32],
+        signature: &'a [u8],
+    ) -> BoxFuture<'a, AppResult<bool>> {
+        Box::pin(async move {
+            // Check if address has code (Deployed Smart Wallet)
+            let code = self
+                .provider
+                .get_code(signer, None)
+                .await
+                .map_err(|e| AppError::BlockchainError(format!("Failed to get code: {}", e)))?;
+
+            if !code.is_empty() {
+                // 0x1626ba7e is the bytes4 magic value for isValidSignature
+                let magic_value = [0x16, 0x26, 0xba, 0x7e];
+                let contract = IERC1271::new(signer, Arc::new(self.provider.clone()));
+
+                // Try standard ERC-1271 first for deployed contracts
+                let result = contract
+                    .is_valid_signature(message_hash, signature.to_vec().into())
+                    .call()
+                    .await;
+
+                match result {
+                    Ok(val) => {
+                        if val == magic_value {
+                            return Ok(true);
+                        }
+                    }
+                    Err(e) => {
+                        tracing::warn!("Standard ERC-1271 failed for deployed contract: {}", e);
+                    }
+                }
+            }
+
+            // Fallback to Universal Signature Validator (EIP-6492)
+            // This handles undeployed contracts (counterfactual) and also retries 1271 securely
+            self.verify_signature_universal(signer, message_hash, signature)
+                .await
+        })
+    }
+}
+
+impl ProviderSignatureValidator {
+    /// Use EIP-6492 Universal Signature Validator
+    /// Contract: 0x6492c034cc609e99298b3097c29bc906df0c0522 (Base Mainnet & Sepolia)
+    /// Use EIP-6492 Universal Signature Validator
+    /// Contract: 0x6492c034cc609e99298b3097c29bc906df0c0522 (Base Mainnet & Sepolia)
+    async fn verify_signature_universal(
+        &self,
+        signer: Address,
+        hash: [u8; 32],
+        signature: &[u8],
+    ) -> AppResult<bool> {
+        let validator_addr: Address = "0x6492c034cc609e99298b3097c29bc906df0c0522".parse().unwrap();
+        
+        // UniversalSigValidator Runtime Bytecode (fetched from Base Mainnet)
+        // This allows us to use state overrides if the contract is missing on the current chain
+        let validator_bytecode = "0x608060405234801561000f575f5ffd5b506004361061003f575f3560e01c806376be4cea146100435780638f0684301461007357806398ef1ed8146100a3575b5f5ffd5b61005d600480360381019061005891906108e1565b6100d3565b60405161006a9190610986565b60405180910390f35b61008d6004803603810190610088919061099f565b6105fe565b60405161009a9190610986565b60405180910390f35b6100bd60048036038101906100b8919061099f565b61068d565b6040516100ca9190610986565b60405180910390f35b5f5f8773ffffffffffffffffffffffffffffffffffffffff163b905060605f7f64926492649264926492649264926492649264926492649264926492649264925f1b888860208b8b90506101279190610a46565b908b8b90509261013993929190610a81565b906101449190610ad1565b1490508015610250575f606089895f9060208d8d90506101649190610a46565b9261017193929190610a81565b81019061017e9190610ca2565b8096508193508294505050505f8514806101955750865b15610249575f5f8373ffffffffffffffffffffffffffffffffffffffff16836040516101c19190610d7c565b5f604051808303815f865af19150503d805f81146101fa576040519150601f19603f3d011682016040523d82523d5f602084013e6101ff565b606091505b50915091508161024657806040517f9d0d6e2d00000000000000000000000000000000000000000000000000000000815260040161023d9190610dda565b60405180910390fd5b50505b5050610297565b87878080601f0160208091040260200160405190810160405280939291908181526020018383808284375f81840152601f19601f8201169050808301925050505050505091505b80806102a257505f83115b1561046c578973ffffffffffffffffffffffffffffffffffffffff16631626ba7e8a846040518363ffffffff1660e01b81526004016102e2929190610e09565b602060405180830381865afa92505050801561031c57506040513d601f19601f820116820180604052508101906103199190610e8c565b60015b6103b9573d805f811461034a576040519150601f19603f3d011682016040523d82523d5f602084013e61034f565b606091505b508515801561035d57505f84115b1561037c576103718b8b8b8b8b60016100d3565b9450505050506105f4565b806040517f6f2a95990000000000000000000000000000000000000000000000000000000081526004016103b09190610dda565b60405180910390fd5b5f631626ba7e60e01b7bffffffffffffffffffffffffffffffffffffffffffffffffffffffff1916827bffffffffffffffffffffffffffffffffffffffffffffffffffffffff191614905080158015610410575086155b801561041b57505f85115b1561043b5761042f8c8c8c8c8c60016100d3565b955050505050506105f4565b5f851480156104475750825b8015610451575087155b1561045f57805f526001601ffd5b80955050505050506105f4565b604188889050146104b2576040517f08c379a00000000000000000000000000000000000000000000000000000000081526004016104a990610f37565b60405180910390fd5b5f88885f906020926104c693929190610a81565b906104d19190610ad1565b90505f89896020906040926104e893929190610a81565b906104f39190610ad1565b90505f8a8a604081811061050a57610509610f55565b5b9050013560f81c60f81b60f81c9050601b8160ff16141580156105315750601c8160ff1614155b15610571576040517f08c379a000000000000000000000000000000000000000000000000000000000815260040161056890610ff2565b60405180910390fd5b8c73ffffffffffffffffffffffffffffffffffffffff1660018d8386866040515f81526020016040526040516105aa949392919061102b565b6020604051602081039080840390855afa1580156105ca573d5f5f3e3d5ffd5b5050506020604051035173ffffffffffffffffffffffffffffffffffffffff161496505050505050505b9695505050505050565b5f3073ffffffffffffffffffffffffffffffffffffffff166376be4cea8686868660015f6040518763ffffffff1660e01b8152600401610643969594939291906110a9565b6020604051808303815f875af115801561065f573d5f5f3e3d5ffd5b505050506040513d601f19601f820116820180604052508101906106839190611117565b9050949350505050565b5f3073ffffffffffffffffffffffffffffffffffffffff166376be4cea868686865f5f6040518763ffffffff1660e01b81526004016106d1969594939291906110a9565b6020604051808303815f875af192505050801561070c57506040513d601f19601f820116820180604052508101906107099190611117565b60015b6107a0573d805f811461073a576040519150601f19603f3d011682016040523d82523d5f602084013e61073f565b606091505b505f815190506001810361079c57600160f81b825f8151811061076557610764610f55565b5b602001015160f81c60f81b7effffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff191614925050506107a5565b8082fd5b809150505b949350505050565b5f604051905090565b5f5ffd5b5f5ffd5b5f73ffffffffffffffffffffffffffffffffffffffff82169050919050565b5f6107e7826107be565b9050919050565b6107f7816107dd565b8114610801575f5ffd5b50565b5f81359050610812816107ee565b92915050565b5f819050919050565b61082a81610818565b8114610834575f5ffd5b50565b5f8135905061084581610821565b92915050565b5f5ffd5b5f5ffd5b5f5ffd5b5f5f83601f84011261086c5761086b61084b565b5b8235905067ffffffffffffffff8111156108895761088861084f565b5b6020830191508360018202830111156108a5576108a4610853565b5b9250929050565b5f8115159050919050565b6108c0816108ac565b81146108ca575f5ffd5b50565b5f813590506108db816108b7565b92915050565b5f5f5f5f5f5f60a087890312156108fb576108fa6107b6565b5b5f61090889828a01610804565b965050602061091989828a01610837565b955050604087013567ffffffffffffffff81111561093a576109396107ba565b5b61094689828a01610857565b9450945050606061095989828a016108cd565b925050608061096a89828a016108cd565b9150509295509295509295565b610980816108ac565b82525050565b5f6020820190506109995f830184610977565b92915050565b5f5f5f5f606085870312156109b7576109b66107b6565b5b5f6109c487828801610804565b94505060206109d587828801610837565b935050604085013567ffffffffffffffff8111156109f6576109f56107ba565b5b610a0287828801610857565b925092505092959194509250565b5f819050919050565b7f4e487b71000000000000000000000000000000000000000000000000000000005f52601160045260245ffd5b5f610a5082610a10565b9150610a5b83610a10565b9250828203905081811115610a7357610a72610a19565b5b92915050565b5f5ffd5b5f5ffd5b5f5f85851115610a9457610a93610a79565b5b83861115610aa557610aa4610a7d565b5b6001850283019150848603905094509492505050565b5f82905092915050565b5f82821b905092915050565b5f610adc8383610abb565b82610ae78135610818565b92506020821015610b2757610b227fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff83602003600802610ac5565b831692505b505092915050565b5f610b39826107be565b9050919050565b610b4981610b2f565b8114610b53575f5ffd5b50565b5f81359050610b6481610b40565b92915050565b5f5ffd5b5f601f19601f8301169050919050565b7f4e487b71000000000000000000000000000000000000000000000000000000005f52604160045260245ffd5b610bb482610b6e565b810181811067ffffffffffffffff82111715610bd357610bd2610b7e565b5b80604052505050565b5f610be56107ad565b9050610bf18282610bab565b919050565b5f67ffffffffffffffff821115610c1057610c0f610b7e565b5b610c1982610b6e565b9050602081019050919050565b828183375f83830152505050565b5f610c46610c4184610bf6565b610bdc565b905082815260208101848484011115610c6257610c61610b6a565b5b610c6d848285610c26565b509392505050565b5f82601f830112610c8957610c8861084b565b5b8135610c99848260208601610c34565b91505092915050565b5f5f5f60608486031215610cb957610cb86107b6565b5b5f610cc686828701610b56565b935050602084013567ffffffffffffffff811115610ce757610ce66107ba565b5b610cf386828701610c75565b925050604084013567ffffffffffffffff811115610d1457610d136107ba565b5b610d2086828701610c75565b9150509250925092565b5f81519050919050565b5f81905092915050565b8281835e5f83830152505050565b5f610d5682610d2a565b610d608185610d34565b9350610d70818560208601610d3e565b80840191505092915050565b5f610d878284610d4c565b915081905092915050565b5f82825260208201905092915050565b5f610dac82610d2a565b610db68185610d92565b9350610dc6818560208601610d3e565b610dcf81610b6e565b840191505092915050565b5f6020820190508181035f830152610df28184610da2565b905092915050565b610e0381610818565b82525050565b5f604082019050610e1c5f830185610dfa565b8181036020830152610e2e8184610da2565b90509392505050565b5f7fffffffff0000000000000000000000000000000000000000000000000000000082169050919050565b610e6b81610e37565b8114610e75575f5ffd5b50565b5f81519050610e8681610e62565b92915050565b5f60208284031215610ea157610ea06107b6565b5b5f610eae84828501610e78565b91505092915050565b5f82825260208201905092915050565b7f5369676e617475726556616c696461746f72237265636f7665725369676e65725f8201527f3a20696e76616c6964207369676e6174757265206c656e677468000000000000602082015250565b5f610f21603a83610eb7565b9150610f2c82610ec7565b604082019050919050565b5f6020820190508181035f830152610f4e81610f15565b9050919050565b7f4e487b71000000000000000000000000000000000000000000000000000000005f52603260045260245ffd5b7f5369676e617475726556616c696461746f723a20696e76616c6964207369676e5f8201527f617475726520762076616c756500000000000000000000000000000000000000602082015250565b5f610fdc602d83610eb7565b9150610fe782610f82565b604082019050919050565b5f6020820190508181035f83015261100981610fd0565b9050919050565b5f60ff82169050919050565b61102581611010565b82525050565b5f60808201905061103e5f830187610dfa565b61104b602083018661101c565b6110586040830185610dfa565b6110656060830184610dfa565b95945050505050565b611077816107dd565b82525050565b5f6110888385610d92565b9350611095838584610c26565b61109e83610b6e565b840190509392505050565b5f60a0820190506110bc5f83018961106e565b6110c96020830188610dfa565b81810360408301526110dc81868861107d565b90506110eb6060830185610977565b6110f86080830184610977565b979650505050505050565b5f81519050611111816108b7565b92915050565b5f6020828403121561112c5761112b6107b6565b5b5f61113984828501611103565b9150509291505056fea2646970667358221220a097e3b3de576882cc80ec9fc7e5e58495b422f643739b61de8d128d51ee11ee64736f6c634300081c0033";
+        
+        let contract = UniversalSigValidator::new(validator_addr, Arc::new(self.provider.clone()));
+        
+        // Encode the transaction data
+        let calldata = contract
+            .is_valid_sig(signer, hash, signature.to_vec().into())
+            .calldata()
+            .ok_or_else(|| AppError::BlockchainError("Failed to encode calldata".to_string()))?;
+
+        // Construct the raw JSON-RPC request for eth_call with state overrides
+        // Params: [ { to, data }, "latest", { address: { code } } ]
+        
+        let tx_obj = serde_json::json!({
+            "to": validator_addr,
+            "data": calldata,
+        });
+        
+        // State override object: address -> { code: bytecode }
+        let state_overrides = serde_json::json!({
+            format!("{:?}", validator_addr): {
+                "code": validator_bytecode
+            }
+        });
+        
+        let params = (tx_obj, "latest", state_overrides);
+        
+        let result: Result<ethers::types::Bytes, _> = self.provider
+            .request("eth_call", params)
+            .await;
+            
+        match result {
+            Ok(bytes) => {
+                // Decode bool result (first 32 bytes)
+                if bytes.len() >= 32 {
+                     let is_valid = bytes[31] != 0; // check last byte of 32-byte word
+                     return Ok(is_valid);
+                }
+                Ok(false)
+            },
+            Err(e) => {
+                 tracing::error!("Universal Sig Validator (State Override) failed: {:?}", e);
+                 Ok(false)
+            }
+        }
+    }
+}
+
+/// Redis key for a successful smart wallet verification. The signature is part of
+/// the key, so only a resubmission of the exact same signature is served from cache.
+fn erc1271_cache_key(signer: Address, message_hash: &[u8; 32], signature: &[u8]) -> String {
+    let mut preimage = signer.as_bytes().to_vec();
+    preimage.extend_from_slice(message_hash);
+    preimage.extend_from_slice(signature);
+    format!(
+        "erc1271:{}",
+        ethers::utils::hex::encode(ethers::utils::keccak256(preimage))
+    )
 }
 
 pub struct BlockchainService {
@@ -101,7 +849,15 @@ pub struct BlockchainService {
     invoice_repo: Arc<InvoiceRepository>,
     funding_repo: Arc<FundingRepository>,
     pinata_service: Arc<PinataService>,
+    idrx_token: TokenDeployment,
     idrx_decimals: u8,
+    permit_token: Arc<dyn PermitToken>,
+    gas_oracle: Arc<dyn GasOracle>,
+    signature_validator: Arc<dyn ContractSignatureValidator>,
+    investment_events: Arc<dyn InvestmentEventSource>,
+    nft_owners: Arc<dyn NftOwnerSource>,
+    redis_pool: Option<deadpool_redis::Pool>,
+    metrics: Arc<Metrics>,
 }
 
 impl BlockchainService {
@@ -111,7 +867,8 @@ impl BlockchainService {
         funding_repo: Arc<FundingRepository>,
         pinata_service: Arc<PinataService>,
     ) -> AppResult<Self> {
-        let provider = Provider::<Http>::try_from(&config.blockchain_rpc_url)
+        let idrx_token = Self::select_idrx_token(&config);
+        let provider = Provider::<Http>::try_from(&idrx_token.rpc_url)
             .map_err(|e| AppError::BlockchainError(e.to_string()))?;
 
         let wallet = if !config.private_key.is_empty() {
@@ -119,14 +876,40 @@ impl BlockchainService {
                 .private_key
                 .parse()
                 .map_err(|e: WalletError| AppError::BlockchainError(e.to_string()))?;
-            Some(wallet.with_chain_id(config.chain_id))
+            Some(wallet.with_chain_id(idrx_token.chain_id))
         } else {
             tracing::warn!("Private key not configured, blockchain operations will be limited");
             None
         };
 
-        // IDRX uses 2 decimals (like IDR)
-        let idrx_decimals = 2u8;
+        let idrx_decimals = Self::detect_idrx_decimals(&config, &provider, &idrx_token).await;
+
+        let permit_token = Arc::new(ContractPermitToken {
+            config: config.clone(),
+            provider: provider.clone(),
+            wallet: wallet.clone(),
+            contract_addr: idrx_token.contract_addr.clone(),
+        });
+        let gas_oracle = Arc::new(ProviderGasOracle {
+            config: config.clone(),
+            provider: provider.clone(),
+            wallet: wallet.clone(),
+            contract_addr: idrx_token.contract_addr.clone(),
+        });
+        let signature_validator = Arc::new(ProviderSignatureValidator {
+            provider: provider.clone(),
+        });
+        let investment_events = Arc::new(ProviderInvestmentEventSource {
+            config: config.clone(),
+            provider: provider.clone(),
+            pool_contract_addr: idrx_token.pool_contract_addr.clone(),
+            idrx_decimals,
+        });
+        let nft_owners = Arc::new(ProviderNftOwnerSource {
+            config: config.clone(),
+            provider: provider.clone(),
+            nft_contract_addr: idrx_token.nft_contract_addr.clone(),
+        });
 
         Ok(Self {
             config,
@@ -135,18 +918,194 @@ impl BlockchainService {
             invoice_repo,
             funding_repo,
             pinata_service,
+            idrx_token,
             idrx_decimals,
+            permit_token,
+            gas_oracle,
+            signature_validator,
+            investment_events,
+            nft_owners,
+            redis_pool: None,
+            metrics: Arc::new(Metrics::new()),
+        })
+    }
+
+    /// Replace the permit transport, e.g. with a mock token in tests
+    pub fn with_permit_token(mut self, permit_token: Arc<dyn PermitToken>) -> Self {
+        self.permit_token = permit_token;
+        self
+    }
+
+    /// Replace the gas reads, e.g. with a mock oracle in tests
+    pub fn with_gas_oracle(mut self, gas_oracle: Arc<dyn GasOracle>) -> Self {
+        self.gas_oracle = gas_oracle;
+        self
+    }
+
+    /// Replace the smart wallet signature checks, e.g. with a mock in tests
+    pub fn with_signature_validator(
+        mut self,
+        signature_validator: Arc<dyn ContractSignatureValidator>,
+    ) -> Self {
+        self.signature_validator = signature_validator;
+        self
+    }
+
+    /// Replace the InvoicePool event reads, e.g. with a mock in tests
+    pub fn with_investment_events(
+        mut self,
+        investment_events: Arc<dyn InvestmentEventSource>,
+    ) -> Self {
+        self.investment_events = investment_events;
+        self
+    }
+
+    /// Replace the InvoiceNFT owner reads, e.g. with a mock in tests
+    pub fn with_nft_owners(mut self, nft_owners: Arc<dyn NftOwnerSource>) -> Self {
+        self.nft_owners = nft_owners;
+        self
+    }
+
+    /// Cache successful smart wallet verifications in Redis; without a pool every
+    /// verification goes to the RPC node
+    pub fn with_redis(mut self, redis_pool: Option<deadpool_redis::Pool>) -> Self {
+        self.redis_pool = redis_pool;
+        self
+    }
+
+    /// Share the application's metrics registry for on-chain transfer counters
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Pick the highest-priority IDRX deployment that is configured and not paused.
+    /// Falls back to the primary one when nothing else is usable.
+    fn select_idrx_token(config: &Config) -> TokenDeployment {
+        let tokens = config.idrx_tokens();
+        let active = tokens
+            .iter()
+            .find(|t| !t.contract_addr.is_empty() && !config.is_idrx_token_paused(&t.contract_addr))
+            .cloned();
+
+        match active {
+            Some(token) => {
+                if token != tokens[0] {
+                    tracing::warn!(
+                        "Primary IDRX token is unavailable, using fallback {} on chain {}",
+                        token.contract_addr,
+                        token.chain_id
+                    );
+                }
+                token
+            }
+            None => {
+                tracing::warn!("No usable IDRX token configured, using the primary one");
+                tokens[0].clone()
+            }
+        }
+    }
+
+    /// IDRX deployment used for balances, transfer verification and transfers
+    pub fn active_idrx_token(&self) -> &TokenDeployment {
+        &self.idrx_token
+    }
+
+    /// InvoicePool contract on the active deployment's chain
+    pub fn pool_contract_addr(&self) -> &str {
+        &self.idrx_token.pool_contract_addr
+    }
+
+    fn pool_contract_address(&self) -> AppResult<Address> {
+        self.idrx_token.pool_contract_addr.parse().map_err(|_| {
+            AppError::BlockchainError("Invalid InvoicePool contract address".to_string())
+        })
+    }
+
+    fn nft_contract_address(&self) -> AppResult<Address> {
+        self.idrx_token.nft_contract_addr.parse().map_err(|_| {
+            AppError::BlockchainError("Invalid InvoiceNFT contract address".to_string())
         })
     }
 
+    /// Decimals of the active IDRX token, read from the contract at startup
+    pub fn idrx_decimals(&self) -> u8 {
+        self.idrx_decimals
+    }
+
+    /// Read `decimals()` from the token once. A wrong value silently scales every
+    /// amount, so the contract wins over `IDRX_DECIMALS`, which is only used when
+    /// the call fails and, without an RPC call, in test mode.
+    async fn detect_idrx_decimals(
+        config: &Config,
+        provider: &Provider<Http>,
+        token: &TokenDeployment,
+    ) -> u8 {
+        if config.skip_blockchain_verification {
+            return config.idrx_decimals;
+        }
+
+        let detected = match token.contract_addr.parse::<Address>() {
+            Ok(addr) => IERC20::new(addr, Arc::new(provider.clone()))
+                .decimals()
+                .call()
+                .await
+                .map_err(|e| e.to_string()),
+            Err(_) => Err("invalid contract address".to_string()),
+        };
+
+        match detected {
+            Ok(decimals) if decimals > MAX_IDRX_DECIMALS => {
+                tracing::error!(
+                    "IDRX token {} reports unsupported {} decimals. Using IDRX_DECIMALS={}",
+                    token.contract_addr,
+                    decimals,
+                    config.idrx_decimals
+                );
+                config.idrx_decimals
+            }
+            Ok(decimals) => {
+                if decimals != config.idrx_decimals {
+                    tracing::warn!(
+                        "IDRX token {} reports {} decimals, overriding IDRX_DECIMALS={}",
+                        token.contract_addr,
+                        decimals,
+                        config.idrx_decimals
+                    );
+                } else {
+                    tracing::info!(
+                        "IDRX token {} uses {} decimals",
+                        token.contract_addr,
+                        decimals
+                    );
+                }
+                decimals
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "Could not read decimals of IDRX token {}: {}. Using IDRX_DECIMALS={}",
+                    token.contract_addr,
+                    e,
+                    config.idrx_decimals
+                );
+                config.idrx_decimals
+            }
+        }
+    }
+
     // ==================== IDRX Token Methods ====================
 
+    /// Address of the active IDRX token contract
+    pub fn idrx_contract_address(&self) -> AppResult<Address> {
+        self.idrx_token
+            .contract_addr
+            .parse()
+            .map_err(|_| AppError::BlockchainError("Invalid IDRX contract address".to_string()))
+    }
+
     /// Get IDRX token contract instance
     fn get_idrx_contract(&self) -> AppResult<IERC20<Provider<Http>>> {
-        let contract_addr: Address =
-            self.config.idrx_token_contract_addr.parse().map_err(|_| {
-                AppError::BlockchainError("Invalid IDRX contract address".to_string())
-            })?;
+        let contract_addr = self.idrx_contract_address()?;
 
         Ok(IERC20::new(contract_addr, Arc::new(self.provider.clone())))
     }
@@ -163,9 +1122,42 @@ impl BlockchainService {
                 AppError::BlockchainError(format!("Failed to get IDRX balance: {}", e))
             })?;
 
-        // Convert from token units to Decimal (IDRX has 2 decimals)
-        let balance_f64 = balance.as_u128() as f64 / 10f64.powi(self.idrx_decimals as i32);
-        Ok(Decimal::from_f64_retain(balance_f64).unwrap_or(Decimal::ZERO))
+        self.idrx_units_to_decimal(balance)
+    }
+
+    /// Get IDRX balances for many addresses in one Multicall3 round trip instead of
+    /// one RPC call per address. Balances are returned in the same order as `addresses`.
+    pub async fn get_idrx_balances(&self, addresses: &[&str]) -> AppResult<Vec<Decimal>> {
+        if addresses.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let holders = addresses
+            .iter()
+            .map(|address| {
+                address.parse::<Address>().map_err(|_| {
+                    AppError::ValidationError(format!("Invalid address: {}", address))
+                })
+            })
+            .collect::<AppResult<Vec<Address>>>()?;
+
+        let token_addr = self.idrx_contract_address()?;
+        let multicall_addr: Address = self.config.multicall3_addr.parse().map_err(|_| {
+            AppError::BlockchainError("Invalid Multicall3 contract address".to_string())
+        })?;
+
+        let balances =
+            fetch_erc20_balances(&self.provider, multicall_addr, token_addr, &holders).await?;
+
+        balances
+            .into_iter()
+            .map(|balance| self.idrx_units_to_decimal(balance))
+            .collect()
+    }
+
+    /// Convert from token units to Decimal
+    fn idrx_units_to_decimal(&self, units: U256) -> AppResult<Decimal> {
+        units_to_decimal(units, self.idrx_decimals)
     }
 
     /// Get platform wallet IDRX balance (escrow balance)
@@ -174,116 +1166,157 @@ impl BlockchainService {
             .await
     }
 
-    /// Convert Decimal amount to token units (U256)
-    fn to_token_units(&self, amount: Decimal) -> U256 {
-        let multiplier = 10u128.pow(self.idrx_decimals as u32);
-        let amount_u128 = (amount.to_f64().unwrap_or(0.0) * multiplier as f64) as u128;
-        U256::from(amount_u128)
+    /// On-chain `interestRate` for an invoice in basis points, e.g. 10.5% -> 1050
+    pub fn interest_rate_bps(invoice: &crate::models::Invoice) -> u64 {
+        invoice
+            .effective_interest_rate()
+            .and_then(|r| (r * Decimal::from(100)).round().to_u64())
+            .unwrap_or(0)
     }
 
-    /// Verify signature using ERC-1271 (for Smart Contract Wallets)
-    pub async fn verify_signature_erc1271(
+    /// Convert Decimal amount to token units (U256), truncating below the token's precision.
+    /// Scaling happens in U256, so large amounts at 18 decimals do not overflow.
+    pub fn to_token_units(&self, amount: Decimal) -> AppResult<U256> {
+        if amount.is_sign_negative() && !amount.is_zero() {
+            return Err(AppError::ValidationError(format!(
+                "Token amount {} cannot be negative",
+                amount
+            )));
+        }
+
+        let mantissa = U256::from(amount.mantissa().unsigned_abs());
+        let scale = amount.scale() as usize;
+        let decimals = self.idrx_decimals as usize;
+        if decimals >= scale {
+            mantissa
+                .checked_mul(U256::exp10(decimals - scale))
+                .ok_or_else(|| {
+                    AppError::ValidationError(format!("Token amount {} is too large", amount))
+                })
+        } else {
+            Ok(mantissa / U256::exp10(scale - decimals))
+        }
+    }
+
+    /// Verify an EIP-191 personal_sign signature from an EOA, falling back to
+    /// ERC-1271 / EIP-6492 for smart contract wallets
+    pub async fn verify_wallet_signature(
         &self,
         wallet_address: &str,
-        message_hash: [u8; 32],
-        signature: Vec<u8>,
+        signature_str: &str,
+        message: &str,
     ) -> AppResult<bool> {
-        let addr: Address = wallet_address
+        let wallet_addr: Address = wallet_address
             .parse()
             .map_err(|_| AppError::ValidationError("Invalid wallet address".to_string()))?;
-        
-        // Check if address has code (Deployed Smart Wallet)
-        let code = self.provider.get_code(addr, None).await.map_err(|e| {
-            AppError::BlockchainError(format!("Failed to get code: {}", e))
-        })?;
-
-        if !code.is_empty() {
-             // 0x1626ba7e is the bytes4 magic value for isValidSignature
-            let magic_value = [0x16, 0x26, 0xba, 0x7e];
-            let contract = IERC1271::new(addr, Arc::new(self.provider.clone()));
-            
-            // Try standard ERC-1271 first for deployed contracts
-            let result = contract
-                .is_valid_signature(message_hash, signature.clone().into())
-                .call()
-                .await;
 
-             match result {
-                Ok(val) => {
-                    if val == magic_value {
-                        return Ok(true);
+        // 1. Prepare message hash (EIP-191)
+        let message_hash = self.hash_message(message);
+
+        // 2. Decode signature
+        // Handle 0x prefix if present
+        let sig_clean = signature_str.strip_prefix("0x").unwrap_or(signature_str);
+        let signature_bytes = ethers::utils::hex::decode(sig_clean)
+            .map_err(|_| AppError::ValidationError("Invalid signature hex".to_string()))?;
+
+        // 3. Attempt EOA Verification (Standard ECDSA)
+        // Only if signature length is 65 bytes
+        if signature_bytes.len() == 65 {
+            if let Ok(sig) = signature_str.parse::<ethers::types::Signature>() {
+                match sig.recover(H256::from(message_hash)) {
+                    Ok(recovered) => {
+                        if recovered == wallet_addr {
+                            tracing::info!(
+                                "EOA signature verified for {}",
+                                mask_wallet(wallet_address)
+                            );
+                            return Ok(true);
+                        } else {
+                            tracing::warn!(
+                                "EOA signature recovery mismatch. Wallet: {}, Recovered: {}",
+                                mask_wallet(wallet_address),
+                                mask_wallet(&format!("{:?}", recovered))
+                            );
+                        }
+                    }
+                    Err(e) => {
+                        tracing::warn!("EOA signature recovery failed: {}", e);
                     }
-                },
-                Err(e) => {
-                     tracing::warn!("Standard ERC-1271 failed for deployed contract: {}", e);
                 }
+            } else {
+                tracing::warn!("Failed to parse EOA signature");
             }
+        } else {
+            tracing::info!(
+                "Signature length {} != 65, skipping EOA check (likely Smart Wallet)",
+                signature_bytes.len()
+            );
         }
-        
-        // Fallback to Universal Signature Validator (EIP-6492)
-        // This handles undeployed contracts (counterfactual) and also retries 1271 securely
-        self.verify_signature_universal(addr, message_hash, signature).await
+
+        // 4. Fallback: ERC-1271 Verification (Smart Contract Wallet)
+        tracing::info!(
+            "Attempting ERC-1271 verification for {}",
+            mask_wallet(wallet_address)
+        );
+        self.verify_signature_erc1271(wallet_address, message_hash, signature_bytes)
+            .await
     }
-    
-    /// Use EIP-6492 Universal Signature Validator
-    /// Contract: 0x6492c034cc609e99298b3097c29bc906df0c0522 (Base Mainnet & Sepolia)
-    /// Use EIP-6492 Universal Signature Validator
-    /// Contract: 0x6492c034cc609e99298b3097c29bc906df0c0522 (Base Mainnet & Sepolia)
-    async fn verify_signature_universal(
+
+    /// Verify signature using ERC-1271 (for Smart Contract Wallets). Successful
+    /// verifications are cached in Redis for a short time so a retried login does
+    /// not hit the RPC again; failures are never cached.
+    pub async fn verify_signature_erc1271(
         &self,
-        signer: Address,
-        hash: [u8; 32],
+        wallet_address: &str,
+        message_hash: [u8; 32],
         signature: Vec<u8>,
     ) -> AppResult<bool> {
-        let validator_addr: Address = "0x6492c034cc609e99298b3097c29bc906df0c0522".parse().unwrap();
-        
-        // UniversalSigValidator Runtime Bytecode (fetched from Base Mainnet)
-        // This allows us to use state overrides if the contract is missing on the current chain
-        let validator_bytecode = "0x608060405234801561000f575f5ffd5b506004361061003f575f3560e01c806376be4cea146100435780638f0684301461007357806398ef1ed8146100a3575b5f5ffd5b61005d600480360381019061005891906108e1565b6100d3565b60405161006a9190610986565b60405180910390f35b61008d6004803603810190610088919061099f565b6105fe565b60405161009a9190610986565b60405180910390f35b6100bd60048036038101906100b8919061099f565b61068d565b6040516100ca9190610986565b60405180910390f35b5f5f8773ffffffffffffffffffffffffffffffffffffffff163b905060605f7f64926492649264926492649264926492649264926492649264926492649264925f1b888860208b8b90506101279190610a46565b908b8b90509261013993929190610a81565b906101449190610ad1565b1490508015610250575f606089895f9060208d8d90506101649190610a46565b9261017193929190610a81565b81019061017e9190610ca2565b8096508193508294505050505f8514806101955750865b15610249575f5f8373ffffffffffffffffffffffffffffffffffffffff16836040516101c19190610d7c565b5f604051808303815f865af19150503d805f81146101fa576040519150601f19603f3d011682016040523d82523d5f602084013e6101ff565b606091505b50915091508161024657806040517f9d0d6e2d00000000000000000000000000000000000000000000000000000000815260040161023d9190610dda565b60405180910390fd5b50505b5050610297565b87878080601f0160208091040260200160405190810160405280939291908181526020018383808284375f81840152601f19601f8201169050808301925050505050505091505b80806102a257505f83115b1561046c578973ffffffffffffffffffffffffffffffffffffffff16631626ba7e8a846040518363ffffffff1660e01b81526004016102e2929190610e09565b602060405180830381865afa92505050801561031c57506040513d601f19601f820116820180604052508101906103199190610e8c565b60015b6103b9573d805f811461034a576040519150601f19603f3d011682016040523d82523d5f602084013e61034f565b606091505b508515801561035d57505f84115b1561037c576103718b8b8b8b8b60016100d3565b9450505050506105f4565b806040517f6f2a95990000000000000000000000000000000000000000000000000000000081526004016103b09190610dda565b60405180910390fd5b5f631626ba7e60e01b7bffffffffffffffffffffffffffffffffffffffffffffffffffffffff1916827bffffffffffffffffffffffffffffffffffffffffffffffffffffffff191614905080158015610410575086155b801561041b57505f85115b1561043b5761042f8c8c8c8c8c60016100d3565b955050505050506105f4565b5f851480156104475750825b8015610451575087155b1561045f57805f526001601ffd5b80955050505050506105f4565b604188889050146104b2576040517f08c379a00000000000000000000000000000000000000000000000000000000081526004016104a990610f37565b60405180910390fd5b5f88885f906020926104c693929190610a81565b906104d19190610ad1565b90505f89896020906040926104e893929190610a81565b906104f39190610ad1565b90505f8a8a604081811061050a57610509610f55565b5b9050013560f81c60f81b60f81c9050601b8160ff16141580156105315750601c8160ff1614155b15610571576040517f08c379a000000000000000000000000000000000000000000000000000000000815260040161056890610ff2565b60405180910390fd5b8c73ffffffffffffffffffffffffffffffffffffffff1660018d8386866040515f81526020016040526040516105aa949392919061102b565b6020604051602081039080840390855afa1580156105ca573d5f5f3e3d5ffd5b5050506020604051035173ffffffffffffffffffffffffffffffffffffffff161496505050505050505b9695505050505050565b5f3073ffffffffffffffffffffffffffffffffffffffff166376be4cea8686868660015f6040518763ffffffff1660e01b8152600401610643969594939291906110a9565b6020604051808303815f875af115801561065f573d5f5f3e3d5ffd5b505050506040513d601f19601f820116820180604052508101906106839190611117565b9050949350505050565b5f3073ffffffffffffffffffffffffffffffffffffffff166376be4cea868686865f5f6040518763ffffffff1660e01b81526004016106d1969594939291906110a9565b6020604051808303815f875af192505050801561070c57506040513d601f19601f820116820180604052508101906107099190611117565b60015b6107a0573d805f811461073a576040519150601f19603f3d011682016040523d82523d5f602084013e61073f565b606091505b505f815190506001810361079c57600160f81b825f8151811061076557610764610f55565b5b602001015160f81c60f81b7effffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff191614925050506107a5565b8082fd5b809150505b949350505050565b5f604051905090565b5f5ffd5b5f5ffd5b5f73ffffffffffffffffffffffffffffffffffffffff82169050919050565b5f6107e7826107be565b9050919050565b6107f7816107dd565b8114610801575f5ffd5b50565b5f81359050610812816107ee565b92915050565b5f819050919050565b61082a81610818565b8114610834575f5ffd5b50565b5f8135905061084581610821565b92915050565b5f5ffd5b5f5ffd5b5f5ffd5b5f5f83601f84011261086c5761086b61084b565b5b8235905067ffffffffffffffff8111156108895761088861084f565b5b6020830191508360018202830111156108a5576108a4610853565b5b9250929050565b5f8115159050919050565b6108c0816108ac565b81146108ca575f5ffd5b50565b5f813590506108db816108b7565b92915050565b5f5f5f5f5f5f60a087890312156108fb576108fa6107b6565b5b5f61090889828a01610804565b965050602061091989828a01610837565b955050604087013567ffffffffffffffff81111561093a576109396107ba565b5b61094689828a01610857565b9450945050606061095989828a016108cd565b925050608061096a89828a016108cd565b9150509295509295509295565b610980816108ac565b82525050565b5f6020820190506109995f830184610977565b92915050565b5f5f5f5f606085870312156109b7576109b66107b6565b5b5f6109c487828801610804565b94505060206109d587828801610837565b935050604085013567ffffffffffffffff8111156109f6576109f56107ba565b5b610a0287828801610857565b925092505092959194509250565b5f819050919050565b7f4e487b71000000000000000000000000000000000000000000000000000000005f52601160045260245ffd5b5f610a5082610a10565b9150610a5b83610a10565b9250828203905081811115610a7357610a72610a19565b5b92915050565b5f5ffd5b5f5ffd5b5f5f85851115610a9457610a93610a79565b5b83861115610aa557610aa4610a7d565b5b6001850283019150848603905094509492505050565b5f82905092915050565b5f82821b905092915050565b5f610adc8383610abb565b82610ae78135610818565b92506020821015610b2757610b227fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff83602003600802610ac5565b831692505b505092915050565b5f610b39826107be565b9050919050565b610b4981610b2f565b8114610b53575f5ffd5b50565b5f81359050610b6481610b40565b92915050565b5f5ffd5b5f601f19601f8301169050919050565b7f4e487b71000000000000000000000000000000000000000000000000000000005f52604160045260245ffd5b610bb482610b6e565b810181811067ffffffffffffffff82111715610bd357610bd2610b7e565b5b80604052505050565b5f610be56107ad565b9050610bf18282610bab565b919050565b5f67ffffffffffffffff821115610c1057610c0f610b7e565b5b610c1982610b6e565b9050602081019050919050565b828183375f83830152505050565b5f610c46610c4184610bf6565b610bdc565b905082815260208101848484011115610c6257610c61610b6a565b5b610c6d848285610c26565b509392505050565b5f82601f830112610c8957610c8861084b565b5b8135610c99848260208601610c34565b91505092915050565b5f5f5f60608486031215610cb957610cb86107b6565b5b5f610cc686828701610b56565b935050602084013567ffffffffffffffff811115610ce757610ce66107ba565b5b610cf386828701610c75565b925050604084013567ffffffffffffffff811115610d1457610d136107ba565b5b610d2086828701610c75565b9150509250925092565b5f81519050919050565b5f81905092915050565b8281835e5f83830152505050565b5f610d5682610d2a565b610d608185610d34565b9350610d70818560208601610d3e565b80840191505092915050565b5f610d878284610d4c565b915081905092915050565b5f82825260208201905092915050565b5f610dac82610d2a565b610db68185610d92565b9350610dc6818560208601610d3e565b610dcf81610b6e565b840191505092915050565b5f6020820190508181035f830152610df28184610da2565b905092915050565b610e0381610818565b82525050565b5f604082019050610e1c5f830185610dfa565b8181036020830152610e2e8184610da2565b90509392505050565b5f7fffffffff0000000000000000000000000000000000000000000000000000000082169050919050565b610e6b81610e37565b8114610e75575f5ffd5b50565b5f81519050610e8681610e62565b92915050565b5f60208284031215610ea157610ea06107b6565b5b5f610eae84828501610e78565b91505092915050565b5f82825260208201905092915050565b7f5369676e617475726556616c696461746f72237265636f7665725369676e65725f8201527f3a20696e76616c6964207369676e6174757265206c656e677468000000000000602082015250565b5f610f21603a83610eb7565b9150610f2c82610ec7565b604082019050919050565b5f6020820190508181035f830152610f4e81610f15565b9050919050565b7f4e487b71000000000000000000000000000000000000000000000000000000005f52603260045260245ffd5b7f5369676e617475726556616c696461746f723a20696e76616c6964207369676e5f8201527f617475726520762076616c756500000000000000000000000000000000000000602082015250565b5f610fdc602d83610eb7565b9150610fe782610f82565b604082019050919050565b5f6020820190508181035f83015261100981610fd0565b9050919050565b5f60ff82169050919050565b61102581611010565b82525050565b5f60808201905061103e5f830187610dfa565b61104b602083018661101c565b6110586040830185610dfa565b6110656060830184610dfa565b95945050505050565b611077816107dd565b82525050565b5f6110888385610d92565b9350611095838584610c26565b61109e83610b6e565b840190509392505050565b5f60a0820190506110bc5f83018961106e565b6110c96020830188610dfa565b81810360408301526110dc81868861107d565b90506110eb6060830185610977565b6110f86080830184610977565b979650505050505050565b5f81519050611111816108b7565b92915050565b5f6020828403121561112c5761112b6107b6565b5b5f61113984828501611103565b9150509291505056fea2646970667358221220a097e3b3de576882cc80ec9fc7e5e58495b422f643739b61de8d128d51ee11ee64736f6c634300081c0033";
-        
-        let contract = UniversalSigValidator::new(validator_addr, Arc::new(self.provider.clone()));
-        
-        // Encode the transaction data
-        let calldata = contract
-            .is_valid_sig(signer, hash, signature.into())
-            .calldata()
-            .ok_or_else(|| AppError::BlockchainError("Failed to encode calldata".to_string()))?;
+        let addr: Address = wallet_address
+            .parse()
+            .map_err(|_| AppError::ValidationError("Invalid wallet address".to_string()))?;
+        let cache_key = erc1271_cache_key(addr, &message_hash, &signature);
 
-        // Construct the raw JSON-RPC request for eth_call with state overrides
-        // Params: [ { to, data }, "latest", { address: { code } } ]
-        
-        let tx_obj = serde_json::json!({
-            "to": validator_addr,
-            "data": calldata,
-        });
-        
-        // State override object: address -> { code: bytecode }
-        let state_overrides = serde_json::json!({
-            format!("{:?}", validator_addr): {
-                "code": validator_bytecode
+        if let Some(pool) = &self.redis_pool {
+            match Self::cached_signature(pool, &cache_key).await {
+                Ok(true) => return Ok(true),
+                Ok(false) => {}
+                Err(e) => tracing::warn!("Signature cache unavailable: {}", e),
             }
-        });
-        
-        let params = (tx_obj, "latest", state_overrides);
-        
-        let result: Result<ethers::types::Bytes, _> = self.provider
-            .request("eth_call", params)
-            .await;
-            
-        match result {
-            Ok(bytes) => {
-                // Decode bool result (first 32 bytes)
-                if bytes.len() >= 32 {
-                     let is_valid = bytes[31] != 0; // check last byte of 32-byte word
-                     return Ok(is_valid);
+        }
+
+        let is_valid = self
+            .signature_validator
+            .is_valid_signature(addr, message_hash, &signature)
+            .await?;
+
+        if is_valid {
+            if let Some(pool) = &self.redis_pool {
+                if let Err(e) = Self::cache_signature(pool, &cache_key).await {
+                    tracing::warn!("Failed to cache signature verification: {}", e);
                 }
-                Ok(false)
-            },
-            Err(e) => {
-                 tracing::error!("Universal Sig Validator (State Override) failed: {:?}", e);
-                 Ok(false)
             }
         }
+
+        Ok(is_valid)
+    }
+
+    async fn cached_signature(pool: &deadpool_redis::Pool, key: &str) -> anyhow::Result<bool> {
+        let mut conn = pool.get().await?;
+        let exists: bool = redis::cmd("EXISTS").arg(key).query_async(&mut conn).await?;
+        Ok(exists)
+    }
+
+    async fn cache_signature(pool: &deadpool_redis::Pool, key: &str) -> anyhow::Result<()> {
+        let mut conn = pool.get().await?;
+        redis::cmd("SET")
+            .arg(key)
+            .arg(1)
+            .arg("EX")
+            .arg(ERC1271_CACHE_TTL_SECS)
+            .query_async::<_, ()>(&mut conn)
+            .await?;
+
+        Ok(())
     }
 
     /// Prepare message hash for verification (matches EIP-191 Personal Sign)
@@ -292,18 +1325,23 @@ impl BlockchainService {
     }
 
     /// Verify an IDRX transfer transaction
-    /// Returns details if the transfer is valid and matches expected parameters
+    /// Returns details if the transfer is valid and matches expected parameters.
+    /// With `expected_from` set, only a transfer sent from that address matches.
+    /// The transferred amount may differ from `expected_amount` by at most `tolerance`;
+    /// pass `Decimal::ZERO` to require an exact match.
     pub async fn verify_idrx_transfer(
         &self,
         tx_hash: &str,
         expected_to: &str,
+        expected_from: Option<&str>,
         expected_amount: Decimal,
+        tolerance: Decimal,
     ) -> AppResult<VerifiedTransfer> {
         if self.config.skip_blockchain_verification {
             tracing::info!("SKIPPING blockchain verification (Test Mode)");
             return Ok(VerifiedTransfer {
                 tx_hash: tx_hash.to_string(),
-                from: "0xTestUser".to_string(),
+                from: expected_from.unwrap_or("0xTestUser").to_string(),
                 to: expected_to.to_string(),
                 amount: expected_amount,
                 block_number: 12345,
@@ -319,6 +1357,12 @@ impl BlockchainService {
         let expected_to_addr: Address = expected_to
             .parse()
             .map_err(|_| AppError::ValidationError("Invalid recipient address".to_string()))?;
+        let expected_from_addr: Option<Address> = expected_from
+            .map(|from| {
+                from.parse()
+                    .map_err(|_| AppError::ValidationError("Invalid sender address".to_string()))
+            })
+            .transpose()?;
 
         // Get the transaction receipt
         let receipt = self
@@ -341,10 +1385,7 @@ impl BlockchainService {
         }
 
         // Parse Transfer events from logs
-        let contract_addr: Address =
-            self.config.idrx_token_contract_addr.parse().map_err(|_| {
-                AppError::BlockchainError("Invalid IDRX contract address".to_string())
-            })?;
+        let contract_addr = self.idrx_contract_address()?;
 
         // Transfer event signature: Transfer(address,address,uint256)
         let transfer_topic = H256::from_slice(&ethers::utils::keccak256(
@@ -354,6 +1395,7 @@ impl BlockchainService {
         let mut verified_from = String::new();
         let mut verified_amount = Decimal::ZERO;
         let mut found_transfer = false;
+        let mut other_sender = None;
 
         for log in receipt.logs.iter() {
             // Check if log is from IDRX contract and is a Transfer event
@@ -367,14 +1409,15 @@ impl BlockchainService {
 
                     if to_addr == expected_to_addr {
                         let from_addr = Address::from_slice(&log.topics[1].as_bytes()[12..32]);
+                        if expected_from_addr.is_some_and(|expected| expected != from_addr) {
+                            other_sender = Some(from_addr);
+                            continue;
+                        }
                         verified_from = format!("{:?}", from_addr);
 
                         // Amount is in data field
-                        let amount_u256 = U256::from_big_endian(&log.data);
-                        let amount_f64 =
-                            amount_u256.as_u128() as f64 / 10f64.powi(self.idrx_decimals as i32);
                         verified_amount =
-                            Decimal::from_f64_retain(amount_f64).unwrap_or(Decimal::ZERO);
+                            self.idrx_units_to_decimal(U256::from_big_endian(&log.data))?;
                         found_transfer = true;
                         break;
                     }
@@ -383,14 +1426,20 @@ impl BlockchainService {
         }
 
         if !found_transfer {
+            if let Some(sender) = other_sender {
+                return Err(AppError::ValidationError(format!(
+                    "IDRX transfer was sent from {:?}, not from {}",
+                    sender,
+                    expected_from.unwrap_or_default()
+                )));
+            }
             return Err(AppError::BlockchainError(
                 "No matching IDRX transfer found to expected recipient".to_string(),
             ));
         }
 
-        // Verify amount (allow small rounding difference)
         let diff = (verified_amount - expected_amount).abs();
-        if diff > Decimal::from_f64_retain(0.01).unwrap() {
+        if diff > tolerance {
             return Err(AppError::BlockchainError(format!(
                 "Transfer amount mismatch: expected {}, got {}",
                 expected_amount, verified_amount
@@ -414,12 +1463,15 @@ impl BlockchainService {
     pub async fn verify_investment_transfer(
         &self,
         tx_hash: &str,
+        expected_from: Option<&str>,
         expected_amount: Decimal,
     ) -> AppResult<VerifiedTransfer> {
         self.verify_idrx_transfer(
             tx_hash,
             &self.config.platform_wallet_address,
+            expected_from,
             expected_amount,
+            TRANSFER_AMOUNT_TOLERANCE,
         )
         .await
     }
@@ -432,12 +1484,106 @@ impl BlockchainService {
     ) -> AppResult<VerifiedTransfer> {
         self.verify_idrx_transfer(
             tx_hash,
-            &self.config.invoice_pool_contract_addr,
+            &self.idrx_token.pool_contract_addr,
+            None,
             expected_amount,
+            TRANSFER_AMOUNT_TOLERANCE,
         )
         .await
     }
 
+    /// Pull `amount` IDRX from the permit owner into the platform wallet using an
+    /// EIP-2612 permit. The permit must name the platform wallet as spender and
+    /// allow at least `amount`.
+    pub async fn transfer_with_permit(
+        &self,
+        req: &InvestWithPermitRequest,
+        amount: Decimal,
+    ) -> AppResult<SentTransaction> {
+        let parse_address = |value: &str, field: &str| {
+            value
+                .parse::<Address>()
+                .map_err(|_| AppError::ValidationError(format!("Invalid permit {}", field)))
+        };
+        let parse_bytes32 = |value: &str, field: &str| {
+            value
+                .parse::<H256>()
+                .map(|h| h.to_fixed_bytes())
+                .map_err(|_| AppError::ValidationError(format!("Invalid permit {}", field)))
+        };
+
+        let permit = PermitAuthorization {
+            owner: parse_address(&req.owner, "owner")?,
+            spender: parse_address(&req.spender, "spender")?,
+            value: U256::from_dec_str(&req.value)
+                .map_err(|_| AppError::ValidationError("Invalid permit value".to_string()))?,
+            deadline: U256::from(req.deadline.max(0) as u64),
+            v: req.v,
+            r: parse_bytes32(&req.r, "r")?,
+            s: parse_bytes32(&req.s, "s")?,
+        };
+
+        let platform_wallet = parse_address(&self.config.platform_wallet_address, "spender")?;
+        if permit.spender != platform_wallet {
+            return Err(AppError::ValidationError(
+                "Permit spender must be the platform wallet".to_string(),
+            ));
+        }
+
+        let amount_units = self.to_token_units(amount)?;
+        if permit.value < amount_units {
+            return Err(AppError::ValidationError(format!(
+                "Permit value {} is below the investment amount of {} units",
+                permit.value, amount_units
+            )));
+        }
+
+        tracing::info!(
+            "Pulling {} IDRX from {:?} with permit",
+            amount,
+            permit.owner
+        );
+        self.permit_token
+            .permit_and_transfer(&permit, platform_wallet, amount_units)
+            .await
+    }
+
+    /// Gas units an IDRX transfer of `amount` to `to` from the platform wallet is expected to use
+    pub async fn estimate_gas_for_transfer(&self, to: &str, amount: Decimal) -> AppResult<U256> {
+        self.gas_oracle
+            .estimate_transfer_gas(to, self.to_token_units(amount)?)
+            .await
+    }
+
+    /// Expected gas cost of an IDRX transfer and the platform wallet's native balance to pay it
+    pub async fn check_transfer_gas(&self, to: &str, amount: Decimal) -> AppResult<GasCheck> {
+        Ok(GasCheck {
+            gas_limit: self.estimate_gas_for_transfer(to, amount).await?,
+            gas_price: self.gas_oracle.gas_price().await?,
+            platform_balance: self.gas_oracle.platform_native_balance().await?,
+        })
+    }
+
+    /// Refuse to send when the platform wallet cannot pay the transfer's gas,
+    /// instead of letting the node reject it after the DB work is done
+    pub async fn ensure_platform_gas(&self, to: &str, amount: Decimal) -> AppResult<GasCheck> {
+        let check = self.check_transfer_gas(to, amount).await?;
+        if !check.is_sufficient() {
+            tracing::error!(
+                "Platform wallet holds {} wei, transfer to {} needs about {} wei of gas",
+                check.platform_balance,
+                mask_wallet(to),
+                check.cost()
+            );
+            return Err(AppError::InsufficientGas(format!(
+                "Platform wallet holds {} wei of native token but about {} wei is needed for gas",
+                check.platform_balance,
+                check.cost()
+            )));
+        }
+        Ok(check)
+    }
+
     /// Transfer IDRX from platform wallet to a recipient
     /// Used for disbursements to exporters and returns to investors
     pub async fn transfer_idrx(
@@ -445,10 +1591,27 @@ impl BlockchainService {
         to_address: &str,
         amount: Decimal,
         tx_type: OnChainTxType,
-    ) -> AppResult<String> {
+    ) -> AppResult<SentTransaction> {
+        self.metrics.record_transfer_attempt();
+        let result = self.send_idrx_transfer(to_address, amount, tx_type).await;
+        self.metrics.record_transfer_outcome(result.is_ok());
+        result
+    }
+
+    async fn send_idrx_transfer(
+        &self,
+        to_address: &str,
+        amount: Decimal,
+        tx_type: OnChainTxType,
+    ) -> AppResult<SentTransaction> {
+        self.ensure_platform_gas(to_address, amount).await?;
+
         if self.config.skip_blockchain_verification {
             tracing::info!("SKIPPING blockchain transfer logic (Test Mode)");
-            return Ok(format!("0xTestTransferHash_{}", Uuid::new_v4()));
+            return Ok(SentTransaction::simulated(format!(
+                "0xTestTransferHash_{}",
+                Uuid::new_v4()
+            )));
         }
 
         let wallet = self.wallet.as_ref().ok_or_else(|| {
@@ -459,28 +1622,46 @@ impl BlockchainService {
             .parse()
             .map_err(|_| AppError::ValidationError("Invalid recipient address".to_string()))?;
 
-        let contract_addr: Address =
-            self.config.idrx_token_contract_addr.parse().map_err(|_| {
-                AppError::BlockchainError("Invalid IDRX contract address".to_string())
-            })?;
+        let contract_addr = self.idrx_contract_address()?;
 
         let client = SignerMiddleware::new(self.provider.clone(), wallet.clone());
         let contract = IERC20::new(contract_addr, Arc::new(client));
 
-        let amount_units = self.to_token_units(amount);
+        let amount_units = self.to_token_units(amount)?;
 
         tracing::info!(
             "Transferring {} IDRX to {} for {:?}",
             amount,
-            to_address,
+            mask_wallet(to_address),
             tx_type
         );
 
+        tracing::debug!(
+            signer = ?wallet.address(),
+            token = ?contract_addr,
+            to = ?to_addr,
+            amount_units = %amount_units,
+            ?tx_type,
+            "Sending IDRX transfer"
+        );
+
         let tx = contract.transfer(to_addr, amount_units);
-        let pending_tx = tx
-            .send()
-            .await
-            .map_err(|e| AppError::BlockchainError(format!("Transfer failed: {}", e)))?;
+        let pending_tx = tx.send().await.map_err(|e| {
+            let reason = revert_reason(&e);
+            // The full recipient is only in the debug-level pre-send log
+            tracing::error!(
+                signer = ?wallet.address(),
+                to = %mask_wallet(&format!("{:?}", to_addr)),
+                amount_units = %amount_units,
+                revert_reason = ?reason,
+                "IDRX transfer send failed: {}",
+                e
+            );
+            AppError::BlockchainError(match reason {
+                Some(reason) => format!("Transfer failed: {} (reverted: {})", e, reason),
+                None => format!("Transfer failed: {}", e),
+            })
+        })?;
 
         let tx_hash = format!("{:?}", pending_tx.tx_hash());
 
@@ -496,15 +1677,17 @@ impl BlockchainService {
             ));
         }
 
+        let sent = SentTransaction::from_receipt(&receipt);
         tracing::info!(
-            "IDRX transfer completed: {} - {} IDRX to {} (block: {:?})",
+            "IDRX transfer completed: {} - {} IDRX to {} (block: {}, gas: {})",
             tx_hash,
             amount,
-            to_address,
-            receipt.block_number
+            mask_wallet(to_address),
+            sent.block_number,
+            sent.gas_used
         );
 
-        Ok(tx_hash)
+        Ok(sent)
     }
 
     /// Disburse funds to exporter (transfer IDRX from platform to exporter)
@@ -521,6 +1704,7 @@ impl BlockchainService {
         );
         self.transfer_idrx(exporter_wallet, amount, OnChainTxType::Disbursement)
             .await
+            .map(|sent| sent.tx_hash)
     }
 
     /// Return funds to investor (transfer IDRX from platform to investor)
@@ -533,84 +1717,83 @@ impl BlockchainService {
         tracing::info!("Returning {} IDRX to investor for pool {}", amount, pool_id);
         self.transfer_idrx(investor_wallet, amount, OnChainTxType::InvestorReturn)
             .await
+            .map(|sent| sent.tx_hash)
     }
 
-    /// Get all IDRX transfers for an address (for transparency/audit)
+    /// Get IDRX transfers for an address (for transparency/audit), newest first.
+    ///
+    /// Scans at most `transfer_history_max_block_range` blocks starting at
+    /// `from_block` (default: the token deployment block). When more blocks or
+    /// transfers remain, `next_from_block` is where the next page starts.
     pub async fn get_transfer_history(
         &self,
         address: &str,
         from_block: Option<u64>,
-    ) -> AppResult<Vec<serde_json::Value>> {
+        to_block: Option<u64>,
+        limit: Option<usize>,
+    ) -> AppResult<TransferHistoryPage> {
         let addr: Address = address
             .parse()
             .map_err(|_| AppError::ValidationError("Invalid address".to_string()))?;
 
-        let contract_addr: Address =
-            self.config.idrx_token_contract_addr.parse().map_err(|_| {
-                AppError::BlockchainError("Invalid IDRX contract address".to_string())
-            })?;
-
-        let transfer_topic = H256::from_slice(&ethers::utils::keccak256(
-            "Transfer(address,address,uint256)",
-        ));
-
-        // Pad address to 32 bytes for topic filter
-        let addr_topic = H256::from_slice(&{
-            let mut padded = [0u8; 32];
-            padded[12..32].copy_from_slice(addr.as_bytes());
-            padded
-        });
-
-        let from = from_block.map(U64::from).unwrap_or(U64::from(0));
+        let contract_addr = self.idrx_contract_address()?;
 
-        // Get incoming transfers (to = address)
-        let incoming_filter = Filter::new()
-            .address(contract_addr)
-            .topic0(transfer_topic)
-            .topic2(addr_topic)
-            .from_block(from);
-
-        // Get outgoing transfers (from = address)
-        let outgoing_filter = Filter::new()
-            .address(contract_addr)
-            .topic0(transfer_topic)
-            .topic1(addr_topic)
-            .from_block(from);
-
-        let incoming_logs = self
-            .provider
-            .get_logs(&incoming_filter)
-            .await
-            .map_err(|e| AppError::BlockchainError(e.to_string()))?;
+        let from = from_block.unwrap_or(self.config.transfer_history_start_block);
+        let upper = match to_block {
+            Some(to_block) => to_block,
+            None => self.get_block_number().await?,
+        };
+        let max_range = self.config.transfer_history_max_block_range.max(1);
+        let to = upper.min(from.saturating_add(max_range - 1));
+        if from > to {
+            return Err(AppError::ValidationError(
+                "from_block must not be after to_block".to_string(),
+            ));
+        }
+        let limit = limit
+            .unwrap_or(DEFAULT_TRANSFER_HISTORY_LIMIT)
+            .clamp(1, MAX_TRANSFER_HISTORY_LIMIT);
 
-        let outgoing_logs = self
-            .provider
-            .get_logs(&outgoing_filter)
-            .await
-            .map_err(|e| AppError::BlockchainError(e.to_string()))?;
+        let logs = fetch_transfer_logs(&self.provider, contract_addr, addr, from, to).await?;
 
-        let mut transfers = Vec::new();
-
-        for log in incoming_logs.iter().chain(outgoing_logs.iter()) {
-            if log.topics.len() >= 3 {
-                let from_addr = Address::from_slice(&log.topics[1].as_bytes()[12..32]);
-                let to_addr = Address::from_slice(&log.topics[2].as_bytes()[12..32]);
-                let amount_u256 = U256::from_big_endian(&log.data);
-                let amount = amount_u256.as_u128() as f64 / 10f64.powi(self.idrx_decimals as i32);
-
-                transfers.push(serde_json::json!({
-                    "tx_hash": format!("{:?}", log.transaction_hash.unwrap_or_default()),
-                    "block_number": log.block_number.map(|n| n.as_u64()),
-                    "from": format!("{:?}", from_addr),
-                    "to": format!("{:?}", to_addr),
-                    "amount": amount,
-                    "direction": if to_addr == addr { "incoming" } else { "outgoing" },
-                    "explorer_url": self.get_explorer_url(&format!("{:?}", log.transaction_hash.unwrap_or_default())),
-                }));
+        let mut transfers = Vec::with_capacity(logs.len());
+        for log in logs {
+            if log.topics.len() < 3 {
+                continue;
             }
+            let from_addr = Address::from_slice(&log.topics[1].as_bytes()[12..32]);
+            let to_addr = Address::from_slice(&log.topics[2].as_bytes()[12..32]);
+            let amount = self
+                .idrx_units_to_decimal(U256::from_big_endian(&log.data))?
+                .to_f64()
+                .unwrap_or(0.0);
+            let tx_hash = format!("{:?}", log.transaction_hash.unwrap_or_default());
+            let direction = if to_addr == addr {
+                "incoming"
+            } else {
+                "outgoing"
+            };
+
+            transfers.push(TransferRecord {
+                explorer_url: self.get_explorer_url(&tx_hash),
+                tx_hash,
+                block_number: log.block_number.map(|n| n.as_u64()).unwrap_or(0),
+                log_index: log.log_index.map(|i| i.as_u64()).unwrap_or(0),
+                from: format!("{:?}", from_addr),
+                to: format!("{:?}", to_addr),
+                amount,
+                direction,
+            });
         }
 
-        Ok(transfers)
+        let (transfers, next_from_block) = paginate_transfers(transfers, limit, to, upper);
+
+        Ok(TransferHistoryPage {
+            transfers,
+            from_block: from,
+            to_block: to,
+            next_from_block,
+        })
     }
 
     pub async fn get_chain_id(&self) -> AppResult<u64> {
@@ -622,6 +1805,14 @@ impl BlockchainService {
         Ok(chain_id.as_u64())
     }
 
+    pub async fn get_platform_fee_bps(&self) -> AppResult<u32> {
+        fetch_platform_fee_bps(
+            Arc::new(self.provider.clone()),
+            self.pool_contract_address()?,
+        )
+        .await
+    }
+
     pub async fn get_block_number(&self) -> AppResult<u64> {
         let block_number = self
             .provider
@@ -680,8 +1871,21 @@ impl BlockchainService {
         format!("{}/tx/{}", self.config.block_explorer_url, tx_hash)
     }
 
+    /// Explorer page of one token of an NFT contract
+    pub fn get_nft_explorer_url(&self, contract_address: &str, token_id: i64) -> String {
+        format!(
+            "{}/nft/{}/{}",
+            self.config.block_explorer_url, contract_address, token_id
+        )
+    }
+
+    /// Current holder of the invoice NFT `token_id`, read from the contract
+    pub async fn get_nft_owner(&self, token_id: i64) -> AppResult<Address> {
+        self.nft_owners.owner_of(token_id).await
+    }
+
     pub fn get_contract_address(&self) -> &str {
-        &self.config.invoice_nft_contract_addr
+        &self.idrx_token.nft_contract_addr
     }
 
     pub fn get_platform_wallet(&self) -> &str {
@@ -695,10 +1899,7 @@ impl BlockchainService {
         invoice: &crate::models::Invoice,
         uri: &str,
     ) -> AppResult<(i64, String, String)> {
-        let contract_addr: Address =
-            self.config.invoice_nft_contract_addr.parse().map_err(|_| {
-                AppError::BlockchainError("Invalid InvoiceNFT contract address".to_string())
-            })?;
+        let contract_addr = self.nft_contract_address()?;
 
         if self.config.skip_blockchain_verification {
             tracing::info!("SKIPPING blockchain minting (Test Mode)");
@@ -728,14 +1929,11 @@ impl BlockchainService {
                 AppError::ValidationError("Invalid exporter wallet address".to_string())
             })?;
 
-        let amount_units = self.to_token_units(invoice.amount);
-        let advance_amount = invoice.advance_amount.unwrap_or(invoice.amount);
-        let advance_units = self.to_token_units(advance_amount);
+        let amount_units = self.to_token_units(invoice.amount)?;
+        // The on-chain pool raises the same funding target as the DB pool
+        let advance_units = self.to_token_units(invoice.funding_target())?;
 
-        let interest_bps: u64 = invoice
-            .interest_rate
-            .map(|r| (r.to_f64().unwrap_or(0.0) * 100.0) as u64) // e.g. 10.5% -> 1050 bps
-            .unwrap_or(0);
+        let interest_bps = Self::interest_rate_bps(invoice);
 
         let issue_date = U256::from(
             invoice
@@ -793,7 +1991,7 @@ impl BlockchainService {
             .map_err(|e| AppError::BlockchainError(format!("Failed to get token ID: {}", e)))?;
 
         let token_id = token_id_u256.as_u64() as i64;
-        let contract_address_str = self.config.invoice_nft_contract_addr.clone();
+        let contract_address_str = self.idrx_token.nft_contract_addr.clone();
 
         Ok((token_id, tx_hash, contract_address_str))
     }
@@ -805,6 +2003,11 @@ impl BlockchainService {
             .await?
             .ok_or_else(|| AppError::NotFound("Invoice not found".to_string()))?;
 
+        if self.config.skip_blockchain_verification {
+            tracing::info!("SKIPPING NFT metadata upload (Test Mode)");
+            return Ok(format!("ipfs://test-metadata-{}", invoice_id));
+        }
+
         let metadata = serde_json::json!({
             "name": format!("VESSEL Invoice #{}", invoice.invoice_number),
             "description": format!("Tokenized invoice from VESSEL platform"),
@@ -838,12 +2041,35 @@ impl BlockchainService {
             ]
         });
 
-        let metadata_uri = self
+        // Mint retries and re-approvals produce identical metadata; reuse its pin
+        let content_hash = json_content_hash(&metadata);
+        if let Some(cid) = self
+            .invoice_repo
+            .find_cached_metadata_cid(&content_hash)
+            .await?
+        {
+            tracing::info!(
+                "Reusing pinned NFT metadata {} for invoice {}",
+                cid,
+                invoice_id
+            );
+            return Ok(self.pinata_service.gateway_url(&cid));
+        }
+
+        let cid = self
             .pinata_service
-            .upload_json(metadata, &format!("vessel-invoice-{}", invoice_id))
+            .pin_json(&metadata, &format!("vessel-invoice-{}", invoice_id))
             .await?;
+        self.invoice_repo
+            .cache_metadata_cid(&content_hash, &cid)
+            .await?;
+
+        Ok(self.pinata_service.gateway_url(&cid))
+    }
 
-        Ok(metadata_uri)
+    /// Investments the InvoicePool contract recorded for `token_id`
+    pub async fn get_investment_events(&self, token_id: i64) -> AppResult<Vec<OnChainInvestment>> {
+        self.investment_events.investment_events(token_id).await
     }
 
     pub async fn record_investment_on_chain(
@@ -861,13 +2087,7 @@ impl BlockchainService {
             AppError::BlockchainError("Platform wallet not configured".to_string())
         })?;
 
-        let contract_addr: Address =
-            self.config
-                .invoice_pool_contract_addr
-                .parse()
-                .map_err(|_| {
-                    AppError::BlockchainError("Invalid InvoicePool contract address".to_string())
-                })?;
+        let contract_addr = self.pool_contract_address()?;
 
         let client = SignerMiddleware::new(self.provider.clone(), wallet.clone());
         let contract = InvoicePool::new(contract_addr, Arc::new(client));
@@ -876,7 +2096,7 @@ impl BlockchainService {
             .parse()
             .map_err(|_| AppError::ValidationError("Invalid investor address".to_string()))?;
 
-        let amount_units = self.to_token_units(amount);
+        let amount_units = self.to_token_units(amount)?;
 
         tracing::info!(
             "Recording investment on-chain: token {} from {} amount {}",
@@ -911,32 +2131,29 @@ impl BlockchainService {
         token_id: i64,
         total_amount: Decimal,
         investor_returns: Vec<Decimal>,
-    ) -> AppResult<String> {
+    ) -> AppResult<SentTransaction> {
         if self.config.skip_blockchain_verification {
             tracing::info!("SKIPPING blockchain repayment recording (Test Mode)");
-            return Ok("0xTestRecordRepayHash".to_string());
+            return Ok(SentTransaction::simulated(format!(
+                "0xTestRecordRepayHash_{}",
+                Uuid::new_v4()
+            )));
         }
 
         let wallet = self.wallet.as_ref().ok_or_else(|| {
             AppError::BlockchainError("Platform wallet not configured".to_string())
         })?;
 
-        let contract_addr: Address =
-            self.config
-                .invoice_pool_contract_addr
-                .parse()
-                .map_err(|_| {
-                    AppError::BlockchainError("Invalid InvoicePool contract address".to_string())
-                })?;
+        let contract_addr = self.pool_contract_address()?;
 
         let client = SignerMiddleware::new(self.provider.clone(), wallet.clone());
         let contract = InvoicePool::new(contract_addr, Arc::new(client));
 
-        let total_amount_units = self.to_token_units(total_amount);
-        let returns_units: Vec<U256> = investor_returns
+        let total_amount_units = self.to_token_units(total_amount)?;
+        let returns_units = investor_returns
             .iter()
             .map(|&amount| self.to_token_units(amount))
-            .collect();
+            .collect::<AppResult<Vec<U256>>>()?;
 
         tracing::info!(
             "Recording repayment on-chain: token {} amount {}",
@@ -944,10 +2161,39 @@ impl BlockchainService {
             total_amount
         );
 
-        let tx = contract.record_repayment(U256::from(token_id), total_amount_units, returns_units);
+        tracing::debug!(
+            signer = ?wallet.address(),
+            pool_contract = ?contract_addr,
+            token_id,
+            total_amount_units = %total_amount_units,
+            returns_units = ?returns_units,
+            "Sending recordRepayment"
+        );
+
+        let tx = contract.record_repayment(
+            U256::from(token_id),
+            total_amount_units,
+            returns_units.clone(),
+        );
 
         let pending_tx = tx.send().await.map_err(|e| {
-            AppError::BlockchainError(format!("Failed to send record repayment tx: {}", e))
+            let reason = revert_reason(&e);
+            tracing::error!(
+                signer = ?wallet.address(),
+                token_id,
+                total_amount_units = %total_amount_units,
+                returns_units = ?returns_units,
+                revert_reason = ?reason,
+                "recordRepayment send failed: {}",
+                e
+            );
+            AppError::BlockchainError(match reason {
+                Some(reason) => format!(
+                    "Failed to send record repayment tx: {} (reverted: {})",
+                    e, reason
+                ),
+                None => format!("Failed to send record repayment tx: {}", e),
+            })
         })?;
 
         let receipt = pending_tx
@@ -962,7 +2208,7 @@ impl BlockchainService {
                 AppError::BlockchainError("Record repayment transaction failed".to_string())
             })?;
 
-        Ok(format!("{:?}", receipt.transaction_hash))
+        Ok(SentTransaction::from_receipt(&receipt))
     }
 
     pub async fn verify_shipment_on_chain(&self, token_id: i64) -> AppResult<String> {
@@ -975,10 +2221,7 @@ impl BlockchainService {
             AppError::BlockchainError("Platform wallet not configured".to_string())
         })?;
 
-        let contract_addr: Address =
-            self.config.invoice_nft_contract_addr.parse().map_err(|_| {
-                AppError::BlockchainError("Invalid InvoiceNFT contract address".to_string())
-            })?;
+        let contract_addr = self.nft_contract_address()?;
 
         let client = SignerMiddleware::new(self.provider.clone(), wallet.clone());
         let contract = InvoiceNFT::new(contract_addr, Arc::new(client));
@@ -1003,30 +2246,41 @@ impl BlockchainService {
         Ok(format!("{:?}", receipt.transaction_hash))
     }
 
-    pub async fn record_disbursement_on_chain(&self, token_id: i64) -> AppResult<String> {
+    /// Record the pool's disbursement on the InvoicePool contract, which pays the
+    /// advance to the exporter's wallet, or to `recipient` when one is given
+    pub async fn record_disbursement_on_chain(
+        &self,
+        token_id: i64,
+        recipient: Option<&str>,
+    ) -> AppResult<SentTransaction> {
         if self.config.skip_blockchain_verification {
             tracing::info!("SKIPPING blockchain disbursement recording (Test Mode)");
-            return Ok("0xTestRecordDisburseHash".to_string());
+            return Ok(SentTransaction::simulated(format!(
+                "0xTestRecordDisburseHash_{}",
+                Uuid::new_v4()
+            )));
         }
 
         let wallet = self.wallet.as_ref().ok_or_else(|| {
             AppError::BlockchainError("Platform wallet not configured".to_string())
         })?;
 
-        let contract_addr: Address =
-            self.config
-                .invoice_pool_contract_addr
-                .parse()
-                .map_err(|_| {
-                    AppError::BlockchainError("Invalid InvoicePool contract address".to_string())
-                })?;
+        let contract_addr = self.pool_contract_address()?;
 
         let client = SignerMiddleware::new(self.provider.clone(), wallet.clone());
         let contract = InvoicePool::new(contract_addr, Arc::new(client));
 
         tracing::info!("Recording disbursement on-chain for token {}", token_id);
 
-        let tx = contract.record_disbursement(U256::from(token_id));
+        let tx = match recipient {
+            None => contract.record_disbursement(U256::from(token_id)),
+            Some(recipient) => {
+                let recipient: Address = recipient.parse().map_err(|_| {
+                    AppError::BlockchainError("Invalid disbursement recipient address".to_string())
+                })?;
+                contract.record_disbursement_to(U256::from(token_id), recipient)
+            }
+        };
 
         let pending_tx = tx.send().await.map_err(|e| {
             AppError::BlockchainError(format!("Failed to send record disbursement tx: {}", e))
@@ -1044,7 +2298,7 @@ impl BlockchainService {
                 AppError::BlockchainError("Record disbursement transaction failed".to_string())
             })?;
 
-        Ok(format!("{:?}", receipt.transaction_hash))
+        Ok(SentTransaction::from_receipt(&receipt))
     }
 
     pub async fn create_pool_on_chain(&self, token_id: i64) -> AppResult<String> {
@@ -1057,13 +2311,7 @@ impl BlockchainService {
             AppError::BlockchainError("Platform wallet not configured".to_string())
         })?;
 
-        let contract_addr: Address =
-            self.config
-                .invoice_pool_contract_addr
-                .parse()
-                .map_err(|_| {
-                    AppError::BlockchainError("Invalid InvoicePool contract address".to_string())
-                })?;
+        let contract_addr = self.pool_contract_address()?;
 
         let client = SignerMiddleware::new(self.provider.clone(), wallet.clone());
         let contract = InvoicePool::new(contract_addr, Arc::new(client));
@@ -1088,6 +2336,43 @@ impl BlockchainService {
         Ok(format!("{:?}", receipt.transaction_hash))
     }
 
+    /// Reopen the token's pool after it was closed without raising anything, so
+    /// a relisted invoice can take investments again
+    pub async fn reopen_pool_on_chain(&self, token_id: i64) -> AppResult<String> {
+        if self.config.skip_blockchain_verification {
+            tracing::info!("SKIPPING blockchain pool reopen (Test Mode)");
+            return Ok("0xTestReopenPoolHash".to_string());
+        }
+
+        let wallet = self.wallet.as_ref().ok_or_else(|| {
+            AppError::BlockchainError("Platform wallet not configured".to_string())
+        })?;
+
+        let contract_addr = self.pool_contract_address()?;
+
+        let client = SignerMiddleware::new(self.provider.clone(), wallet.clone());
+        let contract = InvoicePool::new(contract_addr, Arc::new(client));
+
+        tracing::info!("Reopening pool on-chain for token {}", token_id);
+
+        let tx = contract.reopen_pool(U256::from(token_id));
+
+        let pending_tx = tx.send().await.map_err(|e| {
+            AppError::BlockchainError(format!("Failed to send reopenPool tx: {}", e))
+        })?;
+
+        let receipt = pending_tx
+            .await
+            .map_err(|e| {
+                AppError::BlockchainError(format!("Failed to wait for reopenPool receipt: {}", e))
+            })?
+            .ok_or_else(|| {
+                AppError::BlockchainError("reopenPool transaction failed".to_string())
+            })?;
+
+        Ok(format!("{:?}", receipt.transaction_hash))
+    }
+
     pub async fn close_pool_on_chain(&self, token_id: i64) -> AppResult<String> {
         if self.config.skip_blockchain_verification {
             tracing::info!("SKIPPING blockchain pool close (Test Mode)");
@@ -1098,13 +2383,7 @@ impl BlockchainService {
             AppError::BlockchainError("Platform wallet not configured".to_string())
         })?;
 
-        let contract_addr: Address =
-            self.config
-                .invoice_pool_contract_addr
-                .parse()
-                .map_err(|_| {
-                    AppError::BlockchainError("Invalid InvoicePool contract address".to_string())
-                })?;
+        let contract_addr = self.pool_contract_address()?;
 
         let client = SignerMiddleware::new(self.provider.clone(), wallet.clone());
         let contract = InvoicePool::new(contract_addr, Arc::new(client));
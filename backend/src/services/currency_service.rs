@@ -4,22 +4,75 @@ use std::sync::Arc;
 use crate::config::Config;
 use crate::error::{AppError, AppResult};
 use crate::models::{
-    get_supported_currencies, ConvertCurrencyResponse, DisbursementEstimateResponse,
+    ConvertCurrencyResponse, CurrencyPreviewResponse, DisbursementEstimateResponse,
     SupportedCurrency,
 };
-use crate::utils::generate_random_token;
+use crate::repository::CurrencyRepository;
+use crate::utils::{sign_rate_quote, RateQuote};
 
 pub struct CurrencyService {
+    currency_repo: Arc<CurrencyRepository>,
     config: Arc<Config>,
 }
 
 impl CurrencyService {
-    pub fn new(config: Arc<Config>) -> Self {
-        Self { config }
+    pub fn new(currency_repo: Arc<CurrencyRepository>, config: Arc<Config>) -> Self {
+        Self {
+            currency_repo,
+            config,
+        }
+    }
+
+    pub async fn get_supported_currencies(&self) -> AppResult<Vec<SupportedCurrency>> {
+        self.currency_repo.find_all().await
     }
 
-    pub fn get_supported_currencies(&self) -> Vec<SupportedCurrency> {
-        get_supported_currencies()
+    /// Looks up a currency and rejects it unless it can be converted to IDRX
+    async fn convertible_currency(&self, code: &str) -> AppResult<SupportedCurrency> {
+        let currency = self
+            .currency_repo
+            .find_by_code(code)
+            .await?
+            .ok_or_else(|| AppError::ValidationError(format!("Unsupported currency: {}", code)))?;
+
+        if !currency.conversion_enabled {
+            return Err(AppError::ValidationError(format!(
+                "Conversion from {} is currently disabled",
+                code
+            )));
+        }
+
+        Ok(currency)
+    }
+
+    /// Conversion preview for display; unlike get_locked_exchange_rate it issues no rate lock
+    pub async fn preview_conversion(
+        &self,
+        from_currency: &str,
+        amount: f64,
+    ) -> AppResult<CurrencyPreviewResponse> {
+        if amount <= 0.0 {
+            return Err(AppError::ValidationError(
+                "Amount must be positive".to_string(),
+            ));
+        }
+
+        self.convertible_currency(from_currency).await?;
+
+        let exchange_rate = self.get_exchange_rate(from_currency).await?;
+        let buffer_rate = self.config.default_buffer_rate;
+        let effective_rate = exchange_rate * (1.0 - buffer_rate);
+
+        Ok(CurrencyPreviewResponse {
+            from_currency: from_currency.to_string(),
+            to_currency: "IDRX".to_string(),
+            original_amount: amount,
+            exchange_rate,
+            buffer_rate,
+            buffer_amount: amount * exchange_rate * buffer_rate,
+            effective_rate,
+            converted_amount: amount * effective_rate,
+        })
     }
 
     pub async fn get_locked_exchange_rate(
@@ -28,13 +81,7 @@ impl CurrencyService {
         amount: f64,
     ) -> AppResult<ConvertCurrencyResponse> {
         // Validate currency
-        let supported = self.get_supported_currencies();
-        if !supported.iter().any(|c| c.code == from_currency) {
-            return Err(AppError::ValidationError(format!(
-                "Unsupported currency: {}",
-                from_currency
-            )));
-        }
+        self.convertible_currency(from_currency).await?;
 
         // Get exchange rate (in production, this would call an external API)
         let exchange_rate = self.get_exchange_rate(from_currency).await?;
@@ -46,11 +93,18 @@ impl CurrencyService {
         // Calculate converted amount
         let converted_amount = amount * effective_rate;
 
-        // Generate lock token
-        let lock_token = generate_random_token();
-
-        // Lock expires in 30 minutes
-        let locked_until = (Utc::now() + Duration::minutes(30)).to_rfc3339();
+        // Sign the quote so invoice creation can trust the rate without re-quoting
+        let expires = Utc::now() + Duration::minutes(self.config.quote_ttl_minutes);
+        let lock_token = sign_rate_quote(
+            &self.config.quote_signing_secret,
+            &RateQuote {
+                from_currency: from_currency.to_string(),
+                to_currency: "IDR".to_string(),
+                rate: effective_rate,
+                expires_at: expires.timestamp(),
+            },
+        );
+        let locked_until = expires.to_rfc3339();
 
         Ok(ConvertCurrencyResponse {
             from_currency: from_currency.to_string(),
@@ -66,7 +120,7 @@ impl CurrencyService {
     }
 
     pub fn calculate_disbursement_estimate(&self, idr_amount: f64) -> DisbursementEstimateResponse {
-        let platform_fee_pct = self.config.platform_fee_percentage;
+        let platform_fee_pct = self.config.platform_fee_percentage();
         let platform_fee_amount = idr_amount * (platform_fee_pct / 100.0);
         let net_disbursement = idr_amount - platform_fee_amount;
 
@@ -1,65 +1,392 @@
+use futures_util::future::BoxFuture;
+use handlebars::Handlebars;
 use lettre::{
-    message::{header::ContentType, Mailbox},
+    message::{Mailbox, MultiPart},
     transport::smtp::authentication::Credentials,
-    Message, AsyncSmtpTransport, Tokio1Executor, AsyncTransport,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
 };
+use serde::Serialize;
+use serde_json::json;
 use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
 
 use crate::config::Config;
 use crate::error::{AppError, AppResult};
+use crate::repository::OutboundEmailRepository;
+use crate::utils::mask_email;
 
-pub struct EmailService {
+/// Email templates under `templates/email`, each with an HTML and a plain-text
+/// variant. Both variants are wrapped in the shared `layout` partial.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmailTemplate {
+    Otp,
+    InvestmentConfirmation,
+    PoolFunded,
+    Disbursed,
+    RepaymentInstructions,
+    InvestmentRepaid,
+    MitraApproved,
+    MitraRejected,
+    MitraRevoked,
+    KycApproved,
+    KycRejected,
+    ImporterAccess,
+    ImporterPaymentRequest,
+    ImporterPaymentOverdue,
+    RepaymentDue,
+}
+
+impl EmailTemplate {
+    pub const ALL: [EmailTemplate; 15] = [
+        EmailTemplate::Otp,
+        EmailTemplate::InvestmentConfirmation,
+        EmailTemplate::PoolFunded,
+        EmailTemplate::Disbursed,
+        EmailTemplate::RepaymentInstructions,
+        EmailTemplate::InvestmentRepaid,
+        EmailTemplate::MitraApproved,
+        EmailTemplate::MitraRejected,
+        EmailTemplate::MitraRevoked,
+        EmailTemplate::KycApproved,
+        EmailTemplate::KycRejected,
+        EmailTemplate::ImporterAccess,
+        EmailTemplate::ImporterPaymentRequest,
+        EmailTemplate::ImporterPaymentOverdue,
+        EmailTemplate::RepaymentDue,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            EmailTemplate::Otp => "otp",
+            EmailTemplate::InvestmentConfirmation => "investment_confirmation",
+            EmailTemplate::PoolFunded => "pool_funded",
+            EmailTemplate::Disbursed => "disbursed",
+            EmailTemplate::RepaymentInstructions => "repayment_instructions",
+            EmailTemplate::InvestmentRepaid => "investment_repaid",
+            EmailTemplate::MitraApproved => "mitra_approved",
+            EmailTemplate::MitraRejected => "mitra_rejected",
+            EmailTemplate::MitraRevoked => "mitra_revoked",
+            EmailTemplate::KycApproved => "kyc_approved",
+            EmailTemplate::KycRejected => "kyc_rejected",
+            EmailTemplate::ImporterAccess => "importer_access",
+            EmailTemplate::ImporterPaymentRequest => "importer_payment_request",
+            EmailTemplate::ImporterPaymentOverdue => "importer_payment_overdue",
+            EmailTemplate::RepaymentDue => "repayment_due",
+        }
+    }
+
+    /// Codes and sign-in links expire within minutes and the caller reports
+    /// whether they went out, so these are sent immediately instead of queued
+    pub fn sends_inline(self) -> bool {
+        matches!(self, EmailTemplate::Otp | EmailTemplate::ImporterAccess)
+    }
+
+    /// (HTML, text) sources, embedded so the binary doesn't depend on the working directory
+    fn sources(self) -> (&'static str, &'static str) {
+        macro_rules! template {
+            ($name:literal) => {
+                (
+                    include_str!(concat!("../../templates/email/", $name, ".html.hbs")),
+                    include_str!(concat!("../../templates/email/", $name, ".txt.hbs")),
+                )
+            };
+        }
+
+        match self {
+            EmailTemplate::Otp => template!("otp"),
+            EmailTemplate::InvestmentConfirmation => template!("investment_confirmation"),
+            EmailTemplate::PoolFunded => template!("pool_funded"),
+            EmailTemplate::Disbursed => template!("disbursed"),
+            EmailTemplate::RepaymentInstructions => template!("repayment_instructions"),
+            EmailTemplate::InvestmentRepaid => template!("investment_repaid"),
+            EmailTemplate::MitraApproved => template!("mitra_approved"),
+            EmailTemplate::MitraRejected => template!("mitra_rejected"),
+            EmailTemplate::MitraRevoked => template!("mitra_revoked"),
+            EmailTemplate::KycApproved => template!("kyc_approved"),
+            EmailTemplate::KycRejected => template!("kyc_rejected"),
+            EmailTemplate::ImporterAccess => template!("importer_access"),
+            EmailTemplate::ImporterPaymentRequest => template!("importer_payment_request"),
+            EmailTemplate::ImporterPaymentOverdue => template!("importer_payment_overdue"),
+            EmailTemplate::RepaymentDue => template!("repayment_due"),
+        }
+    }
+}
+
+/// Both bodies of a rendered email, sent as multipart/alternative
+#[derive(Debug, Clone)]
+pub struct RenderedEmail {
+    pub html: String,
+    pub text: String,
+}
+
+/// Delivers rendered emails; replaceable so tests can capture what was sent
+pub trait EmailSender: Send + Sync {
+    fn send<'a>(
+        &'a self,
+        to: &'a str,
+        subject: &'a str,
+        email: &'a RenderedEmail,
+    ) -> BoxFuture<'a, AppResult<()>>;
+
+    /// Whether `send` actually delivers. Queued emails are left pending while
+    /// it does not, so they go out once delivery is configured.
+    fn is_configured(&self) -> bool {
+        true
+    }
+}
+
+/// Sends through the configured SMTP relay. Without SMTP credentials (local
+/// and test environments) the email is logged and dropped.
+pub struct SmtpEmailSender {
     config: Arc<Config>,
 }
 
+impl EmailSender for SmtpEmailSender {
+    fn send<'a>(
+        &'a self,
+        to: &'a str,
+        subject: &'a str,
+        email: &'a RenderedEmail,
+    ) -> BoxFuture<'a, AppResult<()>> {
+        Box::pin(async move {
+            if !self.is_configured() {
+                tracing::warn!("SMTP not configured, skipping email to {}", mask_email(to));
+                return Ok(());
+            }
+
+            let from_mailbox: Mailbox = self.config.smtp_from.parse().unwrap_or_else(|_| {
+                format!("VESSEL <{}>", self.config.smtp_username)
+                    .parse()
+                    .unwrap()
+            });
+
+            let to_mailbox: Mailbox = to
+                .parse()
+                .map_err(|_| AppError::ValidationError("Invalid email address".to_string()))?;
+
+            let message = Message::builder()
+                .from(from_mailbox)
+                .to(to_mailbox)
+                .subject(subject)
+                .multipart(MultiPart::alternative_plain_html(
+                    email.text.clone(),
+                    email.html.clone(),
+                ))
+                .map_err(|e| AppError::EmailError(e.to_string()))?;
+
+            let creds = Credentials::new(
+                self.config.smtp_username.clone(),
+                self.config.smtp_password.clone(),
+            );
+
+            let mailer =
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&self.config.smtp_host)
+                    .map_err(|e| AppError::EmailError(e.to_string()))?
+                    .credentials(creds)
+                    .port(self.config.smtp_port)
+                    .timeout(Some(std::time::Duration::from_secs(10)))
+                    .build();
+
+            mailer
+                .send(message)
+                .await
+                .map_err(|e| AppError::EmailError(e.to_string()))?;
+
+            tracing::info!("Email sent to {}", mask_email(to));
+            Ok(())
+        })
+    }
+
+    fn is_configured(&self) -> bool {
+        !self.config.smtp_username.is_empty() && !self.config.smtp_password.is_empty()
+    }
+}
+
+/// Queued emails claimed per pass of the delivery worker
+const EMAIL_DELIVERY_BATCH: i64 = 50;
+
+pub struct EmailService {
+    html: Handlebars<'static>,
+    text: Handlebars<'static>,
+    sender: Arc<dyn EmailSender>,
+    outbox: Option<Arc<OutboundEmailRepository>>,
+    max_attempts: i32,
+    retry_base_secs: i64,
+}
+
 impl EmailService {
+    /// Compiles every template up front, so a broken template fails at startup
+    /// rather than when the first email of that kind is sent.
     pub fn new(config: Arc<Config>) -> Self {
-        Self { config }
+        let mut html = Handlebars::new();
+        // Plain-text bodies must not be HTML-escaped
+        let mut text = Handlebars::new();
+        text.register_escape_fn(handlebars::no_escape);
+
+        html.register_partial(
+            "layout",
+            include_str!("../../templates/email/layout.html.hbs"),
+        )
+        .expect("Invalid email layout template");
+        text.register_partial(
+            "layout",
+            include_str!("../../templates/email/layout.txt.hbs"),
+        )
+        .expect("Invalid email layout template");
+
+        for template in EmailTemplate::ALL {
+            let (html_source, text_source) = template.sources();
+            html.register_template_string(template.name(), html_source)
+                .unwrap_or_else(|e| panic!("Invalid email template {}: {}", template.name(), e));
+            text.register_template_string(template.name(), text_source)
+                .unwrap_or_else(|e| panic!("Invalid email template {}: {}", template.name(), e));
+        }
+
+        Self {
+            html,
+            text,
+            max_attempts: config.email_max_attempts,
+            retry_base_secs: config.email_retry_base_secs,
+            sender: Arc::new(SmtpEmailSender { config }),
+            outbox: None,
+        }
+    }
+
+    /// Replace the delivery, e.g. with a recording sender in tests
+    pub fn with_sender(mut self, sender: Arc<dyn EmailSender>) -> Self {
+        self.sender = sender;
+        self
     }
 
-    pub async fn send_email(&self, to: &str, subject: &str, body: &str) -> AppResult<()> {
-        if self.config.smtp_username.is_empty() || self.config.smtp_password.is_empty() {
-            tracing::warn!("SMTP not configured, skipping email to {}", to);
-            return Ok(());
+    /// Queue notification emails in `outbound_emails` instead of sending them
+    /// inline; `deliver_queued_emails` sends them
+    pub fn with_outbox(mut self, outbox: Arc<OutboundEmailRepository>) -> Self {
+        self.outbox = Some(outbox);
+        self
+    }
+
+    /// Render both variants of `template` with `context`
+    pub fn render<T: Serialize>(
+        &self,
+        template: EmailTemplate,
+        context: &T,
+    ) -> AppResult<RenderedEmail> {
+        let render = |registry: &Handlebars<'static>| {
+            registry.render(template.name(), context).map_err(|e| {
+                AppError::EmailError(format!("Failed to render {}: {}", template.name(), e))
+            })
+        };
+
+        Ok(RenderedEmail {
+            html: render(&self.html)?,
+            text: render(&self.text)?,
+        })
+    }
+
+    /// Render `template` and send it to `to`. With an outbox, templates that
+    /// are not sent inline are queued for the delivery worker instead.
+    pub async fn send_template<T: Serialize>(
+        &self,
+        to: &str,
+        subject: &str,
+        template: EmailTemplate,
+        context: &T,
+    ) -> AppResult<()> {
+        let email = self.render(template, context)?;
+        match &self.outbox {
+            Some(outbox) if !template.sends_inline() => {
+                outbox
+                    .create(to, subject, template.name(), &email.html, &email.text)
+                    .await?;
+                Ok(())
+            }
+            _ => self.send_email(to, subject, &email).await,
         }
+    }
 
-        let from_mailbox: Mailbox = self.config.smtp_from.parse().unwrap_or_else(|_| {
-            format!("VESSEL <{}>", self.config.smtp_username)
-                .parse()
-                .unwrap()
-        });
-
-        let to_mailbox: Mailbox = to
-            .parse()
-            .map_err(|_| AppError::ValidationError("Invalid email address".to_string()))?;
-
-        let email = Message::builder()
-            .from(from_mailbox)
-            .to(to_mailbox)
-            .subject(subject)
-            .header(ContentType::TEXT_HTML)
-            .body(body.to_string())
-            .map_err(|e| AppError::EmailError(e.to_string()))?;
-
-        let creds = Credentials::new(
-            self.config.smtp_username.clone(),
-            self.config.smtp_password.clone(),
-        );
-
-        let mailer = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&self.config.smtp_host)
-            .map_err(|e| AppError::EmailError(e.to_string()))?
-            .credentials(creds)
-            .port(self.config.smtp_port)
-            .timeout(Some(std::time::Duration::from_secs(10)))
-            .build();
-
-        mailer
-            .send(email)
-            .await
-            .map_err(|e| AppError::EmailError(e.to_string()))?;
-
-        tracing::info!("Email sent to {}", to);
-        Ok(())
+    /// Send queued emails that are due. A failed send is retried after a delay
+    /// that doubles with each attempt; once `max_attempts` have failed the email
+    /// is dead-lettered. Returns how many were sent. Nothing is claimed while the
+    /// sender is not configured, so queued emails are not marked sent unsent.
+    pub async fn deliver_queued_emails(&self) -> AppResult<usize> {
+        let Some(outbox) = &self.outbox else {
+            return Ok(0);
+        };
+        if !self.sender.is_configured() {
+            tracing::debug!("Email delivery not configured, leaving queued emails pending");
+            return Ok(0);
+        }
+
+        let mut sent = 0;
+        for queued in outbox.claim_due(EMAIL_DELIVERY_BATCH).await? {
+            let email = RenderedEmail {
+                html: queued.html_body,
+                text: queued.text_body,
+            };
+            let result = self
+                .sender
+                .send(&queued.recipient, &queued.subject, &email)
+                .await;
+            match result {
+                Ok(()) => {
+                    outbox.mark_sent(queued.id).await?;
+                    sent += 1;
+                }
+                Err(e) if queued.attempts >= self.max_attempts => {
+                    tracing::error!(
+                        "Email {} to {} dead-lettered after {} attempts: {}",
+                        queued.template,
+                        mask_email(&queued.recipient),
+                        queued.attempts,
+                        e
+                    );
+                    outbox.mark_dead(queued.id, &e.to_string()).await?;
+                }
+                Err(e) => {
+                    let retry_in = self.retry_base_secs << (queued.attempts - 1).clamp(0, 10);
+                    tracing::warn!(
+                        "Email {} to {} failed (attempt {}), retrying in {}s: {}",
+                        queued.template,
+                        mask_email(&queued.recipient),
+                        queued.attempts,
+                        retry_in,
+                        e
+                    );
+                    outbox
+                        .mark_failed(queued.id, &e.to_string(), retry_in)
+                        .await?;
+                }
+            }
+        }
+
+        Ok(sent)
+    }
+
+    /// Send a rendered email
+    pub async fn send_email(
+        &self,
+        to: &str,
+        subject: &str,
+        email: &RenderedEmail,
+    ) -> AppResult<()> {
+        self.sender.send(to, subject, email).await
+    }
+
+    pub async fn send_otp(
+        &self,
+        to: &str,
+        subject: &str,
+        code: &str,
+        expiry_minutes: i64,
+    ) -> AppResult<()> {
+        self.send_template(
+            to,
+            subject,
+            EmailTemplate::Otp,
+            &json!({ "code": code, "expiry_minutes": expiry_minutes }),
+        )
+        .await
     }
 
     pub async fn send_pool_funded_notification(
@@ -68,56 +395,66 @@ impl EmailService {
         invoice_number: &str,
         amount: f64,
     ) -> AppResult<()> {
-        let subject = "VESSEL - Your Invoice is Fully Funded!";
-        let body = format!(
-            r#"
-            <html>
-            <body style="font-family: Arial, sans-serif; padding: 20px;">
-                <h2>Great News!</h2>
-                <p>Your invoice <strong>{}</strong> has been fully funded.</p>
-                <p>Funded Amount: <strong>Rp {:.2}</strong></p>
-                <p>You can now request disbursement from your VESSEL dashboard.</p>
-                <hr>
-                <p style="color: #666; font-size: 12px;">VESSEL - Invoice Factoring Platform on Base Network</p>
-            </body>
-            </html>
-            "#,
-            invoice_number, amount
-        );
-
-        self.send_email(to, subject, &body).await
+        self.send_template(
+            to,
+            "VESSEL - Your Invoice is Fully Funded!",
+            EmailTemplate::PoolFunded,
+            &json!({
+                "invoice_number": invoice_number,
+                "amount": format!("{:.2}", amount),
+            }),
+        )
+        .await
     }
 
+    /// The transaction is only shown for on-chain investments, which have a tx
+    /// hash to verify on the explorer.
     pub async fn send_investment_confirmation(
         &self,
         to: &str,
         invoice_number: &str,
+        pool_id: Uuid,
         amount: f64,
         tranche: &str,
         expected_return: f64,
+        tx_hash: Option<&str>,
+        explorer_url: Option<&str>,
+    ) -> AppResult<()> {
+        self.send_template(
+            to,
+            "VESSEL - Investment Confirmed",
+            EmailTemplate::InvestmentConfirmation,
+            &json!({
+                "invoice_number": invoice_number,
+                "pool_id": pool_id,
+                "amount": format!("{:.2}", amount),
+                "tranche": tranche,
+                "expected_return": format!("{:.2}", expected_return),
+                "tx_hash": tx_hash,
+                "explorer_url": explorer_url,
+            }),
+        )
+        .await
+    }
+
+    pub async fn send_investment_repaid_notification(
+        &self,
+        to: &str,
+        invoice_number: &str,
+        amount: f64,
+        return_amount: f64,
     ) -> AppResult<()> {
-        let subject = "VESSEL - Investment Confirmed";
-        let body = format!(
-            r#"
-            <html>
-            <body style="font-family: Arial, sans-serif; padding: 20px;">
-                <h2>Investment Confirmed</h2>
-                <p>Your investment in invoice <strong>{}</strong> has been confirmed.</p>
-                <table>
-                    <tr><td>Amount:</td><td><strong>Rp {:.2}</strong></td></tr>
-                    <tr><td>Tranche:</td><td><strong>{}</strong></td></tr>
-                    <tr><td>Expected Return:</td><td><strong>Rp {:.2}</strong></td></tr>
-                </table>
-                <p>Track your investment in your VESSEL portfolio.</p>
-                <hr>
-                <p style="color: #666; font-size: 12px;">VESSEL - Invoice Factoring Platform on Base Network</p>
-            </body>
-            </html>
-            "#,
-            invoice_number, amount, tranche, expected_return
-        );
-
-        self.send_email(to, subject, &body).await
+        self.send_template(
+            to,
+            "VESSEL - Investment Repaid",
+            EmailTemplate::InvestmentRepaid,
+            &json!({
+                "invoice_number": invoice_number,
+                "amount": format!("{:.2}", amount),
+                "return_amount": format!("{:.2}", return_amount),
+            }),
+        )
+        .await
     }
 
     pub async fn send_mitra_approval_notification(
@@ -125,23 +462,68 @@ impl EmailService {
         to: &str,
         company_name: &str,
     ) -> AppResult<()> {
-        let subject = "VESSEL - Mitra Application Approved!";
-        let body = format!(
-            r#"
-            <html>
-            <body style="font-family: Arial, sans-serif; padding: 20px;">
-                <h2>Congratulations!</h2>
-                <p>Your mitra application for <strong>{}</strong> has been approved.</p>
-                <p>You can now create invoices and request funding on VESSEL.</p>
-                <hr>
-                <p style="color: #666; font-size: 12px;">VESSEL - Invoice Factoring Platform on Base Network</p>
-            </body>
-            </html>
-            "#,
-            company_name
-        );
-
-        self.send_email(to, subject, &body).await
+        self.send_template(
+            to,
+            "VESSEL - Mitra Application Approved!",
+            EmailTemplate::MitraApproved,
+            &json!({ "company_name": company_name }),
+        )
+        .await
+    }
+
+    pub async fn send_mitra_rejection_notification(
+        &self,
+        to: &str,
+        company_name: &str,
+        reason: &str,
+    ) -> AppResult<()> {
+        self.send_template(
+            to,
+            "VESSEL - Mitra Application Rejected",
+            EmailTemplate::MitraRejected,
+            &json!({ "company_name": company_name, "reason": reason }),
+        )
+        .await
+    }
+
+    pub async fn send_mitra_revocation_notification(
+        &self,
+        to: &str,
+        company_name: &str,
+        reason: &str,
+    ) -> AppResult<()> {
+        self.send_template(
+            to,
+            "VESSEL - Mitra Status Revoked",
+            EmailTemplate::MitraRevoked,
+            &json!({ "company_name": company_name, "reason": reason }),
+        )
+        .await
+    }
+
+    pub async fn send_kyc_approval_notification(&self, to: &str, full_name: &str) -> AppResult<()> {
+        self.send_template(
+            to,
+            "VESSEL - Identity Verification Approved",
+            EmailTemplate::KycApproved,
+            &json!({ "full_name": full_name }),
+        )
+        .await
+    }
+
+    pub async fn send_kyc_rejection_notification(
+        &self,
+        to: &str,
+        full_name: &str,
+        reason: &str,
+    ) -> AppResult<()> {
+        self.send_template(
+            to,
+            "VESSEL - Identity Verification Rejected",
+            EmailTemplate::KycRejected,
+            &json!({ "full_name": full_name, "reason": reason }),
+        )
+        .await
     }
 
     pub async fn send_disbursement_notification(
@@ -150,23 +532,109 @@ impl EmailService {
         invoice_number: &str,
         amount: f64,
     ) -> AppResult<()> {
-        let subject = "VESSEL - Funds Disbursed!";
-        let body = format!(
-            r#"
-            <html>
-            <body style="font-family: Arial, sans-serif; padding: 20px;">
-                <h2>Funds Disbursed</h2>
-                <p>Funds for invoice <strong>{}</strong> have been disbursed to your wallet.</p>
-                <p>Disbursed Amount: <strong>Rp {:.2}</strong></p>
-                <p>Check your wallet for the IDRX transfer.</p>
-                <hr>
-                <p style="color: #666; font-size: 12px;">VESSEL - Invoice Factoring Platform on Base Network</p>
-            </body>
-            </html>
-            "#,
-            invoice_number, amount
-        );
-
-        self.send_email(to, subject, &body).await
+        self.send_template(
+            to,
+            "VESSEL - Funds Disbursed!",
+            EmailTemplate::Disbursed,
+            &json!({
+                "invoice_number": invoice_number,
+                "amount": format!("{:.2}", amount),
+            }),
+        )
+        .await
+    }
+
+    pub async fn send_importer_access_link(
+        &self,
+        to: &str,
+        link: &str,
+        expiry_minutes: i64,
+    ) -> AppResult<()> {
+        self.send_template(
+            to,
+            "VESSEL - Your Invoice Payments",
+            EmailTemplate::ImporterAccess,
+            &json!({ "link": link, "expiry_minutes": expiry_minutes }),
+        )
+        .await
+    }
+
+    /// Tell the buyer what is owed on an invoice and where to pay it
+    pub async fn send_importer_payment_request(
+        &self,
+        to: &str,
+        buyer_name: &str,
+        amount_due: f64,
+        due_date: &str,
+        payment_link: &str,
+    ) -> AppResult<()> {
+        self.send_template(
+            to,
+            "VESSEL - Invoice Payment Request",
+            EmailTemplate::ImporterPaymentRequest,
+            &json!({
+                "buyer_name": buyer_name,
+                "amount_due": format!("{:.2}", amount_due),
+                "due_date": due_date,
+                "payment_link": payment_link,
+            }),
+        )
+        .await
+    }
+
+    /// Reminder for a payment that is past its due date
+    pub async fn send_importer_payment_overdue(
+        &self,
+        to: &str,
+        buyer_name: &str,
+        amount_due: f64,
+        due_date: &str,
+        payment_link: &str,
+    ) -> AppResult<()> {
+        self.send_template(
+            to,
+            "VESSEL - Invoice Payment Overdue",
+            EmailTemplate::ImporterPaymentOverdue,
+            &json!({
+                "buyer_name": buyer_name,
+                "amount_due": format!("{:.2}", amount_due),
+                "due_date": due_date,
+                "payment_link": payment_link,
+            }),
+        )
+        .await
     }
+
+    pub async fn send_repayment_due(
+        &self,
+        to: &str,
+        invoice_number: &str,
+        due_date: &str,
+        grace_days: i64,
+    ) -> AppResult<()> {
+        self.send_template(
+            to,
+            "VESSEL - Invoice Repayment Due",
+            EmailTemplate::RepaymentDue,
+            &json!({
+                "invoice_number": invoice_number,
+                "due_date": due_date,
+                "grace_days": grace_days,
+            }),
+        )
+        .await
+    }
+}
+
+/// Deliver queued emails on a fixed interval
+pub fn spawn_email_delivery_task(email_service: Arc<EmailService>, every: Duration) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(every);
+        loop {
+            interval.tick().await;
+            if let Err(e) = email_service.deliver_queued_emails().await {
+                tracing::error!("Queued email delivery failed: {}", e);
+            }
+        }
+    });
 }
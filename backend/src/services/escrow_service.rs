@@ -2,9 +2,11 @@ use rust_decimal::Decimal;
 use std::sync::Arc;
 use uuid::Uuid;
 
-use crate::error::AppResult;
+use crate::error::{AppError, AppResult};
+use crate::models::User;
+use crate::utils::mask_wallet;
 
-use super::BlockchainService;
+use super::{BlockchainService, TransferHistoryPage};
 
 /// On-chain escrow record
 #[derive(Debug, Clone, serde::Serialize)]
@@ -18,6 +20,13 @@ pub struct EscrowRecord {
     pub explorer_url: String,
 }
 
+/// IDRX sent from escrow to an investor
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct InvestorPayout {
+    pub wallet: String,
+    pub tx_hash: String,
+}
+
 #[derive(Debug, Clone, serde::Serialize)]
 pub enum EscrowStatus {
     Pending,
@@ -65,13 +74,13 @@ impl EscrowService {
 
         // Verify the on-chain transfer
         let verified = blockchain
-            .verify_investment_transfer(tx_hash, amount)
+            .verify_investment_transfer(tx_hash, None, amount)
             .await?;
 
         tracing::info!(
             "Verified investment deposit: {} IDRX from {} to platform wallet for pool {}",
             verified.amount,
-            verified.from,
+            mask_wallet(&verified.from),
             pool_id
         );
 
@@ -122,7 +131,7 @@ impl EscrowService {
             amount,
             pool_id,
             exporter_id,
-            exporter_wallet
+            mask_wallet(exporter_wallet)
         );
 
         // Execute on-chain transfer
@@ -140,72 +149,82 @@ impl EscrowService {
         Ok(tx_hash)
     }
 
-    /// Release investor returns from escrow
+    /// Release investor returns from escrow to the investor's payout wallet
     /// This triggers an actual IDRX transfer on-chain
     pub async fn release_to_investor(
         &self,
         pool_id: Uuid,
-        investor_id: Uuid,
-        investor_wallet: &str,
+        investor: &User,
         amount: Decimal,
-    ) -> AppResult<String> {
+    ) -> AppResult<InvestorPayout> {
         let blockchain = self.get_blockchain_service()?;
+        let wallet = Self::payout_wallet(investor)?;
 
         tracing::info!(
             "Returning {} IDRX from pool {} to investor {} (wallet: {})",
             amount,
             pool_id,
-            investor_id,
-            investor_wallet
+            investor.id,
+            mask_wallet(&wallet)
         );
 
         // Execute on-chain transfer
         let tx_hash = blockchain
-            .return_to_investor(investor_wallet, amount, pool_id)
+            .return_to_investor(&wallet, amount, pool_id)
             .await?;
 
         tracing::info!(
             "Investor return completed: {} - {} IDRX to investor {}",
             tx_hash,
             amount,
-            investor_id
+            investor.id
         );
 
-        Ok(tx_hash)
+        Ok(InvestorPayout { wallet, tx_hash })
     }
 
-    /// Refund escrowed funds to investor (in case of pool cancellation)
+    /// Refund escrowed funds to the investor's payout wallet (in case of pool cancellation)
     /// This triggers an actual IDRX transfer on-chain
     pub async fn refund_to_investor(
         &self,
         pool_id: Uuid,
-        investor_id: Uuid,
-        investor_wallet: &str,
+        investor: &User,
         amount: Decimal,
-    ) -> AppResult<String> {
+    ) -> AppResult<InvestorPayout> {
         let blockchain = self.get_blockchain_service()?;
+        let wallet = Self::payout_wallet(investor)?;
 
         tracing::info!(
             "Refunding {} IDRX from pool {} to investor {} (wallet: {})",
             amount,
             pool_id,
-            investor_id,
-            investor_wallet
+            investor.id,
+            mask_wallet(&wallet)
         );
 
         // Execute on-chain transfer (same as return, different context)
         let tx_hash = blockchain
-            .return_to_investor(investor_wallet, amount, pool_id)
+            .return_to_investor(&wallet, amount, pool_id)
             .await?;
 
         tracing::info!(
             "Refund completed: {} - {} IDRX to investor {}",
             tx_hash,
             amount,
-            investor_id
+            investor.id
         );
 
-        Ok(tx_hash)
+        Ok(InvestorPayout { wallet, tx_hash })
+    }
+
+    /// Payouts only go to the login wallet or a payout wallet verified by signature
+    fn payout_wallet(investor: &User) -> AppResult<String> {
+        investor.payout_wallet().map(str::to_string).ok_or_else(|| {
+            AppError::Forbidden(format!(
+                "Investor {} has no verified wallet to pay out to",
+                investor.id
+            ))
+        })
     }
 
     /// Get escrow balance for platform wallet (total IDRX held)
@@ -220,14 +239,24 @@ impl EscrowService {
         blockchain.get_idrx_balance(address).await
     }
 
+    /// Get IDRX balances for many addresses in a single batched RPC call
+    pub async fn get_address_balances(&self, addresses: &[&str]) -> AppResult<Vec<Decimal>> {
+        let blockchain = self.get_blockchain_service()?;
+        blockchain.get_idrx_balances(addresses).await
+    }
+
     /// Get transaction history for transparency/audit
     pub async fn get_transaction_history(
         &self,
         address: &str,
         from_block: Option<u64>,
-    ) -> AppResult<Vec<serde_json::Value>> {
+        to_block: Option<u64>,
+        limit: Option<usize>,
+    ) -> AppResult<TransferHistoryPage> {
         let blockchain = self.get_blockchain_service()?;
-        blockchain.get_transfer_history(address, from_block).await
+        blockchain
+            .get_transfer_history(address, from_block, to_block, limit)
+            .await
     }
 }
 
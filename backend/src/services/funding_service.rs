@@ -1,21 +1,41 @@
-use chrono::{Duration, Utc};
+use chrono::{Datelike, Duration, NaiveDate, Utc};
 use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
-use rust_decimal::Decimal;
+use rust_decimal::{Decimal, RoundingStrategy};
+use sqlx::PgConnection;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
 use std::sync::Arc;
 use uuid::Uuid;
 
-use crate::config::Config;
+use crate::config::{Config, InvestmentMode};
 use crate::error::{AppError, AppResult};
 use crate::models::{
-    FundingPool, FundingPoolResponse, InvestRequest, Investment, InvestorPortfolio,
-    InvoiceDashboard, MitraDashboard, TimelineStatus,
+    AdminPoolDetail, AdminPoolListItem, BankAccount, DisbursementMode, DisbursementPreview,
+    FundableInvoiceFilter, FundingPool, FundingPoolResponse, InvestRequest,
+    InvestWithPermitRequest, Investment, InvestmentDetail, InvestmentDiscrepancy,
+    InvestmentPreview, InvestmentReceipt, InvestorPortfolio, InvestorRepayment, Invoice,
+    InvoiceDashboard, InvoiceFundingStatus, InvoiceNftView, MitraDashboard, PlatformStats,
+    PoolFilter, PoolInvestmentLimitsRequest, PoolOnChainAudit, PoolTimelineEvent,
+    PoolVerificationStatus, RepaymentBreakdown, TaxSummary, TimelineStatus, TrancheBreakdown,
+    User, REPAYMENT_OUTSTANDING_STATUSES,
 };
 use crate::repository::{
     FundingRepository, InvoiceRepository, RiskQuestionnaireRepository, TransactionRepository,
     UserRepository,
 };
+use crate::utils::{mask_wallet, Cursor, Metrics};
 
-use super::{BlockchainService, EmailService, EscrowService};
+use super::{
+    validate_fundable_filter, ActivityLogger, ActivityType, BlockchainService, EmailService,
+    EmailTemplate, EscrowService, ManualOffRamp, NotificationKind, NotificationService, OffRamp,
+    SentTransaction, VerifiedTransfer, WebhookEvent,
+};
+
+const PLATFORM_STATS_CACHE_KEY: &str = "marketplace:platform_stats";
+const PLATFORM_STATS_CACHE_TTL_SECS: u64 = 60;
+/// Age after which a pending on-chain investment is treated as stuck rather
+/// than still completing
+const PENDING_INVESTMENT_RECONCILE_AFTER_MINUTES: i64 = 10;
 
 pub struct FundingService {
     funding_repo: Arc<FundingRepository>,
@@ -26,7 +46,12 @@ pub struct FundingService {
     email_service: Arc<EmailService>,
     escrow_service: Arc<EscrowService>,
     blockchain_service: Arc<BlockchainService>,
+    notification_service: Arc<NotificationService>,
+    activity_logger: Arc<ActivityLogger>,
     config: Arc<Config>,
+    redis_pool: Option<deadpool_redis::Pool>,
+    offramp: Arc<dyn OffRamp>,
+    metrics: Arc<Metrics>,
 }
 
 impl FundingService {
@@ -39,6 +64,8 @@ impl FundingService {
         email_service: Arc<EmailService>,
         escrow_service: Arc<EscrowService>,
         blockchain_service: Arc<BlockchainService>,
+        notification_service: Arc<NotificationService>,
+        activity_logger: Arc<ActivityLogger>,
         config: Arc<Config>,
     ) -> Self {
         Self {
@@ -50,10 +77,33 @@ impl FundingService {
             email_service,
             escrow_service,
             blockchain_service,
+            notification_service,
+            activity_logger,
             config,
+            redis_pool: None,
+            offramp: Arc::new(ManualOffRamp),
+            metrics: Arc::new(Metrics::new()),
         }
     }
 
+    /// Cache platform stats in Redis; without a pool they are computed on every request
+    pub fn with_redis(mut self, redis_pool: Option<deadpool_redis::Pool>) -> Self {
+        self.redis_pool = redis_pool;
+        self
+    }
+
+    /// Replace the off-ramp used for bank disbursements, e.g. with a mock in tests
+    pub fn with_offramp(mut self, offramp: Arc<dyn OffRamp>) -> Self {
+        self.offramp = offramp;
+        self
+    }
+
+    /// Share the application's metrics registry for investment counters
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
     pub async fn create_pool(&self, invoice_id: Uuid) -> AppResult<FundingPool> {
         let invoice = self
             .invoice_repo
@@ -68,23 +118,33 @@ impl FundingService {
             ));
         }
 
-        // Check if pool already exists
+        // Closed pools may be relisted; a concurrent create still hits the unique index
         if self
             .funding_repo
-            .find_by_invoice(invoice_id)
+            .find_active_by_invoice(invoice_id)
             .await?
             .is_some()
         {
             return Err(AppError::Conflict(
-                "Pool already exists for this invoice".to_string(),
+                "An active pool already exists for this invoice".to_string(),
             ));
         }
 
-        // Calculate tranche amounts
-        // The pool target should be the advance amount (e.g. 80% of invoice total), not the full invoice value
-        let target_amount = invoice.advance_amount.unwrap_or(invoice.amount);
-        let priority_ratio = invoice.priority_ratio.to_f64().unwrap_or(80.0) / 100.0;
-        let priority_target = target_amount * Decimal::from_f64(priority_ratio).unwrap();
+        // The pool only raises up to the invoice's funding limit
+        let target_amount = invoice.funding_target();
+
+        // AML: cap how much a mitra can raise within the rolling window
+        self.check_velocity_cap(
+            invoice.exporter_id,
+            "advance_payment",
+            target_amount,
+            self.config.mitra_velocity_cap,
+        )
+        .await?;
+
+        // Split the target between tranches; the catalyst tranche takes the rounding remainder
+        let priority_target =
+            (target_amount * invoice.priority_ratio / Decimal::from(100)).round_dp(2);
         let catalyst_target = target_amount - priority_target;
 
         // Get interest rates
@@ -94,6 +154,19 @@ impl FundingService {
         // Calculate deadline
         let deadline = Utc::now() + Duration::days(invoice.funding_duration_days as i64);
 
+        // A relisted invoice keeps its NFT, whose on-chain pool was closed along
+        // with the previous pool; reopen it so investments can be recorded again
+        let relisting = self
+            .funding_repo
+            .find_by_invoice(invoice_id)
+            .await?
+            .is_some();
+        let reopen_tx_hash = if relisting {
+            Some(self.reopen_pool_on_chain(invoice_id).await?)
+        } else {
+            None
+        };
+
         // Create pool
         let pool = self
             .funding_repo
@@ -105,8 +178,18 @@ impl FundingService {
                 priority_rate,
                 catalyst_rate,
                 deadline,
+                self.config.investment_limit_ratios(false),
+                self.config.investment_limit_ratios(true),
             )
             .await?;
+        let pool = match reopen_tx_hash {
+            Some(tx_hash) => {
+                self.funding_repo
+                    .set_create_pool_tx_hash(pool.id, &tx_hash)
+                    .await?
+            }
+            None => pool,
+        };
 
         // Update invoice status to funding
         self.invoice_repo
@@ -116,6 +199,164 @@ impl FundingService {
         Ok(pool)
     }
 
+    async fn reopen_pool_on_chain(&self, invoice_id: Uuid) -> AppResult<String> {
+        let token_id = self
+            .invoice_repo
+            .find_nft_by_invoice(invoice_id)
+            .await?
+            .and_then(|nft| nft.token_id)
+            .ok_or_else(|| AppError::NotFound("NFT record not found for invoice".to_string()))?;
+
+        self.blockchain_service.reopen_pool_on_chain(token_id).await
+    }
+
+    /// Approve an invoice under review with `approve`, then tokenize it. The whole
+    /// sequence holds the invoice's approval lock, so concurrent or repeated
+    /// approvals mint one NFT and open one pool. `approve` is only awaited while the
+    /// invoice still needs approving; afterwards the call just finishes tokenization.
+    pub async fn approve_and_tokenize<F>(
+        &self,
+        invoice_id: Uuid,
+        approve: F,
+    ) -> AppResult<FundingPool>
+    where
+        F: Future<Output = AppResult<Invoice>>,
+    {
+        let lock = self.invoice_repo.lock_approval(invoice_id).await?;
+        let result = async {
+            let invoice = self
+                .invoice_repo
+                .find_by_id(invoice_id)
+                .await?
+                .ok_or_else(|| AppError::NotFound("Invoice not found".to_string()))?;
+
+            if !matches!(
+                invoice.status.as_str(),
+                "approved" | "tokenized" | "funding"
+            ) {
+                approve.await?;
+            }
+            self.tokenize_locked(invoice_id).await
+        }
+        .await;
+        self.invoice_repo.release_approval_lock(lock).await?;
+
+        result.map(|(pool, _)| pool)
+    }
+
+    /// Mint the invoice NFT and open its funding pool on-chain and in the DB.
+    /// If minting fails the invoice stays `approved` so it can be retried
+    /// with `retry_tokenize`.
+    pub async fn tokenize_invoice(&self, invoice_id: Uuid) -> AppResult<FundingPool> {
+        let lock = self.invoice_repo.lock_approval(invoice_id).await?;
+        let result = self.tokenize_locked(invoice_id).await;
+        self.invoice_repo.release_approval_lock(lock).await?;
+
+        result.map(|(pool, _)| pool)
+    }
+
+    /// Retry tokenization of an approved invoice without re-grading it.
+    /// Returns `None` without doing anything if the invoice already has an active pool.
+    pub async fn retry_tokenize(&self, invoice_id: Uuid) -> AppResult<Option<FundingPool>> {
+        let lock = self.invoice_repo.lock_approval(invoice_id).await?;
+        let result = self.tokenize_locked(invoice_id).await;
+        self.invoice_repo.release_approval_lock(lock).await?;
+
+        let (pool, created) = result?;
+        if !created {
+            tracing::info!("Invoice {} already tokenized, skipping retry", invoice_id);
+            return Ok(None);
+        }
+        Ok(Some(pool))
+    }
+
+    /// Tokenization steps, each skipped when an earlier attempt already did it: an
+    /// active pool ends the sequence and a recorded NFT is reused instead of minting
+    /// again. Returns the pool and whether it was created by this call.
+    /// Callers must hold the invoice's approval lock.
+    async fn tokenize_locked(&self, invoice_id: Uuid) -> AppResult<(FundingPool, bool)> {
+        if let Some(pool) = self.funding_repo.find_active_by_invoice(invoice_id).await? {
+            return Ok((pool, false));
+        }
+
+        let invoice = self
+            .invoice_repo
+            .find_by_id(invoice_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Invoice not found".to_string()))?;
+
+        let token_id = match self.invoice_repo.find_nft_by_invoice(invoice.id).await? {
+            Some(nft) => nft
+                .token_id
+                .ok_or_else(|| AppError::InternalError("Token ID missing".to_string()))?,
+            None => self.mint_invoice(&invoice).await?,
+        };
+
+        // A relisted invoice already has its on-chain pool, which `create_pool` reopens
+        let relisting = self
+            .funding_repo
+            .find_by_invoice(invoice.id)
+            .await?
+            .is_some();
+        if relisting {
+            return Ok((self.create_pool(invoice.id).await?, true));
+        }
+
+        // Shipment verification is a prerequisite for creating the pool on-chain
+        self.blockchain_service
+            .verify_shipment_on_chain(token_id)
+            .await?;
+        let create_pool_tx_hash = self
+            .blockchain_service
+            .create_pool_on_chain(token_id)
+            .await?;
+
+        // Also moves the invoice to "funding"
+        let pool = self.create_pool(invoice.id).await?;
+        let pool = self
+            .funding_repo
+            .set_create_pool_tx_hash(pool.id, &create_pool_tx_hash)
+            .await?;
+
+        Ok((pool, true))
+    }
+
+    /// Mint the NFT of an approved invoice, record it and mark the invoice `tokenized`
+    async fn mint_invoice(&self, invoice: &Invoice) -> AppResult<i64> {
+        if invoice.status != "approved" {
+            return Err(AppError::BadRequest(
+                "Invoice must be approved before tokenization".to_string(),
+            ));
+        }
+
+        let metadata_uri = self.blockchain_service.create_nft_metadata(invoice.id).await?;
+        let (token_id, tx_hash, contract_addr) = self
+            .blockchain_service
+            .mint_invoice_nft(invoice, &metadata_uri)
+            .await
+            .map_err(|e| {
+                tracing::error!("Minting failed for invoice {}: {}", invoice.id, e);
+                e
+            })?;
+
+        self.invoice_repo
+            .create_nft(
+                invoice.id,
+                token_id,
+                &contract_addr,
+                self.blockchain_service.active_idrx_token().chain_id as i32,
+                invoice.exporter_wallet_address.as_deref().unwrap_or(""),
+                &tx_hash,
+                &metadata_uri,
+            )
+            .await?;
+        self.invoice_repo
+            .update_status(invoice.id, "tokenized")
+            .await?;
+
+        Ok(token_id)
+    }
+
     pub async fn get_pool(&self, id: Uuid) -> AppResult<FundingPoolResponse> {
         let pool = self
             .funding_repo
@@ -125,32 +366,758 @@ impl FundingService {
 
         let invoice = self.invoice_repo.find_by_id(pool.invoice_id).await?;
 
-        self.build_pool_response(pool, invoice)
+        self.build_pool_response(pool, invoice)
+    }
+
+    pub async fn list_pools(
+        &self,
+        page: i32,
+        per_page: i32,
+    ) -> AppResult<(Vec<FundingPoolResponse>, i64)> {
+        let (pools, total) = self.funding_repo.find_all(page, per_page).await?;
+
+        let mut responses = Vec::new();
+        for pool in pools {
+            let invoice = self.invoice_repo.find_by_id(pool.invoice_id).await?;
+            responses.push(self.build_pool_response(pool, invoice)?);
+        }
+
+        Ok((responses, total))
+    }
+
+    /// Marketplace pools matching `filter`
+    pub async fn list_marketplace(
+        &self,
+        filter: &FundableInvoiceFilter,
+        page: i32,
+        per_page: i32,
+    ) -> AppResult<(Vec<FundingPoolResponse>, i64)> {
+        validate_fundable_filter(filter)?;
+
+        let (pools, total) = self
+            .funding_repo
+            .find_marketplace(filter, page, per_page)
+            .await?;
+
+        let mut responses = Vec::new();
+        for pool in pools {
+            let invoice = self.invoice_repo.find_by_id(pool.invoice_id).await?;
+            responses.push(self.build_pool_response(pool, invoice)?);
+        }
+
+        Ok((responses, total))
+    }
+
+    /// Every pool for admin oversight, e.g. `filled` pools awaiting disbursement
+    pub async fn list_all_pools(
+        &self,
+        filter: &PoolFilter,
+        page: i32,
+        per_page: i32,
+    ) -> AppResult<(Vec<AdminPoolListItem>, i64)> {
+        if let Some(status) = filter.status.as_deref() {
+            if !["open", "filled", "disbursed", "closed"].contains(&status) {
+                return Err(AppError::ValidationError(format!(
+                    "Unknown pool status '{}'",
+                    status
+                )));
+            }
+        }
+        let by_deadline = match filter.sort_by.as_deref() {
+            None | Some("newest") => false,
+            Some("deadline") => true,
+            Some(other) => {
+                return Err(AppError::ValidationError(format!(
+                    "Unknown sort '{}', expected 'deadline' or 'newest'",
+                    other
+                )))
+            }
+        };
+
+        let (pools, total) = self
+            .funding_repo
+            .find_all_filtered(filter.status.as_deref(), by_deadline, page, per_page)
+            .await?;
+
+        let now = Utc::now().naive_utc();
+        let mut items = Vec::new();
+        for pool in pools {
+            let invoice = self.invoice_repo.find_by_id(pool.invoice_id).await?;
+            let days_to_deadline = pool.deadline.map(|d| (d - now).num_days());
+            items.push(AdminPoolListItem {
+                pool: self.build_pool_response(pool, invoice)?,
+                days_to_deadline,
+            });
+        }
+
+        Ok((items, total))
+    }
+
+    /// Platform-wide aggregates, cached in Redis for a minute. A Redis failure only
+    /// costs the cache; the stats are then computed from the database.
+    pub async fn get_platform_stats(&self) -> AppResult<PlatformStats> {
+        if let Some(pool) = &self.redis_pool {
+            match Self::cached_platform_stats(pool).await {
+                Ok(Some(stats)) => return Ok(stats),
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Platform stats cache unavailable: {}", e),
+            }
+        }
+
+        let stats = self.funding_repo.get_platform_stats(None).await?;
+
+        if let Some(pool) = &self.redis_pool {
+            if let Err(e) = Self::cache_platform_stats(pool, &stats).await {
+                tracing::warn!("Failed to cache platform stats: {}", e);
+            }
+        }
+
+        Ok(stats)
+    }
+
+    async fn cached_platform_stats(
+        pool: &deadpool_redis::Pool,
+    ) -> anyhow::Result<Option<PlatformStats>> {
+        let mut conn = pool.get().await?;
+        let cached: Option<String> = redis::cmd("GET")
+            .arg(PLATFORM_STATS_CACHE_KEY)
+            .query_async(&mut conn)
+            .await?;
+
+        Ok(cached.map(|json| serde_json::from_str(&json)).transpose()?)
+    }
+
+    async fn cache_platform_stats(
+        pool: &deadpool_redis::Pool,
+        stats: &PlatformStats,
+    ) -> anyhow::Result<()> {
+        let mut conn = pool.get().await?;
+        redis::cmd("SET")
+            .arg(PLATFORM_STATS_CACHE_KEY)
+            .arg(serde_json::to_string(stats)?)
+            .arg("EX")
+            .arg(PLATFORM_STATS_CACHE_TTL_SECS)
+            .query_async::<_, ()>(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Pool, invoice, investors, transactions, on-chain checks and timeline in one view
+    /// for admins. The independent lookups run concurrently.
+    pub async fn get_admin_pool_detail(&self, id: Uuid) -> AppResult<AdminPoolDetail> {
+        let pool = self
+            .funding_repo
+            .find_by_id(id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Pool not found".to_string()))?;
+
+        let (invoice, nft, investments, transactions) = tokio::try_join!(
+            self.invoice_repo.find_by_id(pool.invoice_id),
+            self.invoice_repo.find_nft_by_invoice(pool.invoice_id),
+            self.funding_repo.find_admin_pool_investments(pool.id),
+            self.tx_repo.find_by_invoice(pool.invoice_id),
+        )?;
+        let invoice = invoice.ok_or_else(|| AppError::NotFound("Invoice not found".to_string()))?;
+
+        let verified_investments = investments.iter().filter(|i| i.tx_verified).count();
+        let invested: Decimal = investments.iter().map(|i| i.amount).sum();
+        let verification = PoolVerificationStatus {
+            nft_minted: nft.as_ref().is_some_and(|n| n.token_id.is_some()),
+            pool_created_on_chain: pool.create_pool_tx_hash.is_some(),
+            verified_investments,
+            unverified_investments: investments.len() - verified_investments,
+            funded_amount_matches: invested == pool.funded_amount,
+        };
+
+        let event = |event: &str, occurred_at, reference_id| PoolTimelineEvent {
+            event: event.to_string(),
+            occurred_at,
+            reference_id,
+        };
+        let mut timeline = vec![event(
+            "invoice_created",
+            invoice.created_at,
+            Some(invoice.id),
+        )];
+        if let Some(minted_at) = nft.as_ref().and_then(|n| n.minted_at) {
+            timeline.push(event("nft_minted", minted_at, None));
+        }
+        timeline.push(event(
+            "pool_opened",
+            pool.opened_at.unwrap_or(pool.created_at),
+            None,
+        ));
+        for inv in &investments {
+            timeline.push(event(
+                "investment",
+                inv.invested_at,
+                Some(inv.investment_id),
+            ));
+            if let Some(repaid_at) = inv.repaid_at {
+                timeline.push(event(
+                    "investment_repaid",
+                    repaid_at,
+                    Some(inv.investment_id),
+                ));
+            }
+        }
+        for (name, at) in [
+            ("pool_filled", pool.filled_at),
+            ("pool_disbursed", pool.disbursed_at),
+            ("pool_closed", pool.closed_at),
+        ] {
+            if let Some(at) = at {
+                timeline.push(event(name, at, None));
+            }
+        }
+        timeline.sort_by_key(|e| e.occurred_at);
+
+        Ok(AdminPoolDetail {
+            pool,
+            invoice,
+            nft,
+            investments,
+            transactions,
+            verification,
+            timeline,
+        })
+    }
+
+    /// The invoice's NFT with explorer links. With `verify_owner`, the holder is
+    /// also read from the contract and compared with the recorded owner; a
+    /// failed read is reported in the view rather than failing the request.
+    pub async fn get_invoice_nft(
+        &self,
+        invoice_id: Uuid,
+        verify_owner: bool,
+    ) -> AppResult<InvoiceNftView> {
+        self.invoice_repo
+            .find_by_id(invoice_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Invoice not found".to_string()))?;
+
+        let mut view = InvoiceNftView {
+            invoice_id,
+            minted: false,
+            nft: None,
+            explorer_url: None,
+            opensea_url: None,
+            mint_tx_explorer_url: None,
+            onchain_owner: None,
+            owner_matches: None,
+            owner_check_error: None,
+        };
+        let Some(nft) = self.invoice_repo.find_nft_by_invoice(invoice_id).await? else {
+            return Ok(view);
+        };
+        let Some(token_id) = nft.token_id else {
+            return Ok(view);
+        };
+
+        view.minted = true;
+        view.explorer_url = nft.contract_address.as_deref().map(|contract| {
+            self.blockchain_service
+                .get_nft_explorer_url(contract, token_id)
+        });
+        view.opensea_url = nft.opensea_url();
+        view.mint_tx_explorer_url = nft
+            .mint_tx_hash
+            .as_deref()
+            .map(|tx_hash| self.blockchain_service.get_explorer_url(tx_hash));
+
+        if verify_owner && nft.is_live() {
+            match self.blockchain_service.get_nft_owner(token_id).await {
+                Ok(owner) => {
+                    let owner = format!("{:?}", owner);
+                    view.owner_matches = Some(
+                        nft.owner_address
+                            .as_deref()
+                            .is_some_and(|recorded| recorded.eq_ignore_ascii_case(&owner)),
+                    );
+                    view.onchain_owner = Some(owner);
+                }
+                Err(e) => view.owner_check_error = Some(e.to_string()),
+            }
+        }
+        view.nft = Some(nft);
+
+        Ok(view)
+    }
+
+    /// Investment flow (ON-CHAIN):
+    /// 1. Investor transfers IDRX to platform wallet (done before calling this)
+    /// 2. This endpoint verifies the on-chain transaction
+    /// 3. Records the investment with verified tx_hash
+    /// 4. Updates pool funded amounts
+    ///    All transactions are transparent and verifiable on Base mainnet
+    pub async fn invest(&self, investor_id: Uuid, req: InvestRequest) -> AppResult<Investment> {
+        self.finalize_investment(investor_id, req).await
+    }
+
+    /// Confirm an IDRX transfer to the platform wallet, e.g. after the client lost
+    /// the response to `invest`. Shares the finalization path with `invest`, so
+    /// the same transfer is only ever recorded once.
+    pub async fn confirm_investment(
+        &self,
+        investor_id: Uuid,
+        req: InvestRequest,
+    ) -> AppResult<Investment> {
+        if self.config.investment_mode != InvestmentMode::OnChain {
+            return Err(AppError::BadRequest(
+                "Off-chain investments are settled on submission; there is no transfer to confirm"
+                    .to_string(),
+            ));
+        }
+        if req.tx_hash.is_empty() {
+            return Err(AppError::ValidationError(
+                "Transaction hash of the transfer to confirm is required".to_string(),
+            ));
+        }
+
+        self.finalize_investment(investor_id, req).await
+    }
+
+    /// Invest by pulling IDRX with an EIP-2612 permit signed by the investor rather
+    /// than a transfer made beforehand. The pulled transfer then goes through the
+    /// same verification and recording as `invest`.
+    pub async fn invest_with_permit(
+        &self,
+        investor_id: Uuid,
+        req: InvestWithPermitRequest,
+    ) -> AppResult<Investment> {
+        if !self.config.enable_permit_investments {
+            return Err(AppError::BadRequest(
+                "Permit-based investment is not enabled".to_string(),
+            ));
+        }
+        if self.config.investment_mode != InvestmentMode::OnChain {
+            return Err(AppError::BadRequest(
+                "Permit-based investment requires on-chain investment mode".to_string(),
+            ));
+        }
+        if req.deadline <= Utc::now().timestamp() {
+            return Err(AppError::ValidationError(
+                "Permit deadline has passed".to_string(),
+            ));
+        }
+        if !req.tnc_accepted {
+            return Err(AppError::ValidationError(
+                "Must accept terms and conditions".to_string(),
+            ));
+        }
+
+        // Funds move before the investment is recorded, so every check that
+        // finalization repeats runs up front
+        self.check_investor_eligibility(investor_id).await?;
+
+        let investor = self
+            .user_repo
+            .find_by_id(investor_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Investor not found".to_string()))?;
+        let owns_wallet = investor
+            .wallet_address
+            .as_deref()
+            .is_some_and(|wallet| wallet.eq_ignore_ascii_case(&req.owner));
+        if !owns_wallet {
+            return Err(AppError::Forbidden(
+                "Permit owner must be your connected wallet".to_string(),
+            ));
+        }
+
+        let mut invest_req = InvestRequest {
+            pool_id: req.pool_id,
+            amount: req.amount,
+            tranche: req.tranche.clone(),
+            tx_hash: String::new(),
+            tnc_accepted: req.tnc_accepted,
+            catalyst_consents: req.catalyst_consents.clone(),
+        };
+        let CheckedInvestment { amount, .. } =
+            self.check_investment(investor_id, &invest_req).await?;
+
+        let sent = self
+            .blockchain_service
+            .transfer_with_permit(&req, amount)
+            .await?;
+
+        invest_req.tx_hash = sent.tx_hash.clone();
+        let investment = match self.finalize_investment(investor_id, invest_req).await {
+            Ok(investment) => investment,
+            Err(e) => {
+                tracing::error!(
+                    "Permit transfer {} from investor {} was not recorded: {}",
+                    sent.tx_hash,
+                    investor_id,
+                    e
+                );
+                self.refund_unclaimed_transfer(&sent.tx_hash, &req.owner, amount)
+                    .await;
+                return Err(e);
+            }
+        };
+
+        if let Err(e) = self.record_gas(&sent).await {
+            tracing::warn!("Failed to record gas for {}: {}", sent.tx_hash, e);
+        }
+
+        Ok(investment)
+    }
+
+    /// Return a pulled transfer to `owner` when no investment claimed it, so the
+    /// funds are still in the platform wallet. A claimed transfer may already
+    /// have been forwarded and is reconciled from its pending investment instead.
+    async fn refund_unclaimed_transfer(&self, tx_hash: &str, owner: &str, amount: Decimal) {
+        match self.funding_repo.find_investment_by_tx_hash(tx_hash).await {
+            Ok(None) => {
+                match self
+                    .blockchain_service
+                    .transfer_idrx(
+                        owner,
+                        amount,
+                        crate::services::blockchain_service::OnChainTxType::Refund,
+                    )
+                    .await
+                {
+                    Ok(refund) => tracing::info!(
+                        "Refunded {} IDRX of transfer {} to {} in {}",
+                        amount,
+                        tx_hash,
+                        mask_wallet(owner),
+                        refund.tx_hash
+                    ),
+                    Err(e) => tracing::error!(
+                        "Failed to refund {} IDRX of transfer {} to {}: {}",
+                        amount,
+                        tx_hash,
+                        mask_wallet(owner),
+                        e
+                    ),
+                }
+            }
+            Ok(Some(investment)) => tracing::error!(
+                "Transfer {} is claimed by investment {} ({}); not refunded",
+                tx_hash,
+                investment.id,
+                investment.status
+            ),
+            Err(e) => tracing::error!(
+                "Could not check whether transfer {} was claimed; not refunded: {}",
+                tx_hash,
+                e
+            ),
+        }
+    }
+
+    /// Single finalization path for `invest` and `confirm_investment`.
+    /// A transfer backs at most one investment across all investors: the
+    /// unique index on the investment's tx hash makes the first finalization
+    /// claim it, later ones by the same investor return that investment unchanged.
+    async fn finalize_investment(
+        &self,
+        investor_id: Uuid,
+        req: InvestRequest,
+    ) -> AppResult<Investment> {
+        self.check_investor_eligibility(investor_id).await?;
+
+        if self.config.investment_mode != InvestmentMode::OnChain || req.tx_hash.is_empty() {
+            return self.record_investment(investor_id, req).await;
+        }
+
+        if let Some(existing) = self.find_finalized(investor_id, &req.tx_hash).await? {
+            return Ok(existing);
+        }
+
+        let tx_hash = req.tx_hash.clone();
+        match self.record_investment(investor_id, req).await {
+            // A concurrent finalization claimed the transfer first, failing this
+            // one on the unique tx hash or the one-investment-per-pool check
+            Err(e @ (AppError::Conflict(_) | AppError::Forbidden(_))) => {
+                match self.find_finalized(investor_id, &tx_hash).await? {
+                    Some(existing) => Ok(existing),
+                    None => Err(e),
+                }
+            }
+            result => result,
+        }
+    }
+
+    async fn find_finalized(
+        &self,
+        investor_id: Uuid,
+        tx_hash: &str,
+    ) -> AppResult<Option<Investment>> {
+        let existing = self
+            .funding_repo
+            .find_investment_by_tx_hash(tx_hash)
+            .await?;
+        if let Some(existing) = &existing {
+            if existing.investor_id != investor_id {
+                return Err(AppError::Conflict(
+                    "This transfer already backs another investment".to_string(),
+                ));
+            }
+            tracing::info!(
+                "Transfer {} already finalized as investment {}",
+                tx_hash,
+                existing.id
+            );
+        }
+
+        Ok(existing)
+    }
+
+    /// Investors must have an approved KYC verification (when `require_investor_kyc`
+    /// is set) and a completed risk questionnaire before funding any tranche.
+    async fn check_investor_eligibility(&self, investor_id: Uuid) -> AppResult<()> {
+        if self.config.require_investor_kyc && !self.user_repo.is_kyc_approved(investor_id).await? {
+            return Err(AppError::KycRequired);
+        }
+
+        if !self.rq_repo.has_completed(investor_id).await? {
+            return Err(AppError::RiskQuestionnaireRequired);
+        }
+
+        Ok(())
+    }
+
+    async fn record_investment(
+        &self,
+        investor_id: Uuid,
+        req: InvestRequest,
+    ) -> AppResult<Investment> {
+        let on_chain = self.config.investment_mode == InvestmentMode::OnChain;
+
+        // On-chain investments are backed by a transfer the investor already made
+        if on_chain && req.tx_hash.is_empty() {
+            return Err(AppError::ValidationError(
+                "Transaction hash is required. Please transfer IDRX to platform wallet first."
+                    .to_string(),
+            ));
+        }
+
+        let CheckedInvestment {
+            pool,
+            investor,
+            amount,
+            is_catalyst,
+            interest_rate,
+            payout_wallet,
+            ..
+        } = self.check_investment(investor_id, &req).await?;
+
+        // ============ ON-CHAIN VERIFICATION ============
+        // Verify the IDRX transfer transaction on Base mainnet
+        // This ensures the investor actually sent IDRX to the platform wallet,
+        // from their own connected wallet rather than someone else's transfer.
+        // Off-chain mode has no transfer; the balance is debited during settlement.
+        let verified_transfer = if on_chain {
+            let wallet = investor.wallet_address.as_deref().ok_or_else(|| {
+                AppError::ValidationError(
+                    "Connect the wallet you transferred IDRX from".to_string(),
+                )
+            })?;
+            let verified_transfer = self.blockchain_service
+                .verify_investment_transfer(&req.tx_hash, Some(wallet), amount)
+                .await
+                .map_err(|e| AppError::BlockchainError(format!(
+                    "Failed to verify on-chain transfer: {}. Please ensure you have transferred {} IDRX to the platform wallet.",
+                    e, amount
+                )))?;
+
+            tracing::info!(
+                "Verified on-chain investment: {} IDRX from {} (tx: {}, block: {})",
+                verified_transfer.amount,
+                mask_wallet(&verified_transfer.from),
+                verified_transfer.tx_hash,
+                verified_transfer.block_number
+            );
+            Some(verified_transfer)
+        } else {
+            None
+        };
+
+        let invoice = self
+            .invoice_repo
+            .find_by_id(pool.invoice_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Invoice not found".to_string()))?;
+        let expected_return = projected_return(&invoice, amount, interest_rate);
+
+        // Claim the tranche capacity and the investment row in one short
+        // transaction. The pool row is locked only until this commits, so
+        // concurrent investors cannot both pass the availability check in
+        // `check_investment`, yet no lock or connection is held while on-chain
+        // sends are in flight. On-chain investments are claimed as `pending`.
+        let mut tx = self.funding_repo.begin().await?;
+        self.funding_repo
+            .reserve_capacity_tx(&mut tx, req.pool_id, is_catalyst, amount)
+            .await?;
+
+        let investment = match &verified_transfer {
+            Some(_) => {
+                self.funding_repo
+                    .create_investment_tx(
+                        &mut tx,
+                        req.pool_id,
+                        investor_id,
+                        amount,
+                        expected_return,
+                        &req.tranche,
+                        Some(&req.tx_hash),
+                        "pending",
+                    )
+                    .await?
+            }
+            None => {
+                self.record_off_chain_investment(
+                    &mut tx,
+                    investor_id,
+                    &req,
+                    amount,
+                    expected_return,
+                )
+                .await?
+            }
+        };
+
+        let investor_count = self
+            .funding_repo
+            .count_investors_in_pool_tx(&mut tx, req.pool_id)
+            .await? as i32;
+
+        self.funding_repo
+            .update_investor_count_tx(&mut tx, req.pool_id, investor_count)
+            .await?;
+
+        // Off-chain investments are final here; on-chain ones fill the pool once confirmed
+        let filled_pool = if verified_transfer.is_none() {
+            self.fill_if_funded_tx(&mut tx, &pool).await?
+        } else {
+            None
+        };
+
+        tx.commit().await?;
+
+        let (investment, filled_pool) = match &verified_transfer {
+            Some(transfer) => {
+                self.complete_on_chain_investment(
+                    investment,
+                    &pool,
+                    is_catalyst,
+                    transfer,
+                    &payout_wallet,
+                )
+                .await?
+            }
+            None => (investment, filled_pool),
+        };
+
+        if let Some(filled_pool) = filled_pool {
+            self.notify_pool_filled(&filled_pool, &invoice).await?;
+        }
+
+        self.activity_logger
+            .log(
+                investor_id,
+                ActivityType::InvestmentCreated,
+                &format!(
+                    "Invested {} IDRX in the {} tranche of invoice {}",
+                    amount, req.tranche, invoice.invoice_number
+                ),
+            )
+            .await;
+
+        self.notification_service
+            .notify(
+                investor_id,
+                NotificationKind::InvestmentConfirmed,
+                serde_json::json!({
+                    "investment_id": investment.id,
+                    "pool_id": req.pool_id,
+                    "invoice_number": invoice.invoice_number,
+                    "tranche": req.tranche,
+                    "amount": amount,
+                    "expected_return": expected_return,
+                }),
+            )
+            .await;
+
+        // Send confirmation email with on-chain tx details
+        if let Some(email) = &investor.email {
+            let _ = self
+                .email_service
+                .send_investment_confirmation(
+                    email,
+                    &invoice.invoice_number,
+                    req.pool_id,
+                    amount.to_f64().unwrap_or(0.0),
+                    &req.tranche,
+                    expected_return.to_f64().unwrap_or(0.0),
+                    verified_transfer.as_ref().map(|t| t.tx_hash.as_str()),
+                    verified_transfer.as_ref().map(|t| t.explorer_url.as_str()),
+                )
+                .await;
+        }
+
+        match &verified_transfer {
+            Some(transfer) => tracing::info!(
+                "Investment recorded: {} IDRX in pool {} by investor {} - viewable at {}",
+                amount,
+                req.pool_id,
+                investor_id,
+                transfer.explorer_url
+            ),
+            None => tracing::info!(
+                "Off-chain investment recorded: {} IDRX in pool {} by investor {}",
+                amount,
+                req.pool_id,
+                investor_id
+            ),
+        }
+        self.metrics.record_investment(&req.tranche);
+
+        Ok(investment)
     }
 
-    pub async fn list_pools(
+    /// Dry run of `invest`: every eligibility, pool, tranche and limit check runs,
+    /// but nothing is verified on-chain and nothing is written. Returns the first
+    /// blocking error, or what the investment would look like.
+    pub async fn preview_investment(
         &self,
-        page: i32,
-        per_page: i32,
-    ) -> AppResult<(Vec<FundingPoolResponse>, i64)> {
-        let (pools, total) = self.funding_repo.find_all(page, per_page).await?;
+        investor_id: Uuid,
+        req: InvestRequest,
+    ) -> AppResult<InvestmentPreview> {
+        self.check_investor_eligibility(investor_id).await?;
+        let checked = self.check_investment(investor_id, &req).await?;
 
-        let mut responses = Vec::new();
-        for pool in pools {
-            let invoice = self.invoice_repo.find_by_id(pool.invoice_id).await?;
-            responses.push(self.build_pool_response(pool, invoice)?);
-        }
+        let invoice = self
+            .invoice_repo
+            .find_by_id(checked.pool.invoice_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Invoice not found".to_string()))?;
 
-        Ok((responses, total))
+        Ok(InvestmentPreview {
+            pool_id: req.pool_id,
+            tranche: req.tranche,
+            amount: checked.amount,
+            interest_rate: checked.interest_rate,
+            expected_return: projected_return(&invoice, checked.amount, checked.interest_rate),
+            maturity_date: invoice.due_date,
+            min_investment: checked.min_limit,
+            max_investment: checked.max_limit,
+            tranche_remaining: checked.tranche_remaining,
+        })
     }
 
-    /// Investment flow (ON-CHAIN):
-    /// 1. Investor transfers IDRX to platform wallet (done before calling this)
-    /// 2. This endpoint verifies the on-chain transaction
-    /// 3. Records the investment with verified tx_hash
-    /// 4. Updates pool funded amounts
-    ///    All transactions are transparent and verifiable on Base mainnet
-    pub async fn invest(&self, investor_id: Uuid, req: InvestRequest) -> AppResult<Investment> {
+    /// Checks shared by `record_investment` and `preview_investment`. Reads only;
+    /// the capacity is claimed again under the pool lock when recording.
+    async fn check_investment(
+        &self,
+        investor_id: Uuid,
+        req: &InvestRequest,
+    ) -> AppResult<CheckedInvestment> {
         // Validate TnC acceptance
         if !req.tnc_accepted {
             return Err(AppError::ValidationError(
@@ -158,14 +1125,6 @@ impl FundingService {
             ));
         }
 
-        // Validate tx_hash is provided
-        if req.tx_hash.is_empty() {
-            return Err(AppError::ValidationError(
-                "Transaction hash is required. Please transfer IDRX to platform wallet first."
-                    .to_string(),
-            ));
-        }
-
         // Get pool
         let pool = self
             .funding_repo
@@ -178,6 +1137,15 @@ impl FundingService {
             return Err(AppError::PoolNotOpen);
         }
 
+        // The expiry processor runs asynchronously, so an expired pool may still be "open"
+        if let Some(deadline) = pool.deadline {
+            if Utc::now().naive_utc() >= deadline {
+                return Err(AppError::BadRequest(
+                    "Funding deadline for this pool has passed".to_string(),
+                ));
+            }
+        }
+
         // Check if investor already invested in this pool
         if self
             .funding_repo
@@ -222,32 +1190,37 @@ impl FundingService {
             .await?
             .ok_or_else(|| AppError::NotFound("Investor not found".to_string()))?;
 
-        // Investor must have a wallet address
-        let _investor_wallet = investor.wallet_address.as_ref().ok_or_else(|| {
-            AppError::ValidationError("Investor wallet address not set".to_string())
-        })?;
+        // Returns are paid to the payout wallet, so it must be known and verified
+        let payout_wallet = investor
+            .payout_wallet()
+            .ok_or_else(|| {
+                AppError::ValidationError("Investor has no verified payout wallet".to_string())
+            })?
+            .to_string();
 
         let amount = Decimal::from_f64(req.amount)
             .ok_or_else(|| AppError::ValidationError("Invalid amount".to_string()))?;
 
-        // ============ ON-CHAIN VERIFICATION ============
-        // Verify the IDRX transfer transaction on Base mainnet
-        // This ensures the investor actually sent IDRX to the platform wallet
-        let verified_transfer = self.blockchain_service
-            .verify_investment_transfer(&req.tx_hash, amount)
-            .await
-            .map_err(|e| AppError::BlockchainError(format!(
-                "Failed to verify on-chain transfer: {}. Please ensure you have transferred {} IDRX to the platform wallet.",
-                e, amount
-            )))?;
+        // IDRX has 2 decimals; sub-cent amounts would be rounded when converted to token units
+        if amount.round_dp(2) != amount {
+            return Err(AppError::ValidationError(format!(
+                "Amount {} has more than 2 decimal places; IDRX amounts are limited to 2",
+                req.amount
+            )));
+        }
 
-        tracing::info!(
-            "Verified on-chain investment: {} IDRX from {} (tx: {}, block: {})",
-            verified_transfer.amount,
-            verified_transfer.from,
-            verified_transfer.tx_hash,
-            verified_transfer.block_number
-        );
+        // AML: cap how much an investor can invest within the rolling window
+        self.check_velocity_cap(
+            investor_id,
+            "investment",
+            amount,
+            self.config.investor_velocity_cap,
+        )
+        .await?;
+
+        // Limit concentration on a single mitra across all of their pools
+        self.check_mitra_exposure_cap(investor_id, &pool, amount)
+            .await?;
 
         // Check tranche availability
         let (available, interest_rate) = if is_catalyst {
@@ -258,16 +1231,31 @@ impl FundingService {
             (available, pool.priority_interest_rate)
         };
 
-        // Check 10-90% Limits based on TRANCHE target
+        // Min/max limits are relative to the chosen TRANCHE target, not the whole pool,
+        // so a small catalyst tranche stays investable
         let tranche_target = if is_catalyst {
             pool.catalyst_target
         } else {
             pool.priority_target
         };
 
-        let min_limit = tranche_target * Decimal::from_f64(0.1).unwrap();
-        let max_limit = tranche_target * Decimal::from_f64(0.9).unwrap();
-        
+        // Limits stored on the pool at creation; older pools use the config defaults
+        let (pool_min, pool_max) = if is_catalyst {
+            (
+                pool.catalyst_min_invest_ratio,
+                pool.catalyst_max_invest_ratio,
+            )
+        } else {
+            (pool.min_invest_ratio, pool.max_invest_ratio)
+        };
+        let (default_min, default_max) = self.config.investment_limit_ratios(is_catalyst);
+        let min_ratio = pool_min.unwrap_or(default_min);
+        let max_ratio = pool_max.unwrap_or(default_max);
+        let min_limit = tranche_target * min_ratio;
+        let max_limit = tranche_target * max_ratio;
+        let min_pct = (min_ratio * Decimal::ONE_HUNDRED).normalize();
+        let max_pct = (max_ratio * Decimal::ONE_HUNDRED).normalize();
+
         let tranche_remaining = if is_catalyst {
             pool.catalyst_target - pool.catalyst_funded
         } else {
@@ -277,14 +1265,14 @@ impl FundingService {
         if tranche_remaining >= min_limit {
             if amount < min_limit {
                 return Err(AppError::ValidationError(format!(
-                    "Minimum investment is 10% of {} tranche target ({})",
-                    req.tranche, min_limit
+                    "Minimum investment is {}% of {} tranche target ({})",
+                    min_pct, req.tranche, min_limit
                 )));
             }
             if amount > max_limit {
                 return Err(AppError::ValidationError(format!(
-                    "Maximum investment is 90% of {} tranche target ({})",
-                    req.tranche, max_limit
+                    "Maximum investment is {}% of {} tranche target ({})",
+                    max_pct, req.tranche, max_limit
                 )));
             }
         } else {
@@ -304,12 +1292,351 @@ impl FundingService {
             )));
         }
 
-        // Forward funds to InvoicePool Contract (Platform -> Contract)
-        // Since we verified the user sent to Platform, we now move it to Contract
-        // Note: verify_investment_transfer confirmed user sent to Platform Wallet
+        Ok(CheckedInvestment {
+            pool,
+            investor,
+            amount,
+            is_catalyst,
+            interest_rate,
+            payout_wallet,
+            min_limit,
+            max_limit,
+            tranche_remaining,
+        })
+    }
 
-        let contract_addr = &self.config.invoice_pool_contract_addr;
-        let _forward_tx = self
+    /// Mark the pool filled if this investment completed it, and the invoice funded with it
+    async fn fill_if_funded_tx(
+        &self,
+        conn: &mut PgConnection,
+        pool: &FundingPool,
+    ) -> AppResult<Option<FundingPool>> {
+        let filled = self
+            .funding_repo
+            .set_filled_if_funded_tx(&mut *conn, pool.id)
+            .await?;
+        if filled.is_some() {
+            self.invoice_repo
+                .update_status_tx(conn, pool.invoice_id, "funded")
+                .await?;
+        }
+
+        Ok(filled)
+    }
+
+    /// Move a claimed investment's funds to the InvoicePool contract, record it
+    /// there and confirm the claim. Runs outside any transaction.
+    /// While the funds are still in the platform wallet a failure releases the
+    /// claim, so the transfer can be confirmed again. Once they have moved, a
+    /// failure leaves the investment `pending` for reconciliation instead.
+    async fn complete_on_chain_investment(
+        &self,
+        investment: Investment,
+        pool: &FundingPool,
+        is_catalyst: bool,
+        transfer: &VerifiedTransfer,
+        payout_wallet: &str,
+    ) -> AppResult<(Investment, Option<FundingPool>)> {
+        let forward_tx_hash = match self.forward_investment_funds(investment.amount).await {
+            Ok(forward_tx_hash) => forward_tx_hash,
+            Err(e) => {
+                if let Err(release_err) = self
+                    .funding_repo
+                    .release_pending_investment(&investment, is_catalyst)
+                    .await
+                {
+                    tracing::error!(
+                        "Failed to release claim of investment {}: {}",
+                        investment.id,
+                        release_err
+                    );
+                }
+                return Err(e);
+            }
+        };
+        // Reconciliation checks this transfer before recording the investment on the contract
+        if let Err(e) = self
+            .funding_repo
+            .set_investment_forward_tx(investment.id, &forward_tx_hash)
+            .await
+        {
+            tracing::error!(
+                "Investment {} was forwarded in {} but the transfer could not be stored: {}",
+                investment.id,
+                forward_tx_hash,
+                e
+            );
+        }
+
+        if let Err(e) = self
+            .record_investment_on_pool_contract(pool, investment.amount, payout_wallet)
+            .await
+        {
+            tracing::error!(
+                "Investment {} was forwarded to the pool contract but not recorded there; left pending: {}",
+                investment.id,
+                e
+            );
+            return Err(e);
+        }
+
+        let investment_id = investment.id;
+        self.confirm_on_chain_investment(investment, pool, transfer, payout_wallet)
+            .await
+            .map_err(|e| {
+                tracing::error!(
+                    "Investment {} is recorded on-chain but could not be confirmed; left pending: {}",
+                    investment_id,
+                    e
+                );
+                e
+            })
+    }
+
+    /// Second short transaction of an on-chain investment: the transfer is
+    /// recorded for the audit trail, the claim confirmed and the pool filled
+    /// if this was the last pending investment to complete it
+    async fn confirm_on_chain_investment(
+        &self,
+        investment: Investment,
+        pool: &FundingPool,
+        transfer: &VerifiedTransfer,
+        payout_wallet: &str,
+    ) -> AppResult<(Investment, Option<FundingPool>)> {
+        let mut tx = self.funding_repo.begin().await?;
+        // Serializes confirmations so the last one sees no other pending investment
+        self.funding_repo.lock_pool_tx(&mut tx, pool.id).await?;
+
+        self.tx_repo
+            .create_blockchain_transaction_tx(
+                &mut tx,
+                investment.investor_id,
+                "investment",
+                investment.amount,
+                &transfer.tx_hash,
+                transfer.block_number as i64,
+                Some(pool.invoice_id),
+                Some(&format!("On-chain IDRX investment in pool {}", pool.id)),
+                &transfer.explorer_url,
+            )
+            .await?;
+
+        let investment = self
+            .funding_repo
+            .confirm_investment_tx(&mut tx, investment.id, payout_wallet)
+            .await?;
+        let filled_pool = self.fill_if_funded_tx(&mut tx, pool).await?;
+
+        tx.commit().await?;
+
+        Ok((investment, filled_pool))
+    }
+
+    /// Finish an on-chain investment left `pending`: forward its funds to the
+    /// InvoicePool contract if that never happened, or check the stored forward
+    /// on-chain, record it on the contract unless the contract already has it,
+    /// then confirm it from its stored transfer. Until then the pending row
+    /// keeps its pool from filling and being disbursed.
+    pub async fn reconcile_pending_investment(&self, investment_id: Uuid) -> AppResult<Investment> {
+        if self.config.investment_mode != InvestmentMode::OnChain {
+            return Err(AppError::BadRequest(
+                "Only on-chain investments can be left pending".to_string(),
+            ));
+        }
+
+        let investment = self
+            .funding_repo
+            .find_investment_by_id(investment_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Investment not found".to_string()))?;
+        if investment.status != "pending" {
+            return Err(AppError::Conflict(format!(
+                "Investment is {}, not pending",
+                investment.status
+            )));
+        }
+        // A recent claim may still be completing in the request that made it
+        let stuck_before = (Utc::now()
+            - Duration::minutes(PENDING_INVESTMENT_RECONCILE_AFTER_MINUTES))
+        .naive_utc();
+        if investment.created_at > stuck_before {
+            return Err(AppError::Conflict(format!(
+                "Investment was claimed less than {} minutes ago and may still complete",
+                PENDING_INVESTMENT_RECONCILE_AFTER_MINUTES
+            )));
+        }
+        let tx_hash = investment.tx_hash.clone().ok_or_else(|| {
+            AppError::InternalError("Pending investment has no transfer hash".to_string())
+        })?;
+
+        let pool = self
+            .funding_repo
+            .find_by_id(investment.pool_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Pool not found".to_string()))?;
+        let invoice = self
+            .invoice_repo
+            .find_by_id(pool.invoice_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Invoice not found".to_string()))?;
+        let payout_wallet = self
+            .user_repo
+            .find_by_id(investment.investor_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Investor not found".to_string()))?
+            .payout_wallet()
+            .ok_or_else(|| {
+                AppError::ValidationError("Investor has no verified payout wallet".to_string())
+            })?
+            .to_string();
+
+        let transfer = self
+            .blockchain_service
+            .verify_investment_transfer(&tx_hash, None, investment.amount)
+            .await?;
+
+        // The claim is committed before its funds are forwarded, so a crash in
+        // between leaves an investment the contract never received
+        let forward_tx_hash = match investment.forward_tx_hash.clone() {
+            Some(forward_tx_hash) => forward_tx_hash,
+            None => {
+                tracing::warn!(
+                    "Pending investment {} was never forwarded to the pool contract; forwarding it now",
+                    investment.id
+                );
+                let forward_tx_hash = self.forward_investment_funds(investment.amount).await?;
+                self.funding_repo
+                    .set_investment_forward_tx(investment.id, &forward_tx_hash)
+                    .await?;
+                forward_tx_hash
+            }
+        };
+        self.blockchain_service
+            .verify_idrx_transfer_to_contract(&forward_tx_hash, investment.amount)
+            .await?;
+
+        if self
+            .is_recorded_on_pool_contract(&pool, &investment, &payout_wallet)
+            .await?
+        {
+            tracing::info!(
+                "Investment {} is already recorded on the pool contract",
+                investment.id
+            );
+        } else {
+            self.record_investment_on_pool_contract(&pool, investment.amount, &payout_wallet)
+                .await?;
+        }
+
+        let (investment, filled_pool) = self
+            .confirm_on_chain_investment(investment, &pool, &transfer, &payout_wallet)
+            .await?;
+        if let Some(filled_pool) = filled_pool {
+            self.notify_pool_filled(&filled_pool, &invoice).await?;
+        }
+
+        tracing::info!(
+            "Reconciled pending investment {} of {} IDRX in pool {}",
+            investment.id,
+            investment.amount,
+            pool.id
+        );
+        self.metrics.record_investment(&investment.tranche);
+
+        Ok(investment)
+    }
+
+    /// Whether the contract holds more investments of this wallet and amount
+    /// than the pool's confirmed investments account for
+    async fn is_recorded_on_pool_contract(
+        &self,
+        pool: &FundingPool,
+        investment: &Investment,
+        payout_wallet: &str,
+    ) -> AppResult<bool> {
+        let token_id = self
+            .invoice_repo
+            .find_nft_by_invoice(pool.invoice_id)
+            .await?
+            .and_then(|nft| nft.token_id)
+            .ok_or_else(|| {
+                AppError::InternalError("Token ID missing from NFT record".to_string())
+            })?;
+        let matches = |wallet: &str, amount: Decimal| {
+            wallet.eq_ignore_ascii_case(payout_wallet) && amount == investment.amount
+        };
+
+        let on_chain = self
+            .blockchain_service
+            .get_investment_events(token_id)
+            .await?
+            .iter()
+            .filter(|event| matches(&format!("{:?}", event.investor), event.amount))
+            .count();
+        let confirmed = self
+            .funding_repo
+            .find_investments_by_pool(pool.id)
+            .await?
+            .iter()
+            .filter(|i| i.status != "pending")
+            .filter(|i| matches(i.payout_wallet.as_deref().unwrap_or_default(), i.amount))
+            .count();
+
+        Ok(on_chain > confirmed)
+    }
+
+    /// Webhook, exporter notification and email for a pool that just filled
+    async fn notify_pool_filled(
+        &self,
+        filled_pool: &FundingPool,
+        invoice: &Invoice,
+    ) -> AppResult<()> {
+        self.notification_service.send_webhook(
+            WebhookEvent::PoolFilled,
+            serde_json::json!({
+                "pool_id": filled_pool.id,
+                "invoice_id": filled_pool.invoice_id,
+                "invoice_number": invoice.invoice_number,
+                "target_amount": filled_pool.target_amount,
+                "funded_amount": filled_pool.funded_amount,
+                "investor_count": filled_pool.investor_count,
+            }),
+        );
+
+        // Notify exporter
+        self.notification_service
+            .notify(
+                invoice.exporter_id,
+                NotificationKind::PoolFunded,
+                serde_json::json!({
+                    "pool_id": filled_pool.id,
+                    "invoice_id": filled_pool.invoice_id,
+                    "invoice_number": invoice.invoice_number,
+                    "funded_amount": filled_pool.funded_amount,
+                }),
+            )
+            .await;
+        if let Some(exporter) = self.user_repo.find_by_id(invoice.exporter_id).await? {
+            if let Some(email) = &exporter.email {
+                let _ = self
+                    .email_service
+                    .send_pool_funded_notification(
+                        email,
+                        &invoice.invoice_number,
+                        filled_pool.target_amount.to_f64().unwrap_or(0.0),
+                    )
+                    .await;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Forward the investor's verified transfer from the platform wallet to the
+    /// InvoicePool contract, returning the forwarding transfer's hash
+    async fn forward_investment_funds(&self, amount: Decimal) -> AppResult<String> {
+        let contract_addr = self.blockchain_service.pool_contract_addr();
+        let sent = self
             .blockchain_service
             .transfer_idrx(
                 contract_addr,
@@ -321,6 +1648,17 @@ impl FundingService {
                 AppError::BlockchainError(format!("Failed to forward funds to contract: {}", e))
             })?;
 
+        Ok(sent.tx_hash)
+    }
+
+    /// Record the investment on the InvoicePool contract, which pays returns to
+    /// `payout_wallet`
+    async fn record_investment_on_pool_contract(
+        &self,
+        pool: &FundingPool,
+        amount: Decimal,
+        payout_wallet: &str,
+    ) -> AppResult<()> {
         // Record on Smart Contract
         // Get Token ID
         let nft = self
@@ -336,7 +1674,7 @@ impl FundingService {
         // Call contract - with self-healing for missing pools
         let contract_result = self
             .blockchain_service
-            .record_investment_on_chain(token_id, &verified_transfer.from, amount)
+            .record_investment_on_chain(token_id, payout_wallet, amount)
             .await;
 
         let _contract_tx = match contract_result {
@@ -357,120 +1695,192 @@ impl FundingService {
                 tracing::info!("Self-healing successful, retrying investment record...");
                 // Retry recording investment
                 self.blockchain_service
-                    .record_investment_on_chain(token_id, &verified_transfer.from, amount)
+                    .record_investment_on_chain(token_id, payout_wallet, amount)
                     .await?
             },
             Err(e) => return Err(e),
         };
 
-        // Calculate expected return
+
+        Ok(())
+    }
+
+    /// Off-chain mode: pay for the investment from the investor's IDRX balance.
+    /// The debit rolls back with `conn` if the investment cannot be recorded.
+    async fn record_off_chain_investment(
+        &self,
+        conn: &mut PgConnection,
+        investor_id: Uuid,
+        req: &InvestRequest,
+        amount: Decimal,
+        expected_return: Decimal,
+    ) -> AppResult<Investment> {
+        self.tx_repo
+            .debit_balance_tx(
+                &mut *conn,
+                investor_id,
+                "funding",
+                amount,
+                Some(req.pool_id),
+                Some("funding_pool"),
+                Some(&format!("Off-chain IDRX investment in pool {}", req.pool_id)),
+            )
+            .await?
+            .ok_or(AppError::InsufficientBalance)?;
+
+        self.funding_repo
+            .create_investment_tx(
+                conn,
+                req.pool_id,
+                investor_id,
+                amount,
+                expected_return,
+                &req.tranche,
+                None,
+                "active",
+            )
+            .await
+    }
+
+    /// Receipt for one of the investor's investments
+    pub async fn get_investment_receipt(
+        &self,
+        investor_id: Uuid,
+        investment_id: Uuid,
+    ) -> AppResult<InvestmentReceipt> {
+        let investment = self
+            .funding_repo
+            .find_investment_by_id(investment_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Investment not found".to_string()))?;
+
+        if investment.investor_id != investor_id {
+            return Err(AppError::Forbidden("Not your investment".to_string()));
+        }
+
+        self.build_investment_receipt(investment).await
+    }
+
+    /// The investor's own investment with its pool, invoice and current return
+    pub async fn get_investment_detail(
+        &self,
+        investor_id: Uuid,
+        investment_id: Uuid,
+    ) -> AppResult<InvestmentDetail> {
+        let investment = self
+            .funding_repo
+            .find_investment_by_id(investment_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Investment not found".to_string()))?;
+
+        if investment.investor_id != investor_id {
+            return Err(AppError::Forbidden("Not your investment".to_string()));
+        }
+
+        let pool = self
+            .funding_repo
+            .find_by_id(investment.pool_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Pool not found".to_string()))?;
+        let invoice = self
+            .invoice_repo
+            .find_by_id(pool.invoice_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Invoice not found".to_string()))?;
+
+        let interest_rate = if investment.tranche == "catalyst" {
+            pool.catalyst_interest_rate
+        } else {
+            pool.priority_interest_rate
+        };
+        let expected_gain = investment.expected_return - investment.amount;
+        let today = Utc::now().date_naive();
+        let current_return = match investment.actual_return {
+            Some(actual) => actual - investment.amount,
+            None if investment.status == "defaulted" => Decimal::ZERO,
+            None => {
+                let accrued = investment.amount * interest_rate / Decimal::from(100)
+                    * Decimal::from(accrual_days(&invoice, today))
+                    / Decimal::from(365);
+                accrued.min(expected_gain).round_dp(2)
+            }
+        };
+
+        let explorer_url = |hash: &Option<String>| {
+            hash.as_deref()
+                .map(|h| self.blockchain_service.get_explorer_url(h))
+        };
+
+        Ok(InvestmentDetail {
+            interest_rate,
+            current_return,
+            expected_gain,
+            days_remaining: (invoice.due_date - today).num_days(),
+            tx_explorer_url: explorer_url(&investment.tx_hash),
+            return_tx_explorer_url: explorer_url(&investment.return_tx_hash),
+            investment,
+            pool,
+            invoice,
+        })
+    }
+
+    /// Combine the investment row with its pool, invoice and recorded on-chain transfer
+    pub async fn build_investment_receipt(
+        &self,
+        investment: Investment,
+    ) -> AppResult<InvestmentReceipt> {
+        let pool = self
+            .funding_repo
+            .find_by_id(investment.pool_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Pool not found".to_string()))?;
+
         let invoice = self
             .invoice_repo
             .find_by_id(pool.invoice_id)
             .await?
             .ok_or_else(|| AppError::NotFound("Invoice not found".to_string()))?;
 
-        let days_until_due = (invoice.due_date - chrono::Utc::now().date_naive()).num_days();
-        let annual_rate = interest_rate.to_f64().unwrap_or(10.0) / 100.0;
-        let daily_rate = annual_rate / 365.0;
-        let interest = amount.to_f64().unwrap_or(0.0) * daily_rate * days_until_due as f64;
-        let expected_return = Decimal::from_f64(amount.to_f64().unwrap_or(0.0) + interest).unwrap();
-
-        // Record the on-chain transaction in database for audit trail
-        self.tx_repo
-            .create_blockchain_transaction(
-                investor_id,
-                "investment",
-                amount,
-                &req.tx_hash,
-                verified_transfer.block_number as i64,
-                Some(pool.invoice_id),
-                Some(&format!("On-chain IDRX investment in pool {}", req.pool_id)),
-                &verified_transfer.explorer_url,
-            )
-            .await?;
-
-        // Create investment record with verified tx_hash
-        let investment = self
-            .funding_repo
-            .create_investment(
-                req.pool_id,
-                investor_id,
-                amount,
-                expected_return,
-                &req.tranche,
-                &req.tx_hash,
-            )
-            .await?;
-
-        // Update pool funded amounts
-        let new_funded = pool.funded_amount + amount;
-        let (new_priority_funded, new_catalyst_funded) = if is_catalyst {
-            (pool.priority_funded, pool.catalyst_funded + amount)
+        let interest_rate = if investment.tranche == "catalyst" {
+            pool.catalyst_interest_rate
         } else {
-            (pool.priority_funded + amount, pool.catalyst_funded)
+            pool.priority_interest_rate
         };
 
-        let investor_count = self
-            .funding_repo
-            .count_investors_in_pool(req.pool_id)
-            .await? as i32;
-
-        self.funding_repo
-            .update_funded_amount(
-                req.pool_id,
-                new_funded,
-                new_priority_funded,
-                new_catalyst_funded,
-                investor_count,
-            )
-            .await?;
-
-        // Check if pool is now fully funded
-        if new_funded >= pool.target_amount {
-            self.funding_repo.set_filled(req.pool_id).await?;
-            self.invoice_repo
-                .update_status(pool.invoice_id, "funded")
-                .await?;
-
-            // Notify exporter
-            if let Some(exporter) = self.user_repo.find_by_id(invoice.exporter_id).await? {
-                if let Some(email) = &exporter.email {
-                    let _ = self
-                        .email_service
-                        .send_pool_funded_notification(
-                            email,
-                            &invoice.invoice_number,
-                            pool.target_amount.to_f64().unwrap_or(0.0),
-                        )
-                        .await;
-                }
-            }
-        }
-
-        // Send confirmation email with on-chain tx details
-        if let Some(email) = &investor.email {
-            let _ = self
-                .email_service
-                .send_investment_confirmation(
-                    email,
-                    &invoice.invoice_number,
-                    amount.to_f64().unwrap_or(0.0),
-                    &req.tranche,
-                    expected_return.to_f64().unwrap_or(0.0),
-                )
-                .await;
-        }
+        let transaction = match &investment.tx_hash {
+            Some(hash) => self.tx_repo.find_by_tx_hash(hash).await?,
+            None => None,
+        };
 
-        tracing::info!(
-            "Investment recorded: {} IDRX in pool {} by investor {} - viewable at {}",
-            amount,
-            req.pool_id,
-            investor_id,
-            verified_transfer.explorer_url
-        );
+        let explorer_url = transaction
+            .as_ref()
+            .and_then(|tx| tx.explorer_url.clone())
+            .or_else(|| {
+                investment
+                    .tx_hash
+                    .as_ref()
+                    .map(|hash| self.blockchain_service.get_explorer_url(hash))
+            });
 
-        Ok(investment)
+        Ok(InvestmentReceipt {
+            investment_id: investment.id,
+            pool_id: pool.id,
+            invoice_id: invoice.id,
+            invoice_number: invoice.invoice_number,
+            tranche: investment.tranche,
+            amount: investment.amount,
+            interest_rate,
+            expected_return: investment.expected_return,
+            maturity_date: invoice.due_date,
+            investment_status: investment.status,
+            explorer_url,
+            block_number: transaction.as_ref().and_then(|tx| tx.block_number),
+            confirmation_status: transaction
+                .map(|tx| tx.status)
+                .unwrap_or_else(|| "pending".to_string()),
+            tx_hash: investment.tx_hash,
+            invested_at: investment.invested_at,
+        })
     }
 
     pub async fn get_investor_portfolio(&self, investor_id: Uuid) -> AppResult<InvestorPortfolio> {
@@ -498,15 +1908,109 @@ impl FundingService {
         })
     }
 
+    /// Build a CSV of all the investor's investments and returns (header-only when empty)
+    pub async fn export_portfolio_csv(&self, investor_id: Uuid) -> AppResult<String> {
+        let rows = self
+            .funding_repo
+            .find_portfolio_export_rows(investor_id)
+            .await?;
+
+        let explorer_url = |hash: &Option<String>| {
+            hash.as_deref()
+                .map(|h| self.blockchain_service.get_explorer_url(h))
+                .unwrap_or_default()
+        };
+
+        let mut csv = String::from(
+            "invoice_number,tranche,principal,expected_return,actual_return,invested_at,repaid_at,status,tx_explorer_url,return_tx_explorer_url\n",
+        );
+        for row in &rows {
+            let fields = [
+                row.invoice_number.clone(),
+                row.tranche.clone(),
+                row.principal.to_string(),
+                row.expected_return.to_string(),
+                row.actual_return.map(|r| r.to_string()).unwrap_or_default(),
+                row.invested_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+                row.repaid_at
+                    .map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string())
+                    .unwrap_or_default(),
+                row.status.clone(),
+                explorer_url(&row.tx_hash),
+                explorer_url(&row.return_tx_hash),
+            ];
+            let line: Vec<String> = fields.iter().map(|f| escape_csv_field(f)).collect();
+            csv.push_str(&line.join(","));
+            csv.push('\n');
+        }
+
+        Ok(csv)
+    }
+
+    /// Realized returns of investments repaid during `year`, for tax reporting
+    pub async fn get_tax_summary(&self, investor_id: Uuid, year: i32) -> AppResult<TaxSummary> {
+        let current_year = Utc::now().year();
+        if !(2000..=current_year).contains(&year) {
+            return Err(AppError::ValidationError(format!(
+                "Year must be between 2000 and {}",
+                current_year
+            )));
+        }
+
+        let investments = self
+            .funding_repo
+            .find_repaid_in_year(investor_id, year)
+            .await?;
+
+        Ok(TaxSummary {
+            year,
+            investment_count: investments.len(),
+            total_principal: investments.iter().map(|i| i.principal).sum(),
+            total_realized_return: investments.iter().map(|i| i.realized_return).sum(),
+            total_gain: investments.iter().map(|i| i.gain).sum(),
+            investments,
+        })
+    }
+
+    /// The tax summary as CSV: one row per repaid investment plus a total row
+    pub async fn export_tax_summary_csv(&self, investor_id: Uuid, year: i32) -> AppResult<String> {
+        let summary = self.get_tax_summary(investor_id, year).await?;
+
+        let mut csv = String::from(
+            "invoice_number,tranche,principal,realized_return,gain,invested_at,repaid_at\n",
+        );
+        for item in &summary.investments {
+            let fields = [
+                item.invoice_number.clone(),
+                item.tranche.clone(),
+                item.principal.to_string(),
+                item.realized_return.to_string(),
+                item.gain.to_string(),
+                item.invested_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+                item.repaid_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            ];
+            let line: Vec<String> = fields.iter().map(|f| escape_csv_field(f)).collect();
+            csv.push_str(&line.join(","));
+            csv.push('\n');
+        }
+        csv.push_str(&format!(
+            "TOTAL,,{},{},{},,\n",
+            summary.total_principal, summary.total_realized_return, summary.total_gain
+        ));
+
+        Ok(csv)
+    }
+
     pub async fn get_investor_investments(
         &self,
         investor_id: Uuid,
         page: i32,
         per_page: i32,
+        cursor: Option<Cursor>,
     ) -> AppResult<(Vec<crate::models::InvestorActiveInvestment>, i64)> {
         let (investments, total) = self
             .funding_repo
-            .find_investments_by_investor(investor_id, page, per_page)
+            .find_investments_by_investor(investor_id, page, per_page, cursor)
             .await?;
 
         let mut enriched_investments = Vec::new();
@@ -532,6 +2036,7 @@ impl FundingService {
                 ("Disbursed", "blue")
             } else {
                 match inv.status.as_str() {
+                    "pending" => ("Pending", "yellow"),
                     "active" => ("Active", "green"),
                     "repaid" => ("Repaid", "blue"),
                     "defaulted" => ("Defaulted", "red"),
@@ -562,6 +2067,7 @@ impl FundingService {
                 total_expected: inv.expected_return.to_f64().unwrap_or(0.0),
                 due_date: invoice.due_date.and_hms_opt(0, 0, 0).unwrap(),
                 days_remaining: days_remaining as i32,
+                is_overdue: inv.status == "active" && invoice.is_overdue(Utc::now().date_naive()),
                 status: inv.status.clone(),
                 status_display: status_display.to_string(),
                 status_color: status_color.to_string(),
@@ -572,19 +2078,101 @@ impl FundingService {
         Ok((enriched_investments, total))
     }
 
+    /// Mark funded and disbursed invoices that reached their due date as
+    /// `matured` and tell each mitra that repayment is due. Matured invoices
+    /// still unpaid after the grace period are reported as default-eligible.
+    pub async fn mark_matured_invoices(&self) -> AppResult<usize> {
+        let today = Utc::now().date_naive();
+        let grace_days = self.config.invoice_default_grace_days;
+
+        let matured = self.invoice_repo.mark_matured(today).await?;
+        for invoice in &matured {
+            let payload = serde_json::json!({
+                "invoice_id": invoice.id,
+                "invoice_number": invoice.invoice_number,
+                "due_date": invoice.due_date,
+            });
+            self.notification_service
+                .notify(invoice.exporter_id, NotificationKind::RepaymentDue, payload)
+                .await;
+
+            // The status change is already committed, so a lookup failure only skips the email
+            let exporter = match self.user_repo.find_by_id(invoice.exporter_id).await {
+                Ok(exporter) => exporter,
+                Err(e) => {
+                    tracing::error!(
+                        "Failed to load mitra of matured invoice {}: {}",
+                        invoice.id,
+                        e
+                    );
+                    continue;
+                }
+            };
+            if let Some(exporter) = exporter {
+                if let Some(email) = &exporter.email {
+                    if let Err(e) = self
+                        .email_service
+                        .send_repayment_due(
+                            email,
+                            &invoice.invoice_number,
+                            &invoice.due_date.format("%Y-%m-%d").to_string(),
+                            grace_days,
+                        )
+                        .await
+                    {
+                        tracing::error!("Failed to send repayment due for {}: {}", invoice.id, e);
+                    }
+                }
+            }
+        }
+        if !matured.is_empty() {
+            tracing::info!("Marked {} invoices matured", matured.len());
+        }
+
+        match self.default_eligible_invoices().await {
+            Ok(eligible) if !eligible.is_empty() => tracing::warn!(
+                "{} matured invoices are more than {} days past due and eligible for default",
+                eligible.len(),
+                grace_days
+            ),
+            Ok(_) => {}
+            Err(e) => tracing::error!("Failed to count default-eligible invoices: {}", e),
+        }
+
+        Ok(matured.len())
+    }
+
+    /// Matured invoices more than `invoice_default_grace_days` past due
+    pub async fn default_eligible_invoices(&self) -> AppResult<Vec<Invoice>> {
+        let cutoff =
+            Utc::now().date_naive() - Duration::days(self.config.invoice_default_grace_days);
+        self.invoice_repo.find_matured_due_before(cutoff).await
+    }
+
     pub async fn get_mitra_dashboard(&self, mitra_id: Uuid) -> AppResult<MitraDashboard> {
         let (invoices, _) = self
             .invoice_repo
-            .find_by_exporter(mitra_id, None, 1, 100)
+            .find_by_exporter(mitra_id, None, false, 1, 100, None)
             .await?;
 
+        let invoice_ids: Vec<Uuid> = invoices.iter().map(|i| i.id).collect();
+        let pools: HashMap<Uuid, FundingPool> = self
+            .funding_repo
+            .find_by_invoices(&invoice_ids)
+            .await?
+            .into_iter()
+            .map(|pool| (pool.invoice_id, pool))
+            .collect();
+
         let mut total_financing = 0.0;
         let mut total_owed = 0.0;
         let mut total_days = 0;
         let mut active_invoices = Vec::new();
 
         for invoice in invoices.iter() {
-            if invoice.status == "funded" || invoice.status == "funding" {
+            if invoice.status == "funding"
+                || REPAYMENT_OUTSTANDING_STATUSES.contains(&invoice.status.as_str())
+            {
                 let amount = invoice.amount.to_f64().unwrap_or(0.0);
                 total_financing += amount;
 
@@ -594,12 +2182,23 @@ impl FundingService {
                     .unwrap_or(Decimal::from(10))
                     .to_f64()
                     .unwrap_or(10.0);
-                let days_until_due =
-                    (invoice.due_date - chrono::Utc::now().date_naive()).num_days();
-                let interest = amount * (interest_rate / 100.0) * (days_until_due as f64 / 365.0);
+                let today = Utc::now().date_naive();
+                let days_until_due = (invoice.due_date - today).num_days();
+
+                // Settled pools stop accruing on the day they were repaid
+                let as_of = match pools.get(&invoice.id) {
+                    Some(pool) if pool.status == "closed" => {
+                        pool.closed_at.map(|at| at.date()).unwrap_or(today)
+                    }
+                    _ => today,
+                };
+                let interest = amount
+                    * (interest_rate / 100.0)
+                    * (accrual_days(invoice, as_of) as f64 / 365.0);
                 let owed = amount + interest;
                 total_owed += owed;
-                total_days += days_until_due as i32;
+                // Overdue invoices still count as one day so the average tenor stays positive
+                total_days += days_until_due.max(1) as i32;
 
                 active_invoices.push(InvoiceDashboard {
                     invoice_id: invoice.id,
@@ -618,6 +2217,7 @@ impl FundingService {
                     }
                     .to_string(),
                     days_remaining: days_until_due as i32,
+                    is_overdue: invoice.is_overdue(today),
                     funded_amount: amount, // Simplified
                     total_owed: owed,
                 });
@@ -645,49 +2245,455 @@ impl FundingService {
     }
 
     pub async fn disburse_pool(&self, pool_id: Uuid) -> AppResult<FundingPool> {
+        self.disburse_pool_with_mode(pool_id, DisbursementMode::Wallet, false)
+            .await
+    }
+
+    /// Disbursement requested by the mitra that owns a filled pool, to its wallet or bank account
+    pub async fn request_exporter_disbursement(
+        &self,
+        exporter_id: Uuid,
+        pool_id: Uuid,
+        mode: DisbursementMode,
+    ) -> AppResult<FundingPool> {
         let pool = self
             .funding_repo
             .find_by_id(pool_id)
             .await?
             .ok_or_else(|| AppError::NotFound("Pool not found".to_string()))?;
+        let invoice = self
+            .invoice_repo
+            .find_by_id(pool.invoice_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Invoice not found".to_string()))?;
 
-        // Allow disbursement if Filled OR Closed (manually closed early)
-        // Check if pool has funds
+        if invoice.exporter_id != exporter_id {
+            return Err(AppError::Forbidden(
+                "You can only request disbursement of your own pools".to_string(),
+            ));
+        }
+
+        self.disburse_pool_with_mode(pool_id, mode, true).await
+    }
+
+    /// Lock the pool and stop it taking investments before its amounts are read
+    /// for disbursement. An open pool is moved to `filled`, which waits for any
+    /// pending investment to settle. With `require_eligible` the mitra's
+    /// disbursement threshold is checked on the locked row. Also returns whether
+    /// the pool was open, so it can be reopened if the disbursement is refused.
+    async fn claim_pool_for_disbursement(
+        &self,
+        pool_id: Uuid,
+        require_eligible: bool,
+    ) -> AppResult<(FundingPool, bool)> {
+        let mut tx = self.funding_repo.begin().await?;
+        let pool = self.funding_repo.lock_pool_tx(&mut tx, pool_id).await?;
+
+        if require_eligible {
+            self.check_disbursement_eligible(&pool)?;
+        }
         if pool.funded_amount <= Decimal::ZERO {
-             return Err(AppError::BadRequest(
+            return Err(AppError::BadRequest(
                 "Pool has no funds to disburse".to_string(),
             ));
         }
 
+        let was_open = pool.status == "open";
+        let pool = match pool.status.as_str() {
+            "open" => self
+                .funding_repo
+                .close_for_disbursement_tx(&mut tx, pool_id)
+                .await?
+                .ok_or_else(|| {
+                    AppError::Conflict(
+                        "Pool has investments still being confirmed; try again shortly".to_string(),
+                    )
+                })?,
+            "filled" | "closed" => pool,
+            status => {
+                return Err(AppError::BadRequest(format!(
+                    "Pool cannot be disbursed while {}",
+                    status
+                )))
+            }
+        };
+
+        tx.commit().await?;
+        Ok((pool, was_open))
+    }
+
+    /// Whether the pool's mitra may request disbursement: the pool is filled, or
+    /// still open with at least `min_disbursement_ratio` of its target raised
+    fn check_disbursement_eligible(&self, pool: &FundingPool) -> AppResult<()> {
+        match pool.status.as_str() {
+            "filled" => Ok(()),
+            "open" => {
+                let ratio =
+                    Decimal::from_f64(self.config.min_disbursement_ratio).unwrap_or(Decimal::ONE);
+                let threshold = (pool.target_amount * ratio).round_dp(2);
+                if pool.funded_amount > Decimal::ZERO && pool.funded_amount >= threshold {
+                    Ok(())
+                } else {
+                    Err(AppError::BadRequest(format!(
+                        "Pool has raised {} of its {} IDRX target; at least {} is required before disbursement",
+                        pool.funded_amount, pool.target_amount, threshold
+                    )))
+                }
+            }
+            status => Err(AppError::BadRequest(format!(
+                "Pool cannot be disbursed while {}",
+                status
+            ))),
+        }
+    }
+
+    /// Funding progress of one of the exporter's invoices: pool and tranche
+    /// progress, investors, time left and whether disbursement can be requested
+    pub async fn get_invoice_funding_status(
+        &self,
+        exporter_id: Uuid,
+        invoice_id: Uuid,
+    ) -> AppResult<InvoiceFundingStatus> {
         let invoice = self
             .invoice_repo
-            .find_by_id(pool.invoice_id)
+            .find_by_id(invoice_id)
             .await?
             .ok_or_else(|| AppError::NotFound("Invoice not found".to_string()))?;
+        if invoice.exporter_id != exporter_id {
+            return Err(AppError::Forbidden("Not your invoice".to_string()));
+        }
 
-        // 1. Trigger Smart Contract Disbursement (Contract Transfers Tokens)
-        let nft = self
+        let pool = self
+            .funding_repo
+            .find_by_invoice(invoice_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Invoice has no funding pool".to_string()))?;
+        let seconds_remaining = pool
+            .deadline
+            .map(|deadline| (deadline - Utc::now().naive_utc()).num_seconds().max(0));
+
+        Ok(InvoiceFundingStatus {
+            invoice_id: invoice.id,
+            invoice_status: invoice.status,
+            investor_count: pool.investor_count,
+            seconds_remaining,
+            disbursement_eligible: self.check_disbursement_eligible(&pool).is_ok(),
+            pool: self.build_pool_response(pool, None)?,
+        })
+    }
+
+    /// Compare the pool's on-chain investments in the DB with the InvoicePool
+    /// contract's `InvestmentRecorded` events. Totals are matched per wallet:
+    /// the contract records the investor's payout wallet at investment time,
+    /// which is stored on the investment. Investments from before that column
+    /// fall back to the investor's current payout wallet.
+    pub async fn audit_pool_onchain(&self, pool_id: Uuid) -> AppResult<PoolOnChainAudit> {
+        let pool = self
+            .funding_repo
+            .find_by_id(pool_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Pool not found".to_string()))?;
+        let token_id = self
             .invoice_repo
             .find_nft_by_invoice(pool.invoice_id)
             .await?
-            .ok_or_else(|| AppError::NotFound("NFT record not found".to_string()))?;
+            .and_then(|nft| nft.token_id)
+            .ok_or_else(|| {
+                AppError::BadRequest("Invoice has no minted NFT to audit against".to_string())
+            })?;
 
-        let token_id = nft.token_id.ok_or_else(|| {
-            AppError::InternalError("Token ID missing from NFT record".to_string())
-        })?;
+        let events = self
+            .blockchain_service
+            .get_investment_events(token_id)
+            .await?;
+        let investments: Vec<Investment> = self
+            .funding_repo
+            .find_investments_by_pool(pool_id)
+            .await?
+            .into_iter()
+            .filter(|i| i.tx_hash.is_some())
+            .collect();
+
+        let mut tallies: BTreeMap<String, WalletTally> = BTreeMap::new();
+        let mut wallets: HashMap<Uuid, String> = HashMap::new();
+        for investment in &investments {
+            let stored = investment.payout_wallet.as_deref().map(str::to_lowercase);
+            let wallet = match (stored, wallets.get(&investment.investor_id)) {
+                (Some(wallet), _) => wallet,
+                (None, Some(wallet)) => wallet.clone(),
+                (None, None) => {
+                    let wallet = self
+                        .user_repo
+                        .find_by_id(investment.investor_id)
+                        .await?
+                        .and_then(|u| u.payout_wallet().map(str::to_lowercase))
+                        .unwrap_or_default();
+                    wallets.insert(investment.investor_id, wallet.clone());
+                    wallet
+                }
+            };
+            let tally = tallies.entry(wallet).or_default();
+            tally.investor_id = Some(investment.investor_id);
+            tally.db_amount += investment.amount;
+            tally.db_count += 1;
+        }
+        for event in &events {
+            let tally = tallies.entry(format!("{:?}", event.investor)).or_default();
+            tally.onchain_amount += event.amount;
+            tally.onchain_count += 1;
+        }
 
-        tracing::info!("Disbursing pool {} (Token ID: {})", pool.id, token_id);
+        let discrepancies: Vec<InvestmentDiscrepancy> = tallies
+            .into_iter()
+            .filter(|(_, t)| t.db_amount != t.onchain_amount || t.db_count != t.onchain_count)
+            .map(|(investor_wallet, t)| InvestmentDiscrepancy {
+                investor_wallet,
+                investor_id: t.investor_id,
+                db_amount: t.db_amount.to_f64().unwrap_or(0.0),
+                onchain_amount: t.onchain_amount.to_f64().unwrap_or(0.0),
+                db_count: t.db_count,
+                onchain_count: t.onchain_count,
+            })
+            .collect();
+
+        Ok(PoolOnChainAudit {
+            pool_id,
+            token_id,
+            db_investment_count: investments.len(),
+            onchain_investment_count: events.len(),
+            db_total: investments
+                .iter()
+                .map(|i| i.amount)
+                .sum::<Decimal>()
+                .to_f64()
+                .unwrap_or(0.0),
+            onchain_total: events
+                .iter()
+                .map(|e| e.amount)
+                .sum::<Decimal>()
+                .to_f64()
+                .unwrap_or(0.0),
+            is_consistent: discrepancies.is_empty(),
+            discrepancies,
+            audited_at: Utc::now(),
+        })
+    }
 
-        let _tx_hash = self
-            .blockchain_service
-            .record_disbursement_on_chain(token_id)
+    /// Audit every pool that received an on-chain investment within `lookback`.
+    /// Discrepancies and failed audits are logged; returns the inconsistent pools.
+    pub async fn audit_recent_pools_onchain(&self, lookback: Duration) -> AppResult<Vec<Uuid>> {
+        let since = (Utc::now() - lookback).naive_utc();
+        let pools = self
+            .funding_repo
+            .find_pools_with_onchain_investments_since(since)
+            .await?;
+
+        let mut inconsistent = Vec::new();
+        for pool in pools {
+            match self.audit_pool_onchain(pool.id).await {
+                Ok(audit) if !audit.is_consistent => {
+                    tracing::warn!(
+                        "On-chain audit of pool {}: {} investor(s) differ ({} DB vs {} on-chain investments)",
+                        pool.id,
+                        audit.discrepancies.len(),
+                        audit.db_investment_count,
+                        audit.onchain_investment_count
+                    );
+                    inconsistent.push(pool.id);
+                }
+                Ok(_) => {}
+                Err(e) => tracing::error!("On-chain audit of pool {} failed: {}", pool.id, e),
+            }
+        }
+
+        Ok(inconsistent)
+    }
+
+    /// Amount and gas of disbursing the pool to the mitra's wallet, without sending anything
+    pub async fn get_disbursement_preview(&self, pool_id: Uuid) -> AppResult<DisbursementPreview> {
+        let pool = self
+            .funding_repo
+            .find_by_id(pool_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Pool not found".to_string()))?;
+        let invoice = self
+            .invoice_repo
+            .find_by_id(pool.invoice_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Invoice not found".to_string()))?;
+
+        let platform_fee = self.platform_fee(pool.funded_amount);
+        let net_disbursement = pool.funded_amount - platform_fee;
+        let recipient = invoice
+            .exporter_wallet_address
+            .filter(|wallet| !wallet.is_empty());
+
+        // The same checks disbursement makes, reported instead of failing the preview
+        let mut blockers = Vec::new();
+        if let Err(AppError::BadRequest(msg)) = self.require_on_chain_settlement() {
+            blockers.push(msg);
+        }
+        if !["open", "filled", "closed"].contains(&pool.status.as_str()) {
+            blockers.push(format!("Pool cannot be disbursed while {}", pool.status));
+        }
+        if pool.funded_amount <= Decimal::ZERO {
+            blockers.push("Pool has no funds to disburse".to_string());
+        }
+
+        let gas = match &recipient {
+            Some(wallet) => Some(
+                self.blockchain_service
+                    .check_transfer_gas(wallet, net_disbursement)
+                    .await?,
+            ),
+            None => {
+                blockers.push("Mitra has no wallet address to disburse to".to_string());
+                None
+            }
+        };
+        if matches!(&gas, Some(gas) if !gas.is_sufficient()) {
+            blockers.push("Platform wallet cannot pay the transfer gas".to_string());
+        }
+
+        Ok(DisbursementPreview {
+            pool_id: pool.id,
+            pool_status: pool.status,
+            recipient_wallet: recipient,
+            funded_amount: pool.funded_amount.to_f64().unwrap_or(0.0),
+            platform_fee: platform_fee.to_f64().unwrap_or(0.0),
+            idrx_amount: net_disbursement.to_f64().unwrap_or(0.0),
+            estimated_gas: gas.as_ref().map(|gas| gas.gas_limit.low_u64()),
+            gas_price_wei: gas.as_ref().map(|gas| gas.gas_price.to_string()),
+            estimated_gas_cost_wei: gas.as_ref().map(|gas| gas.cost().to_string()),
+            platform_native_balance_wei: gas.as_ref().map(|gas| gas.platform_balance.to_string()),
+            sufficient_native_balance: gas.as_ref().is_some_and(|gas| gas.is_sufficient()),
+            blockers,
+        })
+    }
+
+    async fn disburse_pool_with_mode(
+        &self,
+        pool_id: Uuid,
+        mode: DisbursementMode,
+        require_eligible: bool,
+    ) -> AppResult<FundingPool> {
+        self.require_on_chain_settlement()?;
+
+        let invoice_id = self
+            .funding_repo
+            .find_by_id(pool_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Pool not found".to_string()))?
+            .invoice_id;
+        let invoice = self
+            .invoice_repo
+            .find_by_id(invoice_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Invoice not found".to_string()))?;
+
+        let bank_account = match mode {
+            DisbursementMode::Wallet => None,
+            DisbursementMode::Bank => Some(
+                self.user_repo
+                    .find_primary_verified_bank_account(invoice.exporter_id)
+                    .await?
+                    .ok_or_else(|| {
+                        AppError::BadRequest(
+                            "A verified primary bank account is required for bank disbursement"
+                                .to_string(),
+                        )
+                    })?,
+            ),
+        };
+
+        // Bank disbursements are paid on-chain to the off-ramp wallet, which
+        // pays the mitra's bank account in fiat
+        let recipient = match &bank_account {
+            None => invoice
+                .exporter_wallet_address
+                .clone()
+                .filter(|wallet| !wallet.is_empty())
+                .ok_or_else(|| {
+                    AppError::BadRequest("Mitra has no wallet address to disburse to".to_string())
+                })?,
+            Some(_) if self.config.offramp_wallet_address.is_empty() => {
+                return Err(AppError::BadRequest(
+                    "Bank disbursement is not available on this deployment".to_string(),
+                ));
+            }
+            Some(_) => self.config.offramp_wallet_address.clone(),
+        };
+
+        // Allow disbursement if Filled OR Closed (manually closed early). Amounts
+        // are only read once the pool can no longer take investments.
+        let (pool, was_open) = self
+            .claim_pool_for_disbursement(pool_id, require_eligible)
+            .await?;
+
+        // The platform fee is withheld and the rest goes to the mitra
+        let platform_fee = self.platform_fee(pool.funded_amount);
+        let net_disbursement = pool.funded_amount - platform_fee;
+
+        // 0. Escrow must actually hold the pool's funds on-chain, not just in the DB,
+        // and the platform wallet must afford the gas. Nothing has been sent yet, so
+        // a pool closed early for this disbursement goes back to taking investments.
+        let checks = async {
+            self.verify_pool_escrow_backing(&pool).await?;
+            self.blockchain_service
+                .ensure_platform_gas(&recipient, net_disbursement)
+                .await
+        };
+        if let Err(e) = checks.await {
+            if was_open {
+                if let Err(reopen_err) = self
+                    .funding_repo
+                    .reopen_after_refused_disbursement(pool.id)
+                    .await
+                {
+                    tracing::error!(
+                        "Failed to reopen pool {} after its disbursement was refused: {}",
+                        pool.id,
+                        reopen_err
+                    );
+                }
+            }
+            return Err(e);
+        }
+
+        // 1. Record the disbursement on-chain in either mode, so the contract's
+        // pool moves to Disbursed and can later take the repayment
+        let tx_hash = self
+            .disburse_on_chain(
+                &pool,
+                &invoice,
+                net_disbursement,
+                platform_fee,
+                bank_account.as_ref(),
+            )
             .await?;
 
-        // 2. Update status to disbursed
+        // 2. Update status to disbursed. The advance is what was actually raised
+        // less the fee, which is below the invoice's target after an early disbursement.
         let pool = self.funding_repo.set_disbursed(pool.id).await?;
         self.invoice_repo
             .update_status(pool.invoice_id, "disbursed")
             .await?;
+        self.invoice_repo
+            .set_advance_amount(pool.invoice_id, net_disbursement)
+            .await?;
+
+        self.notification_service.send_webhook(
+            WebhookEvent::PoolDisbursed,
+            serde_json::json!({
+                "pool_id": pool.id,
+                "invoice_id": pool.invoice_id,
+                "invoice_number": invoice.invoice_number,
+                "funded_amount": pool.funded_amount,
+                "net_disbursement": net_disbursement,
+                "platform_fee": platform_fee,
+                "tx_hash": tx_hash,
+            }),
+        );
 
         // 3. Calculate Repayment Amount (Funded Amount + Interest)
         // Formula: Principal + (Principal * Rate * Days / 365)
@@ -710,12 +2716,27 @@ impl FundingService {
             pool.id, principal, interest, repayment_amount
         );
 
+        self.notification_service
+            .notify(
+                invoice.exporter_id,
+                NotificationKind::PoolDisbursed,
+                serde_json::json!({
+                    "pool_id": pool.id,
+                    "invoice_id": pool.invoice_id,
+                    "invoice_number": invoice.invoice_number,
+                    "net_disbursement": net_disbursement,
+                    "repayment_amount": repayment_amount,
+                    "due_date": due_date,
+                }),
+            )
+            .await;
+
         // 4. Generate/Prepare Repayment QR (Send to Exporter)
         if let Some(exporter) = self.user_repo.find_by_id(invoice.exporter_id).await? {
             if let Some(email) = &exporter.email {
                 // Generate QR Code URL pointing to Contract Address (more trustless)
-                let contract_address = self.config.invoice_pool_contract_addr.clone();
-                let qr_data = format!("ethereum:{}?value={}&token={}", contract_address, repayment_amount, self.config.idrx_token_contract_addr);
+                let contract_address = self.blockchain_service.pool_contract_addr();
+                let qr_data = format!("ethereum:{}?value={}&token={}", contract_address, repayment_amount, self.blockchain_service.active_idrx_token().contract_addr);
                 let qr_url = format!("https://api.qrserver.com/v1/create-qr-code/?size=300x300&data={}", urlencoding::encode(&qr_data));
 
                 let _ = self
@@ -723,47 +2744,380 @@ impl FundingService {
                     .send_disbursement_notification(
                         email,
                         &invoice.invoice_number,
-                        pool.funded_amount.to_f64().unwrap_or(0.0),
+                        net_disbursement.to_f64().unwrap_or(0.0),
                     )
                     .await;
                 
                 // Send separate "Repayment Instructions" email
-                 let _ = self.email_service.send_email(
-                    email,
-                    "Funding Disbursed - Repayment Instructions",
-                    &format!(
-                        "<html>
-                            <body>
-                                <h1>Funding Disbursed!</h1>
-                                <p>Your invoice #{} has been funded with {} IDRX.</p>
-                                <p>The funds have been sent to your wallet.</p>
-                                <hr/>
-                                <h2>Repayment Instructions</h2>
-                                <p>Please repay the total amount before the due date: {}</p>
-                                <h3>Total Repayment: {} IDRX</h3>
-                                <p>(Principal: {} + Interest: {})</p>
-                                <br/>
-                                <img src='{}' alt='Repayment QR Code' />
-                                <p>Send IDRX directly to Contract: {}</p>
-                                <p><small>Your payment will be automatically distributed to investors.</small></p>
-                            </body>
-                        </html>",
-                        invoice.invoice_number,
-                        pool.funded_amount,
-                        due_date,
-                        repayment_amount.round_dp(2),
-                        principal,
-                        Decimal::from_f64(interest).unwrap_or(Decimal::ZERO).round_dp(2),
-                        qr_url,
-                        contract_address
+                let _ = self
+                    .email_service
+                    .send_template(
+                        email,
+                        "Funding Disbursed - Repayment Instructions",
+                        EmailTemplate::RepaymentInstructions,
+                        &serde_json::json!({
+                            "invoice_number": invoice.invoice_number,
+                            "funded_amount": pool.funded_amount.to_string(),
+                            "net_disbursement": net_disbursement.to_string(),
+                            "platform_fee": platform_fee.to_string(),
+                            "due_date": due_date.to_string(),
+                            "repayment_amount": repayment_amount.round_dp(2).to_string(),
+                            "principal": principal.to_string(),
+                            "interest": Decimal::from_f64(interest)
+                                .unwrap_or(Decimal::ZERO)
+                                .round_dp(2)
+                                .to_string(),
+                            "qr_url": qr_url,
+                            "contract_address": contract_address,
+                        }),
                     )
-                ).await;
+                    .await;
             }
         }
 
         Ok(pool)
     }
 
+    /// Trigger the contract disbursement (the contract transfers the tokens) and
+    /// record the advance, fee and gas. With a `bank_account` the contract pays
+    /// the off-ramp wallet and the advance is handed to the off-ramp.
+    /// Returns the transaction hash.
+    async fn disburse_on_chain(
+        &self,
+        pool: &FundingPool,
+        invoice: &Invoice,
+        net_disbursement: Decimal,
+        platform_fee: Decimal,
+        bank_account: Option<&BankAccount>,
+    ) -> AppResult<String> {
+        let nft = self
+            .invoice_repo
+            .find_nft_by_invoice(pool.invoice_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("NFT record not found".to_string()))?;
+
+        let token_id = nft.token_id.ok_or_else(|| {
+            AppError::InternalError("Token ID missing from NFT record".to_string())
+        })?;
+
+        tracing::info!("Disbursing pool {} (Token ID: {})", pool.id, token_id);
+
+        let recipient = bank_account.map(|_| self.config.offramp_wallet_address.as_str());
+        let sent = self
+            .blockchain_service
+            .record_disbursement_on_chain(token_id, recipient)
+            .await?;
+        let tx_hash = sent.tx_hash.clone();
+        let explorer_url = self.blockchain_service.get_explorer_url(&tx_hash);
+
+        match bank_account {
+            // Record the advance paid to the mitra (also feeds the AML velocity window)
+            None => {
+                self.tx_repo
+                    .create_blockchain_transaction(
+                        invoice.exporter_id,
+                        "advance_payment",
+                        net_disbursement,
+                        &tx_hash,
+                        sent.block_number as i64,
+                        Some(pool.invoice_id),
+                        Some(&format!("Disbursement of pool {}", pool.id)),
+                        &explorer_url,
+                    )
+                    .await?;
+            }
+            Some(account) => {
+                self.disburse_to_bank(
+                    pool,
+                    invoice,
+                    account,
+                    net_disbursement,
+                    platform_fee,
+                    &tx_hash,
+                    &explorer_url,
+                )
+                .await?;
+            }
+        }
+        self.record_platform_fee(
+            invoice.exporter_id,
+            platform_fee,
+            &tx_hash,
+            pool.invoice_id,
+            &format!("Disbursement fee for pool {}", pool.id),
+            &explorer_url,
+        )
+        .await?;
+        self.record_gas(&sent).await?;
+
+        Ok(tx_hash)
+    }
+
+    /// Record a `pending_offramp` bank disbursement of the advance the contract
+    /// paid to the off-ramp wallet in `tx_hash`, and hand it to the off-ramp.
+    /// The disbursement already happened on-chain, so if the off-ramp refuses
+    /// it the transaction is only marked failed for operations to pay out.
+    async fn disburse_to_bank(
+        &self,
+        pool: &FundingPool,
+        invoice: &Invoice,
+        account: &BankAccount,
+        net_disbursement: Decimal,
+        platform_fee: Decimal,
+        tx_hash: &str,
+        explorer_url: &str,
+    ) -> AppResult<()> {
+        tracing::info!("Disbursing pool {} to bank account {}", pool.id, account.id);
+
+        let tx = self
+            .tx_repo
+            .create_bank_disbursement(
+                invoice.exporter_id,
+                pool.invoice_id,
+                account.id,
+                net_disbursement,
+                tx_hash,
+                explorer_url,
+                &format!(
+                    "Disbursement of pool {} to {} {} ({}), platform fee {}",
+                    pool.id,
+                    account.bank_name,
+                    account.masked_account_number(),
+                    account.account_name,
+                    platform_fee
+                ),
+            )
+            .await?;
+
+        if let Err(e) = self
+            .offramp
+            .request_payout(account, net_disbursement, tx.id)
+            .await
+        {
+            tracing::error!(
+                "Off-ramp refused payout {} of pool {}: {}",
+                tx.id,
+                pool.id,
+                e
+            );
+            self.tx_repo.update_status(tx.id, "failed").await?;
+        }
+
+        Ok(())
+    }
+
+    /// Disbursement and repayment settle through the InvoicePool contract, which
+    /// never saw investments debited from off-chain balances
+    fn require_on_chain_settlement(&self) -> AppResult<()> {
+        if self.config.investment_mode != InvestmentMode::OnChain {
+            return Err(AppError::BadRequest(
+                "Disbursement and repayment are not available in off-chain investment mode"
+                    .to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Platform fee on `amount` using the same basis points as the InvoicePool contract
+    fn platform_fee(&self, amount: Decimal) -> Decimal {
+        // The contract truncates integer token units, so round toward zero
+        (amount * Decimal::from(self.config.platform_fee_bps) / Decimal::from(10_000))
+            .round_dp_with_strategy(2, RoundingStrategy::ToZero)
+    }
+
+    /// Smallest repayment that still covers `investor_total` after the platform fee,
+    /// i.e. `total - fee(total) >= investor_total`
+    fn gross_up_repayment(&self, investor_total: Decimal) -> Decimal {
+        let fee_rate = Decimal::from(self.config.platform_fee_bps) / Decimal::from(10_000);
+        let mut total_repayment = if fee_rate < Decimal::ONE {
+            (investor_total / (Decimal::ONE - fee_rate))
+                .round_dp_with_strategy(2, RoundingStrategy::AwayFromZero)
+        } else {
+            investor_total
+        };
+        while total_repayment - self.platform_fee(total_repayment) < investor_total {
+            total_repayment += Decimal::new(1, 2);
+        }
+        total_repayment
+    }
+
+    /// Attach the gas the platform wallet paid for `sent` to the transaction rows
+    /// recorded under its hash
+    async fn record_gas(&self, sent: &SentTransaction) -> AppResult<()> {
+        self.tx_repo
+            .record_gas_usage(
+                &sent.tx_hash,
+                sent.gas_used as i64,
+                sent.effective_gas_price as i64,
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn record_platform_fee(
+        &self,
+        payer_id: Uuid,
+        fee: Decimal,
+        tx_hash: &str,
+        invoice_id: Uuid,
+        description: &str,
+        explorer_url: &str,
+    ) -> AppResult<()> {
+        if fee <= Decimal::ZERO {
+            return Ok(());
+        }
+
+        self.tx_repo
+            .create_blockchain_transaction(
+                payer_id,
+                "platform_fee",
+                fee,
+                tx_hash,
+                0,
+                Some(invoice_id),
+                Some(description),
+                explorer_url,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Re-verify every investment transfer of the pool on-chain and make sure the
+    /// verified total covers the pool's funded amount before funds leave escrow
+    async fn verify_pool_escrow_backing(&self, pool: &FundingPool) -> AppResult<Decimal> {
+        let investments = self.funding_repo.find_investments_by_pool(pool.id).await?;
+
+        let mut seen_hashes = std::collections::HashSet::new();
+        let mut verified_total = Decimal::ZERO;
+
+        for investment in &investments {
+            let Some(tx_hash) = investment.tx_hash.as_deref() else {
+                tracing::warn!("Investment {} has no transfer tx_hash", investment.id);
+                continue;
+            };
+
+            // A single transfer can only back one investment
+            if !seen_hashes.insert(tx_hash.to_lowercase()) {
+                tracing::warn!(
+                    "Transfer {} is referenced by more than one investment in pool {}",
+                    tx_hash,
+                    pool.id
+                );
+                continue;
+            }
+
+            match self
+                .blockchain_service
+                .verify_investment_transfer(tx_hash, None, investment.amount)
+                .await
+            {
+                Ok(transfer) => verified_total += transfer.amount,
+                Err(e) => tracing::warn!(
+                    "Investment {} transfer {} failed on-chain verification: {}",
+                    investment.id,
+                    tx_hash,
+                    e
+                ),
+            }
+        }
+
+        let tolerance = Decimal::new(1, 2); // 0.01 IDRX rounding
+        if verified_total + tolerance < pool.funded_amount {
+            tracing::error!(
+                "Escrow shortfall for pool {}: verified {} IDRX on-chain, funded_amount {}",
+                pool.id,
+                verified_total,
+                pool.funded_amount
+            );
+            return Err(AppError::BadRequest(format!(
+                "Escrow shortfall: only {} of {} IDRX verified on-chain for this pool",
+                verified_total, pool.funded_amount
+            )));
+        }
+
+        Ok(verified_total)
+    }
+
+    /// AML velocity check: the user's confirmed `tx_type` volume over the rolling
+    /// window plus `amount` must not exceed `cap` (a cap of 0 disables the check)
+    async fn check_velocity_cap(
+        &self,
+        user_id: Uuid,
+        tx_type: &str,
+        amount: Decimal,
+        cap: f64,
+    ) -> AppResult<()> {
+        if cap <= 0.0 {
+            return Ok(());
+        }
+
+        let window_days = self.config.velocity_window_days;
+        let since = (Utc::now() - Duration::days(window_days)).naive_utc();
+        let window_total = self
+            .tx_repo
+            .sum_user_amount_since(user_id, tx_type, since)
+            .await?;
+        let cap = Decimal::from_f64(cap).unwrap_or(Decimal::MAX);
+
+        if window_total + amount > cap {
+            tracing::warn!(
+                "AML velocity cap breached: user {} {} volume {} + {} exceeds {} over {} days",
+                user_id,
+                tx_type,
+                window_total,
+                amount,
+                cap,
+                window_days
+            );
+            return Err(AppError::ComplianceLimitExceeded(format!(
+                "Rolling {}-day {} limit of {} IDRX exceeded ({} IDRX already used)",
+                window_days, tx_type, cap, window_total
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Concentration check: the investor's active principal across every pool of
+    /// the pool's exporter plus `amount` must not exceed `investor_mitra_exposure_cap`
+    /// (0 disables the check)
+    async fn check_mitra_exposure_cap(
+        &self,
+        investor_id: Uuid,
+        pool: &FundingPool,
+        amount: Decimal,
+    ) -> AppResult<()> {
+        let cap = self.config.investor_mitra_exposure_cap;
+        if cap <= 0.0 {
+            return Ok(());
+        }
+
+        let invoice = self
+            .invoice_repo
+            .find_by_id(pool.invoice_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Invoice not found".to_string()))?;
+        let exposure = self
+            .funding_repo
+            .sum_active_exposure_to_exporter(investor_id, invoice.exporter_id)
+            .await?;
+        let cap = Decimal::from_f64(cap).unwrap_or(Decimal::MAX);
+
+        if exposure + amount > cap {
+            return Err(AppError::ConcentrationLimitExceeded(format!(
+                "Investing {} IDRX would raise your active exposure to this mitra to {} IDRX, \
+                 above the limit of {} IDRX ({} IDRX already invested)",
+                amount,
+                exposure + amount,
+                cap,
+                exposure
+            )));
+        }
+
+        Ok(())
+    }
+
     fn build_pool_response(
         &self,
         pool: FundingPool,
@@ -815,6 +3169,38 @@ impl FundingService {
         })
     }
 
+    /// Override the single-investment limits of an open pool, e.g. to allow larger
+    /// tickets on grade A invoices than the config defaults
+    pub async fn set_pool_investment_limits(
+        &self,
+        pool_id: Uuid,
+        req: PoolInvestmentLimitsRequest,
+    ) -> AppResult<FundingPool> {
+        let pool = self
+            .funding_repo
+            .find_by_id(pool_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Pool not found".to_string()))?;
+
+        if pool.status != "open" {
+            return Err(AppError::BadRequest(
+                "Investment limits can only be changed while the pool is open".to_string(),
+            ));
+        }
+
+        let (min, max) = (req.min_invest_ratio, req.max_invest_ratio);
+        if min <= Decimal::ZERO || min > max || max > Decimal::ONE {
+            return Err(AppError::ValidationError(
+                "Investment ratios must satisfy 0 < min_invest_ratio <= max_invest_ratio <= 1"
+                    .to_string(),
+            ));
+        }
+
+        self.funding_repo
+            .set_investment_limits(pool_id, min, max)
+            .await
+    }
+
     pub async fn close_pool(&self, pool_id: Uuid) -> AppResult<FundingPool> {
         let pool = self
             .funding_repo
@@ -862,6 +3248,8 @@ impl FundingService {
     }
 
     pub async fn process_repayment(&self, pool_id: Uuid, tx_hash: String, total_amount: Decimal) -> AppResult<FundingPool> {
+        self.require_on_chain_settlement()?;
+
         let pool = self
             .funding_repo
             .find_by_id(pool_id)
@@ -888,7 +3276,7 @@ impl FundingService {
         let investments = self.funding_repo.find_investments_by_pool(pool_id).await?;
         let mut investor_returns_amounts = Vec::new();
 
-        for inv in investments {
+        for inv in &investments {
             // For now, assume full repayment: Return = Expected Return
             investor_returns_amounts.push(inv.expected_return);
         }
@@ -897,27 +3285,219 @@ impl FundingService {
         let nft = self.invoice_repo.find_nft_by_invoice(pool.invoice_id).await?.unwrap();
         let token_id = nft.token_id.unwrap();
 
-        let _repay_tx_hash = self.blockchain_service
+        let sent = self
+            .blockchain_service
             .record_repayment_on_chain(token_id, total_amount, investor_returns_amounts)
             .await?;
+        let repay_tx_hash = sent.tx_hash.clone();
+
+        // 4. Record the platform fee the contract withholds from the repayment
+        let invoice = self.invoice_repo.find_by_id(pool.invoice_id).await?;
+        if let Some(invoice) = &invoice {
+            self.record_platform_fee(
+                invoice.exporter_id,
+                self.platform_fee(total_amount),
+                &repay_tx_hash,
+                pool.invoice_id,
+                &format!("Repayment fee for pool {}", pool.id),
+                &self.blockchain_service.get_explorer_url(&repay_tx_hash),
+            )
+            .await?;
+        }
+        self.record_gas(&sent).await?;
 
         // 5. Update DB Status
         let updated_pool = self.funding_repo.set_repaid(pool_id).await?;
-        self.invoice_repo.update_status(pool.invoice_id, "paid").await?;
+        self.invoice_repo.update_status(pool.invoice_id, "repaid").await?;
+
+        self.notification_service.send_webhook(
+            WebhookEvent::PoolRepaid,
+            serde_json::json!({
+                "pool_id": pool_id,
+                "invoice_id": pool.invoice_id,
+                "total_amount": total_amount,
+                "tx_hash": repay_tx_hash,
+            }),
+        );
+
+        if let Some(invoice) = &invoice {
+            self.notify_repaid(invoice, pool_id, &investments).await;
+        }
 
         Ok(updated_pool)
     }
 
+    /// In-app notifications for a repaid pool: one to the exporter, one per investment
+    async fn notify_repaid(&self, invoice: &Invoice, pool_id: Uuid, investments: &[Investment]) {
+        self.notification_service
+            .notify(
+                invoice.exporter_id,
+                NotificationKind::PoolRepaid,
+                serde_json::json!({
+                    "pool_id": pool_id,
+                    "invoice_id": invoice.id,
+                    "invoice_number": invoice.invoice_number,
+                }),
+            )
+            .await;
+
+        for inv in investments {
+            self.notification_service
+                .notify(
+                    inv.investor_id,
+                    NotificationKind::InvestmentRepaid,
+                    serde_json::json!({
+                        "investment_id": inv.id,
+                        "pool_id": pool_id,
+                        "invoice_number": invoice.invoice_number,
+                        "amount": inv.amount,
+                        "return_amount": inv.expected_return,
+                    }),
+                )
+                .await;
+
+            if let Ok(Some(investor)) = self.user_repo.find_by_id(inv.investor_id).await {
+                if let Some(email) = &investor.email {
+                    let _ = self
+                        .email_service
+                        .send_investment_repaid_notification(
+                            email,
+                            &invoice.invoice_number,
+                            inv.amount.to_f64().unwrap_or(0.0),
+                            inv.expected_return.to_f64().unwrap_or(0.0),
+                        )
+                        .await;
+                }
+            }
+        }
+    }
+
+    /// Repayment the mitra owes for a pool: investor principal + interest, grossed up
+    /// so the amount left after the contract's platform fee covers every investor
+    pub async fn get_repayment_breakdown(
+        &self,
+        mitra_id: Uuid,
+        pool_id: Uuid,
+    ) -> AppResult<RepaymentBreakdown> {
+        self.get_repayment_schedule(mitra_id, false, pool_id).await
+    }
+
+    /// What the mitra repays for a pool and how it is split between the tranches,
+    /// each investor and the platform fee, visible to the owning mitra and admins.
+    /// The investor totals, the fee and the rounding remainder add up exactly to
+    /// `total_repayment`.
+    pub async fn get_repayment_schedule(
+        &self,
+        requester_id: Uuid,
+        is_admin: bool,
+        pool_id: Uuid,
+    ) -> AppResult<RepaymentBreakdown> {
+        let pool = self
+            .funding_repo
+            .find_by_id(pool_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Pool not found".to_string()))?;
+
+        let invoice = self
+            .invoice_repo
+            .find_by_id(pool.invoice_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Invoice not found".to_string()))?;
+
+        if !is_admin && invoice.exporter_id != requester_id {
+            return Err(AppError::Forbidden("Not the invoice owner".to_string()));
+        }
+
+        let investments = self.funding_repo.find_investments_by_pool(pool_id).await?;
+
+        let tranche_breakdown = |tranche: &str, rate: Decimal| {
+            let mut principal = Decimal::ZERO;
+            let mut total = Decimal::ZERO;
+            let mut investor_count = 0;
+            for inv in investments.iter().filter(|i| i.tranche == tranche) {
+                principal += inv.amount;
+                total += inv.expected_return;
+                investor_count += 1;
+            }
+            TrancheBreakdown {
+                tranche: tranche.to_string(),
+                principal,
+                interest_rate: rate,
+                interest_amount: total - principal,
+                total,
+                investor_count,
+            }
+        };
+        let priority = tranche_breakdown("priority", pool.priority_interest_rate);
+        let catalyst = tranche_breakdown("catalyst", pool.catalyst_interest_rate);
+
+        let investor_total: Decimal = investments.iter().map(|i| i.expected_return).sum();
+        let principal: Decimal = investments.iter().map(|i| i.amount).sum();
+
+        // The contract withholds the fee and keeps whatever is left after paying investors
+        let total_repayment = self.gross_up_repayment(investor_total);
+        let platform_fee = self.platform_fee(total_repayment);
+
+        let investors = investments
+            .iter()
+            .map(|inv| InvestorRepayment {
+                investor_id: inv.investor_id,
+                tranche: inv.tranche.clone(),
+                principal: inv.amount,
+                interest: inv.expected_return - inv.amount,
+                total: inv.expected_return,
+            })
+            .collect();
+
+        let due_date = invoice.due_date.and_hms_opt(0, 0, 0).unwrap().and_utc();
+        let days_remaining = (invoice.due_date - Utc::now().date_naive()).num_days() as i32;
+
+        Ok(RepaymentBreakdown {
+            pool_id,
+            invoice_number: invoice.invoice_number,
+            principal_amount: principal,
+            total_interest: investor_total - principal,
+            platform_fee,
+            rounding_remainder: total_repayment - platform_fee - investor_total,
+            total_repayment,
+            priority_breakdown: priority,
+            catalyst_breakdown: catalyst,
+            due_date,
+            days_remaining,
+            investors,
+        })
+    }
+
     /// Get all funding pools for a specific mitra (exporter)
     pub async fn get_mitra_pools(
         &self,
         mitra_id: Uuid,
         page: i32,
         per_page: i32,
+    ) -> AppResult<(Vec<FundingPoolResponse>, i64)> {
+        self.list_exporter_pools(mitra_id, false, page, per_page).await
+    }
+
+    /// Admin audit view of an exporter's pools, including those of soft-deleted invoices
+    pub async fn get_exporter_pools_for_audit(
+        &self,
+        exporter_id: Uuid,
+        page: i32,
+        per_page: i32,
+    ) -> AppResult<(Vec<FundingPoolResponse>, i64)> {
+        self.list_exporter_pools(exporter_id, true, page, per_page).await
+    }
+
+    async fn list_exporter_pools(
+        &self,
+        exporter_id: Uuid,
+        include_deleted_invoices: bool,
+        page: i32,
+        per_page: i32,
     ) -> AppResult<(Vec<FundingPoolResponse>, i64)> {
         let (pools, total) = self
             .funding_repo
-            .find_by_exporter(mitra_id, page, per_page)
+            .find_by_exporter(exporter_id, include_deleted_invoices, page, per_page)
             .await?;
 
         let mut responses = Vec::new();
@@ -946,6 +3526,10 @@ impl FundingService {
             return Err(AppError::Forbidden("Not the invoice owner".to_string()));
         }
 
+        if invoice.deleted_at.is_some() {
+            return Err(AppError::NotFound("Invoice not found".to_string()));
+        }
+
         // Get the pool
         let pool = self
             .funding_repo
@@ -964,6 +3548,8 @@ impl FundingService {
         invoice_id: Uuid,
         req: crate::models::RepayInvoiceRequest,
     ) -> AppResult<String> {
+        self.require_on_chain_settlement()?;
+
         // 1. Get Invoice & ownership check
         let invoice = self
             .invoice_repo
@@ -992,18 +3578,45 @@ impl FundingService {
         let payment_amount = Decimal::from_f64(req.amount)
             .ok_or_else(|| AppError::ValidationError("Invalid amount".to_string()))?;
 
-        // 3. Verify Mitra Transfer (Mitra -> Platform)
+        // 3. Check the investments can be paid out before touching the chain
+        let investments = self.funding_repo.find_investments_by_pool(pool.id).await?;
+        let invested: Decimal = investments.iter().map(|inv| inv.amount).sum();
+        if investments.is_empty() {
+            return Err(AppError::BadRequest("No investments to repay".to_string()));
+        }
+        if invested != pool.funded_amount {
+            tracing::error!(
+                "Investment ledger of pool {} is inconsistent: investments total {} IDRX, funded_amount {}",
+                pool.id,
+                invested,
+                pool.funded_amount
+            );
+            return Err(AppError::Conflict(format!(
+                "Pool investments total {} IDRX but its funded amount is {}; repayment needs reconciliation",
+                invested, pool.funded_amount
+            )));
+        }
+
+        let total_returns: Decimal = investments.iter().map(|inv| inv.expected_return).sum();
+        if total_returns > payment_amount {
+            return Err(AppError::BadRequest(format!(
+                "Repayment amount {} does not cover investor returns of {}",
+                payment_amount, total_returns
+            )));
+        }
+
+        // 4. Verify Mitra Transfer (Mitra -> Platform)
         // Verify user sent funds to platform wallet
         let _verified_transfer = self
             .blockchain_service
-            .verify_investment_transfer(&req.tx_hash, payment_amount)
+            .verify_investment_transfer(&req.tx_hash, None, payment_amount)
             .await
             .map_err(|e| {
                 AppError::BlockchainError(format!("Failed to verify repayment transfer: {}", e))
             })?;
 
-        // 4. Forward Funds (Platform -> Contract)
-        let contract_addr = &self.config.invoice_pool_contract_addr;
+        // 5. Forward Funds (Platform -> Contract)
+        let contract_addr = self.blockchain_service.pool_contract_addr();
         let _forward_tx = self
             .blockchain_service
             .transfer_idrx(
@@ -1013,18 +3626,14 @@ impl FundingService {
             )
             .await?;
 
-        // 5. Calculate Investor Returns
-        let investments = self.funding_repo.find_investments_by_pool(pool.id).await?;
-
+        // 6. Calculate Investor Returns
         let mut returns: Vec<Decimal> = Vec::new();
 
         // Logic: Iterate investments and determine return amount.
         // For Hackathon/MVP: we assume full repayment triggers full expected return payment.
         // We push expected_return for each investment.
-        // NOTE: If payment_amount < sum(expected_returns), this will fail on contract side (insufficient balance).
-        // The frontend must ensure amount covers total obligation.
 
-        for inv in investments {
+        for inv in &investments {
             returns.push(inv.expected_return);
 
             // Update investment status locally
@@ -1033,7 +3642,7 @@ impl FundingService {
                 .await?;
         }
 
-        // 6. Record on Chain (Contract distributes funds)
+        // 7. Record on Chain (Contract distributes funds)
         let nft = self
             .invoice_repo
             .find_nft_by_invoice(invoice_id)
@@ -1044,21 +3653,140 @@ impl FundingService {
             .token_id
             .ok_or_else(|| AppError::InternalError("Token ID missing".to_string()))?;
 
-        let tx_hash = self
+        let sent = self
             .blockchain_service
             .record_repayment_on_chain(token_id, payment_amount, returns)
             .await?;
-
-        // 7. Update Invoice/Pool status
+        let tx_hash = sent.tx_hash.clone();
+
+        // Record the platform fee the contract withholds from the repayment
+        self.record_platform_fee(
+            exporter_id,
+            self.platform_fee(payment_amount),
+            &tx_hash,
+            invoice_id,
+            &format!("Repayment fee for pool {}", pool.id),
+            &self.blockchain_service.get_explorer_url(&tx_hash),
+        )
+        .await?;
+        self.record_gas(&sent).await?;
+
+        // 8. Update Invoice/Pool status
         let _ = self
             .invoice_repo
             .update_status(invoice_id, "repaid")
             .await?;
         let _ = self.funding_repo.set_closed(pool.id).await?;
 
+        self.notification_service.send_webhook(
+            WebhookEvent::PoolRepaid,
+            serde_json::json!({
+                "pool_id": pool.id,
+                "invoice_id": invoice_id,
+                "total_amount": payment_amount,
+                "tx_hash": tx_hash,
+            }),
+        );
+        self.notify_repaid(&invoice, pool.id, &investments).await;
+
         // We should also update stored investments with the real return tx hash if available or use the block tx hash
         // Skipping detailed per-investment tx hash update for now, or use the same hash.
 
         Ok(tx_hash)
     }
 }
+
+/// Outcome of the investment checks: what recording would use
+struct CheckedInvestment {
+    pool: FundingPool,
+    investor: User,
+    amount: Decimal,
+    is_catalyst: bool,
+    interest_rate: Decimal,
+    payout_wallet: String,
+    min_limit: Decimal,
+    max_limit: Decimal,
+    tranche_remaining: Decimal,
+}
+
+/// Per-wallet totals on both sides of an on-chain audit
+#[derive(Default)]
+struct WalletTally {
+    investor_id: Option<Uuid>,
+    db_amount: Decimal,
+    db_count: usize,
+    onchain_amount: Decimal,
+    onchain_count: usize,
+}
+
+/// Periodically move invoices that reached their due date to `matured`
+pub fn spawn_invoice_maturity_task(
+    funding_service: Arc<FundingService>,
+    every: std::time::Duration,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(every);
+        loop {
+            interval.tick().await;
+            if let Err(e) = funding_service.mark_matured_invoices().await {
+                tracing::error!("Invoice maturity scan failed: {}", e);
+            }
+        }
+    });
+}
+
+/// Periodically audit pools with recent on-chain investments against the contract
+pub fn spawn_onchain_audit_task(
+    funding_service: Arc<FundingService>,
+    every: std::time::Duration,
+    lookback: Duration,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(every);
+        loop {
+            interval.tick().await;
+            if let Err(e) = funding_service.audit_recent_pools_onchain(lookback).await {
+                tracing::error!("On-chain investment audit failed: {}", e);
+            }
+        }
+    });
+}
+
+/// Days of interest an invoice has accrued by `as_of`, counted from its issue
+/// date and capped at the full tenor once the due date has passed
+pub fn accrual_days(invoice: &Invoice, as_of: NaiveDate) -> i64 {
+    let tenor = (invoice.due_date - invoice.issue_date).num_days().max(0);
+    (as_of.min(invoice.due_date) - invoice.issue_date)
+        .num_days()
+        .clamp(0, tenor)
+}
+
+/// Principal plus interest at `interest_rate` (annual %) for whatever the
+/// invoice has not yet accrued
+fn projected_return(invoice: &Invoice, amount: Decimal, interest_rate: Decimal) -> Decimal {
+    let days_until_due =
+        accrual_days(invoice, invoice.due_date) - accrual_days(invoice, Utc::now().date_naive());
+    let annual_rate = interest_rate.to_f64().unwrap_or(10.0) / 100.0;
+    let daily_rate = annual_rate / 365.0;
+    let interest = amount.to_f64().unwrap_or(0.0) * daily_rate * days_until_due as f64;
+    Decimal::from_f64(amount.to_f64().unwrap_or(0.0) + interest).unwrap()
+}
+
+/// Quote a CSV field when it contains a delimiter, quote or line break.
+/// Text a spreadsheet would evaluate as a formula is prefixed with `'`;
+/// plain numbers such as a negative gain are left as they are.
+fn escape_csv_field(field: &str) -> String {
+    let is_formula =
+        field.starts_with(['=', '+', '-', '@', '\t', '\r']) && field.parse::<Decimal>().is_err();
+    let field = if is_formula {
+        format!("'{}", field)
+    } else {
+        field.to_string()
+    };
+
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
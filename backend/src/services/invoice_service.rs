@@ -7,10 +7,12 @@ use uuid::Uuid;
 use crate::config::Config;
 use crate::error::{AppError, AppResult};
 use crate::models::{
-    AdminGradeSuggestionResponse, CreateInvoiceFundingRequest, Invoice, InvoiceDocument,
-    RepeatBuyerCheckResponse,
+    AdminGradeSuggestionResponse, CreateInvoiceFundingRequest, DocumentIntegrityCheck,
+    DocumentIntegrityReport, DocumentIntegrityStatus, FundableInvoiceFilter, Invoice,
+    InvoiceDocument, RepeatBuyerCheckResponse,
 };
 use crate::repository::{FundingRepository, InvoiceRepository, MitraRepository, UserRepository};
+use crate::utils::{aggregate_document_hash, keccak256_hex, verify_rate_quote, Cursor};
 
 use super::PinataService;
 
@@ -54,6 +56,11 @@ impl InvoiceService {
             .await?
             .ok_or_else(|| AppError::Forbidden("Must be an approved mitra".to_string()))?;
 
+        if mitra.status == "revoked" {
+            return Err(AppError::Forbidden(
+                "Mitra status has been revoked; new funding requests are not allowed".to_string(),
+            ));
+        }
         if mitra.status != "approved" {
             return Err(AppError::Forbidden(
                 "Mitra application not yet approved".to_string(),
@@ -71,8 +78,36 @@ impl InvoiceService {
         let due_date = NaiveDate::parse_from_str(&req.due_date, "%Y-%m-%d")
             .map_err(|_| AppError::ValidationError("Invalid due date format".to_string()))?;
 
+        // Funding requests are issued today, so the due date must lie strictly after it
+        let issue_date = chrono::Utc::now().date_naive();
+        if due_date <= issue_date {
+            return Err(AppError::ValidationError(
+                "Due date must be after the issue date".to_string(),
+            ));
+        }
+
+        // The exchange rate comes from the server-signed quote, not from the client
+        let quote = verify_rate_quote(&self.config.quote_signing_secret, &req.rate_lock_token)?;
+        if quote.from_currency != req.original_currency || quote.to_currency != "IDR" {
+            return Err(AppError::ValidationError(
+                "Exchange rate quote is for a different currency".to_string(),
+            ));
+        }
+        if (quote.rate - req.locked_exchange_rate).abs() > 1e-6 {
+            return Err(AppError::ValidationError(
+                "Exchange rate does not match the signed quote".to_string(),
+            ));
+        }
+        let idr_amount = req.original_amount * quote.rate;
+        if (idr_amount - req.idr_amount).abs() > 1.0 {
+            return Err(AppError::ValidationError(
+                "IDR amount does not match the quoted exchange rate".to_string(),
+            ));
+        }
+
         // Create invoice
-        let amount = Decimal::from_f64(req.idr_amount)
+        let amount = Decimal::from_f64(idr_amount)
+            .map(|a| a.round_dp(2))
             .ok_or_else(|| AppError::ValidationError("Invalid amount".to_string()))?;
 
         // Parse ratios and rates
@@ -94,7 +129,7 @@ impl InvoiceService {
                 &req.invoice_number,
                 "IDR",
                 amount,
-                chrono::Utc::now().date_naive(),
+                issue_date,
                 due_date,
                 req.description.as_deref(),
                 &req.wallet_address,
@@ -180,14 +215,50 @@ impl InvoiceService {
         page: i32,
         per_page: i32,
         status: Option<String>,
+        include_deleted: bool,
+        cursor: Option<Cursor>,
     ) -> AppResult<(Vec<Invoice>, i64)> {
         self.invoice_repo
-            .find_by_exporter(exporter_id, status, page, per_page)
+            .find_by_exporter(exporter_id, status, include_deleted, page, per_page, cursor)
             .await
     }
 
-    pub async fn list_fundable(&self, page: i32, per_page: i32) -> AppResult<(Vec<Invoice>, i64)> {
-        self.invoice_repo.find_fundable(page, per_page).await
+    /// Soft-delete a draft or rejected invoice owned by the exporter. Its pools and
+    /// investments are kept for audit; deletion is refused while a pool is still active.
+    pub async fn delete_invoice(&self, exporter_id: Uuid, id: Uuid) -> AppResult<Invoice> {
+        let invoice = self
+            .invoice_repo
+            .find_by_id(id)
+            .await?
+            .filter(|i| i.deleted_at.is_none())
+            .ok_or_else(|| AppError::NotFound("Invoice not found".to_string()))?;
+
+        if invoice.exporter_id != exporter_id {
+            return Err(AppError::Forbidden("Not the invoice owner".to_string()));
+        }
+
+        if invoice.status != "draft" && invoice.status != "rejected" {
+            return Err(AppError::Conflict(format!(
+                "Only draft or rejected invoices can be deleted (status is {})",
+                invoice.status
+            )));
+        }
+
+        self.invoice_repo.soft_delete(id).await
+    }
+
+    /// Fundable invoices matching `filter`
+    pub async fn list_fundable(
+        &self,
+        filter: &FundableInvoiceFilter,
+        page: i32,
+        per_page: i32,
+    ) -> AppResult<(Vec<Invoice>, i64)> {
+        validate_fundable_filter(filter)?;
+
+        self.invoice_repo
+            .find_fundable(filter, page, per_page)
+            .await
     }
 
     pub async fn list_pending(&self, page: i32, per_page: i32) -> AppResult<(Vec<Invoice>, i64)> {
@@ -269,14 +340,31 @@ impl InvoiceService {
             .update_grade(id, grade, grade_score, funding_limit)
             .await?;
 
-        // Update interest rates if provided
-        if let (Some(pr), Some(cr)) = (priority_rate, catalyst_rate) {
-            let priority = Decimal::from_f64(pr)
-                .ok_or_else(|| AppError::ValidationError("Invalid priority rate".to_string()))?;
-            let catalyst = Decimal::from_f64(cr)
-                .ok_or_else(|| AppError::ValidationError("Invalid catalyst rate".to_string()))?;
+        // Tranche rates from the review override the requested ones. The funding
+        // terms are the tranche rates; `interest_rate` is their blend.
+        let (priority, catalyst) = match (priority_rate, catalyst_rate) {
+            (Some(pr), Some(cr)) => (
+                Some(Decimal::from_f64(pr).ok_or_else(|| {
+                    AppError::ValidationError("Invalid priority rate".to_string())
+                })?),
+                Some(Decimal::from_f64(cr).ok_or_else(|| {
+                    AppError::ValidationError("Invalid catalyst rate".to_string())
+                })?),
+            ),
+            _ => (
+                invoice.priority_interest_rate,
+                invoice.catalyst_interest_rate,
+            ),
+        };
+        if let (Some(priority), Some(catalyst)) = (priority, catalyst) {
+            let blended = Invoice::blend_interest_rates(
+                invoice.priority_ratio,
+                invoice.catalyst_ratio,
+                priority,
+                catalyst,
+            );
             self.invoice_repo
-                .update_interest_rates(id, priority, catalyst)
+                .update_interest_rates(id, priority, catalyst, blended)
                 .await?;
         }
 
@@ -383,10 +471,11 @@ impl InvoiceService {
             .await?;
 
         // Calculate hash
-        let file_hash = format!("{:x}", md5::compute(&file_data));
+        let file_hash = keccak256_hex(&file_data);
 
         // Create document record
-        self.invoice_repo
+        let document = self
+            .invoice_repo
             .create_document(
                 invoice_id,
                 document_type,
@@ -395,7 +484,31 @@ impl InvoiceService {
                 &file_hash,
                 file_data.len() as i32,
             )
-            .await
+            .await?;
+
+        self.refresh_document_hash(invoice_id).await?;
+
+        Ok(document)
+    }
+
+    /// Recompute `invoices.document_hash`, the hash minted into the NFT, from the
+    /// invoice's current documents
+    pub async fn refresh_document_hash(&self, invoice_id: Uuid) -> AppResult<Option<String>> {
+        let documents = self
+            .invoice_repo
+            .find_documents_by_invoice(invoice_id)
+            .await?;
+        if documents.is_empty() {
+            return Ok(None);
+        }
+
+        let hashes: Vec<&str> = documents.iter().map(|d| d.file_hash.as_str()).collect();
+        let document_hash = aggregate_document_hash(&hashes);
+        self.invoice_repo
+            .update_document_hash(invoice_id, &document_hash)
+            .await?;
+
+        Ok(Some(document_hash))
     }
 
     pub async fn get_documents(&self, invoice_id: Uuid) -> AppResult<Vec<InvoiceDocument>> {
@@ -404,6 +517,112 @@ impl InvoiceService {
             .await
     }
 
+    /// Fetch a document's bytes from IPFS for the invoice owner or an admin, so
+    /// clients never need the gateway URL
+    pub async fn download_document(
+        &self,
+        requester_id: Uuid,
+        is_admin: bool,
+        invoice_id: Uuid,
+        document_id: Uuid,
+    ) -> AppResult<(InvoiceDocument, Vec<u8>)> {
+        let invoice = self
+            .invoice_repo
+            .find_by_id(invoice_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Invoice not found".to_string()))?;
+
+        if !is_admin && invoice.exporter_id != requester_id {
+            return Err(AppError::Forbidden("Not the invoice owner".to_string()));
+        }
+
+        let document = self
+            .invoice_repo
+            .find_document(invoice_id, document_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Document not found".to_string()))?;
+
+        let cid = self
+            .pinata_service
+            .get_ipfs_hash_from_url(&document.file_url)
+            .unwrap_or_default();
+        let data = self.pinata_service.fetch_file(&cid).await?;
+
+        Ok((document, data))
+    }
+
+    /// Re-download every document from IPFS and compare it against the hash
+    /// recorded at upload, so tampered or unpinned files surface before approval
+    pub async fn verify_documents(&self, invoice_id: Uuid) -> AppResult<DocumentIntegrityReport> {
+        self.invoice_repo
+            .find_by_id(invoice_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Invoice not found".to_string()))?;
+
+        let documents = self
+            .invoice_repo
+            .find_documents_by_invoice(invoice_id)
+            .await?;
+
+        let mut checks = Vec::with_capacity(documents.len());
+        for doc in documents {
+            let cid = self
+                .pinata_service
+                .get_ipfs_hash_from_url(&doc.file_url)
+                .unwrap_or_default();
+
+            let (actual_hash, status, error) = match self.pinata_service.fetch_file(&cid).await {
+                Ok(data) => {
+                    // Documents uploaded before the switch to keccak256 carry a bare md5 hex
+                    let actual = if doc.file_hash.starts_with("0x") {
+                        keccak256_hex(&data)
+                    } else {
+                        format!("{:x}", md5::compute(&data))
+                    };
+                    let status = if actual.eq_ignore_ascii_case(&doc.file_hash) {
+                        DocumentIntegrityStatus::Verified
+                    } else {
+                        tracing::warn!(
+                            "Document {} of invoice {} does not match its recorded hash",
+                            doc.id,
+                            invoice_id
+                        );
+                        DocumentIntegrityStatus::Mismatch
+                    };
+                    (Some(actual), status, None)
+                }
+                Err(e) => (
+                    None,
+                    DocumentIntegrityStatus::Unreachable,
+                    Some(e.to_string()),
+                ),
+            };
+
+            checks.push(DocumentIntegrityCheck {
+                document_id: doc.id,
+                document_type: doc.document_type,
+                file_name: doc.file_name,
+                cid,
+                expected_hash: doc.file_hash,
+                actual_hash,
+                status,
+                error,
+            });
+        }
+
+        let count = |status| checks.iter().filter(|c| c.status == status).count();
+        let mismatched = count(DocumentIntegrityStatus::Mismatch);
+        let unreachable = count(DocumentIntegrityStatus::Unreachable);
+
+        Ok(DocumentIntegrityReport {
+            invoice_id,
+            all_verified: mismatched == 0 && unreachable == 0,
+            mismatched,
+            unreachable,
+            documents: checks,
+        })
+    }
+
     fn calculate_country_score(&self, country: &str) -> i32 {
         // Tier 1 countries (low risk)
         let tier1 = vec![
@@ -471,3 +690,20 @@ impl InvoiceService {
         score.min(35) // Cap at 35
     }
 }
+
+/// Rejects filters the fundable invoice and marketplace listings cannot apply
+pub(crate) fn validate_fundable_filter(filter: &FundableInvoiceFilter) -> AppResult<()> {
+    if matches!(filter.country_tier, Some(tier) if !(1..=3).contains(&tier)) {
+        return Err(AppError::ValidationError(
+            "country_tier must be 1, 2 or 3".to_string(),
+        ));
+    }
+    if let (Some(min), Some(max)) = (filter.min_days_remaining, filter.max_days_remaining) {
+        if min > max {
+            return Err(AppError::ValidationError(
+                "min_days_remaining cannot exceed max_days_remaining".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
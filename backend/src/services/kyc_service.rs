@@ -0,0 +1,157 @@
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::models::{KycStatusResponse, KycSubmitRequest, KycVerification};
+use crate::repository::UserRepository;
+
+use super::EmailService;
+
+pub struct KycService {
+    user_repo: Arc<UserRepository>,
+    email_service: Arc<EmailService>,
+}
+
+impl KycService {
+    pub fn new(user_repo: Arc<UserRepository>, email_service: Arc<EmailService>) -> Self {
+        Self {
+            user_repo,
+            email_service,
+        }
+    }
+
+    pub async fn submit(&self, user_id: Uuid, req: KycSubmitRequest) -> AppResult<KycVerification> {
+        if req.nik.len() != 16 || !req.nik.chars().all(|c| c.is_ascii_digit()) {
+            return Err(AppError::ValidationError(
+                "NIK must be 16 digits".to_string(),
+            ));
+        }
+
+        // Resubmission is allowed after rejection, but not while pending or once approved
+        if let Some(existing) = self.user_repo.find_kyc_by_user(user_id).await? {
+            if existing.status == "pending" {
+                return Err(AppError::Conflict(
+                    "KYC verification already pending".to_string(),
+                ));
+            }
+            if existing.status == "approved" {
+                return Err(AppError::Conflict("KYC already approved".to_string()));
+            }
+        }
+
+        self.user_repo
+            .upsert_kyc(
+                user_id,
+                &req.nik,
+                &req.full_name,
+                &req.ktp_photo_url,
+                &req.selfie_url,
+            )
+            .await
+    }
+
+    pub async fn get_status(&self, user_id: Uuid) -> AppResult<KycStatusResponse> {
+        let verification = self.user_repo.find_kyc_by_user(user_id).await?;
+
+        Ok(match verification {
+            Some(kyc) => KycStatusResponse {
+                status: kyc.status.clone(),
+                rejection_reason: kyc.rejection_reason.clone(),
+                verified_at: kyc.verified_at,
+                verification: Some(kyc),
+            },
+            None => KycStatusResponse {
+                status: "none".to_string(),
+                verification: None,
+                rejection_reason: None,
+                verified_at: None,
+            },
+        })
+    }
+
+    pub async fn get_pending(
+        &self,
+        page: i32,
+        per_page: i32,
+    ) -> AppResult<(Vec<KycVerification>, i64)> {
+        self.user_repo.find_pending_kyc(page, per_page).await
+    }
+
+    pub async fn get_verification(&self, id: Uuid) -> AppResult<KycVerification> {
+        self.user_repo
+            .find_kyc_by_id(id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("KYC verification not found".to_string()))
+    }
+
+    pub async fn approve(&self, id: Uuid, admin_id: Uuid) -> AppResult<KycVerification> {
+        let kyc = self.get_verification(id).await?;
+
+        if kyc.status != "pending" {
+            return Err(AppError::BadRequest(
+                "KYC verification is not pending".to_string(),
+            ));
+        }
+
+        // A concurrent review may have decided it since the check above
+        let approved = self
+            .user_repo
+            .approve_kyc(id, admin_id)
+            .await?
+            .ok_or_else(|| {
+                AppError::Conflict("KYC verification was already reviewed".to_string())
+            })?;
+        self.user_repo.set_verified(approved.user_id, true).await?;
+
+        if let Some(user) = self.user_repo.find_by_id(approved.user_id).await? {
+            if let Some(email) = &user.email {
+                let _ = self
+                    .email_service
+                    .send_kyc_approval_notification(email, &approved.full_name)
+                    .await;
+            }
+        }
+
+        Ok(approved)
+    }
+
+    pub async fn reject(
+        &self,
+        id: Uuid,
+        admin_id: Uuid,
+        reason: &str,
+    ) -> AppResult<KycVerification> {
+        let kyc = self.get_verification(id).await?;
+
+        if kyc.status != "pending" {
+            return Err(AppError::BadRequest(
+                "KYC verification is not pending".to_string(),
+            ));
+        }
+
+        if reason.trim().is_empty() {
+            return Err(AppError::ValidationError(
+                "Rejection reason is required".to_string(),
+            ));
+        }
+
+        let rejected = self
+            .user_repo
+            .reject_kyc(id, admin_id, reason)
+            .await?
+            .ok_or_else(|| {
+                AppError::Conflict("KYC verification was already reviewed".to_string())
+            })?;
+
+        if let Some(user) = self.user_repo.find_by_id(rejected.user_id).await? {
+            if let Some(email) = &user.email {
+                let _ = self
+                    .email_service
+                    .send_kyc_rejection_notification(email, &rejected.full_name, reason)
+                    .await;
+            }
+        }
+
+        Ok(rejected)
+    }
+}
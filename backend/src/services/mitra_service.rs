@@ -3,18 +3,19 @@ use uuid::Uuid;
 
 use crate::error::{AppError, AppResult};
 use crate::models::{
-    MitraApplication, MitraApplicationDetailResponse, MitraApplyRequest, MitraDocumentsStatus,
-    MitraStatusResponse, MitraUserInfo,
+    MemberStatus, MitraApplication, MitraApplicationDetailResponse, MitraApplyRequest,
+    MitraDocumentsStatus, MitraStatusResponse, MitraUserInfo, RepaymentBreakdown, Role,
 };
 use crate::repository::{MitraRepository, UserRepository};
 
-use super::{EmailService, PinataService};
+use super::{EmailService, FundingService, PinataService};
 
 pub struct MitraService {
     mitra_repo: Arc<MitraRepository>,
     user_repo: Arc<UserRepository>,
     email_service: Arc<EmailService>,
     pinata_service: Arc<PinataService>,
+    funding_service: Option<Arc<FundingService>>,
 }
 
 impl MitraService {
@@ -29,9 +30,30 @@ impl MitraService {
             user_repo,
             email_service,
             pinata_service,
+            funding_service: None,
         }
     }
 
+    /// Pool repayment figures are computed by the funding service
+    pub fn with_funding_service(mut self, funding_service: Arc<FundingService>) -> Self {
+        self.funding_service = Some(funding_service);
+        self
+    }
+
+    /// Repayment the mitra owes for one of their pools
+    pub async fn get_repayment_breakdown(
+        &self,
+        mitra_id: Uuid,
+        pool_id: Uuid,
+    ) -> AppResult<RepaymentBreakdown> {
+        let funding_service = self.funding_service.as_ref().ok_or_else(|| {
+            AppError::InternalError("Funding service is not configured".to_string())
+        })?;
+        funding_service
+            .get_repayment_breakdown(mitra_id, pool_id)
+            .await
+    }
+
     pub async fn apply(
         &self,
         user_id: Uuid,
@@ -47,6 +69,11 @@ impl MitraService {
             if existing.status == "approved" {
                 return Err(AppError::Conflict("Already approved as mitra".to_string()));
             }
+            if existing.status == "revoked" {
+                return Err(AppError::Forbidden(
+                    "Mitra status has been revoked".to_string(),
+                ));
+            }
         }
 
         // Create application
@@ -190,10 +217,10 @@ impl MitraService {
 
         // Update user role to mitra
         self.user_repo
-            .update_role(application.user_id, "mitra")
+            .update_role(application.user_id, Role::Mitra)
             .await?;
         self.user_repo
-            .update_member_status(application.user_id, "member_mitra")
+            .update_member_status(application.user_id, MemberStatus::MemberMitra)
             .await?;
         self.user_repo
             .set_profile_completed(application.user_id, true)
@@ -227,6 +254,65 @@ impl MitraService {
             ));
         }
 
-        self.mitra_repo.reject(id, admin_id, reason).await
+        let rejected = self.mitra_repo.reject(id, admin_id, reason).await?;
+
+        // Send notification email
+        if let Some(user) = self.user_repo.find_by_id(rejected.user_id).await? {
+            if let Some(email) = &user.email {
+                let _ = self
+                    .email_service
+                    .send_mitra_rejection_notification(email, &rejected.company_name, reason)
+                    .await;
+            }
+        }
+
+        Ok(rejected)
+    }
+
+    /// Revoke an approved mitra. New funding requests are blocked from then on;
+    /// invoices and pools that already exist are left as they are.
+    pub async fn revoke(
+        &self,
+        id: Uuid,
+        admin_id: Uuid,
+        reason: &str,
+    ) -> AppResult<MitraApplication> {
+        let detail = self.get_application(id).await?;
+        let application = detail.application;
+
+        if application.status != "approved" {
+            return Err(AppError::BadRequest(
+                "Only an approved mitra can be revoked".to_string(),
+            ));
+        }
+        if reason.trim().is_empty() {
+            return Err(AppError::ValidationError(
+                "Revocation reason is required".to_string(),
+            ));
+        }
+
+        let revoked = self
+            .mitra_repo
+            .revoke(id, admin_id, reason)
+            .await?
+            .ok_or_else(|| {
+                AppError::BadRequest("Only an approved mitra can be revoked".to_string())
+            })?;
+
+        // Role stays mitra so existing invoices can still be viewed and repaid
+        self.user_repo
+            .update_member_status(revoked.user_id, MemberStatus::CalonAnggotaMitra)
+            .await?;
+
+        if let Some(user) = self.user_repo.find_by_id(revoked.user_id).await? {
+            if let Some(email) = &user.email {
+                let _ = self
+                    .email_service
+                    .send_mitra_revocation_notification(email, &revoked.company_name, reason)
+                    .await;
+            }
+        }
+
+        Ok(revoked)
     }
 }
@@ -1,5 +1,6 @@
 #![allow(dead_code)] // Many service methods are implemented for future features
 
+mod activity_logger;
 mod auth_service;
 mod blockchain_service;
 mod currency_service;
@@ -7,12 +8,17 @@ mod email_service;
 mod escrow_service;
 mod funding_service;
 mod invoice_service;
+mod kyc_service;
 mod mitra_service;
+mod notification_service;
+mod offramp_service;
 mod otp_service;
 mod payment_service;
 mod pinata_service;
 mod risk_questionnaire_service;
+mod user_service;
 
+pub use activity_logger::*;
 pub use auth_service::*;
 pub use blockchain_service::*;
 pub use currency_service::*;
@@ -20,11 +26,15 @@ pub use email_service::*;
 pub use escrow_service::*;
 pub use funding_service::*;
 pub use invoice_service::*;
+pub use kyc_service::*;
 pub use mitra_service::*;
+pub use notification_service::*;
+pub use offramp_service::*;
 pub use otp_service::*;
 pub use payment_service::*;
 pub use pinata_service::*;
 pub use risk_questionnaire_service::*;
+pub use user_service::*;
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,258 @@
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::error::{AppError, AppResult};
+use crate::models::Notification;
+use crate::repository::NotificationRepository;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Header carrying `sha256=<hex HMAC of the raw body>`
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "X-Vessel-Signature";
+pub const WEBHOOK_EVENT_HEADER: &str = "X-Vessel-Event";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookEvent {
+    PoolFilled,
+    PoolDisbursed,
+    PoolRepaid,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::PoolFilled => "pool_filled",
+            WebhookEvent::PoolDisbursed => "pool_disbursed",
+            WebhookEvent::PoolRepaid => "pool_repaid",
+        }
+    }
+}
+
+/// Kinds of in-app notification written to a user's inbox
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationKind {
+    InvestmentConfirmed,
+    PoolFunded,
+    PoolDisbursed,
+    PoolRepaid,
+    InvestmentRepaid,
+    RepaymentDue,
+}
+
+impl NotificationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationKind::InvestmentConfirmed => "investment_confirmed",
+            NotificationKind::PoolFunded => "pool_funded",
+            NotificationKind::PoolDisbursed => "pool_disbursed",
+            NotificationKind::PoolRepaid => "pool_repaid",
+            NotificationKind::InvestmentRepaid => "investment_repaid",
+            NotificationKind::RepaymentDue => "repayment_due",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct WebhookPayload<'a> {
+    id: Uuid,
+    event: &'a str,
+    occurred_at: chrono::DateTime<chrono::Utc>,
+    data: serde_json::Value,
+}
+
+/// A signed webhook ready to be delivered
+#[derive(Debug, Clone)]
+pub struct WebhookDelivery {
+    pub url: String,
+    pub event: String,
+    pub body: String,
+    pub signature: String,
+}
+
+/// Transport for webhook deliveries. `send` must return immediately;
+/// implementations deliver in the background.
+pub trait WebhookSender: Send + Sync {
+    fn send(&self, delivery: WebhookDelivery);
+}
+
+/// POSTs deliveries from a spawned task, retrying with exponential backoff
+pub struct HttpWebhookSender {
+    client: reqwest::Client,
+    max_retries: u32,
+}
+
+impl HttpWebhookSender {
+    pub fn new(config: &Config) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.webhook_timeout_secs))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            client,
+            max_retries: config.webhook_max_retries,
+        }
+    }
+}
+
+impl WebhookSender for HttpWebhookSender {
+    fn send(&self, delivery: WebhookDelivery) {
+        let client = self.client.clone();
+        let max_retries = self.max_retries;
+
+        tokio::spawn(async move {
+            let mut attempt = 0;
+            loop {
+                let result = client
+                    .post(&delivery.url)
+                    .header("Content-Type", "application/json")
+                    .header(WEBHOOK_EVENT_HEADER, &delivery.event)
+                    .header(WEBHOOK_SIGNATURE_HEADER, &delivery.signature)
+                    .body(delivery.body.clone())
+                    .send()
+                    .await;
+
+                match result {
+                    Ok(resp) if resp.status().is_success() => return,
+                    Ok(resp) => tracing::warn!(
+                        "Webhook {} attempt {} returned {}",
+                        delivery.event,
+                        attempt + 1,
+                        resp.status()
+                    ),
+                    Err(e) => tracing::warn!(
+                        "Webhook {} attempt {} failed: {}",
+                        delivery.event,
+                        attempt + 1,
+                        e
+                    ),
+                }
+
+                if attempt >= max_retries {
+                    tracing::error!(
+                        "Webhook {} dropped after {} attempts",
+                        delivery.event,
+                        attempt + 1
+                    );
+                    return;
+                }
+                tokio::time::sleep(Duration::from_secs(1u64 << attempt.min(6))).await;
+                attempt += 1;
+            }
+        });
+    }
+}
+
+/// Delivers lifecycle events: signed webhooks to the integrator endpoint and
+/// in-app notifications to the users involved
+pub struct NotificationService {
+    config: Arc<Config>,
+    sender: Arc<dyn WebhookSender>,
+    notification_repo: Arc<NotificationRepository>,
+}
+
+impl NotificationService {
+    pub fn new(config: Arc<Config>, notification_repo: Arc<NotificationRepository>) -> Self {
+        let sender = Arc::new(HttpWebhookSender::new(&config));
+        Self {
+            config,
+            sender,
+            notification_repo,
+        }
+    }
+
+    pub fn with_sender(
+        config: Arc<Config>,
+        sender: Arc<dyn WebhookSender>,
+        notification_repo: Arc<NotificationRepository>,
+    ) -> Self {
+        Self {
+            config,
+            sender,
+            notification_repo,
+        }
+    }
+
+    /// Write an in-app notification. Best-effort like the email sends it
+    /// accompanies: failures are traced and never propagated.
+    pub async fn notify(&self, user_id: Uuid, kind: NotificationKind, payload: serde_json::Value) {
+        if let Err(e) = self
+            .notification_repo
+            .create(user_id, kind.as_str(), &payload.to_string())
+            .await
+        {
+            tracing::warn!(
+                "Failed to record {} notification for user {}: {}",
+                kind.as_str(),
+                user_id,
+                e
+            );
+        }
+    }
+
+    /// Most recent first
+    pub async fn list_for_user(
+        &self,
+        user_id: Uuid,
+        unread_only: bool,
+        page: i32,
+        per_page: i32,
+    ) -> AppResult<(Vec<Notification>, i64)> {
+        self.notification_repo
+            .find_by_user(user_id, unread_only, page, per_page)
+            .await
+    }
+
+    pub async fn mark_read(&self, user_id: Uuid, notification_id: Uuid) -> AppResult<Notification> {
+        self.notification_repo
+            .mark_read(user_id, notification_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Notification not found".to_string()))
+    }
+
+    pub async fn mark_all_read(&self, user_id: Uuid) -> AppResult<u64> {
+        self.notification_repo.mark_all_read(user_id).await
+    }
+
+    /// Sign and hand an event to the sender. No-op when no webhook URL is configured.
+    pub fn send_webhook(&self, event: WebhookEvent, data: serde_json::Value) {
+        if self.config.webhook_url.is_empty() {
+            return;
+        }
+
+        let payload = WebhookPayload {
+            id: Uuid::new_v4(),
+            event: event.as_str(),
+            occurred_at: chrono::Utc::now(),
+            data,
+        };
+        let body = match serde_json::to_string(&payload) {
+            Ok(body) => body,
+            Err(e) => {
+                tracing::error!("Failed to serialize {} webhook: {}", event.as_str(), e);
+                return;
+            }
+        };
+
+        let signature = Self::sign(&self.config.webhook_secret, &body);
+        self.sender.send(WebhookDelivery {
+            url: self.config.webhook_url.clone(),
+            event: event.as_str().to_string(),
+            body,
+            signature,
+        });
+    }
+
+    /// `sha256=<hex>` HMAC of the body with the shared secret
+    pub fn sign(secret: &str, body: &str) -> String {
+        let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(body.as_bytes());
+        format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+    }
+}
@@ -0,0 +1,42 @@
+use futures_util::future::BoxFuture;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::error::AppResult;
+use crate::models::BankAccount;
+
+/// Converts IDRX to fiat and pays it into a bank account
+pub trait OffRamp: Send + Sync {
+    /// Request a payout of `amount` to `account`. `reference` is the id of the
+    /// `bank_disbursement` transaction, used to reconcile the payout once it settles.
+    fn request_payout<'a>(
+        &'a self,
+        account: &'a BankAccount,
+        amount: Decimal,
+        reference: Uuid,
+    ) -> BoxFuture<'a, AppResult<()>>;
+}
+
+/// No off-ramp partner is integrated yet: payouts are only logged and settled by
+/// operations, leaving the transaction `pending_offramp`
+pub struct ManualOffRamp;
+
+impl OffRamp for ManualOffRamp {
+    fn request_payout<'a>(
+        &'a self,
+        account: &'a BankAccount,
+        amount: Decimal,
+        reference: Uuid,
+    ) -> BoxFuture<'a, AppResult<()>> {
+        Box::pin(async move {
+            tracing::info!(
+                "Manual off-ramp payout {} of {} IDRX to {} {}",
+                reference,
+                amount,
+                account.bank_code,
+                account.masked_account_number()
+            );
+            Ok(())
+        })
+    }
+}
@@ -5,7 +5,7 @@ use crate::config::Config;
 use crate::error::{AppError, AppResult};
 use crate::models::{SendOtpResponse, VerifyOtpResponse};
 use crate::repository::OtpRepository;
-use crate::utils::{generate_otp, JwtManager};
+use crate::utils::{generate_otp, mask_email, JwtManager};
 
 use super::EmailService;
 
@@ -41,8 +41,8 @@ impl OtpService {
         let code = generate_otp();
         let expires_at = Utc::now() + Duration::minutes(self.config.otp_expiry_minutes);
 
-        // Log OTP for development/debugging
-        tracing::info!("Generated OTP for {}: {}", email, code);
+        // The code itself is a credential and never goes to the logs
+        tracing::info!("Generated OTP for {}", mask_email(email));
 
         // Delete any existing OTPs for this email and purpose
         self.otp_repo.delete_by_email(email, purpose).await?;
@@ -60,24 +60,9 @@ impl OtpService {
             _ => "VESSEL - Verification Code",
         };
 
-        let body = format!(
-            r#"
-            <html>
-            <body style="font-family: Arial, sans-serif; padding: 20px;">
-                <h2>VESSEL Verification Code</h2>
-                <p>Your verification code is:</p>
-                <h1 style="font-size: 32px; letter-spacing: 5px; color: #2563eb;">{}</h1>
-                <p>This code will expire in {} minutes.</p>
-                <p>If you didn't request this code, please ignore this email.</p>
-                <hr>
-                <p style="color: #666; font-size: 12px;">VESSEL - Invoice Factoring Platform on Base Network</p>
-            </body>
-            </html>
-            "#,
-            code, self.config.otp_expiry_minutes
-        );
-
-        self.email_service.send_email(email, subject, &body).await?;
+        self.email_service
+            .send_otp(email, subject, &code, self.config.otp_expiry_minutes)
+            .await?;
 
         Ok(SendOtpResponse {
             message: format!("OTP sent to {}", email),
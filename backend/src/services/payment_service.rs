@@ -1,19 +1,38 @@
-use rust_decimal::prelude::ToPrimitive;
+use chrono::{NaiveDate, NaiveTime};
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use uuid::Uuid;
 
-use crate::error::AppResult;
+use crate::config::Config;
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    BuyerPaymentsResponse, ImporterPayRequest, ImporterPayment, PlatformGasStats,
+    PlatformRevenuePoint, PlatformRevenueQuery, PlatformRevenueSummary, PoolGasUsage,
+    WalletNonceResponse,
+};
 use crate::repository::{
-    FundingRepository, InvoiceRepository, TransactionRepository, UserRepository,
+    FundingRepository, ImporterPaymentRepository, InvoiceRepository, TransactionRepository,
+    UserRepository,
 };
+use crate::utils::{generate_random_token, is_valid_email, sha256_hex};
 
-use super::BlockchainService;
+use super::{BlockchainService, EmailService};
 
 pub struct PaymentService {
     user_repo: Arc<UserRepository>,
     tx_repo: Arc<TransactionRepository>,
     funding_repo: Arc<FundingRepository>,
     invoice_repo: Arc<InvoiceRepository>,
+    importer_payment_repo: Arc<ImporterPaymentRepository>,
     blockchain_service: Arc<BlockchainService>,
+    email_service: Arc<EmailService>,
+    config: Arc<Config>,
+    /// Outstanding signing nonces keyed by (payment id, lowercased wallet)
+    payment_nonces: Arc<RwLock<HashMap<(Uuid, String), String>>>,
 }
 
 impl PaymentService {
@@ -22,19 +41,554 @@ impl PaymentService {
         tx_repo: Arc<TransactionRepository>,
         funding_repo: Arc<FundingRepository>,
         invoice_repo: Arc<InvoiceRepository>,
+        importer_payment_repo: Arc<ImporterPaymentRepository>,
         blockchain_service: Arc<BlockchainService>,
+        email_service: Arc<EmailService>,
+        config: Arc<Config>,
     ) -> Self {
         Self {
             user_repo,
             tx_repo,
             funding_repo,
             invoice_repo,
+            importer_payment_repo,
             blockchain_service,
+            email_service,
+            config,
+            payment_nonces: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    fn payment_signing_message(payment_id: Uuid, wallet: &str, nonce: &str) -> String {
+        format!(
+            "VESSEL importer payment\n\nI authorize paying this invoice from my wallet.\n\nPayment: {}\nWallet: {}\nNonce: {}",
+            payment_id, wallet, nonce
+        )
+    }
+
+    /// Issue a nonce binding `wallet_address` to a pending importer payment.
+    /// The importer signs the returned message and submits it with `pay`.
+    pub async fn get_payment_nonce(
+        &self,
+        payment_id: Uuid,
+        wallet_address: &str,
+    ) -> AppResult<WalletNonceResponse> {
+        let wallet = wallet_address.to_lowercase();
+        if wallet.parse::<ethers::types::Address>().is_err() {
+            return Err(AppError::ValidationError(
+                "Invalid wallet address".to_string(),
+            ));
+        }
+
+        let payment = self
+            .importer_payment_repo
+            .find_by_id(payment_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Payment not found".to_string()))?;
+        if !payment.is_payable() {
+            return Err(AppError::BadRequest(
+                "Payment is not in pending status".to_string(),
+            ));
+        }
+
+        let nonce = generate_random_token();
+        let message = Self::payment_signing_message(payment_id, &wallet, &nonce);
+        {
+            let mut nonces = self.payment_nonces.write().await;
+            nonces.insert((payment_id, wallet), nonce.clone());
+        }
+
+        Ok(WalletNonceResponse { nonce, message })
+    }
+
+    /// Bill the buyer of a pool's invoice: record the importer payment, store its
+    /// link on the invoice and email the buyer. Returns the existing payment
+    /// without emailing again when the pool has already been billed.
+    pub async fn create_importer_payment(&self, pool_id: Uuid) -> AppResult<ImporterPayment> {
+        if let Some(existing) = self.importer_payment_repo.find_by_pool(pool_id).await? {
+            return Ok(existing);
+        }
+
+        let pool = self
+            .funding_repo
+            .find_by_id(pool_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Pool not found".to_string()))?;
+        let invoice = self
+            .invoice_repo
+            .find_by_id(pool.invoice_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Invoice not found".to_string()))?;
+        let buyer_email = invoice
+            .buyer_email
+            .as_deref()
+            .filter(|email| is_valid_email(email))
+            .ok_or_else(|| AppError::BadRequest("Invoice has no valid buyer email".to_string()))?;
+
+        let created = self
+            .importer_payment_repo
+            .create(
+                invoice.id,
+                pool.id,
+                buyer_email,
+                &invoice.buyer_name,
+                invoice.idrx_amount.unwrap_or(invoice.amount),
+                "IDRX",
+                invoice.due_date.and_time(NaiveTime::MIN).and_utc(),
+            )
+            .await;
+        let payment = match created {
+            Ok(payment) => payment,
+            // A concurrent request created the pool's payment first; it sends the email
+            Err(AppError::Conflict(_)) => {
+                return self
+                    .importer_payment_repo
+                    .find_by_pool(pool_id)
+                    .await?
+                    .ok_or_else(|| AppError::NotFound("Importer payment not found".to_string()));
+            }
+            Err(e) => return Err(e),
+        };
+
+        let link = self.payment_link(payment.id);
+        self.invoice_repo
+            .set_payment_link(invoice.id, &link)
+            .await?;
+
+        if let Err(e) = self
+            .email_service
+            .send_importer_payment_request(
+                &payment.buyer_email,
+                &payment.buyer_name,
+                payment.amount_due.to_f64().unwrap_or(0.0),
+                &payment.due_date.format("%Y-%m-%d").to_string(),
+                &link,
+            )
+            .await
+        {
+            tracing::error!("Failed to send payment request for {}: {}", payment.id, e);
         }
+
+        Ok(payment)
     }
 
-    pub async fn get_platform_revenue(&self) -> AppResult<f64> {
-        let revenue = self.tx_repo.get_platform_revenue().await?;
-        Ok(revenue.to_f64().unwrap_or(0.0))
+    /// Flip pending payments past their due date to `overdue` and remind each
+    /// buyer once. Returns how many payments became overdue.
+    pub async fn mark_overdue_payments(&self) -> AppResult<usize> {
+        let mut marked = 0;
+        for pending in self.importer_payment_repo.find_pending_overdue().await? {
+            // Settled since the scan
+            let Some(payment) = self.importer_payment_repo.mark_overdue(pending.id).await? else {
+                continue;
+            };
+            marked += 1;
+
+            let outstanding = payment.amount_due - payment.amount_paid;
+            if let Err(e) = self
+                .email_service
+                .send_importer_payment_overdue(
+                    &payment.buyer_email,
+                    &payment.buyer_name,
+                    outstanding.to_f64().unwrap_or(0.0),
+                    &payment.due_date.format("%Y-%m-%d").to_string(),
+                    &self.payment_link(payment.id),
+                )
+                .await
+            {
+                tracing::error!("Failed to send overdue reminder for {}: {}", payment.id, e);
+            }
+        }
+
+        if marked > 0 {
+            tracing::info!("Marked {} importer payments overdue", marked);
+        }
+        Ok(marked)
     }
+
+    /// Page where the buyer pays an importer payment
+    fn payment_link(&self, payment_id: Uuid) -> String {
+        format!(
+            "{}/importer/pay/{}",
+            self.config.frontend_url.trim_end_matches('/'),
+            payment_id
+        )
+    }
+
+    /// Email a one-time link to the buyer's payments overview. Succeeds silently for
+    /// addresses without payments so the endpoint cannot be used to probe buyers.
+    pub async fn request_buyer_access(&self, email: &str) -> AppResult<()> {
+        let email = email.trim().to_lowercase();
+        let Some(token) = self.issue_buyer_access_token(&email).await? else {
+            return Ok(());
+        };
+
+        let link = format!(
+            "{}/importer/payments?email={}&token={}",
+            self.config.frontend_url.trim_end_matches('/'),
+            urlencoding::encode(&email),
+            token
+        );
+        if let Err(e) = self
+            .email_service
+            .send_importer_access_link(
+                &email,
+                &link,
+                self.config.importer_access_token_ttl_minutes,
+            )
+            .await
+        {
+            tracing::error!("Failed to send buyer access link: {}", e);
+        }
+
+        Ok(())
+    }
+
+    /// Create a one-time access token for a buyer email, or `None` if the
+    /// email has no importer payments. Only the token's hash is stored.
+    pub async fn issue_buyer_access_token(&self, email: &str) -> AppResult<Option<String>> {
+        let email = email.trim().to_lowercase();
+        if !is_valid_email(&email) {
+            return Err(AppError::ValidationError("Invalid email address".to_string()));
+        }
+        if !self.importer_payment_repo.buyer_has_payments(&email).await? {
+            return Ok(None);
+        }
+
+        let token = generate_random_token();
+        let expires_at = chrono::Utc::now()
+            + chrono::Duration::minutes(self.config.importer_access_token_ttl_minutes);
+        self.importer_payment_repo
+            .create_access_token(&email, &sha256_hex(&token), expires_at)
+            .await?;
+
+        Ok(Some(token))
+    }
+
+    /// Every importer payment for the buyer email, authorized by a token from
+    /// `request_buyer_access`. The token is consumed on success.
+    pub async fn list_buyer_payments(
+        &self,
+        email: &str,
+        token: &str,
+    ) -> AppResult<BuyerPaymentsResponse> {
+        let email = email.trim().to_lowercase();
+        if !self
+            .importer_payment_repo
+            .consume_access_token(&email, &sha256_hex(token.trim()))
+            .await?
+        {
+            return Err(AppError::Unauthorized(
+                "Invalid or expired access link".to_string(),
+            ));
+        }
+
+        let payments = self.importer_payment_repo.find_by_buyer_email(&email).await?;
+        let total_outstanding = payments
+            .iter()
+            .filter(|p| p.payment_status == "pending" || p.payment_status == "overdue")
+            .map(|p| p.remaining_amount)
+            .sum();
+
+        Ok(BuyerPaymentsResponse {
+            buyer_email: email,
+            payments,
+            total_outstanding,
+        })
+    }
+
+    /// Platform fee revenue for an optional date range, with a day or month time series
+    pub async fn get_platform_revenue(
+        &self,
+        query: &PlatformRevenueQuery,
+    ) -> AppResult<PlatformRevenueSummary> {
+        let granularity = query.granularity.as_deref().unwrap_or("month");
+        if granularity != "day" && granularity != "month" {
+            return Err(AppError::ValidationError(
+                "granularity must be 'day' or 'month'".to_string(),
+            ));
+        }
+
+        let parse_date = |value: &Option<String>, field: &str| -> AppResult<Option<NaiveDate>> {
+            value
+                .as_deref()
+                .map(|v| {
+                    NaiveDate::parse_from_str(v, "%Y-%m-%d").map_err(|_| {
+                        AppError::ValidationError(format!("{} must be a YYYY-MM-DD date", field))
+                    })
+                })
+                .transpose()
+        };
+        let from = parse_date(&query.from, "from")?;
+        let to = parse_date(&query.to, "to")?;
+
+        if let (Some(from), Some(to)) = (from, to) {
+            if from > to {
+                return Err(AppError::ValidationError(
+                    "from must not be after to".to_string(),
+                ));
+            }
+        }
+
+        let rows = self
+            .tx_repo
+            .get_platform_revenue_rows(granularity, from, to)
+            .await?;
+
+        let mut summary = PlatformRevenueSummary {
+            total_revenue: Decimal::ZERO,
+            confirmed_revenue: Decimal::ZERO,
+            pending_revenue: Decimal::ZERO,
+            tx_count: 0,
+            currency: "IDRX".to_string(),
+            platform_fee_bps: self.config.platform_fee_bps,
+            granularity: granularity.to_string(),
+            from,
+            to,
+            series: Vec::new(),
+        };
+
+        for row in rows {
+            match row.period {
+                Some(period) => summary.series.push(PlatformRevenuePoint {
+                    period,
+                    confirmed: row.confirmed,
+                    pending: row.pending,
+                    tx_count: row.tx_count,
+                }),
+                None => {
+                    summary.confirmed_revenue = row.confirmed;
+                    summary.pending_revenue = row.pending;
+                    summary.total_revenue = row.confirmed + row.pending;
+                    summary.tx_count = row.tx_count;
+                }
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Gas the platform wallet paid for its own sends, per pool and platform-wide.
+    /// Sends that are not tied to a pool only count towards the total.
+    pub async fn get_platform_gas_stats(&self) -> AppResult<PlatformGasStats> {
+        let rows = self.tx_repo.get_gas_usage_rows().await?;
+
+        let mut stats = PlatformGasStats {
+            total_gas_used: 0,
+            total_fee_wei: Decimal::ZERO,
+            tx_count: 0,
+            pools: Vec::new(),
+        };
+
+        for row in rows {
+            if row.is_total {
+                stats.total_gas_used = row.gas_used;
+                stats.total_fee_wei = row.fee_wei;
+                stats.tx_count = row.tx_count;
+            } else if let Some(pool_id) = row.pool_id {
+                stats.pools.push(PoolGasUsage {
+                    pool_id,
+                    gas_used: row.gas_used,
+                    fee_wei: row.fee_wei,
+                    tx_count: row.tx_count,
+                });
+            }
+        }
+
+        Ok(stats)
+    }
+
+    /// Submit an importer payment. When an idempotency key is supplied it is reserved
+    /// first, and a replay within the TTL returns the stored result instead of
+    /// applying the payment again.
+    pub async fn submit_importer_payment(
+        &self,
+        payment_id: Uuid,
+        req: &ImporterPayRequest,
+        idempotency_key: Option<&str>,
+    ) -> AppResult<ImporterPayment> {
+        if let Some(key) = idempotency_key {
+            if key.is_empty() || key.len() > 255 {
+                return Err(AppError::ValidationError(
+                    "Idempotency-Key must be 1-255 characters".to_string(),
+                ));
+            }
+        }
+
+        let wallet = req.wallet_address.to_lowercase();
+        let fingerprint = format!("{}:{}:{}", req.amount, req.tx_hash, wallet);
+
+        let payment = self
+            .importer_payment_repo
+            .find_by_id(payment_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Payment not found".to_string()))?;
+
+        let Some(key) = idempotency_key else {
+            return self.apply_importer_payment(payment, req, &wallet).await;
+        };
+
+        // Reserve the key before applying the payment, so a concurrent request
+        // with the same key cannot apply it twice
+        if !self
+            .importer_payment_repo
+            .reserve_idempotency_key(
+                payment_id,
+                key,
+                &fingerprint,
+                self.config.idempotency_key_ttl_hours,
+            )
+            .await?
+        {
+            return self
+                .replay_importer_payment(payment_id, key, &fingerprint)
+                .await;
+        }
+
+        let result = self.apply_importer_payment(payment, req, &wallet).await;
+        match &result {
+            Ok(updated) => {
+                let body = serde_json::to_string(updated).map_err(|e| {
+                    AppError::InternalError(format!("Failed to encode response: {}", e))
+                })?;
+                self.importer_payment_repo
+                    .complete_idempotency_key(payment_id, key, &body)
+                    .await?;
+            }
+            Err(_) => {
+                self.importer_payment_repo
+                    .release_idempotency_key(payment_id, key)
+                    .await?;
+            }
+        }
+
+        result
+    }
+
+    /// Response of an earlier request holding the same idempotency key
+    async fn replay_importer_payment(
+        &self,
+        payment_id: Uuid,
+        key: &str,
+        fingerprint: &str,
+    ) -> AppResult<ImporterPayment> {
+        let record = self
+            .importer_payment_repo
+            .find_idempotency_key(payment_id, key, self.config.idempotency_key_ttl_hours)
+            .await?
+            .ok_or_else(|| {
+                AppError::Conflict("Idempotency-Key is being released; retry".to_string())
+            })?;
+
+        if record.request_fingerprint != fingerprint {
+            return Err(AppError::Conflict(
+                "Idempotency-Key was already used with a different request".to_string(),
+            ));
+        }
+        if record.status != "completed" {
+            return Err(AppError::Conflict(
+                "A request with this Idempotency-Key is still being processed".to_string(),
+            ));
+        }
+
+        tracing::info!(
+            "Replaying importer payment {} for idempotency key {}",
+            payment_id,
+            key
+        );
+        serde_json::from_str(&record.response_body).map_err(|e| {
+            AppError::InternalError(format!("Failed to decode stored response: {}", e))
+        })
+    }
+
+    /// Verify the signed request and its on-chain transfer, then settle the payment
+    async fn apply_importer_payment(
+        &self,
+        payment: ImporterPayment,
+        req: &ImporterPayRequest,
+        wallet: &str,
+    ) -> AppResult<ImporterPayment> {
+        let payment_id = payment.id;
+        if !payment.is_payable() {
+            return Err(AppError::BadRequest(
+                "Payment is not in pending status".to_string(),
+            ));
+        }
+
+        let amount = Decimal::from_f64(req.amount)
+            .map(|a| a.round_dp(2))
+            .filter(|a| *a > Decimal::ZERO)
+            .ok_or_else(|| AppError::ValidationError("Invalid amount".to_string()))?;
+
+        // Payments settle the invoice in full: underpayment is rejected and any
+        // excess is kept as credit
+        let outstanding = payment.amount_due - payment.amount_paid;
+        if amount < outstanding {
+            return Err(AppError::BadRequest(format!(
+                "Payment must cover the full amount due of {} {}",
+                outstanding, payment.currency
+            )));
+        }
+
+        // The signature must cover the server-built message for this payment's nonce
+        let nonce_key = (payment_id, wallet.to_string());
+        {
+            let nonces = self.payment_nonces.read().await;
+            let stored_nonce = nonces
+                .get(&nonce_key)
+                .ok_or_else(|| AppError::ValidationError("Invalid or expired nonce".to_string()))?;
+            if stored_nonce != &req.nonce {
+                return Err(AppError::ValidationError("Nonce mismatch".to_string()));
+            }
+        }
+
+        let message = Self::payment_signing_message(payment_id, wallet, &req.nonce);
+        if !self
+            .blockchain_service
+            .verify_wallet_signature(wallet, &req.signature, &message)
+            .await?
+        {
+            return Err(AppError::InvalidCredentials);
+        }
+
+        // Importer payments are matched to the cent, and must come from the wallet
+        // that signed for this payment
+        let transfer = self
+            .blockchain_service
+            .verify_idrx_transfer(
+                &req.tx_hash,
+                self.blockchain_service.pool_contract_addr(),
+                Some(wallet),
+                amount,
+                Decimal::ZERO,
+            )
+            .await?;
+
+        {
+            let mut nonces = self.payment_nonces.write().await;
+            nonces.remove(&nonce_key);
+        }
+
+        self.importer_payment_repo
+            .settle_payment(
+                payment_id,
+                transfer.amount,
+                transfer.amount - outstanding,
+                &req.tx_hash,
+                wallet,
+            )
+            .await
+    }
+}
+
+/// Periodically flag importer payments that went past their due date
+pub fn spawn_overdue_payment_task(payment_service: Arc<PaymentService>, every: Duration) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(every);
+        loop {
+            interval.tick().await;
+            if let Err(e) = payment_service.mark_overdue_payments().await {
+                tracing::error!("Overdue payment scan failed: {}", e);
+            }
+        }
+    });
 }
@@ -1,13 +1,144 @@
+use futures_util::future::BoxFuture;
 use reqwest::multipart;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::config::Config;
 use crate::error::{AppError, AppResult};
 
+/// Transport for reading pinned content back through the IPFS gateway
+pub trait IpfsGateway: Send + Sync {
+    fn fetch<'a>(&'a self, url: &'a str) -> BoxFuture<'a, AppResult<Vec<u8>>>;
+}
+
+/// GETs content over HTTP
+pub struct HttpIpfsGateway {
+    client: reqwest::Client,
+}
+
+impl HttpIpfsGateway {
+    pub fn new() -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .unwrap_or_default();
+
+        Self { client }
+    }
+}
+
+impl Default for HttpIpfsGateway {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IpfsGateway for HttpIpfsGateway {
+    fn fetch<'a>(&'a self, url: &'a str) -> BoxFuture<'a, AppResult<Vec<u8>>> {
+        Box::pin(async move {
+            let response = self
+                .client
+                .get(url)
+                .send()
+                .await
+                .map_err(|e| AppError::IpfsError(e.to_string()))?;
+
+            if !response.status().is_success() {
+                return Err(AppError::IpfsError(format!(
+                    "Gateway returned {}",
+                    response.status()
+                )));
+            }
+
+            let bytes = response
+                .bytes()
+                .await
+                .map_err(|e| AppError::IpfsError(e.to_string()))?;
+
+            Ok(bytes.to_vec())
+        })
+    }
+}
+
+/// Transport for pinning JSON documents, returning the CID
+pub trait JsonPinner: Send + Sync {
+    fn pin_json<'a>(
+        &'a self,
+        content: &'a serde_json::Value,
+        name: &'a str,
+    ) -> BoxFuture<'a, AppResult<String>>;
+}
+
+/// Pins through the Pinata `pinJSONToIPFS` API
+pub struct PinataJsonPinner {
+    config: Arc<Config>,
+    client: reqwest::Client,
+}
+
+impl PinataJsonPinner {
+    pub fn new(config: Arc<Config>) -> Self {
+        Self {
+            config,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+impl JsonPinner for PinataJsonPinner {
+    fn pin_json<'a>(
+        &'a self,
+        content: &'a serde_json::Value,
+        name: &'a str,
+    ) -> BoxFuture<'a, AppResult<String>> {
+        Box::pin(async move {
+            if self.config.pinata_jwt.is_empty() {
+                return Err(AppError::IpfsError("Pinata JWT not configured".to_string()));
+            }
+
+            let body = serde_json::json!({
+                "pinataContent": content,
+                "pinataMetadata": {
+                    "name": name
+                }
+            });
+
+            let response = self
+                .client
+                .post("https://api.pinata.cloud/pinning/pinJSONToIPFS")
+                .header(
+                    "Authorization",
+                    format!("Bearer {}", self.config.pinata_jwt),
+                )
+                .header("Content-Type", "application/json")
+                .json(&body)
+                .send()
+                .await
+                .map_err(|e| AppError::IpfsError(e.to_string()))?;
+
+            if !response.status().is_success() {
+                let error_text = response.text().await.unwrap_or_default();
+                return Err(AppError::IpfsError(format!(
+                    "Pinata upload failed: {}",
+                    error_text
+                )));
+            }
+
+            let result: PinataResponse = response
+                .json()
+                .await
+                .map_err(|e| AppError::IpfsError(e.to_string()))?;
+
+            Ok(result.ipfs_hash)
+        })
+    }
+}
+
 pub struct PinataService {
     config: Arc<Config>,
     client: reqwest::Client,
+    gateway: Arc<dyn IpfsGateway>,
+    json_pinner: Arc<dyn JsonPinner>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -29,12 +160,24 @@ pub struct PinataMetadata {
 
 impl PinataService {
     pub fn new(config: Arc<Config>) -> Self {
+        Self::with_gateway(config, Arc::new(HttpIpfsGateway::new()))
+    }
+
+    pub fn with_gateway(config: Arc<Config>, gateway: Arc<dyn IpfsGateway>) -> Self {
         Self {
+            json_pinner: Arc::new(PinataJsonPinner::new(config.clone())),
             config,
             client: reqwest::Client::new(),
+            gateway,
         }
     }
 
+    /// Replace the JSON pinning transport, e.g. with a mock in tests
+    pub fn with_json_pinner(mut self, json_pinner: Arc<dyn JsonPinner>) -> Self {
+        self.json_pinner = json_pinner;
+        self
+    }
+
     pub async fn upload_file(&self, file_data: Vec<u8>, file_name: &str) -> AppResult<String> {
         if self.config.pinata_jwt.is_empty() {
             return Err(AppError::IpfsError("Pinata JWT not configured".to_string()));
@@ -72,66 +215,30 @@ impl PinataService {
             .await
             .map_err(|e| AppError::IpfsError(e.to_string()))?;
 
-        let gateway_url = if self.config.pinata_gateway_url.is_empty() {
-            format!("https://gateway.pinata.cloud/ipfs/{}", result.ipfs_hash)
-        } else {
-            format!(
-                "https://{}/ipfs/{}",
-                self.config.pinata_gateway_url, result.ipfs_hash
-            )
-        };
-
-        Ok(gateway_url)
+        Ok(self.gateway_url(&result.ipfs_hash))
     }
 
     pub async fn upload_json(&self, json_data: serde_json::Value, name: &str) -> AppResult<String> {
-        if self.config.pinata_jwt.is_empty() {
-            return Err(AppError::IpfsError("Pinata JWT not configured".to_string()));
-        }
-
-        let body = serde_json::json!({
-            "pinataContent": json_data,
-            "pinataMetadata": {
-                "name": name
-            }
-        });
-
-        let response = self
-            .client
-            .post("https://api.pinata.cloud/pinning/pinJSONToIPFS")
-            .header(
-                "Authorization",
-                format!("Bearer {}", self.config.pinata_jwt),
-            )
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| AppError::IpfsError(e.to_string()))?;
+        let cid = self.pin_json(&json_data, name).await?;
+        Ok(self.gateway_url(&cid))
+    }
 
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            return Err(AppError::IpfsError(format!(
-                "Pinata upload failed: {}",
-                error_text
-            )));
-        }
+    /// Pin a JSON document and return its CID
+    pub async fn pin_json(&self, json_data: &serde_json::Value, name: &str) -> AppResult<String> {
+        self.json_pinner.pin_json(json_data, name).await
+    }
 
-        let result: PinataResponse = response
-            .json()
-            .await
-            .map_err(|e| AppError::IpfsError(e.to_string()))?;
+    /// Download pinned content by CID through the configured gateway
+    pub async fn fetch_file(&self, cid: &str) -> AppResult<Vec<u8>> {
+        self.gateway.fetch(&self.gateway_url(cid)).await
+    }
 
-        let gateway_url = if self.config.pinata_gateway_url.is_empty() {
-            format!("https://gateway.pinata.cloud/ipfs/{}", result.ipfs_hash)
+    pub fn gateway_url(&self, cid: &str) -> String {
+        if self.config.pinata_gateway_url.is_empty() {
+            format!("https://gateway.pinata.cloud/ipfs/{}", cid)
         } else {
-            format!(
-                "https://{}/ipfs/{}",
-                self.config.pinata_gateway_url, result.ipfs_hash
-            )
-        };
-
-        Ok(gateway_url)
+            format!("https://{}/ipfs/{}", self.config.pinata_gateway_url, cid)
+        }
     }
 
     pub fn get_ipfs_hash_from_url(&self, url: &str) -> Option<String> {
@@ -3,7 +3,7 @@ use uuid::Uuid;
 
 use crate::error::{AppError, AppResult};
 use crate::models::{
-    get_risk_questions, RiskQuestion, RiskQuestionnaire, RiskQuestionnaireAnswers,
+    RiskQuestion, RiskQuestionSet, RiskQuestionnaire, RiskQuestionnaireReview,
     RiskQuestionnaireStatusResponse, SubmitRiskQuestionnaireRequest,
 };
 use crate::repository::RiskQuestionnaireRepository;
@@ -17,62 +17,97 @@ impl RiskQuestionnaireService {
         Self { rq_repo }
     }
 
-    pub fn get_questions(&self) -> Vec<RiskQuestion> {
-        get_risk_questions()
+    /// The currently active question set
+    pub async fn get_questions(&self) -> AppResult<RiskQuestionSet> {
+        self.rq_repo
+            .find_active_question_set()
+            .await?
+            .ok_or_else(|| AppError::NotFound("No active risk questionnaire".to_string()))
     }
 
-    pub async fn submit(
+    /// Publish a new question set and make it the active one. Answers given
+    /// for earlier versions stay valid and are evaluated against their own set.
+    pub async fn publish_questions(
         &self,
-        user_id: Uuid,
-        req: SubmitRiskQuestionnaireRequest,
-    ) -> AppResult<RiskQuestionnaire> {
-        // Validate answers
-        if !(1..=3).contains(&req.q1_answer) {
+        catalyst_min_score: i32,
+        questions: Vec<RiskQuestion>,
+    ) -> AppResult<RiskQuestionSet> {
+        if questions.is_empty() {
             return Err(AppError::ValidationError(
-                "Invalid answer for Q1".to_string(),
+                "A question set needs at least one question".to_string(),
             ));
         }
-        if !(1..=2).contains(&req.q2_answer) {
-            return Err(AppError::ValidationError(
-                "Invalid answer for Q2".to_string(),
-            ));
-        }
-        if !(1..=2).contains(&req.q3_answer) {
-            return Err(AppError::ValidationError(
-                "Invalid answer for Q3".to_string(),
-            ));
+        if let Some(question) = questions.iter().find(|q| q.options.is_empty()) {
+            return Err(AppError::ValidationError(format!(
+                "Q{} has no options",
+                question.id
+            )));
         }
 
-        // Check if catalyst should be unlocked
-        let catalyst_unlocked =
-            self.check_catalyst_unlocked(req.q1_answer, req.q2_answer, req.q3_answer);
+        let version = self
+            .rq_repo
+            .create_question_set(catalyst_min_score, &questions)
+            .await?;
+        self.activate_version(version).await
+    }
 
-        // Check if already exists
-        if let Some(_existing) = self.rq_repo.find_by_user(user_id).await? {
-            // Update existing
-            self.rq_repo
-                .update(
-                    user_id,
-                    req.q1_answer,
-                    req.q2_answer,
-                    req.q3_answer,
-                    catalyst_unlocked,
-                    req.selected_tier,
-                )
-                .await
-        } else {
-            // Create new
-            self.rq_repo
-                .create(
-                    user_id,
-                    req.q1_answer,
-                    req.q2_answer,
-                    req.q3_answer,
-                    catalyst_unlocked,
-                    req.selected_tier,
-                )
-                .await
+    /// Switch the active question set to an existing version
+    pub async fn activate_version(&self, version: i32) -> AppResult<RiskQuestionSet> {
+        if !self.rq_repo.activate_question_set(version).await? {
+            return Err(AppError::NotFound(format!(
+                "Risk questionnaire version {} not found",
+                version
+            )));
         }
+        self.get_questions().await
+    }
+
+    pub async fn submit(
+        &self,
+        user_id: Uuid,
+        req: SubmitRiskQuestionnaireRequest,
+    ) -> AppResult<RiskQuestionnaire> {
+        // Answers are only accepted for the questions currently shown
+        let questions = self.get_questions().await?;
+        if let Some(version) = req.version {
+            if version != questions.version {
+                return Err(AppError::ValidationError(format!(
+                    "Questionnaire version {} is no longer active; reload the questions",
+                    version
+                )));
+            }
+        }
+
+        let answers = req.answer_list();
+        let catalyst_unlocked = questions.evaluate(&answers)?;
+
+        // A re-take overwrites the earlier answers
+        self.rq_repo
+            .upsert(
+                user_id,
+                questions.version,
+                &answers,
+                catalyst_unlocked,
+                req.selected_tier,
+            )
+            .await
+    }
+
+    /// The user's stored answers for review, with the catalyst eligibility
+    /// recorded when they were submitted
+    pub async fn get_answers(&self, user_id: Uuid) -> AppResult<RiskQuestionnaireReview> {
+        let rq =
+            self.rq_repo.find_by_user(user_id).await?.ok_or_else(|| {
+                AppError::NotFound("Risk questionnaire not completed".to_string())
+            })?;
+
+        Ok(RiskQuestionnaireReview {
+            catalyst_unlocked: rq.catalyst_unlocked,
+            question_version: rq.question_version,
+            answers: rq.answers,
+            selected_tier: rq.selected_tier,
+            completed_at: rq.completed_at,
+        })
     }
 
     pub async fn get_status(&self, user_id: Uuid) -> AppResult<RiskQuestionnaireStatusResponse> {
@@ -84,26 +119,17 @@ impl RiskQuestionnaireService {
                 catalyst_unlocked: r.catalyst_unlocked,
                 selected_tier: Some(r.selected_tier),
                 completed_at: Some(r.completed_at),
-                answers: Some(RiskQuestionnaireAnswers {
-                    q1_answer: r.q1_answer,
-                    q2_answer: r.q2_answer,
-                    q3_answer: r.q3_answer,
-                }),
+                question_version: Some(r.question_version),
+                answers: Some(r.answers),
             }),
             None => Ok(RiskQuestionnaireStatusResponse {
                 completed: false,
                 catalyst_unlocked: false,
                 selected_tier: None,
                 completed_at: None,
+                question_version: None,
                 answers: None,
             }),
         }
     }
-
-    fn check_catalyst_unlocked(&self, q1: i32, q2: i32, q3: i32) -> bool {
-        // Q1: Must be >= 2 (1+ years experience)
-        // Q2: Must be 1 (understands risk)
-        // Q3: Must be 1 (willing to be first loss)
-        q1 >= 2 && q2 == 1 && q3 == 1
-    }
 }
@@ -1,23 +1,43 @@
+use ethers::signers::{LocalWallet, Signer};
+use rust_decimal::Decimal;
 use sqlx::PgPool;
 use std::sync::Arc;
+use uuid::Uuid;
 
 use crate::config::Config;
-use crate::models::{MitraApplication, RegisterRequest};
-use crate::repository::{MitraRepository, OtpRepository, UserRepository};
+use crate::error::AppError;
+use crate::models::{
+    ConnectWalletRequest, LoginResponse, MitraApplication, RegisterRequest, Role,
+    WalletLoginRequest,
+};
+use crate::repository::{
+    ActivityRepository, FundingRepository, InvoiceRepository, MitraRepository, OtpRepository,
+    UserRepository,
+};
+use crate::services::blockchain_service::BlockchainService;
 use crate::services::email_service::EmailService;
 use crate::services::pinata_service::PinataService;
-use crate::services::{AuthService, OtpService};
-use crate::utils::JwtManager;
+use crate::services::{ActivityLogger, AuthService, EscrowService, OtpService};
+use crate::utils::{hash_password, JwtManager};
+
+use super::funding_test::create_investor;
 
 // Mock implementations or helpers could go here if we were using mockall fully,
 // but for integration logic with DB, we setup the service with real repos.
 
 pub fn get_test_config() -> Config {
     // Load from environment (supports .env) to correct credentials
-    Config::from_env().expect("Failed to load configuration from environment")
+    let mut config = Config::from_env().expect("Failed to load configuration from environment");
+    // Most tests seed investors without a KYC verification; the KYC gate tests turn it back on
+    config.require_investor_kyc = false;
+    config
 }
 
 pub async fn setup_services(pool: PgPool) -> Arc<AuthService> {
+    setup_services_with_config(pool, get_test_config()).await
+}
+
+pub async fn setup_services_with_config(pool: PgPool, config: Config) -> Arc<AuthService> {
     // Run migrations to ensure schema exists
     crate::database::run_migrations(&pool)
         .await
@@ -27,11 +47,24 @@ pub async fn setup_services(pool: PgPool) -> Arc<AuthService> {
     let mitra_repo = Arc::new(MitraRepository::new(pool.clone()));
     let otp_repo = Arc::new(OtpRepository::new(pool.clone()));
 
-    let config = Arc::new(get_test_config());
+    let config = Arc::new(config);
     let jwt_manager = Arc::new(JwtManager::new(&config.jwt_secret, 24, 24));
 
     let email_service = Arc::new(EmailService::new(config.clone()));
-    let _pinata_service = Arc::new(PinataService::new(config.clone()));
+    let pinata_service = Arc::new(PinataService::new(config.clone()));
+    let blockchain_service = Arc::new(
+        BlockchainService::new(
+            config.clone(),
+            Arc::new(InvoiceRepository::new(pool.clone())),
+            Arc::new(FundingRepository::new(pool.clone())),
+            pinata_service,
+        )
+        .await
+        .expect("Failed to init blockchain service"),
+    );
+    let activity_logger = Arc::new(ActivityLogger::new(Arc::new(ActivityRepository::new(
+        pool.clone(),
+    ))));
 
     let otp_service = Arc::new(OtpService::new(
         otp_repo,
@@ -46,6 +79,8 @@ pub async fn setup_services(pool: PgPool) -> Arc<AuthService> {
         jwt_manager,
         otp_service,
         config,
+        blockchain_service,
+        activity_logger,
     ))
 }
 
@@ -123,7 +158,7 @@ async fn test_register_mitra_success() {
     let response = result.unwrap();
 
     assert_eq!(response.user.email.as_deref(), Some(email));
-    assert_eq!(response.user.role, "mitra");
+    assert_eq!(response.user.role, Role::Mitra);
 
     // 5. Verify MitraApplication exists in DB
     let app = sqlx::query_as::<_, MitraApplication>(
@@ -176,3 +211,452 @@ async fn test_register_fail_invalid_otp() {
     let result = auth_service.register(req).await;
     assert!(result.is_err());
 }
+
+/// Register a fresh mitra and return its login tokens
+async fn register_mitra_with_tokens(
+    pool: &PgPool,
+    auth_service: &AuthService,
+    email: &str,
+    username: &str,
+) -> LoginResponse {
+    let config = get_test_config();
+    sqlx::query("DELETE FROM users WHERE email = $1")
+        .bind(email)
+        .execute(pool)
+        .await
+        .ok();
+
+    let otp_token = JwtManager::new(&config.jwt_secret, 24, 24)
+        .generate_otp_token(email, "registration")
+        .expect("Failed to generate OTP token");
+
+    let req = RegisterRequest {
+        email: email.to_string(),
+        username: username.to_string(),
+        password: "password123".to_string(),
+        confirm_password: "password123".to_string(),
+        cooperative_agreement: true,
+        otp_token,
+        company_name: None,
+        company_type: None,
+        npwp: None,
+        annual_revenue: None,
+        address: None,
+        business_description: None,
+        website_url: None,
+        year_founded: None,
+        key_products: None,
+        export_markets: None,
+    };
+
+    auth_service
+        .register(req)
+        .await
+        .expect("Registration should succeed")
+}
+
+#[tokio::test]
+async fn test_tokens_revoked_after_password_change() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect to DB");
+
+    let auth_service = setup_services(pool.clone()).await;
+    let email = "test_token_revoke_pw@example.com";
+    let login = register_mitra_with_tokens(&pool, &auth_service, email, "revoke_pw").await;
+
+    assert!(auth_service.authenticate(&login.access_token).await.is_ok());
+
+    let new_hash = hash_password("newpassword123").unwrap();
+    UserRepository::new(pool.clone())
+        .update_password(login.user.id, &new_hash)
+        .await
+        .expect("Failed to change password");
+
+    let access = auth_service.authenticate(&login.access_token).await;
+    assert!(
+        matches!(access, Err(AppError::InvalidToken)),
+        "Access token issued before password change must be rejected: {:?}",
+        access
+    );
+    let refresh = auth_service.refresh_token(&login.refresh_token).await;
+    assert!(
+        matches!(refresh, Err(AppError::InvalidToken)),
+        "Refresh token issued before password change must be rejected: {:?}",
+        refresh
+    );
+
+    // Cleanup
+    sqlx::query("DELETE FROM users WHERE email = $1")
+        .bind(email)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_logout_revokes_tokens() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect to DB");
+
+    let auth_service = setup_services(pool.clone()).await;
+    let email = "test_token_revoke_logout@example.com";
+    let login = register_mitra_with_tokens(&pool, &auth_service, email, "revoke_logout").await;
+
+    auth_service
+        .logout(login.user.id)
+        .await
+        .expect("Logout failed");
+
+    assert!(auth_service.authenticate(&login.access_token).await.is_err());
+    assert!(auth_service.refresh_token(&login.refresh_token).await.is_err());
+
+    // Cleanup
+    sqlx::query("DELETE FROM users WHERE email = $1")
+        .bind(email)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+/// Escrow backed by a test-mode blockchain service, so payouts are simulated
+async fn setup_escrow_service(pool: &PgPool) -> EscrowService {
+    let mut config = get_test_config();
+    config.skip_blockchain_verification = true;
+    let config = Arc::new(config);
+
+    let blockchain_service = BlockchainService::new(
+        config.clone(),
+        Arc::new(InvoiceRepository::new(pool.clone())),
+        Arc::new(FundingRepository::new(pool.clone())),
+        Arc::new(PinataService::new(config)),
+    )
+    .await
+    .expect("Failed to init blockchain service");
+
+    let mut escrow_service = EscrowService::new();
+    escrow_service.set_blockchain_service(Arc::new(blockchain_service));
+    escrow_service
+}
+
+#[tokio::test]
+async fn test_investor_returns_go_to_verified_payout_wallet() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect to DB");
+
+    let auth_service = setup_services(pool.clone()).await;
+    let escrow_service = setup_escrow_service(&pool).await;
+    let user_repo = UserRepository::new(pool.clone());
+
+    let investor_id = create_investor(&pool, "investor_payout@test.com").await;
+    let investor = user_repo
+        .find_by_id(investor_id)
+        .await
+        .expect("Failed to load investor")
+        .expect("Investor not found");
+    let login_wallet = investor
+        .wallet_address
+        .clone()
+        .expect("Login wallet not set");
+
+    // Without a payout wallet, returns go to the login wallet
+    let payout = escrow_service
+        .release_to_investor(Uuid::new_v4(), &investor, Decimal::from(1_000_000))
+        .await
+        .expect("Release to login wallet failed");
+    assert_eq!(payout.wallet, login_wallet);
+
+    let wallet = LocalWallet::new(&mut rand::thread_rng());
+    let wallet_address = format!("{:?}", wallet.address());
+    let nonce = auth_service
+        .get_wallet_nonce(&wallet_address)
+        .await
+        .expect("Failed to get wallet nonce");
+    let signature = wallet
+        .sign_message(&nonce.message)
+        .await
+        .expect("Failed to sign nonce message");
+
+    let investor = auth_service
+        .set_payout_wallet(
+            investor_id,
+            ConnectWalletRequest {
+                wallet_address: wallet_address.clone(),
+                signature: format!("0x{}", signature),
+                message: nonce.message,
+                nonce: nonce.nonce,
+            },
+        )
+        .await
+        .expect("Setting payout wallet failed");
+    assert_eq!(
+        investor.wallet_address.as_deref(),
+        Some(login_wallet.as_str())
+    );
+    assert_eq!(
+        investor.payout_wallet_address.as_deref(),
+        Some(wallet_address.to_lowercase().as_str())
+    );
+    assert!(investor.payout_wallet_verified_at.is_some());
+
+    let payout = escrow_service
+        .release_to_investor(Uuid::new_v4(), &investor, Decimal::from(1_000_000))
+        .await
+        .expect("Release to payout wallet failed");
+    assert_eq!(payout.wallet, wallet_address.to_lowercase());
+    assert_ne!(payout.wallet, login_wallet);
+
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(investor_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_payout_to_unverified_wallet_rejected() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect to DB");
+
+    let auth_service = setup_services(pool.clone()).await;
+    let escrow_service = setup_escrow_service(&pool).await;
+
+    let investor_id = create_investor(&pool, "investor_payout_unverified@test.com").await;
+
+    // A signature from a different wallet does not verify the payout wallet
+    let wallet = LocalWallet::new(&mut rand::thread_rng());
+    let other_wallet = LocalWallet::new(&mut rand::thread_rng());
+    let wallet_address = format!("{:?}", wallet.address());
+    let nonce = auth_service
+        .get_wallet_nonce(&wallet_address)
+        .await
+        .expect("Failed to get wallet nonce");
+    let signature = other_wallet
+        .sign_message(&nonce.message)
+        .await
+        .expect("Failed to sign nonce message");
+    let result = auth_service
+        .set_payout_wallet(
+            investor_id,
+            ConnectWalletRequest {
+                wallet_address: wallet_address.clone(),
+                signature: format!("0x{}", signature),
+                message: nonce.message,
+                nonce: nonce.nonce,
+            },
+        )
+        .await;
+    assert!(matches!(result, Err(AppError::InvalidCredentials)));
+
+    // An address stored without verification is never paid
+    let investor = sqlx::query_as::<_, crate::models::User>(
+        "UPDATE users SET payout_wallet_address = $1 WHERE id = $2 RETURNING *",
+    )
+    .bind(wallet_address.to_lowercase())
+    .bind(investor_id)
+    .fetch_one(&pool)
+    .await
+    .expect("Failed to set unverified payout wallet");
+
+    let result = escrow_service
+        .release_to_investor(Uuid::new_v4(), &investor, Decimal::from(1_000_000))
+        .await;
+    assert!(
+        matches!(result, Err(AppError::Forbidden(_))),
+        "Payout to unverified wallet should be rejected: {:?}",
+        result
+    );
+
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(investor_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+/// Sign a fresh wallet login for a random, never-seen wallet
+async fn unknown_wallet_login(auth_service: &AuthService) -> (String, WalletLoginRequest) {
+    let wallet = LocalWallet::new(&mut rand::thread_rng());
+    let wallet_address = format!("{:?}", wallet.address());
+    let nonce = auth_service
+        .get_wallet_nonce(&wallet_address)
+        .await
+        .expect("Failed to get wallet nonce");
+    let signature = wallet
+        .sign_message(&nonce.message)
+        .await
+        .expect("Failed to sign nonce message");
+
+    let req = WalletLoginRequest {
+        wallet_address: wallet_address.clone(),
+        signature: format!("0x{}", signature),
+        message: nonce.message,
+        nonce: nonce.nonce,
+    };
+    (wallet_address.to_lowercase(), req)
+}
+
+#[tokio::test]
+async fn test_wallet_login_autoregisters_unknown_wallet_by_default() {
+    let mut config = get_test_config();
+    config.allow_wallet_autoregister = true;
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect to DB");
+    let auth_service = setup_services_with_config(pool.clone(), config).await;
+
+    let (wallet_address, req) = unknown_wallet_login(&auth_service).await;
+    let login = auth_service
+        .wallet_login(req)
+        .await
+        .expect("Wallet login should create an investor");
+    assert_eq!(login.user.role, Role::Investor);
+    assert_eq!(
+        login.user.wallet_address.as_deref(),
+        Some(wallet_address.as_str())
+    );
+
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(login.user.id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_wallet_login_rejects_unknown_wallet_when_autoregister_disabled() {
+    let mut config = get_test_config();
+    config.allow_wallet_autoregister = false;
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect to DB");
+    let auth_service = setup_services_with_config(pool.clone(), config).await;
+
+    let (wallet_address, req) = unknown_wallet_login(&auth_service).await;
+    let result = auth_service.wallet_login(req).await;
+    assert!(matches!(result, Err(AppError::Forbidden(_))));
+
+    let user = UserRepository::new(pool.clone())
+        .find_by_wallet(&wallet_address)
+        .await
+        .expect("Failed to look up wallet");
+    assert!(user.is_none(), "No account should have been created");
+}
+
+#[tokio::test]
+async fn test_wallet_investors_sharing_address_prefix_both_created() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect to DB");
+    let user_repo = UserRepository::new(pool.clone());
+
+    // Same first 8 hex chars, different remainder
+    let wallet_with_prefix =
+        |prefix: &str| format!("0x{}{}", prefix, &Uuid::new_v4().simple().to_string()[..32]);
+    let first_wallet = wallet_with_prefix("abcdef01");
+    let second_wallet = wallet_with_prefix("abcdef01");
+
+    let first = user_repo
+        .create_investor_with_wallet(&first_wallet)
+        .await
+        .expect("First wallet investor failed");
+    let second = user_repo
+        .create_investor_with_wallet(&second_wallet)
+        .await
+        .expect("Second wallet investor with same prefix failed");
+    assert_ne!(first.id, second.id);
+    assert_ne!(first.username, second.username);
+    assert_eq!(first.username.as_deref(), Some(first_wallet.as_str()));
+
+    // A username already holding the wallet address does not block the wallet
+    let taken_wallet = wallet_with_prefix("12345678");
+    let squatter =
+        create_investor(&pool, &format!("squatter_{}@test.com", &taken_wallet[2..])).await;
+    sqlx::query("UPDATE users SET username = $1 WHERE id = $2")
+        .bind(&taken_wallet)
+        .bind(squatter)
+        .execute(&pool)
+        .await
+        .expect("Failed to take username");
+    let third = user_repo
+        .create_investor_with_wallet(&taken_wallet)
+        .await
+        .expect("Wallet investor with taken username failed");
+    assert_eq!(third.wallet_address.as_deref(), Some(taken_wallet.as_str()));
+    let suffixed = third.username.expect("Suffixed username expected");
+    assert!(suffixed.starts_with(&format!("{}_", taken_wallet)));
+    assert!(suffixed.len() <= 50);
+
+    // The wallet itself stays unique: a second create resolves to the same account
+    let duplicate = user_repo
+        .create_investor_with_wallet(&first_wallet)
+        .await
+        .expect("Existing wallet should resolve to its account");
+    assert_eq!(duplicate.id, first.id);
+
+    for id in [first.id, second.id, third.id, squatter] {
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_concurrent_first_wallet_logins_resolve_to_one_user() {
+    let mut config = get_test_config();
+    config.allow_wallet_autoregister = true;
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect to DB");
+    let auth_service = setup_services_with_config(pool.clone(), config).await;
+
+    // Two first logins that both missed `find_by_wallet` create the account at
+    // once, each on its own connection
+    let wallet = LocalWallet::new(&mut rand::thread_rng());
+    let wallet_address = format!("{:?}", wallet.address());
+    let first_repo = UserRepository::new(pool.clone());
+    let second_repo = UserRepository::new(pool.clone());
+    let (first, second) = tokio::join!(
+        first_repo.create_investor_with_wallet(&wallet_address),
+        second_repo.create_investor_with_wallet(&wallet_address),
+    );
+    let first = first.expect("First login failed");
+    let second = second.expect("Second login failed");
+    assert_eq!(first.id, second.id);
+
+    // The wallet signs in to that one account
+    let nonce = auth_service
+        .get_wallet_nonce(&wallet_address)
+        .await
+        .expect("Failed to get wallet nonce");
+    let signature = wallet
+        .sign_message(&nonce.message)
+        .await
+        .expect("Failed to sign nonce message");
+    let login = auth_service
+        .wallet_login(WalletLoginRequest {
+            wallet_address: wallet_address.clone(),
+            signature: format!("0x{}", signature),
+            message: nonce.message,
+            nonce: nonce.nonce,
+        })
+        .await
+        .expect("Wallet login failed");
+    assert_eq!(login.user.id, first.id);
+
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(first.id)
+        .execute(&pool)
+        .await
+        .ok();
+}
@@ -0,0 +1,450 @@
+use ethers::abi::{self, Token};
+use ethers::providers::Provider;
+use ethers::types::{Address, Bytes, Filter, Log, H256, U256};
+use futures_util::future::BoxFuture;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::config::{Config, TokenDeployment};
+use crate::database::create_redis_pool;
+use crate::error::{AppError, AppResult};
+use crate::repository::{FundingRepository, InvoiceRepository};
+use crate::services::{
+    decode_revert_reason, fetch_erc20_balances, fetch_platform_fee_bps, fetch_transfer_logs,
+    paginate_transfers, BlockchainService, ContractSignatureValidator, JsonPinner, PinataService,
+    TransferRecord,
+};
+
+use super::auth_test::get_test_config;
+use super::funding_test::{create_mitra_and_invoice, setup_funding_service};
+
+/// Encode a Multicall3 `aggregate3` return value holding one successful `balanceOf` per balance
+fn mock_aggregate3_response(balances: &[u64]) -> Bytes {
+    let results = balances
+        .iter()
+        .map(|balance| {
+            Token::Tuple(vec![
+                Token::Bool(true),
+                Token::Bytes(abi::encode(&[Token::Uint(U256::from(*balance))])),
+            ])
+        })
+        .collect();
+    Bytes::from(abi::encode(&[Token::Array(results)]))
+}
+
+#[tokio::test]
+async fn test_fetch_balances_in_single_multicall() {
+    let (provider, mock) = Provider::mocked();
+    // Only one response is queued: a per-address lookup would run out of responses
+    mock.push::<Bytes, _>(mock_aggregate3_response(&[150_000, 0, 42]))
+        .unwrap();
+
+    let holders = vec![
+        Address::from_low_u64_be(1),
+        Address::from_low_u64_be(2),
+        Address::from_low_u64_be(3),
+    ];
+    let balances = fetch_erc20_balances(
+        &provider,
+        Address::from_low_u64_be(0xca11),
+        Address::from_low_u64_be(0x1d7),
+        &holders,
+    )
+    .await
+    .expect("Batched balance lookup failed");
+
+    assert_eq!(
+        balances,
+        vec![U256::from(150_000), U256::zero(), U256::from(42)]
+    );
+}
+
+#[tokio::test]
+async fn test_fetch_balances_rejects_short_multicall_response() {
+    let (provider, mock) = Provider::mocked();
+    mock.push::<Bytes, _>(mock_aggregate3_response(&[1])).unwrap();
+
+    let holders = vec![Address::from_low_u64_be(1), Address::from_low_u64_be(2)];
+    let result = fetch_erc20_balances(
+        &provider,
+        Address::from_low_u64_be(0xca11),
+        Address::from_low_u64_be(0x1d7),
+        &holders,
+    )
+    .await;
+
+    assert!(result.is_err(), "Result count must match the request");
+}
+
+#[tokio::test]
+async fn test_platform_fee_bps_read_from_pool_contract() {
+    let (provider, mock) = Provider::mocked();
+    mock.push::<Bytes, _>(Bytes::from(abi::encode(&[Token::Uint(U256::from(250))])))
+        .unwrap();
+
+    let fee_bps = fetch_platform_fee_bps(Arc::new(provider), Address::from_low_u64_be(0x9001))
+        .await
+        .expect("Fee lookup failed");
+    assert_eq!(fee_bps, 250);
+
+    let (provider, mock) = Provider::mocked();
+    mock.push::<Bytes, _>(Bytes::from(abi::encode(&[Token::Uint(U256::MAX)])))
+        .unwrap();
+    let result = fetch_platform_fee_bps(Arc::new(provider), Address::from_low_u64_be(0x9001)).await;
+    assert!(matches!(result, Err(AppError::BlockchainError(_))));
+}
+
+#[tokio::test]
+async fn test_transfer_logs_query_requested_block_range() {
+    let (provider, mock) = Provider::mocked();
+    mock.push::<Vec<Log>, _>(Vec::new()).unwrap();
+    mock.push::<Vec<Log>, _>(Vec::new()).unwrap();
+
+    let token = Address::from_low_u64_be(0x1d7);
+    let holder = Address::from_low_u64_be(0xabc);
+    let logs = fetch_transfer_logs(&provider, token, holder, 100, 200)
+        .await
+        .expect("Transfer log lookup failed");
+    assert!(logs.is_empty());
+
+    let base_filter = Filter::new()
+        .address(token)
+        .event("Transfer(address,address,uint256)")
+        .from_block(100u64)
+        .to_block(200u64);
+    mock.assert_request(
+        "eth_getLogs",
+        [base_filter.clone().topic2(H256::from(holder))],
+    )
+    .expect("Incoming transfer filter mismatch");
+    mock.assert_request("eth_getLogs", [base_filter.topic1(H256::from(holder))])
+        .expect("Outgoing transfer filter mismatch");
+}
+
+fn transfer_at(block_number: u64, log_index: u64) -> TransferRecord {
+    TransferRecord {
+        tx_hash: format!("0x{:064x}", block_number * 100 + log_index),
+        block_number,
+        log_index,
+        from: format!("{:?}", Address::from_low_u64_be(1)),
+        to: format!("{:?}", Address::from_low_u64_be(2)),
+        amount: 1.0,
+        direction: "incoming",
+        explorer_url: String::new(),
+    }
+}
+
+#[test]
+fn test_transfer_page_is_newest_first_and_ends_on_block_boundary() {
+    let transfers = vec![
+        transfer_at(120, 0),
+        transfer_at(105, 1),
+        transfer_at(130, 0),
+        transfer_at(105, 0),
+        transfer_at(120, 3),
+    ];
+
+    // Block 120 doesn't fit entirely, so the page stops before it
+    let (page, next_from_block) = paginate_transfers(transfers.clone(), 3, 200, 500);
+    let blocks: Vec<u64> = page.iter().map(|t| t.block_number).collect();
+    assert_eq!(blocks, vec![105, 105]);
+    assert_eq!(page[0].log_index, 1);
+    assert_eq!(next_from_block, Some(120));
+
+    let (page, next_from_block) = paginate_transfers(transfers, 10, 200, 500);
+    let blocks: Vec<u64> = page.iter().map(|t| t.block_number).collect();
+    assert_eq!(blocks, vec![130, 120, 120, 105, 105]);
+    assert_eq!(next_from_block, Some(201));
+}
+
+#[test]
+fn test_last_transfer_page_has_no_cursor() {
+    let (page, next_from_block) = paginate_transfers(vec![transfer_at(150, 0)], 10, 200, 200);
+    assert_eq!(page.len(), 1);
+    assert_eq!(next_from_block, None);
+}
+
+async fn blockchain_service_with(config: Config) -> BlockchainService {
+    let config = Arc::new(config);
+    let pool = PgPool::connect_lazy(&config.database_url).expect("Invalid database URL");
+    BlockchainService::new(
+        config.clone(),
+        Arc::new(InvoiceRepository::new(pool.clone())),
+        Arc::new(FundingRepository::new(pool)),
+        Arc::new(PinataService::new(config)),
+    )
+    .await
+    .expect("Failed to create blockchain service")
+}
+
+#[tokio::test]
+async fn test_paused_primary_token_switches_to_fallback() {
+    let primary = format!("{:?}", Address::from_low_u64_be(0x1d7));
+    let fallback = format!("{:?}", Address::from_low_u64_be(0xfa11));
+    let fallback_pool = format!("{:?}", Address::from_low_u64_be(0xfa12));
+    let fallback_nft = format!("{:?}", Address::from_low_u64_be(0xfa13));
+
+    let mut config = get_test_config();
+    config.idrx_token_contract_addr = primary.clone();
+    config.idrx_fallback_tokens = vec![TokenDeployment {
+        chain_id: 84532,
+        contract_addr: fallback.clone(),
+        pool_contract_addr: fallback_pool.clone(),
+        nft_contract_addr: fallback_nft.clone(),
+        rpc_url: "https://sepolia.base.org".to_string(),
+    }];
+    config.idrx_paused_tokens = Vec::new();
+    config
+        .validate_idrx_tokens()
+        .expect("Token config should be valid");
+
+    let service = blockchain_service_with(config.clone()).await;
+    assert_eq!(service.active_idrx_token().contract_addr, primary);
+    assert_eq!(
+        service.idrx_contract_address().unwrap(),
+        Address::from_low_u64_be(0x1d7)
+    );
+
+    // Pausing the primary token moves balance and transfer calls to the fallback
+    config.idrx_paused_tokens = vec![primary.clone()];
+    let service = blockchain_service_with(config.clone()).await;
+    assert_eq!(service.active_idrx_token().chain_id, 84532);
+    assert_eq!(
+        service.idrx_contract_address().unwrap(),
+        Address::from_low_u64_be(0xfa11)
+    );
+    // The pool and NFT contracts move to the fallback chain with the token
+    assert_eq!(service.pool_contract_addr(), fallback_pool);
+    assert_eq!(service.get_contract_address(), fallback_nft);
+
+    // A fallback without its own pool contract is rejected at startup
+    config.idrx_fallback_tokens[0].pool_contract_addr = String::new();
+    assert!(config.validate_idrx_tokens().is_err());
+    config.idrx_fallback_tokens[0].pool_contract_addr = fallback_pool;
+
+    // Malformed fallback addresses are rejected at startup
+    config.idrx_fallback_tokens[0].contract_addr = "0xnot-an-address".to_string();
+    assert!(config.validate_idrx_tokens().is_err());
+}
+
+/// Counts uploads and hands out a fresh CID for each
+struct CountingPinner {
+    uploads: AtomicUsize,
+}
+
+impl JsonPinner for CountingPinner {
+    fn pin_json<'a>(
+        &'a self,
+        _content: &'a serde_json::Value,
+        _name: &'a str,
+    ) -> BoxFuture<'a, AppResult<String>> {
+        self.uploads.fetch_add(1, Ordering::SeqCst);
+        Box::pin(async move { Ok(format!("QmMetadata{}", Uuid::new_v4().simple())) })
+    }
+}
+
+#[tokio::test]
+async fn test_identical_nft_metadata_is_uploaded_once() {
+    let mut config = get_test_config();
+    config.skip_blockchain_verification = false;
+    config.pinata_gateway_url = String::new();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (_, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_nft_metadata@test.com").await;
+
+    let config = Arc::new(config);
+    let pinner = Arc::new(CountingPinner {
+        uploads: AtomicUsize::new(0),
+    });
+    let pinata_service = PinataService::new(config.clone()).with_json_pinner(pinner.clone());
+    let service = BlockchainService::new(
+        config,
+        Arc::new(InvoiceRepository::new(pool.clone())),
+        Arc::new(FundingRepository::new(pool.clone())),
+        Arc::new(pinata_service),
+    )
+    .await
+    .expect("Failed to create blockchain service");
+
+    let first_uri = service
+        .create_nft_metadata(invoice_id)
+        .await
+        .expect("First metadata upload failed");
+    // A mint retry builds the same metadata again
+    let retry_uri = service
+        .create_nft_metadata(invoice_id)
+        .await
+        .expect("Retried metadata upload failed");
+
+    assert_eq!(pinner.uploads.load(Ordering::SeqCst), 1);
+    assert_eq!(first_uri, retry_uri);
+    assert!(first_uri.starts_with("https://gateway.pinata.cloud/ipfs/QmMetadata"));
+
+    let cid = first_uri.rsplit('/').next().unwrap_or_default();
+    sqlx::query("DELETE FROM nft_metadata_cache WHERE cid = $1")
+        .bind(cid)
+        .execute(&pool)
+        .await
+        .ok();
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+/// Stands in for the RPC node: counts on-chain checks and returns a fixed verdict
+struct CountingSignatureValidator {
+    valid: bool,
+    calls: AtomicUsize,
+}
+
+impl ContractSignatureValidator for CountingSignatureValidator {
+    fn is_valid_signature<'a>(
+        &'a self,
+        _signer: Address,
+        _message_hash: [u8; 32],
+        _signature: &'a [u8],
+    ) -> BoxFuture<'a, AppResult<bool>> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        Box::pin(async move { Ok(self.valid) })
+    }
+}
+
+/// Blockchain service whose smart wallet checks go to a counting validator,
+/// with verifications cached in the configured Redis
+async fn smart_wallet_service(valid: bool) -> (BlockchainService, Arc<CountingSignatureValidator>) {
+    let config = get_test_config();
+    let redis_pool = create_redis_pool(&config)
+        .await
+        .expect("Failed to connect to Redis");
+    let validator = Arc::new(CountingSignatureValidator {
+        valid,
+        calls: AtomicUsize::new(0),
+    });
+    let service = blockchain_service_with(config)
+        .await
+        .with_signature_validator(validator.clone())
+        .with_redis(Some(redis_pool));
+    (service, validator)
+}
+
+/// A smart wallet login: the signature is not 65 bytes, so the EOA check is skipped
+fn smart_wallet_login() -> (String, String, String) {
+    let wallet = format!("{:?}", Address::random());
+    let signature = format!("0x{}", "ab".repeat(96));
+    let message = format!("Sign in to VESSEL\nNonce: {}", Uuid::new_v4());
+    (wallet, signature, message)
+}
+
+#[tokio::test]
+async fn test_erc1271_verification_is_cached_within_ttl() {
+    let (service, validator) = smart_wallet_service(true).await;
+    let (wallet, signature, message) = smart_wallet_login();
+
+    assert!(service
+        .verify_wallet_signature(&wallet, &signature, &message)
+        .await
+        .expect("First verification failed"));
+    // A retried login within the TTL is answered from the cache
+    assert!(service
+        .verify_wallet_signature(&wallet, &signature, &message)
+        .await
+        .expect("Retried verification failed"));
+    assert_eq!(validator.calls.load(Ordering::SeqCst), 1);
+
+    // Another message needs its own on-chain check
+    let (_, _, other_message) = smart_wallet_login();
+    service
+        .verify_wallet_signature(&wallet, &signature, &other_message)
+        .await
+        .expect("Verification of another message failed");
+    assert_eq!(validator.calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_failed_erc1271_verification_is_not_cached() {
+    let (service, validator) = smart_wallet_service(false).await;
+    let (wallet, signature, message) = smart_wallet_login();
+
+    for _ in 0..2 {
+        let verified = service
+            .verify_wallet_signature(&wallet, &signature, &message)
+            .await
+            .expect("Verification errored");
+        assert!(!verified);
+    }
+    assert_eq!(validator.calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_configured_idrx_decimals_drive_token_units() {
+    let mut config = get_test_config();
+    config.skip_blockchain_verification = true;
+    config.idrx_decimals = 18;
+
+    // Test mode takes the configured decimals without asking the contract
+    let service = blockchain_service_with(config.clone()).await;
+    assert_eq!(service.idrx_decimals(), 18);
+    assert_eq!(
+        service.to_token_units(Decimal::new(15, 1)).unwrap(),
+        U256::from(1_500_000_000_000_000_000u128)
+    );
+    assert_eq!(
+        service.to_token_units(Decimal::from(100_000_000)).unwrap(),
+        U256::from(100_000_000u128) * U256::exp10(18)
+    );
+    // Beyond u128 once scaled, which used to come out as zero
+    assert_eq!(
+        service.to_token_units(Decimal::MAX).unwrap(),
+        U256::from(Decimal::MAX.mantissa() as u128) * U256::exp10(18)
+    );
+    assert!(matches!(
+        service.to_token_units(Decimal::from(-1)),
+        Err(AppError::ValidationError(_))
+    ));
+
+    config.idrx_decimals = 2;
+    let service = blockchain_service_with(config).await;
+    assert_eq!(
+        service.to_token_units(Decimal::new(15, 1)).unwrap(),
+        U256::from(150)
+    );
+    // Amounts below the token's precision are truncated
+    assert_eq!(
+        service.to_token_units(Decimal::new(1999, 3)).unwrap(),
+        U256::from(199)
+    );
+}
+
+#[test]
+fn test_revert_reason_decodes_solidity_error_string() {
+    // `require(false, "Pool is not open")` as returned in the error data of eth_call
+    let data = hex::decode(concat!(
+        "08c379a0",
+        "0000000000000000000000000000000000000000000000000000000000000020",
+        "0000000000000000000000000000000000000000000000000000000000000010",
+        "506f6f6c206973206e6f74206f70656e00000000000000000000000000000000",
+    ))
+    .unwrap();
+    assert_eq!(
+        decode_revert_reason(&data).as_deref(),
+        Some("Pool is not open")
+    );
+
+    // Panic(uint256) and bare or truncated data carry no message
+    let panic = [
+        &[0x4e, 0x48, 0x7b, 0x71][..],
+        &abi::encode(&[Token::Uint(U256::from(0x11))]),
+    ]
+    .concat();
+    assert_eq!(decode_revert_reason(&panic), None);
+    assert_eq!(decode_revert_reason(&[]), None);
+    assert_eq!(decode_revert_reason(&data[..40]), None);
+}
@@ -0,0 +1,62 @@
+use actix_web::{http::header, test, web, App, HttpResponse};
+
+use crate::middleware::compression;
+use crate::utils::ApiResponse;
+
+/// Stand-in for a large list endpoint such as the marketplace
+async fn large_list() -> HttpResponse {
+    let items: Vec<serde_json::Value> = (0..500)
+        .map(|i| {
+            serde_json::json!({
+                "id": i,
+                "invoice_number": format!("INV-2024-{:05}", i),
+                "status": "open",
+                "target_amount": "100000000.00",
+            })
+        })
+        .collect();
+    HttpResponse::Ok().json(ApiResponse::paginated(items, 500, 1, 500))
+}
+
+#[actix_web::test]
+async fn test_large_response_compressed_when_client_accepts_gzip() {
+    let app = test::init_service(
+        App::new()
+            .wrap(compression(true))
+            .route("/marketplace", web::get().to(large_list)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/marketplace")
+        .insert_header((header::ACCEPT_ENCODING, "gzip"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert!(resp.status().is_success());
+    assert_eq!(
+        resp.headers()
+            .get(header::CONTENT_ENCODING)
+            .and_then(|v| v.to_str().ok()),
+        Some("gzip")
+    );
+}
+
+#[actix_web::test]
+async fn test_compression_disabled_by_config() {
+    let app = test::init_service(
+        App::new()
+            .wrap(compression(false))
+            .route("/marketplace", web::get().to(large_list)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/marketplace")
+        .insert_header((header::ACCEPT_ENCODING, "gzip"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert!(resp.status().is_success());
+    assert!(resp.headers().get(header::CONTENT_ENCODING).is_none());
+}
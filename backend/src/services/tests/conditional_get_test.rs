@@ -0,0 +1,77 @@
+use actix_web::{http::header, http::StatusCode, test, web, App, HttpRequest, HttpResponse};
+use chrono::{NaiveDate, NaiveDateTime};
+use std::sync::RwLock;
+
+use crate::utils::{conditional_json, ApiResponse};
+
+fn version(second: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2024, 5, 1)
+        .unwrap()
+        .and_hms_opt(12, 0, second)
+        .unwrap()
+}
+
+/// Stand-in for a detail endpoint whose entity was last updated at the shared version
+async fn detail(req: HttpRequest, updated_at: web::Data<RwLock<NaiveDateTime>>) -> HttpResponse {
+    let updated_at = *updated_at.read().unwrap();
+    conditional_json(
+        &req,
+        &[updated_at],
+        ApiResponse::success(serde_json::json!({ "id": 1 }), "Detail retrieved"),
+    )
+}
+
+#[actix_web::test]
+async fn test_matching_etag_returns_not_modified_without_body() {
+    let updated_at = web::Data::new(RwLock::new(version(0)));
+    let app = test::init_service(
+        App::new()
+            .app_data(updated_at.clone())
+            .route("/detail", web::get().to(detail)),
+    )
+    .await;
+
+    let resp = test::call_service(&app, test::TestRequest::get().uri("/detail").to_request()).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let etag = resp
+        .headers()
+        .get(header::ETAG)
+        .and_then(|v| v.to_str().ok())
+        .expect("ETag header")
+        .to_string();
+    assert!(etag.starts_with("W/\""), "ETag should be weak: {}", etag);
+    assert!(resp.headers().contains_key(header::LAST_MODIFIED));
+    assert!(!test::read_body(resp).await.is_empty());
+
+    // Same version: 304 with the validator and no body
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/detail")
+            .insert_header((header::IF_NONE_MATCH, etag.clone()))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(
+        resp.headers().get(header::ETAG).and_then(|v| v.to_str().ok()),
+        Some(etag.as_str())
+    );
+    assert!(test::read_body(resp).await.is_empty());
+
+    // The entity changes: the old ETag no longer matches
+    *updated_at.write().unwrap() = version(1);
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/detail")
+            .insert_header((header::IF_NONE_MATCH, etag.clone()))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_ne!(
+        resp.headers().get(header::ETAG).and_then(|v| v.to_str().ok()),
+        Some(etag.as_str())
+    );
+}
@@ -0,0 +1,181 @@
+use crate::config::{Config, TrustedProxy};
+use crate::error::AppError;
+
+use super::auth_test::get_test_config;
+
+/// Test config with every validated setting overridden to a sane value
+fn valid_config() -> Config {
+    let mut config = get_test_config();
+    config.jwt_secret = "a".repeat(32);
+    config.quote_signing_secret = "b".repeat(32);
+    config.chain_id = 84532;
+    config.invoice_nft_contract_addr = "0x1111111111111111111111111111111111111111".to_string();
+    config.invoice_pool_contract_addr = "0x2222222222222222222222222222222222222222".to_string();
+    config.idrx_token_contract_addr = "0x3333333333333333333333333333333333333333".to_string();
+    config.idrx_fallback_tokens = Vec::new();
+    config.idrx_decimals = 2;
+    config.min_disbursement_ratio = 1.0;
+    config.platform_wallet_address = String::new();
+    config.offramp_wallet_address = String::new();
+    config.multicall3_addr = "0xcA11bde05977b3631167028862bE2a173976CA11".to_string();
+    config.jwt_expiry_hours = 24;
+    config.jwt_refresh_expiry_hours = 168;
+    config.quote_ttl_minutes = 30;
+    config.idempotency_key_ttl_hours = 24;
+    config.importer_access_token_ttl_minutes = 30;
+    config.importer_overdue_scan_interval_minutes = 60;
+    config.invoice_maturity_scan_interval_minutes = 60;
+    config.invoice_default_grace_days = 30;
+    config.onchain_audit_lookback_hours = 24;
+    config.otp_expiry_minutes = 5;
+    config.email_max_attempts = 5;
+    config.email_retry_base_secs = 60;
+    config.email_retry_interval_secs = 30;
+    config.webhook_url = String::new();
+    config.cors_allowed_methods = vec!["GET".to_string(), "POST".to_string()];
+    config.cors_allowed_headers = vec!["Content-Type".to_string()];
+    config.rate_limit_client_ip_header = None;
+    config.rate_limit_trusted_proxies = Vec::new();
+    config
+}
+
+fn assert_invalid(config: &Config, key: &str) {
+    match config.validate() {
+        Err(AppError::ValidationError(msg)) => assert!(msg.contains(key), "{}", msg),
+        other => panic!("Expected a validation error for {}, got {:?}", key, other),
+    }
+}
+
+#[test]
+fn test_valid_config_passes() {
+    valid_config().validate().expect("Config should be valid");
+}
+
+#[test]
+fn test_rejects_malformed_contract_address() {
+    let mut config = valid_config();
+    config.invoice_nft_contract_addr = "0x1234".to_string();
+    assert_invalid(&config, "INVOICE_NFT_CONTRACT_ADDRESS");
+
+    // Hex without the 0x prefix is not accepted either
+    let mut config = valid_config();
+    config.invoice_pool_contract_addr = "2222222222222222222222222222222222222222".to_string();
+    assert_invalid(&config, "FUNDING_POOL_CONTRACT_ADDRESS");
+}
+
+#[test]
+fn test_rejects_unparseable_platform_wallet() {
+    let mut config = valid_config();
+    config.platform_wallet_address = "0xNotAWalletAddressAtAll00000000000000000".to_string();
+    assert_invalid(&config, "PLATFORM_WALLET_ADDRESS");
+}
+
+#[test]
+fn test_rejects_short_secrets() {
+    let mut config = valid_config();
+    config.jwt_secret = "secret".to_string();
+    assert_invalid(&config, "JWT_SECRET");
+
+    let mut config = valid_config();
+    config.quote_signing_secret = "short".to_string();
+    assert_invalid(&config, "QUOTE_SIGNING_SECRET");
+
+    // Quotes are never signed with the JWT key
+    let mut config = valid_config();
+    config.quote_signing_secret = String::new();
+    assert_invalid(&config, "QUOTE_SIGNING_SECRET");
+
+    let mut config = valid_config();
+    config.quote_signing_secret = config.jwt_secret.clone();
+    assert_invalid(&config, "QUOTE_SIGNING_SECRET");
+
+    let mut config = valid_config();
+    config.webhook_url = "https://hooks.example.com/vessel".to_string();
+    config.webhook_secret = String::new();
+    assert_invalid(&config, "WEBHOOK_SECRET");
+}
+
+#[test]
+fn test_rejects_non_positive_expiries() {
+    let mut config = valid_config();
+    config.jwt_expiry_hours = 0;
+    assert_invalid(&config, "JWT_EXPIRY_HOURS");
+
+    let mut config = valid_config();
+    config.otp_expiry_minutes = -5;
+    assert_invalid(&config, "OTP_EXPIRY_MINUTES");
+
+    let mut config = valid_config();
+    config.jwt_refresh_expiry_hours = 1;
+    assert_invalid(&config, "JWT_REFRESH_EXPIRY_HOURS");
+}
+
+#[test]
+fn test_rejects_zero_email_retry_interval() {
+    let mut config = valid_config();
+    config.email_retry_interval_secs = 0;
+    assert_invalid(&config, "EMAIL_RETRY_INTERVAL_SECS");
+}
+
+#[test]
+fn test_rejects_zero_chain_id() {
+    let mut config = valid_config();
+    config.chain_id = 0;
+    assert_invalid(&config, "CHAIN_ID");
+}
+
+#[test]
+fn test_rejects_invalid_cors_lists() {
+    let mut config = valid_config();
+    config.cors_allowed_methods = vec!["GET".to_string(), "FETCH".to_string()];
+    assert_invalid(&config, "CORS_ALLOWED_METHODS");
+
+    let mut config = valid_config();
+    config.cors_allowed_methods = Vec::new();
+    assert_invalid(&config, "CORS_ALLOWED_METHODS");
+
+    let mut config = valid_config();
+    config.cors_allowed_headers = vec!["X Request Id".to_string()];
+    assert_invalid(&config, "CORS_ALLOWED_HEADERS");
+}
+
+#[test]
+fn test_platform_fee_percentage_follows_bps() {
+    let mut config = valid_config();
+    config.platform_fee_bps = 250;
+    assert_eq!(config.platform_fee_percentage(), 2.5);
+}
+
+#[test]
+fn test_client_ip_header_requires_trusted_proxies() {
+    let mut config = valid_config();
+    config.rate_limit_client_ip_header = Some("Fly-Client-IP".to_string());
+    assert_invalid(&config, "RATE_LIMIT_CLIENT_IP_HEADER");
+
+    config.rate_limit_trusted_proxies = vec!["172.16.0.0/12".parse().unwrap()];
+    config
+        .validate()
+        .expect("Header with trusted proxies should be valid");
+
+    config.rate_limit_client_ip_header = Some("Client IP".to_string());
+    assert_invalid(&config, "RATE_LIMIT_CLIENT_IP_HEADER");
+}
+
+#[test]
+fn test_trusted_proxy_ranges() {
+    let range: TrustedProxy = "172.16.0.0/12".parse().unwrap();
+    assert!(range.contains("172.16.0.1".parse().unwrap()));
+    assert!(range.contains("172.31.255.255".parse().unwrap()));
+    assert!(!range.contains("172.32.0.1".parse().unwrap()));
+    assert!(!range.contains("::1".parse().unwrap()));
+
+    let single: TrustedProxy = "fdaa::3".parse().unwrap();
+    assert!(single.contains("fdaa::3".parse().unwrap()));
+    assert!(!single.contains("fdaa::4".parse().unwrap()));
+
+    let any: TrustedProxy = "0.0.0.0/0".parse().unwrap();
+    assert!(any.contains("203.0.113.7".parse().unwrap()));
+
+    assert!("10.0.0.0/33".parse::<TrustedProxy>().is_err());
+    assert!("not-an-ip".parse::<TrustedProxy>().is_err());
+}
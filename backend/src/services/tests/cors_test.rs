@@ -0,0 +1,66 @@
+use actix_web::{http::header, test, web, App, HttpResponse};
+
+use crate::middleware::cors;
+
+use super::auth_test::get_test_config;
+
+async fn ok() -> HttpResponse {
+    HttpResponse::Ok().finish()
+}
+
+#[actix_web::test]
+async fn test_configured_header_in_preflight_allow_list() {
+    let mut config = get_test_config();
+    config.cors_allowed_origins = "http://localhost:3000".to_string();
+    config.cors_allowed_headers = vec!["Content-Type".to_string(), "X-Request-Id".to_string()];
+
+    let app = test::init_service(
+        App::new()
+            .wrap(cors(&config))
+            .route("/invoices", web::post().to(ok)),
+    )
+    .await;
+
+    let req = test::TestRequest::default()
+        .method(actix_web::http::Method::OPTIONS)
+        .uri("/invoices")
+        .insert_header((header::ORIGIN, "http://localhost:3000"))
+        .insert_header((header::ACCESS_CONTROL_REQUEST_METHOD, "POST"))
+        .insert_header((header::ACCESS_CONTROL_REQUEST_HEADERS, "x-request-id"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert!(resp.status().is_success(), "{}", resp.status());
+    let allowed = resp
+        .headers()
+        .get(header::ACCESS_CONTROL_ALLOW_HEADERS)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_lowercase();
+    assert!(allowed.contains("x-request-id"), "{}", allowed);
+}
+
+#[actix_web::test]
+async fn test_unconfigured_header_rejected_in_preflight() {
+    let mut config = get_test_config();
+    config.cors_allowed_origins = "http://localhost:3000".to_string();
+    config.cors_allowed_headers = vec!["Content-Type".to_string()];
+
+    let app = test::init_service(
+        App::new()
+            .wrap(cors(&config))
+            .route("/invoices", web::post().to(ok)),
+    )
+    .await;
+
+    let req = test::TestRequest::default()
+        .method(actix_web::http::Method::OPTIONS)
+        .uri("/invoices")
+        .insert_header((header::ORIGIN, "http://localhost:3000"))
+        .insert_header((header::ACCESS_CONTROL_REQUEST_METHOD, "POST"))
+        .insert_header((header::ACCESS_CONTROL_REQUEST_HEADERS, "x-request-id"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert!(!resp.status().is_success());
+}
@@ -0,0 +1,66 @@
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use crate::error::AppError;
+use crate::repository::CurrencyRepository;
+use crate::services::CurrencyService;
+
+use super::auth_test::get_test_config;
+
+async fn setup_currency_service() -> (CurrencyService, PgPool) {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    crate::database::run_migrations(&pool)
+        .await
+        .expect("Failed to run migrations");
+
+    let service = CurrencyService::new(
+        Arc::new(CurrencyRepository::new(pool.clone())),
+        Arc::new(config),
+    );
+    (service, pool)
+}
+
+#[tokio::test]
+async fn test_supported_currencies_include_metadata() {
+    let (service, _pool) = setup_currency_service().await;
+
+    let currencies = service
+        .get_supported_currencies()
+        .await
+        .expect("List failed");
+    let jpy = currencies
+        .iter()
+        .find(|c| c.code == "JPY")
+        .expect("JPY should be seeded");
+    assert_eq!(jpy.decimals, 0);
+    assert!(jpy.conversion_enabled);
+}
+
+#[tokio::test]
+async fn test_preview_conversion_supported_currency() {
+    let (service, _pool) = setup_currency_service().await;
+    let buffer_rate = get_test_config().default_buffer_rate;
+
+    let preview = service
+        .preview_conversion("USD", 1_000.0)
+        .await
+        .expect("Preview failed");
+
+    assert_eq!(preview.to_currency, "IDRX");
+    assert_eq!(preview.exchange_rate, 15500.0);
+    assert_eq!(preview.buffer_rate, buffer_rate);
+    assert!((preview.effective_rate - 15500.0 * (1.0 - buffer_rate)).abs() < 1e-6);
+    assert!((preview.buffer_amount - 1_000.0 * 15500.0 * buffer_rate).abs() < 1e-6);
+    assert!((preview.converted_amount + preview.buffer_amount - 1_000.0 * 15500.0).abs() < 1e-6);
+}
+
+#[tokio::test]
+async fn test_preview_conversion_unsupported_currency_rejected() {
+    let (service, _pool) = setup_currency_service().await;
+
+    let result = service.preview_conversion("XYZ", 1_000.0).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+}
@@ -0,0 +1,302 @@
+use futures_util::future::BoxFuture;
+use serde_json::json;
+use sqlx::PgPool;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::models::OutboundEmail;
+use crate::repository::OutboundEmailRepository;
+use crate::services::email_service::{EmailSender, EmailService, EmailTemplate, RenderedEmail};
+
+use super::auth_test::get_test_config;
+
+fn email_service() -> EmailService {
+    EmailService::new(Arc::new(get_test_config()))
+}
+
+/// Recipients that already failed once; shared because any test's worker may
+/// pick up another test's queued email
+static FAILED_ONCE: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Rejects `bounce_*` recipients on every attempt and `flaky_*` recipients on
+/// the first attempt only
+struct FlakyEmailSender;
+
+impl EmailSender for FlakyEmailSender {
+    fn send<'a>(
+        &'a self,
+        to: &'a str,
+        _subject: &'a str,
+        _email: &'a RenderedEmail,
+    ) -> BoxFuture<'a, AppResult<()>> {
+        let rejected = to.starts_with("bounce_")
+            || (to.starts_with("flaky_") && {
+                let mut failed = FAILED_ONCE.lock().unwrap();
+                let first = !failed.iter().any(|r| r == to);
+                if first {
+                    failed.push(to.to_string());
+                }
+                first
+            });
+        Box::pin(async move {
+            if rejected {
+                Err(AppError::EmailError("550 mailbox unavailable".to_string()))
+            } else {
+                Ok(())
+            }
+        })
+    }
+}
+
+async fn setup_email_outbox() -> (EmailService, Arc<OutboundEmailRepository>, PgPool) {
+    let mut config = get_test_config();
+    config.email_max_attempts = 3;
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    crate::database::run_migrations(&pool)
+        .await
+        .expect("Failed to run migrations");
+
+    let outbox = Arc::new(OutboundEmailRepository::new(pool.clone()));
+    let service = EmailService::new(Arc::new(config))
+        .with_sender(Arc::new(FlakyEmailSender))
+        .with_outbox(outbox.clone());
+    (service, outbox, pool)
+}
+
+async fn queued_email(pool: &PgPool, recipient: &str) -> Option<OutboundEmail> {
+    sqlx::query_as::<_, OutboundEmail>("SELECT * FROM outbound_emails WHERE recipient = $1")
+        .bind(recipient)
+        .fetch_optional(pool)
+        .await
+        .expect("Failed to load queued email")
+}
+
+/// Skip the backoff so the next delivery pass retries the email
+async fn make_due(pool: &PgPool, id: Uuid) {
+    sqlx::query("UPDATE outbound_emails SET next_attempt_at = NOW() WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await
+        .expect("Failed to make email due");
+}
+
+#[test]
+fn test_investment_confirmation_links_transaction_and_pool() {
+    let pool_id = Uuid::new_v4();
+    let tx_hash = "0xConfirmationTransferHash";
+    let explorer_url = format!("https://sepolia.basescan.org/tx/{}", tx_hash);
+
+    let email = email_service()
+        .render(
+            EmailTemplate::InvestmentConfirmation,
+            &json!({
+                "invoice_number": "INV-2024-042",
+                "pool_id": pool_id,
+                "amount": "10000000.00",
+                "tranche": "priority",
+                "expected_return": "10250000.00",
+                "tx_hash": tx_hash,
+                "explorer_url": explorer_url,
+            }),
+        )
+        .expect("Render failed");
+
+    for body in [&email.html, &email.text] {
+        assert!(body.contains("INV-2024-042"));
+        assert!(body.contains("Rp 10000000.00"));
+        assert!(body.contains(&pool_id.to_string()));
+        assert!(body.contains(&explorer_url));
+    }
+    assert!(email
+        .html
+        .contains(&format!(r#"<a href="{}">{}</a>"#, explorer_url, tx_hash)));
+    assert!(email
+        .html
+        .contains("VESSEL - Invoice Factoring Platform on Base Network"));
+    assert!(!email.text.contains('<'));
+}
+
+#[test]
+fn test_off_chain_investment_confirmation_has_no_transaction_row() {
+    let email = email_service()
+        .render(
+            EmailTemplate::InvestmentConfirmation,
+            &json!({
+                "invoice_number": "INV-2024-043",
+                "pool_id": Uuid::new_v4(),
+                "amount": "5000000.00",
+                "tranche": "catalyst",
+                "expected_return": "5200000.00",
+                "tx_hash": null,
+                "explorer_url": null,
+            }),
+        )
+        .expect("Render failed");
+
+    assert!(email.html.contains("INV-2024-043"));
+    assert!(!email.html.contains("Transaction:"));
+    assert!(!email.text.contains("Transaction:"));
+}
+
+#[test]
+fn test_only_html_variant_is_escaped() {
+    let link = "https://vessel.test/importer/payments?email=a%40b.com&token=abc";
+
+    let email = email_service()
+        .render(
+            EmailTemplate::ImporterAccess,
+            &json!({ "link": link, "expiry_minutes": 15 }),
+        )
+        .expect("Render failed");
+
+    assert!(email.html.contains("&amp;token"));
+    assert!(!email.html.contains(link));
+    assert!(email.text.contains(link));
+}
+
+#[test]
+fn test_kyc_rejection_escapes_name_and_reason() {
+    let email = email_service()
+        .render(
+            EmailTemplate::KycRejected,
+            &json!({
+                "full_name": "<b>Budi</b>",
+                "reason": "<a href=\"https://evil.test\">Resubmit here</a>",
+            }),
+        )
+        .expect("Render failed");
+
+    assert!(email.html.contains("&lt;b&gt;Budi&lt;/b&gt;"));
+    assert!(!email.html.contains("<a href"));
+    assert!(email.html.contains("&lt;a href="));
+}
+
+#[tokio::test]
+async fn test_failed_email_is_recorded_and_retried() {
+    let (service, outbox, pool) = setup_email_outbox().await;
+    let recipient = format!("flaky_{}@test.com", Uuid::new_v4().simple());
+
+    service
+        .send_kyc_approval_notification(&recipient, "Budi Santoso")
+        .await
+        .expect("Queueing failed");
+    let queued = queued_email(&pool, &recipient)
+        .await
+        .expect("Email was not queued");
+    assert_eq!(queued.template, "kyc_approved");
+    assert!(queued.html_body.contains("Budi Santoso"));
+
+    service
+        .deliver_queued_emails()
+        .await
+        .expect("Delivery pass failed");
+    let failed = outbox.find_by_id(queued.id).await.unwrap().unwrap();
+    assert_eq!(failed.status, "failed");
+    assert_eq!(failed.attempts, 1);
+    assert!(failed.last_error.unwrap().contains("550"));
+    assert!(failed.next_attempt_at > failed.updated_at);
+
+    make_due(&pool, queued.id).await;
+    service
+        .deliver_queued_emails()
+        .await
+        .expect("Delivery pass failed");
+    let sent = outbox.find_by_id(queued.id).await.unwrap().unwrap();
+    assert_eq!(sent.status, "sent");
+    assert_eq!(sent.attempts, 2);
+    assert!(sent.sent_at.is_some());
+    assert!(sent.last_error.is_none());
+
+    // OTPs bypass the queue, so the caller sees the failure
+    let bounce = format!("bounce_{}@test.com", Uuid::new_v4().simple());
+    assert!(service.send_otp(&bounce, "OTP", "123456", 5).await.is_err());
+    assert!(queued_email(&pool, &bounce).await.is_none());
+
+    sqlx::query("DELETE FROM outbound_emails WHERE id = $1")
+        .bind(queued.id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_queued_email_stays_pending_without_smtp() {
+    let (_, outbox, pool) = setup_email_outbox().await;
+    let mut config = get_test_config();
+    config.smtp_username = String::new();
+    config.smtp_password = String::new();
+    // Default SMTP sender, which cannot deliver without credentials
+    let service = EmailService::new(Arc::new(config)).with_outbox(outbox.clone());
+    let recipient = format!("nosmtp_{}@test.com", Uuid::new_v4().simple());
+
+    service
+        .send_kyc_approval_notification(&recipient, "Budi Santoso")
+        .await
+        .expect("Queueing failed");
+    let id = queued_email(&pool, &recipient)
+        .await
+        .expect("Email was not queued")
+        .id;
+
+    let sent = service
+        .deliver_queued_emails()
+        .await
+        .expect("Delivery pass failed");
+    assert_eq!(sent, 0);
+    let email = outbox.find_by_id(id).await.unwrap().unwrap();
+    assert_eq!(email.status, "pending");
+    assert_eq!(email.attempts, 0);
+    assert!(email.sent_at.is_none());
+
+    sqlx::query("DELETE FROM outbound_emails WHERE id = $1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_permanently_failing_email_is_dead_lettered() {
+    let (service, outbox, pool) = setup_email_outbox().await;
+    let recipient = format!("bounce_{}@test.com", Uuid::new_v4().simple());
+
+    service
+        .send_disbursement_notification(&recipient, "INV-2024-099", 1_000_000.0)
+        .await
+        .expect("Queueing failed");
+    let id = queued_email(&pool, &recipient)
+        .await
+        .expect("Email was not queued")
+        .id;
+
+    for attempt in 1..=3 {
+        service
+            .deliver_queued_emails()
+            .await
+            .expect("Delivery pass failed");
+        let email = outbox.find_by_id(id).await.unwrap().unwrap();
+        assert_eq!(email.attempts, attempt);
+        let expected = if attempt < 3 { "failed" } else { "dead" };
+        assert_eq!(email.status, expected);
+        make_due(&pool, id).await;
+    }
+
+    // Dead-lettered emails are never claimed again
+    service
+        .deliver_queued_emails()
+        .await
+        .expect("Delivery pass failed");
+    let dead = outbox.find_by_id(id).await.unwrap().unwrap();
+    assert_eq!(dead.status, "dead");
+    assert_eq!(dead.attempts, 3);
+    assert!(dead.sent_at.is_none());
+
+    sqlx::query("DELETE FROM outbound_emails WHERE id = $1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .ok();
+}
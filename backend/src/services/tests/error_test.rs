@@ -0,0 +1,119 @@
+use actix_web::{http::StatusCode, test, web, App, ResponseError};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::handlers;
+use crate::models::Role;
+use crate::repository::{ActivityRepository, UserRepository};
+
+use super::auth_test::get_test_config;
+use super::user_test::setup_app_state;
+
+#[tokio::test]
+async fn test_duplicate_email_is_conflict_not_server_error() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    crate::database::run_migrations(&pool)
+        .await
+        .expect("Failed to run migrations");
+    let user_repo = UserRepository::new(pool.clone());
+
+    let email = "duplicate_email_conflict@test.com";
+    sqlx::query("DELETE FROM users WHERE email = $1")
+        .bind(email)
+        .execute(&pool)
+        .await
+        .ok();
+
+    let user = user_repo
+        .create(email, "duplicate_first", "hash", Role::Investor)
+        .await
+        .expect("First insert should succeed");
+
+    let err = user_repo
+        .create(email, "duplicate_second", "hash", Role::Investor)
+        .await
+        .expect_err("Duplicate email must be rejected");
+    match &err {
+        AppError::Conflict(msg) => assert!(
+            msg.contains("users_email_key"),
+            "Message should name the constraint: {}",
+            msg
+        ),
+        other => panic!("Expected Conflict, got {:?}", other),
+    }
+    assert_eq!(err.error_response().status(), StatusCode::CONFLICT);
+
+    // Cleanup
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(user.id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_missing_foreign_key_is_bad_request() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    crate::database::run_migrations(&pool)
+        .await
+        .expect("Failed to run migrations");
+
+    let err = ActivityRepository::new(pool)
+        .create(Uuid::new_v4(), "login", "Orphan activity")
+        .await
+        .expect_err("Activity for an unknown user must be rejected");
+
+    assert!(matches!(err, AppError::BadRequest(_)), "Got {:?}", err);
+    assert_eq!(err.error_response().status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn test_zero_amount_invest_rejected_with_field_details() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let state = setup_app_state(pool).await;
+    // No auth middleware: a request that reached the handler would be 401
+    let app = test::init_service(
+        App::new()
+            .app_data(state)
+            .route("/investments", web::post().to(handlers::funding::invest))
+            .route("/auth/login", web::post().to(handlers::auth::login)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/investments")
+        .set_json(serde_json::json!({
+            "pool_id": Uuid::new_v4(),
+            "amount": 0.0,
+            "tranche": "priority",
+            "tnc_accepted": true,
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"]["code"], "VALIDATION_ERROR");
+    assert_eq!(
+        body["error"]["details"],
+        serde_json::json!([{ "field": "amount", "message": "Amount must be positive" }])
+    );
+
+    let req = test::TestRequest::post()
+        .uri("/auth/login")
+        .set_json(serde_json::json!({ "email_or_username": "someone", "password": "" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"]["details"][0]["field"], "password");
+}
@@ -1,18 +1,31 @@
+use ethers::types::{Address, U256};
 use sqlx::PgPool;
 use sqlx::Row;
 use std::sync::Arc;
 use uuid::Uuid;
 
-use crate::models::{InvestRequest, RepayInvoiceRequest};
+use crate::config::{Config, InvestmentMode};
+use crate::error::AppError;
+use crate::models::{
+    CatalystConsents, DisbursementMode, FundableInvoiceFilter, InvestRequest,
+    InvestWithPermitRequest, PoolFilter, PoolInvestmentLimitsRequest, RepayInvoiceRequest,
+};
 use crate::repository::{
-    FundingRepository, InvoiceRepository, MitraRepository, RiskQuestionnaireRepository,
-    TransactionRepository, UserRepository,
+    ActivityRepository, FundingRepository, InvoiceRepository, MitraRepository,
+    NotificationRepository, RiskQuestionnaireRepository, TransactionRepository, UserRepository,
+};
+use crate::services::blockchain_service::{
+    BlockchainService, GasOracle, InvestmentEventSource, NftOwnerSource, OnChainInvestment,
+    PermitAuthorization, PermitToken, SentTransaction,
 };
-use crate::services::blockchain_service::BlockchainService;
 use crate::services::email_service::EmailService;
 use crate::services::escrow_service::EscrowService;
 use crate::services::pinata_service::PinataService;
-use crate::services::{FundingService, InvoiceService, MitraService};
+use crate::services::{
+    accrual_days, ActivityLogger, FundingService, InvoiceService, MitraService,
+    NotificationService, WebhookSender,
+};
+use crate::utils::{mask_wallet, Masked};
 
 use super::auth_test::get_test_config;
 
@@ -23,12 +36,63 @@ pub async fn setup_funding_service(
     Arc<InvoiceService>,
     Arc<MitraService>,
     PgPool,
+) {
+    setup_funding_service_with_config(pool, get_test_config()).await
+}
+
+pub async fn setup_funding_service_with_config(
+    pool: PgPool,
+    config: Config,
+) -> (
+    Arc<FundingService>,
+    Arc<InvoiceService>,
+    Arc<MitraService>,
+    PgPool,
+) {
+    setup_funding_service_with_sender(pool, config, None).await
+}
+
+/// `webhook_sender` replaces the HTTP webhook transport, e.g. with a recording mock
+pub async fn setup_funding_service_with_sender(
+    pool: PgPool,
+    config: Config,
+    webhook_sender: Option<Arc<dyn WebhookSender>>,
+) -> (
+    Arc<FundingService>,
+    Arc<InvoiceService>,
+    Arc<MitraService>,
+    PgPool,
+) {
+    setup_funding_service_with_mocks(pool, config, webhook_sender, None, None, None, None).await
+}
+
+/// `permit_token` replaces the IDRX permit contract calls, `gas_oracle` the gas
+/// reads, `investment_events` the InvoicePool event queries and `nft_owners`
+/// the InvoiceNFT `ownerOf` reads
+pub async fn setup_funding_service_with_mocks(
+    pool: PgPool,
+    mut config: Config,
+    webhook_sender: Option<Arc<dyn WebhookSender>>,
+    permit_token: Option<Arc<dyn PermitToken>>,
+    gas_oracle: Option<Arc<dyn GasOracle>>,
+    investment_events: Option<Arc<dyn InvestmentEventSource>>,
+    nft_owners: Option<Arc<dyn NftOwnerSource>>,
+) -> (
+    Arc<FundingService>,
+    Arc<InvoiceService>,
+    Arc<MitraService>,
+    PgPool,
 ) {
     crate::database::run_migrations(&pool)
         .await
         .expect("Failed to run migrations");
+    // The on-chain cleanup would drop balances that concurrent off-chain tests debit
+    if config.investment_mode == InvestmentMode::OffChain {
+        crate::database::run_investment_mode_migrations(&pool, config.investment_mode)
+            .await
+            .expect("Failed to run investment mode migrations");
+    }
 
-    let mut config = get_test_config();
     config.skip_blockchain_verification = true; // Enable test mode
     let config = Arc::new(config);
 
@@ -43,16 +107,36 @@ pub async fn setup_funding_service(
     let pinata_service = Arc::new(PinataService::new(config.clone()));
     let escrow_service = Arc::new(EscrowService::new());
 
-    let blockchain_service = Arc::new(
-        BlockchainService::new(
-            config.clone(),
-            invoice_repo.clone(),
-            funding_repo.clone(),
-            pinata_service.clone(),
-        )
-        .await
-        .expect("Failed to init blockchain service"),
-    );
+    let blockchain_service = BlockchainService::new(
+        config.clone(),
+        invoice_repo.clone(),
+        funding_repo.clone(),
+        pinata_service.clone(),
+    )
+    .await
+    .expect("Failed to init blockchain service");
+    let blockchain_service = match permit_token {
+        Some(token) => blockchain_service.with_permit_token(token),
+        None => blockchain_service,
+    };
+    let blockchain_service = match gas_oracle {
+        Some(oracle) => blockchain_service.with_gas_oracle(oracle),
+        None => blockchain_service,
+    };
+    let blockchain_service = match investment_events {
+        Some(events) => blockchain_service.with_investment_events(events),
+        None => blockchain_service,
+    };
+    let blockchain_service = Arc::new(match nft_owners {
+        Some(owners) => blockchain_service.with_nft_owners(owners),
+        None => blockchain_service,
+    });
+
+    let notification_repo = Arc::new(NotificationRepository::new(pool.clone()));
+    let notification_service = Arc::new(match webhook_sender {
+        Some(sender) => NotificationService::with_sender(config.clone(), sender, notification_repo),
+        None => NotificationService::new(config.clone(), notification_repo),
+    });
 
     let funding_service = Arc::new(FundingService::new(
         funding_repo.clone(),
@@ -63,15 +147,20 @@ pub async fn setup_funding_service(
         email_service.clone(),
         escrow_service,
         blockchain_service.clone(),
+        notification_service,
+        Arc::new(ActivityLogger::new(Arc::new(ActivityRepository::new(pool.clone())))),
         config.clone(),
     ));
 
-    let mitra_service = Arc::new(MitraService::new(
-        mitra_repo.clone(),
-        user_repo.clone(),
-        email_service.clone(),
-        pinata_service.clone(),
-    ));
+    let mitra_service = Arc::new(
+        MitraService::new(
+            mitra_repo.clone(),
+            user_repo.clone(),
+            email_service.clone(),
+            pinata_service.clone(),
+        )
+        .with_funding_service(funding_service.clone()),
+    );
 
     let invoice_service = Arc::new(InvoiceService::new(
         invoice_repo,
@@ -86,7 +175,7 @@ pub async fn setup_funding_service(
 }
 
 // Helpers
-async fn create_investor(pool: &PgPool, base_email: &str) -> Uuid {
+pub async fn create_investor(pool: &PgPool, base_email: &str) -> Uuid {
     let user_id = Uuid::new_v4();
     let email = format!("{}_{}", user_id.simple(), base_email);
     let username = format!("investor_{}", user_id.simple());
@@ -94,7 +183,7 @@ async fn create_investor(pool: &PgPool, base_email: &str) -> Uuid {
 
     sqlx::query(
         r#"INSERT INTO users (id, email, username, password_hash, role, member_status, is_verified, is_active, wallet_address)
-           VALUES ($1, $2, $3, 'hash', 'investor', 'individual', true, true, $4)"#
+           VALUES ($1, $2, $3, 'hash', 'investor', 'calon_anggota_pendana', true, true, $4)"#
     )
     .bind(user_id)
     .bind(email)
@@ -104,10 +193,25 @@ async fn create_investor(pool: &PgPool, base_email: &str) -> Uuid {
     .await
     .expect("Failed to create investor");
 
+    // Investing requires a completed risk questionnaire
+    sqlx::query(
+        r#"INSERT INTO risk_questionnaires (user_id, q1_answer, q2_answer, q3_answer, catalyst_unlocked)
+           VALUES ($1, 2, 1, 1, true)"#,
+    )
+    .bind(user_id)
+    .execute(pool)
+    .await
+    .expect("Failed to complete risk questionnaire");
+
     user_id
 }
 
-async fn create_mitra_and_invoice(
+/// Transfer hashes are unique across investors, so each test needs its own
+pub fn unique_tx_hash() -> String {
+    format!("0x{}", Uuid::new_v4().simple())
+}
+
+pub async fn create_mitra_and_invoice(
     pool: &PgPool,
     _invoice_service: &Arc<InvoiceService>,
     base_email: &str,
@@ -147,7 +251,7 @@ async fn create_mitra_and_invoice(
             $1, 'Buyer PT', 'ID', 'buyer@test.com', $2,
             'IDRX', 100000000.0, NOW(), NOW() + INTERVAL '30 days', NULL, 'draft',
             $3,
-            70.0, 30.0, 80.0, false,
+            70.0, 30.0, 100.0, false,
             false, 100, 'A', 0.02, 30,
             12.0, 15.0, 100000000.0, 80.0
         )
@@ -170,7 +274,7 @@ async fn create_mitra_and_invoice(
     (user_id, invoice.id)
 }
 
-async fn setup_pool(
+pub async fn setup_pool(
     pool: &PgPool,
     funding_service: &Arc<FundingService>,
     invoice_id: Uuid,
@@ -209,7 +313,7 @@ async fn test_invest_limits_success() {
 
     let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
 
-    let (_, invoice_id) =
+    let (mitra_id, invoice_id) =
         create_mitra_and_invoice(&pool, &invoice_service, "mitra_invest_ok@test.com").await;
     let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
     let investor_id = create_investor(&pool, "investor_ok@test.com").await;
@@ -222,7 +326,7 @@ async fn test_invest_limits_success() {
         tranche: "priority".to_string(),
         tnc_accepted: true,
         catalyst_consents: None,
-        tx_hash: "0xTransferHash".to_string(),
+        tx_hash: unique_tx_hash(),
     };
 
     let result = funding_service.invest(investor_id, req).await;
@@ -232,16 +336,7 @@ async fn test_invest_limits_success() {
         result.err()
     );
 
-    // Cleanup
-    // Cleanup
-    sqlx::query("DELETE FROM users WHERE email = 'mitra_invest_ok@test.com'")
-        .execute(&pool)
-        .await
-        .ok();
-    sqlx::query("DELETE FROM users WHERE email = 'investor_ok@test.com'")
-        .execute(&pool)
-        .await
-        .ok();
+    cleanup_disbursement_test(&pool, investor_id, mitra_id).await;
 }
 
 #[tokio::test]
@@ -253,7 +348,7 @@ async fn test_invest_limits_fail_min() {
         .expect("Failed to connect");
 
     let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
-    let (_, invoice_id) =
+    let (mitra_id, invoice_id) =
         create_mitra_and_invoice(&pool, &invoice_service, "mitra_invest_min@test.com").await;
     let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
     let investor_id = create_investor(&pool, "investor_min@test.com").await;
@@ -266,7 +361,7 @@ async fn test_invest_limits_fail_min() {
         tranche: "priority".to_string(),
         tnc_accepted: true,
         catalyst_consents: None,
-        tx_hash: "0xTransferHash".to_string(),
+        tx_hash: unique_tx_hash(),
     };
 
     let result = funding_service.invest(investor_id, req).await;
@@ -274,16 +369,7 @@ async fn test_invest_limits_fail_min() {
 
     // Parse error checking? For now just ensure failure.
 
-    // Cleanup
-    // Cleanup
-    sqlx::query("DELETE FROM users WHERE email = 'mitra_invest_min@test.com'")
-        .execute(&pool)
-        .await
-        .ok();
-    sqlx::query("DELETE FROM users WHERE email = 'investor_min@test.com'")
-        .execute(&pool)
-        .await
-        .ok();
+    cleanup_disbursement_test(&pool, investor_id, mitra_id).await;
 }
 
 #[tokio::test]
@@ -295,7 +381,7 @@ async fn test_invest_limits_fail_max() {
         .expect("Failed to connect");
 
     let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
-    let (_, invoice_id) =
+    let (mitra_id, invoice_id) =
         create_mitra_and_invoice(&pool, &invoice_service, "mitra_invest_max@test.com").await;
     let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
     let investor_id = create_investor(&pool, "investor_max@test.com").await;
@@ -308,22 +394,278 @@ async fn test_invest_limits_fail_max() {
         tranche: "priority".to_string(),
         tnc_accepted: true,
         catalyst_consents: None,
-        tx_hash: "0xTransferHash".to_string(),
+        tx_hash: unique_tx_hash(),
     };
 
     let result = funding_service.invest(investor_id, req).await;
     assert!(result.is_err(), "Investment 95% should fail");
 
+    cleanup_disbursement_test(&pool, investor_id, mitra_id).await;
+}
+
+#[tokio::test]
+async fn test_dry_run_over_max_reports_limit_without_touching_chain() {
+    let mut config = get_test_config();
+    config.investment_mode = InvestmentMode::OnChain;
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (funding_service, invoice_service, _, pool) =
+        setup_funding_service_with_config(pool, config).await;
+
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_dry_run@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_dry_run@test.com").await;
+
+    // No transfer hash: a dry run never reaches the on-chain verification
+    let request = |amount: f64| InvestRequest {
+        pool_id,
+        amount,
+        tranche: "priority".to_string(),
+        tnc_accepted: true,
+        catalyst_consents: None,
+        tx_hash: String::new(),
+    };
+
+    // Target: 100M. Max: 90M.
+    let result = funding_service
+        .preview_investment(investor_id, request(95_000_000.0))
+        .await;
+    match result {
+        Err(AppError::ValidationError(msg)) => {
+            assert!(msg.contains("Maximum investment"), "Got {}", msg)
+        }
+        other => panic!("Expected the max limit error, got {:?}", other),
+    }
+
+    let preview = funding_service
+        .preview_investment(investor_id, request(20_000_000.0))
+        .await
+        .expect("Dry run within limits should pass");
+    assert_eq!(preview.amount, rust_decimal::Decimal::from(20_000_000));
+    assert!(preview.expected_return >= preview.amount);
+
+    // Neither run recorded an investment or a transfer
+    let (investments,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM investments WHERE investor_id = $1")
+            .bind(investor_id)
+            .fetch_one(&pool)
+            .await
+            .expect("Failed to count investments");
+    assert_eq!(investments, 0);
+    let (transactions,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM transactions WHERE user_id = $1")
+            .bind(investor_id)
+            .fetch_one(&pool)
+            .await
+            .expect("Failed to count transactions");
+    assert_eq!(transactions, 0);
+
     // Cleanup
-    // Cleanup
-    sqlx::query("DELETE FROM users WHERE email = 'mitra_invest_max@test.com'")
-        .execute(&pool)
+    for user_id in [mitra_id, investor_id] {
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_invest_enforces_custom_pool_limits() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
         .await
-        .ok();
-    sqlx::query("DELETE FROM users WHERE email = 'investor_max@test.com'")
-        .execute(&pool)
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_custom_limits@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+
+    // The config defaults are stored on the pool when it is created
+    let created = funding_service
+        .get_pool(pool_id)
         .await
-        .ok();
+        .expect("Pool missing");
+    let (priority_min, priority_max) = config.investment_limit_ratios(false);
+    let (catalyst_min, catalyst_max) = config.investment_limit_ratios(true);
+    assert_eq!(created.pool.min_invest_ratio, Some(priority_min));
+    assert_eq!(created.pool.max_invest_ratio, Some(priority_max));
+    assert_eq!(created.pool.catalyst_min_invest_ratio, Some(catalyst_min));
+    assert_eq!(created.pool.catalyst_max_invest_ratio, Some(catalyst_max));
+
+    // Ratios outside (0, 1] or with min above max are refused
+    for (min, max) in [("0", "0.5"), ("0.6", "0.5"), ("0.05", "1.5")] {
+        let result = funding_service
+            .set_pool_investment_limits(
+                pool_id,
+                PoolInvestmentLimitsRequest {
+                    min_invest_ratio: min.parse().unwrap(),
+                    max_invest_ratio: max.parse().unwrap(),
+                },
+            )
+            .await;
+        assert!(matches!(result, Err(AppError::ValidationError(_))));
+    }
+
+    let funding_pool = funding_service
+        .set_pool_investment_limits(
+            pool_id,
+            PoolInvestmentLimitsRequest {
+                min_invest_ratio: "0.05".parse().unwrap(),
+                max_invest_ratio: "0.5".parse().unwrap(),
+            },
+        )
+        .await
+        .expect("Failed to set limits");
+    assert_eq!(funding_pool.min_invest_ratio, Some("0.05".parse().unwrap()));
+    assert_eq!(
+        funding_pool.catalyst_min_invest_ratio,
+        Some("0.05".parse().unwrap())
+    );
+
+    // Priority target is 70M, so the pool allows 3.5M..35M per investment
+    let invest = |investor_id: Uuid, amount: f64| {
+        let funding_service = funding_service.clone();
+        async move {
+            funding_service
+                .invest(
+                    investor_id,
+                    InvestRequest {
+                        pool_id,
+                        amount,
+                        tranche: "priority".to_string(),
+                        tnc_accepted: true,
+                        catalyst_consents: None,
+                        tx_hash: format!("0x{}", Uuid::new_v4().simple()),
+                    },
+                )
+                .await
+        }
+    };
+    let investors = [
+        create_investor(&pool, "investor_custom_min@test.com").await,
+        create_investor(&pool, "investor_custom_below@test.com").await,
+        create_investor(&pool, "investor_custom_above@test.com").await,
+        create_investor(&pool, "investor_custom_max@test.com").await,
+    ];
+
+    // 4M is under the 10% config default but above this pool's 5%
+    let result = invest(investors[0], 4_000_000.0).await;
+    assert!(result.is_ok(), "4M should be accepted: {:?}", result.err());
+    let result = invest(investors[1], 3_000_000.0).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+
+    // 36M is under the 90% config default but above this pool's 50%
+    let result = invest(investors[2], 36_000_000.0).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+    let result = invest(investors[3], 35_000_000.0).await;
+    assert!(result.is_ok(), "35M should be accepted: {:?}", result.err());
+
+    for user_id in investors.into_iter().chain([mitra_id]) {
+        sqlx::query("DELETE FROM transactions WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .ok();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_invest_two_decimal_amount_accepted() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_cents_ok@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_cents_ok@test.com").await;
+
+    let req = InvestRequest {
+        pool_id,
+        amount: 20_000_000.25,
+        tranche: "priority".to_string(),
+        tnc_accepted: true,
+        catalyst_consents: None,
+        tx_hash: format!("0x{}", Uuid::new_v4().simple()),
+    };
+
+    let investment = funding_service
+        .invest(investor_id, req)
+        .await
+        .expect("2-decimal amount should be accepted");
+    assert_eq!(
+        investment.amount,
+        rust_decimal::Decimal::new(2_000_000_025, 2)
+    );
+
+    for user_id in [investor_id, mitra_id] {
+        sqlx::query("DELETE FROM transactions WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .ok();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_invest_sub_cent_amount_rejected() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_cents_bad@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_cents_bad@test.com").await;
+
+    let req = InvestRequest {
+        pool_id,
+        amount: 20_000_000.005,
+        tranche: "priority".to_string(),
+        tnc_accepted: true,
+        catalyst_consents: None,
+        tx_hash: format!("0x{}", Uuid::new_v4().simple()),
+    };
+
+    let result = funding_service.invest(investor_id, req).await;
+    match result {
+        Err(AppError::ValidationError(msg)) => assert!(msg.contains("2 decimal places"), "{}", msg),
+        other => panic!("3-decimal amount should be rejected: {:?}", other),
+    }
+
+    let investment = FundingRepository::new(pool.clone())
+        .find_investment_by_pool_and_investor(pool_id, investor_id)
+        .await
+        .expect("Failed to look up investment");
+    assert!(investment.is_none());
+
+    for user_id in [investor_id, mitra_id] {
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
 }
 
 #[tokio::test]
@@ -347,7 +689,7 @@ async fn test_repay_invoice_success() {
         tranche: "priority".to_string(),
         tnc_accepted: true,
         catalyst_consents: None,
-        tx_hash: "0xTransferHash".to_string(),
+        tx_hash: unique_tx_hash(),
     };
     funding_service
         .invest(investor_id, req)
@@ -394,16 +736,56 @@ async fn test_repay_invoice_success() {
     let inv_status: String = inv_row.get("status");
     assert_eq!(inv_status, "repaid");
 
-    // Cleanup
-    // Cleanup
-    sqlx::query("DELETE FROM users WHERE email = 'mitra_repay@test.com'")
-        .execute(&pool)
+    cleanup_disbursement_test(&pool, investor_id, mitra_id).await;
+}
+
+#[tokio::test]
+async fn test_admin_process_repayment_marks_invoice_repaid() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
         .await
-        .ok();
-    sqlx::query("DELETE FROM users WHERE email = 'investor_repay@test.com'")
-        .execute(&pool)
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_admin_repay@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_admin_repay@test.com").await;
+
+    let req = InvestRequest {
+        pool_id,
+        amount: 20_000_000.0,
+        tranche: "priority".to_string(),
+        tnc_accepted: true,
+        catalyst_consents: None,
+        tx_hash: unique_tx_hash(),
+    };
+    funding_service
+        .invest(investor_id, req)
         .await
-        .ok();
+        .expect("Investment failed");
+    funding_service
+        .disburse_pool(pool_id)
+        .await
+        .expect("Disbursement failed");
+
+    funding_service
+        .process_repayment(
+            pool_id,
+            unique_tx_hash(),
+            rust_decimal::Decimal::from(21_000_000),
+        )
+        .await
+        .expect("Repayment failed");
+
+    let (status,): (String,) = sqlx::query_as("SELECT status FROM invoices WHERE id = $1")
+        .bind(invoice_id)
+        .fetch_one(&pool)
+        .await
+        .expect("Failed to load invoice");
+    assert_eq!(status, "repaid");
+
+    cleanup_disbursement_test(&pool, investor_id, mitra_id).await;
 }
 
 #[tokio::test]
@@ -416,7 +798,7 @@ async fn test_duplicate_investment_fails() {
 
     let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
 
-    let (_, invoice_id) =
+    let (mitra_id, invoice_id) =
         create_mitra_and_invoice(&pool, &invoice_service, "mitra_dupe_inv@test.com").await;
     let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
     let investor_id = create_investor(&pool, "investor_dupe@test.com").await;
@@ -428,7 +810,7 @@ async fn test_duplicate_investment_fails() {
         tranche: "priority".to_string(),
         tnc_accepted: true,
         catalyst_consents: None,
-        tx_hash: "0xTx1".to_string(),
+        tx_hash: unique_tx_hash(),
     };
     funding_service
         .invest(investor_id, req1)
@@ -442,29 +824,86 @@ async fn test_duplicate_investment_fails() {
         tranche: "priority".to_string(),
         tnc_accepted: true,
         catalyst_consents: None,
-        tx_hash: "0xTx2".to_string(),
+        tx_hash: unique_tx_hash(),
     };
     let result = funding_service.invest(investor_id, req2).await;
     assert!(result.is_err(), "Duplicate investment should fail");
 
-    // Cleanup
-    sqlx::query("DELETE FROM users WHERE email = 'mitra_dupe_inv@test.com'")
-        .execute(&pool)
-        .await
-        .ok();
-    sqlx::query("DELETE FROM users WHERE email = 'investor_dupe@test.com'")
-        .execute(&pool)
-        .await
-        .ok();
+    cleanup_disbursement_test(&pool, investor_id, mitra_id).await;
 }
 
-// ============================================================
-// MITRA POOLS TESTS
-// ============================================================
-
 #[tokio::test]
-async fn test_get_mitra_pools_success() {
-    let mut config = get_test_config();
+async fn test_invest_requires_completed_risk_questionnaire() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_rq_gate@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_rq_gate@test.com").await;
+    sqlx::query("DELETE FROM risk_questionnaires WHERE user_id = $1")
+        .bind(investor_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to reset questionnaire");
+
+    let invest_request = || InvestRequest {
+        pool_id,
+        amount: 20_000_000.0,
+        tranche: "priority".to_string(),
+        tnc_accepted: true,
+        catalyst_consents: None,
+        tx_hash: format!("0x{}", Uuid::new_v4().simple()),
+    };
+
+    let result = funding_service.invest(investor_id, invest_request()).await;
+    assert!(
+        matches!(result, Err(AppError::RiskQuestionnaireRequired)),
+        "Investment without questionnaire should be rejected: {:?}",
+        result.err()
+    );
+    let result = funding_service
+        .confirm_investment(investor_id, invest_request())
+        .await;
+    assert!(matches!(result, Err(AppError::RiskQuestionnaireRequired)));
+
+    RiskQuestionnaireRepository::new(pool.clone())
+        .create(investor_id, 1, 2, 2, false, "priority".to_string())
+        .await
+        .expect("Failed to complete questionnaire");
+
+    let result = funding_service.invest(investor_id, invest_request()).await;
+    assert!(
+        result.is_ok(),
+        "Investment after questionnaire should succeed: {:?}",
+        result.err()
+    );
+
+    for user_id in [investor_id, mitra_id] {
+        sqlx::query("DELETE FROM transactions WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .ok();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+// ============================================================
+// MITRA POOLS TESTS
+// ============================================================
+
+#[tokio::test]
+async fn test_get_mitra_pools_success() {
+    let mut config = get_test_config();
     config.skip_blockchain_verification = true;
     let pool = PgPool::connect(&config.database_url)
         .await
@@ -612,7 +1051,7 @@ async fn test_get_mitra_pools_multiple() {
                 $1, 'Buyer PT', 'ID', 'buyer@test.com', $2,
                 'IDRX', 100000000.0, NOW(), NOW() + INTERVAL '30 days', NULL, 'tokenized',
                 $3,
-                70.0, 30.0, 80.0, false,
+                70.0, 30.0, 100.0, false,
                 false, 100, 'A', 0.02, 30,
                 12.0, 15.0, 100000000.0, 80.0
             )
@@ -1019,7 +1458,7 @@ async fn test_get_pool_by_invoice_no_pool_exists() {
             $1, 'Buyer PT', 'ID', 'buyer@test.com', $2,
             'IDRX', 100000000.0, NOW(), NOW() + INTERVAL '30 days', NULL, 'draft',
             $3,
-            70.0, 30.0, 80.0, false,
+            70.0, 30.0, 100.0, false,
             false, 100, 'A', 0.02, 30,
             12.0, 15.0, 100000000.0, 80.0
         )
@@ -1278,3 +1717,4633 @@ async fn test_get_investor_portfolio_empty() {
         .await
         .ok();
 }
+
+#[tokio::test]
+async fn test_export_portfolio_csv_row_count_matches_investments() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let investor_id = create_investor(&pool, "investor_export_csv@test.com").await;
+
+    let mut mitra_ids = Vec::new();
+    for i in 0..2 {
+        let (mitra_id, invoice_id) = create_mitra_and_invoice(
+            &pool,
+            &invoice_service,
+            &format!("mitra_export_csv_{}@test.com", i),
+        )
+        .await;
+        mitra_ids.push(mitra_id);
+        let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+        if i == 0 {
+            // Mitra-chosen text a spreadsheet would run as a formula
+            sqlx::query("UPDATE invoices SET invoice_number = $2 WHERE id = $1")
+                .bind(invoice_id)
+                .bind(format!(
+                    "=HYPERLINK(\"http://evil.test\")-{}",
+                    Uuid::new_v4().simple()
+                ))
+                .execute(&pool)
+                .await
+                .expect("Failed to rename invoice");
+        }
+
+        let req = InvestRequest {
+            pool_id,
+            amount: 20_000_000.0,
+            tranche: "priority".to_string(),
+            tnc_accepted: true,
+            catalyst_consents: None,
+            tx_hash: format!("0xExportHash{}", i),
+        };
+        funding_service
+            .invest(investor_id, req)
+            .await
+            .expect("Investment should succeed");
+    }
+
+    let csv = funding_service
+        .export_portfolio_csv(investor_id)
+        .await
+        .expect("Export should succeed");
+    let lines: Vec<&str> = csv.lines().collect();
+
+    let (_, total) = funding_service
+        .get_investor_investments(investor_id, 1, 100, None)
+        .await
+        .expect("Failed to list investments");
+    assert_eq!(lines.len() as i64, total + 1, "Header plus one row per investment");
+    assert!(lines[0].starts_with("invoice_number,tranche,principal"));
+    assert!(lines[1].contains("/tx/0xExportHash"));
+    assert!(
+        lines
+            .iter()
+            .any(|line| line.starts_with("\"'=HYPERLINK(\"\"http://evil.test\"\")-")),
+        "Formula must be neutralized: {}",
+        csv
+    );
+
+    // Investor with no investments gets a header-only file
+    let empty_investor_id = create_investor(&pool, "investor_export_empty@test.com").await;
+    let empty_csv = funding_service
+        .export_portfolio_csv(empty_investor_id)
+        .await
+        .expect("Export should succeed");
+    assert_eq!(empty_csv.lines().count(), 1);
+
+    // Cleanup
+    for id in [investor_id, empty_investor_id]
+        .iter()
+        .chain(mitra_ids.iter())
+    {
+        sqlx::query("DELETE FROM transactions WHERE user_id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .ok();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_disburse_fully_backed_pool_succeeds() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_disburse_backed@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_disburse_backed@test.com").await;
+
+    let req = InvestRequest {
+        pool_id,
+        amount: 20_000_000.0,
+        tranche: "priority".to_string(),
+        tnc_accepted: true,
+        catalyst_consents: None,
+        tx_hash: "0xBackedTransferHash".to_string(),
+    };
+    funding_service
+        .invest(investor_id, req)
+        .await
+        .expect("Investment failed");
+
+    let result = funding_service.disburse_pool(pool_id).await;
+    assert!(
+        result.is_ok(),
+        "Fully backed pool should disburse: {:?}",
+        result.err()
+    );
+    assert_eq!(result.unwrap().status, "disbursed");
+
+    // Cleanup
+    for id in [investor_id, mitra_id] {
+        sqlx::query("DELETE FROM transactions WHERE user_id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .ok();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_disburse_under_backed_pool_blocked() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_disburse_short@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_disburse_short@test.com").await;
+
+    let req = InvestRequest {
+        pool_id,
+        amount: 20_000_000.0,
+        tranche: "priority".to_string(),
+        tnc_accepted: true,
+        catalyst_consents: None,
+        tx_hash: "0xShortTransferHash".to_string(),
+    };
+    funding_service
+        .invest(investor_id, req)
+        .await
+        .expect("Investment failed");
+
+    // DB claims 10M more than any verified transfer backs
+    sqlx::query("UPDATE funding_pools SET funded_amount = funded_amount + 10000000 WHERE id = $1")
+        .bind(pool_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to inflate funded amount");
+
+    let result = funding_service.disburse_pool(pool_id).await;
+    assert!(
+        matches!(result, Err(AppError::BadRequest(ref msg)) if msg.contains("shortfall")),
+        "Under-backed pool must not disburse: {:?}",
+        result.err()
+    );
+
+    let status: (String,) = sqlx::query_as("SELECT status FROM funding_pools WHERE id = $1")
+        .bind(pool_id)
+        .fetch_one(&pool)
+        .await
+        .expect("Failed to fetch pool");
+    assert_ne!(status.0, "disbursed");
+
+    // Cleanup
+    for id in [investor_id, mitra_id] {
+        sqlx::query("DELETE FROM transactions WHERE user_id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .ok();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_refused_early_disbursement_reopens_pool() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_disburse_reopen@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_disburse_reopen@test.com").await;
+    let late_investor_id = create_investor(&pool, "late_investor_disburse_reopen@test.com").await;
+
+    let invest = || InvestRequest {
+        pool_id,
+        amount: 20_000_000.0,
+        tranche: "priority".to_string(),
+        tnc_accepted: true,
+        catalyst_consents: None,
+        tx_hash: unique_tx_hash(),
+    };
+    funding_service
+        .invest(investor_id, invest())
+        .await
+        .expect("Investment failed");
+
+    // The escrow check fails, after the open pool was claimed for disbursement
+    sqlx::query("UPDATE funding_pools SET funded_amount = funded_amount + 10000000 WHERE id = $1")
+        .bind(pool_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to inflate funded amount");
+    let result = funding_service.disburse_pool(pool_id).await;
+    assert!(
+        matches!(result, Err(AppError::BadRequest(ref msg)) if msg.contains("shortfall")),
+        "Under-backed pool must not disburse: {:?}",
+        result.err()
+    );
+
+    let (status, filled_at): (String, Option<chrono::NaiveDateTime>) =
+        sqlx::query_as("SELECT status, filled_at FROM funding_pools WHERE id = $1")
+            .bind(pool_id)
+            .fetch_one(&pool)
+            .await
+            .expect("Failed to fetch pool");
+    assert_eq!(status, "open");
+    assert!(filled_at.is_none());
+
+    // Nothing was sent, so the pool keeps taking investments
+    sqlx::query("UPDATE funding_pools SET funded_amount = funded_amount - 10000000 WHERE id = $1")
+        .bind(pool_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to restore funded amount");
+    funding_service
+        .invest(late_investor_id, invest())
+        .await
+        .expect("Reopened pool should take investments");
+
+    // Cleanup
+    for id in [investor_id, late_investor_id, mitra_id] {
+        sqlx::query("DELETE FROM transactions WHERE user_id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .ok();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+/// Platform wallet holding far less native token than a transfer's gas costs
+struct MockGasOracle;
+
+impl GasOracle for MockGasOracle {
+    fn estimate_transfer_gas<'a>(
+        &'a self,
+        _to: &'a str,
+        _amount: U256,
+    ) -> futures_util::future::BoxFuture<'a, crate::error::AppResult<U256>> {
+        Box::pin(async { Ok(U256::from(65_000u64)) })
+    }
+
+    fn gas_price(&self) -> futures_util::future::BoxFuture<'_, crate::error::AppResult<U256>> {
+        Box::pin(async { Ok(U256::from(1_000_000u64)) })
+    }
+
+    fn platform_native_balance(
+        &self,
+    ) -> futures_util::future::BoxFuture<'_, crate::error::AppResult<U256>> {
+        Box::pin(async { Ok(U256::from(1_000u64)) })
+    }
+}
+
+#[tokio::test]
+async fn test_disburse_blocked_when_platform_gas_insufficient() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) = setup_funding_service_with_mocks(
+        pool,
+        config,
+        None,
+        None,
+        Some(Arc::new(MockGasOracle) as Arc<dyn GasOracle>),
+        None,
+        None,
+    )
+    .await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_disburse_gas@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_disburse_gas@test.com").await;
+
+    let req = InvestRequest {
+        pool_id,
+        amount: 20_000_000.0,
+        tranche: "priority".to_string(),
+        tnc_accepted: true,
+        catalyst_consents: None,
+        tx_hash: "0xGasTransferHash".to_string(),
+    };
+    funding_service
+        .invest(investor_id, req)
+        .await
+        .expect("Investment failed");
+
+    let preview = funding_service
+        .get_disbursement_preview(pool_id)
+        .await
+        .expect("Preview failed");
+    assert_eq!(preview.estimated_gas, Some(65_000));
+    assert_eq!(
+        preview.estimated_gas_cost_wei.as_deref(),
+        Some("65000000000")
+    );
+    assert_eq!(preview.platform_native_balance_wei.as_deref(), Some("1000"));
+    assert!(!preview.sufficient_native_balance);
+    assert_eq!(
+        preview.blockers,
+        vec!["Platform wallet cannot pay the transfer gas".to_string()]
+    );
+    assert!(preview.idrx_amount < preview.funded_amount);
+
+    let result = funding_service.disburse_pool(pool_id).await;
+    assert!(
+        matches!(result, Err(AppError::InsufficientGas(_))),
+        "Disbursement must not proceed without gas: {:?}",
+        result.err()
+    );
+
+    let status: (String,) = sqlx::query_as("SELECT status FROM funding_pools WHERE id = $1")
+        .bind(pool_id)
+        .fetch_one(&pool)
+        .await
+        .expect("Failed to fetch pool");
+    assert_ne!(status.0, "disbursed");
+
+    // Cleanup
+    for id in [investor_id, mitra_id] {
+        sqlx::query("DELETE FROM transactions WHERE user_id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .ok();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_disburse_records_platform_fee() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, mitra_service, pool) = setup_funding_service(pool).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_disburse_fee@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_disburse_fee@test.com").await;
+
+    let req = InvestRequest {
+        pool_id,
+        amount: 20_000_000.0,
+        tranche: "priority".to_string(),
+        tnc_accepted: true,
+        catalyst_consents: None,
+        tx_hash: "0xFeeTransferHash".to_string(),
+    };
+    funding_service
+        .invest(investor_id, req)
+        .await
+        .expect("Investment failed");
+
+    let disbursed = funding_service
+        .disburse_pool(pool_id)
+        .await
+        .expect("Disbursement failed");
+
+    let advance: (rust_decimal::Decimal,) = sqlx::query_as(
+        "SELECT amount FROM transactions WHERE invoice_id = $1 AND type = 'advance_payment'",
+    )
+    .bind(invoice_id)
+    .fetch_one(&pool)
+    .await
+    .expect("Advance payment not recorded");
+    let fee: (rust_decimal::Decimal,) = sqlx::query_as(
+        "SELECT amount FROM transactions WHERE invoice_id = $1 AND type = 'platform_fee'",
+    )
+    .bind(invoice_id)
+    .fetch_one(&pool)
+    .await
+    .expect("Platform fee not recorded");
+
+    let expected_fee = 20_000_000u64 * config.platform_fee_bps as u64 / 10_000;
+    assert_eq!(fee.0, rust_decimal::Decimal::from(expected_fee));
+    assert_eq!(advance.0 + fee.0, disbursed.funded_amount);
+
+    // Breakdown grosses the repayment up so investors are whole after the fee
+    let breakdown = mitra_service
+        .get_repayment_breakdown(mitra_id, pool_id)
+        .await
+        .expect("Breakdown failed");
+    assert_eq!(breakdown.priority_breakdown.investor_count, 1);
+    assert!(breakdown.platform_fee > rust_decimal::Decimal::ZERO);
+    let investor_total = breakdown.principal_amount + breakdown.total_interest;
+    assert!(breakdown.total_repayment - breakdown.platform_fee >= investor_total);
+
+    // Another mitra cannot read the breakdown
+    let result = mitra_service
+        .get_repayment_breakdown(investor_id, pool_id)
+        .await;
+    assert!(matches!(result, Err(AppError::Forbidden(_))));
+
+    // Cleanup
+    for id in [investor_id, mitra_id] {
+        sqlx::query("DELETE FROM transactions WHERE user_id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .ok();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_retried_blockchain_transaction_is_recorded_once() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let tx_repo = TransactionRepository::new(pool.clone());
+    let investor_id = create_investor(&pool, "investor_tx_retry@test.com").await;
+    let other_investor_id = create_investor(&pool, "investor_tx_retry_other@test.com").await;
+    let tx_hash = format!("0x{}", Uuid::new_v4().simple());
+    let record = |user_id: Uuid, tx_type: &'static str, amount: i64| {
+        let tx_repo = tx_repo.clone();
+        let tx_hash = tx_hash.clone();
+        async move {
+            tx_repo
+                .create_blockchain_transaction(
+                    user_id,
+                    tx_type,
+                    rust_decimal::Decimal::from(amount),
+                    &tx_hash,
+                    42,
+                    None,
+                    Some("Retried recording"),
+                    "https://explorer.test/tx",
+                )
+                .await
+        }
+    };
+
+    let first = record(investor_id, "investment", 1_000_000)
+        .await
+        .expect("Recording failed");
+    let retried = record(investor_id, "investment", 1_000_000)
+        .await
+        .expect("Retry failed");
+    assert_eq!(first.id, retried.id);
+
+    // A different amount or user under the same hash is not a retry
+    let result = record(investor_id, "investment", 2_000_000).await;
+    assert!(matches!(result, Err(AppError::Conflict(_))));
+    let result = record(other_investor_id, "investment", 1_000_000).await;
+    assert!(matches!(result, Err(AppError::Conflict(_))));
+
+    // The same hash can still back a different transaction type
+    let fee = record(investor_id, "platform_fee", 10_000)
+        .await
+        .expect("Recording fee failed");
+    assert_ne!(fee.id, first.id);
+
+    let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM transactions WHERE tx_hash = $1")
+        .bind(&tx_hash)
+        .fetch_one(&pool)
+        .await
+        .expect("Count failed");
+    assert_eq!(count.0, 2);
+
+    // Cleanup
+    sqlx::query("DELETE FROM transactions WHERE user_id = $1")
+        .bind(investor_id)
+        .execute(&pool)
+        .await
+        .ok();
+    for id in [investor_id, other_investor_id] {
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_repayment_schedule_splits_total_between_investors_and_fee() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_schedule@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let priority_investor = create_investor(&pool, "investor_schedule_p@test.com").await;
+    let catalyst_investor = create_investor(&pool, "investor_schedule_c@test.com").await;
+
+    // Odd amounts so the interest and the fee do not land on whole cents
+    for (investor_id, amount, tranche, catalyst_consents) in [
+        (priority_investor, 12_345_678.0, "priority", None),
+        (
+            catalyst_investor,
+            7_654_321.0,
+            "catalyst",
+            Some(CatalystConsents {
+                first_loss_consent: true,
+                risk_loss_consent: true,
+                not_bank_consent: true,
+            }),
+        ),
+    ] {
+        funding_service
+            .invest(
+                investor_id,
+                InvestRequest {
+                    pool_id,
+                    amount,
+                    tranche: tranche.to_string(),
+                    tnc_accepted: true,
+                    catalyst_consents,
+                    tx_hash: format!("0x{}", Uuid::new_v4().simple()),
+                },
+            )
+            .await
+            .expect("Investment failed");
+    }
+
+    let schedule = funding_service
+        .get_repayment_schedule(mitra_id, false, pool_id)
+        .await
+        .expect("Schedule failed");
+    assert_eq!(schedule.investors.len(), 2);
+    for investor in &schedule.investors {
+        assert_eq!(investor.principal + investor.interest, investor.total);
+    }
+
+    // Computed in Decimal, so the split adds up exactly
+    let investor_total: rust_decimal::Decimal = schedule.investors.iter().map(|i| i.total).sum();
+    assert!(schedule.platform_fee > rust_decimal::Decimal::ZERO);
+    assert!(schedule.rounding_remainder >= rust_decimal::Decimal::ZERO);
+    assert!(schedule.rounding_remainder < rust_decimal::Decimal::new(1, 2));
+    assert_eq!(
+        investor_total + schedule.platform_fee + schedule.rounding_remainder,
+        schedule.total_repayment
+    );
+    assert_eq!(
+        schedule.priority_breakdown.total + schedule.catalyst_breakdown.total,
+        investor_total
+    );
+
+    // Same total the mitra is asked to repay
+    let breakdown = funding_service
+        .get_repayment_breakdown(mitra_id, pool_id)
+        .await
+        .expect("Breakdown failed");
+    assert_eq!(schedule.total_repayment, breakdown.total_repayment);
+
+    // Admins can read any schedule, other users cannot
+    assert!(funding_service
+        .get_repayment_schedule(Uuid::new_v4(), true, pool_id)
+        .await
+        .is_ok());
+    let result = funding_service
+        .get_repayment_schedule(priority_investor, false, pool_id)
+        .await;
+    assert!(matches!(result, Err(AppError::Forbidden(_))));
+
+    // Cleanup
+    for id in [priority_investor, catalyst_investor, mitra_id] {
+        sqlx::query("DELETE FROM transactions WHERE user_id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .ok();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_investment_receipt_includes_explorer_url_and_expected_return() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_receipt@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_receipt@test.com").await;
+
+    let req = InvestRequest {
+        pool_id,
+        amount: 20_000_000.0,
+        tranche: "priority".to_string(),
+        tnc_accepted: true,
+        catalyst_consents: None,
+        tx_hash: "0xReceiptTransferHash".to_string(),
+    };
+    let investment = funding_service
+        .invest(investor_id, req)
+        .await
+        .expect("Investment failed");
+
+    let receipt = funding_service
+        .get_investment_receipt(investor_id, investment.id)
+        .await
+        .expect("Receipt failed");
+
+    assert_eq!(receipt.investment_id, investment.id);
+    assert_eq!(receipt.pool_id, pool_id);
+    assert_eq!(receipt.invoice_id, invoice_id);
+    assert_eq!(receipt.tx_hash.as_deref(), Some("0xReceiptTransferHash"));
+    assert!(receipt.explorer_url.as_deref().is_some_and(|url| !url.is_empty()));
+    assert_eq!(receipt.expected_return, investment.expected_return);
+    assert!(receipt.expected_return >= investment.amount);
+    assert_eq!(receipt.confirmation_status, "confirmed");
+
+    // Other users cannot read the receipt
+    let result = funding_service
+        .get_investment_receipt(mitra_id, investment.id)
+        .await;
+    assert!(matches!(result, Err(AppError::Forbidden(_))));
+
+    // Cleanup
+    for id in [investor_id, mitra_id] {
+        sqlx::query("DELETE FROM transactions WHERE user_id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .ok();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+async fn invest_for_detail_test(
+    mitra_email: &str,
+    investor_email: &str,
+) -> (Arc<FundingService>, PgPool, Uuid, Uuid, Uuid, Uuid) {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, mitra_email).await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, investor_email).await;
+
+    let investment = funding_service
+        .invest(
+            investor_id,
+            InvestRequest {
+                pool_id,
+                amount: 20_000_000.0,
+                tranche: "priority".to_string(),
+                tnc_accepted: true,
+                catalyst_consents: None,
+                tx_hash: format!("0xDetailTransfer_{}", Uuid::new_v4().simple()),
+            },
+        )
+        .await
+        .expect("Investment failed");
+
+    (
+        funding_service,
+        pool,
+        mitra_id,
+        investor_id,
+        pool_id,
+        investment.id,
+    )
+}
+
+#[tokio::test]
+async fn test_investment_detail_for_owner() {
+    let (funding_service, pool, mitra_id, investor_id, pool_id, investment_id) =
+        invest_for_detail_test("mitra_inv_detail@test.com", "investor_inv_detail@test.com").await;
+
+    let detail = funding_service
+        .get_investment_detail(investor_id, investment_id)
+        .await
+        .expect("Detail failed");
+
+    assert_eq!(detail.investment.id, investment_id);
+    assert_eq!(detail.pool.id, pool_id);
+    assert_eq!(detail.invoice.id, detail.pool.invoice_id);
+    assert_eq!(detail.interest_rate, detail.pool.priority_interest_rate);
+    assert_eq!(
+        detail.expected_gain,
+        detail.investment.expected_return - detail.investment.amount
+    );
+    assert!(detail.current_return >= rust_decimal::Decimal::ZERO);
+    assert!(detail.current_return <= detail.expected_gain);
+    assert_eq!(
+        detail.days_remaining,
+        (detail.invoice.due_date - chrono::Utc::now().date_naive()).num_days()
+    );
+    assert!(detail
+        .tx_explorer_url
+        .as_deref()
+        .is_some_and(|url| url.ends_with(detail.investment.tx_hash.as_deref().unwrap())));
+    assert!(detail.return_tx_explorer_url.is_none());
+
+    // The investor sees the exporter's invoice, but not its buyer email or wallet
+    let wallet = detail.invoice.exporter_wallet_address.clone().unwrap();
+    let masked = serde_json::to_value(Masked(&detail)).expect("Failed to serialize");
+    assert_eq!(masked["invoice"]["buyer_email"], "b***@test.com");
+    assert_eq!(
+        masked["invoice"]["exporter_wallet_address"],
+        mask_wallet(&wallet)
+    );
+
+    cleanup_disbursement_test(&pool, investor_id, mitra_id).await;
+}
+
+#[tokio::test]
+async fn test_investment_detail_forbidden_for_other_user() {
+    let (funding_service, pool, mitra_id, investor_id, _, investment_id) = invest_for_detail_test(
+        "mitra_inv_detail_foreign@test.com",
+        "investor_inv_detail_foreign@test.com",
+    )
+    .await;
+    let other_investor_id = create_investor(&pool, "other_inv_detail_foreign@test.com").await;
+
+    let result = funding_service
+        .get_investment_detail(other_investor_id, investment_id)
+        .await;
+    assert!(
+        matches!(result, Err(AppError::Forbidden(_))),
+        "Expected Forbidden, got {:?}",
+        result
+    );
+
+    cleanup_disbursement_test(&pool, investor_id, mitra_id).await;
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(other_investor_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_invoice_funding_status_for_owner() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_funding_status@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+
+    // Half of the priority tranche raised by two investors, a day left
+    sqlx::query(
+        "UPDATE funding_pools SET priority_funded = priority_target / 2, funded_amount = priority_target / 2, investor_count = 2, deadline = NOW() + INTERVAL '1 day' WHERE id = $1",
+    )
+    .bind(pool_id)
+    .execute(&pool)
+    .await
+    .expect("Failed to fund pool");
+
+    let status = funding_service
+        .get_invoice_funding_status(mitra_id, invoice_id)
+        .await
+        .expect("Owner should see the funding status");
+    let expected_pct = rust_decimal::prelude::ToPrimitive::to_f64(
+        &(status.pool.pool.priority_target
+            / rust_decimal::Decimal::from(2)
+            / status.pool.pool.target_amount
+            * rust_decimal::Decimal::from(100)),
+    )
+    .unwrap();
+    assert_eq!(status.pool.pool.id, pool_id);
+    assert!((status.pool.percentage_funded - expected_pct).abs() < 0.01);
+    assert!((status.pool.priority_percentage_funded - 50.0).abs() < 0.01);
+    assert_eq!(status.pool.catalyst_percentage_funded, 0.0);
+    assert_eq!(status.investor_count, 2);
+    let remaining = status.seconds_remaining.expect("Pool has a deadline");
+    assert!(remaining > 0 && remaining <= 86_400);
+    assert!(!status.disbursement_eligible);
+
+    sqlx::query("UPDATE funding_pools SET status = 'filled' WHERE id = $1")
+        .bind(pool_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to fill pool");
+    let status = funding_service
+        .get_invoice_funding_status(mitra_id, invoice_id)
+        .await
+        .expect("Owner should see the funding status");
+    assert!(status.disbursement_eligible);
+
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_invoice_funding_status_forbidden_for_other_mitra() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let (mitra_id, invoice_id) = create_mitra_and_invoice(
+        &pool,
+        &invoice_service,
+        "mitra_funding_status_owner@test.com",
+    )
+    .await;
+    setup_pool(&pool, &funding_service, invoice_id).await;
+
+    let result = funding_service
+        .get_invoice_funding_status(Uuid::new_v4(), invoice_id)
+        .await;
+    assert!(
+        matches!(result, Err(AppError::Forbidden(_))),
+        "Expected Forbidden, got {:?}",
+        result
+    );
+
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_soft_deleted_invoice_pool_hidden_from_mitra_visible_to_admin() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_soft_delete@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+
+    // Pool was closed without funding, so nothing is active any more
+    sqlx::query("UPDATE funding_pools SET status = 'closed' WHERE id = $1")
+        .bind(pool_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to close pool");
+    sqlx::query("UPDATE invoices SET status = 'rejected' WHERE id = $1")
+        .bind(invoice_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to reject invoice");
+
+    let deleted = invoice_service
+        .delete_invoice(mitra_id, invoice_id)
+        .await
+        .expect("Soft delete should succeed");
+    assert!(deleted.deleted_at.is_some());
+
+    // Mitra views exclude the pool and the invoice
+    let (mitra_pools, mitra_total) = funding_service
+        .get_mitra_pools(mitra_id, 1, 10)
+        .await
+        .expect("Mitra pools failed");
+    assert_eq!(mitra_total, 0);
+    assert!(mitra_pools.iter().all(|p| p.pool.id != pool_id));
+    let (mitra_invoices, _) = invoice_service
+        .list_by_exporter(mitra_id, 1, 10, None, false, None)
+        .await
+        .expect("Mitra invoices failed");
+    assert!(mitra_invoices.iter().all(|i| i.id != invoice_id));
+    let result = funding_service.get_pool_by_invoice(mitra_id, invoice_id).await;
+    assert!(matches!(result, Err(AppError::NotFound(_))));
+
+    // Admin audit view still has the financial record
+    let (admin_pools, admin_total) = funding_service
+        .get_exporter_pools_for_audit(mitra_id, 1, 10)
+        .await
+        .expect("Admin pools failed");
+    assert_eq!(admin_total, 1);
+    assert_eq!(admin_pools[0].pool.id, pool_id);
+    let (admin_invoices, _) = invoice_service
+        .list_by_exporter(mitra_id, 1, 10, None, true, None)
+        .await
+        .expect("Admin invoices failed");
+    assert!(admin_invoices.iter().any(|i| i.id == invoice_id));
+
+    // Cleanup
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_soft_delete_invoice_with_active_pool_rejected() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_delete_guard@test.com").await;
+    setup_pool(&pool, &funding_service, invoice_id).await;
+
+    let result = invoice_service.delete_invoice(mitra_id, invoice_id).await;
+    assert!(
+        matches!(result, Err(AppError::Conflict(_))),
+        "Invoice with an open pool must not be deleted: {:?}",
+        result.map(|i| i.id)
+    );
+
+    let deleted_at: (Option<chrono::NaiveDateTime>,) =
+        sqlx::query_as("SELECT deleted_at FROM invoices WHERE id = $1")
+            .bind(invoice_id)
+            .fetch_one(&pool)
+            .await
+            .expect("Failed to fetch invoice");
+    assert!(deleted_at.0.is_none());
+
+    // Cleanup
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_soft_delete_draft_invoice_allowed() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (_, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_delete_draft@test.com").await;
+
+    // Someone else's invoice is off limits
+    let result = invoice_service
+        .delete_invoice(Uuid::new_v4(), invoice_id)
+        .await;
+    assert!(matches!(result, Err(AppError::Forbidden(_))));
+
+    let deleted = invoice_service
+        .delete_invoice(mitra_id, invoice_id)
+        .await
+        .expect("Draft invoice should be deletable");
+    assert!(deleted.deleted_at.is_some());
+
+    // The row is kept but drops out of listings, and a second delete finds nothing
+    let (invoices, total) = invoice_service
+        .list_by_exporter(mitra_id, 1, 10, None, false, None)
+        .await
+        .expect("Mitra invoices failed");
+    assert_eq!(total, 0);
+    assert!(invoices.is_empty());
+    let result = invoice_service.delete_invoice(mitra_id, invoice_id).await;
+    assert!(matches!(result, Err(AppError::NotFound(_))));
+
+    // Cleanup
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_soft_delete_funded_invoice_refused() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (_, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_delete_funded@test.com").await;
+
+    for status in ["tokenized", "funded"] {
+        sqlx::query("UPDATE invoices SET status = $2 WHERE id = $1")
+            .bind(invoice_id)
+            .bind(status)
+            .execute(&pool)
+            .await
+            .expect("Failed to update invoice status");
+
+        let result = invoice_service.delete_invoice(mitra_id, invoice_id).await;
+        assert!(
+            matches!(result, Err(AppError::Conflict(_))),
+            "A {} invoice must not be deleted",
+            status
+        );
+    }
+
+    let deleted_at: (Option<chrono::NaiveDateTime>,) =
+        sqlx::query_as("SELECT deleted_at FROM invoices WHERE id = $1")
+            .bind(invoice_id)
+            .fetch_one(&pool)
+            .await
+            .expect("Failed to fetch invoice");
+    assert!(deleted_at.0.is_none());
+
+    // Cleanup
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+async fn seed_confirmed_transaction(pool: &PgPool, user_id: Uuid, tx_type: &str, amount: f64) {
+    sqlx::query(
+        r#"INSERT INTO transactions (user_id, type, amount, currency, tx_hash, status)
+           VALUES ($1, $2, $3, 'IDRX', $4, 'confirmed')"#,
+    )
+    .bind(user_id)
+    .bind(tx_type)
+    .bind(rust_decimal::Decimal::from_f64_retain(amount).unwrap())
+    .bind(unique_tx_hash())
+    .execute(pool)
+    .await
+    .expect("Failed to seed transaction");
+}
+
+#[tokio::test]
+async fn test_invest_under_velocity_cap_succeeds() {
+    let mut config = get_test_config();
+    config.investor_velocity_cap = 50_000_000.0;
+    config.velocity_window_days = 30;
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) =
+        setup_funding_service_with_config(pool, config).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_velocity_ok@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_velocity_ok@test.com").await;
+
+    // 20M already invested this window + 20M now = 40M <= 50M cap
+    seed_confirmed_transaction(&pool, investor_id, "investment", 20_000_000.0).await;
+
+    let req = InvestRequest {
+        pool_id,
+        amount: 20_000_000.0,
+        tranche: "priority".to_string(),
+        tnc_accepted: true,
+        catalyst_consents: None,
+        tx_hash: unique_tx_hash(),
+    };
+
+    let result = funding_service.invest(investor_id, req).await;
+    assert!(
+        result.is_ok(),
+        "Investment under velocity cap should succeed: {:?}",
+        result.err()
+    );
+
+    // Cleanup
+    sqlx::query("DELETE FROM transactions WHERE user_id = $1")
+        .bind(investor_id)
+        .execute(&pool)
+        .await
+        .ok();
+    sqlx::query("DELETE FROM users WHERE id = $1 OR id = $2")
+        .bind(investor_id)
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_invest_over_velocity_cap_fails() {
+    let mut config = get_test_config();
+    config.investor_velocity_cap = 30_000_000.0;
+    config.velocity_window_days = 30;
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) =
+        setup_funding_service_with_config(pool, config).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_velocity_over@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_velocity_over@test.com").await;
+
+    // 20M already invested this window + 20M now = 40M > 30M cap
+    seed_confirmed_transaction(&pool, investor_id, "investment", 20_000_000.0).await;
+
+    let req = InvestRequest {
+        pool_id,
+        amount: 20_000_000.0,
+        tranche: "priority".to_string(),
+        tnc_accepted: true,
+        catalyst_consents: None,
+        tx_hash: unique_tx_hash(),
+    };
+
+    let result = funding_service.invest(investor_id, req).await;
+    assert!(
+        matches!(result, Err(AppError::ComplianceLimitExceeded(_))),
+        "Investment over velocity cap should fail with compliance error: {:?}",
+        result.err()
+    );
+
+    // Cleanup
+    sqlx::query("DELETE FROM transactions WHERE user_id = $1")
+        .bind(investor_id)
+        .execute(&pool)
+        .await
+        .ok();
+    sqlx::query("DELETE FROM users WHERE id = $1 OR id = $2")
+        .bind(investor_id)
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_create_pool_over_mitra_velocity_cap_fails() {
+    let mut config = get_test_config();
+    config.mitra_velocity_cap = 150_000_000.0;
+    config.velocity_window_days = 30;
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) =
+        setup_funding_service_with_config(pool, config).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_velocity_pool@test.com").await;
+
+    // 100M already raised this window + 100M pool target > 150M cap
+    seed_confirmed_transaction(&pool, mitra_id, "advance_payment", 100_000_000.0).await;
+
+    sqlx::query("UPDATE invoices SET status = 'tokenized' WHERE id = $1")
+        .bind(invoice_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to update invoice status");
+
+    let result = funding_service.create_pool(invoice_id).await;
+    assert!(
+        matches!(result, Err(AppError::ComplianceLimitExceeded(_))),
+        "Pool over mitra velocity cap should fail with compliance error: {:?}",
+        result.err()
+    );
+
+    // Cleanup
+    sqlx::query("DELETE FROM transactions WHERE user_id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+/// Two open pools of the same mitra, for per-mitra exposure tests
+async fn setup_two_pools_of_one_mitra(
+    pool: &PgPool,
+    funding_service: &Arc<FundingService>,
+    invoice_service: &Arc<InvoiceService>,
+    base_email: &str,
+) -> (Uuid, Uuid, Uuid, Uuid) {
+    let (mitra_id, first_invoice) =
+        create_mitra_and_invoice(pool, invoice_service, base_email).await;
+    let (other_mitra_id, second_invoice) =
+        create_mitra_and_invoice(pool, invoice_service, base_email).await;
+    sqlx::query("UPDATE invoices SET exporter_id = $1 WHERE id = $2")
+        .bind(mitra_id)
+        .bind(second_invoice)
+        .execute(pool)
+        .await
+        .expect("Failed to move invoice");
+
+    let first_pool = setup_pool(pool, funding_service, first_invoice).await;
+    let second_pool = setup_pool(pool, funding_service, second_invoice).await;
+    (mitra_id, other_mitra_id, first_pool, second_pool)
+}
+
+fn priority_request(pool_id: Uuid, amount: f64) -> InvestRequest {
+    InvestRequest {
+        pool_id,
+        amount,
+        tranche: "priority".to_string(),
+        tnc_accepted: true,
+        catalyst_consents: None,
+        tx_hash: format!("0x{}", Uuid::new_v4().simple()),
+    }
+}
+
+#[tokio::test]
+async fn test_invest_under_mitra_exposure_cap_succeeds() {
+    let mut config = get_test_config();
+    config.skip_blockchain_verification = true;
+    config.investor_mitra_exposure_cap = 30_000_000.0;
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) =
+        setup_funding_service_with_config(pool, config).await;
+    let (mitra_id, other_mitra_id, first_pool, second_pool) = setup_two_pools_of_one_mitra(
+        &pool,
+        &funding_service,
+        &invoice_service,
+        "mitra_exposure_under@test.com",
+    )
+    .await;
+    let investor_id = create_investor(&pool, "investor_exposure_under@test.com").await;
+
+    funding_service
+        .invest(investor_id, priority_request(first_pool, 20_000_000.0))
+        .await
+        .expect("First investment failed");
+
+    // 20M + 10M reaches the cap exactly
+    let result = funding_service
+        .invest(investor_id, priority_request(second_pool, 10_000_000.0))
+        .await;
+    assert!(result.is_ok(), "Expected success, got {:?}", result.err());
+
+    // Cleanup
+    for id in [investor_id, mitra_id, other_mitra_id] {
+        sqlx::query("DELETE FROM transactions WHERE user_id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .ok();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_invest_over_mitra_exposure_cap_fails() {
+    let mut config = get_test_config();
+    config.skip_blockchain_verification = true;
+    config.investor_mitra_exposure_cap = 30_000_000.0;
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) =
+        setup_funding_service_with_config(pool, config).await;
+    let (mitra_id, other_mitra_id, first_pool, second_pool) = setup_two_pools_of_one_mitra(
+        &pool,
+        &funding_service,
+        &invoice_service,
+        "mitra_exposure_over@test.com",
+    )
+    .await;
+    let investor_id = create_investor(&pool, "investor_exposure_over@test.com").await;
+
+    funding_service
+        .invest(investor_id, priority_request(first_pool, 20_000_000.0))
+        .await
+        .expect("First investment failed");
+
+    // Exposure is summed across the mitra's pools: 20M + 15M exceeds 30M
+    let result = funding_service
+        .invest(investor_id, priority_request(second_pool, 15_000_000.0))
+        .await;
+    match result {
+        Err(AppError::ConcentrationLimitExceeded(msg)) => assert!(
+            msg.contains("20000000"),
+            "Message should name the exposure: {}",
+            msg
+        ),
+        other => panic!("Expected ConcentrationLimitExceeded, got {:?}", other),
+    }
+
+    // Nothing was recorded for the rejected investment
+    let second: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM investments WHERE pool_id = $1")
+        .bind(second_pool)
+        .fetch_one(&pool)
+        .await
+        .expect("Count failed");
+    assert_eq!(second.0, 0);
+
+    // Cleanup
+    for id in [investor_id, mitra_id, other_mitra_id] {
+        sqlx::query("DELETE FROM transactions WHERE user_id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .ok();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_concurrent_investments_cannot_overfill_tranche() {
+    let mut config = get_test_config();
+    config.skip_blockchain_verification = true;
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) =
+        setup_funding_service_with_config(pool, config).await;
+
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_concurrent@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let first_investor = create_investor(&pool, "investor_concurrent_a@test.com").await;
+    let second_investor = create_investor(&pool, "investor_concurrent_b@test.com").await;
+
+    // Priority target is 70M; leave 15M so only one of two 10M investments fits
+    sqlx::query(
+        "UPDATE funding_pools SET priority_funded = priority_target - 15000000, funded_amount = priority_target - 15000000 WHERE id = $1",
+    )
+    .bind(pool_id)
+    .execute(&pool)
+    .await
+    .expect("Failed to adjust funded amount");
+
+    let invest_request = |tx_hash: &str| InvestRequest {
+        pool_id,
+        amount: 10_000_000.0,
+        tranche: "priority".to_string(),
+        tnc_accepted: true,
+        catalyst_consents: None,
+        tx_hash: tx_hash.to_string(),
+    };
+
+    let (first, second) = tokio::join!(
+        funding_service.invest(first_investor, invest_request("0xConcurrentHashA")),
+        funding_service.invest(second_investor, invest_request("0xConcurrentHashB")),
+    );
+
+    let succeeded = [first.is_ok(), second.is_ok()]
+        .iter()
+        .filter(|ok| **ok)
+        .count();
+    assert_eq!(succeeded, 1, "Exactly one concurrent investment should fit");
+
+    let row = sqlx::query(
+        "SELECT priority_funded, priority_target, funded_amount FROM funding_pools WHERE id = $1",
+    )
+    .bind(pool_id)
+    .fetch_one(&pool)
+    .await
+    .expect("Failed to fetch pool");
+    let priority_funded: rust_decimal::Decimal = row.get("priority_funded");
+    let priority_target: rust_decimal::Decimal = row.get("priority_target");
+    let funded_amount: rust_decimal::Decimal = row.get("funded_amount");
+    assert!(priority_funded <= priority_target, "Tranche was overfilled");
+    assert_eq!(
+        priority_target - priority_funded,
+        rust_decimal::Decimal::from(5_000_000)
+    );
+    assert_eq!(funded_amount, priority_funded);
+
+    // Cleanup
+    for id in [first_investor, second_investor, mitra_id] {
+        sqlx::query("DELETE FROM transactions WHERE user_id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .ok();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_catalyst_minimum_is_relative_to_tranche_target() {
+    let mut config = get_test_config();
+    config.skip_blockchain_verification = true;
+    config.catalyst_min_investment_pct = 10.0;
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) =
+        setup_funding_service_with_config(pool, config).await;
+
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_catalyst_min@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_catalyst_min@test.com").await;
+
+    // Pool target 100M, catalyst tranche 30M: 5M is below 10% of the pool (10M)
+    // but above 10% of the catalyst tranche (3M)
+    let req = InvestRequest {
+        pool_id,
+        amount: 5_000_000.0,
+        tranche: "catalyst".to_string(),
+        tnc_accepted: true,
+        catalyst_consents: Some(CatalystConsents {
+            first_loss_consent: true,
+            risk_loss_consent: true,
+            not_bank_consent: true,
+        }),
+        tx_hash: "0xCatalystMinHash".to_string(),
+    };
+
+    let result = funding_service.invest(investor_id, req).await;
+    assert!(
+        result.is_ok(),
+        "Catalyst investment above the tranche minimum should succeed: {:?}",
+        result.err()
+    );
+
+    // Cleanup
+    sqlx::query("DELETE FROM transactions WHERE user_id = $1")
+        .bind(investor_id)
+        .execute(&pool)
+        .await
+        .ok();
+    sqlx::query("DELETE FROM users WHERE id = $1 OR id = $2")
+        .bind(investor_id)
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+async fn invest_with_deadline(
+    email_prefix: &str,
+    deadline: chrono::NaiveDateTime,
+) -> Result<crate::models::Investment, AppError> {
+    let mut config = get_test_config();
+    config.skip_blockchain_verification = true;
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) =
+        setup_funding_service_with_config(pool, config).await;
+
+    let mitra_email = format!("mitra_{}@test.com", email_prefix);
+    let investor_email = format!("investor_{}@test.com", email_prefix);
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, &mitra_email).await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, &investor_email).await;
+
+    // Pool stays "open"; only the deadline moves
+    sqlx::query("UPDATE funding_pools SET deadline = $2 WHERE id = $1")
+        .bind(pool_id)
+        .bind(deadline)
+        .execute(&pool)
+        .await
+        .expect("Failed to set deadline");
+
+    let req = InvestRequest {
+        pool_id,
+        amount: 20_000_000.0,
+        tranche: "priority".to_string(),
+        tnc_accepted: true,
+        catalyst_consents: None,
+        tx_hash: format!("0xDeadlineHash_{}", email_prefix),
+    };
+    let result = funding_service.invest(investor_id, req).await;
+
+    // Cleanup
+    sqlx::query("DELETE FROM transactions WHERE user_id = $1")
+        .bind(investor_id)
+        .execute(&pool)
+        .await
+        .ok();
+    sqlx::query("DELETE FROM users WHERE id = $1 OR id = $2")
+        .bind(investor_id)
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+
+    result
+}
+
+#[tokio::test]
+async fn test_invest_just_before_deadline_succeeds() {
+    let deadline = chrono::Utc::now().naive_utc() + chrono::Duration::minutes(1);
+    let result = invest_with_deadline("deadline_before", deadline).await;
+    assert!(
+        result.is_ok(),
+        "Investment before the deadline should succeed: {:?}",
+        result.err()
+    );
+}
+
+#[tokio::test]
+async fn test_invest_after_deadline_rejected_while_pool_open() {
+    let deadline = chrono::Utc::now().naive_utc() - chrono::Duration::seconds(1);
+    let result = invest_with_deadline("deadline_after", deadline).await;
+    assert!(
+        matches!(result, Err(AppError::BadRequest(_))),
+        "Investment after the deadline should be rejected: {:?}",
+        result
+    );
+}
+
+#[tokio::test]
+async fn test_invest_records_investment_created_activity() {
+    let mut config = get_test_config();
+    config.skip_blockchain_verification = true;
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) =
+        setup_funding_service_with_config(pool, config).await;
+
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_activity@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_activity@test.com").await;
+
+    let req = InvestRequest {
+        pool_id,
+        amount: 20_000_000.0,
+        tranche: "priority".to_string(),
+        tnc_accepted: true,
+        catalyst_consents: None,
+        tx_hash: "0xActivityHash".to_string(),
+    };
+    funding_service
+        .invest(investor_id, req)
+        .await
+        .expect("Investment failed");
+
+    let (activities, total) = ActivityRepository::new(pool.clone())
+        .find_by_user(investor_id, 1, 10)
+        .await
+        .expect("Failed to load activity");
+    assert_eq!(total, 1);
+    assert_eq!(activities[0].activity_type, "investment_created");
+    assert!(activities[0].summary.contains("priority"));
+
+    // Cleanup
+    sqlx::query("DELETE FROM transactions WHERE user_id = $1")
+        .bind(investor_id)
+        .execute(&pool)
+        .await
+        .ok();
+    sqlx::query("DELETE FROM users WHERE id = $1 OR id = $2")
+        .bind(investor_id)
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_retry_tokenize_mints_and_creates_pool_for_approved_invoice() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_retry_tokenize@test.com").await;
+
+    // Graded and approved, but the mint never went through
+    sqlx::query("UPDATE invoices SET status = 'approved' WHERE id = $1")
+        .bind(invoice_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to approve invoice");
+
+    let created = funding_service
+        .retry_tokenize(invoice_id)
+        .await
+        .expect("Retry tokenize failed");
+    let funding_pool = created.expect("Retry should create a funding pool");
+    assert_eq!(funding_pool.invoice_id, invoice_id);
+
+    let nft = InvoiceRepository::new(pool.clone())
+        .find_nft_by_invoice(invoice_id)
+        .await
+        .expect("Failed to load NFT")
+        .expect("NFT record should exist");
+    assert_eq!(nft.token_id, Some(12345));
+
+    let invoice = invoice_service
+        .get_invoice(invoice_id)
+        .await
+        .expect("Failed to load invoice");
+    assert_eq!(invoice.status, "funding");
+    // Grading is untouched
+    assert_eq!(invoice.grade.as_deref(), Some("A"));
+
+    // Cleanup
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_retry_tokenize_is_noop_when_already_tokenized() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_retry_noop@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+
+    let created = funding_service
+        .retry_tokenize(invoice_id)
+        .await
+        .expect("Retry on a tokenized invoice should succeed");
+    assert!(created.is_none());
+
+    let nft_count: (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM invoice_nfts WHERE invoice_id = $1")
+            .bind(invoice_id)
+            .fetch_one(&pool)
+            .await
+            .expect("Failed to count NFTs");
+    assert_eq!(nft_count.0, 1);
+
+    let funding_pool = FundingRepository::new(pool.clone())
+        .find_by_invoice(invoice_id)
+        .await
+        .expect("Failed to load pool")
+        .expect("Pool should still exist");
+    assert_eq!(funding_pool.id, pool_id);
+
+    let invoice = invoice_service
+        .get_invoice(invoice_id)
+        .await
+        .expect("Failed to load invoice");
+    assert_eq!(invoice.status, "funding");
+
+    // Cleanup
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_concurrent_approvals_create_one_nft_and_one_pool() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_concurrent_approve@test.com")
+            .await;
+    sqlx::query("UPDATE invoices SET status = 'pending_review' WHERE id = $1")
+        .bind(invoice_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to submit invoice");
+
+    let (first, second) = tokio::join!(
+        funding_service.approve_and_tokenize(
+            invoice_id,
+            invoice_service.approve(invoice_id, "A", None, None)
+        ),
+        funding_service.approve_and_tokenize(
+            invoice_id,
+            invoice_service.approve(invoice_id, "A", None, None)
+        ),
+    );
+    let first = first.expect("First approval failed");
+    let second = second.expect("Second approval failed");
+    assert_eq!(first.id, second.id);
+
+    let nft_count: (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM invoice_nfts WHERE invoice_id = $1")
+            .bind(invoice_id)
+            .fetch_one(&pool)
+            .await
+            .expect("Failed to count NFTs");
+    assert_eq!(nft_count.0, 1);
+
+    let pool_count: (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM funding_pools WHERE invoice_id = $1")
+            .bind(invoice_id)
+            .fetch_one(&pool)
+            .await
+            .expect("Failed to count pools");
+    assert_eq!(pool_count.0, 1);
+
+    let invoice = invoice_service
+        .get_invoice(invoice_id)
+        .await
+        .expect("Failed to load invoice");
+    assert_eq!(invoice.status, "funding");
+
+    // Cleanup
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_off_chain_investment_debits_balance() {
+    let mut config = get_test_config();
+    config.investment_mode = InvestmentMode::OffChain;
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (funding_service, invoice_service, _, pool) =
+        setup_funding_service_with_config(pool, config).await;
+
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_off_chain@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_off_chain@test.com").await;
+    let poor_investor_id = create_investor(&pool, "investor_off_chain_poor@test.com").await;
+
+    for (user_id, balance) in [(investor_id, 50_000_000), (poor_investor_id, 5_000_000)] {
+        sqlx::query("UPDATE users SET balance_idrx = $2 WHERE id = $1")
+            .bind(user_id)
+            .bind(rust_decimal::Decimal::from(balance))
+            .execute(&pool)
+            .await
+            .expect("Failed to fund balance");
+    }
+
+    // No transfer hash is needed off-chain
+    let investment = funding_service
+        .invest(
+            investor_id,
+            InvestRequest {
+                pool_id,
+                amount: 20_000_000.0,
+                tranche: "priority".to_string(),
+                tnc_accepted: true,
+                catalyst_consents: None,
+                tx_hash: String::new(),
+            },
+        )
+        .await
+        .expect("Off-chain investment should succeed");
+    assert!(investment.tx_hash.is_none());
+
+    let balance: (rust_decimal::Decimal,) =
+        sqlx::query_as("SELECT balance_idrx FROM users WHERE id = $1")
+            .bind(investor_id)
+            .fetch_one(&pool)
+            .await
+            .expect("Failed to load balance");
+    assert_eq!(balance.0, rust_decimal::Decimal::from(30_000_000));
+
+    let (ledger, total) = TransactionRepository::new(pool.clone())
+        .find_balance_transactions_by_user(investor_id, 1, 10)
+        .await
+        .expect("Failed to load balance transactions");
+    assert_eq!(total, 1);
+    assert_eq!(ledger[0].tx_type, "funding");
+    assert_eq!(ledger[0].amount, rust_decimal::Decimal::from(20_000_000));
+    assert_eq!(ledger[0].balance_before, Some(rust_decimal::Decimal::from(50_000_000)));
+    assert_eq!(ledger[0].reference_id, Some(pool_id));
+
+    // A balance below the amount is rejected and releases the reserved capacity
+    let result = funding_service
+        .invest(
+            poor_investor_id,
+            InvestRequest {
+                pool_id,
+                amount: 10_000_000.0,
+                tranche: "priority".to_string(),
+                tnc_accepted: true,
+                catalyst_consents: None,
+                tx_hash: String::new(),
+            },
+        )
+        .await;
+    assert!(
+        matches!(result, Err(AppError::InsufficientBalance)),
+        "Got {:?}",
+        result
+    );
+
+    let funding_pool = FundingRepository::new(pool.clone())
+        .find_by_id(pool_id)
+        .await
+        .expect("Failed to load pool")
+        .expect("Pool should exist");
+    assert_eq!(funding_pool.priority_funded, rust_decimal::Decimal::from(20_000_000));
+
+    // Cleanup
+    for user_id in [mitra_id, investor_id, poor_investor_id] {
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_investment_rolls_back_when_a_later_write_fails() {
+    let mut config = get_test_config();
+    config.investment_mode = InvestmentMode::OffChain;
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (funding_service, invoice_service, _, pool) =
+        setup_funding_service_with_config(pool, config).await;
+
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_invest_rollback@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_invest_rollback@test.com").await;
+    sqlx::query("UPDATE users SET balance_idrx = 50000000 WHERE id = $1")
+        .bind(investor_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to fund balance");
+
+    // Fail the investor count update, which runs after the investment row is written
+    let trigger = format!("fail_investor_count_{}", pool_id.simple());
+    sqlx::query(&format!(
+        "CREATE OR REPLACE FUNCTION {}() RETURNS trigger AS $$ BEGIN RAISE EXCEPTION 'forced failure'; END $$ LANGUAGE plpgsql",
+        trigger
+    ))
+    .execute(&pool)
+    .await
+    .expect("Failed to create trigger function");
+    sqlx::query(&format!(
+        "CREATE TRIGGER {0} BEFORE UPDATE OF investor_count ON funding_pools FOR EACH ROW WHEN (NEW.id = '{1}') EXECUTE FUNCTION {0}()",
+        trigger, pool_id
+    ))
+    .execute(&pool)
+    .await
+    .expect("Failed to create trigger");
+
+    let result = funding_service
+        .invest(
+            investor_id,
+            InvestRequest {
+                pool_id,
+                amount: 20_000_000.0,
+                tranche: "priority".to_string(),
+                tnc_accepted: true,
+                catalyst_consents: None,
+                tx_hash: String::new(),
+            },
+        )
+        .await;
+
+    sqlx::query(&format!(
+        "DROP TRIGGER IF EXISTS {} ON funding_pools",
+        trigger
+    ))
+    .execute(&pool)
+    .await
+    .ok();
+    sqlx::query(&format!("DROP FUNCTION IF EXISTS {}()", trigger))
+        .execute(&pool)
+        .await
+        .ok();
+
+    assert!(result.is_err(), "Investment should fail");
+
+    let investments = FundingRepository::new(pool.clone())
+        .find_investments_by_pool(pool_id)
+        .await
+        .expect("Failed to load investments");
+    assert!(investments.is_empty(), "Investment row was not rolled back");
+
+    let funding_pool = FundingRepository::new(pool.clone())
+        .find_by_id(pool_id)
+        .await
+        .expect("Failed to load pool")
+        .expect("Pool should exist");
+    assert_eq!(funding_pool.priority_funded, rust_decimal::Decimal::ZERO);
+    assert_eq!(funding_pool.funded_amount, rust_decimal::Decimal::ZERO);
+
+    let balance: (rust_decimal::Decimal,) =
+        sqlx::query_as("SELECT balance_idrx FROM users WHERE id = $1")
+            .bind(investor_id)
+            .fetch_one(&pool)
+            .await
+            .expect("Failed to load balance");
+    assert_eq!(balance.0, rust_decimal::Decimal::from(50_000_000));
+
+    let (_, ledger_total) = TransactionRepository::new(pool.clone())
+        .find_balance_transactions_by_user(investor_id, 1, 10)
+        .await
+        .expect("Failed to load balance transactions");
+    assert_eq!(ledger_total, 0);
+
+    // Cleanup
+    for user_id in [mitra_id, investor_id] {
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_on_chain_investment_verifies_transfer() {
+    let mut config = get_test_config();
+    config.investment_mode = InvestmentMode::OnChain;
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (funding_service, invoice_service, _, pool) =
+        setup_funding_service_with_config(pool, config).await;
+
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_on_chain@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_on_chain@test.com").await;
+
+    let request = |tx_hash: &str| InvestRequest {
+        pool_id,
+        amount: 20_000_000.0,
+        tranche: "priority".to_string(),
+        tnc_accepted: true,
+        catalyst_consents: None,
+        tx_hash: tx_hash.to_string(),
+    };
+
+    // Without a transfer there is nothing to verify
+    let result = funding_service.invest(investor_id, request("")).await;
+    assert!(
+        matches!(result, Err(AppError::ValidationError(_))),
+        "Got {:?}",
+        result
+    );
+
+    let tx_hash = format!("0x{}", Uuid::new_v4().simple());
+    let investment = funding_service
+        .invest(investor_id, request(&tx_hash))
+        .await
+        .expect("On-chain investment should succeed");
+    assert_eq!(investment.tx_hash.as_deref(), Some(tx_hash.as_str()));
+
+    // The verified transfer is recorded; the balance is not touched
+    let transaction = TransactionRepository::new(pool.clone())
+        .find_by_tx_hash(&tx_hash)
+        .await
+        .expect("Failed to load transaction")
+        .expect("Verified transfer should be recorded");
+    assert_eq!(transaction.tx_type, "investment");
+    assert_eq!(transaction.block_number, Some(12345));
+
+    let (_, ledger_total) = TransactionRepository::new(pool.clone())
+        .find_balance_transactions_by_user(investor_id, 1, 10)
+        .await
+        .expect("Failed to load balance transactions");
+    assert_eq!(ledger_total, 0);
+
+    // Cleanup
+    for user_id in [mitra_id, investor_id] {
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_invest_and_confirm_race_records_one_investment() {
+    let mut config = get_test_config();
+    config.investment_mode = InvestmentMode::OnChain;
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (funding_service, invoice_service, _, pool) =
+        setup_funding_service_with_config(pool, config).await;
+
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_confirm_race@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_confirm_race@test.com").await;
+
+    let tx_hash = format!("0x{}", Uuid::new_v4().simple());
+    let request = || InvestRequest {
+        pool_id,
+        amount: 20_000_000.0,
+        tranche: "priority".to_string(),
+        tnc_accepted: true,
+        catalyst_consents: None,
+        tx_hash: tx_hash.clone(),
+    };
+
+    let (invested, confirmed) = tokio::join!(
+        funding_service.invest(investor_id, request()),
+        funding_service.confirm_investment(investor_id, request()),
+    );
+    let invested = invested.expect("invest should succeed");
+    let confirmed = confirmed.expect("confirm should return the same investment");
+    assert_eq!(invested.id, confirmed.id);
+
+    let count: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM investments WHERE investor_id = $1 AND tx_hash = $2",
+    )
+    .bind(investor_id)
+    .bind(&tx_hash)
+    .fetch_one(&pool)
+    .await
+    .expect("Failed to count investments");
+    assert_eq!(count, 1);
+
+    // Capacity was claimed once
+    let funded = funding_service
+        .get_pool(pool_id)
+        .await
+        .expect("Failed to load pool")
+        .pool
+        .priority_funded;
+    assert_eq!(funded, rust_decimal::Decimal::from(20_000_000));
+
+    // Cleanup
+    for user_id in [mitra_id, investor_id] {
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_transfer_cannot_back_investments_of_two_investors() {
+    let mut config = get_test_config();
+    config.investment_mode = InvestmentMode::OnChain;
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (funding_service, invoice_service, _, pool) =
+        setup_funding_service_with_config(pool, config).await;
+
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_shared_transfer@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let first_investor = create_investor(&pool, "investor_shared_transfer_a@test.com").await;
+    let second_investor = create_investor(&pool, "investor_shared_transfer_b@test.com").await;
+
+    let tx_hash = format!("0x{}", Uuid::new_v4().simple());
+    let request = || InvestRequest {
+        pool_id,
+        amount: 20_000_000.0,
+        tranche: "priority".to_string(),
+        tnc_accepted: true,
+        catalyst_consents: None,
+        tx_hash: tx_hash.clone(),
+    };
+
+    funding_service
+        .invest(first_investor, request())
+        .await
+        .expect("First investment should succeed");
+
+    let result = funding_service.invest(second_investor, request()).await;
+    assert!(
+        matches!(result, Err(AppError::Conflict(_))),
+        "Got {:?}",
+        result
+    );
+
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM investments WHERE tx_hash = $1")
+        .bind(&tx_hash)
+        .fetch_one(&pool)
+        .await
+        .expect("Failed to count investments");
+    assert_eq!(count, 1);
+
+    // Cleanup
+    for user_id in [mitra_id, first_investor, second_investor] {
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_on_chain_investment_left_pending_when_contract_record_fails() {
+    let mut config = get_test_config();
+    config.investment_mode = InvestmentMode::OnChain;
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (funding_service, invoice_service, _, pool) =
+        setup_funding_service_with_config(pool, config).await;
+
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_invest_pending@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_invest_pending@test.com").await;
+
+    // Funds are forwarded, but without a token id the contract cannot record the investment
+    sqlx::query("DELETE FROM invoice_nfts WHERE invoice_id = $1")
+        .bind(invoice_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to remove NFT record");
+
+    let tx_hash = format!("0x{}", Uuid::new_v4().simple());
+    let result = funding_service
+        .invest(
+            investor_id,
+            InvestRequest {
+                pool_id,
+                amount: 20_000_000.0,
+                tranche: "priority".to_string(),
+                tnc_accepted: true,
+                catalyst_consents: None,
+                tx_hash: tx_hash.clone(),
+            },
+        )
+        .await;
+    assert!(result.is_err(), "Investment should fail");
+
+    // The claim survives for reconciliation instead of being rolled back
+    let status: String = sqlx::query_scalar(
+        "SELECT status FROM investments WHERE investor_id = $1 AND tx_hash = $2",
+    )
+    .bind(investor_id)
+    .bind(&tx_hash)
+    .fetch_one(&pool)
+    .await
+    .expect("Pending investment should remain");
+    assert_eq!(status, "pending");
+
+    let funding_pool = FundingRepository::new(pool.clone())
+        .find_by_id(pool_id)
+        .await
+        .expect("Failed to load pool")
+        .expect("Pool should exist");
+    assert_eq!(
+        funding_pool.priority_funded,
+        rust_decimal::Decimal::from(20_000_000)
+    );
+    assert_eq!(funding_pool.status, "open");
+
+    // The transfer is only recorded once the investment is confirmed
+    let transaction = TransactionRepository::new(pool.clone())
+        .find_by_tx_hash(&tx_hash)
+        .await
+        .expect("Failed to load transaction");
+    assert!(transaction.is_none());
+
+    // Cleanup
+    for user_id in [mitra_id, investor_id] {
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_admin_pool_detail_covers_full_lifecycle() {
+    let mut config = get_test_config();
+    config.investment_mode = InvestmentMode::OnChain;
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (funding_service, invoice_service, _, pool) =
+        setup_funding_service_with_config(pool, config).await;
+
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_admin_detail@test.com").await;
+    sqlx::query("UPDATE invoices SET status = 'approved' WHERE id = $1")
+        .bind(invoice_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to approve invoice");
+    let pool_id = funding_service
+        .tokenize_invoice(invoice_id)
+        .await
+        .expect("Tokenization failed")
+        .id;
+
+    let first_investor = create_investor(&pool, "investor_admin_detail_a@test.com").await;
+    let second_investor = create_investor(&pool, "investor_admin_detail_b@test.com").await;
+    let mut tx_hashes = Vec::new();
+    for investor_id in [first_investor, second_investor] {
+        let tx_hash = format!("0x{}", Uuid::new_v4().simple());
+        let req = InvestRequest {
+            pool_id,
+            amount: 20_000_000.0,
+            tranche: "priority".to_string(),
+            tnc_accepted: true,
+            catalyst_consents: None,
+            tx_hash: tx_hash.clone(),
+        };
+        funding_service
+            .invest(investor_id, req)
+            .await
+            .expect("Investment failed");
+        tx_hashes.push(tx_hash);
+    }
+
+    // The second transfer never confirmed
+    sqlx::query("UPDATE transactions SET status = 'pending' WHERE tx_hash = $1")
+        .bind(&tx_hashes[1])
+        .execute(&pool)
+        .await
+        .expect("Failed to mark transfer pending");
+
+    funding_service
+        .disburse_pool(pool_id)
+        .await
+        .expect("Disbursement failed");
+    funding_service
+        .repay_invoice(
+            mitra_id,
+            invoice_id,
+            RepayInvoiceRequest {
+                tx_hash: "0xAdminDetailRepayHash".to_string(),
+                amount: 42_000_000.0,
+            },
+        )
+        .await
+        .expect("Repayment failed");
+
+    let detail = funding_service
+        .get_admin_pool_detail(pool_id)
+        .await
+        .expect("Admin pool detail failed");
+    assert_eq!(detail.pool.id, pool_id);
+    assert_eq!(detail.pool.status, "closed");
+    assert_eq!(detail.invoice.id, invoice_id);
+    assert!(detail.nft.is_some());
+
+    // Investors with contact details, in investment order
+    assert_eq!(detail.investments.len(), 2);
+    let first = &detail.investments[0];
+    assert_eq!(first.investor_id, first_investor);
+    assert!(first
+        .investor_email
+        .ends_with("investor_admin_detail_a@test.com"));
+    assert!(first.investor_wallet.is_some());
+    assert!(first.tx_verified);
+    assert_eq!(first.block_number, Some(12345));
+    let second = &detail.investments[1];
+    assert_eq!(second.investor_id, second_investor);
+    assert!(!second.tx_verified);
+
+    // Investments, disbursement and fee all show up against the invoice
+    let types: Vec<&str> = detail
+        .transactions
+        .iter()
+        .map(|t| t.tx_type.as_str())
+        .collect();
+    assert_eq!(types.iter().filter(|t| **t == "investment").count(), 2);
+    assert!(types.contains(&"advance_payment"));
+    assert!(types.contains(&"platform_fee"));
+
+    let verification = &detail.verification;
+    assert!(verification.nft_minted);
+    assert!(verification.pool_created_on_chain);
+    assert_eq!(verification.verified_investments, 1);
+    assert_eq!(verification.unverified_investments, 1);
+    assert!(verification.funded_amount_matches);
+
+    let events: Vec<&str> = detail.timeline.iter().map(|e| e.event.as_str()).collect();
+    for expected in [
+        "invoice_created",
+        "nft_minted",
+        "pool_opened",
+        "pool_disbursed",
+        "pool_closed",
+    ] {
+        assert!(
+            events.contains(&expected),
+            "Missing {} in {:?}",
+            expected,
+            events
+        );
+    }
+    assert_eq!(events.iter().filter(|e| **e == "investment").count(), 2);
+    assert!(detail
+        .timeline
+        .windows(2)
+        .all(|w| w[0].occurred_at <= w[1].occurred_at));
+
+    let result = funding_service.get_admin_pool_detail(Uuid::new_v4()).await;
+    assert!(matches!(result, Err(AppError::NotFound(_))));
+
+    // Cleanup
+    sqlx::query("DELETE FROM transactions WHERE invoice_id = $1")
+        .bind(invoice_id)
+        .execute(&pool)
+        .await
+        .ok();
+    for user_id in [first_investor, second_investor, mitra_id] {
+        sqlx::query("DELETE FROM transactions WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .ok();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_platform_stats_aggregates_pools_and_investments() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_stats@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let priority_investor = create_investor(&pool, "investor_stats_p@test.com").await;
+    let catalyst_investor = create_investor(&pool, "investor_stats_c@test.com").await;
+
+    let priority = funding_service
+        .invest(
+            priority_investor,
+            InvestRequest {
+                pool_id,
+                amount: 20_000_000.0,
+                tranche: "priority".to_string(),
+                tnc_accepted: true,
+                catalyst_consents: None,
+                tx_hash: format!("0x{}", Uuid::new_v4().simple()),
+            },
+        )
+        .await
+        .expect("Priority investment failed");
+    let catalyst = funding_service
+        .invest(
+            catalyst_investor,
+            InvestRequest {
+                pool_id,
+                amount: 10_000_000.0,
+                tranche: "catalyst".to_string(),
+                tnc_accepted: true,
+                catalyst_consents: Some(CatalystConsents {
+                    first_loss_consent: true,
+                    risk_loss_consent: true,
+                    not_bank_consent: true,
+                }),
+                tx_hash: format!("0x{}", Uuid::new_v4().simple()),
+            },
+        )
+        .await
+        .expect("Catalyst investment failed");
+
+    // Settle one investment and default the other
+    sqlx::query("UPDATE investments SET status = 'repaid', actual_return = 21000000 WHERE id = $1")
+        .bind(priority.id)
+        .execute(&pool)
+        .await
+        .expect("Failed to repay investment");
+    sqlx::query("UPDATE investments SET status = 'defaulted' WHERE id = $1")
+        .bind(catalyst.id)
+        .execute(&pool)
+        .await
+        .expect("Failed to default investment");
+
+    let stats = FundingRepository::new(pool.clone())
+        .get_platform_stats(Some(mitra_id))
+        .await
+        .expect("Failed to compute stats");
+    assert_eq!(stats.total_funded, 30_000_000.0);
+    assert_eq!(stats.total_repaid, 21_000_000.0);
+    assert_eq!(stats.active_pools, 1);
+    assert_eq!(stats.total_investors, 2);
+    assert_eq!(stats.total_exporters, 1);
+    // (20M * 12% + 10M * 15%) / 30M
+    assert!((stats.average_yield - 13.0).abs() < 1e-9);
+    // 10M defaulted out of 30M settled
+    assert!((stats.default_rate - 100.0 / 3.0).abs() < 1e-6);
+
+    // Unscoped stats cover at least the seeded pool
+    let platform = funding_service
+        .get_platform_stats()
+        .await
+        .expect("Failed to get platform stats");
+    assert!(platform.total_funded >= stats.total_funded);
+    assert!(platform.active_pools >= 1);
+    assert!(platform.total_investors >= 2);
+
+    for user_id in [priority_investor, catalyst_investor, mitra_id] {
+        sqlx::query("DELETE FROM transactions WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .ok();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+/// Mark an invoice funded with issue and due dates relative to today
+async fn seed_dated_invoice(
+    pool: &PgPool,
+    invoice_id: Uuid,
+    issued_days_ago: i64,
+    due_in_days: i64,
+) -> crate::models::Invoice {
+    let today = chrono::Utc::now().date_naive();
+    sqlx::query(
+        "UPDATE invoices SET status = 'funded', issue_date = $2, due_date = $3 WHERE id = $1",
+    )
+    .bind(invoice_id)
+    .bind(today - chrono::Duration::days(issued_days_ago))
+    .bind(today + chrono::Duration::days(due_in_days))
+    .execute(pool)
+    .await
+    .expect("Failed to date invoice");
+
+    InvoiceRepository::new(pool.clone())
+        .find_by_id(invoice_id)
+        .await
+        .expect("Failed to load invoice")
+        .expect("Invoice missing")
+}
+
+/// Give a seeded invoice a fully funded pool in `status`
+async fn seed_pool_with_status(pool: &PgPool, invoice_id: Uuid, status: &str) {
+    sqlx::query(
+        r#"INSERT INTO funding_pools (invoice_id, target_amount, funded_amount, status)
+           VALUES ($1, 100000000, 100000000, $2)"#,
+    )
+    .bind(invoice_id)
+    .bind(status)
+    .execute(pool)
+    .await
+    .expect("Failed to seed pool");
+}
+
+/// 100M at 12% p.a. for the given number of days
+fn expected_owed(days: i64) -> f64 {
+    100_000_000.0 + 100_000_000.0 * 0.12 * days as f64 / 365.0
+}
+
+#[tokio::test]
+async fn test_funded_invoice_matures_at_and_after_due_date() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+
+    // (email, days until due, pool status, matures)
+    let cases = [
+        ("maturity_before_due@test.com", 1, "disbursed", false),
+        ("maturity_on_due@test.com", 0, "disbursed", true),
+        ("maturity_after_due@test.com", -3, "disbursed", true),
+        // The mitra never received a filled but undisbursed pool's funds
+        ("maturity_undisbursed@test.com", -3, "filled", false),
+    ];
+    let mut seeded = Vec::new();
+    for (email, due_in_days, pool_status, _) in cases {
+        let (mitra_id, invoice_id) = create_mitra_and_invoice(&pool, &invoice_service, email).await;
+        seed_dated_invoice(&pool, invoice_id, 30, due_in_days).await;
+        seed_pool_with_status(&pool, invoice_id, pool_status).await;
+        seeded.push((mitra_id, invoice_id));
+    }
+
+    funding_service
+        .mark_matured_invoices()
+        .await
+        .expect("Maturity scan failed");
+
+    for ((mitra_id, invoice_id), (email, due_in_days, _, matures)) in seeded.iter().zip(cases) {
+        let invoice = InvoiceRepository::new(pool.clone())
+            .find_by_id(*invoice_id)
+            .await
+            .expect("Failed to load invoice")
+            .expect("Invoice missing");
+        let expected = if matures { "matured" } else { "funded" };
+        assert_eq!(invoice.status, expected, "{}", email);
+
+        let (notified,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM notifications WHERE user_id = $1 AND kind = 'repayment_due'",
+        )
+        .bind(mitra_id)
+        .fetch_one(&pool)
+        .await
+        .expect("Failed to count notifications");
+        assert_eq!(notified, matures as i64, "{}", email);
+
+        // Only past the due date is repayment overdue
+        let dashboard = funding_service
+            .get_mitra_dashboard(*mitra_id)
+            .await
+            .expect("Failed to load dashboard");
+        assert_eq!(dashboard.active_invoices.len(), 1, "{}", email);
+        assert_eq!(
+            dashboard.active_invoices[0].is_overdue,
+            due_in_days < 0,
+            "{}",
+            email
+        );
+    }
+
+    for (mitra_id, invoice_id) in seeded {
+        sqlx::query("DELETE FROM invoices WHERE id = $1")
+            .bind(invoice_id)
+            .execute(&pool)
+            .await
+            .ok();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(mitra_id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_matured_invoice_past_grace_is_default_eligible() {
+    let config = get_test_config();
+    let grace_days = config.invoice_default_grace_days;
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+
+    let (late_mitra_id, late_invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "default_eligible_late@test.com").await;
+    seed_dated_invoice(&pool, late_invoice_id, grace_days + 60, -(grace_days + 1)).await;
+    seed_pool_with_status(&pool, late_invoice_id, "disbursed").await;
+    let (recent_mitra_id, recent_invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "default_eligible_recent@test.com").await;
+    seed_dated_invoice(&pool, recent_invoice_id, 30, -1).await;
+    seed_pool_with_status(&pool, recent_invoice_id, "disbursed").await;
+
+    funding_service
+        .mark_matured_invoices()
+        .await
+        .expect("Maturity scan failed");
+
+    let eligible: Vec<Uuid> = funding_service
+        .default_eligible_invoices()
+        .await
+        .expect("Failed to list default-eligible invoices")
+        .into_iter()
+        .map(|invoice| invoice.id)
+        .collect();
+    assert!(eligible.contains(&late_invoice_id));
+    assert!(!eligible.contains(&recent_invoice_id));
+
+    for (mitra_id, invoice_id) in [
+        (late_mitra_id, late_invoice_id),
+        (recent_mitra_id, recent_invoice_id),
+    ] {
+        sqlx::query("DELETE FROM invoices WHERE id = $1")
+            .bind(invoice_id)
+            .execute(&pool)
+            .await
+            .ok();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(mitra_id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_disbursed_invoice_matures_after_due_date() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "maturity_disbursed@test.com").await;
+    // Financial terms lock once funding starts, so the dates are set while still a draft
+    sqlx::query("UPDATE invoices SET issue_date = CURRENT_DATE - 33, due_date = CURRENT_DATE - 3 WHERE id = $1")
+        .bind(invoice_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to date invoice");
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_maturity_disbursed@test.com").await;
+
+    let req = InvestRequest {
+        pool_id,
+        amount: 20_000_000.0,
+        tranche: "priority".to_string(),
+        tnc_accepted: true,
+        catalyst_consents: None,
+        tx_hash: unique_tx_hash(),
+    };
+    funding_service
+        .invest(investor_id, req)
+        .await
+        .expect("Investment failed");
+    funding_service
+        .disburse_pool(pool_id)
+        .await
+        .expect("Disbursement failed");
+
+    let invoice_repo = InvoiceRepository::new(pool.clone());
+    let invoice = invoice_repo
+        .find_by_id(invoice_id)
+        .await
+        .expect("Failed to load invoice")
+        .expect("Invoice missing");
+    assert_eq!(invoice.status, "disbursed");
+
+    funding_service
+        .mark_matured_invoices()
+        .await
+        .expect("Maturity scan failed");
+
+    let invoice = invoice_repo
+        .find_by_id(invoice_id)
+        .await
+        .expect("Failed to load invoice")
+        .expect("Invoice missing");
+    assert_eq!(invoice.status, "matured");
+
+    // Cleanup
+    for id in [investor_id, mitra_id] {
+        sqlx::query("DELETE FROM transactions WHERE user_id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .ok();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_accrual_for_active_invoice_runs_to_today() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "accrual_active@test.com").await;
+
+    let invoice = seed_dated_invoice(&pool, invoice_id, 10, 20).await;
+    let today = chrono::Utc::now().date_naive();
+    assert_eq!(accrual_days(&invoice, today), 10);
+
+    let dashboard = funding_service
+        .get_mitra_dashboard(mitra_id)
+        .await
+        .expect("Failed to load dashboard");
+    assert!((dashboard.total_owed_to_investors - expected_owed(10)).abs() < 1e-3);
+    assert_eq!(dashboard.active_invoices[0].days_remaining, 20);
+
+    sqlx::query("DELETE FROM invoices WHERE id = $1")
+        .bind(invoice_id)
+        .execute(&pool)
+        .await
+        .ok();
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_accrual_for_overdue_invoice_capped_at_tenor() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "accrual_overdue@test.com").await;
+
+    // 30 day tenor that ended 10 days ago
+    let invoice = seed_dated_invoice(&pool, invoice_id, 40, -10).await;
+    let today = chrono::Utc::now().date_naive();
+    assert_eq!(accrual_days(&invoice, today), 30);
+    assert_eq!(
+        accrual_days(&invoice, today + chrono::Duration::days(365)),
+        30
+    );
+
+    let dashboard = funding_service
+        .get_mitra_dashboard(mitra_id)
+        .await
+        .expect("Failed to load dashboard");
+    assert!((dashboard.total_owed_to_investors - expected_owed(30)).abs() < 1e-3);
+    assert_eq!(dashboard.active_invoices[0].status_color, "red");
+
+    sqlx::query("DELETE FROM invoices WHERE id = $1")
+        .bind(invoice_id)
+        .execute(&pool)
+        .await
+        .ok();
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_accrual_for_repaid_invoice_stops_at_repayment_date() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "accrual_repaid@test.com").await;
+    // Dates are set before the pool opens, which locks the invoice's financial terms
+    seed_dated_invoice(&pool, invoice_id, 30, 30).await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let invoice = InvoiceRepository::new(pool.clone())
+        .update_status(invoice_id, "funded")
+        .await
+        .expect("Failed to mark invoice funded");
+
+    // Repaid five days ago, well before the due date
+    sqlx::query(
+        "UPDATE funding_pools SET status = 'closed', closed_at = NOW() - INTERVAL '5 days' WHERE id = $1",
+    )
+    .bind(pool_id)
+    .execute(&pool)
+    .await
+    .expect("Failed to close pool");
+
+    let repaid_on = chrono::Utc::now().date_naive() - chrono::Duration::days(5);
+    assert_eq!(accrual_days(&invoice, repaid_on), 25);
+
+    let dashboard = funding_service
+        .get_mitra_dashboard(mitra_id)
+        .await
+        .expect("Failed to load dashboard");
+    assert!((dashboard.total_owed_to_investors - expected_owed(25)).abs() < 1e-3);
+
+    sqlx::query("DELETE FROM funding_pools WHERE id = $1")
+        .bind(pool_id)
+        .execute(&pool)
+        .await
+        .ok();
+    sqlx::query("DELETE FROM invoice_nfts WHERE invoice_id = $1")
+        .bind(invoice_id)
+        .execute(&pool)
+        .await
+        .ok();
+    sqlx::query("DELETE FROM invoices WHERE id = $1")
+        .bind(invoice_id)
+        .execute(&pool)
+        .await
+        .ok();
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_pool_can_be_relisted_after_closing() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "relist_pool@test.com").await;
+    let first_pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+
+    // Closing the unfunded pool sends the invoice back to tokenized
+    funding_service
+        .close_pool(first_pool_id)
+        .await
+        .expect("Failed to close pool");
+
+    let relisted = funding_service
+        .create_pool(invoice_id)
+        .await
+        .expect("Relisting after close should succeed");
+    assert_ne!(relisted.id, first_pool_id);
+    assert_eq!(relisted.status, "open");
+    // The invoice's closed on-chain pool is reopened rather than created again
+    assert_eq!(
+        relisted.create_pool_tx_hash.as_deref(),
+        Some("0xTestReopenPoolHash")
+    );
+
+    // The invoice now resolves to the new pool, not the closed one
+    let current = FundingRepository::new(pool.clone())
+        .find_by_invoice(invoice_id)
+        .await
+        .expect("Failed to load pool")
+        .expect("Pool should exist");
+    assert_eq!(current.id, relisted.id);
+
+    sqlx::query("DELETE FROM invoices WHERE id = $1")
+        .bind(invoice_id)
+        .execute(&pool)
+        .await
+        .ok();
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_second_active_pool_for_invoice_blocked() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "second_pool@test.com").await;
+    setup_pool(&pool, &funding_service, invoice_id).await;
+
+    // Put the invoice back into a poolable state while the first pool is still open
+    sqlx::query("UPDATE invoices SET status = 'tokenized' WHERE id = $1")
+        .bind(invoice_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to reset invoice status");
+
+    let result = funding_service.create_pool(invoice_id).await;
+    assert!(matches!(result, Err(AppError::Conflict(_))));
+
+    // Bypassing the service check, the partial unique index still refuses it
+    let result = FundingRepository::new(pool.clone())
+        .create_pool(
+            invoice_id,
+            rust_decimal::Decimal::from(80_000_000),
+            rust_decimal::Decimal::from(56_000_000),
+            rust_decimal::Decimal::from(24_000_000),
+            rust_decimal::Decimal::from(12),
+            rust_decimal::Decimal::from(15),
+            chrono::Utc::now() + chrono::Duration::days(30),
+            config.investment_limit_ratios(false),
+            config.investment_limit_ratios(true),
+        )
+        .await;
+    assert!(matches!(result, Err(AppError::Conflict(_))));
+
+    let active: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM funding_pools WHERE invoice_id = $1 AND status <> 'closed'",
+    )
+    .bind(invoice_id)
+    .fetch_one(&pool)
+    .await
+    .expect("Failed to count pools");
+    assert_eq!(active, 1);
+
+    sqlx::query("DELETE FROM invoices WHERE id = $1")
+        .bind(invoice_id)
+        .execute(&pool)
+        .await
+        .ok();
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_tax_summary_only_counts_repayments_in_year() {
+    use chrono::Datelike;
+    use rust_decimal::Decimal;
+
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_tax_summary@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_tax_summary@test.com").await;
+
+    let year = chrono::Utc::now().year() - 1;
+    let at = |y: i32, m: u32, d: u32, h: u32| {
+        chrono::NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_opt(h, 0, 0)
+            .unwrap()
+    };
+    // (principal, return, status, repaid_at)
+    let seeded = [
+        (10_000_000, 10_500_000, "repaid", Some(at(year, 3, 15, 12))),
+        (20_000_000, 21_000_000, "repaid", Some(at(year, 12, 31, 23))),
+        (
+            7_000_000,
+            7_300_000,
+            "repaid",
+            Some(at(year - 1, 12, 31, 23)),
+        ),
+        (5_000_000, 5_200_000, "repaid", Some(at(year + 1, 1, 1, 0))),
+        (9_000_000, 9_400_000, "active", None),
+    ];
+    for (principal, actual_return, status, repaid_at) in seeded {
+        sqlx::query(
+            r#"INSERT INTO investments (pool_id, investor_id, amount, expected_return, actual_return, status, tranche, invested_at, repaid_at)
+               VALUES ($1, $2, $3, $4, $4, $5, 'priority', $6, $7)"#,
+        )
+        .bind(pool_id)
+        .bind(investor_id)
+        .bind(Decimal::from(principal))
+        .bind(Decimal::from(actual_return))
+        .bind(status)
+        .bind(at(year - 1, 6, 1, 9))
+        .bind(repaid_at)
+        .execute(&pool)
+        .await
+        .expect("Failed to seed investment");
+    }
+
+    let summary = funding_service
+        .get_tax_summary(investor_id, year)
+        .await
+        .expect("Failed to build tax summary");
+    assert_eq!(summary.year, year);
+    assert_eq!(summary.investment_count, 2);
+    assert_eq!(summary.total_principal, Decimal::from(30_000_000));
+    assert_eq!(summary.total_realized_return, Decimal::from(31_500_000));
+    assert_eq!(summary.total_gain, Decimal::from(1_500_000));
+    assert!(summary
+        .investments
+        .iter()
+        .all(|i| i.repaid_at.year() == year));
+
+    let csv = funding_service
+        .export_tax_summary_csv(investor_id, year)
+        .await
+        .expect("Failed to export tax summary");
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 4, "Header, two investments and a total row");
+    assert!(lines[0].starts_with("invoice_number,tranche,principal"));
+    assert!(lines[3].starts_with("TOTAL,,30000000"));
+
+    // Future years have nothing to report yet
+    let result = funding_service.get_tax_summary(investor_id, year + 2).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+
+    for user_id in [investor_id, mitra_id] {
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+/// Stands in for an EIP-2612 IDRX contract: records each permit and returns a
+/// fixed `transferFrom` hash
+#[derive(Default)]
+struct MockPermitToken {
+    calls: std::sync::Mutex<Vec<PermitCall>>,
+}
+
+/// Permit, transfer recipient and amount pulled
+type PermitCall = (PermitAuthorization, Address, U256);
+
+impl PermitToken for MockPermitToken {
+    fn permit_and_transfer<'a>(
+        &'a self,
+        permit: &'a PermitAuthorization,
+        to: Address,
+        amount: U256,
+    ) -> futures_util::future::BoxFuture<'a, crate::error::AppResult<SentTransaction>> {
+        self.calls
+            .lock()
+            .unwrap()
+            .push((permit.clone(), to, amount));
+        let sent = SentTransaction {
+            tx_hash: format!("0xMockPermitTransfer_{}", Uuid::new_v4().simple()),
+            block_number: 1,
+            gas_used: 90_000,
+            effective_gas_price: 1_000_000,
+        };
+        Box::pin(async move { Ok(sent) })
+    }
+}
+
+const PERMIT_PLATFORM_WALLET: &str = "0x70997970c51812dc3a010c7d01b50e0d17dc79c8";
+
+struct PermitFixture {
+    funding_service: Arc<FundingService>,
+    token: Arc<MockPermitToken>,
+    pool: PgPool,
+    mitra_id: Uuid,
+    investor_id: Uuid,
+    pool_id: Uuid,
+    /// Investor's connected wallet, which signs the permits
+    owner: String,
+}
+
+async fn setup_permit_investment(email: &str, enabled: bool) -> PermitFixture {
+    let mut config = get_test_config();
+    config.enable_permit_investments = enabled;
+    config.investment_mode = InvestmentMode::OnChain;
+    config.platform_wallet_address = PERMIT_PLATFORM_WALLET.to_string();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let token = Arc::new(MockPermitToken::default());
+    let (funding_service, invoice_service, _, pool) = setup_funding_service_with_mocks(
+        pool,
+        config,
+        None,
+        Some(token.clone() as Arc<dyn PermitToken>),
+        None,
+        None,
+        None,
+    )
+    .await;
+
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, &format!("mitra_{}", email)).await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, &format!("investor_{}", email)).await;
+
+    let owner = format!("0x{}", &Uuid::new_v4().simple().to_string()[..32]) + "00000000";
+    sqlx::query("UPDATE users SET wallet_address = $1 WHERE id = $2")
+        .bind(&owner)
+        .bind(investor_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to set investor wallet");
+
+    PermitFixture {
+        funding_service,
+        token,
+        pool,
+        mitra_id,
+        investor_id,
+        pool_id,
+        owner,
+    }
+}
+
+fn permit_request(pool_id: Uuid, owner: &str, deadline: i64) -> InvestWithPermitRequest {
+    InvestWithPermitRequest {
+        pool_id,
+        amount: 20_000_000.0,
+        tranche: "priority".to_string(),
+        tnc_accepted: true,
+        catalyst_consents: None,
+        owner: owner.to_string(),
+        spender: PERMIT_PLATFORM_WALLET.to_string(),
+        // 20M IDRX at 2 decimals
+        value: "2000000000".to_string(),
+        deadline,
+        v: 27,
+        r: format!("0x{}", "11".repeat(32)),
+        s: format!("0x{}", "22".repeat(32)),
+    }
+}
+
+#[tokio::test]
+async fn test_invest_with_permit_pulls_funds_and_records_investment() {
+    let fixture = setup_permit_investment("permit_ok@test.com", true).await;
+
+    let deadline = chrono::Utc::now().timestamp() + 600;
+    let investment = fixture
+        .funding_service
+        .invest_with_permit(
+            fixture.investor_id,
+            permit_request(fixture.pool_id, &fixture.owner, deadline),
+        )
+        .await
+        .expect("Permit investment failed");
+
+    let calls = fixture.token.calls.lock().unwrap().clone();
+    assert_eq!(calls.len(), 1, "Expected one permit + transferFrom");
+    let (permit, to, amount) = &calls[0];
+    assert_eq!(format!("{:?}", permit.owner), fixture.owner);
+    assert_eq!(format!("{:?}", to), PERMIT_PLATFORM_WALLET);
+    assert_eq!(*amount, U256::from(2_000_000_000u64));
+    assert_eq!(permit.deadline, U256::from(deadline as u64));
+
+    assert!(investment
+        .tx_hash
+        .as_deref()
+        .is_some_and(|hash| hash.starts_with("0xMockPermitTransfer_")));
+    assert_eq!(investment.amount, rust_decimal::Decimal::from(20_000_000));
+
+    for id in [fixture.investor_id, fixture.mitra_id] {
+        sqlx::query("DELETE FROM transactions WHERE user_id = $1")
+            .bind(id)
+            .execute(&fixture.pool)
+            .await
+            .ok();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(&fixture.pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_invest_with_permit_rejects_expired_deadline_and_disabled_flag() {
+    let fixture = setup_permit_investment("permit_expired@test.com", true).await;
+
+    let expired = chrono::Utc::now().timestamp() - 1;
+    let result = fixture
+        .funding_service
+        .invest_with_permit(
+            fixture.investor_id,
+            permit_request(fixture.pool_id, &fixture.owner, expired),
+        )
+        .await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+
+    // A permit signed by another wallet is refused
+    let deadline = chrono::Utc::now().timestamp() + 600;
+    let other_owner = format!("0x{}", "ab".repeat(20));
+    let result = fixture
+        .funding_service
+        .invest_with_permit(
+            fixture.investor_id,
+            permit_request(fixture.pool_id, &other_owner, deadline),
+        )
+        .await;
+    assert!(matches!(result, Err(AppError::Forbidden(_))));
+    assert!(
+        fixture.token.calls.lock().unwrap().is_empty(),
+        "No funds may move"
+    );
+
+    // Deployments whose IDRX has no permit keep the flag off
+    let disabled = setup_permit_investment("permit_disabled@test.com", false).await;
+    let result = disabled
+        .funding_service
+        .invest_with_permit(
+            disabled.investor_id,
+            permit_request(disabled.pool_id, &disabled.owner, deadline),
+        )
+        .await;
+    assert!(matches!(result, Err(AppError::BadRequest(_))));
+    assert!(disabled.token.calls.lock().unwrap().is_empty());
+
+    for id in [
+        fixture.investor_id,
+        fixture.mitra_id,
+        disabled.investor_id,
+        disabled.mitra_id,
+    ] {
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(&fixture.pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_invest_with_permit_checks_limits_before_pulling_funds() {
+    let fixture = setup_permit_investment("permit_limits@test.com", true).await;
+
+    // Below the tranche minimum: rejected before the permit is submitted
+    let deadline = chrono::Utc::now().timestamp() + 600;
+    let mut request = permit_request(fixture.pool_id, &fixture.owner, deadline);
+    request.amount = 1_000.0;
+    let result = fixture
+        .funding_service
+        .invest_with_permit(fixture.investor_id, request)
+        .await;
+    assert!(
+        matches!(result, Err(AppError::ValidationError(_))),
+        "Got {:?}",
+        result
+    );
+    assert!(
+        fixture.token.calls.lock().unwrap().is_empty(),
+        "No funds may move"
+    );
+
+    for id in [fixture.investor_id, fixture.mitra_id] {
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(&fixture.pool)
+            .await
+            .ok();
+    }
+}
+
+/// Invest in the pool and mark it filled, ready for the mitra to request disbursement
+async fn fill_pool(
+    pool: &PgPool,
+    funding_service: &Arc<FundingService>,
+    pool_id: Uuid,
+    investor_id: Uuid,
+) {
+    let req = InvestRequest {
+        pool_id,
+        amount: 20_000_000.0,
+        tranche: "priority".to_string(),
+        tnc_accepted: true,
+        catalyst_consents: None,
+        tx_hash: format!("0xFill_{}", Uuid::new_v4().simple()),
+    };
+    funding_service
+        .invest(investor_id, req)
+        .await
+        .expect("Investment failed");
+    sqlx::query("UPDATE funding_pools SET status = 'filled' WHERE id = $1")
+        .bind(pool_id)
+        .execute(pool)
+        .await
+        .expect("Failed to fill pool");
+}
+
+async fn create_bank_account(pool: &PgPool, user_id: Uuid, verified: bool) -> Uuid {
+    let row = sqlx::query(
+        r#"INSERT INTO bank_accounts (user_id, bank_code, bank_name, account_number, account_name, is_primary, verified_at)
+           VALUES ($1, 'bca', 'Bank Central Asia', '1234567890', 'PT Mitra Test', true, CASE WHEN $2 THEN NOW() END)
+           RETURNING id"#,
+    )
+    .bind(user_id)
+    .bind(verified)
+    .fetch_one(pool)
+    .await
+    .expect("Failed to create bank account");
+    row.get("id")
+}
+
+async fn cleanup_disbursement_test(pool: &PgPool, investor_id: Uuid, mitra_id: Uuid) {
+    for id in [investor_id, mitra_id] {
+        sqlx::query("DELETE FROM transactions WHERE user_id = $1")
+            .bind(id)
+            .execute(pool)
+            .await
+            .ok();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_exporter_wallet_disbursement_pays_on_chain() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_wallet_disburse@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_wallet_disburse@test.com").await;
+    fill_pool(&pool, &funding_service, pool_id, investor_id).await;
+
+    // Only the pool's own mitra may request it
+    let result = funding_service
+        .request_exporter_disbursement(investor_id, pool_id, DisbursementMode::Wallet)
+        .await;
+    assert!(matches!(result, Err(AppError::Forbidden(_))));
+
+    let disbursed = funding_service
+        .request_exporter_disbursement(mitra_id, pool_id, DisbursementMode::Wallet)
+        .await
+        .expect("Wallet disbursement failed");
+    assert_eq!(disbursed.status, "disbursed");
+
+    let types: Vec<(String, String)> =
+        sqlx::query_as("SELECT type, status FROM transactions WHERE invoice_id = $1")
+            .bind(invoice_id)
+            .fetch_all(&pool)
+            .await
+            .expect("Failed to load transactions");
+    assert!(types.contains(&("advance_payment".to_string(), "confirmed".to_string())));
+    assert!(!types.iter().any(|(t, _)| t == "bank_disbursement"));
+
+    cleanup_disbursement_test(&pool, investor_id, mitra_id).await;
+}
+
+#[tokio::test]
+async fn test_exporter_bank_disbursement_records_pending_offramp() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let mut config = config;
+    config.offramp_wallet_address = "0x00000000000000000000000000000000000000aa".to_string();
+    let (funding_service, invoice_service, _, pool) =
+        setup_funding_service_with_config(pool, config.clone()).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_bank_disburse@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_bank_disburse@test.com").await;
+    fill_pool(&pool, &funding_service, pool_id, investor_id).await;
+    let account_id = create_bank_account(&pool, mitra_id, true).await;
+
+    let disbursed = funding_service
+        .request_exporter_disbursement(mitra_id, pool_id, DisbursementMode::Bank)
+        .await
+        .expect("Bank disbursement failed");
+    assert_eq!(disbursed.status, "disbursed");
+
+    let row = sqlx::query(
+        "SELECT amount, status, bank_account_id, tx_hash, notes FROM transactions WHERE invoice_id = $1 AND type = 'bank_disbursement'",
+    )
+    .bind(invoice_id)
+    .fetch_one(&pool)
+    .await
+    .expect("Bank disbursement not recorded");
+    let expected_fee =
+        rust_decimal::Decimal::from(20_000_000u64 * config.platform_fee_bps as u64 / 10_000);
+    assert_eq!(
+        row.get::<rust_decimal::Decimal, _>("amount"),
+        disbursed.funded_amount - expected_fee
+    );
+    assert_eq!(row.get::<String, _>("status"), "pending_offramp");
+    assert_eq!(
+        row.get::<Option<Uuid>, _>("bank_account_id"),
+        Some(account_id)
+    );
+    // The contract paid the advance to the off-ramp wallet
+    assert!(row.get::<Option<String>, _>("tx_hash").is_some());
+    assert!(row
+        .get::<Option<String>, _>("notes")
+        .unwrap_or_default()
+        .contains("****7890"));
+
+    // No advance goes to the mitra's wallet in bank mode
+    let advances: (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM transactions WHERE invoice_id = $1 AND type = 'advance_payment'",
+    )
+    .bind(invoice_id)
+    .fetch_one(&pool)
+    .await
+    .expect("Failed to count advances");
+    assert_eq!(advances.0, 0);
+
+    cleanup_disbursement_test(&pool, investor_id, mitra_id).await;
+}
+
+#[tokio::test]
+async fn test_bank_disbursement_requires_verified_account() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_bank_unverified@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_bank_unverified@test.com").await;
+    fill_pool(&pool, &funding_service, pool_id, investor_id).await;
+    create_bank_account(&pool, mitra_id, false).await;
+
+    let result = funding_service
+        .request_exporter_disbursement(mitra_id, pool_id, DisbursementMode::Bank)
+        .await;
+    assert!(matches!(result, Err(AppError::BadRequest(_))));
+
+    let status: (String,) = sqlx::query_as("SELECT status FROM funding_pools WHERE id = $1")
+        .bind(pool_id)
+        .fetch_one(&pool)
+        .await
+        .expect("Pool not found");
+    assert_eq!(status.0, "filled");
+
+    cleanup_disbursement_test(&pool, investor_id, mitra_id).await;
+}
+
+#[tokio::test]
+async fn test_bank_disbursement_requires_offramp_wallet() {
+    let mut config = get_test_config();
+    config.offramp_wallet_address = String::new();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) =
+        setup_funding_service_with_config(pool, config).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_bank_no_offramp@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_bank_no_offramp@test.com").await;
+    fill_pool(&pool, &funding_service, pool_id, investor_id).await;
+    create_bank_account(&pool, mitra_id, true).await;
+
+    let result = funding_service
+        .request_exporter_disbursement(mitra_id, pool_id, DisbursementMode::Bank)
+        .await;
+    assert!(matches!(result, Err(AppError::BadRequest(_))));
+
+    cleanup_disbursement_test(&pool, investor_id, mitra_id).await;
+}
+
+/// Open pool with 45M of its 100M target raised, and a service whose mitra may
+/// disburse from `min_disbursement_ratio` of the target
+async fn setup_partially_funded_pool(
+    min_disbursement_ratio: f64,
+    base_email: &str,
+) -> (Arc<FundingService>, PgPool, Uuid, Uuid, Uuid, Uuid) {
+    let mut config = get_test_config();
+    config.min_disbursement_ratio = min_disbursement_ratio;
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) =
+        setup_funding_service_with_config(pool, config).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, &format!("mitra_{}", base_email)).await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, &format!("investor_{}", base_email)).await;
+
+    let req = InvestRequest {
+        pool_id,
+        amount: 45_000_000.0,
+        tranche: "priority".to_string(),
+        tnc_accepted: true,
+        catalyst_consents: None,
+        tx_hash: format!("0xPartial_{}", Uuid::new_v4().simple()),
+    };
+    funding_service
+        .invest(investor_id, req)
+        .await
+        .expect("Investment failed");
+
+    (
+        funding_service,
+        pool,
+        pool_id,
+        invoice_id,
+        mitra_id,
+        investor_id,
+    )
+}
+
+#[tokio::test]
+async fn test_early_disbursement_above_threshold() {
+    let (funding_service, pool, pool_id, invoice_id, mitra_id, investor_id) =
+        setup_partially_funded_pool(0.4, "early_disburse@test.com").await;
+
+    let status = funding_service
+        .get_invoice_funding_status(mitra_id, invoice_id)
+        .await
+        .expect("Funding status failed");
+    assert_eq!(status.pool.pool.status, "open");
+    assert!(status.disbursement_eligible);
+
+    let disbursed = funding_service
+        .request_exporter_disbursement(mitra_id, pool_id, DisbursementMode::Wallet)
+        .await
+        .expect("Early disbursement failed");
+    assert_eq!(disbursed.status, "disbursed");
+    assert_eq!(
+        disbursed.funded_amount,
+        rust_decimal::Decimal::from(45_000_000)
+    );
+
+    // Advance and fee are based on the 45M raised, not the 100M target
+    let advance: (rust_decimal::Decimal,) = sqlx::query_as(
+        "SELECT amount FROM transactions WHERE invoice_id = $1 AND type = 'advance_payment'",
+    )
+    .bind(invoice_id)
+    .fetch_one(&pool)
+    .await
+    .expect("Advance payment not recorded");
+    let fee: (rust_decimal::Decimal,) = sqlx::query_as(
+        "SELECT amount FROM transactions WHERE invoice_id = $1 AND type = 'platform_fee'",
+    )
+    .bind(invoice_id)
+    .fetch_one(&pool)
+    .await
+    .expect("Platform fee not recorded");
+    assert_eq!(advance.0 + fee.0, disbursed.funded_amount);
+
+    let invoice_advance: (Option<rust_decimal::Decimal>,) =
+        sqlx::query_as("SELECT advance_amount FROM invoices WHERE id = $1")
+            .bind(invoice_id)
+            .fetch_one(&pool)
+            .await
+            .expect("Invoice not found");
+    assert_eq!(invoice_advance.0, Some(advance.0));
+
+    cleanup_disbursement_test(&pool, investor_id, mitra_id).await;
+}
+
+#[tokio::test]
+async fn test_early_disbursement_waits_for_pending_investments() {
+    let (funding_service, pool, pool_id, _, mitra_id, investor_id) =
+        setup_partially_funded_pool(0.4, "early_disburse_pending@test.com").await;
+
+    // An investment still being confirmed on-chain may yet be released
+    sqlx::query("UPDATE investments SET status = 'pending' WHERE pool_id = $1")
+        .bind(pool_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to mark investment pending");
+
+    let result = funding_service
+        .request_exporter_disbursement(mitra_id, pool_id, DisbursementMode::Wallet)
+        .await;
+    assert!(
+        matches!(result, Err(AppError::Conflict(_))),
+        "Pool with a pending investment must not disburse: {:?}",
+        result
+    );
+
+    let status: (String,) = sqlx::query_as("SELECT status FROM funding_pools WHERE id = $1")
+        .bind(pool_id)
+        .fetch_one(&pool)
+        .await
+        .expect("Pool not found");
+    assert_eq!(status.0, "open");
+
+    cleanup_disbursement_test(&pool, investor_id, mitra_id).await;
+}
+
+#[tokio::test]
+async fn test_disbursement_below_threshold_rejected() {
+    let (funding_service, pool, pool_id, invoice_id, mitra_id, investor_id) =
+        setup_partially_funded_pool(0.8, "below_threshold@test.com").await;
+
+    let status = funding_service
+        .get_invoice_funding_status(mitra_id, invoice_id)
+        .await
+        .expect("Funding status failed");
+    assert!(!status.disbursement_eligible);
+
+    let result = funding_service
+        .request_exporter_disbursement(mitra_id, pool_id, DisbursementMode::Wallet)
+        .await;
+    assert!(
+        matches!(result, Err(AppError::BadRequest(ref msg)) if msg.contains("at least 80000000")),
+        "Below-threshold pool must not disburse: {:?}",
+        result
+    );
+
+    let status: (String,) = sqlx::query_as("SELECT status FROM funding_pools WHERE id = $1")
+        .bind(pool_id)
+        .fetch_one(&pool)
+        .await
+        .expect("Pool not found");
+    assert_eq!(status.0, "open");
+
+    cleanup_disbursement_test(&pool, investor_id, mitra_id).await;
+}
+
+#[tokio::test]
+async fn test_pool_target_is_capped_by_funding_limit() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let (repeat_mitra_id, repeat_invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_limit_repeat@test.com").await;
+    let (new_mitra_id, new_invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_limit_new@test.com").await;
+
+    // Same 100M invoice: a repeat buyer may be funded in full, a new buyer up to 60%
+    for (invoice_id, is_repeat, limit) in
+        [(repeat_invoice_id, true, 100), (new_invoice_id, false, 60)]
+    {
+        sqlx::query(
+            "UPDATE invoices SET is_repeat_buyer = $2, funding_limit_percentage = $3 WHERE id = $1",
+        )
+        .bind(invoice_id)
+        .bind(is_repeat)
+        .bind(rust_decimal::Decimal::from(limit))
+        .execute(&pool)
+        .await
+        .expect("Failed to set funding limit");
+    }
+
+    let repeat_pool_id = setup_pool(&pool, &funding_service, repeat_invoice_id).await;
+    let new_pool_id = setup_pool(&pool, &funding_service, new_invoice_id).await;
+
+    let targets = |pool_id: Uuid| {
+        let pool = pool.clone();
+        async move {
+            let row = sqlx::query(
+                "SELECT target_amount, priority_target, catalyst_target FROM funding_pools WHERE id = $1",
+            )
+            .bind(pool_id)
+            .fetch_one(&pool)
+            .await
+            .expect("Pool not found");
+            (
+                row.get::<rust_decimal::Decimal, _>("target_amount"),
+                row.get::<rust_decimal::Decimal, _>("priority_target"),
+                row.get::<rust_decimal::Decimal, _>("catalyst_target"),
+            )
+        }
+    };
+    let million = |m: i64| rust_decimal::Decimal::from(m * 1_000_000);
+
+    assert_eq!(
+        targets(repeat_pool_id).await,
+        (million(100), million(70), million(30))
+    );
+    assert_eq!(
+        targets(new_pool_id).await,
+        (million(60), million(42), million(18))
+    );
+
+    for id in [repeat_mitra_id, new_mitra_id] {
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_repayment_breakdown_matches_repayment_distribution() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_breakdown_repay@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_breakdown_repay@test.com").await;
+    fill_pool(&pool, &funding_service, pool_id, investor_id).await;
+    funding_service
+        .disburse_pool(pool_id)
+        .await
+        .expect("Disbursement failed");
+
+    let breakdown = funding_service
+        .get_repayment_breakdown(mitra_id, pool_id)
+        .await
+        .expect("Breakdown failed");
+    let result = funding_service
+        .get_repayment_breakdown(investor_id, pool_id)
+        .await;
+    assert!(matches!(result, Err(AppError::Forbidden(_))));
+
+    // Repay exactly what the breakdown asks for
+    let tx_hash = funding_service
+        .repay_invoice(
+            mitra_id,
+            invoice_id,
+            RepayInvoiceRequest {
+                tx_hash: format!("0xBreakdownRepay_{}", Uuid::new_v4().simple()),
+                amount: breakdown.total_repayment.to_string().parse().unwrap(),
+            },
+        )
+        .await
+        .expect("Repayment failed");
+
+    let fee: (rust_decimal::Decimal,) = sqlx::query_as(
+        "SELECT amount FROM transactions WHERE tx_hash = $1 AND type = 'platform_fee'",
+    )
+    .bind(&tx_hash)
+    .fetch_one(&pool)
+    .await
+    .expect("Repayment fee not recorded");
+    let returned: (rust_decimal::Decimal,) =
+        sqlx::query_as("SELECT SUM(actual_return) FROM investments WHERE pool_id = $1")
+            .bind(pool_id)
+            .fetch_one(&pool)
+            .await
+            .expect("Failed to sum returns");
+
+    assert_eq!(fee.0, breakdown.platform_fee);
+    assert_eq!(
+        returned.0,
+        breakdown.principal_amount + breakdown.total_interest
+    );
+    assert_eq!(
+        breakdown.principal_amount
+            + breakdown.total_interest
+            + breakdown.platform_fee
+            + breakdown.rounding_remainder,
+        breakdown.total_repayment
+    );
+
+    cleanup_disbursement_test(&pool, investor_id, mitra_id).await;
+}
+
+#[tokio::test]
+async fn test_repay_invoice_rejects_pool_without_investments() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_repay_empty@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    sqlx::query("UPDATE funding_pools SET status = 'disbursed' WHERE id = $1")
+        .bind(pool_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to disburse pool");
+
+    let result = funding_service
+        .repay_invoice(
+            mitra_id,
+            invoice_id,
+            RepayInvoiceRequest {
+                tx_hash: format!("0xEmptyRepay_{}", Uuid::new_v4().simple()),
+                amount: 1_000_000.0,
+            },
+        )
+        .await;
+    assert!(
+        matches!(&result, Err(AppError::BadRequest(msg)) if msg == "No investments to repay"),
+        "Expected BadRequest, got {:?}",
+        result
+    );
+
+    // Nothing was recorded and the pool stays open for repayment
+    let (status,): (String,) = sqlx::query_as("SELECT status FROM funding_pools WHERE id = $1")
+        .bind(pool_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(status, "disbursed");
+    let (fees,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM transactions WHERE invoice_id = $1 AND type = 'platform_fee'",
+    )
+    .bind(invoice_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(fees, 0);
+
+    cleanup_disbursement_test(&pool, mitra_id, mitra_id).await;
+}
+
+#[tokio::test]
+async fn test_repay_invoice_reports_investment_ledger_mismatch() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_repay_mismatch@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_repay_mismatch@test.com").await;
+
+    let req = InvestRequest {
+        pool_id,
+        amount: 20_000_000.0,
+        tranche: "priority".to_string(),
+        tnc_accepted: true,
+        catalyst_consents: None,
+        tx_hash: unique_tx_hash(),
+    };
+    funding_service
+        .invest(investor_id, req)
+        .await
+        .expect("Investment failed");
+
+    // The pool claims 5M more than its investments add up to
+    sqlx::query(
+        "UPDATE funding_pools SET status = 'disbursed', funded_amount = funded_amount + 5000000 WHERE id = $1",
+    )
+    .bind(pool_id)
+    .execute(&pool)
+    .await
+    .expect("Failed to disburse pool");
+
+    let result = funding_service
+        .repay_invoice(
+            mitra_id,
+            invoice_id,
+            RepayInvoiceRequest {
+                tx_hash: unique_tx_hash(),
+                amount: 30_000_000.0,
+            },
+        )
+        .await;
+    match result {
+        Err(AppError::Conflict(msg)) => {
+            assert!(msg.contains("20000000"), "{}", msg);
+            assert!(msg.contains("25000000"), "{}", msg);
+        }
+        other => panic!("Expected Conflict, got {:?}", other.map(|_| ())),
+    }
+
+    cleanup_disbursement_test(&pool, investor_id, mitra_id).await;
+}
+
+#[tokio::test]
+async fn test_repay_invoice_rejects_amount_below_investor_returns() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_repay_short@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_repay_short@test.com").await;
+    fill_pool(&pool, &funding_service, pool_id, investor_id).await;
+    funding_service
+        .disburse_pool(pool_id)
+        .await
+        .expect("Disbursement failed");
+
+    // Principal alone does not cover the interest owed to the investor
+    let result = funding_service
+        .repay_invoice(
+            mitra_id,
+            invoice_id,
+            RepayInvoiceRequest {
+                tx_hash: format!("0xShortRepay_{}", Uuid::new_v4().simple()),
+                amount: 20_000_000.0,
+            },
+        )
+        .await;
+    assert!(
+        matches!(&result, Err(AppError::BadRequest(msg)) if msg.contains("does not cover")),
+        "Expected BadRequest, got {:?}",
+        result
+    );
+
+    let (status,): (String,) = sqlx::query_as("SELECT status FROM investments WHERE pool_id = $1")
+        .bind(pool_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_ne!(status, "repaid");
+    let (pool_status,): (String,) =
+        sqlx::query_as("SELECT status FROM funding_pools WHERE id = $1")
+            .bind(pool_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(pool_status, "disbursed");
+
+    cleanup_disbursement_test(&pool, investor_id, mitra_id).await;
+}
+
+/// InvoicePool events the test sets up front
+#[derive(Default)]
+struct MockInvestmentEvents {
+    events: std::sync::Mutex<Vec<OnChainInvestment>>,
+}
+
+impl MockInvestmentEvents {
+    fn set(&self, events: Vec<(Address, i64)>) {
+        *self.events.lock().unwrap() = events
+            .into_iter()
+            .map(|(investor, amount)| OnChainInvestment {
+                investor,
+                amount: rust_decimal::Decimal::from(amount),
+                tx_hash: None,
+            })
+            .collect();
+    }
+}
+
+impl InvestmentEventSource for MockInvestmentEvents {
+    fn investment_events(
+        &self,
+        _token_id: i64,
+    ) -> futures_util::future::BoxFuture<'_, crate::error::AppResult<Vec<OnChainInvestment>>> {
+        let events = self.events.lock().unwrap().clone();
+        Box::pin(async move { Ok(events) })
+    }
+}
+
+#[tokio::test]
+async fn test_onchain_audit_reports_matching_and_mismatching_events() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let events = Arc::new(MockInvestmentEvents::default());
+    let (funding_service, invoice_service, _, pool) = setup_funding_service_with_mocks(
+        pool,
+        config,
+        None,
+        None,
+        None,
+        Some(events.clone() as Arc<dyn InvestmentEventSource>),
+        None,
+    )
+    .await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_onchain_audit@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_onchain_audit@test.com").await;
+
+    // The contract records the investor's wallet, so give them a real address
+    let wallet = format!("0x{:0>40}", investor_id.simple().to_string());
+    sqlx::query("UPDATE users SET wallet_address = $2 WHERE id = $1")
+        .bind(investor_id)
+        .bind(&wallet)
+        .execute(&pool)
+        .await
+        .expect("Failed to set wallet");
+    let investor: Address = wallet.parse().unwrap();
+    let stranger: Address = "0x00000000000000000000000000000000000000ff"
+        .parse()
+        .unwrap();
+
+    fill_pool(&pool, &funding_service, pool_id, investor_id).await;
+
+    events.set(vec![(investor, 20_000_000)]);
+    let audit = funding_service
+        .audit_pool_onchain(pool_id)
+        .await
+        .expect("Audit failed");
+    assert!(audit.is_consistent, "{:?}", audit.discrepancies);
+    assert_eq!(audit.token_id, 123);
+    assert_eq!(audit.db_investment_count, 1);
+    assert_eq!(audit.onchain_investment_count, 1);
+    assert_eq!(audit.db_total, audit.onchain_total);
+
+    // The contract keeps paying the wallet recorded at investment time, so a
+    // later wallet change must not show up as a discrepancy
+    sqlx::query("UPDATE users SET wallet_address = $2 WHERE id = $1")
+        .bind(investor_id)
+        .bind(format!("0x{:0>40}", Uuid::new_v4().simple().to_string()))
+        .execute(&pool)
+        .await
+        .expect("Failed to change wallet");
+    let audit = funding_service
+        .audit_pool_onchain(pool_id)
+        .await
+        .expect("Audit failed");
+    assert!(audit.is_consistent, "{:?}", audit.discrepancies);
+
+    // Short on-chain amount for the investor plus an investment the DB never saw
+    events.set(vec![(investor, 15_000_000), (stranger, 5_000_000)]);
+    let audit = funding_service
+        .audit_pool_onchain(pool_id)
+        .await
+        .expect("Audit failed");
+    assert!(!audit.is_consistent);
+    assert_eq!(audit.onchain_investment_count, 2);
+    assert_eq!(audit.db_total, audit.onchain_total);
+    assert_eq!(audit.discrepancies.len(), 2);
+
+    let short = audit
+        .discrepancies
+        .iter()
+        .find(|d| d.investor_wallet == wallet)
+        .expect("Investor discrepancy missing");
+    assert_eq!(short.investor_id, Some(investor_id));
+    assert_eq!(short.db_amount, 20_000_000.0);
+    assert_eq!(short.onchain_amount, 15_000_000.0);
+    assert_eq!((short.db_count, short.onchain_count), (1, 1));
+
+    let unknown = audit
+        .discrepancies
+        .iter()
+        .find(|d| d.investor_wallet == format!("{:?}", stranger))
+        .expect("Unknown investor discrepancy missing");
+    assert_eq!(unknown.investor_id, None);
+    assert_eq!((unknown.db_count, unknown.onchain_count), (0, 1));
+
+    cleanup_disbursement_test(&pool, investor_id, mitra_id).await;
+}
+
+#[tokio::test]
+async fn test_reconcile_completes_stuck_pending_investment() {
+    let mut config = get_test_config();
+    config.investment_mode = InvestmentMode::OnChain;
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    // The contract has no record of the investment yet
+    let events = Arc::new(MockInvestmentEvents::default());
+    let (funding_service, invoice_service, _, pool) = setup_funding_service_with_mocks(
+        pool,
+        config,
+        None,
+        None,
+        None,
+        Some(events.clone() as Arc<dyn InvestmentEventSource>),
+        None,
+    )
+    .await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_reconcile@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_reconcile@test.com").await;
+
+    let tx_hash = unique_tx_hash();
+    let investment = funding_service
+        .invest(
+            investor_id,
+            InvestRequest {
+                pool_id,
+                amount: 20_000_000.0,
+                tranche: "priority".to_string(),
+                tnc_accepted: true,
+                catalyst_consents: None,
+                tx_hash: tx_hash.clone(),
+            },
+        )
+        .await
+        .expect("Investment failed");
+
+    // Put it back where a failed contract call leaves it: forwarded but unconfirmed
+    sqlx::query(
+        "UPDATE investments SET status = 'pending', payout_wallet = NULL, created_at = NOW() WHERE id = $1",
+    )
+    .bind(investment.id)
+    .execute(&pool)
+    .await
+    .expect("Failed to reset investment");
+    sqlx::query("DELETE FROM transactions WHERE tx_hash = $1")
+        .bind(&tx_hash)
+        .execute(&pool)
+        .await
+        .expect("Failed to delete transaction");
+
+    // A fresh claim may still be completing in its own request
+    let result = funding_service
+        .reconcile_pending_investment(investment.id)
+        .await;
+    assert!(matches!(result, Err(AppError::Conflict(_))), "{:?}", result);
+
+    sqlx::query("UPDATE investments SET created_at = NOW() - INTERVAL '1 hour' WHERE id = $1")
+        .bind(investment.id)
+        .execute(&pool)
+        .await
+        .expect("Failed to age investment");
+
+    let reconciled = funding_service
+        .reconcile_pending_investment(investment.id)
+        .await
+        .expect("Reconcile failed");
+    assert_eq!(reconciled.status, "active");
+    assert_eq!(
+        reconciled.payout_wallet.as_deref(),
+        Some(format!("0xInvest_{}", investor_id.simple()).as_str())
+    );
+    let (recorded,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM transactions WHERE tx_hash = $1 AND type = 'investment'",
+    )
+    .bind(&tx_hash)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(recorded, 1);
+
+    // Only pending investments are reconciled
+    let result = funding_service
+        .reconcile_pending_investment(investment.id)
+        .await;
+    assert!(matches!(result, Err(AppError::Conflict(_))), "{:?}", result);
+
+    cleanup_disbursement_test(&pool, investor_id, mitra_id).await;
+}
+
+#[tokio::test]
+async fn test_reconcile_forwards_never_forwarded_investment_first() {
+    let mut config = get_test_config();
+    config.investment_mode = InvestmentMode::OnChain;
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let events = Arc::new(MockInvestmentEvents::default());
+    let (funding_service, invoice_service, _, pool) = setup_funding_service_with_mocks(
+        pool,
+        config,
+        None,
+        None,
+        None,
+        Some(events.clone() as Arc<dyn InvestmentEventSource>),
+        None,
+    )
+    .await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_reconcile_unforwarded@test.com")
+            .await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_reconcile_unforwarded@test.com").await;
+
+    let tx_hash = unique_tx_hash();
+    let investment = funding_service
+        .invest(
+            investor_id,
+            InvestRequest {
+                pool_id,
+                amount: 20_000_000.0,
+                tranche: "priority".to_string(),
+                tnc_accepted: true,
+                catalyst_consents: None,
+                tx_hash: tx_hash.clone(),
+            },
+        )
+        .await
+        .expect("Investment failed");
+    assert!(investment.forward_tx_hash.is_some());
+
+    // Where a crash between the claim and the forward leaves it: pending, never forwarded
+    sqlx::query(
+        "UPDATE investments SET status = 'pending', payout_wallet = NULL, forward_tx_hash = NULL, created_at = NOW() - INTERVAL '1 hour' WHERE id = $1",
+    )
+    .bind(investment.id)
+    .execute(&pool)
+    .await
+    .expect("Failed to reset investment");
+    sqlx::query("DELETE FROM transactions WHERE tx_hash = $1")
+        .bind(&tx_hash)
+        .execute(&pool)
+        .await
+        .expect("Failed to delete transaction");
+
+    let reconciled = funding_service
+        .reconcile_pending_investment(investment.id)
+        .await
+        .expect("Reconcile failed");
+    assert_eq!(reconciled.status, "active");
+    // The funds were forwarded before the contract was asked to record them
+    assert!(reconciled.forward_tx_hash.is_some());
+
+    cleanup_disbursement_test(&pool, investor_id, mitra_id).await;
+}
+
+#[tokio::test]
+async fn test_admin_pool_list_filters_filled_pools() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let (open_mitra_id, open_invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_admin_list_open@test.com").await;
+    let (filled_mitra_id, filled_invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_admin_list_filled@test.com")
+            .await;
+    let open_pool_id = setup_pool(&pool, &funding_service, open_invoice_id).await;
+    let filled_pool_id = setup_pool(&pool, &funding_service, filled_invoice_id).await;
+
+    sqlx::query(
+        "UPDATE funding_pools SET status = 'filled', funded_amount = target_amount, deadline = NOW() + INTERVAL '3 days 1 hour' WHERE id = $1",
+    )
+    .bind(filled_pool_id)
+    .execute(&pool)
+    .await
+    .expect("Failed to fill pool");
+
+    let filter = PoolFilter {
+        status: Some("filled".to_string()),
+        sort_by: Some("deadline".to_string()),
+    };
+    let (pools, total) = funding_service
+        .list_all_pools(&filter, 1, 1000)
+        .await
+        .expect("Listing pools failed");
+
+    assert!(total >= 1);
+    assert!(pools.iter().all(|p| p.pool.pool.status == "filled"));
+    assert!(!pools.iter().any(|p| p.pool.pool.id == open_pool_id));
+    let filled = pools
+        .iter()
+        .find(|p| p.pool.pool.id == filled_pool_id)
+        .expect("Filled pool missing from the list");
+    assert_eq!(filled.days_to_deadline, Some(3));
+    assert!((filled.pool.percentage_funded - 100.0).abs() < 0.01);
+    assert_eq!(
+        filled.pool.invoice.as_ref().map(|i| i.id),
+        Some(filled_invoice_id)
+    );
+
+    let unknown = PoolFilter {
+        status: Some("pending".to_string()),
+        sort_by: None,
+    };
+    assert!(matches!(
+        funding_service.list_all_pools(&unknown, 1, 10).await,
+        Err(AppError::ValidationError(_))
+    ));
+
+    for mitra_id in [open_mitra_id, filled_mitra_id] {
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(mitra_id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+/// Answers every `ownerOf` read with the same holder
+struct MockNftOwners {
+    owner: Address,
+}
+
+impl NftOwnerSource for MockNftOwners {
+    fn owner_of(
+        &self,
+        _token_id: i64,
+    ) -> futures_util::future::BoxFuture<'_, crate::error::AppResult<Address>> {
+        let owner = self.owner;
+        Box::pin(async move { Ok(owner) })
+    }
+}
+
+#[tokio::test]
+async fn test_invoice_nft_reports_minted_token_and_unminted_invoice() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let contract = "0x00000000000000000000000000000000000000aa";
+    let holder = "0x00000000000000000000000000000000000000bb";
+    let owners = Arc::new(MockNftOwners {
+        owner: holder.parse().unwrap(),
+    });
+    let (funding_service, invoice_service, _, pool) = setup_funding_service_with_mocks(
+        pool,
+        config.clone(),
+        None,
+        None,
+        None,
+        None,
+        Some(owners as Arc<dyn NftOwnerSource>),
+    )
+    .await;
+    let (minted_mitra_id, minted_invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_nft_minted@test.com").await;
+    let (unminted_mitra_id, unminted_invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_nft_unminted@test.com").await;
+
+    let invoice_repo = InvoiceRepository::new(pool.clone());
+    invoice_repo
+        .create_nft(
+            minted_invoice_id,
+            4242,
+            contract,
+            84532,
+            // Recorded checksummed; the contract read comes back lowercase
+            "0x00000000000000000000000000000000000000BB",
+            "0xmint",
+            "ipfs://metadata",
+        )
+        .await
+        .expect("Failed to record NFT");
+
+    let minted = funding_service
+        .get_invoice_nft(minted_invoice_id, true)
+        .await
+        .expect("NFT lookup failed");
+    assert!(minted.minted);
+    assert_eq!(minted.nft.as_ref().and_then(|n| n.token_id), Some(4242));
+    let explorer_url = format!("{}/nft/{}/4242", config.block_explorer_url, contract);
+    let opensea_url = format!(
+        "https://testnets.opensea.io/assets/base_sepolia/{}/4242",
+        contract
+    );
+    assert_eq!(minted.explorer_url.as_deref(), Some(explorer_url.as_str()));
+    assert_eq!(minted.opensea_url.as_deref(), Some(opensea_url.as_str()));
+    assert_eq!(minted.onchain_owner.as_deref(), Some(holder));
+    assert_eq!(minted.owner_matches, Some(true));
+    assert!(minted.owner_check_error.is_none());
+
+    // Without `verify` the chain is not asked
+    let unchecked = funding_service
+        .get_invoice_nft(minted_invoice_id, false)
+        .await
+        .expect("NFT lookup failed");
+    assert!(unchecked.minted);
+    assert!(unchecked.onchain_owner.is_none());
+    assert!(unchecked.owner_matches.is_none());
+
+    let unminted = funding_service
+        .get_invoice_nft(unminted_invoice_id, true)
+        .await
+        .expect("NFT lookup failed");
+    assert!(!unminted.minted);
+    assert!(unminted.nft.is_none());
+    assert!(unminted.explorer_url.is_none());
+    assert!(unminted.onchain_owner.is_none());
+
+    assert!(matches!(
+        funding_service.get_invoice_nft(Uuid::new_v4(), false).await,
+        Err(AppError::NotFound(_))
+    ));
+
+    for mitra_id in [minted_mitra_id, unminted_mitra_id] {
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(mitra_id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_marketplace_filters_pools_once_per_pool() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+
+    // The buyer country matches one tier row by code and another by name
+    sqlx::query(
+        r#"INSERT INTO country_tiers (country_code, country_name, tier) VALUES
+           ('ZZQ', 'Marketplace Testland', 3), ('ZZR', 'ZZQ', 2)
+           ON CONFLICT (country_code) DO NOTHING"#,
+    )
+    .execute(&pool)
+    .await
+    .expect("Failed to seed country tiers");
+
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "marketplace_filter_mitra@test.com")
+            .await;
+    sqlx::query("UPDATE invoices SET buyer_country = 'ZZQ' WHERE id = $1")
+        .bind(invoice_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to set buyer country");
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+
+    let listed = |filter: FundableInvoiceFilter| {
+        let funding_service = funding_service.clone();
+        async move {
+            let (pools, _) = funding_service
+                .list_marketplace(&filter, 1, 100)
+                .await
+                .expect("Failed to list marketplace");
+            pools.iter().filter(|p| p.pool.id == pool_id).count()
+        }
+    };
+
+    // The code match wins, so the pool is listed once and only under tier 3
+    assert_eq!(listed(FundableInvoiceFilter::default()).await, 1);
+    assert_eq!(
+        listed(FundableInvoiceFilter {
+            grade: Some("a".to_string()),
+            country_tier: Some(3),
+            ..Default::default()
+        })
+        .await,
+        1
+    );
+    assert_eq!(
+        listed(FundableInvoiceFilter {
+            country_tier: Some(2),
+            ..Default::default()
+        })
+        .await,
+        0
+    );
+    assert_eq!(
+        listed(FundableInvoiceFilter {
+            grade: Some("B".to_string()),
+            ..Default::default()
+        })
+        .await,
+        0
+    );
+    // The better-paying tranche of the fixture pays 15%
+    assert_eq!(
+        listed(FundableInvoiceFilter {
+            min_yield: Some(rust_decimal::Decimal::from(16)),
+            ..Default::default()
+        })
+        .await,
+        0
+    );
+
+    let result = funding_service
+        .list_marketplace(
+            &FundableInvoiceFilter {
+                country_tier: Some(4),
+                ..Default::default()
+            },
+            1,
+            100,
+        )
+        .await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+    sqlx::query("DELETE FROM country_tiers WHERE country_code IN ('ZZQ', 'ZZR')")
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_disbursement_preview_reports_blockers() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_preview_blockers@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    sqlx::query("UPDATE invoices SET exporter_wallet_address = NULL WHERE id = $1")
+        .bind(invoice_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to clear wallet");
+    sqlx::query("UPDATE funding_pools SET status = 'disbursed' WHERE id = $1")
+        .bind(pool_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to mark pool disbursed");
+
+    // A missing wallet is reported instead of estimating gas against an empty address
+    let preview = funding_service
+        .get_disbursement_preview(pool_id)
+        .await
+        .expect("Preview failed");
+    assert_eq!(preview.pool_status, "disbursed");
+    assert!(preview.recipient_wallet.is_none());
+    assert!(preview.estimated_gas.is_none());
+    assert!(!preview.sufficient_native_balance);
+    assert_eq!(
+        preview.blockers,
+        vec![
+            "Pool cannot be disbursed while disbursed".to_string(),
+            "Pool has no funds to disburse".to_string(),
+            "Mitra has no wallet address to disburse to".to_string(),
+        ]
+    );
+
+    sqlx::query("UPDATE funding_pools SET status = 'filled' WHERE id = $1")
+        .bind(pool_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to mark pool filled");
+    let result = funding_service.disburse_pool(pool_id).await;
+    assert!(matches!(result, Err(AppError::BadRequest(_))));
+
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_off_chain_pool_disbursement_is_rejected() {
+    let mut config = get_test_config();
+    config.investment_mode = InvestmentMode::OffChain;
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (funding_service, invoice_service, _, pool) =
+        setup_funding_service_with_config(pool, config).await;
+
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_off_chain_disburse@test.com")
+            .await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_off_chain_disburse@test.com").await;
+    sqlx::query("UPDATE users SET balance_idrx = 100000000 WHERE id = $1")
+        .bind(investor_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to fund balance");
+    funding_service
+        .invest(
+            investor_id,
+            InvestRequest {
+                pool_id,
+                amount: 20_000_000.0,
+                tranche: "priority".to_string(),
+                tnc_accepted: true,
+                catalyst_consents: None,
+                tx_hash: String::new(),
+            },
+        )
+        .await
+        .expect("Off-chain investment should succeed");
+    sqlx::query("UPDATE funding_pools SET status = 'filled' WHERE id = $1")
+        .bind(pool_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to mark pool filled");
+
+    // The pool contract never recorded the investment, so nothing is settled on-chain
+    let preview = funding_service
+        .get_disbursement_preview(pool_id)
+        .await
+        .expect("Preview failed");
+    assert!(preview.blockers.contains(
+        &"Disbursement and repayment are not available in off-chain investment mode".to_string()
+    ));
+
+    let result = funding_service.disburse_pool(pool_id).await;
+    assert!(
+        matches!(result, Err(AppError::BadRequest(_))),
+        "Got {:?}",
+        result
+    );
+    let funding_pool = FundingRepository::new(pool.clone())
+        .find_by_id(pool_id)
+        .await
+        .expect("Failed to load pool")
+        .expect("Pool should exist");
+    assert_eq!(funding_pool.status, "filled");
+
+    cleanup_disbursement_test(&pool, investor_id, mitra_id).await;
+}
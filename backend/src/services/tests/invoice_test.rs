@@ -0,0 +1,1008 @@
+use actix_web::http::header::TryIntoHeaderValue;
+use chrono::{Duration, Utc};
+use futures_util::future::BoxFuture;
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::handlers::invoice::attachment_disposition;
+use crate::models::{CreateInvoiceFundingRequest, DocumentIntegrityStatus, FundableInvoiceFilter};
+use crate::repository::{
+    CurrencyRepository, FundingRepository, InvoiceRepository, MitraRepository, UserRepository,
+};
+use crate::services::{
+    BlockchainService, CurrencyService, InvoiceService, IpfsGateway, PinataService,
+};
+use crate::utils::{keccak256_hex, sign_rate_quote, Cursor, RateQuote};
+
+use super::auth_test::get_test_config;
+use super::funding_test::{create_mitra_and_invoice, setup_funding_service};
+
+async fn create_approved_mitra(pool: &PgPool) -> Uuid {
+    let user_id = Uuid::new_v4();
+    sqlx::query(
+        r#"INSERT INTO users (id, email, username, password_hash, role, member_status, is_verified, is_active, wallet_address)
+           VALUES ($1, $2, $3, 'hash', 'mitra', 'member_mitra', true, true, $4)"#
+    )
+    .bind(user_id)
+    .bind(format!("{}_invoice_mitra@test.com", user_id.simple()))
+    .bind(format!("mitra_{}", user_id.simple()))
+    .bind(format!("0xMitra_{}", user_id.simple()))
+    .execute(pool)
+    .await
+    .expect("Failed to create mitra");
+
+    sqlx::query(
+        r#"INSERT INTO mitra_applications (user_id, company_name, company_type, npwp, annual_revenue, status)
+           VALUES ($1, 'Due Date Test PT', 'PT', '1234567890123456', '1M-5M', 'approved')"#,
+    )
+    .bind(user_id)
+    .execute(pool)
+    .await
+    .expect("Failed to create mitra application");
+
+    user_id
+}
+
+fn signed_quote(rate: f64) -> String {
+    sign_rate_quote(
+        &get_test_config().quote_signing_secret,
+        &RateQuote {
+            from_currency: "USD".to_string(),
+            to_currency: "IDR".to_string(),
+            rate,
+            expires_at: (Utc::now() + Duration::minutes(30)).timestamp(),
+        },
+    )
+}
+
+fn funding_request(due_date: String) -> CreateInvoiceFundingRequest {
+    CreateInvoiceFundingRequest {
+        buyer_company_name: "Buyer Co".to_string(),
+        buyer_country: "Singapore".to_string(),
+        buyer_email: "buyer@test.com".to_string(),
+        invoice_number: format!("INV-{}", Uuid::new_v4().simple()),
+        original_currency: "USD".to_string(),
+        original_amount: 10_000.0,
+        locked_exchange_rate: 15_000.0,
+        rate_lock_token: signed_quote(15_000.0),
+        idr_amount: 150_000_000.0,
+        due_date,
+        funding_duration_days: None,
+        priority_ratio: None,
+        catalyst_ratio: None,
+        priority_interest_rate: 10.0,
+        catalyst_interest_rate: 15.0,
+        is_repeat_buyer: false,
+        repeat_buyer_proof: None,
+        data_confirmation: true,
+        description: None,
+        wallet_address: "0xMitraWallet".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_create_funding_request_past_due_date_fails() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (_, invoice_service, _, pool) = setup_funding_service(pool).await;
+
+    let mitra_id = create_approved_mitra(&pool).await;
+    let past = (Utc::now().date_naive() - Duration::days(30))
+        .format("%Y-%m-%d")
+        .to_string();
+
+    let result = invoice_service
+        .create_funding_request(mitra_id, funding_request(past))
+        .await;
+    match result {
+        Err(AppError::ValidationError(msg)) => {
+            assert!(msg.contains("Due date must be after the issue date"))
+        }
+        other => panic!("Expected ValidationError, got {:?}", other.map(|i| i.id)),
+    }
+
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_create_funding_request_due_before_issue_fails() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (_, invoice_service, _, pool) = setup_funding_service(pool).await;
+
+    let mitra_id = create_approved_mitra(&pool).await;
+    // Funding requests are issued today, so a due date of today is not after the issue date
+    let today = Utc::now().date_naive().format("%Y-%m-%d").to_string();
+
+    let result = invoice_service
+        .create_funding_request(mitra_id, funding_request(today))
+        .await;
+    match result {
+        Err(AppError::ValidationError(msg)) => assert!(msg.contains("issue date")),
+        other => panic!("Expected ValidationError, got {:?}", other.map(|i| i.id)),
+    }
+
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_create_funding_request_future_due_date_succeeds() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (_, invoice_service, _, pool) = setup_funding_service(pool).await;
+
+    let mitra_id = create_approved_mitra(&pool).await;
+    let future = (Utc::now().date_naive() + Duration::days(60))
+        .format("%Y-%m-%d")
+        .to_string();
+
+    let result = invoice_service
+        .create_funding_request(mitra_id, funding_request(future))
+        .await;
+    assert!(
+        result.is_ok(),
+        "Future due date should be accepted: {:?}",
+        result.err()
+    );
+
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_revoked_mitra_cannot_create_funding_request() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (_, invoice_service, mitra_service, pool) = setup_funding_service(pool).await;
+
+    let mitra_id = create_approved_mitra(&pool).await;
+    let admin_id = Uuid::new_v4();
+    sqlx::query(
+        r#"INSERT INTO users (id, email, username, password_hash, role, member_status, is_verified, is_active)
+           VALUES ($1, $2, $3, 'hash', 'admin', 'admin', true, true)"#,
+    )
+    .bind(admin_id)
+    .bind(format!("{}_revoke_admin@test.com", admin_id.simple()))
+    .bind(format!("admin_{}", admin_id.simple()))
+    .execute(&pool)
+    .await
+    .expect("Failed to create admin");
+
+    let future = || {
+        (Utc::now().date_naive() + Duration::days(60))
+            .format("%Y-%m-%d")
+            .to_string()
+    };
+    let existing = invoice_service
+        .create_funding_request(mitra_id, funding_request(future()))
+        .await
+        .expect("Approved mitra should create invoices");
+
+    let application = MitraRepository::new(pool.clone())
+        .find_by_user(mitra_id)
+        .await
+        .expect("Failed to load application")
+        .expect("Application should exist");
+    let revoked = mitra_service
+        .revoke(application.id, admin_id, "Repeated late repayments")
+        .await
+        .expect("Revoke failed");
+    assert_eq!(revoked.status, "revoked");
+    assert_eq!(
+        revoked.rejection_reason.as_deref(),
+        Some("Repeated late repayments")
+    );
+
+    let member_status: (String,) = sqlx::query_as("SELECT member_status FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .fetch_one(&pool)
+        .await
+        .expect("Failed to load user");
+    assert_eq!(member_status.0, "calon_anggota_mitra");
+
+    let result = invoice_service
+        .create_funding_request(mitra_id, funding_request(future()))
+        .await;
+    match result {
+        Err(AppError::Forbidden(msg)) => assert!(msg.contains("revoked")),
+        other => panic!("Expected Forbidden, got {:?}", other.map(|i| i.id)),
+    }
+
+    // Invoices created before the revocation are untouched
+    let invoice = invoice_service
+        .get_invoice(existing.id)
+        .await
+        .expect("Existing invoice should remain");
+    assert_eq!(invoice.status, existing.status);
+
+    // A revoked mitra cannot be revoked again
+    let again = mitra_service
+        .revoke(application.id, admin_id, "Again")
+        .await;
+    assert!(matches!(again, Err(AppError::BadRequest(_))));
+
+    for id in [mitra_id, admin_id] {
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_financial_terms_locked_after_funding_starts() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (_, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let invoice_repo = InvoiceRepository::new(pool.clone());
+
+    let mitra_id = create_approved_mitra(&pool).await;
+    let future = (Utc::now().date_naive() + Duration::days(60))
+        .format("%Y-%m-%d")
+        .to_string();
+    let invoice = invoice_service
+        .create_funding_request(mitra_id, funding_request(future))
+        .await
+        .expect("Failed to create invoice");
+
+    let set_amount = |amount: i64| {
+        let pool = pool.clone();
+        let id = invoice.id;
+        async move {
+            sqlx::query("UPDATE invoices SET amount = $2 WHERE id = $1")
+                .bind(id)
+                .bind(Decimal::from(amount))
+                .execute(&pool)
+                .await
+                .map_err(AppError::from)
+        }
+    };
+
+    // Before funding the amount can still be corrected
+    set_amount(140_000_000)
+        .await
+        .expect("Pre-funding update should succeed");
+
+    invoice_repo
+        .update_status(invoice.id, "funding")
+        .await
+        .expect("Failed to move invoice to funding");
+
+    // The lock holds for any write, not just one repository method
+    match set_amount(500_000_000).await {
+        Err(AppError::Conflict(msg)) => assert!(msg.contains("locked")),
+        other => panic!("Expected Conflict, got {:?}", other.map(|_| ())),
+    }
+
+    // Fields other than the financial terms can still change
+    invoice_repo
+        .set_repeat_buyer(invoice.id, true)
+        .await
+        .expect("Non-financial update should succeed");
+
+    // Still locked once the advance is disbursed and repayment is owed
+    invoice_repo
+        .update_status(invoice.id, "disbursed")
+        .await
+        .expect("Failed to move invoice to disbursed");
+    match set_amount(500_000_000).await {
+        Err(AppError::Conflict(msg)) => assert!(msg.contains("locked")),
+        other => panic!("Expected Conflict, got {:?}", other.map(|_| ())),
+    }
+
+    let stored = invoice_repo
+        .find_by_id(invoice.id)
+        .await
+        .expect("Failed to load invoice")
+        .expect("Invoice missing");
+    assert_eq!(stored.amount, Decimal::from(140_000_000));
+
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_create_funding_request_with_server_quote_succeeds() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (_, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let currency_service = CurrencyService::new(
+        Arc::new(CurrencyRepository::new(pool.clone())),
+        Arc::new(config),
+    );
+
+    let mitra_id = create_approved_mitra(&pool).await;
+    let quote = currency_service
+        .get_locked_exchange_rate("USD", 10_000.0)
+        .await
+        .expect("Quote failed");
+
+    let mut req = funding_request(
+        (Utc::now().date_naive() + Duration::days(60))
+            .format("%Y-%m-%d")
+            .to_string(),
+    );
+    req.locked_exchange_rate = quote.effective_rate;
+    req.idr_amount = quote.converted_amount;
+    req.rate_lock_token = quote.rate_lock_token;
+
+    let invoice = invoice_service
+        .create_funding_request(mitra_id, req)
+        .await
+        .expect("Signed quote should be accepted");
+    assert_eq!(
+        invoice.amount,
+        Decimal::from_f64(quote.converted_amount).unwrap().round_dp(2)
+    );
+
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_create_funding_request_tampered_quote_rejected() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (_, invoice_service, _, pool) = setup_funding_service(pool).await;
+
+    let mitra_id = create_approved_mitra(&pool).await;
+    let future = (Utc::now().date_naive() + Duration::days(60))
+        .format("%Y-%m-%d")
+        .to_string();
+
+    // Raise the rate inside the token but keep the original signature
+    let genuine = signed_quote(15_000.0);
+    let (_, signature) = genuine.split_once('.').unwrap();
+    let expires_at = (Utc::now() + Duration::minutes(30)).timestamp();
+    let forged_payload = hex::encode(format!("USD:IDR:16000:{}", expires_at));
+    let mut req = funding_request(future.clone());
+    req.locked_exchange_rate = 16_000.0;
+    req.idr_amount = 160_000_000.0;
+    req.rate_lock_token = format!("{}.{}", forged_payload, signature);
+
+    let result = invoice_service.create_funding_request(mitra_id, req).await;
+    match result {
+        Err(AppError::ValidationError(msg)) => assert!(msg.contains("quote")),
+        other => panic!("Expected ValidationError, got {:?}", other.map(|i| i.id)),
+    }
+
+    // A genuine quote cannot be paired with a different client-side rate either
+    let mut req = funding_request(future);
+    req.locked_exchange_rate = 16_000.0;
+    req.idr_amount = 160_000_000.0;
+    let result = invoice_service.create_funding_request(mitra_id, req).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+/// Serves fixed content per gateway URL; unknown CIDs are unreachable
+struct MockGateway {
+    files: HashMap<String, Vec<u8>>,
+}
+
+impl IpfsGateway for MockGateway {
+    fn fetch<'a>(&'a self, url: &'a str) -> BoxFuture<'a, AppResult<Vec<u8>>> {
+        let result = self
+            .files
+            .get(url)
+            .cloned()
+            .ok_or_else(|| AppError::IpfsError("504 Gateway Timeout".to_string()));
+        Box::pin(async move { result })
+    }
+}
+
+#[tokio::test]
+async fn test_verify_documents_reports_mismatch_and_unreachable() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (_, funding_invoice_service, _, pool) = setup_funding_service(pool).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &funding_invoice_service, "mitra_doc_verify@test.com")
+            .await;
+
+    let mut config = config;
+    config.pinata_gateway_url = String::new();
+    let config = Arc::new(config);
+    let gateway = MockGateway {
+        files: HashMap::from([
+            (
+                "https://gateway.pinata.cloud/ipfs/QmIntact".to_string(),
+                b"original invoice".to_vec(),
+            ),
+            (
+                "https://gateway.pinata.cloud/ipfs/QmTampered".to_string(),
+                b"edited bill of lading".to_vec(),
+            ),
+        ]),
+    };
+    let invoice_service = InvoiceService::new(
+        Arc::new(InvoiceRepository::new(pool.clone())),
+        Arc::new(FundingRepository::new(pool.clone())),
+        Arc::new(UserRepository::new(pool.clone())),
+        Arc::new(MitraRepository::new(pool.clone())),
+        Arc::new(PinataService::with_gateway(
+            config.clone(),
+            Arc::new(gateway),
+        )),
+        config,
+    );
+
+    let invoice_repo = InvoiceRepository::new(pool.clone());
+    for (document_type, cid, content) in [
+        ("invoice", "QmIntact", "original invoice"),
+        ("bill_of_lading", "QmTampered", "original bill of lading"),
+        ("packing_list", "QmUnpinned", "original packing list"),
+    ] {
+        invoice_repo
+            .create_document(
+                invoice_id,
+                document_type,
+                &format!("{}.pdf", document_type),
+                &format!("https://gateway.pinata.cloud/ipfs/{}", cid),
+                &keccak256_hex(content.as_bytes()),
+                content.len() as i32,
+            )
+            .await
+            .expect("Failed to create document");
+    }
+
+    let report = invoice_service
+        .verify_documents(invoice_id)
+        .await
+        .expect("Verification should run");
+    assert!(!report.all_verified);
+    assert_eq!(report.mismatched, 1);
+    assert_eq!(report.unreachable, 1);
+
+    let status_of = |cid: &str| {
+        report
+            .documents
+            .iter()
+            .find(|d| d.cid == cid)
+            .map(|d| d.status)
+            .expect("Document should be in the report")
+    };
+    assert_eq!(status_of("QmIntact"), DocumentIntegrityStatus::Verified);
+    assert_eq!(status_of("QmTampered"), DocumentIntegrityStatus::Mismatch);
+    assert_eq!(
+        status_of("QmUnpinned"),
+        DocumentIntegrityStatus::Unreachable
+    );
+
+    let tampered = report
+        .documents
+        .iter()
+        .find(|d| d.cid == "QmTampered")
+        .unwrap();
+    assert_eq!(
+        tampered.actual_hash.as_deref(),
+        Some(keccak256_hex(b"edited bill of lading").as_str())
+    );
+    assert_ne!(
+        tampered.actual_hash.as_deref(),
+        Some(tampered.expected_hash.as_str())
+    );
+
+    let result = invoice_service.verify_documents(Uuid::new_v4()).await;
+    assert!(matches!(result, Err(AppError::NotFound(_))));
+
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_download_document_restricted_to_owner_and_admin() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (_, funding_invoice_service, _, pool) = setup_funding_service(pool).await;
+    let (mitra_id, invoice_id) = create_mitra_and_invoice(
+        &pool,
+        &funding_invoice_service,
+        "mitra_doc_download@test.com",
+    )
+    .await;
+
+    let mut config = config;
+    config.pinata_gateway_url = String::new();
+    let config = Arc::new(config);
+    let gateway = MockGateway {
+        files: HashMap::from([(
+            "https://gateway.pinata.cloud/ipfs/QmDownload".to_string(),
+            b"%PDF-1.7 invoice".to_vec(),
+        )]),
+    };
+    let invoice_service = InvoiceService::new(
+        Arc::new(InvoiceRepository::new(pool.clone())),
+        Arc::new(FundingRepository::new(pool.clone())),
+        Arc::new(UserRepository::new(pool.clone())),
+        Arc::new(MitraRepository::new(pool.clone())),
+        Arc::new(PinataService::with_gateway(
+            config.clone(),
+            Arc::new(gateway),
+        )),
+        config,
+    );
+
+    let document = InvoiceRepository::new(pool.clone())
+        .create_document(
+            invoice_id,
+            "invoice",
+            "Invoice.PDF",
+            "https://gateway.pinata.cloud/ipfs/QmDownload",
+            &keccak256_hex(b"%PDF-1.7 invoice"),
+            16,
+        )
+        .await
+        .expect("Failed to create document");
+
+    // Owner gets the bytes along with the original name and type
+    let (doc, data) = invoice_service
+        .download_document(mitra_id, false, invoice_id, document.id)
+        .await
+        .expect("Owner should download");
+    assert_eq!(data, b"%PDF-1.7 invoice".to_vec());
+    assert_eq!(doc.file_name, "Invoice.PDF");
+    assert_eq!(doc.content_type(), "application/pdf");
+
+    // Admins may read any invoice's documents
+    let result = invoice_service
+        .download_document(Uuid::new_v4(), true, invoice_id, document.id)
+        .await;
+    assert!(result.is_ok());
+
+    // Anyone else is refused
+    let result = invoice_service
+        .download_document(Uuid::new_v4(), false, invoice_id, document.id)
+        .await;
+    assert!(matches!(result, Err(AppError::Forbidden(_))));
+
+    // Unknown document ids, and documents of another invoice, are not found
+    let result = invoice_service
+        .download_document(mitra_id, false, invoice_id, Uuid::new_v4())
+        .await;
+    assert!(matches!(result, Err(AppError::NotFound(_))));
+    let result = invoice_service
+        .download_document(mitra_id, true, Uuid::new_v4(), document.id)
+        .await;
+    assert!(matches!(result, Err(AppError::NotFound(_))));
+
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[test]
+fn test_attachment_disposition_escapes_file_name() {
+    let header = attachment_disposition("faktur \"Q1\"\r\nX: y.pdf")
+        .try_into_value()
+        .expect("Disposition must be a valid header value");
+    let header = header.to_str().unwrap();
+    assert!(
+        header.starts_with(r#"attachment; filename="faktur \"Q1\"__X: y.pdf""#),
+        "{}",
+        header
+    );
+    assert!(
+        header.contains("filename*=UTF-8''faktur%20%22Q1%22%0D%0AX%3A%20y.pdf"),
+        "{}",
+        header
+    );
+
+    // Non-ASCII names survive in filename* and are replaced in the fallback
+    let header = attachment_disposition("tagihané.pdf").to_string();
+    assert!(header.contains(r#"filename="tagihan_.pdf""#), "{}", header);
+    assert!(
+        header.contains("filename*=UTF-8''tagihan%C3%A9.pdf"),
+        "{}",
+        header
+    );
+}
+
+#[tokio::test]
+async fn test_approval_sets_minted_interest_rate_from_tranche_terms() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (_, invoice_service, _, pool) = setup_funding_service(pool).await;
+
+    let mitra_id = create_approved_mitra(&pool).await;
+    let future = (Utc::now().date_naive() + Duration::days(60))
+        .format("%Y-%m-%d")
+        .to_string();
+    let invoice = invoice_service
+        .create_funding_request(mitra_id, funding_request(future))
+        .await
+        .expect("Failed to create invoice");
+    InvoiceRepository::new(pool.clone())
+        .update_status(invoice.id, "pending_review")
+        .await
+        .expect("Failed to submit invoice");
+
+    // Admin overrides the requested 10% / 15% with 9.5% / 14% on an 80/20 split
+    let approved = invoice_service
+        .approve(invoice.id, "A", Some(9.5), Some(14.0))
+        .await
+        .expect("Approval should succeed");
+    assert_eq!(approved.priority_interest_rate, Some(Decimal::new(95, 1)));
+    assert_eq!(approved.catalyst_interest_rate, Some(Decimal::from(14)));
+
+    // 0.8 * 9.5 + 0.2 * 14 = 10.4
+    assert_eq!(approved.interest_rate, Some(Decimal::new(1040, 2)));
+    assert_eq!(BlockchainService::interest_rate_bps(&approved), 1040);
+
+    // Invoices approved before `interest_rate` was populated mint the same rate
+    let legacy = crate::models::Invoice {
+        interest_rate: None,
+        ..approved
+    };
+    assert_eq!(BlockchainService::interest_rate_bps(&legacy), 1040);
+
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_invoice_document_hash_is_reproducible_from_documents() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (_, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_doc_hash@test.com").await;
+
+    let invoice_repo = InvoiceRepository::new(pool.clone());
+    let contents: [&[u8]; 3] = [b"invoice pdf", b"bill of lading", b"packing list"];
+    for (i, content) in contents.iter().enumerate() {
+        invoice_repo
+            .create_document(
+                invoice_id,
+                "other",
+                &format!("doc-{}.pdf", i),
+                &format!("https://gateway.pinata.cloud/ipfs/QmDoc{}", i),
+                &keccak256_hex(content),
+                content.len() as i32,
+            )
+            .await
+            .expect("Failed to create document");
+    }
+
+    let refreshed = invoice_service
+        .refresh_document_hash(invoice_id)
+        .await
+        .expect("Failed to refresh document hash")
+        .expect("Invoice with documents should get a hash");
+    let stored = invoice_service
+        .get_invoice(invoice_id)
+        .await
+        .expect("Failed to load invoice")
+        .document_hash
+        .expect("document_hash should be stored");
+    assert_eq!(stored, refreshed);
+    assert_eq!(stored.len(), 66);
+    assert!(stored.starts_with("0x"));
+
+    // Recompute from the stored documents alone: keccak256 over the sorted
+    // 32-byte document hashes
+    let documents = invoice_service
+        .get_documents(invoice_id)
+        .await
+        .expect("Failed to load documents");
+    let mut digests: Vec<[u8; 32]> = documents
+        .iter()
+        .map(|d| {
+            assert_eq!(d.file_hash.len(), 66);
+            let bytes = hex::decode(d.file_hash.trim_start_matches("0x")).unwrap();
+            bytes.try_into().unwrap()
+        })
+        .collect();
+    digests.sort();
+    let expected = format!(
+        "0x{}",
+        hex::encode(ethers::utils::keccak256(digests.concat()))
+    );
+    assert_eq!(stored, expected);
+
+    // Each document hash is the keccak256 of its content
+    for content in contents {
+        let hash = keccak256_hex(content);
+        assert!(documents.iter().any(|d| d.file_hash == hash));
+    }
+
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+/// Copies the fixture invoice under a fresh invoice number
+async fn copy_invoice(pool: &PgPool, invoice_id: Uuid) {
+    sqlx::query(
+        r#"
+        INSERT INTO invoices (exporter_id, buyer_name, buyer_country, buyer_email, invoice_number,
+                              currency, amount, issue_date, due_date, status, exporter_wallet_address)
+        SELECT exporter_id, buyer_name, buyer_country, buyer_email, $2,
+               currency, amount, issue_date, due_date, status, exporter_wallet_address
+        FROM invoices WHERE id = $1
+        "#,
+    )
+    .bind(invoice_id)
+    .bind(format!("INV-{}", Uuid::new_v4().simple()))
+    .execute(pool)
+    .await
+    .expect("Failed to copy invoice");
+}
+
+#[tokio::test]
+async fn test_list_by_exporter_pages_are_stable_on_created_at_ties() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (_, invoice_service, _, pool) = setup_funding_service(pool).await;
+
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "paging_mitra@test.com").await;
+    for _ in 0..6 {
+        copy_invoice(&pool, invoice_id).await;
+    }
+    // Identical timestamps leave only the tiebreaker to order the pages
+    sqlx::query("UPDATE invoices SET created_at = NOW() WHERE exporter_id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to align created_at");
+
+    let mut seen = HashSet::new();
+    let mut page = 1;
+    loop {
+        let (invoices, total) = invoice_service
+            .list_by_exporter(mitra_id, page, 3, None, false, None)
+            .await
+            .expect("Failed to list invoices");
+        assert_eq!(total, 7);
+        if invoices.is_empty() {
+            break;
+        }
+        for invoice in invoices {
+            assert!(seen.insert(invoice.id), "{} returned twice", invoice.id);
+        }
+        page += 1;
+    }
+    assert_eq!(seen.len(), 7);
+
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_list_by_exporter_cursor_pages_do_not_overlap() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (_, invoice_service, _, pool) = setup_funding_service(pool).await;
+
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "cursor_mitra@test.com").await;
+    for _ in 0..5 {
+        copy_invoice(&pool, invoice_id).await;
+    }
+    // Identical timestamps leave only the id to order the pages
+    sqlx::query(
+        "UPDATE invoices SET created_at = NOW() - INTERVAL '1 hour' WHERE exporter_id = $1",
+    )
+    .bind(mitra_id)
+    .execute(&pool)
+    .await
+    .expect("Failed to align created_at");
+
+    let (first, total) = invoice_service
+        .list_by_exporter(mitra_id, 1, 3, None, false, None)
+        .await
+        .expect("Failed to list first page");
+    assert_eq!(total, 6);
+    let cursor = Cursor::after_page(&first, 3, |i| (i.created_at, i.id))
+        .expect("A full page has a next cursor");
+
+    // A newer invoice would shift an offset-based second page by one row
+    copy_invoice(&pool, invoice_id).await;
+
+    let cursor = Cursor::decode(&cursor.encode()).expect("Cursor should round-trip");
+    let (second, _) = invoice_service
+        .list_by_exporter(mitra_id, 2, 3, None, false, Some(cursor))
+        .await
+        .expect("Failed to list second page");
+
+    let mut seen = HashSet::new();
+    for invoice in first.iter().chain(second.iter()) {
+        assert!(seen.insert(invoice.id), "{} returned twice", invoice.id);
+    }
+    assert_eq!(seen.len(), 6);
+
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+/// Copies the fixture invoice as a fundable invoice with the given grade and buyer country
+async fn copy_fundable_invoice(
+    pool: &PgPool,
+    invoice_id: Uuid,
+    grade: &str,
+    country: &str,
+) -> Uuid {
+    let row: (Uuid,) = sqlx::query_as(
+        r#"
+        INSERT INTO invoices (exporter_id, buyer_name, buyer_country, buyer_email, invoice_number,
+                              currency, amount, issue_date, due_date, status, exporter_wallet_address,
+                              grade, priority_interest_rate, catalyst_interest_rate)
+        SELECT exporter_id, buyer_name, $3, buyer_email, $4,
+               currency, amount, issue_date, due_date, 'approved', exporter_wallet_address,
+               $2, priority_interest_rate, catalyst_interest_rate
+        FROM invoices WHERE id = $1
+        RETURNING id
+        "#,
+    )
+    .bind(invoice_id)
+    .bind(grade)
+    .bind(country)
+    .bind(format!("INV-{}", Uuid::new_v4().simple()))
+    .fetch_one(pool)
+    .await
+    .expect("Failed to copy invoice");
+    row.0
+}
+
+#[tokio::test]
+async fn test_list_fundable_filters_by_grade_and_country_tier() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (_, invoice_service, _, pool) = setup_funding_service(pool).await;
+
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "fundable_filter_mitra@test.com").await;
+    let grade_a_tier_1 = copy_fundable_invoice(&pool, invoice_id, "A", "USA").await;
+    let grade_b_tier_1 = copy_fundable_invoice(&pool, invoice_id, "B", "United States").await;
+    let grade_a_tier_3 = copy_fundable_invoice(&pool, invoice_id, "A", "Nigeria").await;
+    let ours = [grade_a_tier_1, grade_b_tier_1, grade_a_tier_3];
+
+    let matching = |invoices: Vec<crate::models::Invoice>| -> HashSet<Uuid> {
+        invoices
+            .into_iter()
+            .map(|invoice| invoice.id)
+            .filter(|id| ours.contains(id))
+            .collect()
+    };
+
+    // Grades are matched case-insensitively
+    let (invoices, _) = invoice_service
+        .list_fundable(
+            &FundableInvoiceFilter {
+                grade: Some("a".to_string()),
+                ..Default::default()
+            },
+            1,
+            100,
+        )
+        .await
+        .expect("Failed to list by grade");
+    assert!(invoices.iter().all(|i| i.grade.as_deref() == Some("A")));
+    assert_eq!(
+        matching(invoices),
+        HashSet::from([grade_a_tier_1, grade_a_tier_3])
+    );
+
+    // Country tier is looked up by code or by name
+    let (invoices, _) = invoice_service
+        .list_fundable(
+            &FundableInvoiceFilter {
+                country_tier: Some(1),
+                ..Default::default()
+            },
+            1,
+            100,
+        )
+        .await
+        .expect("Failed to list by tier");
+    assert_eq!(
+        matching(invoices),
+        HashSet::from([grade_a_tier_1, grade_b_tier_1])
+    );
+
+    let (invoices, total) = invoice_service
+        .list_fundable(
+            &FundableInvoiceFilter {
+                grade: Some("A".to_string()),
+                country_tier: Some(3),
+                ..Default::default()
+            },
+            1,
+            100,
+        )
+        .await
+        .expect("Failed to list by grade and tier");
+    assert!(total >= 1);
+    assert_eq!(matching(invoices), HashSet::from([grade_a_tier_3]));
+
+    let result = invoice_service
+        .list_fundable(
+            &FundableInvoiceFilter {
+                country_tier: Some(4),
+                ..Default::default()
+            },
+            1,
+            100,
+        )
+        .await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
@@ -0,0 +1,230 @@
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::models::{InvestRequest, KycSubmitRequest};
+use crate::repository::UserRepository;
+use crate::services::email_service::EmailService;
+use crate::services::KycService;
+
+use super::auth_test::get_test_config;
+use super::funding_test::{
+    create_investor, create_mitra_and_invoice, setup_funding_service_with_config, setup_pool,
+    unique_tx_hash,
+};
+
+fn setup_kyc_service(pool: &PgPool) -> Arc<KycService> {
+    let config = Arc::new(get_test_config());
+    let user_repo = Arc::new(UserRepository::new(pool.clone()));
+    let email_service = Arc::new(EmailService::new(config));
+    Arc::new(KycService::new(user_repo, email_service))
+}
+
+async fn create_test_admin(pool: &PgPool) -> Uuid {
+    let admin_id = Uuid::new_v4();
+    sqlx::query(
+        r#"INSERT INTO users (id, email, username, password_hash, role, member_status, is_verified, is_active, cooperative_agreement, email_verified, profile_completed)
+           VALUES ($1, $2, $3, 'hash', 'admin', 'admin', true, true, true, true, true)"#
+    )
+    .bind(admin_id)
+    .bind(format!("{}_kyc_admin@test.com", admin_id.simple()))
+    .bind(format!("admin_{}", admin_id.simple()))
+    .execute(pool)
+    .await
+    .expect("Failed to create test admin");
+
+    admin_id
+}
+
+fn kyc_request() -> KycSubmitRequest {
+    KycSubmitRequest {
+        nik: "3171234567890001".to_string(),
+        full_name: "Test Investor".to_string(),
+        ktp_photo_url: "ipfs://ktp".to_string(),
+        selfie_url: "ipfs://selfie".to_string(),
+    }
+}
+
+async fn cleanup_users(pool: &PgPool, ids: &[Uuid]) {
+    for id in ids {
+        // transactions.user_id does not cascade
+        sqlx::query("DELETE FROM transactions WHERE user_id = $1")
+            .bind(id)
+            .execute(pool)
+            .await
+            .ok();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_kyc_approval_unlocks_investment() {
+    let mut config = get_test_config();
+    config.require_investor_kyc = true;
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let (funding_service, invoice_service, _, pool) =
+        setup_funding_service_with_config(pool, config).await;
+    let kyc_service = setup_kyc_service(&pool);
+
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_kyc_gate@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_kyc_gate@test.com").await;
+    let admin_id = create_test_admin(&pool).await;
+
+    let tx_hash = unique_tx_hash();
+    let invest_request = || InvestRequest {
+        pool_id,
+        amount: 20_000_000.0,
+        tranche: "priority".to_string(),
+        tnc_accepted: true,
+        catalyst_consents: None,
+        tx_hash: tx_hash.clone(),
+    };
+
+    // Without approved KYC the investment is blocked
+    let result = funding_service.invest(investor_id, invest_request()).await;
+    assert!(
+        matches!(result, Err(AppError::KycRequired)),
+        "Investment without KYC should be rejected: {:?}",
+        result.err()
+    );
+
+    let kyc = kyc_service
+        .submit(investor_id, kyc_request())
+        .await
+        .expect("KYC submit should succeed");
+    assert_eq!(kyc.status, "pending");
+
+    // Still blocked while pending
+    let result = funding_service.invest(investor_id, invest_request()).await;
+    assert!(matches!(result, Err(AppError::KycRequired)));
+
+    let approved = kyc_service
+        .approve(kyc.id, admin_id)
+        .await
+        .expect("KYC approve should succeed");
+    assert_eq!(approved.status, "approved");
+    assert_eq!(approved.verified_by, Some(admin_id));
+    assert!(approved.verified_at.is_some());
+
+    let result = funding_service.invest(investor_id, invest_request()).await;
+    assert!(
+        result.is_ok(),
+        "Investment after KYC approval should succeed: {:?}",
+        result.err()
+    );
+
+    cleanup_users(&pool, &[investor_id, mitra_id, admin_id]).await;
+}
+
+#[tokio::test]
+async fn test_kyc_rejection_records_reason() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    crate::database::run_migrations(&pool)
+        .await
+        .expect("Failed to run migrations");
+    let kyc_service = setup_kyc_service(&pool);
+
+    let investor_id = create_investor(&pool, "investor_kyc_reject@test.com").await;
+    let admin_id = create_test_admin(&pool).await;
+
+    let kyc = kyc_service
+        .submit(investor_id, kyc_request())
+        .await
+        .expect("KYC submit should succeed");
+
+    // Reason is mandatory
+    let result = kyc_service.reject(kyc.id, admin_id, "  ").await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+
+    let rejected = kyc_service
+        .reject(kyc.id, admin_id, "KTP photo is blurry")
+        .await
+        .expect("KYC reject should succeed");
+    assert_eq!(rejected.status, "rejected");
+    assert_eq!(
+        rejected.rejection_reason.as_deref(),
+        Some("KTP photo is blurry")
+    );
+    assert_eq!(rejected.verified_by, Some(admin_id));
+    assert!(rejected.verified_at.is_some());
+
+    let status = kyc_service
+        .get_status(investor_id)
+        .await
+        .expect("KYC status should load");
+    assert_eq!(status.status, "rejected");
+    assert_eq!(
+        status.rejection_reason.as_deref(),
+        Some("KTP photo is blurry")
+    );
+
+    cleanup_users(&pool, &[investor_id, admin_id]).await;
+}
+
+#[tokio::test]
+async fn test_concurrent_kyc_reviews_decide_once() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    crate::database::run_migrations(&pool)
+        .await
+        .expect("Failed to run migrations");
+    let kyc_service = setup_kyc_service(&pool);
+
+    let investor_id = create_investor(&pool, "investor_kyc_race@test.com").await;
+    let admin_id = create_test_admin(&pool).await;
+
+    let kyc = kyc_service
+        .submit(investor_id, kyc_request())
+        .await
+        .expect("KYC submit should succeed");
+
+    let (approved, rejected) = tokio::join!(
+        kyc_service.approve(kyc.id, admin_id),
+        kyc_service.reject(kyc.id, admin_id, "KTP photo is blurry"),
+    );
+    assert!(
+        approved.is_ok() != rejected.is_ok(),
+        "Exactly one review should win: {:?} / {:?}",
+        approved,
+        rejected
+    );
+    let loser = if approved.is_ok() {
+        rejected.map(|_| ())
+    } else {
+        approved.map(|_| ())
+    };
+    assert!(matches!(
+        loser,
+        Err(AppError::Conflict(_) | AppError::BadRequest(_))
+    ));
+
+    // A decided verification is never overwritten by a late review
+    let user_repo = UserRepository::new(pool.clone());
+    let late = user_repo
+        .approve_kyc(kyc.id, admin_id)
+        .await
+        .expect("Approve query should run");
+    assert!(late.is_none());
+    let late = user_repo
+        .reject_kyc(kyc.id, admin_id, "Too late")
+        .await
+        .expect("Reject query should run");
+    assert!(late.is_none());
+
+    cleanup_users(&pool, &[investor_id, admin_id]).await;
+}
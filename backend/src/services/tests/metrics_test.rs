@@ -0,0 +1,42 @@
+use actix_web::{http::header, test, web, App};
+use std::sync::Arc;
+
+use crate::handlers::{livez, metrics};
+use crate::middleware::RequestMetrics;
+use crate::utils::Metrics;
+
+#[actix_web::test]
+async fn test_metrics_endpoint_counts_requests_by_route() {
+    let registry = Arc::new(Metrics::new());
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::from(registry.clone()))
+            .wrap(RequestMetrics::new(registry.clone()))
+            .route("/livez", web::get().to(livez))
+            .route("/metrics", web::get().to(metrics)),
+    )
+    .await;
+
+    let resp = test::call_service(&app, test::TestRequest::get().uri("/livez").to_request()).await;
+    assert!(resp.status().is_success());
+
+    let resp =
+        test::call_service(&app, test::TestRequest::get().uri("/metrics").to_request()).await;
+    assert!(resp.status().is_success());
+    let content_type = resp
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    assert!(content_type.starts_with("text/plain"), "{}", content_type);
+
+    let body = test::read_body(resp).await;
+    let body = String::from_utf8(body.to_vec()).expect("metrics are UTF-8");
+    assert!(
+        body.contains(r#"vessel_http_requests_total{method="GET",route="/livez",status="200"} 1"#),
+        "{}",
+        body
+    );
+    assert!(body.contains("vessel_http_request_duration_seconds_bucket"));
+}
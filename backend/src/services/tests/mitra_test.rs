@@ -49,7 +49,7 @@ async fn create_test_user(pool: &PgPool, email: &str) -> Uuid {
     let user_id = Uuid::new_v4();
     sqlx::query(
         r#"INSERT INTO users (id, email, username, password_hash, role, member_status, is_verified, is_active, cooperative_agreement, email_verified, profile_completed)
-           VALUES ($1, $2, $3, 'hash', 'mitra', 'calon_mitra', true, true, true, true, false)"#
+           VALUES ($1, $2, $3, 'hash', 'mitra', 'calon_anggota_mitra', true, true, true, true, false)"#
     )
     .bind(user_id)
     .bind(email)
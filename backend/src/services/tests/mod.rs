@@ -1,5 +1,27 @@
 pub mod auth_test;
+pub mod blockchain_test;
+pub mod compression_test;
+pub mod config_test;
+pub mod conditional_get_test;
+pub mod cors_test;
+pub mod currency_test;
+pub mod email_test;
+pub mod error_test;
 pub mod funding_test;
+pub mod health_test;
+pub mod invoice_test;
+pub mod kyc_test;
+pub mod metrics_test;
 
 pub mod mitra_test;
+pub mod notification_test;
+pub mod openapi_test;
 pub mod otp_test;
+pub mod payment_test;
+pub mod rate_limit_test;
+pub mod request_id_test;
+pub mod risk_questionnaire_test;
+pub mod role_middleware_test;
+pub mod transaction_test;
+pub mod user_role_test;
+pub mod user_test;
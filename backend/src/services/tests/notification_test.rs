@@ -0,0 +1,248 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use sqlx::PgPool;
+use std::sync::{Arc, Mutex};
+
+use crate::models::InvestRequest;
+use crate::repository::NotificationRepository;
+use crate::services::{NotificationService, WebhookDelivery, WebhookSender};
+
+use super::auth_test::get_test_config;
+use super::funding_test::{
+    create_investor, create_mitra_and_invoice, setup_funding_service,
+    setup_funding_service_with_sender, setup_pool,
+};
+
+const TEST_WEBHOOK_SECRET: &str = "webhook-test-secret";
+
+/// Records deliveries instead of POSTing them
+#[derive(Default)]
+struct RecordingSender {
+    deliveries: Mutex<Vec<WebhookDelivery>>,
+}
+
+impl WebhookSender for RecordingSender {
+    fn send(&self, delivery: WebhookDelivery) {
+        self.deliveries.lock().unwrap().push(delivery);
+    }
+}
+
+impl RecordingSender {
+    fn events(&self, event: &str) -> Vec<WebhookDelivery> {
+        self.deliveries
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|d| d.event == event)
+            .cloned()
+            .collect()
+    }
+}
+
+#[test]
+fn test_webhook_signature_is_hmac_of_body() {
+    let body = r#"{"event":"pool_filled"}"#;
+    let signature = NotificationService::sign(TEST_WEBHOOK_SECRET, body);
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(TEST_WEBHOOK_SECRET.as_bytes()).unwrap();
+    mac.update(body.as_bytes());
+    let expected = format!("sha256={}", hex::encode(mac.finalize().into_bytes()));
+
+    assert_eq!(signature, expected);
+    assert_ne!(signature, NotificationService::sign("other-secret", body));
+}
+
+#[tokio::test]
+async fn test_invest_filling_pool_sends_one_signed_pool_filled_webhook() {
+    let mut config = get_test_config();
+    config.webhook_url = "http://webhook.test/vessel".to_string();
+    config.webhook_secret = TEST_WEBHOOK_SECRET.to_string();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+
+    let sender = Arc::new(RecordingSender::default());
+    let (funding_service, invoice_service, _, pool) = setup_funding_service_with_sender(
+        pool,
+        config,
+        Some(sender.clone() as Arc<dyn WebhookSender>),
+    )
+    .await;
+
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_webhook_fill@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let first_investor = create_investor(&pool, "investor_webhook_first@test.com").await;
+    let second_investor = create_investor(&pool, "investor_webhook_second@test.com").await;
+
+    let invest_request = |tx_hash: &str| InvestRequest {
+        pool_id,
+        amount: 20_000_000.0,
+        tranche: "priority".to_string(),
+        tnc_accepted: true,
+        catalyst_consents: None,
+        tx_hash: tx_hash.to_string(),
+    };
+
+    // A partial investment does not fill the pool
+    funding_service
+        .invest(first_investor, invest_request("0xWebhookFirstHash"))
+        .await
+        .expect("First investment failed");
+    assert!(sender.events("pool_filled").is_empty());
+
+    // Leave exactly 20M of headroom so the next investment reaches the target
+    sqlx::query(
+        "UPDATE funding_pools SET funded_amount = target_amount - 20000000 WHERE id = $1",
+    )
+    .bind(pool_id)
+    .execute(&pool)
+    .await
+    .expect("Failed to adjust funded amount");
+
+    funding_service
+        .invest(second_investor, invest_request("0xWebhookSecondHash"))
+        .await
+        .expect("Filling investment failed");
+
+    let filled = sender.events("pool_filled");
+    assert_eq!(filled.len(), 1, "Expected exactly one pool_filled webhook");
+
+    let delivery = &filled[0];
+    assert_eq!(delivery.url, "http://webhook.test/vessel");
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(TEST_WEBHOOK_SECRET.as_bytes()).unwrap();
+    mac.update(delivery.body.as_bytes());
+    let signature = delivery
+        .signature
+        .strip_prefix("sha256=")
+        .expect("Signature must be prefixed with sha256=");
+    mac.verify_slice(&hex::decode(signature).expect("Signature must be hex"))
+        .expect("Webhook signature must verify against the body");
+
+    let body: serde_json::Value = serde_json::from_str(&delivery.body).unwrap();
+    assert_eq!(body["event"], "pool_filled");
+    assert_eq!(body["data"]["pool_id"], pool_id.to_string());
+    assert_eq!(body["data"]["invoice_id"], invoice_id.to_string());
+
+    // Cleanup
+    for id in [first_investor, second_investor, mitra_id] {
+        sqlx::query("DELETE FROM transactions WHERE user_id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .ok();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_filled_pool_notifies_exporter_and_mark_read_sets_flag() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (funding_service, invoice_service, _, pool) = setup_funding_service(pool).await;
+    let notifications = NotificationService::new(
+        Arc::new(config),
+        Arc::new(NotificationRepository::new(pool.clone())),
+    );
+
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_inbox_fill@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_inbox_fill@test.com").await;
+
+    // Leave exactly 20M of headroom so the investment reaches the target
+    sqlx::query("UPDATE funding_pools SET funded_amount = target_amount - 20000000 WHERE id = $1")
+        .bind(pool_id)
+        .execute(&pool)
+        .await
+        .expect("Failed to adjust funded amount");
+
+    funding_service
+        .invest(
+            investor_id,
+            InvestRequest {
+                pool_id,
+                amount: 20_000_000.0,
+                tranche: "priority".to_string(),
+                tnc_accepted: true,
+                catalyst_consents: None,
+                tx_hash: "0xInboxFillHash".to_string(),
+            },
+        )
+        .await
+        .expect("Filling investment failed");
+
+    let (unread, total) = notifications
+        .list_for_user(mitra_id, true, 1, 20)
+        .await
+        .expect("Failed to list notifications");
+    assert_eq!(total, 1);
+    assert_eq!(unread[0].kind, "pool_funded");
+    assert!(unread[0].read_at.is_none());
+    let payload: serde_json::Value = serde_json::from_str(&unread[0].payload).unwrap();
+    assert_eq!(payload["pool_id"], pool_id.to_string());
+
+    // The investor is told their investment went through
+    let (investor_inbox, _) = notifications
+        .list_for_user(investor_id, false, 1, 20)
+        .await
+        .expect("Failed to list notifications");
+    assert!(investor_inbox
+        .iter()
+        .any(|n| n.kind == "investment_confirmed"));
+
+    let read = notifications
+        .mark_read(mitra_id, unread[0].id)
+        .await
+        .expect("Failed to mark notification read");
+    assert!(read.read_at.is_some());
+
+    let (_, unread_total) = notifications
+        .list_for_user(mitra_id, true, 1, 20)
+        .await
+        .expect("Failed to list notifications");
+    assert_eq!(unread_total, 0);
+    let (_, all_total) = notifications
+        .list_for_user(mitra_id, false, 1, 20)
+        .await
+        .expect("Failed to list notifications");
+    assert_eq!(all_total, 1);
+
+    // Another user cannot mark someone else's notification
+    assert!(notifications
+        .mark_read(investor_id, unread[0].id)
+        .await
+        .is_err());
+
+    let marked = notifications
+        .mark_all_read(investor_id)
+        .await
+        .expect("Failed to mark all read");
+    assert!(marked >= 1);
+    let (_, investor_unread) = notifications
+        .list_for_user(investor_id, true, 1, 20)
+        .await
+        .expect("Failed to list notifications");
+    assert_eq!(investor_unread, 0);
+
+    // Cleanup
+    for id in [investor_id, mitra_id] {
+        sqlx::query("DELETE FROM transactions WHERE user_id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .ok();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
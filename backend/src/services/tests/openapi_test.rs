@@ -0,0 +1,42 @@
+use actix_web::{http::StatusCode, test, App};
+use utoipa::openapi::PathItemType;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::openapi::ApiDoc;
+
+#[actix_web::test]
+async fn test_openapi_json_is_served_and_describes_invest() {
+    let app = test::init_service(
+        App::new().service(
+            SwaggerUi::new("/swagger-ui/{_:.*}").url("/api-docs/openapi.json", ApiDoc::openapi()),
+        ),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/api-docs/openapi.json")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let body = test::read_body(resp).await;
+    let spec: utoipa::openapi::OpenApi =
+        serde_json::from_slice(&body).expect("OpenAPI JSON should deserialize");
+
+    let invest = spec
+        .paths
+        .get_path_operation("/api/v1/investments", PathItemType::Post)
+        .expect("POST /api/v1/investments should be documented");
+    assert!(invest.request_body.is_some());
+    assert!(invest.responses.responses.contains_key("201"));
+
+    // Success and error envelopes are part of the schema
+    let schemas = &spec.components.expect("components").schemas;
+    assert!(schemas.contains_key("ApiResponseBody"));
+    assert!(schemas.contains_key("InvestmentReceiptApiResponse"));
+    assert!(schemas.contains_key("ErrorResponse"));
+}
@@ -0,0 +1,955 @@
+use chrono::{Duration, NaiveDate, Utc};
+use ethers::signers::{LocalWallet, Signer};
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+use crate::config::InvestmentMode;
+use crate::error::AppError;
+use crate::models::{ImporterPayRequest, ImporterPayment, InvestRequest, PlatformRevenueQuery};
+use crate::repository::{
+    FundingRepository, ImporterPaymentRepository, InvoiceRepository, TransactionRepository,
+    UserRepository,
+};
+use crate::services::blockchain_service::{
+    BlockchainService, TEST_MODE_GAS_PRICE_WEI, TEST_MODE_GAS_USED,
+};
+use crate::services::email_service::{EmailSender, EmailService, RenderedEmail};
+use crate::services::pinata_service::PinataService;
+use crate::services::PaymentService;
+
+use super::auth_test::get_test_config;
+use super::funding_test::{
+    create_investor, create_mitra_and_invoice, setup_funding_service,
+    setup_funding_service_with_config, setup_pool,
+};
+
+pub async fn setup_payment_service(pool: PgPool) -> (Arc<PaymentService>, PgPool) {
+    setup_payment_service_with_sender(pool, None).await
+}
+
+/// Like `setup_payment_service`, optionally delivering emails through `sender`
+async fn setup_payment_service_with_sender(
+    pool: PgPool,
+    sender: Option<Arc<dyn EmailSender>>,
+) -> (Arc<PaymentService>, PgPool) {
+    let mut config = get_test_config();
+    config.skip_blockchain_verification = true; // Enable test mode
+    let config = Arc::new(config);
+
+    let user_repo = Arc::new(UserRepository::new(pool.clone()));
+    let tx_repo = Arc::new(TransactionRepository::new(pool.clone()));
+    let funding_repo = Arc::new(FundingRepository::new(pool.clone()));
+    let invoice_repo = Arc::new(InvoiceRepository::new(pool.clone()));
+    let importer_payment_repo = Arc::new(ImporterPaymentRepository::new(pool.clone()));
+    let pinata_service = Arc::new(PinataService::new(config.clone()));
+
+    let blockchain_service = Arc::new(
+        BlockchainService::new(
+            config.clone(),
+            invoice_repo.clone(),
+            funding_repo.clone(),
+            pinata_service,
+        )
+        .await
+        .expect("Failed to init blockchain service"),
+    );
+
+    let mut email_service = EmailService::new(config.clone());
+    if let Some(sender) = sender {
+        email_service = email_service.with_sender(sender);
+    }
+
+    let service = Arc::new(PaymentService::new(
+        user_repo,
+        tx_repo,
+        funding_repo,
+        invoice_repo,
+        importer_payment_repo,
+        blockchain_service,
+        Arc::new(email_service),
+        config,
+    ));
+
+    (service, pool)
+}
+
+/// Creates a pending importer payment backed by a real invoice and pool
+async fn create_importer_payment(pool: &PgPool, base_email: &str) -> (Uuid, Uuid) {
+    create_importer_payment_for_buyer(pool, base_email, "buyer@test.com", 30).await
+}
+
+/// Like `create_importer_payment`, for a given buyer email and days until due
+async fn create_importer_payment_for_buyer(
+    pool: &PgPool,
+    base_email: &str,
+    buyer_email: &str,
+    due_in_days: i64,
+) -> (Uuid, Uuid) {
+    let (funding_service, invoice_service, _, _) = setup_funding_service(pool.clone()).await;
+
+    let (mitra_id, invoice_id) = create_mitra_and_invoice(pool, &invoice_service, base_email).await;
+    let pool_id = setup_pool(pool, &funding_service, invoice_id).await;
+
+    let payment = ImporterPaymentRepository::new(pool.clone())
+        .create(
+            invoice_id,
+            pool_id,
+            buyer_email,
+            "Buyer PT",
+            Decimal::from(100_000_000),
+            "IDRX",
+            Utc::now() + Duration::days(due_in_days),
+        )
+        .await
+        .expect("Failed to create importer payment");
+
+    (mitra_id, payment.id)
+}
+
+/// Requests a payment nonce for a fresh wallet and signs it.
+/// Returns the wallet address, nonce and signature.
+async fn sign_payment_nonce(
+    service: &PaymentService,
+    payment_id: Uuid,
+) -> (String, String, String) {
+    let wallet = LocalWallet::new(&mut rand::thread_rng());
+    let wallet_address = format!("{:?}", wallet.address());
+
+    let nonce = service
+        .get_payment_nonce(payment_id, &wallet_address)
+        .await
+        .expect("Failed to get payment nonce");
+    let signature = wallet
+        .sign_message(&nonce.message)
+        .await
+        .expect("Failed to sign payment message");
+
+    (wallet_address, nonce.nonce, format!("0x{}", signature))
+}
+
+#[tokio::test]
+async fn test_importer_pay_with_idempotency_key_succeeds() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (service, pool) = setup_payment_service(pool).await;
+    let (mitra_id, payment_id) = create_importer_payment(&pool, "mitra_idem_first@test.com").await;
+    let (wallet_address, nonce, signature) = sign_payment_nonce(&service, payment_id).await;
+
+    let req = ImporterPayRequest {
+        amount: 100_000_000.0,
+        tx_hash: format!("0xImporterPayHash_{}", Uuid::new_v4().simple()),
+        wallet_address,
+        nonce,
+        signature,
+    };
+
+    let result = service
+        .submit_importer_payment(payment_id, &req, Some("idem-key-first"))
+        .await;
+    assert!(result.is_ok(), "First payment should succeed: {:?}", result.err());
+    let payment = result.unwrap();
+    assert_eq!(payment.amount_paid, Decimal::from(100_000_000));
+
+    let stored: (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM idempotency_keys WHERE payment_id = $1 AND idempotency_key = $2",
+    )
+    .bind(payment_id)
+    .bind("idem-key-first")
+    .fetch_one(&pool)
+    .await
+    .expect("Failed to count idempotency keys");
+    assert_eq!(stored.0, 1);
+
+    // Cleanup
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_importer_pay_replay_returns_same_response() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (service, pool) = setup_payment_service(pool).await;
+    let (mitra_id, payment_id) = create_importer_payment(&pool, "mitra_idem_replay@test.com").await;
+    let (wallet_address, nonce, signature) = sign_payment_nonce(&service, payment_id).await;
+
+    let req = ImporterPayRequest {
+        amount: 100_000_000.0,
+        tx_hash: format!("0xImporterPayHash_{}", Uuid::new_v4().simple()),
+        wallet_address,
+        nonce,
+        signature,
+    };
+
+    let first = service
+        .submit_importer_payment(payment_id, &req, Some("idem-key-replay"))
+        .await
+        .expect("First payment should succeed");
+    let replay = service
+        .submit_importer_payment(payment_id, &req, Some("idem-key-replay"))
+        .await
+        .expect("Replay should return the stored response");
+
+    assert_eq!(first.id, replay.id);
+    assert_eq!(first.amount_paid, replay.amount_paid);
+    assert_eq!(first.updated_at, replay.updated_at);
+
+    // The payment must only have been applied once
+    let paid: (Decimal,) = sqlx::query_as("SELECT amount_paid FROM importer_payments WHERE id = $1")
+        .bind(payment_id)
+        .fetch_one(&pool)
+        .await
+        .expect("Failed to fetch payment");
+    assert_eq!(paid.0, Decimal::from(100_000_000));
+
+    // Same key with a different body is rejected
+    let tampered = ImporterPayRequest {
+        amount: 10_000_000.0,
+        tx_hash: "0xOtherHash".to_string(),
+        ..req
+    };
+    let result = service
+        .submit_importer_payment(payment_id, &tampered, Some("idem-key-replay"))
+        .await;
+    assert!(matches!(result, Err(AppError::Conflict(_))));
+
+    // Cleanup
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_importer_pay_idempotency_key_is_reserved_while_processing() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (service, pool) = setup_payment_service(pool).await;
+    let (mitra_id, payment_id) =
+        create_importer_payment(&pool, "mitra_idem_reserved@test.com").await;
+    let (wallet_address, nonce, signature) = sign_payment_nonce(&service, payment_id).await;
+
+    // A failed request releases its key, so it can be retried
+    let underpaid = ImporterPayRequest {
+        amount: 10_000_000.0,
+        tx_hash: format!("0xUnderpaidHash_{}", Uuid::new_v4().simple()),
+        wallet_address,
+        nonce,
+        signature,
+    };
+    let result = service
+        .submit_importer_payment(payment_id, &underpaid, Some("idem-key-reserved"))
+        .await;
+    assert!(matches!(result, Err(AppError::BadRequest(_))));
+
+    let stored: (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM idempotency_keys WHERE payment_id = $1 AND idempotency_key = $2",
+    )
+    .bind(payment_id)
+    .bind("idem-key-reserved")
+    .fetch_one(&pool)
+    .await
+    .expect("Failed to count idempotency keys");
+    assert_eq!(stored.0, 0);
+
+    // A request still holding the key makes a concurrent one with it conflict
+    let (wallet_address, nonce, signature) = sign_payment_nonce(&service, payment_id).await;
+    let req = ImporterPayRequest {
+        amount: 100_000_000.0,
+        tx_hash: format!("0xReservedHash_{}", Uuid::new_v4().simple()),
+        wallet_address,
+        nonce,
+        signature,
+    };
+    let fingerprint = format!(
+        "{}:{}:{}",
+        req.amount,
+        req.tx_hash,
+        req.wallet_address.to_lowercase()
+    );
+    sqlx::query(
+        "INSERT INTO idempotency_keys (payment_id, idempotency_key, request_fingerprint, response_body, status) VALUES ($1, $2, $3, '', 'in_progress')",
+    )
+    .bind(payment_id)
+    .bind("idem-key-reserved")
+    .bind(&fingerprint)
+    .execute(&pool)
+    .await
+    .expect("Failed to reserve key");
+
+    let result = service
+        .submit_importer_payment(payment_id, &req, Some("idem-key-reserved"))
+        .await;
+    assert!(matches!(result, Err(AppError::Conflict(_))));
+
+    let paid: (Decimal,) = sqlx::query_as("SELECT amount_paid FROM importer_payments WHERE id = $1")
+        .bind(payment_id)
+        .fetch_one(&pool)
+        .await
+        .expect("Failed to fetch payment");
+    assert_eq!(paid.0, Decimal::ZERO);
+
+    // Cleanup
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_importer_pay_with_valid_signature_records_payer_wallet() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (service, pool) = setup_payment_service(pool).await;
+    let (mitra_id, payment_id) = create_importer_payment(&pool, "mitra_signed_pay@test.com").await;
+    let (wallet_address, nonce, signature) = sign_payment_nonce(&service, payment_id).await;
+
+    let req = ImporterPayRequest {
+        amount: 100_000_000.0,
+        tx_hash: format!("0xSignedPayHash_{}", Uuid::new_v4().simple()),
+        wallet_address: wallet_address.clone(),
+        nonce,
+        signature,
+    };
+
+    let payment = service
+        .submit_importer_payment(payment_id, &req, None)
+        .await
+        .expect("Signed payment should succeed");
+    assert_eq!(payment.amount_paid, Decimal::from(100_000_000));
+    assert_eq!(payment.payer_wallet, Some(wallet_address.to_lowercase()));
+
+    // The settled payment cannot be paid again
+    let result = service.submit_importer_payment(payment_id, &req, None).await;
+    assert!(matches!(result, Err(AppError::BadRequest(_))));
+
+    // Cleanup
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_importer_pay_rejects_unsigned_or_invalid_signature() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (service, pool) = setup_payment_service(pool).await;
+    let (mitra_id, payment_id) =
+        create_importer_payment(&pool, "mitra_unsigned_pay@test.com").await;
+
+    // No nonce was issued for this wallet
+    let unsigned = ImporterPayRequest {
+        amount: 100_000_000.0,
+        tx_hash: "0xUnsignedPayHash".to_string(),
+        wallet_address: "0x742d35cc6634c0532925a3b844bc9e7595f0beb0".to_string(),
+        nonce: "made-up-nonce".to_string(),
+        signature: String::new(),
+    };
+    let result = service.submit_importer_payment(payment_id, &unsigned, None).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+
+    // A signature over the nonce by a different wallet
+    let (wallet_address, nonce, _) = sign_payment_nonce(&service, payment_id).await;
+    let (_, _, other_signature) = sign_payment_nonce(&service, payment_id).await;
+    let forged = ImporterPayRequest {
+        amount: 100_000_000.0,
+        tx_hash: "0xForgedPayHash".to_string(),
+        wallet_address: wallet_address.clone(),
+        nonce: nonce.clone(),
+        signature: other_signature,
+    };
+    let result = service.submit_importer_payment(payment_id, &forged, None).await;
+    assert!(result.is_err(), "A signature from another wallet must be rejected");
+
+    // Malformed signature
+    let malformed = ImporterPayRequest {
+        signature: "not-a-signature".to_string(),
+        ..forged
+    };
+    let result = service.submit_importer_payment(payment_id, &malformed, None).await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+
+    let paid: (Decimal, Option<String>) =
+        sqlx::query_as("SELECT amount_paid, payer_wallet FROM importer_payments WHERE id = $1")
+            .bind(payment_id)
+            .fetch_one(&pool)
+            .await
+            .expect("Failed to fetch payment");
+    assert_eq!(paid.0, Decimal::ZERO);
+    assert_eq!(paid.1, None);
+
+    // Cleanup
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_importer_pay_transfer_settles_only_one_payment() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (service, pool) = setup_payment_service(pool).await;
+    let (first_mitra_id, first_payment_id) =
+        create_importer_payment(&pool, "mitra_pay_tx_first@test.com").await;
+    let (second_mitra_id, second_payment_id) =
+        create_importer_payment(&pool, "mitra_pay_tx_second@test.com").await;
+    let tx_hash = format!("0xSharedPayHash_{}", Uuid::new_v4().simple());
+
+    let mut results = Vec::new();
+    for payment_id in [first_payment_id, second_payment_id] {
+        let (wallet_address, nonce, signature) = sign_payment_nonce(&service, payment_id).await;
+        let req = ImporterPayRequest {
+            amount: 100_000_000.0,
+            tx_hash: tx_hash.clone(),
+            wallet_address,
+            nonce,
+            signature,
+        };
+        results.push(
+            service
+                .submit_importer_payment(payment_id, &req, None)
+                .await,
+        );
+    }
+    assert!(
+        results[0].is_ok(),
+        "First payment should succeed: {:?}",
+        results[0]
+    );
+    assert!(
+        matches!(results[1], Err(AppError::Conflict(_))),
+        "The same transfer must not settle a second payment: {:?}",
+        results[1]
+    );
+
+    let status: (String,) =
+        sqlx::query_as("SELECT payment_status FROM importer_payments WHERE id = $1")
+            .bind(second_payment_id)
+            .fetch_one(&pool)
+            .await
+            .expect("Failed to fetch payment");
+    assert_eq!(status.0, "pending");
+
+    // Cleanup
+    for mitra_id in [first_mitra_id, second_mitra_id] {
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(mitra_id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+/// Signs a fresh nonce and submits a payment of `amount` without an idempotency key
+async fn pay_signed(
+    service: &PaymentService,
+    payment_id: Uuid,
+    amount: f64,
+) -> Result<ImporterPayment, AppError> {
+    let (wallet_address, nonce, signature) = sign_payment_nonce(service, payment_id).await;
+    let req = ImporterPayRequest {
+        amount,
+        tx_hash: format!("0xAmountCheckHash_{}", Uuid::new_v4().simple()),
+        wallet_address,
+        nonce,
+        signature,
+    };
+    service.submit_importer_payment(payment_id, &req, None).await
+}
+
+#[tokio::test]
+async fn test_importer_pay_exact_amount_settles_payment() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (service, pool) = setup_payment_service(pool).await;
+    let (mitra_id, payment_id) = create_importer_payment(&pool, "mitra_pay_exact@test.com").await;
+
+    let payment = pay_signed(&service, payment_id, 100_000_000.0)
+        .await
+        .expect("Exact payment should succeed");
+    assert_eq!(payment.payment_status, "paid");
+    assert_eq!(payment.amount_paid, Decimal::from(100_000_000));
+    assert_eq!(payment.credit_amount, Decimal::ZERO);
+    assert!(payment.paid_at.is_some());
+
+    // Cleanup
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_importer_overpayment_is_recorded_as_credit() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (service, pool) = setup_payment_service(pool).await;
+    let (mitra_id, payment_id) = create_importer_payment(&pool, "mitra_pay_over@test.com").await;
+
+    let payment = pay_signed(&service, payment_id, 100_000_250.5)
+        .await
+        .expect("Overpayment should be accepted");
+    assert_eq!(payment.payment_status, "paid");
+    assert_eq!(payment.amount_paid, Decimal::new(10_000_025_050, 2));
+    assert_eq!(payment.credit_amount, Decimal::new(25_050, 2));
+
+    // Cleanup
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_importer_underpayment_is_rejected() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (service, pool) = setup_payment_service(pool).await;
+    let (mitra_id, payment_id) = create_importer_payment(&pool, "mitra_pay_under@test.com").await;
+
+    // One cent short of the amount due
+    let result = pay_signed(&service, payment_id, 99_999_999.99).await;
+    assert!(matches!(result, Err(AppError::BadRequest(_))));
+
+    let stored: (Decimal, String) = sqlx::query_as(
+        "SELECT amount_paid, payment_status FROM importer_payments WHERE id = $1",
+    )
+    .bind(payment_id)
+    .fetch_one(&pool)
+    .await
+    .expect("Failed to fetch payment");
+    assert_eq!(stored.0, Decimal::ZERO);
+    assert_eq!(stored.1, "pending");
+
+    // Cleanup
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_platform_revenue_grouped_by_month() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (service, pool) = setup_payment_service(pool).await;
+    crate::database::run_migrations(&pool)
+        .await
+        .expect("Failed to run migrations");
+    let payer_id = create_investor(&pool, "platform_revenue_payer@test.com").await;
+
+    // Fees far in the past so other tests' fees fall outside the range
+    let fees = [
+        ("2001-01-05 10:00:00", "confirmed", 100_000),
+        ("2001-01-20 10:00:00", "confirmed", 250_000),
+        ("2001-01-31 23:30:00", "pending", 50_000),
+        ("2001-02-01 00:30:00", "confirmed", 400_000),
+        ("2001-02-14 12:00:00", "failed", 999_000),
+        ("2001-03-01 09:00:00", "confirmed", 700_000),
+    ];
+    for (created_at, status, amount) in fees {
+        sqlx::query(
+            r#"INSERT INTO transactions (user_id, type, amount, currency, tx_hash, status, created_at)
+               VALUES ($1, 'platform_fee', $2, 'IDRX', $3, $4, $5::timestamp)"#,
+        )
+        .bind(payer_id)
+        .bind(Decimal::from(amount))
+        .bind(format!("0x{}", Uuid::new_v4().simple()))
+        .bind(status)
+        .bind(created_at)
+        .execute(&pool)
+        .await
+        .expect("Failed to seed fee transaction");
+    }
+
+    let summary = service
+        .get_platform_revenue(&PlatformRevenueQuery {
+            from: Some("2001-01-01".to_string()),
+            to: Some("2001-02-28".to_string()),
+            granularity: Some("month".to_string()),
+        })
+        .await
+        .expect("Revenue query failed");
+
+    assert_eq!(summary.confirmed_revenue, Decimal::from(750_000));
+    assert_eq!(summary.pending_revenue, Decimal::from(50_000));
+    assert_eq!(summary.total_revenue, Decimal::from(800_000));
+    assert_eq!(summary.tx_count, 4);
+
+    assert_eq!(summary.series.len(), 2);
+    let january = &summary.series[0];
+    assert_eq!(january.period, NaiveDate::from_ymd_opt(2001, 1, 1).unwrap());
+    assert_eq!(january.confirmed, Decimal::from(350_000));
+    assert_eq!(january.pending, Decimal::from(50_000));
+    assert_eq!(january.tx_count, 3);
+    let february = &summary.series[1];
+    assert_eq!(february.period, NaiveDate::from_ymd_opt(2001, 2, 1).unwrap());
+    assert_eq!(february.confirmed, Decimal::from(400_000));
+    assert_eq!(february.pending, Decimal::ZERO);
+    assert_eq!(february.tx_count, 1);
+
+    let result = service
+        .get_platform_revenue(&PlatformRevenueQuery {
+            from: Some("2001-03-01".to_string()),
+            to: Some("2001-01-01".to_string()),
+            granularity: None,
+        })
+        .await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+
+    // Cleanup
+    sqlx::query("DELETE FROM transactions WHERE user_id = $1")
+        .bind(payer_id)
+        .execute(&pool)
+        .await
+        .ok();
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(payer_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_buyer_access_token_lists_all_payments_for_email() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (service, pool) = setup_payment_service(pool).await;
+
+    let buyer_email = format!("buyer_{}@test.com", Uuid::new_v4().simple());
+    let (mitra_a, later_payment) =
+        create_importer_payment_for_buyer(&pool, "mitra_buyer_a@test.com", &buyer_email, 60).await;
+    let (mitra_b, sooner_payment) =
+        create_importer_payment_for_buyer(&pool, "mitra_buyer_b@test.com", &buyer_email, 15).await;
+    let (mitra_c, _) = create_importer_payment(&pool, "mitra_buyer_other@test.com").await;
+
+    // Email lookup is case-insensitive
+    let token = service
+        .issue_buyer_access_token(&buyer_email.to_uppercase())
+        .await
+        .expect("Failed to issue token")
+        .expect("Buyer with payments should get a token");
+
+    let overview = service
+        .list_buyer_payments(&buyer_email, &token)
+        .await
+        .expect("Valid token should list payments");
+    assert_eq!(overview.buyer_email, buyer_email);
+    let ids: Vec<Uuid> = overview.payments.iter().map(|p| p.payment_id).collect();
+    assert_eq!(ids, vec![sooner_payment, later_payment]);
+    assert_eq!(overview.total_outstanding, Decimal::from(200_000_000));
+
+    // The link works once
+    let result = service.list_buyer_payments(&buyer_email, &token).await;
+    assert!(matches!(result, Err(AppError::Unauthorized(_))));
+
+    // A token is bound to the email it was issued for
+    let token = service
+        .issue_buyer_access_token(&buyer_email)
+        .await
+        .expect("Failed to issue token")
+        .expect("Buyer with payments should get a token");
+    let result = service.list_buyer_payments("buyer@test.com", &token).await;
+    assert!(matches!(result, Err(AppError::Unauthorized(_))));
+    let result = service.list_buyer_payments(&buyer_email, "not-a-token").await;
+    assert!(matches!(result, Err(AppError::Unauthorized(_))));
+
+    // Unknown buyers get no token
+    let none = service
+        .issue_buyer_access_token("nobody_owes_anything@test.com")
+        .await
+        .expect("Unknown email should not error");
+    assert!(none.is_none());
+
+    // Cleanup
+    sqlx::query("DELETE FROM importer_access_tokens WHERE buyer_email = $1")
+        .bind(&buyer_email)
+        .execute(&pool)
+        .await
+        .ok();
+    for mitra_id in [mitra_a, mitra_b, mitra_c] {
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(mitra_id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+#[tokio::test]
+async fn test_disbursement_records_platform_gas() {
+    let mut config = get_test_config();
+    config.investment_mode = InvestmentMode::OnChain;
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (funding_service, invoice_service, _, pool) =
+        setup_funding_service_with_config(pool, config).await;
+    let (payment_service, pool) = setup_payment_service(pool).await;
+
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_gas@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+    let investor_id = create_investor(&pool, "investor_gas@test.com").await;
+
+    funding_service
+        .invest(
+            investor_id,
+            InvestRequest {
+                pool_id,
+                amount: 20_000_000.0,
+                tranche: "priority".to_string(),
+                tnc_accepted: true,
+                catalyst_consents: None,
+                tx_hash: format!("0x{}", Uuid::new_v4().simple()),
+            },
+        )
+        .await
+        .expect("Investment failed");
+    funding_service
+        .disburse_pool(pool_id)
+        .await
+        .expect("Disbursement failed");
+
+    // Test mode reports gas for the disbursement send
+    let (gas_used, gas_price): (Option<i64>, Option<i64>) = sqlx::query_as(
+        "SELECT gas_used, effective_gas_price FROM transactions WHERE invoice_id = $1 AND type = 'advance_payment'",
+    )
+    .bind(invoice_id)
+    .fetch_one(&pool)
+    .await
+    .expect("Disbursement should be recorded");
+    assert_eq!(gas_used, Some(TEST_MODE_GAS_USED as i64));
+    assert_eq!(gas_price, Some(TEST_MODE_GAS_PRICE_WEI as i64));
+
+    // The advance and the withheld fee share one send, so it is counted once
+    let stats = payment_service
+        .get_platform_gas_stats()
+        .await
+        .expect("Gas stats failed");
+    let pool_gas = stats
+        .pools
+        .iter()
+        .find(|p| p.pool_id == pool_id)
+        .expect("Pool should have gas usage");
+    assert_eq!(pool_gas.tx_count, 1);
+    assert_eq!(pool_gas.gas_used, TEST_MODE_GAS_USED as i64);
+    assert_eq!(
+        pool_gas.fee_wei,
+        Decimal::from(TEST_MODE_GAS_USED * TEST_MODE_GAS_PRICE_WEI)
+    );
+    assert!(stats.total_gas_used >= pool_gas.gas_used);
+    assert!(stats.total_fee_wei >= pool_gas.fee_wei);
+
+    // Cleanup
+    for user_id in [mitra_id, investor_id] {
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+/// Captures emails instead of sending them
+#[derive(Default)]
+struct RecordingEmailSender {
+    sent: Mutex<Vec<(String, String, RenderedEmail)>>,
+}
+
+impl RecordingEmailSender {
+    fn sent(&self) -> Vec<(String, String, RenderedEmail)> {
+        self.sent.lock().unwrap().clone()
+    }
+}
+
+impl EmailSender for RecordingEmailSender {
+    fn send<'a>(
+        &'a self,
+        to: &'a str,
+        subject: &'a str,
+        email: &'a RenderedEmail,
+    ) -> futures_util::future::BoxFuture<'a, crate::error::AppResult<()>> {
+        self.sent
+            .lock()
+            .unwrap()
+            .push((to.to_string(), subject.to_string(), email.clone()));
+        Box::pin(async { Ok(()) })
+    }
+}
+
+#[tokio::test]
+async fn test_creating_importer_payment_emails_buyer_once() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let sender = Arc::new(RecordingEmailSender::default());
+    let (service, pool) =
+        setup_payment_service_with_sender(pool, Some(sender.clone() as Arc<dyn EmailSender>)).await;
+    let (funding_service, invoice_service, _, _) = setup_funding_service(pool.clone()).await;
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &invoice_service, "mitra_payment_request@test.com").await;
+    let pool_id = setup_pool(&pool, &funding_service, invoice_id).await;
+
+    let payment = service
+        .create_importer_payment(pool_id)
+        .await
+        .expect("Failed to create importer payment");
+    assert_eq!(payment.payment_status, "pending");
+    assert_eq!(payment.buyer_email, "buyer@test.com");
+
+    let invoice = InvoiceRepository::new(pool.clone())
+        .find_by_id(invoice_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let link = invoice.payment_link.expect("Payment link should be stored");
+    assert!(link.ends_with(&format!("/importer/pay/{}", payment.id)));
+
+    let sent = sender.sent();
+    assert_eq!(sent.len(), 1, "Exactly one payment request should be sent");
+    assert_eq!(sent[0].0, "buyer@test.com");
+    assert!(sent[0].2.text.contains(&link));
+
+    // Billing the same pool again returns the existing payment without emailing
+    let again = service
+        .create_importer_payment(pool_id)
+        .await
+        .expect("Repeat billing should succeed");
+    assert_eq!(again.id, payment.id);
+    assert_eq!(sender.sent().len(), 1);
+
+    // Cleanup
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_overdue_payment_is_flagged_and_buyer_reminded() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let sender = Arc::new(RecordingEmailSender::default());
+    let (service, pool) =
+        setup_payment_service_with_sender(pool, Some(sender.clone() as Arc<dyn EmailSender>)).await;
+    let buyer_email = format!("overdue_{}@test.com", Uuid::new_v4());
+    let (mitra_id, payment_id) =
+        create_importer_payment_for_buyer(&pool, "mitra_overdue@test.com", &buyer_email, -1).await;
+
+    service
+        .mark_overdue_payments()
+        .await
+        .expect("Overdue scan should succeed");
+
+    let payment = ImporterPaymentRepository::new(pool.clone())
+        .find_by_id(payment_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(payment.payment_status, "overdue");
+    let reminders: Vec<_> = sender
+        .sent()
+        .into_iter()
+        .filter(|(to, _, _)| *to == buyer_email)
+        .collect();
+    assert_eq!(reminders.len(), 1);
+
+    // An overdue payment can still be settled
+    service
+        .get_payment_nonce(payment_id, "0x1111111111111111111111111111111111111111")
+        .await
+        .expect("Overdue payment should remain payable");
+
+    // Cleanup
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_second_importer_payment_for_pool_conflicts() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let (service, pool) = setup_payment_service(pool).await;
+    let (mitra_id, payment_id) =
+        create_importer_payment(&pool, "mitra_pay_unique_pool@test.com").await;
+
+    let repo = ImporterPaymentRepository::new(pool.clone());
+    let payment = repo
+        .find_by_id(payment_id)
+        .await
+        .unwrap()
+        .expect("Payment should exist");
+
+    let result = repo
+        .create(
+            payment.invoice_id,
+            payment.pool_id,
+            "buyer@test.com",
+            "Buyer PT",
+            Decimal::from(100_000_000),
+            "IDRX",
+            Utc::now() + Duration::days(30),
+        )
+        .await;
+    assert!(matches!(result, Err(AppError::Conflict(_))));
+
+    // The service hands back the pool's existing payment
+    let existing = service
+        .create_importer_payment(payment.pool_id)
+        .await
+        .expect("Existing payment should be returned");
+    assert_eq!(existing.id, payment_id);
+
+    // Cleanup
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
@@ -0,0 +1,122 @@
+use actix_web::{http::StatusCode, test, web, App, HttpResponse};
+use std::sync::Arc;
+
+use crate::middleware::rate_limit::{RateLimitConfig, RateLimitMiddleware, RateLimiter};
+
+#[actix_web::test]
+async fn test_request_over_limit_gets_429_with_retry_after() {
+    // No Redis pool: falls back to the in-process limiter
+    let limiter = Arc::new(RateLimiter::with_redis(RateLimitConfig::new(3, 60), None));
+    let app = test::init_service(
+        App::new().service(
+            web::resource("/auth/login")
+                .wrap(RateLimitMiddleware::new("auth/login", limiter.clone()))
+                .route(web::post().to(|| async { HttpResponse::Ok().finish() })),
+        ),
+    )
+    .await;
+
+    let login = || {
+        test::TestRequest::post()
+            .uri("/auth/login")
+            .peer_addr("10.0.0.1:40000".parse().unwrap())
+            .to_request()
+    };
+
+    for attempt in 1..=3 {
+        let resp = test::call_service(&app, login()).await;
+        assert_eq!(
+            resp.status(),
+            StatusCode::OK,
+            "request {} should pass",
+            attempt
+        );
+    }
+
+    let resp = test::call_service(&app, login()).await;
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    let retry_after: u64 = resp
+        .headers()
+        .get("Retry-After")
+        .expect("Retry-After header missing")
+        .to_str()
+        .unwrap()
+        .parse()
+        .expect("Retry-After must be seconds");
+    assert!((1..=60).contains(&retry_after));
+
+    // Limits are per client IP
+    let other_client = test::TestRequest::post()
+        .uri("/auth/login")
+        .peer_addr("10.0.0.2:40000".parse().unwrap())
+        .to_request();
+    let resp = test::call_service(&app, other_client).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn test_forwarded_headers_do_not_bypass_limit() {
+    let limiter = Arc::new(RateLimiter::with_redis(RateLimitConfig::new(1, 60), None));
+    let app = test::init_service(
+        App::new().service(
+            web::resource("/auth/login")
+                .wrap(RateLimitMiddleware::new("auth/login", limiter.clone()))
+                .route(web::post().to(|| async { HttpResponse::Ok().finish() })),
+        ),
+    )
+    .await;
+
+    let login = |forwarded_for: &str| {
+        test::TestRequest::post()
+            .uri("/auth/login")
+            .peer_addr("10.0.0.3:40000".parse().unwrap())
+            .insert_header(("X-Forwarded-For", forwarded_for.to_string()))
+            .to_request()
+    };
+
+    let resp = test::call_service(&app, login("203.0.113.1")).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // Same peer with a different spoofed address is still the same client
+    let resp = test::call_service(&app, login("203.0.113.2")).await;
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[actix_web::test]
+async fn test_client_ip_header_is_read_only_from_trusted_proxies() {
+    let config = RateLimitConfig::new(1, 60).with_client_ip_header(
+        Some("Fly-Client-IP".to_string()),
+        vec!["172.16.0.0/12".parse().unwrap()],
+    );
+    let limiter = Arc::new(RateLimiter::with_redis(config, None));
+    let app = test::init_service(
+        App::new().service(
+            web::resource("/auth/login")
+                .wrap(RateLimitMiddleware::new("auth/login", limiter.clone()))
+                .route(web::post().to(|| async { HttpResponse::Ok().finish() })),
+        ),
+    )
+    .await;
+
+    let login = |peer: &str, client_ip: &str| {
+        test::TestRequest::post()
+            .uri("/auth/login")
+            .peer_addr(peer.parse().unwrap())
+            .insert_header(("Fly-Client-IP", client_ip.to_string()))
+            .to_request()
+    };
+
+    // Behind the proxy each client gets its own window
+    let resp = test::call_service(&app, login("172.16.0.5:40000", "203.0.113.1")).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let resp = test::call_service(&app, login("172.16.0.5:40000", "203.0.113.2")).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let resp = test::call_service(&app, login("172.16.0.5:40000", "203.0.113.1")).await;
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    // A direct client cannot pick its key through the header
+    let resp = test::call_service(&app, login("198.51.100.9:40000", "203.0.113.3")).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let resp = test::call_service(&app, login("198.51.100.9:40000", "203.0.113.4")).await;
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+}
@@ -0,0 +1,103 @@
+use actix_web::dev::Service;
+use actix_web::{test, web, App, HttpResponse};
+
+use crate::error::{AppError, AppResult};
+use crate::middleware::{RequestIdMiddleware, REQUEST_ID_HEADER};
+
+async fn missing_pool() -> AppResult<HttpResponse> {
+    Err(AppError::NotFound("Pool not found".to_string()))
+}
+
+#[actix_web::test]
+async fn test_client_request_id_echoed_in_header_and_error() {
+    let app = test::init_service(
+        App::new()
+            .wrap(RequestIdMiddleware)
+            .route("/pools/missing", web::get().to(missing_pool)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/pools/missing")
+        .insert_header((REQUEST_ID_HEADER, "client-req-42"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), 404);
+    assert_eq!(
+        resp.headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|v| v.to_str().ok()),
+        Some("client-req-42")
+    );
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"]["code"], "NOT_FOUND");
+    assert_eq!(body["error"]["request_id"], "client-req-42");
+}
+
+#[actix_web::test]
+async fn test_request_id_generated_when_missing_or_malformed() {
+    let app = test::init_service(
+        App::new()
+            .wrap(RequestIdMiddleware)
+            .route("/pools/missing", web::get().to(missing_pool)),
+    )
+    .await;
+
+    for supplied in [None, Some("not a valid id\t")] {
+        let mut req = test::TestRequest::get().uri("/pools/missing");
+        if let Some(id) = supplied {
+            req = req.insert_header((REQUEST_ID_HEADER, id));
+        }
+        let resp = test::call_service(&app, req.to_request()).await;
+
+        let header = resp
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+            .expect("Request id header missing");
+        assert!(uuid::Uuid::parse_str(&header).is_ok(), "{}", header);
+
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["error"]["request_id"], header.as_str());
+    }
+}
+
+#[actix_web::test]
+async fn test_request_id_in_error_from_inner_middleware() {
+    let app = test::init_service(
+        App::new().wrap(RequestIdMiddleware).service(
+            web::scope("/pools")
+                // Rejects like the auth middleware, before any handler runs
+                .wrap_fn(|req, srv| {
+                    let authorized = req.headers().contains_key("authorization");
+                    let fut = srv.call(req);
+                    async move {
+                        if !authorized {
+                            return Err(AppError::Unauthorized("Missing token".to_string()).into());
+                        }
+                        fut.await
+                    }
+                })
+                .route("/missing", web::get().to(missing_pool)),
+        ),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/pools/missing")
+        .insert_header((REQUEST_ID_HEADER, "client-req-43"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), 401);
+    assert_eq!(
+        resp.headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|v| v.to_str().ok()),
+        Some("client-req-43")
+    );
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"]["request_id"], "client-req-43");
+}
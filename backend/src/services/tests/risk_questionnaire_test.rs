@@ -0,0 +1,245 @@
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::models::{RiskAnswer, RiskOption, RiskQuestion, SubmitRiskQuestionnaireRequest};
+use crate::repository::RiskQuestionnaireRepository;
+use crate::services::RiskQuestionnaireService;
+
+use super::auth_test::get_test_config;
+
+async fn setup_rq_service() -> (RiskQuestionnaireService, PgPool) {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    crate::database::run_migrations(&pool)
+        .await
+        .expect("Failed to run migrations");
+
+    let rq_repo = Arc::new(RiskQuestionnaireRepository::new(pool.clone()));
+    (RiskQuestionnaireService::new(rq_repo), pool)
+}
+
+async fn create_user(pool: &PgPool) -> Uuid {
+    let user_id = Uuid::new_v4();
+    sqlx::query(
+        r#"INSERT INTO users (id, email, username, password_hash, role, member_status, is_verified, is_active)
+           VALUES ($1, $2, $3, 'hash', 'investor', 'calon_anggota_pendana', true, true)"#,
+    )
+    .bind(user_id)
+    .bind(format!("{}_rq@test.com", user_id.simple()))
+    .bind(format!("rq_{}", user_id.simple()))
+    .execute(pool)
+    .await
+    .expect("Failed to create user");
+    user_id
+}
+
+fn answers(q1: i32, q2: i32, q3: i32) -> SubmitRiskQuestionnaireRequest {
+    SubmitRiskQuestionnaireRequest {
+        version: None,
+        answers: vec![],
+        q1_answer: Some(q1),
+        q2_answer: Some(q2),
+        q3_answer: Some(q3),
+        selected_tier: "priority".to_string(),
+    }
+}
+
+fn answer(question_id: i32, value: i32) -> RiskAnswer {
+    RiskAnswer { question_id, value }
+}
+
+#[tokio::test]
+async fn test_answers_not_found_before_first_submission() {
+    let (rq_service, pool) = setup_rq_service().await;
+    let user_id = create_user(&pool).await;
+
+    let result = rq_service.get_answers(user_id).await;
+    assert!(matches!(result, Err(AppError::NotFound(_))), "{:?}", result);
+
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_first_submission_and_answer_review() {
+    let (rq_service, pool) = setup_rq_service().await;
+    let user_id = create_user(&pool).await;
+
+    let submitted = rq_service
+        .submit(user_id, answers(3, 1, 1))
+        .await
+        .expect("Submission failed");
+    assert!(submitted.catalyst_unlocked);
+
+    let review = rq_service
+        .get_answers(user_id)
+        .await
+        .expect("Answers not found");
+    assert_eq!(
+        review.answers,
+        vec![answer(1, 3), answer(2, 1), answer(3, 1)]
+    );
+    assert!(review.catalyst_unlocked);
+    assert_eq!(review.selected_tier, "priority");
+
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_resubmission_overwrites_answers_and_eligibility() {
+    let (rq_service, pool) = setup_rq_service().await;
+    let user_id = create_user(&pool).await;
+
+    let first = rq_service
+        .submit(user_id, answers(1, 2, 2))
+        .await
+        .expect("First submission failed");
+    assert!(!first.catalyst_unlocked);
+
+    // Re-taking with qualifying answers unlocks catalyst on the same row
+    let retake = rq_service
+        .submit(user_id, answers(2, 1, 1))
+        .await
+        .expect("Re-submission failed");
+    assert_eq!(retake.id, first.id);
+    assert!(retake.catalyst_unlocked);
+    assert!(retake.completed_at >= first.completed_at);
+
+    let review = rq_service
+        .get_answers(user_id)
+        .await
+        .expect("Answers not found");
+    assert_eq!(review.answers[0], answer(1, 2));
+    assert!(review.catalyst_unlocked);
+
+    // And a later re-take can lock it again
+    let relocked = rq_service
+        .submit(user_id, answers(2, 2, 1))
+        .await
+        .expect("Re-submission failed");
+    assert!(!relocked.catalyst_unlocked);
+
+    let rows: (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM risk_questionnaires WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .expect("Failed to count rows");
+    assert_eq!(rows.0, 1);
+
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_active_version_change_keeps_old_answers_valid() {
+    let (rq_service, pool) = setup_rq_service().await;
+    let old_user = create_user(&pool).await;
+    let new_user = create_user(&pool).await;
+
+    let previous = rq_service.get_questions().await.expect("No active set");
+    rq_service
+        .submit(old_user, answers(2, 1, 1))
+        .await
+        .expect("Submission failed");
+
+    // The next version rewords Q1 and adds a fourth option; the answer values
+    // of the earlier version keep their meaning so concurrent tests still pass
+    let mut questions = previous.questions.clone();
+    questions[0].question = "Berapa lama Anda telah berinvestasi?".to_string();
+    questions[0].options.push(RiskOption {
+        value: 4,
+        label: "Lebih dari 5 tahun".to_string(),
+        unlocks_catalyst: true,
+        score: 2,
+    });
+    let published = rq_service
+        .publish_questions(previous.catalyst_min_score, questions)
+        .await
+        .expect("Publish failed");
+    assert!(published.version > previous.version);
+
+    let active = rq_service.get_questions().await.expect("No active set");
+    assert_eq!(active.version, published.version);
+    assert_eq!(
+        active.questions[0].question,
+        "Berapa lama Anda telah berinvestasi?"
+    );
+    assert_eq!(active.questions[0].options.len(), 4);
+
+    // Answers for the new option are accepted under the new version only
+    let submitted = rq_service
+        .submit(
+            new_user,
+            SubmitRiskQuestionnaireRequest {
+                version: Some(published.version),
+                answers: vec![answer(1, 4), answer(2, 1), answer(3, 1)],
+                q1_answer: None,
+                q2_answer: None,
+                q3_answer: None,
+                selected_tier: "catalyst".to_string(),
+            },
+        )
+        .await
+        .expect("Submission failed");
+    assert_eq!(submitted.question_version, published.version);
+    assert!(submitted.catalyst_unlocked);
+
+    let stale = rq_service
+        .submit(
+            new_user,
+            SubmitRiskQuestionnaireRequest {
+                version: Some(previous.version),
+                ..answers(2, 1, 1)
+            },
+        )
+        .await;
+    assert!(
+        matches!(stale, Err(AppError::ValidationError(_))),
+        "{:?}",
+        stale
+    );
+
+    // The earlier answers are still reviewed against their own version
+    let review = rq_service
+        .get_answers(old_user)
+        .await
+        .expect("Answers not found");
+    assert_eq!(review.question_version, previous.version);
+    assert_eq!(
+        review.answers,
+        vec![answer(1, 2), answer(2, 1), answer(3, 1)]
+    );
+    assert!(review.catalyst_unlocked);
+
+    rq_service
+        .activate_version(previous.version)
+        .await
+        .expect("Failed to restore active version");
+    assert_eq!(
+        rq_service.get_questions().await.unwrap().version,
+        previous.version
+    );
+
+    for user_id in [old_user, new_user] {
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
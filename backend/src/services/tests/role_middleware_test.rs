@@ -0,0 +1,91 @@
+use actix_web::{http::StatusCode, test, web, App, HttpResponse};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::middleware::{InvestorOnlyMiddleware, MitraOnlyMiddleware, OptionalAuthMiddleware};
+use crate::models::Role;
+use crate::utils::JwtManager;
+
+use super::auth_test::get_test_config;
+
+async fn ok() -> HttpResponse {
+    HttpResponse::Ok().finish()
+}
+
+fn token_for(role: Role) -> String {
+    let config = get_test_config();
+    JwtManager::new(&config.jwt_secret, 1, 24)
+        .generate_access_token(Uuid::new_v4(), "role@test.com", role, 0)
+        .expect("Failed to issue token")
+}
+
+/// The role-scoped groups as mounted in main, with handlers stubbed out
+macro_rules! role_scoped_app {
+    () => {
+        test::init_service(
+            App::new()
+                .wrap(OptionalAuthMiddleware::new(Arc::new(get_test_config())))
+                .service(
+                    web::scope("/mitra")
+                        .wrap(MitraOnlyMiddleware)
+                        .route("/dashboard", web::get().to(ok)),
+                )
+                .service(
+                    web::scope("/investments")
+                        .wrap(InvestorOnlyMiddleware)
+                        .route("/portfolio", web::get().to(ok)),
+                ),
+        )
+        .await
+    };
+}
+
+fn get_as(role: Role, uri: &str) -> test::TestRequest {
+    test::TestRequest::get()
+        .uri(uri)
+        .insert_header(("Authorization", format!("Bearer {}", token_for(role))))
+}
+
+#[actix_web::test]
+async fn test_investor_rejected_from_mitra_dashboard() {
+    let app = role_scoped_app!();
+
+    let resp = test::call_service(
+        &app,
+        get_as(Role::Investor, "/mitra/dashboard").to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    let resp = test::call_service(&app, get_as(Role::Mitra, "/mitra/dashboard").to_request()).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn test_mitra_rejected_from_investor_portfolio() {
+    let app = role_scoped_app!();
+
+    let resp = test::call_service(
+        &app,
+        get_as(Role::Mitra, "/investments/portfolio").to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    let resp = test::call_service(
+        &app,
+        get_as(Role::Investor, "/investments/portfolio").to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn test_role_scoped_groups_require_authentication() {
+    let app = role_scoped_app!();
+
+    for uri in ["/mitra/dashboard", "/investments/portfolio"] {
+        let resp = test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,111 @@
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{TransactionStatus, TransactionType, TxFilter};
+use crate::repository::TransactionRepository;
+
+use super::auth_test::get_test_config;
+use super::funding_test::create_investor;
+
+async fn setup_tx_repo() -> (TransactionRepository, PgPool) {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    crate::database::run_migrations(&pool)
+        .await
+        .expect("Failed to run migrations");
+
+    (TransactionRepository::new(pool.clone()), pool)
+}
+
+/// Record an on-chain transaction of `tx_type` for the user and move it to `status`
+async fn record(
+    tx_repo: &TransactionRepository,
+    user_id: Uuid,
+    tx_type: &str,
+    status: &str,
+) -> Uuid {
+    let tx_hash = format!("0x{}", Uuid::new_v4().simple());
+    let tx = tx_repo
+        .create(
+            None,
+            Some(user_id),
+            tx_type,
+            Decimal::from(1_000_000),
+            "IDRX",
+            Some(&tx_hash),
+            None,
+            None,
+            None,
+        )
+        .await
+        .expect("Failed to record transaction");
+    tx_repo
+        .update_status(tx.id, status)
+        .await
+        .expect("Failed to update status");
+    tx.id
+}
+
+#[tokio::test]
+async fn test_find_combines_type_and_status_filters() {
+    let (tx_repo, pool) = setup_tx_repo().await;
+    let user_id = create_investor(&pool, "investor_tx_filter@test.com").await;
+
+    let confirmed_investment = record(&tx_repo, user_id, "investment", "confirmed").await;
+    record(&tx_repo, user_id, "investment", "pending").await;
+    record(&tx_repo, user_id, "investor_return", "confirmed").await;
+
+    let filter = TxFilter {
+        user_id: Some(user_id),
+        tx_type: Some(TransactionType::Investment),
+        status: Some(TransactionStatus::Confirmed),
+        ..Default::default()
+    };
+    let (txs, total) = tx_repo
+        .find(&filter, 1, 10, None)
+        .await
+        .expect("Find failed");
+    assert_eq!(total, 1);
+    assert_eq!(txs.len(), 1);
+    assert_eq!(txs[0].id, confirmed_investment);
+
+    // Type alone matches both investments, status alone both confirmed rows
+    let by_type = TxFilter {
+        status: None,
+        ..filter.clone()
+    };
+    let (txs, total) = tx_repo.find(&by_type, 1, 10, None).await.unwrap();
+    assert_eq!(total, 2);
+    assert!(txs.iter().all(|t| t.tx_type == "investment"));
+
+    let by_status = TxFilter {
+        tx_type: None,
+        ..filter.clone()
+    };
+    let (txs, total) = tx_repo.find(&by_status, 1, 10, None).await.unwrap();
+    assert_eq!(total, 2);
+    assert!(txs.iter().all(|t| t.status == "confirmed"));
+
+    // A window ending before the transactions were recorded matches nothing
+    let before = TxFilter {
+        created_to: Some(txs[0].created_at - chrono::Duration::days(1)),
+        ..filter
+    };
+    let (txs, total) = tx_repo.find(&before, 1, 10, None).await.unwrap();
+    assert_eq!(total, 0);
+    assert!(txs.is_empty());
+
+    sqlx::query("DELETE FROM transactions WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .ok();
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
@@ -0,0 +1,80 @@
+use sqlx::PgPool;
+use std::str::FromStr;
+
+use crate::error::AppError;
+use crate::models::{MemberStatus, Role};
+
+use super::auth_test::get_test_config;
+
+async fn connect() -> PgPool {
+    let config = get_test_config();
+    PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect")
+}
+
+#[tokio::test]
+async fn test_role_decodes_every_db_value() {
+    let pool = connect().await;
+
+    for (value, expected) in [
+        ("investor", Role::Investor),
+        ("admin", Role::Admin),
+        ("mitra", Role::Mitra),
+        ("exporter", Role::Exporter),
+    ] {
+        let (role,): (Role,) = sqlx::query_as("SELECT $1::varchar")
+            .bind(value)
+            .fetch_one(&pool)
+            .await
+            .expect("Role should decode");
+        assert_eq!(role, expected);
+        assert_eq!(role.to_string(), value);
+        assert_eq!(Role::from_str(value).unwrap(), expected);
+    }
+}
+
+#[tokio::test]
+async fn test_member_status_decodes_every_db_value() {
+    let pool = connect().await;
+
+    for (value, expected) in [
+        ("calon_anggota_pendana", MemberStatus::CalonAnggotaPendana),
+        ("calon_anggota_mitra", MemberStatus::CalonAnggotaMitra),
+        ("member_mitra", MemberStatus::MemberMitra),
+        ("admin", MemberStatus::Admin),
+    ] {
+        let (status,): (MemberStatus,) = sqlx::query_as("SELECT $1::varchar")
+            .bind(value)
+            .fetch_one(&pool)
+            .await
+            .expect("Member status should decode");
+        assert_eq!(status, expected);
+        assert_eq!(status.to_string(), value);
+        assert_eq!(MemberStatus::from_str(value).unwrap(), expected);
+    }
+}
+
+#[tokio::test]
+async fn test_unknown_values_fail_to_decode() {
+    let pool = connect().await;
+
+    let role = sqlx::query_as::<_, (Role,)>("SELECT 'superuser'::varchar")
+        .fetch_one(&pool)
+        .await;
+    assert!(role.is_err());
+
+    let status = sqlx::query_as::<_, (MemberStatus,)>("SELECT 'individual'::varchar")
+        .fetch_one(&pool)
+        .await;
+    assert!(status.is_err());
+
+    assert!(matches!(
+        Role::from_str("superuser"),
+        Err(AppError::ValidationError(_))
+    ));
+    assert!(matches!(
+        MemberStatus::from_str("calon_mitra"),
+        Err(AppError::ValidationError(_))
+    ));
+}
@@ -0,0 +1,540 @@
+use actix_web::{http::StatusCode, test, web, App};
+use ethers::signers::{LocalWallet, Signer};
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::handlers::{self, AppState};
+use crate::middleware::OptionalAuthMiddleware;
+use crate::models::Role;
+use crate::repository::{
+    ActivityRepository, CurrencyRepository, FundingRepository, ImporterPaymentRepository,
+    InvoiceRepository, MitraRepository, NotificationRepository, OtpRepository,
+    RiskQuestionnaireRepository, TransactionRepository, UserRepository,
+};
+use crate::services::{
+    ActivityLogger, AuthService, BlockchainService, CurrencyService, EmailService, EscrowService,
+    FundingService, InvoiceService, KycService, MitraService, NotificationService, OtpService,
+    PaymentService, PinataService, RiskQuestionnaireService, UserService,
+};
+use crate::utils::{mask_email, mask_wallet, JwtManager, Masked, Metrics};
+
+use super::auth_test::get_test_config;
+use super::funding_test::{create_investor, create_mitra_and_invoice, setup_pool};
+
+async fn setup_user_service() -> (Arc<UserService>, PgPool) {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    crate::database::run_migrations(&pool)
+        .await
+        .expect("Failed to run migrations");
+
+    let user_service = UserService::new(
+        Arc::new(UserRepository::new(pool.clone())),
+        Arc::new(MitraRepository::new(pool.clone())),
+        Arc::new(FundingRepository::new(pool.clone())),
+        Arc::new(InvoiceRepository::new(pool.clone())),
+    );
+    (Arc::new(user_service), pool)
+}
+
+/// Investor with no profile, KYC or wallet, but flagged as completed
+async fn create_bare_investor(pool: &PgPool) -> Uuid {
+    let user_id = Uuid::new_v4();
+    sqlx::query(
+        r#"INSERT INTO users (id, email, username, password_hash, role, member_status, is_verified, is_active, profile_completed)
+           VALUES ($1, $2, $3, 'hash', 'investor', 'calon_anggota_pendana', true, true, true)"#,
+    )
+    .bind(user_id)
+    .bind(format!("{}_profile@test.com", user_id.simple()))
+    .bind(format!("investor_{}", user_id.simple()))
+    .execute(pool)
+    .await
+    .expect("Failed to create investor");
+
+    user_id
+}
+
+async fn profile_completed(pool: &PgPool, user_id: Uuid) -> bool {
+    let row: (bool,) = sqlx::query_as("SELECT profile_completed FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+        .expect("Failed to load user");
+    row.0
+}
+
+#[tokio::test]
+async fn test_empty_profile_lists_every_required_field() {
+    let (user_service, pool) = setup_user_service().await;
+    let user_id = create_bare_investor(&pool).await;
+
+    let completeness = user_service
+        .profile_completeness(user_id)
+        .await
+        .expect("Failed to score profile");
+
+    assert_eq!(completeness.percentage, 0);
+    assert_eq!(
+        completeness.missing_fields,
+        vec!["full_name", "phone", "country", "kyc", "wallet_address"]
+    );
+    assert_eq!(completeness.missing_fields, completeness.required_fields);
+    // Scoring is read-only, so a flag set elsewhere (e.g. by mitra approval) stays
+    assert!(profile_completed(&pool, user_id).await);
+
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_complete_profile_scores_full_and_marks_completed() {
+    let (user_service, pool) = setup_user_service().await;
+    let user_id = create_bare_investor(&pool).await;
+    sqlx::query("UPDATE users SET profile_completed = false, wallet_address = $2 WHERE id = $1")
+        .bind(user_id)
+        .bind(format!("0xProfile_{}", user_id.simple()))
+        .execute(&pool)
+        .await
+        .expect("Failed to set wallet");
+    sqlx::query(
+        "INSERT INTO user_profiles (user_id, full_name, phone, country) VALUES ($1, 'Test Investor', '+6281234567890', 'Indonesia')",
+    )
+    .bind(user_id)
+    .execute(&pool)
+    .await
+    .expect("Failed to create profile");
+    // Submitted KYC counts while it waits for an admin
+    sqlx::query(
+        r#"INSERT INTO kyc_verifications (user_id, nik, full_name, ktp_photo_url, selfie_url, status)
+           VALUES ($1, '3171234567890001', 'Test Investor', 'ipfs://ktp', 'ipfs://selfie', 'pending')"#,
+    )
+    .bind(user_id)
+    .execute(&pool)
+    .await
+    .expect("Failed to submit KYC");
+
+    let completeness = user_service
+        .profile_completeness(user_id)
+        .await
+        .expect("Failed to score profile");
+
+    assert_eq!(completeness.percentage, 100);
+    assert!(completeness.missing_fields.is_empty());
+    assert_eq!(completeness.required_fields.len(), 5);
+    // Reading the score does not write the flag
+    assert!(!profile_completed(&pool, user_id).await);
+
+    user_service
+        .mark_profile_completed_if_complete(user_id)
+        .await
+        .expect("Failed to mark profile");
+    assert!(profile_completed(&pool, user_id).await);
+
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn test_admin_user_detail_includes_mitra_application() {
+    let (user_service, pool) = setup_user_service().await;
+    let user_id = create_bare_investor(&pool).await;
+    let application = MitraRepository::new(pool.clone())
+        .create(
+            user_id,
+            "PT Detail Export",
+            "PT",
+            "01.234.567.8-901.000",
+            "1m_5m",
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .await
+        .expect("Failed to create mitra application");
+
+    let detail = user_service
+        .get_admin_user_detail(user_id)
+        .await
+        .expect("Failed to load user detail");
+
+    assert_eq!(detail.user.id, user_id);
+    let mitra_application = detail
+        .mitra_application
+        .expect("Mitra application should be included");
+    assert_eq!(mitra_application.id, application.id);
+    assert_eq!(mitra_application.company_name, "PT Detail Export");
+    assert!(detail.profile.is_none());
+    assert!(detail.kyc.is_none());
+    assert!(detail.bank_accounts.is_empty());
+    assert_eq!(detail.investment_stats.active_count, 0);
+    assert_eq!(detail.invoice_stats.total_count, 0);
+
+    // Unknown ids are not found
+    let result = user_service.get_admin_user_detail(Uuid::new_v4()).await;
+    assert!(matches!(result, Err(AppError::NotFound(_))));
+
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .ok();
+}
+
+/// Application state wired like `main`, without Redis or background tasks
+pub async fn setup_app_state(pool: PgPool) -> web::Data<AppState> {
+    crate::database::run_migrations(&pool)
+        .await
+        .expect("Failed to run migrations");
+    let config = Arc::new(get_test_config());
+
+    let user_repo = Arc::new(UserRepository::new(pool.clone()));
+    let invoice_repo = Arc::new(InvoiceRepository::new(pool.clone()));
+    let funding_repo = Arc::new(FundingRepository::new(pool.clone()));
+    let tx_repo = Arc::new(TransactionRepository::new(pool.clone()));
+    let otp_repo = Arc::new(OtpRepository::new(pool.clone()));
+    let mitra_repo = Arc::new(MitraRepository::new(pool.clone()));
+    let importer_payment_repo = Arc::new(ImporterPaymentRepository::new(pool.clone()));
+    let rq_repo = Arc::new(RiskQuestionnaireRepository::new(pool.clone()));
+
+    let jwt_manager = Arc::new(JwtManager::new(
+        &config.jwt_secret,
+        config.jwt_expiry_hours,
+        config.jwt_refresh_expiry_hours,
+    ));
+    let metrics = Arc::new(Metrics::new());
+
+    let pinata_service = Arc::new(PinataService::new(config.clone()));
+    let email_service = Arc::new(EmailService::new(config.clone()));
+    let blockchain_service = Arc::new(
+        BlockchainService::new(
+            config.clone(),
+            invoice_repo.clone(),
+            funding_repo.clone(),
+            pinata_service.clone(),
+        )
+        .await
+        .expect("Failed to init blockchain service"),
+    );
+    let escrow_service = Arc::new(EscrowService::new());
+    let otp_service = Arc::new(OtpService::new(
+        otp_repo.clone(),
+        email_service.clone(),
+        config.clone(),
+        jwt_manager.clone(),
+    ));
+    let activity_logger = Arc::new(ActivityLogger::new(Arc::new(ActivityRepository::new(
+        pool.clone(),
+    ))));
+    let auth_service = Arc::new(AuthService::new(
+        user_repo.clone(),
+        mitra_repo.clone(),
+        jwt_manager.clone(),
+        otp_service.clone(),
+        config.clone(),
+        blockchain_service.clone(),
+        activity_logger.clone(),
+    ));
+    let mitra_service = Arc::new(MitraService::new(
+        mitra_repo.clone(),
+        user_repo.clone(),
+        email_service.clone(),
+        pinata_service.clone(),
+    ));
+    let invoice_service = Arc::new(InvoiceService::new(
+        invoice_repo.clone(),
+        funding_repo.clone(),
+        user_repo.clone(),
+        mitra_repo.clone(),
+        pinata_service.clone(),
+        config.clone(),
+    ));
+    let kyc_service = Arc::new(KycService::new(user_repo.clone(), email_service.clone()));
+    let notification_service = Arc::new(NotificationService::new(
+        config.clone(),
+        Arc::new(NotificationRepository::new(pool.clone())),
+    ));
+    let funding_service = Arc::new(FundingService::new(
+        funding_repo.clone(),
+        invoice_repo.clone(),
+        tx_repo.clone(),
+        user_repo.clone(),
+        rq_repo.clone(),
+        email_service.clone(),
+        escrow_service.clone(),
+        blockchain_service.clone(),
+        notification_service.clone(),
+        activity_logger.clone(),
+        config.clone(),
+    ));
+    let payment_service = Arc::new(PaymentService::new(
+        user_repo.clone(),
+        tx_repo.clone(),
+        funding_repo.clone(),
+        invoice_repo.clone(),
+        importer_payment_repo.clone(),
+        blockchain_service.clone(),
+        email_service.clone(),
+        config.clone(),
+    ));
+    let rq_service = Arc::new(RiskQuestionnaireService::new(rq_repo.clone()));
+    let user_service = Arc::new(UserService::new(
+        user_repo.clone(),
+        mitra_repo.clone(),
+        funding_repo.clone(),
+        invoice_repo.clone(),
+    ));
+    let currency_service = Arc::new(CurrencyService::new(
+        Arc::new(CurrencyRepository::new(pool.clone())),
+        config.clone(),
+    ));
+
+    web::Data::new(AppState {
+        config,
+        db_pool: pool,
+        redis_pool: None,
+        jwt_manager,
+        user_repo,
+        invoice_repo,
+        funding_repo,
+        tx_repo,
+        otp_repo,
+        mitra_repo,
+        importer_payment_repo,
+        rq_repo,
+        auth_service,
+        otp_service,
+        mitra_service,
+        invoice_service,
+        kyc_service,
+        funding_service,
+        payment_service,
+        rq_service,
+        currency_service,
+        blockchain_service,
+        pinata_service,
+        email_service,
+        escrow_service,
+        activity_logger,
+        notification_service,
+        user_service,
+        metrics,
+    })
+}
+
+/// Mitra account without a wallet, as created by email registration
+async fn create_walletless_mitra(pool: &PgPool) -> Uuid {
+    let user_id = Uuid::new_v4();
+    sqlx::query(
+        r#"INSERT INTO users (id, email, username, password_hash, role, member_status, is_verified, is_active)
+           VALUES ($1, $2, $3, 'hash', 'mitra', 'member_mitra', true, true)"#,
+    )
+    .bind(user_id)
+    .bind(format!("{}_connect@test.com", user_id.simple()))
+    .bind(format!("mitra_{}", user_id.simple()))
+    .execute(pool)
+    .await
+    .expect("Failed to create mitra");
+    user_id
+}
+
+/// `POST /user/wallet/connect` request for `user_id`, signed by `wallet`
+async fn connect_wallet_request(
+    state: &AppState,
+    user_id: Uuid,
+    wallet: &LocalWallet,
+) -> test::TestRequest {
+    let wallet_address = format!("{:?}", wallet.address());
+    let nonce = state
+        .auth_service
+        .get_wallet_nonce(&wallet_address)
+        .await
+        .expect("Failed to get wallet nonce");
+    let signature = wallet
+        .sign_message(&nonce.message)
+        .await
+        .expect("Failed to sign nonce message");
+    let token = state
+        .jwt_manager
+        .generate_access_token(user_id, "connect@test.com", Role::Mitra, 0)
+        .expect("Failed to issue token");
+
+    test::TestRequest::post()
+        .uri("/user/wallet/connect")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(serde_json::json!({
+            "wallet_address": wallet_address,
+            "signature": format!("0x{}", signature),
+            "message": nonce.message,
+            "nonce": nonce.nonce,
+        }))
+}
+
+#[actix_web::test]
+async fn test_connect_wallet_handler() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let state = setup_app_state(pool.clone()).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(state.clone())
+            .wrap(OptionalAuthMiddleware::new(state.config.clone()))
+            .route(
+                "/user/wallet/connect",
+                web::post().to(handlers::user::connect_wallet),
+            ),
+    )
+    .await;
+
+    let first_id = create_walletless_mitra(&pool).await;
+    let second_id = create_walletless_mitra(&pool).await;
+    let wallet = LocalWallet::new(&mut rand::thread_rng());
+
+    let resp = test::call_service(
+        &app,
+        connect_wallet_request(&state, first_id, &wallet)
+            .await
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(
+        body["data"]["wallet_address"],
+        format!("{:?}", wallet.address()).to_lowercase()
+    );
+
+    // The same wallet cannot be connected to a second account
+    let resp = test::call_service(
+        &app,
+        connect_wallet_request(&state, second_id, &wallet)
+            .await
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+    // A malformed address fails validation before any signature check
+    let resp = test::call_service(
+        &app,
+        connect_wallet_request(&state, second_id, &wallet)
+            .await
+            .set_json(serde_json::json!({
+                "wallet_address": "0x1234",
+                "signature": "0x",
+                "message": "",
+                "nonce": "",
+            }))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    for user_id in [first_id, second_id] {
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
+
+#[actix_web::test]
+async fn test_own_profile_is_unmasked_and_marketplace_is_masked() {
+    let config = get_test_config();
+    let pool = PgPool::connect(&config.database_url)
+        .await
+        .expect("Failed to connect");
+    let state = setup_app_state(pool.clone()).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(state.clone())
+            .wrap(OptionalAuthMiddleware::new(state.config.clone()))
+            .route("/user/profile", web::get().to(handlers::user::get_profile))
+            .route(
+                "/marketplace/{id}/detail",
+                web::get().to(handlers::funding::get_pool_detail),
+            ),
+    )
+    .await;
+
+    let investor_id = create_investor(&pool, "investor_masking@test.com").await;
+    let investor = state
+        .user_repo
+        .find_by_id(investor_id)
+        .await
+        .expect("Failed to load investor")
+        .expect("Investor not found");
+    let (mitra_id, invoice_id) =
+        create_mitra_and_invoice(&pool, &state.invoice_service, "mitra_masking@test.com").await;
+    let pool_id = setup_pool(&pool, &state.funding_service, invoice_id).await;
+
+    // The investor's own profile carries the full email and wallet
+    let token = state
+        .jwt_manager
+        .generate_access_token(investor_id, "investor_masking@test.com", Role::Investor, 0)
+        .expect("Failed to issue token");
+    let req = test::TestRequest::get()
+        .uri("/user/profile")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"]["email"], investor.email.clone().unwrap());
+    assert_eq!(
+        body["data"]["wallet_address"],
+        investor.wallet_address.clone().unwrap()
+    );
+
+    // The same record serialized for another viewer is masked
+    let masked = serde_json::to_value(Masked(&investor)).expect("Failed to serialize");
+    let wallet = investor.wallet_address.as_deref().unwrap();
+    assert_eq!(masked["wallet_address"], mask_wallet(wallet));
+    assert!(masked["wallet_address"].as_str().unwrap().contains("..."));
+    assert_eq!(
+        masked["email"],
+        mask_email(investor.email.as_deref().unwrap())
+    );
+
+    // Public marketplace detail masks the mitra's wallet and the buyer's email
+    let req = test::TestRequest::get()
+        .uri(&format!("/marketplace/{}/detail", pool_id))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let invoice = &body["data"]["invoice"];
+    let mitra_wallet: (String,) = sqlx::query_as("SELECT wallet_address FROM users WHERE id = $1")
+        .bind(mitra_id)
+        .fetch_one(&pool)
+        .await
+        .expect("Mitra not found");
+    assert_eq!(
+        invoice["exporter_wallet_address"],
+        mask_wallet(&mitra_wallet.0)
+    );
+    assert_eq!(invoice["buyer_email"], "b***@test.com");
+
+    for user_id in [investor_id, mitra_id] {
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .ok();
+    }
+}
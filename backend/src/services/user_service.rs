@@ -0,0 +1,210 @@
+use rust_decimal::prelude::ToPrimitive;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use crate::models::{
+    AddBankAccountRequest, AdminUserDetail, BankAccount, ProfileCompleteness, Role, User,
+    UserInvestmentStats, UserProfile,
+};
+use crate::repository::{FundingRepository, InvoiceRepository, MitraRepository, UserRepository};
+
+pub struct UserService {
+    user_repo: Arc<UserRepository>,
+    mitra_repo: Arc<MitraRepository>,
+    funding_repo: Arc<FundingRepository>,
+    invoice_repo: Arc<InvoiceRepository>,
+}
+
+impl UserService {
+    pub fn new(
+        user_repo: Arc<UserRepository>,
+        mitra_repo: Arc<MitraRepository>,
+        funding_repo: Arc<FundingRepository>,
+        invoice_repo: Arc<InvoiceRepository>,
+    ) -> Self {
+        Self {
+            user_repo,
+            mitra_repo,
+            funding_repo,
+            invoice_repo,
+        }
+    }
+
+    /// Admin view of a user: profile, KYC, bank accounts, mitra application
+    /// and aggregate activity on both sides of the marketplace
+    pub async fn get_admin_user_detail(&self, user_id: Uuid) -> AppResult<AdminUserDetail> {
+        let user = self
+            .user_repo
+            .find_by_id(user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+        let (
+            active_amount,
+            _expected_gain,
+            realized_gain,
+            _priority_allocation,
+            _catalyst_allocation,
+            active_count,
+            completed_count,
+        ) = self
+            .funding_repo
+            .get_investor_portfolio_stats(user_id)
+            .await?;
+
+        Ok(AdminUserDetail {
+            profile: self.user_repo.find_profile_by_user_id(user_id).await?,
+            kyc: self.user_repo.find_kyc_by_user(user_id).await?,
+            bank_accounts: self.user_repo.find_bank_accounts_by_user(user_id).await?,
+            mitra_application: self.mitra_repo.find_by_user(user_id).await?,
+            investment_stats: UserInvestmentStats {
+                active_amount: active_amount.to_f64().unwrap_or(0.0),
+                realized_gain: realized_gain.to_f64().unwrap_or(0.0),
+                active_count,
+                completed_count,
+            },
+            invoice_stats: self
+                .invoice_repo
+                .get_exporter_invoice_stats(user_id)
+                .await?,
+            user,
+        })
+    }
+
+    /// Score the user's onboarding progress. Read-only: `profile_completed` is
+    /// written by the flows that change the profile, see
+    /// `mark_profile_completed_if_complete`.
+    pub async fn profile_completeness(&self, user_id: Uuid) -> AppResult<ProfileCompleteness> {
+        let user = self
+            .user_repo
+            .find_by_id(user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+        self.score_profile(&user).await
+    }
+
+    /// Set `profile_completed` once every required field is filled. The flag is
+    /// never cleared here: mitra approval also sets it, and a later edit must not
+    /// undo that.
+    pub async fn mark_profile_completed_if_complete(
+        &self,
+        user_id: Uuid,
+    ) -> AppResult<ProfileCompleteness> {
+        let user = self
+            .user_repo
+            .find_by_id(user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+        let completeness = self.score_profile(&user).await?;
+
+        if completeness.is_complete() && !user.profile_completed {
+            self.user_repo.set_profile_completed(user_id, true).await?;
+        }
+
+        Ok(completeness)
+    }
+
+    async fn score_profile(&self, user: &User) -> AppResult<ProfileCompleteness> {
+        let profile = self.user_repo.find_profile_by_user_id(user.id).await?;
+        // A pending review counts: approval is the admin's step, not the user's
+        let kyc_submitted = self
+            .user_repo
+            .find_kyc_by_user(user.id)
+            .await?
+            .is_some_and(|kyc| kyc.status != "rejected");
+        let has_bank_account = !self
+            .user_repo
+            .find_bank_accounts_by_user(user.id)
+            .await?
+            .is_empty();
+
+        Ok(ProfileCompleteness::from_checks(&required_fields(
+            user,
+            profile.as_ref(),
+            kyc_submitted,
+            has_bank_account,
+        )))
+    }
+
+    /// Add a bank account as the mitra's primary payout account. It stays
+    /// unverified, and is not paid out to, until an admin verifies it.
+    pub async fn add_bank_account(
+        &self,
+        user_id: Uuid,
+        req: AddBankAccountRequest,
+    ) -> AppResult<BankAccount> {
+        let account_number = req.account_number.trim();
+        if !account_number.chars().all(|c| c.is_ascii_digit()) {
+            return Err(AppError::ValidationError(
+                "Account number must contain digits only".to_string(),
+            ));
+        }
+
+        let account = self
+            .user_repo
+            .create_bank_account(
+                user_id,
+                req.bank_code.trim(),
+                req.bank_name.trim(),
+                account_number,
+                req.account_name.trim(),
+            )
+            .await?;
+        self.mark_profile_completed_if_complete(user_id).await?;
+
+        Ok(account)
+    }
+
+    pub async fn verify_bank_account(&self, id: Uuid) -> AppResult<BankAccount> {
+        self.user_repo
+            .verify_bank_account(id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Bank account not found".to_string()))
+    }
+}
+
+/// Required onboarding fields for the user's role and whether each is filled.
+/// Only steps the user can take count: a submitted KYC and an added bank account
+/// are filled even while they wait for an admin to approve or verify them.
+/// Investors need identity and a wallet to invest; mitra additionally need
+/// company details and a bank account for fiat disbursements.
+fn required_fields(
+    user: &User,
+    profile: Option<&UserProfile>,
+    kyc_submitted: bool,
+    has_bank_account: bool,
+) -> Vec<(&'static str, bool)> {
+    let filled = |value: Option<&str>| value.is_some_and(|v| !v.trim().is_empty());
+    let phone = profile
+        .and_then(|p| p.phone.as_deref())
+        .or(user.phone_number.as_deref());
+
+    let mut fields = vec![
+        ("full_name", filled(profile.map(|p| p.full_name.as_str()))),
+        ("phone", filled(phone)),
+        (
+            "country",
+            filled(profile.and_then(|p| p.country.as_deref())),
+        ),
+    ];
+
+    match user.role {
+        Role::Investor => fields.extend([
+            ("kyc", kyc_submitted),
+            ("wallet_address", filled(user.wallet_address.as_deref())),
+        ]),
+        Role::Mitra | Role::Exporter => fields.extend([
+            (
+                "company_name",
+                filled(profile.and_then(|p| p.company_name.as_deref())),
+            ),
+            ("kyc", kyc_submitted),
+            ("bank_account", has_bank_account),
+            ("wallet_address", filled(user.wallet_address.as_deref())),
+        ]),
+        Role::Admin => {}
+    }
+
+    fields
+}
@@ -28,6 +28,75 @@ pub fn generate_random_token() -> String {
     hex::encode(bytes)
 }
 
+/// Hex SHA-256 digest, used to store bearer tokens without keeping the token itself
+pub fn sha256_hex(input: &str) -> String {
+    use sha2::{Digest, Sha256};
+    hex::encode(Sha256::digest(input.as_bytes()))
+}
+
+/// Hex SHA-256 of a JSON document in canonical form (object keys sorted, no
+/// whitespace), so equal documents hash the same whatever their key order
+pub fn json_content_hash(value: &serde_json::Value) -> String {
+    fn write_canonical(value: &serde_json::Value, out: &mut String) {
+        match value {
+            serde_json::Value::Object(map) => {
+                let mut entries: Vec<_> = map.iter().collect();
+                entries.sort_by(|a, b| a.0.cmp(b.0));
+                out.push('{');
+                for (i, (key, value)) in entries.into_iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    out.push_str(&serde_json::Value::String(key.clone()).to_string());
+                    out.push(':');
+                    write_canonical(value, out);
+                }
+                out.push('}');
+            }
+            serde_json::Value::Array(items) => {
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    write_canonical(item, out);
+                }
+                out.push(']');
+            }
+            scalar => out.push_str(&scalar.to_string()),
+        }
+    }
+
+    let mut canonical = String::new();
+    write_canonical(value, &mut canonical);
+    sha256_hex(&canonical)
+}
+
+/// `0x`-prefixed keccak256 of `data`, the 66-char form stored as a document hash
+pub fn keccak256_hex(data: &[u8]) -> String {
+    format!("0x{}", hex::encode(ethers::utils::keccak256(data)))
+}
+
+/// Hash of a set of document hashes: keccak256 over the sorted 32-byte hashes
+/// concatenated, so it does not depend on upload order
+pub fn aggregate_document_hash<S: AsRef<str>>(hashes: &[S]) -> String {
+    let mut hashes: Vec<String> = hashes
+        .iter()
+        .map(|h| h.as_ref().trim_start_matches("0x").to_lowercase())
+        .collect();
+    hashes.sort();
+
+    let mut bytes = Vec::with_capacity(hashes.len() * 32);
+    for h in &hashes {
+        match hex::decode(h) {
+            Ok(decoded) => bytes.extend_from_slice(&decoded),
+            Err(_) => bytes.extend_from_slice(h.as_bytes()),
+        }
+    }
+
+    keccak256_hex(&bytes)
+}
+
 /// Generate a virtual account number
 pub fn generate_va_number(bank_code: &str, user_id: &str) -> String {
     use rand::Rng;
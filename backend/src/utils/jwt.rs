@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::error::{AppError, AppResult};
+use crate::models::Role;
 
 #[derive(Debug, Clone)]
 pub struct JwtManager {
@@ -16,17 +17,28 @@ pub struct JwtManager {
 pub struct Claims {
     pub sub: String, // User ID
     pub email: String,
-    pub role: String,
+    pub role: Role,
     pub exp: i64,
     pub iat: i64,
     #[serde(rename = "type")]
     pub token_type: String, // "access" or "refresh"
+    /// users.token_version at issue time
+    #[serde(default)]
+    pub token_version: i32,
 }
 
 impl Claims {
     pub fn user_id(&self) -> Uuid {
         Uuid::parse_str(&self.sub).unwrap_or_default()
     }
+
+    /// Reject tokens issued before the user's last logout or password change
+    pub fn verify_version(&self, current_version: i32) -> AppResult<()> {
+        if self.token_version != current_version {
+            return Err(AppError::InvalidToken);
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -50,7 +62,8 @@ impl JwtManager {
         &self,
         user_id: Uuid,
         email: &str,
-        role: &str,
+        role: Role,
+        token_version: i32,
     ) -> AppResult<String> {
         let now = Utc::now();
         let exp = now + Duration::hours(self.expiry_hours);
@@ -58,10 +71,11 @@ impl JwtManager {
         let claims = Claims {
             sub: user_id.to_string(),
             email: email.to_string(),
-            role: role.to_string(),
+            role,
             exp: exp.timestamp(),
             iat: now.timestamp(),
             token_type: "access".to_string(),
+            token_version,
         };
 
         encode(
@@ -76,7 +90,8 @@ impl JwtManager {
         &self,
         user_id: Uuid,
         email: &str,
-        role: &str,
+        role: Role,
+        token_version: i32,
     ) -> AppResult<String> {
         let now = Utc::now();
         let exp = now + Duration::hours(self.refresh_expiry_hours);
@@ -84,10 +99,11 @@ impl JwtManager {
         let claims = Claims {
             sub: user_id.to_string(),
             email: email.to_string(),
-            role: role.to_string(),
+            role,
             exp: exp.timestamp(),
             iat: now.timestamp(),
             token_type: "refresh".to_string(),
+            token_version,
         };
 
         encode(
@@ -0,0 +1,82 @@
+use serde::{Serialize, Serializer};
+use std::cell::Cell;
+
+thread_local! {
+    /// Set while a `Masked` value is being serialized on this thread
+    static MASK_PII: Cell<bool> = const { Cell::new(false) };
+}
+
+/// `0x1234...abcd`: enough of a wallet address to recognise it, not to copy it
+pub fn mask_wallet(address: &str) -> String {
+    if address.len() <= 10 || !address.is_ascii() {
+        return "***".to_string();
+    }
+    format!("{}...{}", &address[..6], &address[address.len() - 4..])
+}
+
+/// `j***@example.com`: first character of the local part and the domain
+pub fn mask_email(email: &str) -> String {
+    match email.split_once('@') {
+        Some((local, domain)) => match local.chars().next() {
+            Some(first) => format!("{}***@{}", first, domain),
+            None => format!("***@{}", domain),
+        },
+        None => "***".to_string(),
+    }
+}
+
+/// NIK is never shown partially
+pub fn mask_nik(_nik: &str) -> String {
+    "***".to_string()
+}
+
+/// Serializes the inner value with sensitive fields masked. Wrap responses
+/// shown to viewers other than the data's owner or an admin, e.g. the public
+/// marketplace. Fields opt in with `#[serde(serialize_with = ...)]` using the
+/// serializers below, which write the full value outside a `Masked`.
+#[derive(Debug)]
+pub struct Masked<T>(pub T);
+
+impl<T: Serialize> Serialize for Masked<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let previous = MASK_PII.with(|mask| mask.replace(true));
+        let result = self.0.serialize(serializer);
+        MASK_PII.with(|mask| mask.set(previous));
+        result
+    }
+}
+
+fn serialize_masked<S: Serializer>(
+    value: &str,
+    mask: fn(&str) -> String,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    if MASK_PII.with(Cell::get) {
+        serializer.serialize_str(&mask(value))
+    } else {
+        serializer.serialize_str(value)
+    }
+}
+
+fn serialize_masked_opt<S: Serializer>(
+    value: &Option<String>,
+    mask: fn(&str) -> String,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(value) => serialize_masked(value, mask, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+pub fn serialize_wallet<S: Serializer>(value: &Option<String>, s: S) -> Result<S::Ok, S::Error> {
+    serialize_masked_opt(value, mask_wallet, s)
+}
+
+pub fn serialize_email<S: Serializer>(value: &Option<String>, s: S) -> Result<S::Ok, S::Error> {
+    serialize_masked_opt(value, mask_email, s)
+}
+
+pub fn serialize_nik<S: Serializer>(value: &str, s: S) -> Result<S::Ok, S::Error> {
+    serialize_masked(value, mask_nik, s)
+}
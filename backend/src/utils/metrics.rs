@@ -0,0 +1,153 @@
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGaugeVec, Opts, Registry,
+    TextEncoder,
+};
+use sqlx::PgPool;
+
+/// Prometheus metrics for the backend, served in text format at `/metrics`.
+/// Each instance owns its own registry, so tests can create as many as they need.
+pub struct Metrics {
+    registry: Registry,
+    http_requests: IntCounterVec,
+    http_request_duration: HistogramVec,
+    investments: IntCounterVec,
+    transfers_attempted: IntCounter,
+    transfers_completed: IntCounterVec,
+    db_pool_connections: IntGaugeVec,
+    db_pool: Option<PgPool>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new();
+
+        let http_requests = IntCounterVec::new(
+            Opts::new("vessel_http_requests_total", "HTTP requests handled"),
+            &["method", "route", "status"],
+        )
+        .expect("valid metric");
+        let http_request_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "vessel_http_request_duration_seconds",
+                "HTTP request latency in seconds",
+            ),
+            &["method", "route"],
+        )
+        .expect("valid metric");
+        let investments = IntCounterVec::new(
+            Opts::new("vessel_investments_total", "Investments recorded"),
+            &["tranche"],
+        )
+        .expect("valid metric");
+        let transfers_attempted = IntCounter::new(
+            "vessel_onchain_transfers_attempted_total",
+            "IDRX transfers sent from the platform wallet",
+        )
+        .expect("valid metric");
+        let transfers_completed = IntCounterVec::new(
+            Opts::new(
+                "vessel_onchain_transfers_completed_total",
+                "IDRX transfers from the platform wallet by outcome",
+            ),
+            &["outcome"],
+        )
+        .expect("valid metric");
+        let db_pool_connections = IntGaugeVec::new(
+            Opts::new("vessel_db_pool_connections", "Database pool connections"),
+            &["state"],
+        )
+        .expect("valid metric");
+
+        for collector in [
+            Box::new(http_requests.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(http_request_duration.clone()),
+            Box::new(investments.clone()),
+            Box::new(transfers_attempted.clone()),
+            Box::new(transfers_completed.clone()),
+            Box::new(db_pool_connections.clone()),
+        ] {
+            registry
+                .register(collector)
+                .expect("metric names are unique");
+        }
+
+        Self {
+            registry,
+            http_requests,
+            http_request_duration,
+            investments,
+            transfers_attempted,
+            transfers_completed,
+            db_pool_connections,
+            db_pool: None,
+        }
+    }
+
+    /// Report the pool's connection usage with every scrape
+    pub fn with_db_pool(mut self, db_pool: PgPool) -> Self {
+        self.db_pool = Some(db_pool);
+        self
+    }
+
+    /// `route` is the matched route pattern, never the raw path, to keep label
+    /// cardinality bounded
+    pub fn observe_request(&self, method: &str, route: &str, status: u16, seconds: f64) {
+        self.http_requests
+            .with_label_values(&[method, route, &status.to_string()])
+            .inc();
+        self.http_request_duration
+            .with_label_values(&[method, route])
+            .observe(seconds);
+    }
+
+    pub fn record_investment(&self, tranche: &str) {
+        self.investments.with_label_values(&[tranche]).inc();
+    }
+
+    pub fn record_transfer_attempt(&self) {
+        self.transfers_attempted.inc();
+    }
+
+    pub fn record_transfer_outcome(&self, succeeded: bool) {
+        let outcome = if succeeded { "succeeded" } else { "failed" };
+        self.transfers_completed.with_label_values(&[outcome]).inc();
+    }
+
+    /// Sample the pool's open, idle and maximum connections
+    fn observe_db_pool(&self, pool: &PgPool) {
+        let size = pool.size() as i64;
+        let idle = pool.num_idle() as i64;
+        let max = pool.options().get_max_connections() as i64;
+        self.db_pool_connections
+            .with_label_values(&["open"])
+            .set(size);
+        self.db_pool_connections
+            .with_label_values(&["idle"])
+            .set(idle);
+        self.db_pool_connections
+            .with_label_values(&["in_use"])
+            .set(size - idle);
+        self.db_pool_connections
+            .with_label_values(&["max"])
+            .set(max);
+    }
+
+    /// All metrics in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        if let Some(pool) = &self.db_pool {
+            self.observe_db_pool(pool);
+        }
+
+        let mut buffer = Vec::new();
+        if let Err(e) = TextEncoder::new().encode(&self.registry.gather(), &mut buffer) {
+            tracing::error!("Failed to encode metrics: {}", e);
+        }
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
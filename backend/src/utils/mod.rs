@@ -2,12 +2,21 @@
 
 mod hash;
 mod jwt;
+pub mod mask;
+mod metrics;
+mod pagination;
+mod quote;
 pub mod response;
 mod validator;
 
 pub use hash::*;
 pub use jwt::*;
+pub use mask::{mask_email, mask_nik, mask_wallet, Masked};
+pub use metrics::*;
+pub use pagination::*;
+pub use quote::*;
 pub use response::*;
+pub use self::validator::{is_valid_email, validate_request, ValidatedJson};
 
 /// Verify JWT token helper function used by middleware
 pub fn verify_token(token: &str, secret: &str) -> crate::error::AppResult<Claims> {
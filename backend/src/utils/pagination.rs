@@ -0,0 +1,56 @@
+use chrono::{DateTime, NaiveDateTime};
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+
+/// Keyset position in a list ordered by `(timestamp DESC, id DESC)`. The next
+/// page starts strictly after it, so rows inserted between requests cannot
+/// shift the page boundary the way they shift an offset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    pub at: NaiveDateTime,
+    pub id: Uuid,
+}
+
+impl Cursor {
+    /// Opaque token handed to clients as `next_cursor`
+    pub fn encode(&self) -> String {
+        hex::encode(format!(
+            "{}|{}",
+            self.at.and_utc().timestamp_micros(),
+            self.id
+        ))
+    }
+
+    pub fn decode(token: &str) -> AppResult<Self> {
+        let invalid = || AppError::ValidationError("Invalid cursor".to_string());
+
+        let raw = hex::decode(token).map_err(|_| invalid())?;
+        let raw = String::from_utf8(raw).map_err(|_| invalid())?;
+        let (micros, id) = raw.split_once('|').ok_or_else(invalid)?;
+        let at = micros
+            .parse::<i64>()
+            .ok()
+            .and_then(DateTime::from_timestamp_micros)
+            .ok_or_else(invalid)?
+            .naive_utc();
+        let id = Uuid::parse_str(id).map_err(|_| invalid())?;
+
+        Ok(Self { at, id })
+    }
+
+    /// Cursor after the last item of a full page; `None` once the list is exhausted
+    pub fn after_page<T>(
+        items: &[T],
+        per_page: i32,
+        key: impl Fn(&T) -> (NaiveDateTime, Uuid),
+    ) -> Option<Self> {
+        if per_page <= 0 || items.len() < per_page as usize {
+            return None;
+        }
+        items.last().map(|item| {
+            let (at, id) = key(item);
+            Self { at, id }
+        })
+    }
+}
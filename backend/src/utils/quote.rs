@@ -0,0 +1,68 @@
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::error::{AppError, AppResult};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Exchange rate quote carried in a signed `rate_lock_token`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateQuote {
+    pub from_currency: String,
+    pub to_currency: String,
+    pub rate: f64,
+    /// Unix timestamp (seconds)
+    pub expires_at: i64,
+}
+
+fn quote_mac(secret: &str, payload: &[u8]) -> HmacSha256 {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(payload);
+    mac
+}
+
+/// Token format: `hex(from:to:rate:expires_at).hex(HMAC-SHA256)`
+pub fn sign_rate_quote(secret: &str, quote: &RateQuote) -> String {
+    let payload = format!(
+        "{}:{}:{}:{}",
+        quote.from_currency, quote.to_currency, quote.rate, quote.expires_at
+    );
+    let signature = quote_mac(secret, payload.as_bytes()).finalize().into_bytes();
+    format!("{}.{}", hex::encode(payload), hex::encode(signature))
+}
+
+/// Check the signature and expiry of a `rate_lock_token` and return the quote it carries
+pub fn verify_rate_quote(secret: &str, token: &str) -> AppResult<RateQuote> {
+    let invalid = || AppError::ValidationError("Invalid exchange rate quote".to_string());
+
+    let (payload_hex, signature_hex) = token.split_once('.').ok_or_else(invalid)?;
+    let payload = hex::decode(payload_hex).map_err(|_| invalid())?;
+    let signature = hex::decode(signature_hex).map_err(|_| invalid())?;
+
+    quote_mac(secret, &payload)
+        .verify_slice(&signature)
+        .map_err(|_| invalid())?;
+
+    let payload = String::from_utf8(payload).map_err(|_| invalid())?;
+    let parts: Vec<&str> = payload.split(':').collect();
+    if parts.len() != 4 {
+        return Err(invalid());
+    }
+
+    let quote = RateQuote {
+        from_currency: parts[0].to_string(),
+        to_currency: parts[1].to_string(),
+        rate: parts[2].parse().map_err(|_| invalid())?,
+        expires_at: parts[3].parse().map_err(|_| invalid())?,
+    };
+
+    if quote.expires_at < Utc::now().timestamp() {
+        return Err(AppError::ValidationError(
+            "Exchange rate quote has expired, request a new rate".to_string(),
+        ));
+    }
+
+    Ok(quote)
+}
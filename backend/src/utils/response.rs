@@ -1,7 +1,57 @@
+use actix_web::http::header::{self, HttpDate};
+use actix_web::{HttpRequest, HttpResponse};
+use chrono::NaiveDateTime;
 use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::time::SystemTime;
+use utoipa::ToSchema;
+
+use crate::middleware::current_request_id;
+use crate::models::{
+    ActiveInvestmentListResponse, AdminGradeSuggestionResponse, AdminPoolDetail, AdminPoolListItem,
+    DisbursementPreview, DocumentIntegrityReport, FundingPool, FundingPoolResponse,
+    InvestmentDetail, InvestmentReceipt, InvestorPortfolio, Invoice, InvoiceDocument,
+    InvoiceFundingStatus, InvoiceNftView, LoginResponse, MitraDashboard, PlatformStats,
+    PoolOnChainAudit, RepaymentBreakdown, RepeatBuyerCheckResponse, TaxSummary,
+    WalletNonceResponse,
+};
+
+use super::Cursor;
 
 /// Unified API Response struct
-#[derive(Debug, Serialize)]
+///
+/// The aliases name the concrete envelopes referenced from the OpenAPI spec;
+/// `ApiResponseBody` covers endpoints whose `data` is free-form.
+#[derive(Debug, Serialize, ToSchema)]
+#[aliases(
+    ApiResponseBody = ApiResponse<serde_json::Value>,
+    LoginApiResponse = ApiResponse<LoginResponse>,
+    WalletNonceApiResponse = ApiResponse<WalletNonceResponse>,
+    InvoiceApiResponse = ApiResponse<Invoice>,
+    InvoiceListApiResponse = ApiResponse<Vec<Invoice>>,
+    InvoiceDocumentApiResponse = ApiResponse<InvoiceDocument>,
+    InvoiceDocumentListApiResponse = ApiResponse<Vec<InvoiceDocument>>,
+    InvoiceNftApiResponse = ApiResponse<InvoiceNftView>,
+    DocumentIntegrityApiResponse = ApiResponse<DocumentIntegrityReport>,
+    GradeSuggestionApiResponse = ApiResponse<AdminGradeSuggestionResponse>,
+    RepeatBuyerCheckApiResponse = ApiResponse<RepeatBuyerCheckResponse>,
+    FundingPoolApiResponse = ApiResponse<FundingPool>,
+    FundingPoolDetailApiResponse = ApiResponse<FundingPoolResponse>,
+    FundingPoolListApiResponse = ApiResponse<Vec<FundingPoolResponse>>,
+    AdminPoolDetailApiResponse = ApiResponse<AdminPoolDetail>,
+    AdminPoolListApiResponse = ApiResponse<Vec<AdminPoolListItem>>,
+    DisbursementPreviewApiResponse = ApiResponse<DisbursementPreview>,
+    PoolOnChainAuditApiResponse = ApiResponse<PoolOnChainAudit>,
+    InvestmentReceiptApiResponse = ApiResponse<InvestmentReceipt>,
+    InvestmentDetailApiResponse = ApiResponse<InvestmentDetail>,
+    InvestmentListApiResponse = ApiResponse<ActiveInvestmentListResponse>,
+    PortfolioApiResponse = ApiResponse<InvestorPortfolio>,
+    PlatformStatsApiResponse = ApiResponse<PlatformStats>,
+    TaxSummaryApiResponse = ApiResponse<TaxSummary>,
+    InvoiceFundingStatusApiResponse = ApiResponse<InvoiceFundingStatus>,
+    MitraDashboardApiResponse = ApiResponse<MitraDashboard>,
+    RepaymentScheduleApiResponse = ApiResponse<RepaymentBreakdown>
+)]
 pub struct ApiResponse<T: Serialize> {
     pub success: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -14,18 +64,38 @@ pub struct ApiResponse<T: Serialize> {
     pub pagination: Option<PaginationMeta>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ApiError {
     pub code: String,
     pub message: String,
+    /// `X-Request-Id` of the failed request, for matching a report to the logs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// Fields that failed validation, on `VALIDATION_ERROR` from a request body
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<Vec<FieldError>>,
+}
+
+/// One request field that failed validation
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct PaginationMeta {
     pub page: i32,
     pub per_page: i32,
     pub total: i64,
     pub total_pages: i32,
+    /// Filters applied to the list, for endpoints that accept them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filters: Option<serde_json::Value>,
+    /// Pass as `cursor` to fetch the next page, for endpoints that support it.
+    /// Absent on the last page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
 
 impl<T: Serialize> ApiResponse<T> {
@@ -69,9 +139,27 @@ impl<T: Serialize> ApiResponse<T> {
                 per_page,
                 total,
                 total_pages,
+                filters: None,
+                next_cursor: None,
             }),
         }
     }
+
+    /// Point the client at the page after this one
+    pub fn with_next_cursor(mut self, cursor: Option<Cursor>) -> Self {
+        if let Some(pagination) = self.pagination.as_mut() {
+            pagination.next_cursor = cursor.map(|c| c.encode());
+        }
+        self
+    }
+
+    /// Report the filters applied to a paginated list alongside its pagination
+    pub fn with_filters<F: Serialize>(mut self, filters: &F) -> Self {
+        if let Some(pagination) = self.pagination.as_mut() {
+            pagination.filters = serde_json::to_value(filters).ok();
+        }
+        self
+    }
 }
 
 impl ApiResponse<()> {
@@ -95,6 +183,8 @@ impl ApiResponse<()> {
             error: Some(ApiError {
                 code: "ERROR".to_string(),
                 message: message.to_string(),
+                request_id: current_request_id(),
+                details: None,
             }),
             pagination: None,
         }
@@ -109,8 +199,66 @@ impl ApiResponse<()> {
             error: Some(ApiError {
                 code: code.to_string(),
                 message: message.to_string(),
+                request_id: current_request_id(),
+                details: None,
             }),
             pagination: None,
         }
     }
 }
+
+/// Weak ETag for a representation built from rows with the given versions
+/// (`updated_at` of the entity and of each related row, in a stable order)
+pub fn weak_etag(versions: &[NaiveDateTime]) -> String {
+    let mut hasher = Sha256::new();
+    for version in versions {
+        hasher.update(version.and_utc().timestamp_micros().to_be_bytes());
+    }
+    format!("W/\"{}\"", &hex::encode(hasher.finalize())[..16])
+}
+
+/// Whether an `If-None-Match` value matches `etag` under weak comparison
+fn if_none_match_matches(if_none_match: &str, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let etag = opaque(etag);
+    if_none_match
+        .split(',')
+        .any(|candidate| candidate.trim() == "*" || opaque(candidate) == etag)
+}
+
+/// 200 JSON response carrying `ETag` and `Last-Modified` validators derived from
+/// `versions`, or an empty `304 Not Modified` when the client's `If-None-Match`
+/// already matches (the body is not serialized in that case)
+pub fn conditional_json<T: Serialize>(
+    req: &HttpRequest,
+    versions: &[NaiveDateTime],
+    body: ApiResponse<T>,
+) -> HttpResponse {
+    let etag = weak_etag(versions);
+    let last_modified = versions
+        .iter()
+        .max()
+        .map(|latest| HttpDate::from(SystemTime::from(latest.and_utc())).to_string());
+
+    let not_modified = req
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| if_none_match_matches(value, &etag));
+
+    let mut response = if not_modified {
+        HttpResponse::NotModified()
+    } else {
+        HttpResponse::Ok()
+    };
+    response.insert_header((header::ETAG, etag));
+    if let Some(last_modified) = last_modified {
+        response.insert_header((header::LAST_MODIFIED, last_modified));
+    }
+
+    if not_modified {
+        response.finish()
+    } else {
+        response.json(body)
+    }
+}
@@ -1,30 +1,74 @@
+use actix_web::dev::Payload;
+use actix_web::{web, FromRequest, HttpRequest};
+use futures_util::future::LocalBoxFuture;
+use serde::de::DeserializeOwned;
+use std::ops::Deref;
 use validator::Validate;
 
 use crate::error::{AppError, AppResult};
+use crate::utils::FieldError;
 
 /// Validate a request struct using the validator crate
 pub fn validate_request<T: Validate>(request: &T) -> AppResult<()> {
     request.validate().map_err(|e| {
-        let errors: Vec<String> = e
+        let mut fields: Vec<FieldError> = e
             .field_errors()
             .iter()
             .flat_map(|(field, errors)| {
-                errors.iter().map(move |err| {
-                    format!(
-                        "{}: {}",
-                        field,
-                        err.message
-                            .clone()
-                            .unwrap_or_else(|| "Invalid value".into())
-                    )
+                errors.iter().map(move |err| FieldError {
+                    field: field.to_string(),
+                    message: err
+                        .message
+                        .clone()
+                        .map(|m| m.to_string())
+                        .unwrap_or_else(|| "Invalid value".to_string()),
                 })
             })
             .collect();
+        // field_errors() is a map; keep the response order stable
+        fields.sort_by(|a, b| a.field.cmp(&b.field));
 
-        AppError::ValidationError(errors.join(", "))
+        AppError::InvalidFields(fields)
     })
 }
 
+/// JSON body extractor that runs the type's `Validate` rules, so handlers only
+/// ever see bodies that passed them. Failures are a `VALIDATION_ERROR` with
+/// per-field `details`; malformed JSON is still handled by the `JsonConfig`.
+#[derive(Debug)]
+pub struct ValidatedJson<T>(pub T);
+
+impl<T> ValidatedJson<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for ValidatedJson<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> FromRequest for ValidatedJson<T>
+where
+    T: DeserializeOwned + Validate + 'static,
+{
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let json = web::Json::<T>::from_request(req, payload);
+        Box::pin(async move {
+            let body = json.await?.into_inner();
+            validate_request(&body)?;
+            Ok(ValidatedJson(body))
+        })
+    }
+}
+
 /// Validate email format
 pub fn is_valid_email(email: &str) -> bool {
     // Simple email validation
@@ -87,7 +87,7 @@ contract InvoicePool is AccessControl, ReentrancyGuard, Pausable {
     );
     event DisbursementRecorded(
         uint256 indexed tokenId,
-        address indexed exporter,
+        address indexed recipient,
         uint256 amount
     );
     event RepaymentRecorded(uint256 indexed tokenId, uint256 amount);
@@ -102,6 +102,7 @@ contract InvoicePool is AccessControl, ReentrancyGuard, Pausable {
         uint256 amount
     );
     event PoolClosed(uint256 indexed tokenId);
+    event PoolReopened(uint256 indexed tokenId);
     event PoolDefaulted(uint256 indexed tokenId);
 
     // ... (existing code) ...
@@ -231,6 +232,22 @@ contract InvoicePool is AccessControl, ReentrancyGuard, Pausable {
     function recordDisbursement(
         uint256 tokenId
     ) external onlyRole(OPERATOR_ROLE) nonReentrant {
+        _disburse(tokenId, pools[tokenId].exporter);
+    }
+
+    /**
+     * @dev Record disbursement paid out somewhere other than the exporter's wallet,
+     * e.g. the off-ramp wallet that pays the exporter's bank account in fiat
+     */
+    function recordDisbursementTo(
+        uint256 tokenId,
+        address recipient
+    ) external onlyRole(OPERATOR_ROLE) nonReentrant {
+        require(recipient != address(0), "Invalid recipient");
+        _disburse(tokenId, recipient);
+    }
+
+    function _disburse(uint256 tokenId, address recipient) internal {
         Pool storage pool = pools[tokenId];
         require(
             pool.status == PoolStatus.Filled || pool.status == PoolStatus.Open,
@@ -248,10 +265,10 @@ contract InvoicePool is AccessControl, ReentrancyGuard, Pausable {
             idrxToken.safeTransfer(platformWallet, feeAmount);
         }
         if (disbursementAmount > 0) {
-            idrxToken.safeTransfer(pool.exporter, disbursementAmount);
+            idrxToken.safeTransfer(recipient, disbursementAmount);
         }
 
-        emit DisbursementRecorded(tokenId, pool.exporter, pool.fundedAmount);
+        emit DisbursementRecorded(tokenId, recipient, pool.fundedAmount);
     }
 
     /**
@@ -330,6 +347,28 @@ contract InvoicePool is AccessControl, ReentrancyGuard, Pausable {
         emit PoolClosed(tokenId);
     }
 
+    /**
+     * @dev Reopen a pool that was closed before raising anything, so its invoice
+     * can be relisted. The NFT keeps its token id, so the pool is reset in place.
+     */
+    function reopenPool(
+        uint256 tokenId
+    ) external onlyRole(OPERATOR_ROLE) whenNotPaused {
+        Pool storage pool = pools[tokenId];
+        require(pool.targetAmount > 0, "Pool does not exist");
+        require(pool.status == PoolStatus.Closed, "Pool not closed");
+        require(pool.fundedAmount == 0, "Pool has investments");
+
+        delete poolInvestments[tokenId];
+        pool.investorCount = 0;
+        pool.status = PoolStatus.Open;
+        pool.openedAt = block.timestamp;
+        pool.filledAt = 0;
+        pool.closedAt = 0;
+
+        emit PoolReopened(tokenId);
+    }
+
     /**
      * @dev Mark pool as defaulted
      */
@@ -199,6 +199,14 @@ describe("InvoicePool", function () {
     it("Should not allow non-operator to disburse", async function () {
       await expect(invoicePool.connect(investor1).recordDisbursement(1)).to.be.reverted;
     });
+
+    it("Should record disbursement to another recipient", async function () {
+      await expect(invoicePool.recordDisbursementTo(1, investor2.address))
+        .to.emit(invoicePool, "DisbursementRecorded")
+        .withArgs(1, investor2.address, ethers.parseEther("8000"));
+      const pool = await invoicePool.getPool(1);
+      expect(pool.status).to.equal(2); // Disbursed
+    });
   });
 
   describe("Repayment Recording", function () {
@@ -289,6 +297,40 @@ describe("InvoicePool", function () {
     });
   });
 
+  describe("Pool Reopening", function () {
+    beforeEach(async function () {
+      await mintAndVerifyInvoice();
+      await invoicePool.createPool(1);
+    });
+
+    it("Should reopen a pool closed without funds", async function () {
+      await invoicePool.closePoolEarly(1);
+      await expect(invoicePool.reopenPool(1)).to.emit(invoicePool, "PoolReopened").withArgs(1);
+
+      const pool = await invoicePool.getPool(1);
+      expect(pool.status).to.equal(0); // Open
+      expect(pool.closedAt).to.equal(0);
+
+      await invoicePool.recordInvestment(1, investor1.address, ethers.parseEther("1000"));
+      expect((await invoicePool.getPool(1)).fundedAmount).to.equal(ethers.parseEther("1000"));
+    });
+
+    it("Should not reopen a pool that is not closed", async function () {
+      await expect(invoicePool.reopenPool(1)).to.be.revertedWith("Pool not closed");
+    });
+
+    it("Should not reopen a pool closed with investments", async function () {
+      await invoicePool.recordInvestment(1, investor1.address, ethers.parseEther("1000"));
+      await invoicePool.closePoolEarly(1);
+      await expect(invoicePool.reopenPool(1)).to.be.revertedWith("Pool has investments");
+    });
+
+    it("Should not allow non-operator to reopen pool", async function () {
+      await invoicePool.closePoolEarly(1);
+      await expect(invoicePool.connect(investor1).reopenPool(1)).to.be.reverted;
+    });
+  });
+
   describe("Admin Functions", function () {
     it("Should update platform fee", async function () {
       await invoicePool.setPlatformFee(300); // 3%